@@ -0,0 +1,590 @@
+//! Resumable encoding in keyframe-aligned chunks.
+//!
+//! The source is split at video keyframes into chunks of roughly equal
+//! length. Each chunk's video is encoded to its own file in a work directory
+//! and a manifest records which chunks are finished, so an encode interrupted
+//! by a crash or restart resumes after the last finished chunk. Chunks can be
+//! encoded in parallel. Once all are done they are joined with ffmpeg's
+//! concat demuxer, without re-encoding, and muxed with the source's other
+//! streams.
+
+use std::collections::BTreeSet;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::Poll;
+use std::time::{Duration, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+use super::progress::{EncodeProgress, ProgressParser};
+use crate::command::ToolCommand;
+use crate::tools::ToolRegistry;
+
+const MANIFEST: &str = "manifest.json";
+
+/// The work directory used for chunked encodes of `output`: a hidden
+/// directory next to it, so a restarted encode finds its finished chunks.
+///
+/// It is removed once the output is complete.
+pub fn chunk_dir(output: &Path) -> PathBuf {
+    let name = output
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "output".to_string());
+    output.with_file_name(format!(".{name}.chunks"))
+}
+
+/// How to split an encode.
+#[derive(Debug, Clone)]
+pub(crate) struct ChunkOptions {
+    /// Directory for chunk files and the manifest.
+    pub(crate) work_dir: PathBuf,
+    /// Target chunk length; chunks start at the last keyframe before each
+    /// multiple of it.
+    pub(crate) chunk_secs: f64,
+    /// Number of chunks encoded at the same time.
+    pub(crate) parallelism: usize,
+}
+
+/// ffmpeg arguments of a chunked encode.
+#[derive(Debug, Clone)]
+pub(crate) struct ChunkedEncode {
+    /// Arguments before `-i` when encoding a chunk (hardware decoding).
+    pub(crate) input_args: Vec<String>,
+    /// Video encoder arguments for each chunk.
+    pub(crate) video_args: Vec<String>,
+    /// Stream mapping and codec arguments of the final mux. Input 0 is the
+    /// joined video and input 1 the source.
+    pub(crate) mux_args: Vec<String>,
+}
+
+/// What the chunks in a work directory were encoded from. Chunks are only
+/// reused while this is unchanged.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Settings {
+    source: PathBuf,
+    source_size: u64,
+    source_modified: Option<u64>,
+    duration_secs: f64,
+    chunk_secs: f64,
+    args: Vec<String>,
+}
+
+impl Settings {
+    fn new(
+        input: &Path,
+        duration_secs: f64,
+        chunking: &ChunkOptions,
+        encode: &ChunkedEncode,
+    ) -> sf_core::Result<Self> {
+        let meta = std::fs::metadata(input)?;
+        let source_modified = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
+        Ok(Self {
+            source: input.to_path_buf(),
+            source_size: meta.len(),
+            source_modified,
+            duration_secs,
+            chunk_secs: chunking.chunk_secs,
+            args: encode
+                .input_args
+                .iter()
+                .chain(&encode.video_args)
+                .cloned()
+                .collect(),
+        })
+    }
+}
+
+/// Chunk plan and progress, stored as `manifest.json` in the work directory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Manifest {
+    settings: Settings,
+    /// Chunk start times in seconds from the start of the source; the first
+    /// is always 0.
+    boundaries: Vec<f64>,
+    /// Indices of finished chunks.
+    done: BTreeSet<usize>,
+}
+
+impl Manifest {
+    /// Load the manifest in `dir` if it was written for `settings`.
+    fn load(dir: &Path, settings: &Settings) -> Option<Self> {
+        let data = std::fs::read(dir.join(MANIFEST)).ok()?;
+        let manifest: Self = serde_json::from_slice(&data).ok()?;
+        (manifest.settings == *settings && !manifest.boundaries.is_empty()).then_some(manifest)
+    }
+
+    /// Write the manifest, replacing the previous one atomically.
+    fn save(&self, dir: &Path) -> sf_core::Result<()> {
+        let data = serde_json::to_vec_pretty(self).map_err(|e| {
+            sf_core::Error::Internal(format!("failed to serialize chunk manifest: {e}"))
+        })?;
+        let tmp = dir.join(format!("{MANIFEST}.tmp"));
+        std::fs::write(&tmp, data)?;
+        std::fs::rename(&tmp, dir.join(MANIFEST))?;
+        Ok(())
+    }
+
+    /// Start and length of chunk `index`.
+    fn span(&self, index: usize) -> (f64, f64) {
+        let start = self.boundaries[index];
+        let end = self
+            .boundaries
+            .get(index + 1)
+            .copied()
+            .unwrap_or(self.settings.duration_secs);
+        (start, (end - start).max(0.0))
+    }
+
+    /// Chunks still to encode. Chunks marked done whose file has gone
+    /// missing are encoded again.
+    fn pending(&self, dir: &Path) -> Vec<usize> {
+        (0..self.boundaries.len())
+            .filter(|i| !self.done.contains(i) || !chunk_path(dir, *i).is_file())
+            .collect()
+    }
+}
+
+/// Chunks are Matroska files, but without a media extension so library
+/// scans and the file watcher leave them alone.
+fn chunk_path(dir: &Path, index: usize) -> PathBuf {
+    dir.join(format!("chunk-{index:05}.chunk"))
+}
+
+/// Choose chunk start times: the last keyframe at or before each multiple of
+/// `chunk_secs`, skipping keyframes that would leave a chunk shorter than
+/// half the target, and not starting a chunk in the last half chunk.
+fn plan_boundaries(keyframes: &[f64], duration_secs: f64, chunk_secs: f64) -> Vec<f64> {
+    let min_len = chunk_secs / 2.0;
+    let mut boundaries = vec![0.0];
+    let mut target = chunk_secs;
+    while target < duration_secs - min_len {
+        let last = boundaries[boundaries.len() - 1];
+        let keyframe = keyframes
+            .iter()
+            .copied()
+            .filter(|&k| k <= target && k >= last + min_len && k <= duration_secs - min_len)
+            .reduce(f64::max);
+        if let Some(keyframe) = keyframe {
+            boundaries.push(keyframe);
+        }
+        target += chunk_secs;
+    }
+    boundaries
+}
+
+/// Find video keyframes near each multiple of `chunk_secs`, in seconds from
+/// the start of the source.
+///
+/// Rather than reading every packet, ffprobe seeks to each target time
+/// (landing on the keyframe before it) and reads a few packets from there.
+async fn probe_keyframes(
+    tools: &ToolRegistry,
+    input: &Path,
+    duration_secs: f64,
+    chunk_secs: f64,
+) -> sf_core::Result<Vec<f64>> {
+    let ffprobe = tools.require("ffprobe")?;
+
+    let mut intervals = Vec::new();
+    let mut target = chunk_secs;
+    while target < duration_secs {
+        intervals.push(format!("{target:.3}%+#8"));
+        target += chunk_secs;
+    }
+    if intervals.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut cmd = ToolCommand::new(ffprobe.path.clone());
+    cmd.timeout(Duration::from_secs(600));
+    cmd.args(["-v", "error", "-select_streams", "v:0"]);
+    cmd.args(["-show_entries", "packet=pts_time,flags:format=start_time"]);
+    cmd.args(["-read_intervals", &intervals.join(",")]);
+    cmd.args(["-of", "json"]);
+    cmd.arg(input.to_string_lossy().as_ref());
+    let output = cmd.execute().await?;
+
+    let json: serde_json::Value = serde_json::from_str(&output.stdout)
+        .map_err(|e| sf_core::Error::tool("ffprobe", format!("invalid JSON output: {e}")))?;
+    Ok(parse_keyframes(&json))
+}
+
+/// Extract keyframe times, relative to the format start time, from
+/// ffprobe's packet listing.
+fn parse_keyframes(json: &serde_json::Value) -> Vec<f64> {
+    let number = |v: &serde_json::Value| v.as_str().and_then(|s| s.parse::<f64>().ok());
+    let start = number(&json["format"]["start_time"]).unwrap_or(0.0);
+
+    let mut keyframes: Vec<f64> = json["packets"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter(|p| p["flags"].as_str().is_some_and(|f| f.contains('K')))
+        .filter_map(|p| number(&p["pts_time"]))
+        .map(|t| t - start)
+        .filter(|t| *t > 0.0)
+        .collect();
+    keyframes.sort_by(f64::total_cmp);
+    keyframes.dedup();
+    keyframes
+}
+
+/// Encode `input` to `output` chunk by chunk, resuming from the chunks
+/// already finished in `chunking.work_dir`.
+///
+/// `duration_secs` is the source duration; progress covers the whole encode,
+/// including chunks finished before a restart. The work directory is removed
+/// once `output` is written.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn encode_chunked(
+    tools: &ToolRegistry,
+    input: &Path,
+    output: &Path,
+    encode: &ChunkedEncode,
+    chunking: &ChunkOptions,
+    duration_secs: f64,
+    progress_callback: impl FnMut(EncodeProgress),
+    cancel: Option<CancellationToken>,
+) -> sf_core::Result<()> {
+    let ffmpeg = tools.require("ffmpeg")?;
+    let dir = chunking.work_dir.as_path();
+    std::fs::create_dir_all(dir)?;
+
+    let settings = Settings::new(input, duration_secs, chunking, encode)?;
+    let manifest = match Manifest::load(dir, &settings) {
+        Some(manifest) => manifest,
+        None => {
+            // Chunks from other settings or another source are useless.
+            std::fs::remove_dir_all(dir)?;
+            std::fs::create_dir_all(dir)?;
+            let keyframes =
+                probe_keyframes(tools, input, duration_secs, chunking.chunk_secs).await?;
+            let manifest = Manifest {
+                boundaries: plan_boundaries(&keyframes, duration_secs, chunking.chunk_secs),
+                settings,
+                done: BTreeSet::new(),
+            };
+            manifest.save(dir)?;
+            manifest
+        }
+    };
+
+    let pending = manifest.pending(dir);
+    tracing::info!(
+        "Chunked encode: {:?} -> {:?} ({} chunks, {} to encode, parallelism={})",
+        input,
+        output,
+        manifest.boundaries.len(),
+        pending.len(),
+        chunking.parallelism,
+    );
+
+    let count = manifest.boundaries.len();
+    let encoded = (0..count)
+        .map(|i| {
+            if pending.contains(&i) {
+                0.0
+            } else {
+                manifest.span(i).1
+            }
+        })
+        .collect();
+    let state = Mutex::new(ChunkState {
+        manifest,
+        encoded,
+        callback: progress_callback,
+    });
+
+    let chunks = pending.into_iter().map(|index| {
+        encode_chunk(
+            ffmpeg.path.as_path(),
+            input,
+            dir,
+            encode,
+            index,
+            &state,
+            cancel.clone(),
+        )
+    });
+    run_limited(chunks, chunking.parallelism).await?;
+
+    // Join the chunks and add the source's other streams.
+    let list: String = (0..count)
+        .map(|i| format!("file 'chunk-{i:05}.chunk'\n"))
+        .collect();
+    let list_path = dir.join("concat.txt");
+    std::fs::write(&list_path, list)?;
+
+    let mut cmd = ToolCommand::new(ffmpeg.path.clone());
+    cmd.timeout(Duration::from_secs(86400));
+    cmd.args(["-y", "-f", "concat", "-safe", "0", "-i"]);
+    cmd.arg(list_path.to_string_lossy().as_ref());
+    cmd.arg("-i");
+    cmd.arg(input.to_string_lossy().as_ref());
+    cmd.args(["-map", "0:v:0"]);
+    cmd.args(encode.mux_args.iter().cloned());
+    cmd.arg(output.to_string_lossy().as_ref());
+    cmd.execute_with_stderr_callback(|_| {}, cancel).await?;
+
+    if let Err(e) = std::fs::remove_dir_all(dir) {
+        tracing::warn!("failed to remove chunk directory {:?}: {e}", dir);
+    }
+    Ok(())
+}
+
+/// Shared between the chunks being encoded.
+struct ChunkState<F> {
+    manifest: Manifest,
+    /// Seconds of each chunk encoded so far.
+    encoded: Vec<f64>,
+    callback: F,
+}
+
+/// Encode the video of one chunk, then mark it done in the manifest.
+#[allow(clippy::too_many_arguments)]
+async fn encode_chunk<F: FnMut(EncodeProgress)>(
+    ffmpeg: &Path,
+    input: &Path,
+    dir: &Path,
+    encode: &ChunkedEncode,
+    index: usize,
+    state: &Mutex<ChunkState<F>>,
+    cancel: Option<CancellationToken>,
+) -> sf_core::Result<()> {
+    let (start, len, last) = {
+        let state = state.lock().unwrap();
+        let (start, len) = state.manifest.span(index);
+        (start, len, index + 1 == state.manifest.boundaries.len())
+    };
+
+    // Written under a temporary name so an interrupted chunk is never
+    // mistaken for a finished one.
+    let path = chunk_path(dir, index);
+    let partial = path.with_extension("part");
+
+    let mut cmd = ToolCommand::new(ffmpeg.to_path_buf());
+    cmd.timeout(Duration::from_secs(86400));
+    cmd.args(["-y", "-progress", "pipe:2", "-nostats"]);
+    cmd.args(encode.input_args.iter().cloned());
+    cmd.args(["-ss", &format!("{start:.6}"), "-i"]);
+    cmd.arg(input.to_string_lossy().as_ref());
+    // The last chunk runs to the end of the source.
+    if !last {
+        cmd.args(["-t", &format!("{len:.6}")]);
+    }
+    cmd.args(["-map", "0:v:0", "-an", "-sn", "-dn"]);
+    cmd.args(encode.video_args.iter().cloned());
+    cmd.args(["-f", "matroska"]);
+    cmd.arg(partial.to_string_lossy().as_ref());
+
+    let mut parser = ProgressParser::new(Some(len));
+    cmd.execute_with_stderr_callback(
+        |line| {
+            parser.feed(line, &mut |p: EncodeProgress| {
+                let mut state = state.lock().unwrap();
+                state.encoded[index] = p.pct * len;
+                state.report(p);
+            })
+        },
+        cancel,
+    )
+    .await?;
+
+    std::fs::rename(&partial, &path)?;
+    let mut state = state.lock().unwrap();
+    state.manifest.done.insert(index);
+    state.manifest.save(dir)?;
+    tracing::debug!("finished chunk {index} ({start:.1}s + {len:.1}s)");
+    Ok(())
+}
+
+impl<F: FnMut(EncodeProgress)> ChunkState<F> {
+    /// Report progress over the whole source, with the encoder stats of the
+    /// chunk that reported `chunk`.
+    fn report(&mut self, chunk: EncodeProgress) {
+        let duration = self.manifest.settings.duration_secs;
+        let pct = if duration > 0.0 {
+            (self.encoded.iter().sum::<f64>() / duration).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        (self.callback)(EncodeProgress {
+            pct,
+            total_size: None,
+            frame: None,
+            ..chunk
+        });
+    }
+}
+
+/// Run `tasks` with at most `limit` of them in flight, returning the first
+/// error. Tasks are polled on the current task rather than spawned, so they
+/// keep its task-locals (tool run observer, process control).
+async fn run_limited<F>(mut tasks: impl Iterator<Item = F>, limit: usize) -> sf_core::Result<()>
+where
+    F: Future<Output = sf_core::Result<()>>,
+{
+    let limit = limit.max(1);
+    let mut running: Vec<Pin<Box<F>>> = Vec::new();
+    std::future::poll_fn(|cx| loop {
+        while running.len() < limit {
+            match tasks.next() {
+                Some(task) => running.push(Box::pin(task)),
+                None => break,
+            }
+        }
+        if running.is_empty() {
+            return Poll::Ready(Ok(()));
+        }
+
+        let mut finished = false;
+        let mut i = 0;
+        while i < running.len() {
+            match running[i].as_mut().poll(cx) {
+                Poll::Ready(Ok(())) => {
+                    drop(running.swap_remove(i));
+                    finished = true;
+                }
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => i += 1,
+            }
+        }
+        if !finished {
+            return Poll::Pending;
+        }
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn settings() -> Settings {
+        Settings {
+            source: PathBuf::from("/media/film.mkv"),
+            source_size: 1000,
+            source_modified: Some(1),
+            duration_secs: 1000.0,
+            chunk_secs: 300.0,
+            args: vec!["-c:v".into(), "libx264".into()],
+        }
+    }
+
+    #[test]
+    fn chunk_dir_is_hidden_next_to_output() {
+        assert_eq!(
+            chunk_dir(Path::new("/media/film-pb.mp4")),
+            PathBuf::from("/media/.film-pb.mp4.chunks")
+        );
+    }
+
+    #[test]
+    fn plans_chunks_at_keyframes_before_targets() {
+        let keyframes: Vec<f64> = (1..100).map(|i| i as f64 * 10.0 - 0.5).collect();
+        assert_eq!(
+            plan_boundaries(&keyframes, 1200.0, 300.0),
+            vec![0.0, 299.5, 599.5, 899.5]
+        );
+    }
+
+    #[test]
+    fn plan_skips_short_chunks() {
+        // No keyframe near the first target: the first chunk runs on to the
+        // second target instead of being cut short.
+        assert_eq!(
+            plan_boundaries(&[50.0, 590.0], 1000.0, 300.0),
+            vec![0.0, 590.0]
+        );
+        // Nothing is started in the last half chunk.
+        assert_eq!(
+            plan_boundaries(&[290.0, 590.0, 890.0], 1000.0, 300.0),
+            vec![0.0, 290.0, 590.0]
+        );
+        assert_eq!(plan_boundaries(&[], 1000.0, 300.0), vec![0.0]);
+    }
+
+    #[test]
+    fn parses_keyframes_relative_to_start() {
+        let json = serde_json::json!({
+            "packets": [
+                { "pts_time": "301.500000", "flags": "K__" },
+                { "pts_time": "301.541000", "flags": "___" },
+                { "pts_time": "601.500000", "flags": "K__" },
+                { "pts_time": "301.500000", "flags": "K__" }
+            ],
+            "format": { "start_time": "1.500000" }
+        });
+        assert_eq!(parse_keyframes(&json), vec![300.0, 600.0]);
+    }
+
+    #[test]
+    fn manifest_round_trip_and_resume() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manifest = Manifest {
+            settings: settings(),
+            boundaries: vec![0.0, 300.0, 600.0],
+            done: BTreeSet::new(),
+        };
+        manifest.done.insert(0);
+        manifest.done.insert(1);
+        manifest.save(dir.path()).unwrap();
+        std::fs::write(chunk_path(dir.path(), 0), b"chunk").unwrap();
+
+        let loaded = Manifest::load(dir.path(), &settings()).unwrap();
+        assert_eq!(loaded, manifest);
+        assert_eq!(loaded.span(1), (300.0, 300.0));
+        assert_eq!(loaded.span(2), (600.0, 400.0));
+        // Chunk 1 is marked done but its file is missing.
+        assert_eq!(loaded.pending(dir.path()), vec![1, 2]);
+
+        let changed = Settings {
+            args: vec!["-c:v".into(), "libx265".into()],
+            ..settings()
+        };
+        assert!(Manifest::load(dir.path(), &changed).is_none());
+    }
+
+    #[tokio::test]
+    async fn run_limited_caps_tasks_in_flight() {
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let tasks = (0..6).map(|_| async {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            running.fetch_sub(1, Ordering::SeqCst);
+            Ok(())
+        });
+        run_limited(tasks, 2).await.unwrap();
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn run_limited_stops_at_first_error() {
+        let started = AtomicUsize::new(0);
+        let tasks = (0..4).map(|i| {
+            let started = &started;
+            async move {
+                started.fetch_add(1, Ordering::SeqCst);
+                if i == 0 {
+                    Err(sf_core::Error::Internal("boom".into()))
+                } else {
+                    Ok(())
+                }
+            }
+        });
+        assert!(run_limited(tasks, 1).await.is_err());
+        assert_eq!(started.load(Ordering::SeqCst), 1);
+    }
+}
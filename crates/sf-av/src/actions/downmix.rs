@@ -0,0 +1,368 @@
+//! Loudness-normalized stereo downmix using ffmpeg's `loudnorm` filter.
+//!
+//! Follows the EBU R128 two-pass approach: the first pass measures the
+//! source track's integrated loudness, true peak and loudness range; the
+//! second pass feeds those measurements back to `loudnorm` so it can apply a
+//! linear gain instead of dynamic compression.
+
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::command::ToolCommand;
+use crate::tools::ToolRegistry;
+use crate::workspace::Workspace;
+
+/// Settings for [`downmix_audio`].
+#[derive(Debug, Clone)]
+pub struct DownmixOptions {
+    /// 0-based index of the audio stream to downmix.
+    pub source_track: usize,
+    /// Number of audio streams in the input; the new track is appended after
+    /// them.
+    pub existing_tracks: usize,
+    /// Target integrated loudness in LUFS.
+    pub target_lufs: f64,
+    /// Maximum true peak in dBTP.
+    pub true_peak: f64,
+    /// Target loudness range in LU.
+    pub loudness_range: f64,
+    /// Boost the centre (dialogue) channel with `dialoguenhance`.
+    pub dialogue_boost: bool,
+    /// Output codec for the new track.
+    pub codec: sf_core::AudioCodec,
+    /// Output bitrate (e.g. `192k`), for lossy codecs.
+    pub bitrate: Option<String>,
+    pub title: Option<String>,
+    /// ISO 639-2 language code.
+    pub language: Option<String>,
+    /// Make the new track the default and clear the flag on the others.
+    pub make_default: bool,
+}
+
+/// Loudness measurements printed by `loudnorm` with `print_format=json`.
+///
+/// `input_*` are the source measurements from the first pass; `output_*` are
+/// the measurements of the normalized result from the second pass.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct LoudnormStats {
+    /// Integrated loudness (LUFS).
+    pub input_i: f64,
+    /// True peak (dBTP).
+    pub input_tp: f64,
+    /// Loudness range (LU).
+    pub input_lra: f64,
+    pub input_thresh: f64,
+    pub output_i: f64,
+    pub output_tp: f64,
+    pub output_lra: f64,
+    pub output_thresh: f64,
+    /// Gain offset (LU) applied by the second pass.
+    pub target_offset: f64,
+}
+
+/// `loudnorm` prints every value as a JSON string.
+#[derive(Deserialize)]
+struct RawLoudnormStats {
+    input_i: String,
+    input_tp: String,
+    input_lra: String,
+    input_thresh: String,
+    output_i: String,
+    output_tp: String,
+    output_lra: String,
+    output_thresh: String,
+    target_offset: String,
+}
+
+/// Extract the JSON block `loudnorm` prints at the end of ffmpeg's stderr.
+fn parse_loudnorm_stats(stderr: &str) -> sf_core::Result<LoudnormStats> {
+    let err = |msg: String| sf_core::Error::tool("ffmpeg", msg);
+
+    let start = stderr
+        .rfind('{')
+        .ok_or_else(|| err("no loudnorm measurements in ffmpeg output".into()))?;
+    let end = stderr[start..]
+        .find('}')
+        .map(|i| start + i + 1)
+        .ok_or_else(|| err("truncated loudnorm measurements".into()))?;
+    let raw: RawLoudnormStats = serde_json::from_str(&stderr[start..end])
+        .map_err(|e| err(format!("invalid loudnorm measurements: {e}")))?;
+
+    // Silence measures as "-inf"; clamp it to something usable.
+    let num = |name: &str, v: &str| -> sf_core::Result<f64> {
+        match v.trim() {
+            "-inf" => Ok(-99.0),
+            "inf" => Ok(99.0),
+            v => v
+                .parse()
+                .map_err(|_| err(format!("invalid loudnorm value {name}={v}"))),
+        }
+    };
+
+    Ok(LoudnormStats {
+        input_i: num("input_i", &raw.input_i)?,
+        input_tp: num("input_tp", &raw.input_tp)?,
+        input_lra: num("input_lra", &raw.input_lra)?,
+        input_thresh: num("input_thresh", &raw.input_thresh)?,
+        output_i: num("output_i", &raw.output_i)?,
+        output_tp: num("output_tp", &raw.output_tp)?,
+        output_lra: num("output_lra", &raw.output_lra)?,
+        output_thresh: num("output_thresh", &raw.output_thresh)?,
+        target_offset: num("target_offset", &raw.target_offset)?,
+    })
+}
+
+/// The downmix filters applied before `loudnorm` in both passes.
+///
+/// `dialoguenhance` only takes stereo and outputs 3.0 with the dialogue in a
+/// centre channel, so with the boost the result is downmixed to stereo again.
+fn downmix_filters(opts: &DownmixOptions) -> String {
+    let mut filters = String::from("aformat=channel_layouts=stereo");
+    if opts.dialogue_boost {
+        filters.push_str(",dialoguenhance,aformat=channel_layouts=stereo");
+    }
+    filters
+}
+
+fn loudnorm_target(opts: &DownmixOptions) -> String {
+    format!(
+        "loudnorm=I={}:TP={}:LRA={}",
+        opts.target_lufs, opts.true_peak, opts.loudness_range
+    )
+}
+
+/// The second-pass filter graph, using the first-pass `measured` values.
+fn second_pass_filter(opts: &DownmixOptions, measured: &LoudnormStats) -> String {
+    format!(
+        "[0:a:{src}]{pre},{target}:measured_I={i}:measured_TP={tp}:measured_LRA={lra}:\
+         measured_thresh={thresh}:offset={offset}:linear=true:print_format=json,\
+         aresample=48000[dmx]",
+        src = opts.source_track,
+        pre = downmix_filters(opts),
+        target = loudnorm_target(opts),
+        i = measured.input_i,
+        tp = measured.input_tp,
+        lra = measured.input_lra,
+        thresh = measured.input_thresh,
+        offset = measured.target_offset,
+    )
+}
+
+fn ffmpeg_codec(codec: sf_core::AudioCodec) -> &'static str {
+    match codec {
+        sf_core::AudioCodec::Ac3 => "ac3",
+        sf_core::AudioCodec::Eac3 => "eac3",
+        sf_core::AudioCodec::Flac => "flac",
+        sf_core::AudioCodec::Opus => "libopus",
+        _ => "aac",
+    }
+}
+
+/// Output arguments for the new track's codec, metadata and disposition.
+fn track_args(opts: &DownmixOptions) -> Vec<String> {
+    let n = opts.existing_tracks;
+    let mut args = vec![format!("-c:a:{n}"), ffmpeg_codec(opts.codec).to_string()];
+    if let Some(ref bitrate) = opts.bitrate {
+        args.extend([format!("-b:a:{n}"), bitrate.clone()]);
+    }
+    if let Some(ref title) = opts.title {
+        args.extend([format!("-metadata:s:a:{n}"), format!("title={title}")]);
+    }
+    if let Some(ref language) = opts.language {
+        args.extend([format!("-metadata:s:a:{n}"), format!("language={language}")]);
+    }
+    if opts.make_default {
+        args.extend(["-disposition:a".to_string(), "0".to_string()]);
+        args.extend([format!("-disposition:a:{n}"), "default".to_string()]);
+    } else {
+        args.extend([format!("-disposition:a:{n}"), "0".to_string()]);
+    }
+    args
+}
+
+/// Measure the loudness of one audio track (first `loudnorm` pass).
+async fn measure(
+    tools: &ToolRegistry,
+    input: &Path,
+    opts: &DownmixOptions,
+) -> sf_core::Result<LoudnormStats> {
+    let ffmpeg = tools.require("ffmpeg")?;
+    let filter = format!(
+        "{},{}:print_format=json",
+        downmix_filters(opts),
+        loudnorm_target(opts)
+    );
+
+    let mut cmd = ToolCommand::new(ffmpeg.path.clone());
+    cmd.timeout(Duration::from_secs(6 * 3600));
+    cmd.args(["-hide_banner", "-nostats", "-i"]);
+    cmd.arg(input.to_string_lossy().as_ref());
+    cmd.args(["-map", &format!("0:a:{}", opts.source_track)]);
+    cmd.args(["-af", &filter, "-f", "null", "-"]);
+    let output = cmd.execute().await?;
+
+    parse_loudnorm_stats(&output.stderr)
+}
+
+/// Append a loudness-normalized stereo downmix of one audio track.
+///
+/// All original streams are copied unchanged; the new track is added after
+/// the existing audio tracks. Returns the `loudnorm` measurements: the
+/// source loudness from the first pass and the result from the second.
+pub async fn downmix_audio(
+    workspace: &Workspace,
+    tools: &ToolRegistry,
+    opts: &DownmixOptions,
+) -> sf_core::Result<LoudnormStats> {
+    let input = workspace.input();
+    let output = workspace.output();
+    let ffmpeg = tools.require("ffmpeg")?;
+
+    tracing::info!(
+        "downmix audio track {} of {:?} to stereo at {} LUFS",
+        opts.source_track,
+        input,
+        opts.target_lufs
+    );
+
+    let measured = measure(tools, input, opts).await?;
+    tracing::info!(
+        "measured loudness: I={} LUFS, TP={} dBTP, LRA={} LU",
+        measured.input_i,
+        measured.input_tp,
+        measured.input_lra
+    );
+
+    let mut cmd = ToolCommand::new(ffmpeg.path.clone());
+    cmd.timeout(Duration::from_secs(6 * 3600));
+    cmd.args(["-hide_banner", "-nostats", "-y", "-i"]);
+    cmd.arg(input.to_string_lossy().as_ref());
+    cmd.args(["-filter_complex", &second_pass_filter(opts, &measured)]);
+    cmd.args([
+        "-map", "0:v?", "-map", "0:a", "-map", "[dmx]", "-map", "0:s?",
+    ]);
+    cmd.args(["-map_metadata", "0", "-map_chapters", "0", "-c", "copy"]);
+    cmd.args(track_args(opts));
+    cmd.arg(output.to_string_lossy().as_ref());
+    let result = cmd.execute().await?;
+
+    let normalized = parse_loudnorm_stats(&result.stderr)?;
+    Ok(LoudnormStats {
+        output_i: normalized.output_i,
+        output_tp: normalized.output_tp,
+        output_lra: normalized.output_lra,
+        output_thresh: normalized.output_thresh,
+        target_offset: normalized.target_offset,
+        ..measured
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STDERR: &str = r#"
+Input #0, matroska,webm, from 'in.mkv':
+  Stream #0:1(eng): Audio: truehd, 48000 Hz, 7.1, s32 (24 bit)
+[Parsed_loudnorm_1 @ 0x55d5c9a0]
+{
+	"input_i" : "-27.61",
+	"input_tp" : "-4.47",
+	"input_lra" : "18.06",
+	"input_thresh" : "-39.20",
+	"output_i" : "-23.01",
+	"output_tp" : "-1.00",
+	"output_lra" : "7.10",
+	"output_thresh" : "-34.30",
+	"normalization_type" : "dynamic",
+	"target_offset" : "0.01"
+}
+"#;
+
+    fn opts() -> DownmixOptions {
+        DownmixOptions {
+            source_track: 1,
+            existing_tracks: 2,
+            target_lufs: -23.0,
+            true_peak: -1.0,
+            loudness_range: 7.0,
+            dialogue_boost: false,
+            codec: sf_core::AudioCodec::Aac,
+            bitrate: Some("192k".into()),
+            title: Some("Stereo".into()),
+            language: Some("eng".into()),
+            make_default: false,
+        }
+    }
+
+    #[test]
+    fn parses_loudnorm_json() {
+        let stats = parse_loudnorm_stats(STDERR).unwrap();
+        assert_eq!(stats.input_i, -27.61);
+        assert_eq!(stats.input_tp, -4.47);
+        assert_eq!(stats.input_lra, 18.06);
+        assert_eq!(stats.input_thresh, -39.2);
+        assert_eq!(stats.output_i, -23.01);
+        assert_eq!(stats.target_offset, 0.01);
+    }
+
+    #[test]
+    fn parses_silence_and_rejects_missing_block() {
+        let silent = STDERR.replace("\"-27.61\"", "\"-inf\"");
+        assert_eq!(parse_loudnorm_stats(&silent).unwrap().input_i, -99.0);
+        assert!(parse_loudnorm_stats("no json here").is_err());
+    }
+
+    #[test]
+    fn second_pass_uses_measurements() {
+        let measured = parse_loudnorm_stats(STDERR).unwrap();
+        let filter = second_pass_filter(&opts(), &measured);
+        assert_eq!(
+            filter,
+            "[0:a:1]aformat=channel_layouts=stereo,loudnorm=I=-23:TP=-1:LRA=7:\
+             measured_I=-27.61:measured_TP=-4.47:measured_LRA=18.06:\
+             measured_thresh=-39.2:offset=0.01:linear=true:print_format=json,\
+             aresample=48000[dmx]"
+        );
+
+        let mut boosted = opts();
+        boosted.dialogue_boost = true;
+        let filter = second_pass_filter(&boosted, &measured);
+        assert!(filter.starts_with(
+            "[0:a:1]aformat=channel_layouts=stereo,dialoguenhance,\
+             aformat=channel_layouts=stereo,loudnorm="
+        ));
+    }
+
+    #[test]
+    fn track_args_target_the_new_stream() {
+        let args = track_args(&opts());
+        assert_eq!(
+            args,
+            [
+                "-c:a:2",
+                "aac",
+                "-b:a:2",
+                "192k",
+                "-metadata:s:a:2",
+                "title=Stereo",
+                "-metadata:s:a:2",
+                "language=eng",
+                "-disposition:a:2",
+                "0",
+            ]
+        );
+
+        let mut default = opts();
+        default.make_default = true;
+        let args = track_args(&default);
+        assert!(args.ends_with(&[
+            "-disposition:a".to_string(),
+            "0".to_string(),
+            "-disposition:a:2".to_string(),
+            "default".to_string(),
+        ]));
+    }
+}
@@ -0,0 +1,420 @@
+//! Dolby Vision generation for HDR10 and HDR10+ sources.
+//!
+//! The process:
+//! 1. Read HDR10 static metadata and count video frames (ffprobe)
+//! 2. Extract HEVC elementary stream (ffmpeg)
+//! 3. For HDR10+ sources, extract the dynamic metadata (hdr10plus_tool)
+//! 4. Generate one RPU per frame from the metadata (dolby_vision)
+//! 5. Inject the RPUs into the HEVC stream (dovi_tool inject-rpu)
+//! 6. Remux back with original audio and subtitles (mkvmerge, or ffmpeg for
+//!    MP4)
+
+use std::path::Path;
+use std::time::Duration;
+
+use dolby_vision::rpu::extension_metadata::blocks::{
+    ExtMetadataBlock, ExtMetadataBlockLevel1, ExtMetadataBlockLevel6,
+};
+use dolby_vision::rpu::generate::{GenerateConfig, GenerateProfile, VideoShot};
+use dolby_vision::rpu::vdr_dm_data::CmVersion;
+use dolby_vision::utils::nits_to_pq_12_bit;
+
+use super::hdr10plus::{extract_hevc, remux_hevc};
+use super::{probe_hdr_metadata, HdrMetadata};
+use crate::command::ToolCommand;
+use crate::tools::ToolRegistry;
+use crate::workspace::Workspace;
+
+/// Timeout for the steps that read or rewrite the whole video stream.
+const STREAM_TIMEOUT: Duration = Duration::from_secs(86400);
+
+/// Add Dolby Vision to the workspace input file, an HDR10 or HDR10+ HEVC
+/// video, by injecting a generated RPU.
+///
+/// `profile` must be `8`: the result is profile 8.1, keeping the HDR10 base
+/// layer. Profile 5 needs an IPTPQc2 base layer, so it can't be made from
+/// HDR10 and is rejected. With
+/// `dynamic_metadata` the per-scene L1 metadata comes from the source's
+/// HDR10+ metadata, which needs `hdr10plus_tool`; otherwise a single shot
+/// covers the whole file, with L1 derived from MaxCLL/MaxFALL.
+pub async fn generate_dv(
+    workspace: &Workspace,
+    tools: &ToolRegistry,
+    profile: u8,
+    dynamic_metadata: bool,
+) -> sf_core::Result<()> {
+    let input = workspace.input();
+    let output = workspace.output();
+
+    tracing::info!(
+        "generate DV profile {profile} for {:?} (HDR10+ metadata: {dynamic_metadata})",
+        input
+    );
+
+    let generate_profile = generate_profile(profile)?;
+    let dovi_tool = tools.require("dovi_tool")?;
+    let hdr10plus_tool = if dynamic_metadata {
+        Some(tools.require("hdr10plus_tool")?)
+    } else {
+        None
+    };
+
+    // Step 1: HDR10 static metadata and frame count.
+    let hdr = probe_hdr_metadata(tools, input).await?;
+    let frames = count_frames(tools, input).await?;
+
+    // Step 2: Extract HEVC elementary stream.
+    let hevc_file = workspace.temp_file("video.hevc");
+    extract_hevc(tools, input, &hevc_file).await?;
+
+    // Step 3: Per-scene metadata from HDR10+.
+    let shots = match hdr10plus_tool {
+        Some(hdr10plus_tool) => {
+            let json_file = workspace.temp_file("hdr10plus.json");
+            let mut cmd = ToolCommand::new(hdr10plus_tool.path.clone());
+            cmd.timeout(STREAM_TIMEOUT);
+            cmd.arg("extract");
+            cmd.arg(hevc_file.to_string_lossy().as_ref());
+            cmd.arg("-o");
+            cmd.arg(json_file.to_string_lossy().as_ref());
+            cmd.execute().await?;
+
+            let json: serde_json::Value =
+                serde_json::from_str(&std::fs::read_to_string(&json_file)?).map_err(|e| {
+                    sf_core::Error::tool("hdr10plus_tool", format!("invalid JSON output: {e}"))
+                })?;
+            let shots = hdr10plus_shots(&json);
+            if shots.is_empty() {
+                return Err(sf_core::Error::tool(
+                    "hdr10plus_tool",
+                    "no HDR10+ metadata found in the video stream",
+                ));
+            }
+            fit_shots(shots, frames)
+        }
+        None => Vec::new(),
+    };
+
+    // Step 4: Generate the RPUs.
+    let rpu_file = workspace.temp_file("RPU.bin");
+    {
+        let config = generate_config(generate_profile, &hdr, frames, shots);
+        let path = rpu_file.clone();
+        tokio::task::spawn_blocking(move || config.write_rpus(path))
+            .await
+            .map_err(|e| sf_core::Error::Internal(format!("RPU generation task failed: {e}")))?
+            .map_err(|e| sf_core::Error::Internal(format!("RPU generation failed: {e}")))?;
+    }
+
+    // Step 5: Inject the RPUs.
+    let injected_hevc = workspace.temp_file("video_injected.hevc");
+    {
+        let mut cmd = ToolCommand::new(dovi_tool.path.clone());
+        cmd.timeout(STREAM_TIMEOUT);
+        cmd.args(["inject-rpu", "-i"]);
+        cmd.arg(hevc_file.to_string_lossy().as_ref());
+        cmd.arg("--rpu-in");
+        cmd.arg(rpu_file.to_string_lossy().as_ref());
+        cmd.arg("-o");
+        cmd.arg(injected_hevc.to_string_lossy().as_ref());
+        cmd.execute().await?;
+    }
+
+    // Step 6: Remux the new video with original audio and subtitles.
+    remux_hevc(tools, &injected_hevc, input, &output).await?;
+
+    tracing::info!("DV generation complete: {:?}", output);
+    Ok(())
+}
+
+fn generate_profile(profile: u8) -> sf_core::Result<GenerateProfile> {
+    match profile {
+        8 => Ok(GenerateProfile::Profile81),
+        5 => Err(sf_core::Error::Validation(
+            "Dolby Vision profile 5 needs an IPTPQc2 base layer; \
+             HDR10 sources can only become profile 8.1"
+                .into(),
+        )),
+        other => Err(sf_core::Error::Validation(format!(
+            "cannot generate Dolby Vision profile {other}; only profile 8 (8.1) is supported"
+        ))),
+    }
+}
+
+/// Count the frames of the first video stream by reading its packets.
+async fn count_frames(tools: &ToolRegistry, input: &Path) -> sf_core::Result<usize> {
+    let ffprobe = tools.require("ffprobe")?;
+    let mut cmd = ToolCommand::new(ffprobe.path.clone());
+    cmd.timeout(STREAM_TIMEOUT);
+    cmd.args(["-v", "error", "-select_streams", "v:0", "-count_packets"]);
+    cmd.args(["-show_entries", "stream=nb_read_packets", "-of", "csv=p=0"]);
+    cmd.arg(input.to_string_lossy().as_ref());
+    let output = cmd.execute().await?;
+
+    parse_frame_count(&output.stdout)
+        .ok_or_else(|| sf_core::Error::tool("ffprobe", "could not count video frames"))
+}
+
+/// Parse ffprobe's `nb_read_packets` CSV output.
+fn parse_frame_count(stdout: &str) -> Option<usize> {
+    let count = stdout
+        .lines()
+        .next()?
+        .trim()
+        .trim_end_matches(',')
+        .parse()
+        .ok()?;
+    (count > 0).then_some(count)
+}
+
+/// Group the frames of `hdr10plus_tool extract` output into one shot per
+/// scene, with L1 metadata from the scene's brightest MaxSCL and mean
+/// average RGB.
+fn hdr10plus_shots(json: &serde_json::Value) -> Vec<VideoShot> {
+    let frames = json["SceneInfo"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    // (scene id, frames, max MaxSCL, summed AverageRGB), in 0.1 cd/m².
+    let mut scenes: Vec<(u64, usize, u64, u64)> = Vec::new();
+    for frame in frames {
+        let scene_id = frame["SceneId"].as_u64().unwrap_or_default();
+        let luminance = &frame["LuminanceParameters"];
+        let max_scl = luminance["MaxScl"]
+            .as_array()
+            .and_then(|v| v.iter().filter_map(|n| n.as_u64()).max())
+            .unwrap_or_default();
+        let average = luminance["AverageRGB"].as_u64().unwrap_or_default();
+
+        match scenes.last_mut() {
+            Some(scene) if scene.0 == scene_id => {
+                scene.1 += 1;
+                scene.2 = scene.2.max(max_scl);
+                scene.3 += average;
+            }
+            _ => scenes.push((scene_id, 1, max_scl, average)),
+        }
+    }
+
+    let mut start = 0;
+    scenes
+        .into_iter()
+        .map(|(_, duration, max_scl, average_sum)| {
+            let nits = |tenths: u64| tenths as f64 / 10.0;
+            let l1 = ExtMetadataBlockLevel1::from_stats_cm_version(
+                0,
+                nits_to_pq_12_bit(nits(max_scl)),
+                nits_to_pq_12_bit(nits(average_sum / duration as u64)),
+                CmVersion::V40,
+            );
+            let shot = VideoShot {
+                start,
+                duration,
+                metadata_blocks: vec![ExtMetadataBlock::Level1(l1)],
+                ..Default::default()
+            };
+            start += duration;
+            shot
+        })
+        .collect()
+}
+
+/// Make `shots` cover exactly `frames` frames, stretching or cutting the
+/// last ones when the HDR10+ metadata and the video disagree.
+fn fit_shots(mut shots: Vec<VideoShot>, frames: usize) -> Vec<VideoShot> {
+    let total: usize = shots.iter().map(|s| s.duration).sum();
+    if total != frames {
+        tracing::warn!("HDR10+ metadata covers {total} frames but the video has {frames}");
+    }
+
+    shots.retain(|s| s.start < frames);
+    if let Some(last) = shots.last_mut() {
+        last.duration = frames - last.start;
+    }
+    shots
+}
+
+/// Build the RPU generation config: L6 from the HDR10 mastering display and
+/// content light levels, and `shots` (or one shot for the whole file, with
+/// L1 from MaxCLL/MaxFALL).
+fn generate_config(
+    profile: GenerateProfile,
+    hdr: &HdrMetadata,
+    length: usize,
+    shots: Vec<VideoShot>,
+) -> GenerateConfig {
+    let (max_luminance, min_luminance) = hdr
+        .mastering_display
+        .as_ref()
+        .map_or((1000.0, 0.005), |md| (md.max_luminance, md.min_luminance));
+    let (max_cll, max_fall) = hdr.content_light.unwrap_or_default();
+    let clamp = |v: f64| v.round().clamp(0.0, 10_000.0) as u16;
+
+    let level6 = ExtMetadataBlockLevel6 {
+        max_display_mastering_luminance: clamp(max_luminance),
+        // In units of 0.0001 cd/m².
+        min_display_mastering_luminance: clamp(min_luminance * 10_000.0),
+        max_content_light_level: clamp(max_cll.into()),
+        max_frame_average_light_level: clamp(max_fall.into()),
+    };
+
+    let mut default_metadata_blocks = Vec::new();
+    if max_cll > 0 {
+        let max_fall = if max_fall > 0 { max_fall } else { max_cll };
+        default_metadata_blocks.push(ExtMetadataBlock::Level1(
+            ExtMetadataBlockLevel1::from_stats_cm_version(
+                0,
+                nits_to_pq_12_bit(max_cll),
+                nits_to_pq_12_bit(max_fall),
+                CmVersion::V40,
+            ),
+        ));
+    }
+
+    let shots = if shots.is_empty() {
+        vec![VideoShot {
+            start: 0,
+            duration: length,
+            ..Default::default()
+        }]
+    } else {
+        shots
+    };
+
+    GenerateConfig {
+        profile,
+        length,
+        level6: Some(level6),
+        default_metadata_blocks,
+        shots,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::MasteringDisplay;
+
+    fn hdr10() -> HdrMetadata {
+        HdrMetadata {
+            mastering_display: Some(MasteringDisplay {
+                red: (0.68, 0.32),
+                green: (0.265, 0.69),
+                blue: (0.15, 0.06),
+                white_point: (0.3127, 0.329),
+                max_luminance: 1000.0,
+                min_luminance: 0.0050,
+            }),
+            content_light: Some((1000, 400)),
+            ..Default::default()
+        }
+    }
+
+    fn scene_frame(scene_id: u64, max_scl: [u64; 3], average: u64) -> serde_json::Value {
+        serde_json::json!({
+            "SceneId": scene_id,
+            "LuminanceParameters": { "MaxScl": max_scl, "AverageRGB": average },
+        })
+    }
+
+    fn level1(shot: &VideoShot) -> &ExtMetadataBlockLevel1 {
+        match &shot.metadata_blocks[0] {
+            ExtMetadataBlock::Level1(l1) => l1,
+            other => panic!("unexpected block {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_frame_count() {
+        assert_eq!(parse_frame_count("1440\n"), Some(1440));
+        assert_eq!(parse_frame_count("1440,\n"), Some(1440));
+        assert_eq!(parse_frame_count("0\n"), None);
+        assert_eq!(parse_frame_count("N/A\n"), None);
+        assert_eq!(parse_frame_count(""), None);
+    }
+
+    #[test]
+    fn groups_hdr10plus_frames_into_scenes() {
+        let json = serde_json::json!({
+            "SceneInfo": [
+                scene_frame(0, [10000, 8000, 6000], 1000),
+                scene_frame(0, [12000, 8000, 6000], 3000),
+                scene_frame(1, [4000, 3000, 2000], 500),
+            ]
+        });
+        let shots = hdr10plus_shots(&json);
+        assert_eq!(shots.len(), 2);
+        assert_eq!((shots[0].start, shots[0].duration), (0, 2));
+        assert_eq!((shots[1].start, shots[1].duration), (2, 1));
+
+        let l1 = level1(&shots[0]);
+        assert_eq!(l1.max_pq, nits_to_pq_12_bit(1200.0));
+        assert_eq!(l1.avg_pq, nits_to_pq_12_bit(200.0));
+        assert_eq!(level1(&shots[1]).max_pq, nits_to_pq_12_bit(400.0));
+    }
+
+    #[test]
+    fn fits_shots_to_frame_count() {
+        let shot = |start, duration| VideoShot {
+            start,
+            duration,
+            ..Default::default()
+        };
+
+        let fitted = fit_shots(vec![shot(0, 10), shot(10, 10)], 25);
+        assert_eq!(fitted.last().unwrap().duration, 15);
+
+        let fitted = fit_shots(vec![shot(0, 10), shot(10, 10)], 8);
+        assert_eq!(fitted.len(), 1);
+        assert_eq!(fitted[0].duration, 8);
+    }
+
+    #[test]
+    fn level6_from_hdr10_metadata() {
+        let config = generate_config(GenerateProfile::Profile81, &hdr10(), 24, Vec::new());
+        assert_eq!(
+            config.level6,
+            Some(ExtMetadataBlockLevel6 {
+                max_display_mastering_luminance: 1000,
+                min_display_mastering_luminance: 50,
+                max_content_light_level: 1000,
+                max_frame_average_light_level: 400,
+            })
+        );
+        assert_eq!(config.shots.len(), 1);
+        assert_eq!(config.shots[0].duration, 24);
+        assert_eq!(config.default_metadata_blocks.len(), 1);
+    }
+
+    #[test]
+    fn generates_profile_81_rpus() {
+        let config = generate_config(GenerateProfile::Profile81, &hdr10(), 24, Vec::new());
+        let rpus = config.generate_rpu_list().unwrap();
+        assert_eq!(rpus.len(), 24);
+        assert!(rpus.iter().all(|rpu| rpu.dovi_profile == 8));
+    }
+
+    #[test]
+    fn generates_from_hdr10plus_shots() {
+        let json = serde_json::json!({
+            "SceneInfo": [
+                scene_frame(0, [10000, 8000, 6000], 1000),
+                scene_frame(1, [4000, 3000, 2000], 500),
+            ]
+        });
+        let shots = fit_shots(hdr10plus_shots(&json), 2);
+        let config = generate_config(GenerateProfile::Profile81, &hdr10(), 2, shots);
+        let rpus = config.generate_rpu_list().unwrap();
+        assert_eq!(rpus.len(), 2);
+        assert!(rpus.iter().all(|rpu| rpu.dovi_profile == 8));
+    }
+
+    #[test]
+    fn rejects_unsupported_profiles() {
+        assert!(generate_profile(5).is_err());
+        assert!(generate_profile(7).is_err());
+        assert!(generate_profile(8).is_ok());
+    }
+}
@@ -0,0 +1,226 @@
+//! HDR10+ dynamic metadata handling via `hdr10plus_tool`.
+//!
+//! The metadata lives in SEI messages of the HEVC stream, so each operation
+//! works on the extracted elementary stream (ffmpeg):
+//! - extract: write the metadata to JSON (`hdr10plus_tool extract`)
+//! - inject: add metadata from JSON, e.g. after a re-encode dropped it
+//!   (`hdr10plus_tool inject`)
+//! - remove: strip it for devices that mishandle it (`hdr10plus_tool remove`)
+//!
+//! Inject and remove then remux the new stream with the original audio,
+//! subtitles and chapters (mkvmerge, or ffmpeg for MP4).
+
+use std::path::Path;
+use std::time::Duration;
+
+use super::transcode::is_mp4;
+use crate::command::ToolCommand;
+use crate::tools::ToolRegistry;
+
+/// Timeout for the steps that read or rewrite the whole video stream.
+const STREAM_TIMEOUT: Duration = Duration::from_secs(86400);
+
+/// Write the HDR10+ metadata of `input`'s video to `metadata` as JSON.
+/// Intermediate files go in `temp_dir`.
+pub async fn extract_hdr10plus(
+    tools: &ToolRegistry,
+    input: &Path,
+    metadata: &Path,
+    temp_dir: &Path,
+) -> sf_core::Result<()> {
+    let hdr10plus_tool = tools.require("hdr10plus_tool")?;
+    tracing::info!("extract HDR10+ metadata from {:?} to {:?}", input, metadata);
+
+    let hevc_file = temp_dir.join("hdr10plus_source.hevc");
+    extract_hevc(tools, input, &hevc_file).await?;
+
+    let mut cmd = ToolCommand::new(hdr10plus_tool.path.clone());
+    cmd.timeout(STREAM_TIMEOUT);
+    cmd.arg("extract");
+    cmd.arg(hevc_file.to_string_lossy().as_ref());
+    cmd.arg("-o");
+    cmd.arg(metadata.to_string_lossy().as_ref());
+    let result = cmd.execute().await;
+    let _ = std::fs::remove_file(&hevc_file);
+    result?;
+    Ok(())
+}
+
+/// Inject the HDR10+ metadata JSON `metadata` into `input`'s video, writing
+/// the result to `output`. The metadata must cover every frame.
+pub async fn inject_hdr10plus(
+    tools: &ToolRegistry,
+    input: &Path,
+    metadata: &Path,
+    output: &Path,
+    temp_dir: &Path,
+) -> sf_core::Result<()> {
+    let hdr10plus_tool = tools.require("hdr10plus_tool")?;
+    tracing::info!(
+        "inject HDR10+ metadata from {:?} into {:?}",
+        metadata,
+        input
+    );
+
+    let hevc_file = temp_dir.join("hdr10plus_source.hevc");
+    let injected = temp_dir.join("hdr10plus_injected.hevc");
+    extract_hevc(tools, input, &hevc_file).await?;
+    {
+        let mut cmd = ToolCommand::new(hdr10plus_tool.path.clone());
+        cmd.timeout(STREAM_TIMEOUT);
+        cmd.args(["inject", "-i"]);
+        cmd.arg(hevc_file.to_string_lossy().as_ref());
+        cmd.arg("-j");
+        cmd.arg(metadata.to_string_lossy().as_ref());
+        cmd.arg("-o");
+        cmd.arg(injected.to_string_lossy().as_ref());
+        cmd.execute().await?;
+    }
+    let _ = std::fs::remove_file(&hevc_file);
+
+    remux_hevc(tools, &injected, input, output).await?;
+    let _ = std::fs::remove_file(&injected);
+    Ok(())
+}
+
+/// Strip the HDR10+ metadata from `input`'s video, writing the result (plain
+/// HDR10) to `output`.
+pub async fn remove_hdr10plus(
+    tools: &ToolRegistry,
+    input: &Path,
+    output: &Path,
+    temp_dir: &Path,
+) -> sf_core::Result<()> {
+    let hdr10plus_tool = tools.require("hdr10plus_tool")?;
+    tracing::info!("remove HDR10+ metadata from {:?}", input);
+
+    let hevc_file = temp_dir.join("hdr10plus_source.hevc");
+    let stripped = temp_dir.join("hdr10plus_removed.hevc");
+    extract_hevc(tools, input, &hevc_file).await?;
+    {
+        let mut cmd = ToolCommand::new(hdr10plus_tool.path.clone());
+        cmd.timeout(STREAM_TIMEOUT);
+        cmd.arg("remove");
+        cmd.arg(hevc_file.to_string_lossy().as_ref());
+        cmd.arg("-o");
+        cmd.arg(stripped.to_string_lossy().as_ref());
+        cmd.execute().await?;
+    }
+    let _ = std::fs::remove_file(&hevc_file);
+
+    remux_hevc(tools, &stripped, input, output).await?;
+    let _ = std::fs::remove_file(&stripped);
+    Ok(())
+}
+
+/// Extract the first video stream of `input` as an Annex B HEVC elementary
+/// stream.
+pub(super) async fn extract_hevc(
+    tools: &ToolRegistry,
+    input: &Path,
+    hevc: &Path,
+) -> sf_core::Result<()> {
+    let ffmpeg = tools.require("ffmpeg")?;
+    let mut cmd = ToolCommand::new(ffmpeg.path.clone());
+    cmd.timeout(STREAM_TIMEOUT);
+    cmd.args(["-y", "-i"]);
+    cmd.arg(input.to_string_lossy().as_ref());
+    cmd.args(["-map", "0:v:0", "-c:v", "copy"]);
+    cmd.args(["-bsf:v", "hevc_mp4toannexb", "-f", "hevc"]);
+    cmd.arg(hevc.to_string_lossy().as_ref());
+    cmd.execute().await?;
+    Ok(())
+}
+
+/// Mux the elementary stream `hevc` with everything but the video of
+/// `source` into `output`.
+///
+/// A raw HEVC stream carries no timing, so the frame rate of `source`'s video
+/// is applied to it.
+pub(super) async fn remux_hevc(
+    tools: &ToolRegistry,
+    hevc: &Path,
+    source: &Path,
+    output: &Path,
+) -> sf_core::Result<()> {
+    let frame_rate = probe_frame_rate(tools, source).await?;
+
+    if is_mp4(output) {
+        let ffmpeg = tools.require("ffmpeg")?;
+        let mut cmd = ToolCommand::new(ffmpeg.path.clone());
+        cmd.timeout(STREAM_TIMEOUT);
+        cmd.arg("-y");
+        if let Some(rate) = &frame_rate {
+            cmd.args(["-r", rate]);
+        }
+        cmd.args(["-f", "hevc", "-i"]);
+        cmd.arg(hevc.to_string_lossy().as_ref());
+        cmd.arg("-i");
+        cmd.arg(source.to_string_lossy().as_ref());
+        cmd.args(["-map", "0:v:0", "-map", "1:a?", "-map", "1:s?"]);
+        cmd.args(["-map_metadata", "1", "-map_chapters", "1"]);
+        cmd.args(["-c", "copy", "-tag:v", "hvc1", "-movflags", "+faststart"]);
+        cmd.arg(output.to_string_lossy().as_ref());
+        cmd.execute().await?;
+    } else {
+        let mkvmerge = tools.require("mkvmerge")?;
+        let mut cmd = ToolCommand::new(mkvmerge.path.clone());
+        cmd.timeout(STREAM_TIMEOUT);
+        cmd.arg("-o");
+        cmd.arg(output.to_string_lossy().as_ref());
+        if let Some(rate) = &frame_rate {
+            cmd.arg("--default-duration");
+            cmd.arg(format!("0:{rate}fps"));
+        }
+        cmd.arg(hevc.to_string_lossy().as_ref());
+        cmd.arg("--no-video");
+        cmd.arg(source.to_string_lossy().as_ref());
+        cmd.execute().await?;
+    }
+    Ok(())
+}
+
+/// The frame rate of `input`'s first video stream as a rational
+/// (`24000/1001`), if known.
+async fn probe_frame_rate(tools: &ToolRegistry, input: &Path) -> sf_core::Result<Option<String>> {
+    let ffprobe = tools.require("ffprobe")?;
+    let mut cmd = ToolCommand::new(ffprobe.path.clone());
+    cmd.timeout(Duration::from_secs(60));
+    cmd.args(["-v", "error", "-select_streams", "v:0"]);
+    cmd.args(["-show_entries", "stream=r_frame_rate", "-of", "csv=p=0"]);
+    cmd.arg(input.to_string_lossy().as_ref());
+    let output = cmd.execute().await?;
+    Ok(parse_frame_rate(&output.stdout))
+}
+
+/// Parse ffprobe's `r_frame_rate` CSV output, rejecting `0/0`.
+fn parse_frame_rate(stdout: &str) -> Option<String> {
+    let rate = stdout.lines().next()?.trim().trim_end_matches(',');
+    let (num, den) = rate.split_once('/').unwrap_or((rate, "1"));
+    let valid = num.parse::<u64>().is_ok_and(|n| n > 0) && den.parse::<u64>().is_ok_and(|d| d > 0);
+    valid.then(|| rate.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_frame_rates() {
+        assert_eq!(
+            parse_frame_rate("24000/1001\n").as_deref(),
+            Some("24000/1001")
+        );
+        assert_eq!(parse_frame_rate("25/1,\n").as_deref(), Some("25/1"));
+        assert_eq!(parse_frame_rate("30\n").as_deref(), Some("30"));
+        assert_eq!(parse_frame_rate("0/0\n"), None);
+        assert_eq!(parse_frame_rate(""), None);
+    }
+
+    #[test]
+    fn mp4_outputs_are_muxed_with_ffmpeg() {
+        assert!(is_mp4(Path::new("/m/film.MP4")));
+        assert!(is_mp4(Path::new("/m/film.m4v")));
+        assert!(!is_mp4(Path::new("/m/film.mkv")));
+    }
+}
@@ -1,16 +1,40 @@
-//! Media processing actions: remux, DV conversion, audio, track stripping,
-//! arbitrary command execution, and Profile B encoding.
+//! Media processing actions: remux, DV conversion and generation, HDR10+
+//! metadata extraction/injection/removal, audio, loudness-normalized
+//! downmix, track stripping, track order/flags, subtitle extraction and
+//! conversion, arbitrary command execution, Profile B encoding (optionally in
+//! resumable chunks), HEVC/AV1 transcoding, and decode checks.
 
 mod remux;
 mod dovi;
+mod dv_generate;
+mod hdr10plus;
 mod audio;
+mod downmix;
 mod strip;
+mod subtitles;
+mod tracks;
 mod exec;
 mod profile_b;
+mod chunked;
+mod progress;
+mod transcode;
+mod verify;
 
 pub use remux::remux;
 pub use dovi::convert_dv_profile;
+pub use dv_generate::generate_dv;
+pub use hdr10plus::{extract_hdr10plus, inject_hdr10plus, remove_hdr10plus};
 pub use audio::add_compat_audio;
+pub use downmix::{downmix_audio, DownmixOptions, LoudnormStats};
 pub use strip::strip_tracks;
+pub use subtitles::{
+    convert_subtitles_to_srt, extract_subtitles, is_ass_subtitle, is_text_subtitle,
+    SubtitleExtract,
+};
+pub use tracks::{edit_tracks, TrackLayout, TrackSpec};
 pub use exec::exec_command;
-pub use profile_b::{adaptive_crf, convert_to_profile_b, convert_to_profile_b_with_progress, EncodeProgress};
+pub use chunked::chunk_dir;
+pub use profile_b::{adaptive_crf, convert_to_profile_b, convert_to_profile_b_with_progress};
+pub use progress::EncodeProgress;
+pub use transcode::{probe_hdr_metadata, transcode, HdrMetadata, MasteringDisplay, TranscodeOptions};
+pub use verify::decode_check;
@@ -5,6 +5,8 @@ use std::time::Duration;
 
 use tokio_util::sync::CancellationToken;
 
+use super::chunked::{chunk_dir, encode_chunked, ChunkOptions, ChunkedEncode};
+use super::progress::{EncodeProgress, ProgressParser};
 use crate::command::ToolCommand;
 use crate::tools::ToolRegistry;
 
@@ -135,22 +137,17 @@ pub async fn convert_to_profile_b(
     Ok(())
 }
 
-/// Progress stats from an ffmpeg encode.
-pub struct EncodeProgress {
-    /// 0.0..1.0
-    pub pct: f64,
-    pub fps: Option<f64>,
-    pub bitrate: Option<String>,
-    pub speed: Option<String>,
-    pub total_size: Option<i64>,
-    pub frame: Option<u64>,
-}
-
 /// Like [`convert_to_profile_b`] but streams progress via a callback and
 /// supports cancellation.
 ///
 /// `duration_secs` is the source duration used to compute percentage.
 /// `progress_callback` receives an [`EncodeProgress`] periodically.
+///
+/// When the duration is known and at least two `chunk_secs` long, the video
+/// is encoded in resumable chunks in [`chunk_dir`](super::chunk_dir)`(output)`:
+/// calling this again after a crash or restart continues from the last
+/// finished chunk.
+#[allow(clippy::too_many_arguments)]
 pub async fn convert_to_profile_b_with_progress(
     tools: &ToolRegistry,
     input: &Path,
@@ -182,6 +179,58 @@ pub async fn convert_to_profile_b_with_progress(
         config.hw_accel,
     );
 
+    let mut video_args: Vec<String> = vec![
+        "-c:v".into(),
+        encoder.into(),
+        "-profile:v".into(),
+        "high".into(),
+    ];
+    if use_crf {
+        video_args.extend(["-crf".into(), crf.to_string()]);
+        video_args.extend(["-preset".into(), config.video_preset.clone()]);
+    } else {
+        video_args.extend(["-b:v", "5M", "-maxrate", "8M", "-bufsize", "16M"].map(String::from));
+    }
+    video_args.extend(
+        [
+            "-vf",
+            "scale='min(1920,iw)':'min(1080,ih)':force_original_aspect_ratio=decrease:force_divisible_by=2",
+            "-force_key_frames",
+            "expr:gte(t,n_forced*2)",
+        ]
+        .map(String::from),
+    );
+    let audio_args = ["-c:a", "aac", "-b:a", &config.audio_bitrate, "-ac", "2"];
+
+    let chunk_secs = f64::from(config.chunk_secs);
+    if let Some(duration) = duration_secs.filter(|d| chunk_secs > 0.0 && *d >= 2.0 * chunk_secs) {
+        let mut mux_args: Vec<String> =
+            vec!["-map".into(), "1:a:0".into(), "-c:v".into(), "copy".into()];
+        mux_args.extend(audio_args.map(String::from));
+        mux_args.extend(["-movflags", "+faststart"].map(String::from));
+        let encode = ChunkedEncode {
+            input_args: hwaccel_args.iter().map(|a| a.to_string()).collect(),
+            video_args,
+            mux_args,
+        };
+        let chunking = ChunkOptions {
+            work_dir: chunk_dir(output),
+            chunk_secs,
+            parallelism: config.chunk_parallelism,
+        };
+        return encode_chunked(
+            tools,
+            input,
+            output,
+            &encode,
+            &chunking,
+            duration,
+            progress_callback,
+            cancel,
+        )
+        .await;
+    }
+
     let mut cmd = ToolCommand::new(ffmpeg.path.clone());
     cmd.timeout(Duration::from_secs(86400));
     cmd.args(["-y", "-progress", "pipe:2", "-nostats"]);
@@ -192,80 +241,16 @@ pub async fn convert_to_profile_b_with_progress(
 
     cmd.args(["-i"]);
     cmd.arg(input.to_string_lossy().as_ref());
-
-    cmd.args(["-c:v", encoder, "-profile:v", "high"]);
-
-    if use_crf {
-        cmd.args(["-crf", &crf.to_string()]);
-        cmd.args(["-preset", &config.video_preset]);
-    } else {
-        cmd.args(["-b:v", "5M", "-maxrate", "8M", "-bufsize", "16M"]);
-    }
-
-    cmd.args([
-        "-vf",
-        "scale='min(1920,iw)':'min(1080,ih)':force_original_aspect_ratio=decrease:force_divisible_by=2",
-    ]);
-    cmd.args(["-force_key_frames", "expr:gte(t,n_forced*2)"]);
-    cmd.args(["-c:a", "aac", "-b:a", &config.audio_bitrate, "-ac", "2"]);
+    cmd.args(video_args);
+    cmd.args(audio_args);
     cmd.args(["-movflags", "+faststart"]);
     cmd.args(["-map", "0:v:0", "-map", "0:a:0"]);
     cmd.arg(output.to_string_lossy().as_ref());
 
     // Parse ffmpeg -progress output.
-    let mut last_out_time_us: Option<i64> = None;
-    let mut last_fps: Option<f64> = None;
-    let mut last_bitrate: Option<String> = None;
-    let mut last_speed: Option<String> = None;
-    let mut last_total_size: Option<i64> = None;
-    let mut last_frame: Option<u64> = None;
-    let mut last_callback = std::time::Instant::now();
-
+    let mut parser = ProgressParser::new(duration_secs);
     cmd.execute_with_stderr_callback(
-        |line| {
-            if let Some(val) = line.strip_prefix("out_time_us=") {
-                last_out_time_us = val.trim().parse::<i64>().ok();
-            } else if let Some(val) = line.strip_prefix("fps=") {
-                last_fps = val.trim().parse::<f64>().ok();
-            } else if let Some(val) = line.strip_prefix("bitrate=") {
-                let v = val.trim();
-                if v != "N/A" {
-                    last_bitrate = Some(v.to_string());
-                }
-            } else if let Some(val) = line.strip_prefix("speed=") {
-                let v = val.trim();
-                if v != "N/A" {
-                    last_speed = Some(v.to_string());
-                }
-            } else if let Some(val) = line.strip_prefix("total_size=") {
-                last_total_size = val.trim().parse::<i64>().ok();
-            } else if let Some(val) = line.strip_prefix("frame=") {
-                last_frame = val.trim().parse::<u64>().ok();
-            } else if line.starts_with("progress=") {
-                // End of a progress block — emit callback.
-                if let (Some(out_us), Some(dur)) = (last_out_time_us, duration_secs) {
-                    if dur > 0.0 {
-                        let elapsed_secs = out_us as f64 / 1_000_000.0;
-                        let pct = (elapsed_secs / dur).clamp(0.0, 1.0);
-                        // Throttle to ~2 second intervals.
-                        let now = std::time::Instant::now();
-                        if now.duration_since(last_callback) >= Duration::from_secs(2)
-                            || line.contains("end")
-                        {
-                            progress_callback(EncodeProgress {
-                                pct,
-                                fps: last_fps,
-                                bitrate: last_bitrate.clone(),
-                                speed: last_speed.clone(),
-                                total_size: last_total_size,
-                                frame: last_frame,
-                            });
-                            last_callback = now;
-                        }
-                    }
-                }
-            }
-        },
+        |line| parser.feed(line, &mut progress_callback),
         cancel,
     )
     .await?;
@@ -0,0 +1,126 @@
+//! Parsing of ffmpeg `-progress` output shared by the encoding actions.
+
+use std::time::{Duration, Instant};
+
+/// Progress stats from an ffmpeg encode.
+pub struct EncodeProgress {
+    /// 0.0..1.0
+    pub pct: f64,
+    pub fps: Option<f64>,
+    pub bitrate: Option<String>,
+    pub speed: Option<String>,
+    pub total_size: Option<i64>,
+    pub frame: Option<u64>,
+}
+
+/// Accumulates the `key=value` lines ffmpeg writes with `-progress pipe:2`
+/// and emits an [`EncodeProgress`] at the end of each block, throttled to
+/// roughly every two seconds (the final block is always emitted).
+pub(crate) struct ProgressParser {
+    duration_secs: Option<f64>,
+    out_time_us: Option<i64>,
+    fps: Option<f64>,
+    bitrate: Option<String>,
+    speed: Option<String>,
+    total_size: Option<i64>,
+    frame: Option<u64>,
+    last_callback: Instant,
+}
+
+impl ProgressParser {
+    /// `duration_secs` is the source duration used to compute percentage;
+    /// without it no progress is reported.
+    pub(crate) fn new(duration_secs: Option<f64>) -> Self {
+        Self {
+            duration_secs,
+            out_time_us: None,
+            fps: None,
+            bitrate: None,
+            speed: None,
+            total_size: None,
+            frame: None,
+            last_callback: Instant::now(),
+        }
+    }
+
+    /// Feed one stderr line, calling `callback` when a progress block ends.
+    pub(crate) fn feed(&mut self, line: &str, callback: &mut impl FnMut(EncodeProgress)) {
+        if let Some(val) = line.strip_prefix("out_time_us=") {
+            self.out_time_us = val.trim().parse::<i64>().ok();
+        } else if let Some(val) = line.strip_prefix("fps=") {
+            self.fps = val.trim().parse::<f64>().ok();
+        } else if let Some(val) = line.strip_prefix("bitrate=") {
+            let v = val.trim();
+            if v != "N/A" {
+                self.bitrate = Some(v.to_string());
+            }
+        } else if let Some(val) = line.strip_prefix("speed=") {
+            let v = val.trim();
+            if v != "N/A" {
+                self.speed = Some(v.to_string());
+            }
+        } else if let Some(val) = line.strip_prefix("total_size=") {
+            self.total_size = val.trim().parse::<i64>().ok();
+        } else if let Some(val) = line.strip_prefix("frame=") {
+            self.frame = val.trim().parse::<u64>().ok();
+        } else if line.starts_with("progress=") {
+            // End of a progress block — emit callback.
+            let (Some(out_us), Some(dur)) = (self.out_time_us, self.duration_secs) else {
+                return;
+            };
+            if dur <= 0.0 {
+                return;
+            }
+            let elapsed_secs = out_us as f64 / 1_000_000.0;
+            let pct = (elapsed_secs / dur).clamp(0.0, 1.0);
+            let now = Instant::now();
+            if now.duration_since(self.last_callback) >= Duration::from_secs(2)
+                || line.contains("end")
+            {
+                callback(EncodeProgress {
+                    pct,
+                    fps: self.fps,
+                    bitrate: self.bitrate.clone(),
+                    speed: self.speed.clone(),
+                    total_size: self.total_size,
+                    frame: self.frame,
+                });
+                self.last_callback = now;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emits_final_block() {
+        let mut parser = ProgressParser::new(Some(10.0));
+        let mut seen = Vec::new();
+        let mut cb = |p: EncodeProgress| seen.push((p.pct, p.fps, p.speed));
+        for line in [
+            "frame=120",
+            "fps=48.5",
+            "out_time_us=5000000",
+            "speed=2.01x",
+            "progress=continue",
+            "out_time_us=10000000",
+            "progress=end",
+        ] {
+            parser.feed(line, &mut cb);
+        }
+        // The first block is throttled, the last one is always emitted.
+        assert_eq!(seen, vec![(1.0, Some(48.5), Some("2.01x".to_string()))]);
+    }
+
+    #[test]
+    fn no_progress_without_duration() {
+        let mut parser = ProgressParser::new(None);
+        let mut called = false;
+        parser.feed("out_time_us=1", &mut |_| called = true);
+        parser.feed("progress=end", &mut |_| called = true);
+        assert!(!called);
+    }
+}
@@ -10,6 +10,8 @@ use crate::workspace::Workspace;
 ///   back to `ffmpeg`.
 /// - For other targets, uses `ffmpeg -c copy`.
 ///
+/// Every video, audio and subtitle track is kept, in its original order.
+///
 /// The result is written to the workspace output path.
 pub async fn remux(
     workspace: &Workspace,
@@ -21,6 +23,9 @@ pub async fn remux(
     let ext = match target_container {
         sf_core::Container::Mkv => "mkv",
         sf_core::Container::Mp4 => "mp4",
+        sf_core::Container::Webm => "webm",
+        sf_core::Container::Avi => "avi",
+        sf_core::Container::MpegTs => "ts",
     };
 
     tracing::info!("remux {:?} -> {ext}", input);
@@ -47,14 +52,43 @@ pub async fn remux(
     let mut cmd = ToolCommand::new(ffmpeg.path.clone());
     cmd.args(["-y", "-i"]);
     cmd.arg(input.to_string_lossy().as_ref());
-    cmd.args(["-c", "copy"]);
+    cmd.args(ffmpeg_args(target_container));
+    cmd.arg(output.to_string_lossy().as_ref());
+    cmd.execute().await?;
+
+    Ok(())
+}
+
+/// ffmpeg options for a stream copy of every track. Without `-map 0` ffmpeg
+/// keeps only one stream of each type. Data streams are dropped, and
+/// attachments unless the target is Matroska, since other muxers reject them.
+fn ffmpeg_args(target_container: sf_core::Container) -> Vec<&'static str> {
+    let mut args = vec!["-map", "0", "-map", "-0:d"];
+    if target_container != sf_core::Container::Mkv {
+        args.extend(["-map", "-0:t"]);
+    }
+    args.extend(["-map_metadata", "0", "-map_chapters", "0", "-c", "copy"]);
 
     if target_container == sf_core::Container::Mp4 {
-        cmd.args(["-movflags", "+faststart"]);
+        args.extend(["-movflags", "+faststart"]);
     }
+    args
+}
 
-    cmd.arg(output.to_string_lossy().as_ref());
-    cmd.execute().await?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    Ok(())
+    #[test]
+    fn ffmpeg_keeps_every_track() {
+        let args = ffmpeg_args(sf_core::Container::Mkv).join(" ");
+        assert_eq!(
+            args,
+            "-map 0 -map -0:d -map_metadata 0 -map_chapters 0 -c copy"
+        );
+
+        let args = ffmpeg_args(sf_core::Container::Mp4).join(" ");
+        assert!(args.starts_with("-map 0 -map -0:d -map -0:t "));
+        assert!(args.ends_with("-c copy -movflags +faststart"));
+    }
 }
@@ -0,0 +1,189 @@
+//! Subtitle extraction and conversion via ffmpeg.
+
+use std::path::{Path, PathBuf};
+
+use sf_core::SubtitleFormat;
+
+use crate::command::ToolCommand;
+use crate::tools::ToolRegistry;
+use crate::workspace::Workspace;
+
+/// Whether a probed subtitle codec is text-based (and so can be converted to
+/// SRT/WebVTT). Bitmap formats such as PGS, VobSub and DVB need OCR.
+///
+/// Accepts the names reported by the built-in prober, ffprobe and MediaInfo.
+pub fn is_text_subtitle(codec: &str) -> bool {
+    is_ass_subtitle(codec)
+        || matches!(
+            codec.to_ascii_lowercase().as_str(),
+            "srt" | "subrip" | "utf-8" | "webvtt" | "mov_text" | "mp4 text" | "timed text" | "text"
+        )
+}
+
+/// Whether a probed subtitle codec is ASS/SSA.
+pub fn is_ass_subtitle(codec: &str) -> bool {
+    matches!(codec.to_ascii_lowercase().as_str(), "ass" | "ssa")
+}
+
+/// A subtitle track to extract by [`extract_subtitles`].
+#[derive(Debug, Clone)]
+pub struct SubtitleExtract {
+    /// 0-based index among the input's subtitle tracks.
+    pub track: usize,
+    /// Destination sidecar file.
+    pub path: PathBuf,
+}
+
+fn ffmpeg_subtitle_codec(format: SubtitleFormat) -> &'static str {
+    match format {
+        SubtitleFormat::Srt => "srt",
+        SubtitleFormat::Vtt => "webvtt",
+    }
+}
+
+/// Extract text subtitle tracks of `input` to sidecar files in `format`.
+///
+/// All tracks are written by a single ffmpeg invocation; existing sidecars
+/// are overwritten.
+pub async fn extract_subtitles(
+    tools: &ToolRegistry,
+    input: &Path,
+    tracks: &[SubtitleExtract],
+    format: SubtitleFormat,
+) -> sf_core::Result<()> {
+    if tracks.is_empty() {
+        return Ok(());
+    }
+    let ffmpeg = tools.require("ffmpeg")?;
+
+    tracing::info!(
+        "extract {} subtitle track(s) from {:?} as {format}",
+        tracks.len(),
+        input
+    );
+
+    let mut cmd = ToolCommand::new(ffmpeg.path.clone());
+    cmd.args(["-y", "-i"]);
+    cmd.arg(input.to_string_lossy().as_ref());
+    cmd.args(extract_args(tracks, format));
+    cmd.execute().await?;
+
+    Ok(())
+}
+
+fn extract_args(tracks: &[SubtitleExtract], format: SubtitleFormat) -> Vec<String> {
+    let codec = ffmpeg_subtitle_codec(format);
+    tracks
+        .iter()
+        .flat_map(|t| {
+            [
+                "-map".to_string(),
+                format!("0:s:{}", t.track),
+                "-c:s".to_string(),
+                codec.to_string(),
+                t.path.to_string_lossy().into_owned(),
+            ]
+        })
+        .collect()
+}
+
+/// Convert the given subtitle tracks (0-based subtitle indices, normally
+/// ASS/SSA) to SRT in place, copying every other stream.
+///
+/// ASS styling and positioning are dropped; font attachments are kept.
+pub async fn convert_subtitles_to_srt(
+    workspace: &Workspace,
+    tools: &ToolRegistry,
+    tracks: &[usize],
+) -> sf_core::Result<()> {
+    let input = workspace.input();
+    let output = workspace.output();
+    let ffmpeg = tools.require("ffmpeg")?;
+
+    tracing::info!("convert subtitle tracks {tracks:?} of {:?} to SRT", input);
+
+    let mut cmd = ToolCommand::new(ffmpeg.path.clone());
+    cmd.args(["-y", "-i"]);
+    cmd.arg(input.to_string_lossy().as_ref());
+    cmd.args(convert_args(tracks));
+    cmd.arg(output.to_string_lossy().as_ref());
+    cmd.execute().await?;
+
+    Ok(())
+}
+
+fn convert_args(tracks: &[usize]) -> Vec<String> {
+    let mut args: Vec<String> = ["-map", "0", "-map_metadata", "0", "-c", "copy"]
+        .map(String::from)
+        .to_vec();
+    for track in tracks {
+        args.push(format!("-c:s:{track}"));
+        args.push("srt".into());
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_subtitle_codecs() {
+        for codec in ["SRT", "SUBRIP", "ASS", "ssa", "WebVTT", "MOV_TEXT", "UTF-8"] {
+            assert!(is_text_subtitle(codec), "{codec}");
+        }
+        for codec in ["PGS", "VobSub", "DVB Subtitle", "HDMV_PGS_SUBTITLE"] {
+            assert!(!is_text_subtitle(codec), "{codec}");
+        }
+        assert!(is_ass_subtitle("ASS"));
+        assert!(!is_ass_subtitle("SRT"));
+    }
+
+    #[test]
+    fn extract_writes_one_output_per_track() {
+        let tracks = [
+            SubtitleExtract {
+                track: 0,
+                path: "/m/Movie.eng.vtt".into(),
+            },
+            SubtitleExtract {
+                track: 2,
+                path: "/m/Movie.fre.forced.vtt".into(),
+            },
+        ];
+        assert_eq!(
+            extract_args(&tracks, SubtitleFormat::Vtt),
+            [
+                "-map",
+                "0:s:0",
+                "-c:s",
+                "webvtt",
+                "/m/Movie.eng.vtt",
+                "-map",
+                "0:s:2",
+                "-c:s",
+                "webvtt",
+                "/m/Movie.fre.forced.vtt",
+            ]
+        );
+    }
+
+    #[test]
+    fn convert_only_touches_listed_tracks() {
+        assert_eq!(
+            convert_args(&[1, 3]),
+            [
+                "-map",
+                "0",
+                "-map_metadata",
+                "0",
+                "-c",
+                "copy",
+                "-c:s:1",
+                "srt",
+                "-c:s:3",
+                "srt"
+            ]
+        );
+    }
+}
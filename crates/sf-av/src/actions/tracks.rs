@@ -0,0 +1,310 @@
+//! Reorder audio/subtitle tracks and rewrite their default/forced flags
+//! without re-encoding.
+//!
+//! Matroska files are rewritten with mkvmerge, which keeps attachments and
+//! tags intact; everything else goes through an ffmpeg stream copy.
+
+use std::path::Path;
+
+use crate::command::ToolCommand;
+use crate::tools::ToolRegistry;
+use crate::workspace::Workspace;
+
+/// Output position and flags of one audio or subtitle track.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrackSpec {
+    /// 0-based index among the source's tracks of the same type.
+    pub index: usize,
+    pub default: bool,
+    pub forced: bool,
+}
+
+/// The audio and subtitle layout written by [`edit_tracks`].
+///
+/// `audio` and `subtitles` must list **every** track of that type in the
+/// source, in the desired output order.
+#[derive(Debug, Clone, Default)]
+pub struct TrackLayout {
+    /// Number of video tracks in the source (copied unchanged, first).
+    pub video_tracks: usize,
+    pub audio: Vec<TrackSpec>,
+    pub subtitles: Vec<TrackSpec>,
+}
+
+/// Rewrite the workspace input with the given track order and flags.
+pub async fn edit_tracks(
+    workspace: &Workspace,
+    tools: &ToolRegistry,
+    layout: &TrackLayout,
+) -> sf_core::Result<()> {
+    let input = workspace.input();
+    let output = workspace.output();
+
+    tracing::info!(
+        "rewrite track order/flags of {:?} ({} audio, {} subtitle)",
+        input,
+        layout.audio.len(),
+        layout.subtitles.len()
+    );
+
+    if is_matroska(&output) {
+        let mkvmerge = tools.require("mkvmerge")?;
+        let mut identify = ToolCommand::new(mkvmerge.path.clone());
+        identify.arg("-J");
+        identify.arg(input.to_string_lossy().as_ref());
+        let ids = parse_track_ids(&identify.execute().await?.stdout)?;
+        if ids.audio.len() != layout.audio.len() || ids.subtitles.len() != layout.subtitles.len() {
+            return Err(sf_core::Error::tool(
+                "mkvmerge",
+                format!(
+                    "found {} audio and {} subtitle tracks, expected {} and {}",
+                    ids.audio.len(),
+                    ids.subtitles.len(),
+                    layout.audio.len(),
+                    layout.subtitles.len()
+                ),
+            ));
+        }
+
+        let mut cmd = ToolCommand::new(mkvmerge.path.clone());
+        cmd.arg("-o");
+        cmd.arg(output.to_string_lossy().as_ref());
+        cmd.args(mkvmerge_args(layout, &ids));
+        cmd.arg(input.to_string_lossy().as_ref());
+        cmd.execute().await?;
+    } else {
+        let ffmpeg = tools.require("ffmpeg")?;
+        let mut cmd = ToolCommand::new(ffmpeg.path.clone());
+        cmd.args(["-y", "-i"]);
+        cmd.arg(input.to_string_lossy().as_ref());
+        cmd.args(ffmpeg_args(layout));
+        cmd.arg(output.to_string_lossy().as_ref());
+        cmd.execute().await?;
+    }
+
+    Ok(())
+}
+
+fn is_matroska(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()).is_some_and(|e| {
+        matches!(
+            e.to_ascii_lowercase().as_str(),
+            "mkv" | "mka" | "mks" | "webm"
+        )
+    })
+}
+
+/// mkvmerge track IDs of a file by track type, in file order.
+#[derive(Debug, Default, PartialEq)]
+struct TrackIds {
+    video: Vec<u64>,
+    audio: Vec<u64>,
+    subtitles: Vec<u64>,
+}
+
+/// Read the track IDs from `mkvmerge -J` output. IDs follow the order of the
+/// tracks in the file, which need not be video, then audio, then subtitles.
+fn parse_track_ids(json: &str) -> sf_core::Result<TrackIds> {
+    let json: serde_json::Value = serde_json::from_str(json).map_err(|e| {
+        sf_core::Error::tool("mkvmerge", format!("invalid identification output: {e}"))
+    })?;
+
+    let tracks = json["tracks"].as_array().map(Vec::as_slice);
+    let mut ids = TrackIds::default();
+    for track in tracks.unwrap_or_default() {
+        let Some(id) = track["id"].as_u64() else {
+            continue;
+        };
+        match track["type"].as_str() {
+            Some("video") => ids.video.push(id),
+            Some("audio") => ids.audio.push(id),
+            Some("subtitles") => ids.subtitles.push(id),
+            _ => {}
+        }
+    }
+    Ok(ids)
+}
+
+/// mkvmerge options for the (single) input file, whose tracks have `ids`.
+/// `ids` must have an entry for every audio and subtitle track in `layout`.
+fn mkvmerge_args(layout: &TrackLayout, ids: &TrackIds) -> Vec<String> {
+    let audio = |t: &TrackSpec| ids.audio[t.index];
+    let subtitle = |t: &TrackSpec| ids.subtitles[t.index];
+
+    let order = ids
+        .video
+        .iter()
+        .copied()
+        .chain(layout.audio.iter().map(audio))
+        .chain(layout.subtitles.iter().map(subtitle))
+        .map(|id| format!("0:{id}"))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let mut args = vec!["--track-order".to_string(), order];
+    let flags = layout
+        .audio
+        .iter()
+        .map(|t| (audio(t), t))
+        .chain(layout.subtitles.iter().map(|t| (subtitle(t), t)));
+    for (id, spec) in flags {
+        args.push("--default-track-flag".into());
+        args.push(format!("{id}:{}", u8::from(spec.default)));
+        args.push("--forced-display-flag".into());
+        args.push(format!("{id}:{}", u8::from(spec.forced)));
+    }
+    args
+}
+
+/// ffmpeg mapping and disposition options for a stream copy.
+fn ffmpeg_args(layout: &TrackLayout) -> Vec<String> {
+    let mut args: Vec<String> = vec!["-map".into(), "0:v?".into()];
+    for spec in &layout.audio {
+        args.extend(["-map".into(), format!("0:a:{}", spec.index)]);
+    }
+    for spec in &layout.subtitles {
+        args.extend(["-map".into(), format!("0:s:{}", spec.index)]);
+    }
+    args.extend(
+        [
+            "-map",
+            "0:t?",
+            "-map_metadata",
+            "0",
+            "-map_chapters",
+            "0",
+            "-c",
+            "copy",
+        ]
+        .map(String::from),
+    );
+
+    for (kind, specs) in [("a", &layout.audio), ("s", &layout.subtitles)] {
+        for (n, spec) in specs.iter().enumerate() {
+            args.push(format!("-disposition:{kind}:{n}"));
+            args.push(disposition(spec));
+        }
+    }
+    args
+}
+
+fn disposition(spec: &TrackSpec) -> String {
+    match (spec.default, spec.forced) {
+        (true, true) => "default+forced",
+        (true, false) => "default",
+        (false, true) => "forced",
+        (false, false) => "0",
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(index: usize, default: bool, forced: bool) -> TrackSpec {
+        TrackSpec {
+            index,
+            default,
+            forced,
+        }
+    }
+
+    fn layout() -> TrackLayout {
+        TrackLayout {
+            video_tracks: 1,
+            audio: vec![spec(1, true, false), spec(0, false, false)],
+            subtitles: vec![spec(1, false, true), spec(0, false, false)],
+        }
+    }
+
+    fn ids(video: &[u64], audio: &[u64], subtitles: &[u64]) -> TrackIds {
+        TrackIds {
+            video: video.to_vec(),
+            audio: audio.to_vec(),
+            subtitles: subtitles.to_vec(),
+        }
+    }
+
+    #[test]
+    fn mkvmerge_reorders_by_track_id() {
+        let args = mkvmerge_args(&layout(), &ids(&[0], &[1, 2], &[3, 4]));
+        assert_eq!(args[..2], ["--track-order", "0:0,0:2,0:1,0:4,0:3"]);
+        assert_eq!(
+            args[2..],
+            [
+                "--default-track-flag",
+                "2:1",
+                "--forced-display-flag",
+                "2:0",
+                "--default-track-flag",
+                "1:0",
+                "--forced-display-flag",
+                "1:0",
+                "--default-track-flag",
+                "4:0",
+                "--forced-display-flag",
+                "4:1",
+                "--default-track-flag",
+                "3:0",
+                "--forced-display-flag",
+                "3:0",
+            ]
+        );
+    }
+
+    #[test]
+    fn mkvmerge_uses_identified_ids_for_audio_first_files() {
+        let json = r#"{"tracks": [
+            {"id": 0, "type": "audio"},
+            {"id": 1, "type": "video"},
+            {"id": 2, "type": "subtitles"},
+            {"id": 3, "type": "audio"},
+            {"id": 4, "type": "subtitles"}
+        ]}"#;
+        let ids = parse_track_ids(json).unwrap();
+        assert_eq!(ids, self::ids(&[1], &[0, 3], &[2, 4]));
+
+        let args = mkvmerge_args(&layout(), &ids);
+        assert_eq!(args[..2], ["--track-order", "0:1,0:3,0:0,0:4,0:2"]);
+        assert_eq!(args[2..4], ["--default-track-flag", "3:1"]);
+        assert_eq!(
+            args[10..14].join(" "),
+            "--default-track-flag 4:0 --forced-display-flag 4:1"
+        );
+    }
+
+    #[test]
+    fn default_audio_flag_follows_identified_ids() {
+        // What SetTrackDefaults writes: the second audio track becomes the
+        // default, order unchanged, in a file whose video comes last.
+        let layout = TrackLayout {
+            video_tracks: 1,
+            audio: vec![spec(0, false, false), spec(1, true, false)],
+            subtitles: vec![],
+        };
+        let args = mkvmerge_args(&layout, &ids(&[2], &[0, 1], &[]));
+        assert_eq!(
+            args.join(" "),
+            "--track-order 0:2,0:0,0:1 \
+             --default-track-flag 0:0 --forced-display-flag 0:0 \
+             --default-track-flag 1:1 --forced-display-flag 1:0"
+        );
+    }
+
+    #[test]
+    fn ffmpeg_maps_in_order_with_dispositions() {
+        let args = ffmpeg_args(&layout()).join(" ");
+        assert!(args.starts_with("-map 0:v? -map 0:a:1 -map 0:a:0 -map 0:s:1 -map 0:s:0 "));
+        assert!(args.ends_with(
+            "-c copy -disposition:a:0 default -disposition:a:1 0 \
+             -disposition:s:0 forced -disposition:s:1 0"
+        ));
+    }
+
+    #[test]
+    fn matroska_detection() {
+        assert!(is_matroska(Path::new("/tmp/a.MKV")));
+        assert!(!is_matroska(Path::new("/tmp/a.mp4")));
+    }
+}
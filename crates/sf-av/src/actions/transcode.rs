@@ -0,0 +1,505 @@
+//! HEVC / AV1 re-encoding using ffmpeg (libx265 / libsvtav1).
+//!
+//! Audio, subtitles, chapters and metadata are copied unchanged. HDR10 static
+//! metadata (colour description, mastering display, content light level) is
+//! read from the source with ffprobe and passed to the encoder.
+
+use std::path::Path;
+use std::time::Duration;
+
+use sf_core::VideoCodec;
+use tokio_util::sync::CancellationToken;
+
+use super::progress::{EncodeProgress, ProgressParser};
+use crate::command::ToolCommand;
+use crate::tools::ToolRegistry;
+
+/// Encoder settings for [`transcode`].
+#[derive(Debug, Clone)]
+pub struct TranscodeOptions {
+    /// Target codec: [`VideoCodec::H265`] (libx265) or [`VideoCodec::Av1`]
+    /// (libsvtav1).
+    pub codec: VideoCodec,
+    /// Constant rate factor (None = 20 for x265, 30 for SVT-AV1).
+    pub crf: Option<u32>,
+    /// Encoder preset (None = `slow` for x265, `6` for SVT-AV1).
+    pub preset: Option<String>,
+    /// Encoder tune (e.g. `grain` for x265, `0` for SVT-AV1 visual quality).
+    pub tune: Option<String>,
+    /// Output bit depth, 8 or 10.
+    pub bit_depth: u8,
+}
+
+/// SMPTE ST 2086 mastering display colour volume.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MasteringDisplay {
+    /// CIE 1931 (x, y) chromaticity of the red, green and blue primaries.
+    pub red: (f64, f64),
+    pub green: (f64, f64),
+    pub blue: (f64, f64),
+    /// CIE 1931 (x, y) chromaticity of the white point.
+    pub white_point: (f64, f64),
+    /// Luminance in cd/m².
+    pub max_luminance: f64,
+    pub min_luminance: f64,
+}
+
+impl MasteringDisplay {
+    /// The x265 `master-display` value: chromaticity in units of 0.00002 and
+    /// luminance in units of 0.0001 cd/m².
+    fn x265_param(&self) -> String {
+        let c =
+            |(x, y): (f64, f64)| format!("{},{}", (x * 50_000.0).round(), (y * 50_000.0).round());
+        format!(
+            "G({})B({})R({})WP({})L({},{})",
+            c(self.green),
+            c(self.blue),
+            c(self.red),
+            c(self.white_point),
+            (self.max_luminance * 10_000.0).round(),
+            (self.min_luminance * 10_000.0).round(),
+        )
+    }
+
+    /// The SVT-AV1 `mastering-display` value, in plain units.
+    fn svtav1_param(&self) -> String {
+        let c = |(x, y): (f64, f64)| format!("{x:.4},{y:.4}");
+        format!(
+            "G({})B({})R({})WP({})L({},{})",
+            c(self.green),
+            c(self.blue),
+            c(self.red),
+            c(self.white_point),
+            self.max_luminance,
+            self.min_luminance,
+        )
+    }
+}
+
+/// HDR-related stream properties to carry over to the encode.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HdrMetadata {
+    /// ffmpeg colour names, e.g. `bt2020`, `smpte2084`, `bt2020nc`.
+    pub color_primaries: Option<String>,
+    pub color_transfer: Option<String>,
+    pub color_space: Option<String>,
+    pub mastering_display: Option<MasteringDisplay>,
+    /// Maximum content / frame-average light level in cd/m².
+    pub content_light: Option<(u32, u32)>,
+}
+
+impl HdrMetadata {
+    /// Whether the source uses an HDR transfer function (PQ or HLG).
+    pub fn is_hdr(&self) -> bool {
+        matches!(
+            self.color_transfer.as_deref(),
+            Some("smpte2084") | Some("arib-std-b67")
+        )
+    }
+}
+
+/// Read colour description and HDR10 side data from the first video frame.
+pub async fn probe_hdr_metadata(
+    tools: &ToolRegistry,
+    input: &Path,
+) -> sf_core::Result<HdrMetadata> {
+    let ffprobe = tools.require("ffprobe")?;
+    let mut cmd = ToolCommand::new(ffprobe.path.clone());
+    cmd.timeout(Duration::from_secs(60));
+    cmd.args(["-v", "error", "-select_streams", "v:0"]);
+    cmd.args([
+        "-show_entries",
+        "stream=color_primaries,color_transfer,color_space:frame=side_data_list",
+    ]);
+    cmd.args(["-read_intervals", "%+#1", "-of", "json"]);
+    cmd.arg(input.to_string_lossy().as_ref());
+    let output = cmd.execute().await?;
+
+    let json: serde_json::Value = serde_json::from_str(&output.stdout)
+        .map_err(|e| sf_core::Error::tool("ffprobe", format!("invalid JSON output: {e}")))?;
+    Ok(parse_hdr_metadata(&json))
+}
+
+/// Extract [`HdrMetadata`] from ffprobe's JSON output.
+fn parse_hdr_metadata(json: &serde_json::Value) -> HdrMetadata {
+    let stream = &json["streams"][0];
+    let color = |key: &str| {
+        stream[key]
+            .as_str()
+            .filter(|v| *v != "unknown")
+            .map(str::to_string)
+    };
+
+    let mut hdr = HdrMetadata {
+        color_primaries: color("color_primaries"),
+        color_transfer: color("color_transfer"),
+        color_space: color("color_space"),
+        ..Default::default()
+    };
+
+    let side_data = json["frames"][0]["side_data_list"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    for data in side_data {
+        match data["side_data_type"].as_str() {
+            Some("Mastering display metadata") => {
+                let v = |key: &str| rational(&data[key]);
+                let xy =
+                    |prefix: &str| Some((v(&format!("{prefix}_x"))?, v(&format!("{prefix}_y"))?));
+                hdr.mastering_display = (|| {
+                    Some(MasteringDisplay {
+                        red: xy("red")?,
+                        green: xy("green")?,
+                        blue: xy("blue")?,
+                        white_point: xy("white_point")?,
+                        max_luminance: v("max_luminance")?,
+                        min_luminance: v("min_luminance")?,
+                    })
+                })();
+            }
+            Some("Content light level metadata") => {
+                let v = |key: &str| data[key].as_u64().map(|n| n as u32);
+                if let (Some(max_cll), Some(max_fall)) = (v("max_content"), v("max_average")) {
+                    hdr.content_light = Some((max_cll, max_fall));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    hdr
+}
+
+/// Parse an ffprobe rational (`"34000/50000"`) or plain number.
+fn rational(value: &serde_json::Value) -> Option<f64> {
+    if let Some(n) = value.as_f64() {
+        return Some(n);
+    }
+    let s = value.as_str()?;
+    match s.split_once('/') {
+        Some((num, den)) => {
+            let den: f64 = den.parse().ok()?;
+            (den != 0.0).then_some(num.parse::<f64>().ok()? / den)
+        }
+        None => s.parse().ok(),
+    }
+}
+
+/// Build the video encoder arguments for `opts`, passing through `hdr`.
+fn video_args(
+    opts: &TranscodeOptions,
+    hdr: &HdrMetadata,
+    output: &Path,
+) -> sf_core::Result<Vec<String>> {
+    let pix_fmt = match opts.bit_depth {
+        8 => "yuv420p",
+        10 => "yuv420p10le",
+        other => {
+            return Err(sf_core::Error::Validation(format!(
+                "unsupported transcode bit depth {other} (expected 8 or 10)"
+            )))
+        }
+    };
+
+    let mut args: Vec<String> = Vec::new();
+    let mut push = |items: &[&str]| args.extend(items.iter().map(|s| s.to_string()));
+
+    match opts.codec {
+        VideoCodec::H265 => {
+            let crf = opts.crf.unwrap_or(20).to_string();
+            let preset = opts.preset.as_deref().unwrap_or("slow");
+            let profile = if opts.bit_depth == 10 {
+                "main10"
+            } else {
+                "main"
+            };
+            push(&["-c:v", "libx265", "-crf", &crf, "-preset", preset]);
+            if let Some(ref tune) = opts.tune {
+                push(&["-tune", tune]);
+            }
+            push(&["-pix_fmt", pix_fmt, "-profile:v", profile]);
+
+            let mut params = Vec::new();
+            if hdr.is_hdr() {
+                params.push("hdr-opt=1".to_string());
+                params.push("repeat-headers=1".to_string());
+            }
+            if let Some(ref v) = hdr.color_primaries {
+                params.push(format!("colorprim={v}"));
+            }
+            if let Some(ref v) = hdr.color_transfer {
+                params.push(format!("transfer={v}"));
+            }
+            if let Some(ref v) = hdr.color_space {
+                params.push(format!("colormatrix={v}"));
+            }
+            if let Some(ref md) = hdr.mastering_display {
+                params.push(format!("master-display={}", md.x265_param()));
+            }
+            if let Some((cll, fall)) = hdr.content_light {
+                params.push(format!("max-cll={cll},{fall}"));
+            }
+            if !params.is_empty() {
+                push(&["-x265-params", &params.join(":")]);
+            }
+
+            // Apple players need the hvc1 tag to recognise HEVC in MP4.
+            if is_mp4(output) {
+                push(&["-tag:v", "hvc1"]);
+            }
+        }
+        VideoCodec::Av1 => {
+            let crf = opts.crf.unwrap_or(30).to_string();
+            let preset = opts.preset.as_deref().unwrap_or("6");
+            push(&["-c:v", "libsvtav1", "-crf", &crf, "-preset", preset]);
+            push(&["-pix_fmt", pix_fmt]);
+
+            let mut params = Vec::new();
+            if let Some(ref tune) = opts.tune {
+                params.push(format!("tune={tune}"));
+            }
+            if hdr.is_hdr() {
+                params.push("enable-hdr=1".to_string());
+            }
+            if let Some(ref md) = hdr.mastering_display {
+                params.push(format!("mastering-display={}", md.svtav1_param()));
+            }
+            if let Some((cll, fall)) = hdr.content_light {
+                params.push(format!("content-light={cll},{fall}"));
+            }
+            if !params.is_empty() {
+                push(&["-svtav1-params", &params.join(":")]);
+            }
+        }
+        other => {
+            return Err(sf_core::Error::Validation(format!(
+                "transcode to {other} is not supported (expected h265 or av1)"
+            )))
+        }
+    }
+
+    // Colour description on the output stream, for both encoders.
+    if let Some(ref v) = hdr.color_primaries {
+        push(&["-color_primaries", v]);
+    }
+    if let Some(ref v) = hdr.color_transfer {
+        push(&["-color_trc", v]);
+    }
+    if let Some(ref v) = hdr.color_space {
+        push(&["-colorspace", v]);
+    }
+
+    Ok(args)
+}
+
+/// Whether `path` names an MP4-family file (`.mp4`, `.m4v` or `.mov`).
+pub(super) fn is_mp4(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| matches!(e.to_ascii_lowercase().as_str(), "mp4" | "m4v" | "mov"))
+}
+
+/// Re-encode the first video stream of `input` to HEVC or AV1.
+///
+/// All audio and subtitle streams, chapters and metadata are copied. `hdr` is
+/// the source's HDR metadata (see [`probe_hdr_metadata`]); pass
+/// `HdrMetadata::default()` for SDR sources.
+///
+/// Streams progress like [`convert_to_profile_b_with_progress`] and supports
+/// cancellation. 24-hour timeout to handle very large files.
+///
+/// [`convert_to_profile_b_with_progress`]: super::convert_to_profile_b_with_progress
+#[allow(clippy::too_many_arguments)]
+pub async fn transcode(
+    tools: &ToolRegistry,
+    input: &Path,
+    output: &Path,
+    opts: &TranscodeOptions,
+    hdr: &HdrMetadata,
+    duration_secs: Option<f64>,
+    mut progress_callback: impl FnMut(EncodeProgress),
+    cancel: Option<CancellationToken>,
+) -> sf_core::Result<()> {
+    let ffmpeg = tools.require("ffmpeg")?;
+    let video_args = video_args(opts, hdr, output)?;
+
+    tracing::info!(
+        "Transcode: {:?} -> {:?} (codec={}, crf={:?}, preset={:?}, bit_depth={}, hdr={})",
+        input,
+        output,
+        opts.codec,
+        opts.crf,
+        opts.preset,
+        opts.bit_depth,
+        hdr.is_hdr(),
+    );
+
+    let mut cmd = ToolCommand::new(ffmpeg.path.clone());
+    cmd.timeout(Duration::from_secs(86400));
+    cmd.args(["-y", "-progress", "pipe:2", "-nostats", "-i"]);
+    cmd.arg(input.to_string_lossy().as_ref());
+    cmd.args(["-map", "0:v:0", "-map", "0:a?", "-map", "0:s?"]);
+    cmd.args(["-map_metadata", "0", "-map_chapters", "0"]);
+    cmd.args(video_args);
+    cmd.args(["-c:a", "copy", "-c:s", "copy"]);
+    if is_mp4(output) {
+        cmd.args(["-movflags", "+faststart"]);
+    }
+    cmd.arg(output.to_string_lossy().as_ref());
+
+    let mut parser = ProgressParser::new(duration_secs);
+    cmd.execute_with_stderr_callback(|line| parser.feed(line, &mut progress_callback), cancel)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn opts(codec: VideoCodec) -> TranscodeOptions {
+        TranscodeOptions {
+            codec,
+            crf: None,
+            preset: None,
+            tune: None,
+            bit_depth: 10,
+        }
+    }
+
+    fn hdr10_probe() -> serde_json::Value {
+        serde_json::json!({
+            "frames": [{
+                "side_data_list": [
+                    {
+                        "side_data_type": "Mastering display metadata",
+                        "red_x": "34000/50000", "red_y": "16000/50000",
+                        "green_x": "13250/50000", "green_y": "34500/50000",
+                        "blue_x": "7500/50000", "blue_y": "3000/50000",
+                        "white_point_x": "15635/50000", "white_point_y": "16450/50000",
+                        "min_luminance": "50/10000", "max_luminance": "10000000/10000"
+                    },
+                    {
+                        "side_data_type": "Content light level metadata",
+                        "max_content": 1000, "max_average": 400
+                    }
+                ]
+            }],
+            "streams": [{
+                "color_primaries": "bt2020",
+                "color_transfer": "smpte2084",
+                "color_space": "bt2020nc"
+            }]
+        })
+    }
+
+    fn arg_after<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+        let i = args.iter().position(|a| a == flag)?;
+        args.get(i + 1).map(String::as_str)
+    }
+
+    #[test]
+    fn parses_hdr10_side_data() {
+        let hdr = parse_hdr_metadata(&hdr10_probe());
+        assert!(hdr.is_hdr());
+        assert_eq!(hdr.color_space.as_deref(), Some("bt2020nc"));
+        assert_eq!(hdr.content_light, Some((1000, 400)));
+        let md = hdr.mastering_display.unwrap();
+        assert_eq!(md.red, (0.68, 0.32));
+        assert_eq!(md.max_luminance, 1000.0);
+        assert_eq!(md.min_luminance, 0.005);
+    }
+
+    #[test]
+    fn parses_sdr_without_side_data() {
+        let hdr = parse_hdr_metadata(&serde_json::json!({
+            "frames": [{}],
+            "streams": [{ "color_primaries": "unknown", "color_transfer": "bt709" }]
+        }));
+        assert!(!hdr.is_hdr());
+        assert_eq!(hdr.color_primaries, None);
+        assert_eq!(hdr.mastering_display, None);
+    }
+
+    #[test]
+    fn x265_args_pass_through_hdr10() {
+        let hdr = parse_hdr_metadata(&hdr10_probe());
+        let args = video_args(&opts(VideoCodec::H265), &hdr, &PathBuf::from("out.mkv")).unwrap();
+
+        assert_eq!(arg_after(&args, "-c:v"), Some("libx265"));
+        assert_eq!(arg_after(&args, "-crf"), Some("20"));
+        assert_eq!(arg_after(&args, "-preset"), Some("slow"));
+        assert_eq!(arg_after(&args, "-pix_fmt"), Some("yuv420p10le"));
+        assert_eq!(arg_after(&args, "-profile:v"), Some("main10"));
+        assert_eq!(
+            arg_after(&args, "-x265-params"),
+            Some(
+                "hdr-opt=1:repeat-headers=1:colorprim=bt2020:transfer=smpte2084:\
+                 colormatrix=bt2020nc:\
+                 master-display=G(13250,34500)B(7500,3000)R(34000,16000)WP(15635,16450)L(10000000,50):\
+                 max-cll=1000,400"
+            )
+        );
+        assert_eq!(arg_after(&args, "-color_trc"), Some("smpte2084"));
+        // No hvc1 tag outside MP4.
+        assert!(!args.iter().any(|a| a == "-tag:v"));
+    }
+
+    #[test]
+    fn x265_args_for_sdr_mp4() {
+        let mut o = opts(VideoCodec::H265);
+        o.crf = Some(18);
+        o.tune = Some("grain".into());
+        o.bit_depth = 8;
+        let args = video_args(&o, &HdrMetadata::default(), &PathBuf::from("out.MP4")).unwrap();
+
+        assert_eq!(arg_after(&args, "-crf"), Some("18"));
+        assert_eq!(arg_after(&args, "-tune"), Some("grain"));
+        assert_eq!(arg_after(&args, "-pix_fmt"), Some("yuv420p"));
+        assert_eq!(arg_after(&args, "-profile:v"), Some("main"));
+        assert_eq!(arg_after(&args, "-tag:v"), Some("hvc1"));
+        assert!(!args.iter().any(|a| a == "-x265-params"));
+    }
+
+    #[test]
+    fn svtav1_args_pass_through_hdr10() {
+        let hdr = parse_hdr_metadata(&hdr10_probe());
+        let mut o = opts(VideoCodec::Av1);
+        o.tune = Some("0".into());
+        let args = video_args(&o, &hdr, &PathBuf::from("out.mkv")).unwrap();
+
+        assert_eq!(arg_after(&args, "-c:v"), Some("libsvtav1"));
+        assert_eq!(arg_after(&args, "-crf"), Some("30"));
+        assert_eq!(arg_after(&args, "-preset"), Some("6"));
+        assert_eq!(
+            arg_after(&args, "-svtav1-params"),
+            Some(
+                "tune=0:enable-hdr=1:\
+                 mastering-display=G(0.2650,0.6900)B(0.1500,0.0600)R(0.6800,0.3200)WP(0.3127,0.3290)L(1000,0.005):\
+                 content-light=1000,400"
+            )
+        );
+    }
+
+    #[test]
+    fn rejects_unsupported_codec_and_bit_depth() {
+        let hdr = HdrMetadata::default();
+        let out = PathBuf::from("out.mkv");
+        assert!(video_args(&opts(VideoCodec::H264), &hdr, &out).is_err());
+
+        let mut o = opts(VideoCodec::H265);
+        o.bit_depth = 12;
+        assert!(video_args(&o, &hdr, &out).is_err());
+    }
+
+    #[test]
+    fn parses_rationals() {
+        assert_eq!(rational(&serde_json::json!("1/4")), Some(0.25));
+        assert_eq!(rational(&serde_json::json!("1/0")), None);
+        assert_eq!(rational(&serde_json::json!("0.5")), Some(0.5));
+        assert_eq!(rational(&serde_json::json!(2)), Some(2.0));
+    }
+}
@@ -0,0 +1,33 @@
+//! Full decode check of a media file using ffmpeg.
+
+use std::path::Path;
+use std::time::Duration;
+
+use crate::command::ToolCommand;
+use crate::tools::ToolRegistry;
+
+/// Decode every stream of `input` and discard the result, failing on the
+/// first decoding error.
+///
+/// This catches truncated or corrupt streams that still probe fine. 24-hour
+/// timeout to handle very large files.
+pub async fn decode_check(tools: &ToolRegistry, input: &Path) -> sf_core::Result<()> {
+    let ffmpeg = tools.require("ffmpeg")?;
+
+    let mut cmd = ToolCommand::new(ffmpeg.path.clone());
+    cmd.timeout(Duration::from_secs(86400));
+    cmd.args(["-v", "error", "-xerror", "-i"]);
+    cmd.arg(input.to_string_lossy().as_ref());
+    cmd.args(["-map", "0:v?", "-map", "0:a?", "-f", "null", "-"]);
+    let output = cmd.execute().await?;
+
+    // With -xerror most errors end the run, but some are only reported.
+    let errors = output.stderr.trim();
+    if !errors.is_empty() {
+        return Err(sf_core::Error::tool(
+            "ffmpeg",
+            format!("decoding {} reported errors: {errors}", input.display()),
+        ));
+    }
+    Ok(())
+}
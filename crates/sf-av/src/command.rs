@@ -1,15 +1,57 @@
 //! Builder for executing external tool commands with timeout support.
+//!
+//! Every run can be observed with [`observe_tool_runs`], e.g. to keep a
+//! per-job log of the exact command lines and their stderr, and is killed or
+//! paused through the task's [`ProcessControl`](crate::ProcessControl).
 
+use std::future::Future;
 use std::path::PathBuf;
 use std::process::ExitStatus;
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use tokio::process::Command;
+use tokio::process::{Child, Command};
 use tokio_util::sync::CancellationToken;
 
+use crate::control::ChildControl;
+
 /// Default command timeout: 5 minutes.
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
 
+/// Maximum stderr kept in a [`ToolRun`] (the tail is kept).
+const STDERR_TAIL_BYTES: usize = 8 * 1024;
+
+/// A finished (or failed to start) tool invocation, as reported to
+/// [`observe_tool_runs`] observers.
+#[derive(Debug, Clone)]
+pub struct ToolRun {
+    /// The command line, with arguments quoted as needed.
+    pub command: String,
+    /// Exit code, if the process ran to completion.
+    pub exit_code: Option<i32>,
+    /// The last [`STDERR_TAIL_BYTES`] of stderr.
+    pub stderr: String,
+    /// Wall-clock time from spawn to exit.
+    pub duration: Duration,
+    /// Why the run failed (spawn error, timeout, non-zero exit), if it did.
+    pub error: Option<String>,
+}
+
+/// Callback receiving every [`ToolRun`] inside [`observe_tool_runs`].
+pub type ToolRunObserver = Arc<dyn Fn(ToolRun) + Send + Sync>;
+
+tokio::task_local! {
+    static TOOL_RUN_OBSERVER: ToolRunObserver;
+}
+
+/// Run `fut`, reporting every [`ToolCommand`] it executes to `observer`.
+///
+/// The observer is task-local: commands run on tasks spawned by `fut` are
+/// not reported.
+pub async fn observe_tool_runs<F: Future>(observer: ToolRunObserver, fut: F) -> F::Output {
+    TOOL_RUN_OBSERVER.scope(observer, fut).await
+}
+
 /// Output captured from a tool execution.
 #[derive(Debug, Clone)]
 pub struct ToolOutput {
@@ -48,6 +90,7 @@ pub struct ToolCommand {
     args: Vec<String>,
     timeout: Duration,
     stdin_data: Option<Vec<u8>>,
+    current_dir: Option<PathBuf>,
 }
 
 impl ToolCommand {
@@ -58,6 +101,7 @@ impl ToolCommand {
             args: Vec::new(),
             timeout: DEFAULT_TIMEOUT,
             stdin_data: None,
+            current_dir: None,
         }
     }
 
@@ -85,6 +129,12 @@ impl ToolCommand {
         self
     }
 
+    /// Run the process in `dir` instead of the current directory.
+    pub fn current_dir(&mut self, dir: impl Into<PathBuf>) -> &mut Self {
+        self.current_dir = Some(dir.into());
+        self
+    }
+
     /// Execute the command, streaming stderr lines to a callback.
     ///
     /// This is designed for long-running processes (like ffmpeg) where you want
@@ -94,20 +144,27 @@ impl ToolCommand {
     ///
     /// Returns the full stdout and the collected stderr on success.
     pub async fn execute_with_stderr_callback(
+        &self,
+        on_stderr: impl FnMut(&str),
+        cancel: Option<CancellationToken>,
+    ) -> sf_core::Result<ToolOutput> {
+        let started = Instant::now();
+        let result = self.run_streaming(on_stderr, cancel).await;
+        self.finish(started, result)
+    }
+
+    /// Like [`execute_with_stderr_callback`](Self::execute_with_stderr_callback),
+    /// but a non-zero exit is returned as output rather than an error.
+    async fn run_streaming(
         &self,
         mut on_stderr: impl FnMut(&str),
         cancel: Option<CancellationToken>,
     ) -> sf_core::Result<ToolOutput> {
         use tokio::io::{AsyncBufReadExt, BufReader};
 
-        let program_name = self
-            .program
-            .file_name()
-            .map(|n| n.to_string_lossy().to_string())
-            .unwrap_or_else(|| self.program.to_string_lossy().to_string());
+        let program_name = self.program_name();
 
-        let mut cmd = Command::new(&self.program);
-        cmd.args(&self.args);
+        let mut cmd = self.command();
         cmd.stdout(std::process::Stdio::piped());
         cmd.stderr(std::process::Stdio::piped());
 
@@ -119,16 +176,18 @@ impl ToolCommand {
         let stderr_pipe = child.stderr.take().expect("stderr piped");
         let mut stderr_reader = BufReader::new(stderr_pipe).lines();
         let mut stderr_buf = String::new();
+        let mut control = ChildControl::current(child.id(), cancel);
 
         let cancelled = loop {
-            let line = if let Some(ref token) = cancel {
-                tokio::select! {
-                    biased;
-                    _ = token.cancelled() => break true,
-                    line = stderr_reader.next_line() => line,
+            let line = tokio::select! {
+                biased;
+                cancelled = control.changed() => {
+                    if cancelled {
+                        break true;
+                    }
+                    continue;
                 }
-            } else {
-                stderr_reader.next_line().await
+                line = stderr_reader.next_line() => line,
             };
 
             match line {
@@ -147,18 +206,17 @@ impl ToolCommand {
 
         if cancelled {
             let _ = child.kill().await;
-            return Err(sf_core::Error::Tool {
-                tool: program_name,
-                message: "cancelled".into(),
-            });
+            return Err(cancelled_error(program_name));
         }
 
         // Wait for process exit.
         let status = tokio::time::timeout(Duration::from_secs(30), child.wait())
             .await
-            .map_err(|_| sf_core::Error::Tool {
-                tool: program_name.clone(),
-                message: "timed out waiting for process exit after stderr EOF".into(),
+            .map_err(|_| {
+                sf_core::Error::tool_timeout(
+                    program_name.clone(),
+                    "timed out waiting for process exit after stderr EOF",
+                )
             })?
             .map_err(|e| sf_core::Error::Tool {
                 tool: program_name.clone(),
@@ -176,13 +234,6 @@ impl ToolCommand {
             String::new()
         };
 
-        if !status.success() {
-            return Err(sf_core::Error::Tool {
-                tool: program_name,
-                message: format!("exited with status {}: {}", status, stderr_buf.trim()),
-            });
-        }
-
         Ok(ToolOutput {
             status,
             stdout,
@@ -194,20 +245,23 @@ impl ToolCommand {
     ///
     /// # Errors
     ///
-    /// - Returns [`sf_core::Error::Tool`] if the process times out (message
-    ///   includes the timeout duration).
+    /// - Returns [`sf_core::Error::ToolTimeout`] if the process times out
+    ///   (message includes the timeout duration).
     /// - Returns [`sf_core::Error::Tool`] if the process exits with a non-zero
     ///   status (message includes stderr).
     /// - Returns [`sf_core::Error::Tool`] if spawning the process fails.
     pub async fn execute(&self) -> sf_core::Result<ToolOutput> {
-        let program_name = self
-            .program
-            .file_name()
-            .map(|n| n.to_string_lossy().to_string())
-            .unwrap_or_else(|| self.program.to_string_lossy().to_string());
+        let started = Instant::now();
+        let result = self.run(started).await;
+        self.finish(started, result)
+    }
 
-        let mut cmd = Command::new(&self.program);
-        cmd.args(&self.args);
+    /// Like [`execute`](Self::execute), but a non-zero exit is returned as
+    /// output rather than an error.
+    async fn run(&self, started: Instant) -> sf_core::Result<ToolOutput> {
+        let program_name = self.program_name();
+
+        let mut cmd = self.command();
 
         // If we need to pipe stdin, configure that.
         if self.stdin_data.is_some() {
@@ -234,50 +288,185 @@ impl ToolCommand {
             }
         }
 
-        // Wait with timeout.
-        let result = tokio::time::timeout(self.timeout, child.wait_with_output()).await;
-
-        match result {
-            Ok(Ok(output)) => {
-                let tool_output = ToolOutput {
-                    status: output.status,
-                    stdout: String::from_utf8_lossy(&output.stdout).to_string(),
-                    stderr: String::from_utf8_lossy(&output.stderr).to_string(),
-                };
-
-                if !output.status.success() {
-                    return Err(sf_core::Error::Tool {
-                        tool: program_name,
-                        message: format!(
-                            "exited with status {}: {}",
-                            output.status,
-                            tool_output.stderr.trim()
-                        ),
+        // Drain the pipes while waiting so the child never blocks on a full
+        // pipe.
+        let stdout = tokio::spawn(read_pipe(child.stdout.take()));
+        let stderr = tokio::spawn(read_pipe(child.stderr.take()));
+
+        let status = self.wait(&mut child, started).await?;
+        Ok(ToolOutput {
+            status,
+            stdout: stdout.await.unwrap_or_default(),
+            stderr: stderr.await.unwrap_or_default(),
+        })
+    }
+
+    /// Wait for `child` to exit, killing it on timeout or cancellation.
+    async fn wait(&self, child: &mut Child, started: Instant) -> sf_core::Result<ExitStatus> {
+        let mut control = ChildControl::current(child.id(), None);
+        loop {
+            let deadline = control.deadline(started, self.timeout);
+            let timed_out = async {
+                match deadline {
+                    Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+                    None => std::future::pending().await,
+                }
+            };
+            tokio::select! {
+                status = child.wait() => {
+                    return status.map_err(|e| sf_core::Error::Tool {
+                        tool: self.program_name(),
+                        message: format!("I/O error waiting for process: {e}"),
                     });
                 }
-
-                Ok(tool_output)
+                cancelled = control.changed() => {
+                    if cancelled {
+                        let _ = child.kill().await;
+                        return Err(cancelled_error(self.program_name()));
+                    }
+                }
+                _ = timed_out => {
+                    let _ = child.kill().await;
+                    return Err(sf_core::Error::tool_timeout(
+                        self.program_name(),
+                        format!("timed out after {:?}", self.timeout),
+                    ));
+                }
             }
-            Ok(Err(e)) => Err(sf_core::Error::Tool {
-                tool: program_name,
-                message: format!("I/O error waiting for process: {e}"),
+        }
+    }
+}
+
+impl ToolCommand {
+    fn command(&self) -> Command {
+        let mut cmd = Command::new(&self.program);
+        cmd.args(&self.args);
+        if let Some(dir) = &self.current_dir {
+            cmd.current_dir(dir);
+        }
+        cmd
+    }
+
+    fn program_name(&self) -> String {
+        self.program
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.program.to_string_lossy().to_string())
+    }
+
+    /// The command line, quoted so it can be pasted into a shell.
+    pub fn command_line(&self) -> String {
+        std::iter::once(self.program.to_string_lossy().into_owned())
+            .chain(self.args.iter().cloned())
+            .map(|arg| shell_quote(&arg))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Report the run to the task's observer, then turn a non-zero exit into
+    /// an error.
+    fn finish(
+        &self,
+        started: Instant,
+        result: sf_core::Result<ToolOutput>,
+    ) -> sf_core::Result<ToolOutput> {
+        let error = match &result {
+            Ok(output) if !output.status.success() => Some(sf_core::Error::Tool {
+                tool: self.program_name(),
+                message: format!(
+                    "exited with status {}: {}",
+                    output.status,
+                    output.stderr.trim()
+                ),
             }),
-            Err(_elapsed) => {
-                // Timeout expired -- try to kill the child.
-                // We no longer own `child` after `wait_with_output`, so on
-                // real timeout the future is cancelled and tokio will clean up.
-                Err(sf_core::Error::Tool {
-                    tool: program_name,
-                    message: format!("timed out after {:?}", self.timeout),
-                })
-            }
+            _ => None,
+        };
+
+        let _ = TOOL_RUN_OBSERVER.try_with(|observer| {
+            observer(ToolRun {
+                command: self.command_line(),
+                exit_code: result.as_ref().ok().and_then(|o| o.status.code()),
+                stderr: result
+                    .as_ref()
+                    .map(|o| stderr_tail(&o.stderr).to_string())
+                    .unwrap_or_default(),
+                duration: started.elapsed(),
+                error: match (&result, &error) {
+                    (Err(e), _) | (Ok(_), Some(e)) => Some(e.to_string()),
+                    _ => None,
+                },
+            })
+        });
+
+        match error {
+            Some(e) => Err(e),
+            None => result,
         }
     }
 }
 
+fn cancelled_error(tool: String) -> sf_core::Error {
+    sf_core::Error::Tool {
+        tool,
+        message: "cancelled".into(),
+    }
+}
+
+async fn read_pipe(pipe: Option<impl tokio::io::AsyncRead + Unpin>) -> String {
+    let mut buf = Vec::new();
+    if let Some(mut pipe) = pipe {
+        let _ = tokio::io::AsyncReadExt::read_to_end(&mut pipe, &mut buf).await;
+    }
+    String::from_utf8_lossy(&buf).to_string()
+}
+
+/// The last [`STDERR_TAIL_BYTES`] of `stderr`, cut at a line start where
+/// possible.
+fn stderr_tail(stderr: &str) -> &str {
+    if stderr.len() <= STDERR_TAIL_BYTES {
+        return stderr;
+    }
+    let mut start = stderr.len() - STDERR_TAIL_BYTES;
+    while !stderr.is_char_boundary(start) {
+        start += 1;
+    }
+    let tail = &stderr[start..];
+    match tail.find('\n') {
+        Some(i) => &tail[i + 1..],
+        None => tail,
+    }
+}
+
+fn shell_quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=,+@%".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::path::Path;
+
+    #[tokio::test]
+    async fn runs_in_current_dir() {
+        let tmp = tempfile::tempdir().unwrap();
+        let output = ToolCommand::new(PathBuf::from("pwd"))
+            .current_dir(tmp.path())
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(
+            Path::new(output.stdout.trim()).canonicalize().unwrap(),
+            tmp.path().canonicalize().unwrap()
+        );
+    }
 
     #[tokio::test]
     async fn execute_echo() {
@@ -314,8 +503,93 @@ mod tests {
             .timeout(Duration::from_millis(100))
             .execute()
             .await;
-        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(
+            matches!(err, sf_core::Error::ToolTimeout { .. }),
+            "unexpected error: {err}"
+        );
+        assert!(err.is_transient());
+    }
+
+    #[tokio::test]
+    async fn runs_are_reported_to_the_observer() {
+        let runs = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let observer: ToolRunObserver = {
+            let runs = runs.clone();
+            Arc::new(move |run| runs.lock().unwrap().push(run))
+        };
+
+        observe_tool_runs(observer, async {
+            let _ = ToolCommand::new(PathBuf::from("sh"))
+                .args(["-c", "echo oops >&2; exit 3"])
+                .execute()
+                .await;
+            let _ = ToolCommand::new(PathBuf::from("nonexistent_tool_xyz_12345"))
+                .execute()
+                .await;
+        })
+        .await;
+
+        let runs = runs.lock().unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].command, "sh -c 'echo oops >&2; exit 3'");
+        // `sh` may be missing on minimal environments.
+        if runs[0].exit_code.is_some() {
+            assert_eq!(runs[0].exit_code, Some(3));
+            assert!(runs[0].error.as_deref().unwrap().contains("exited with status"));
+            assert_eq!(runs[0].stderr.trim(), "oops");
+        }
+        assert!(runs[1].exit_code.is_none());
+        assert!(runs[1].error.as_deref().unwrap().contains("failed to spawn"));
+    }
+
+    #[tokio::test]
+    async fn cancelling_the_control_kills_the_process() {
+        let control = crate::ProcessControl::default();
+        let canceller = control.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            canceller.cancel();
+        });
+
+        let started = Instant::now();
+        let result = crate::with_process_control(control, async {
+            ToolCommand::new(PathBuf::from("sleep"))
+                .arg("10")
+                .execute()
+                .await
+        })
+        .await;
         let err = result.unwrap_err().to_string();
-        assert!(err.contains("timed out"), "unexpected error: {err}");
+        assert!(err.contains("cancelled"), "unexpected error: {err}");
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn paused_time_does_not_count_towards_the_timeout() {
+        let control = crate::ProcessControl::default();
+        control.pause();
+        let resumer = control.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(1500)).await;
+            resumer.resume();
+        });
+
+        let result = crate::with_process_control(control, async {
+            ToolCommand::new(PathBuf::from("sleep"))
+                .arg("0.2")
+                .timeout(Duration::from_secs(1))
+                .execute()
+                .await
+        })
+        .await;
+        assert!(result.is_ok(), "unexpected error: {:?}", result.err());
+    }
+
+    #[test]
+    fn stderr_tail_keeps_whole_lines() {
+        let long = "x".repeat(STDERR_TAIL_BYTES) + "\nlast line\n";
+        assert_eq!(stderr_tail(&long), "last line\n");
+        assert_eq!(stderr_tail("short"), "short");
     }
 }
@@ -0,0 +1,236 @@
+//! Pausing and cancelling running tool processes.
+//!
+//! A [`ProcessControl`] is installed for a task with
+//! [`with_process_control`]. Every [`ToolCommand`](crate::ToolCommand) run
+//! inside it is killed as soon as the control is cancelled, and stopped and
+//! continued (`SIGSTOP` / `SIGCONT`) when the control is paused and resumed.
+//! Pausing is only supported on Unix.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+
+/// Cancel, pause and resume handle for the tool processes of a task.
+///
+/// Cloning is cheap; all clones control the same processes.
+#[derive(Debug, Clone)]
+pub struct ProcessControl {
+    cancel: CancellationToken,
+    paused: Arc<watch::Sender<bool>>,
+}
+
+impl Default for ProcessControl {
+    fn default() -> Self {
+        Self::new(CancellationToken::new())
+    }
+}
+
+impl ProcessControl {
+    /// Create a control that is cancelled along with `cancel`.
+    pub fn new(cancel: CancellationToken) -> Self {
+        Self {
+            cancel,
+            paused: Arc::new(watch::channel(false).0),
+        }
+    }
+
+    /// The token cancelled by [`cancel`](Self::cancel).
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancel
+    }
+
+    /// Kill running processes and refuse to wait for new ones.
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+
+    /// Whether [`cancel`](Self::cancel) was called.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// Stop running processes; processes started while paused are stopped
+    /// straight away. Returns `false` if already paused.
+    pub fn pause(&self) -> bool {
+        self.paused
+            .send_if_modified(|paused| !std::mem::replace(paused, true))
+    }
+
+    /// Continue stopped processes. Returns `false` if not paused.
+    pub fn resume(&self) -> bool {
+        self.paused
+            .send_if_modified(|paused| std::mem::replace(paused, false))
+    }
+
+    /// Whether the control is paused.
+    pub fn is_paused(&self) -> bool {
+        *self.paused.borrow()
+    }
+
+    /// Wait until the control is resumed or cancelled.
+    pub async fn wait_while_paused(&self) {
+        let mut paused = self.paused.subscribe();
+        tokio::select! {
+            _ = paused.wait_for(|paused| !*paused) => {}
+            _ = self.cancel.cancelled() => {}
+        }
+    }
+}
+
+tokio::task_local! {
+    static PROCESS_CONTROL: ProcessControl;
+}
+
+/// Run `fut` with every [`ToolCommand`](crate::ToolCommand) it executes
+/// under `control`.
+///
+/// Like [`observe_tool_runs`](crate::observe_tool_runs), the control is
+/// task-local.
+pub async fn with_process_control<F: Future>(control: ProcessControl, fut: F) -> F::Output {
+    PROCESS_CONTROL.scope(control, fut).await
+}
+
+/// The task's [`ProcessControl`] as applied to one child process.
+pub(crate) struct ChildControl {
+    pid: Option<u32>,
+    control: Option<ProcessControl>,
+    paused: Option<watch::Receiver<bool>>,
+    extra: Option<CancellationToken>,
+    paused_since: Option<Instant>,
+    paused_total: Duration,
+}
+
+impl ChildControl {
+    /// Attach the task's control, plus an optional extra cancellation token,
+    /// to the process `pid`.
+    pub(crate) fn current(pid: Option<u32>, extra: Option<CancellationToken>) -> Self {
+        let control = PROCESS_CONTROL.try_with(Clone::clone).ok();
+        let paused = control.as_ref().map(|c| c.paused.subscribe());
+        let mut child = Self {
+            pid,
+            control,
+            paused,
+            extra,
+            paused_since: None,
+            paused_total: Duration::ZERO,
+        };
+        if child
+            .paused
+            .as_mut()
+            .is_some_and(|rx| *rx.borrow_and_update())
+        {
+            child.set_paused(true);
+        }
+        child
+    }
+
+    /// Wait for the next control change. Pauses and resumes are applied to
+    /// the process before returning `false`; `true` means the process should
+    /// be killed.
+    pub(crate) async fn changed(&mut self) -> bool {
+        let paused_rx = &mut self.paused;
+        let paused = async {
+            match paused_rx {
+                Some(rx) => rx.changed().await.ok().map(|()| *rx.borrow_and_update()),
+                None => std::future::pending().await,
+            }
+        };
+        let paused = tokio::select! {
+            _ = cancelled(self.control.as_ref().map(ProcessControl::cancellation_token)) => {
+                return true;
+            }
+            _ = cancelled(self.extra.as_ref()) => return true,
+            paused = paused => paused,
+        };
+        match paused {
+            Some(paused) => self.set_paused(paused),
+            // The control is gone; nothing will change any more.
+            None => self.paused = None,
+        }
+        false
+    }
+
+    /// When a command started at `started` runs out of `timeout`, not
+    /// counting time spent paused. `None` while paused.
+    pub(crate) fn deadline(&self, started: Instant, timeout: Duration) -> Option<Instant> {
+        match self.paused_since {
+            Some(_) => None,
+            None => Some(started + timeout + self.paused_total),
+        }
+    }
+
+    fn set_paused(&mut self, paused: bool) {
+        match (paused, self.paused_since) {
+            (true, None) => self.paused_since = Some(Instant::now()),
+            (false, Some(since)) => {
+                self.paused_total += since.elapsed();
+                self.paused_since = None;
+            }
+            _ => return,
+        }
+        if let Some(pid) = self.pid {
+            signal_stop(pid, paused);
+        }
+    }
+}
+
+async fn cancelled(token: Option<&CancellationToken>) {
+    match token {
+        Some(token) => token.cancelled().await,
+        None => std::future::pending().await,
+    }
+}
+
+#[cfg(unix)]
+fn signal_stop(pid: u32, stop: bool) {
+    let signal = if stop { libc::SIGSTOP } else { libc::SIGCONT };
+    // SAFETY: kill(2) has no memory-safety preconditions.
+    if unsafe { libc::kill(pid as libc::pid_t, signal) } != 0 {
+        tracing::warn!(
+            pid,
+            "failed to signal process: {}",
+            std::io::Error::last_os_error()
+        );
+    }
+}
+
+#[cfg(not(unix))]
+fn signal_stop(pid: u32, _stop: bool) {
+    tracing::warn!(pid, "pausing processes is not supported on this platform");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pause_and_resume_report_changes() {
+        let control = ProcessControl::default();
+        assert!(control.pause());
+        assert!(!control.pause());
+        assert!(control.is_paused());
+        assert!(control.resume());
+        assert!(!control.resume());
+        assert!(!control.is_paused());
+    }
+
+    #[tokio::test]
+    async fn wait_while_paused_ends_on_resume() {
+        let control = ProcessControl::default();
+        control.pause();
+        let waiter = tokio::spawn({
+            let control = control.clone();
+            async move { control.wait_while_paused().await }
+        });
+        tokio::task::yield_now().await;
+        assert!(!waiter.is_finished());
+        control.resume();
+        tokio::time::timeout(Duration::from_secs(5), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
@@ -6,31 +6,40 @@
 //! This crate provides:
 //!
 //! - **Tool discovery** ([`ToolRegistry`]) -- find and cache paths to ffmpeg,
-//!   ffprobe, mediainfo, mkvmerge, mkvextract, and dovi_tool.
+//!   ffprobe, mediainfo, mkvmerge, mkvextract, dovi_tool, and hdr10plus_tool.
 //! - **Command execution** ([`ToolCommand`]) -- async builder with timeout
-//!   support for running external processes.
+//!   support for running external processes, which can be cancelled and
+//!   paused through a [`ProcessControl`].
 //! - **Workspace management** ([`Workspace`]) -- temporary directory lifecycle
 //!   with safe finalization.
 //! - **Probe backends** ([`probe::FfprobeProber`], [`probe::MediaInfoProber`])
 //!   -- implement [`sf_probe::Prober`] by shelling out to CLI tools.
-//! - **Action functions** ([`actions`]) -- remux, DV profile conversion,
-//!   audio track addition, track stripping, and arbitrary command execution.
+//! - **Action functions** ([`actions`]) -- remux, DV profile conversion and
+//!   generation from HDR10/HDR10+, HDR10+ metadata extraction, injection and
+//!   removal, audio track addition, track stripping, arbitrary command
+//!   execution, HEVC/AV1 transcoding, and decode checks.
 
 pub mod actions;
 pub mod command;
+pub mod control;
 pub mod probe;
 pub mod tools;
 pub mod workspace;
 
 // ---- Re-exports for convenience ----
 
-pub use command::{ToolCommand, ToolOutput};
+pub use command::{observe_tool_runs, ToolCommand, ToolOutput, ToolRun, ToolRunObserver};
+pub use control::{with_process_control, ProcessControl};
 pub use probe::{FfprobeProber, MediaInfoProber};
 pub use tools::{ToolConfig, ToolInfo, ToolRegistry};
 pub use workspace::Workspace;
 
 // Action functions
 pub use actions::{
-    add_compat_audio, adaptive_crf, convert_dv_profile, convert_to_profile_b,
-    convert_to_profile_b_with_progress, exec_command, remux, strip_tracks, EncodeProgress,
+    add_compat_audio, adaptive_crf, chunk_dir, convert_dv_profile, convert_subtitles_to_srt,
+    convert_to_profile_b, convert_to_profile_b_with_progress, decode_check, downmix_audio,
+    edit_tracks, exec_command, extract_hdr10plus, extract_subtitles, generate_dv,
+    inject_hdr10plus, is_ass_subtitle, is_text_subtitle, probe_hdr_metadata, remove_hdr10plus,
+    remux, strip_tracks, transcode, DownmixOptions, EncodeProgress, HdrMetadata, LoudnormStats,
+    MasteringDisplay, SubtitleExtract, TrackLayout, TrackSpec, TranscodeOptions,
 };
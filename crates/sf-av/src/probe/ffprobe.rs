@@ -164,9 +164,7 @@ fn parse_ffprobe_output(path: &Path, output: FfprobeOutput) -> sf_core::Result<M
         .and_then(|s| s.parse::<u64>().ok())
         .unwrap_or(0);
 
-    let container = map_container(
-        output.format.format_name.as_deref().unwrap_or(""),
-    );
+    let container = map_container(output.format.format_name.as_deref().unwrap_or(""), path);
 
     let mut video_tracks = Vec::new();
     let mut audio_tracks = Vec::new();
@@ -190,6 +188,7 @@ fn parse_ffprobe_output(path: &Path, output: FfprobeOutput) -> sf_core::Result<M
                     frame_rate: stream.r_frame_rate.and_then(|s| parse_frame_rate(&s)),
                     bit_depth,
                     hdr_format,
+                    hdr10plus: false,
                     dolby_vision: dv_info,
                     default: stream.disposition.default == 1,
                     language: stream.tags.language,
@@ -238,6 +237,7 @@ fn detect_hdr(stream: &FfprobeStream) -> (HdrFormat, Option<DvInfo>) {
                 rpu_present: sd.rpu_present_flag == Some(1),
                 el_present: sd.el_present_flag == Some(1),
                 bl_present: sd.bl_present_flag == Some(1),
+                el_type: None,
             };
             return (HdrFormat::DolbyVision, Some(dv));
         }
@@ -271,10 +271,19 @@ fn parse_frame_rate(rate_str: &str) -> Option<f64> {
     rate_str.parse().ok()
 }
 
-fn map_container(format_name: &str) -> Container {
+/// ffprobe reports `matroska,webm` for both, so Matroska files are told apart
+/// by their DocType, as the other probers do.
+pub(crate) fn map_container(format_name: &str, path: &Path) -> Container {
     let lower = format_name.to_lowercase();
     if lower.contains("matroska") || lower.contains("webm") {
-        Container::Mkv
+        match sf_probe::detect_container(path) {
+            Ok(Container::Webm) => Container::Webm,
+            _ => Container::Mkv,
+        }
+    } else if lower.contains("mpegts") {
+        Container::MpegTs
+    } else if lower == "avi" {
+        Container::Avi
     } else {
         Container::Mp4
     }
@@ -318,8 +327,11 @@ mod tests {
 
     #[test]
     fn container_mapping() {
-        assert_eq!(map_container("matroska,webm"), Container::Mkv);
-        assert_eq!(map_container("mov,mp4,m4a,3gp"), Container::Mp4);
+        let path = Path::new("/nonexistent/a.mkv");
+        assert_eq!(map_container("matroska,webm", path), Container::Mkv);
+        assert_eq!(map_container("mov,mp4,m4a,3gp", path), Container::Mp4);
+        assert_eq!(map_container("mpegts", path), Container::MpegTs);
+        assert_eq!(map_container("avi", path), Container::Avi);
     }
 
     #[test]
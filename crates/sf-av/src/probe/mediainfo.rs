@@ -178,6 +178,7 @@ fn parse_mi_output(path: &Path, mi: MiOutput) -> sf_core::Result<MediaInfo> {
                     frame_rate: track.frame_rate.and_then(|s| s.parse().ok()),
                     bit_depth: track.bit_depth.and_then(|s| s.parse().ok()),
                     hdr_format: hdr,
+                    hdr10plus: has_hdr10plus(track.hdr_format.as_deref()),
                     dolby_vision: dv,
                     default: track.default.as_deref() == Some("Yes"),
                     language: track.language,
@@ -229,7 +230,7 @@ fn parse_hdr_format(hdr_str: Option<&str>) -> HdrFormat {
     let lower = s.to_lowercase();
     if lower.contains("dolby vision") {
         HdrFormat::DolbyVision
-    } else if lower.contains("hdr10+") || lower.contains("hdr10 plus") {
+    } else if has_hdr10plus(Some(s)) {
         HdrFormat::Hdr10Plus
     } else if lower.contains("hdr10") || lower.contains("smpte st 2086") {
         HdrFormat::Hdr10
@@ -240,6 +241,16 @@ fn parse_hdr_format(hdr_str: Option<&str>) -> HdrFormat {
     }
 }
 
+/// Whether the HDR format string mentions HDR10+ (SMPTE ST 2094-40), which
+/// MediaInfo lists after Dolby Vision when a file carries both.
+fn has_hdr10plus(hdr_str: Option<&str>) -> bool {
+    let Some(s) = hdr_str else {
+        return false;
+    };
+    let lower = s.to_lowercase();
+    lower.contains("hdr10+") || lower.contains("hdr10 plus") || lower.contains("2094 app 4")
+}
+
 fn parse_dolby_vision(track: &MiTrack) -> Option<DvInfo> {
     let hdr_str = track.hdr_format.as_ref()?;
     if !hdr_str.to_lowercase().contains("dolby vision") {
@@ -261,13 +272,20 @@ fn parse_dolby_vision(track: &MiTrack) -> Option<DvInfo> {
         rpu_present: components.contains("rpu"),
         el_present: components.contains("el"),
         bl_present: components.contains("bl"),
+        el_type: None,
     })
 }
 
-fn map_container(format_name: &str) -> Container {
+pub(crate) fn map_container(format_name: &str) -> Container {
     let lower = format_name.to_lowercase();
-    if lower.contains("matroska") || lower.contains("mkv") || lower.contains("webm") {
+    if lower.contains("webm") {
+        Container::Webm
+    } else if lower.contains("matroska") || lower.contains("mkv") {
         Container::Mkv
+    } else if lower.contains("mpeg-ts") || lower.contains("bdav") {
+        Container::MpegTs
+    } else if lower == "avi" {
+        Container::Avi
     } else {
         Container::Mp4
     }
@@ -333,6 +351,14 @@ mod tests {
             HdrFormat::Hdr10
         );
         assert_eq!(parse_hdr_format(Some("HDR10+")), HdrFormat::Hdr10Plus);
+        assert_eq!(
+            parse_hdr_format(Some("SMPTE ST 2094 App 4, Version 1")),
+            HdrFormat::Hdr10Plus
+        );
+        assert!(has_hdr10plus(Some(
+            "Dolby Vision, Version 1.0, dvhe.08.06, BL+RPU / SMPTE ST 2094 App 4"
+        )));
+        assert!(!has_hdr10plus(Some("SMPTE ST 2086, HDR10")));
         assert_eq!(parse_hdr_format(None), HdrFormat::Sdr);
     }
 
@@ -340,5 +366,9 @@ mod tests {
     fn container_detection() {
         assert_eq!(map_container("Matroska"), Container::Mkv);
         assert_eq!(map_container("MPEG-4"), Container::Mp4);
+        assert_eq!(map_container("WebM"), Container::Webm);
+        assert_eq!(map_container("MPEG-TS"), Container::MpegTs);
+        assert_eq!(map_container("BDAV"), Container::MpegTs);
+        assert_eq!(map_container("AVI"), Container::Avi);
     }
 }
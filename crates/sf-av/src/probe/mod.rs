@@ -9,3 +9,39 @@ pub mod mediainfo;
 
 pub use self::ffprobe::FfprobeProber;
 pub use self::mediainfo::MediaInfoProber;
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use sf_core::Container;
+
+    /// A file holding just an EBML header with the given DocType.
+    fn ebml_file(doc_type: &str) -> tempfile::NamedTempFile {
+        let mut body = vec![0x42, 0x82, 0x80 | doc_type.len() as u8];
+        body.extend_from_slice(doc_type.as_bytes());
+        body.extend_from_slice(&[0x42, 0x87, 0x81, 0x04]);
+
+        let mut header = vec![0x1A, 0x45, 0xDF, 0xA3, 0x80 | body.len() as u8];
+        header.extend_from_slice(&body);
+
+        // The extension is deliberately wrong for WebM: the DocType decides.
+        let mut file = tempfile::Builder::new().suffix(".mkv").tempfile().unwrap();
+        file.write_all(&header).unwrap();
+        file
+    }
+
+    #[test]
+    fn probers_agree_on_matroska_and_webm() {
+        for (doc_type, mediainfo_format, expected) in [
+            ("webm", "WebM", Container::Webm),
+            ("matroska", "Matroska", Container::Mkv),
+        ] {
+            let file = ebml_file(doc_type);
+            let rust = sf_probe::detect_container(file.path()).unwrap();
+            let ffprobe = super::ffprobe::map_container("matroska,webm", file.path());
+            let mediainfo = super::mediainfo::map_container(mediainfo_format);
+            assert_eq!([rust, ffprobe, mediainfo], [expected; 3], "{doc_type}");
+        }
+    }
+}
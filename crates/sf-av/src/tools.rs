@@ -1,8 +1,8 @@
 //! External tool detection and management.
 //!
 //! The [`ToolRegistry`] discovers and caches the locations of external CLI
-//! tools (ffmpeg, ffprobe, mediainfo, mkvmerge, mkvextract, dovi_tool) and
-//! provides lookup methods for the rest of the crate.
+//! tools (ffmpeg, ffprobe, mediainfo, mkvmerge, mkvextract, dovi_tool,
+//! hdr10plus_tool) and provides lookup methods for the rest of the crate.
 
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -21,6 +21,7 @@ const KNOWN_TOOLS: &[&str] = &[
     "mkvmerge",
     "mkvextract",
     "dovi_tool",
+    "hdr10plus_tool",
 ];
 
 /// Configuration for a single external tool.
@@ -108,6 +109,7 @@ impl ToolRegistry {
                 "mediainfo" => tools_config.mediainfo_path.as_deref(),
                 "mkvmerge" => tools_config.mkvmerge_path.as_deref(),
                 "dovi_tool" => tools_config.dovi_tool_path.as_deref(),
+                "hdr10plus_tool" => tools_config.hdr10plus_tool_path.as_deref(),
                 _ => None,
             };
 
@@ -237,6 +239,7 @@ mod tests {
         assert!(names.contains(&"mkvmerge"));
         assert!(names.contains(&"mkvextract"));
         assert!(names.contains(&"dovi_tool"));
+        assert!(names.contains(&"hdr10plus_tool"));
     }
 
     #[test]
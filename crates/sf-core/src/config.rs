@@ -4,6 +4,7 @@
 //! sub-configs for server, auth, tools, conversion, etc. Every section
 //! defaults sensibly so a completely empty `{}` file is valid.
 
+use chrono::{Datelike, Days, NaiveDateTime, NaiveTime, Weekday};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
@@ -30,6 +31,11 @@ pub struct Config {
     pub metadata: MetadataConfig,
     pub images: ImageConfig,
     pub webhook_security: WebhookSecurityConfig,
+    pub workers: WorkersConfig,
+    pub schedule: ScheduleConfig,
+    pub verification: VerificationConfig,
+    pub trash: TrashConfig,
+    pub transcode: TranscodeConfig,
 }
 
 impl Default for Config {
@@ -45,6 +51,11 @@ impl Default for Config {
             metadata: MetadataConfig::default(),
             images: ImageConfig::default(),
             webhook_security: WebhookSecurityConfig::default(),
+            workers: WorkersConfig::default(),
+            schedule: ScheduleConfig::default(),
+            verification: VerificationConfig::default(),
+            trash: TrashConfig::default(),
+            transcode: TranscodeConfig::default(),
         }
     }
 }
@@ -140,6 +151,52 @@ impl Config {
             }
         }
 
+        if self.conversion.chunk_secs > 0 && self.conversion.chunk_parallelism == 0 {
+            warnings.push(
+                "conversion.chunk_parallelism is 0; chunks will be encoded one at a time".into(),
+            );
+        }
+
+        if self.verification.enabled && self.verification.duration_tolerance_secs < 0.0 {
+            warnings.push(
+                "verification.duration_tolerance_secs is negative; every output will fail \
+                 verification"
+                    .into(),
+            );
+        }
+
+        if self.trash.enabled && self.trash.dir.as_os_str().is_empty() {
+            warnings.push("trash.dir is empty; replaced originals will be deleted".into());
+        }
+
+        if self.transcode.enabled {
+            if self.transcode.dir.as_os_str().is_empty() {
+                warnings.push("transcode.dir is empty; transcoding sessions will fail".into());
+            }
+            if self.transcode.max_sessions == 0 {
+                warnings.push(
+                    "transcode.max_sessions is 0; no transcoding session will start".into(),
+                );
+            }
+            if self.transcode.segment_secs == 0 {
+                warnings.push("transcode.segment_secs is 0; 6 second segments are used".into());
+            }
+        }
+
+        if self.workers.processors == 0 {
+            warnings.push("workers.processors is 0; queued jobs will never run".into());
+        }
+        if self.workers.lease_secs == 0 {
+            warnings.push("workers.lease_secs is 0; running jobs are requeued at once".into());
+        }
+        for class in ResourceClass::ALL {
+            if self.workers.limits.limit(class) == 0 {
+                warnings.push(format!(
+                    "workers.limits.{class} is 0; {class} jobs will never run"
+                ));
+            }
+        }
+
         warnings
     }
 }
@@ -243,6 +300,7 @@ pub struct ToolsConfig {
     pub mediainfo_path: Option<PathBuf>,
     pub mkvmerge_path: Option<PathBuf>,
     pub dovi_tool_path: Option<PathBuf>,
+    pub hdr10plus_tool_path: Option<PathBuf>,
 }
 
 /// Video conversion defaults.
@@ -264,6 +322,14 @@ pub struct ConversionConfig {
     /// hardware decoder and encoder instead of the default libx264.
     #[serde(default)]
     pub hw_accel: Option<String>,
+    /// Encode sources at least twice this long in keyframe-aligned chunks of
+    /// about this many seconds, so an interrupted conversion resumes from
+    /// the last finished chunk. 0 disables chunking.
+    #[serde(default = "default_chunk_secs")]
+    pub chunk_secs: u32,
+    /// Number of chunks encoded at the same time.
+    #[serde(default = "default_chunk_parallelism")]
+    pub chunk_parallelism: usize,
 }
 
 fn default_video_crf() -> u32 {
@@ -278,6 +344,12 @@ fn default_audio_bitrate() -> String {
 fn default_adaptive_crf() -> bool {
     true
 }
+fn default_chunk_secs() -> u32 {
+    300
+}
+fn default_chunk_parallelism() -> usize {
+    1
+}
 
 impl Default for ConversionConfig {
     fn default() -> Self {
@@ -289,6 +361,8 @@ impl Default for ConversionConfig {
             audio_bitrate: default_audio_bitrate(),
             adaptive_crf: default_adaptive_crf(),
             hw_accel: None,
+            chunk_secs: default_chunk_secs(),
+            chunk_parallelism: default_chunk_parallelism(),
         }
     }
 }
@@ -341,6 +415,299 @@ pub struct WebhookSecurityConfig {
     pub allowed_ips: Vec<String>,
 }
 
+/// Background worker pool settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkersConfig {
+    /// Number of workers running rule pipeline jobs.
+    pub processors: usize,
+    /// Number of workers running Profile B conversion jobs.
+    pub conversions: usize,
+    /// Per-class concurrency caps, shared by both kinds of worker.
+    pub limits: ResourceLimits,
+    /// How long a claimed job's lock stays valid without a heartbeat before
+    /// the job is considered abandoned (e.g. after a crash) and requeued.
+    pub lease_secs: u64,
+    /// Delay before the first automatic retry of a transiently failed job;
+    /// doubled for every further retry.
+    pub retry_delay_secs: u64,
+    /// Upper bound for the retry delay.
+    pub max_retry_delay_secs: u64,
+}
+
+impl Default for WorkersConfig {
+    fn default() -> Self {
+        Self {
+            processors: 2,
+            conversions: 1,
+            limits: ResourceLimits::default(),
+            lease_secs: 600,
+            retry_delay_secs: 30,
+            max_retry_delay_secs: 3600,
+        }
+    }
+}
+
+impl WorkersConfig {
+    /// The lock lease as a [`Duration`](std::time::Duration).
+    pub fn lease(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.lease_secs)
+    }
+
+    /// Backoff before retry number `retry_count + 1`.
+    pub fn retry_delay(&self, retry_count: u32) -> std::time::Duration {
+        let secs = self
+            .retry_delay_secs
+            .saturating_mul(1u64 << retry_count.min(32))
+            .min(self.max_retry_delay_secs);
+        std::time::Duration::from_secs(secs)
+    }
+}
+
+/// The kind of load a job puts on the machine, derived from its actions.
+///
+/// Ordered from lightest to heaviest; a job with several actions takes the
+/// class of its heaviest one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResourceClass {
+    /// Short commands and sidecar extraction.
+    Light,
+    /// IO-bound stream copies: remux, track edits, audio additions.
+    Remux,
+    /// CPU-heavy video encodes.
+    Encode,
+}
+
+impl ResourceClass {
+    pub const ALL: [ResourceClass; 3] = [Self::Light, Self::Remux, Self::Encode];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Light => "light",
+            Self::Remux => "remux",
+            Self::Encode => "encode",
+        }
+    }
+}
+
+impl std::fmt::Display for ResourceClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ResourceClass {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|c| c.as_str() == s)
+            .ok_or_else(|| Error::Validation(format!("unknown resource class '{s}'")))
+    }
+}
+
+/// Maximum number of concurrently running jobs per [`ResourceClass`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourceLimits {
+    pub encode: usize,
+    pub remux: usize,
+    pub light: usize,
+}
+
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self {
+            encode: 1,
+            remux: 2,
+            light: 4,
+        }
+    }
+}
+
+impl ResourceLimits {
+    /// The cap for `class`.
+    pub fn limit(&self, class: ResourceClass) -> usize {
+        match class {
+            ResourceClass::Encode => self.encode,
+            ResourceClass::Remux => self.remux,
+            ResourceClass::Light => self.light,
+        }
+    }
+}
+
+/// Time windows in which heavy jobs (encodes, Dolby Vision conversions) may
+/// start. Heavy jobs picked up outside every window are deferred to the next
+/// window start. With no windows configured they run whenever a worker is
+/// free.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScheduleConfig {
+    pub windows: Vec<ScheduleWindow>,
+}
+
+/// A daily processing window in server local time, e.g. `01:00`–`07:00`.
+///
+/// A window whose `end` is not after its `start` runs past midnight into the
+/// next day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleWindow {
+    /// Days on which the window opens (`"mon"`, `"tue"`, ...); empty means
+    /// every day.
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl ScheduleWindow {
+    /// The `(open, close)` times of this window if it opens on `date`'s day.
+    fn span_on(&self, date: chrono::NaiveDate) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        if !self.days.is_empty() && !self.days.contains(&date.weekday()) {
+            return None;
+        }
+        let close_date = if self.end > self.start {
+            date
+        } else {
+            date.checked_add_days(Days::new(1))?
+        };
+        Some((date.and_time(self.start), close_date.and_time(self.end)))
+    }
+}
+
+impl ScheduleConfig {
+    /// When heavy work may next start, given the local time `now`.
+    ///
+    /// Returns `None` if a window is open at `now` (or none are configured),
+    /// otherwise the start of the earliest upcoming window.
+    pub fn next_start(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut next: Option<NaiveDateTime> = None;
+        for window in &self.windows {
+            // Start a day early to catch a window still open from yesterday.
+            for offset in -1i64..=7 {
+                let Some(date) = now
+                    .date()
+                    .checked_add_signed(chrono::Duration::days(offset))
+                else {
+                    continue;
+                };
+                let Some((open, close)) = window.span_on(date) else {
+                    continue;
+                };
+                if open <= now && now < close {
+                    return None;
+                }
+                if open > now && next.is_none_or(|n| open < n) {
+                    next = Some(open);
+                }
+            }
+        }
+        next
+    }
+}
+
+/// Checks run on a pipeline's output before it replaces the original.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VerificationConfig {
+    /// Re-probe the output and compare it with what the actions should have
+    /// produced. A failed check rolls the pipeline back.
+    pub enabled: bool,
+    /// How far the output's duration may differ from the source's.
+    pub duration_tolerance_secs: f64,
+    /// Also decode the whole output with ffmpeg to catch corrupt streams.
+    /// Takes about as long as playing it back at full decoder speed.
+    pub decode_check: bool,
+}
+
+impl Default for VerificationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            duration_tolerance_secs: 2.0,
+            decode_check: false,
+        }
+    }
+}
+
+/// What happens to originals replaced by a pipeline's output.
+///
+/// Originals are moved into `dir`, keeping their path relative to the
+/// library root, and purged once they are older than `retention_days` or
+/// the trash grows past `max_size_gb` (oldest first).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrashConfig {
+    /// Keep replaced originals in the trash. When disabled they are deleted.
+    pub enabled: bool,
+    /// Where trashed originals are kept. Defaults to `/data/trash`, on the
+    /// same volume as the database.
+    pub dir: PathBuf,
+    /// Purge files this many days after they were trashed; 0 keeps them
+    /// until the size limit is reached or they are purged by hand.
+    pub retention_days: u32,
+    /// Maximum total size of the trash in GB; 0 means unlimited.
+    pub max_size_gb: u64,
+}
+
+impl TrashConfig {
+    /// `max_size_gb` in bytes, if limited.
+    pub fn max_size_bytes(&self) -> Option<u64> {
+        (self.max_size_gb > 0).then(|| self.max_size_gb.saturating_mul(1024 * 1024 * 1024))
+    }
+}
+
+impl Default for TrashConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            dir: PathBuf::from("/data/trash"),
+            retention_days: 30,
+            max_size_gb: 0,
+        }
+    }
+}
+
+/// Live transcoding for clients that can't direct-play a file.
+///
+/// Each playback session runs its own ffmpeg process writing H.264/AAC HLS
+/// segments into a directory under `dir`, removed when the session stops or
+/// has been idle for `idle_timeout_secs`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TranscodeConfig {
+    /// Offer a transcoded HLS stream to Jellyfin clients.
+    pub enabled: bool,
+    /// Scratch directory for session segments.
+    pub dir: PathBuf,
+    /// Maximum number of sessions transcoding at once.
+    pub max_sessions: usize,
+    /// Stop sessions whose segments haven't been requested for this long.
+    pub idle_timeout_secs: u64,
+    /// HLS segment duration in seconds.
+    pub segment_secs: u32,
+    pub video_preset: String,
+    pub video_crf: u32,
+    pub audio_bitrate: String,
+}
+
+impl Default for TranscodeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            dir: PathBuf::from("./data/transcode"),
+            max_sessions: 2,
+            idle_timeout_secs: 120,
+            segment_secs: 6,
+            video_preset: "veryfast".into(),
+            video_crf: 23,
+            audio_bitrate: "192k".into(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -355,6 +722,7 @@ mod tests {
         assert_eq!(cfg.conversion.video_crf, 15);
         assert_eq!(cfg.conversion.video_preset, "slow");
         assert_eq!(cfg.images.storage_dir, PathBuf::from("./data/images"));
+        assert_eq!(cfg.trash.dir, PathBuf::from("/data/trash"));
     }
 
     #[test]
@@ -407,6 +775,99 @@ mod tests {
         assert_eq!(cfg.server.port, 8080);
     }
 
+    #[test]
+    fn parse_worker_limits() {
+        let json = r#"{"workers": {"processors": 4, "limits": {"encode": 2}}}"#;
+        let cfg = Config::from_json(json).unwrap();
+        assert_eq!(cfg.workers.processors, 4);
+        assert_eq!(cfg.workers.conversions, 1);
+        assert_eq!(cfg.workers.limits.limit(ResourceClass::Encode), 2);
+        assert_eq!(cfg.workers.limits.limit(ResourceClass::Remux), 2);
+
+        assert_eq!(
+            "remux".parse::<ResourceClass>().unwrap(),
+            ResourceClass::Remux
+        );
+        assert!("gpu".parse::<ResourceClass>().is_err());
+        assert!(ResourceClass::Light < ResourceClass::Encode);
+    }
+
+    #[test]
+    fn retry_delay_doubles_up_to_the_cap() {
+        let workers = WorkersConfig {
+            retry_delay_secs: 30,
+            max_retry_delay_secs: 100,
+            ..WorkersConfig::default()
+        };
+        assert_eq!(workers.retry_delay(0).as_secs(), 30);
+        assert_eq!(workers.retry_delay(1).as_secs(), 60);
+        assert_eq!(workers.retry_delay(2).as_secs(), 100);
+        assert_eq!(workers.retry_delay(60).as_secs(), 100);
+    }
+
+    #[test]
+    fn zero_worker_limit_warns() {
+        let mut cfg = Config::default();
+        cfg.workers.limits.encode = 0;
+        let warnings = cfg.validate();
+        assert!(warnings.iter().any(|w| w.contains("workers.limits.encode")));
+    }
+
+    #[test]
+    fn trash_size_limit() {
+        let mut trash = TrashConfig::default();
+        assert_eq!(trash.max_size_bytes(), None);
+        trash.max_size_gb = 2;
+        assert_eq!(trash.max_size_bytes(), Some(2 * 1024 * 1024 * 1024));
+    }
+
+    #[test]
+    fn zero_transcode_sessions_warns() {
+        let mut cfg = Config::default();
+        cfg.transcode.max_sessions = 0;
+        let warnings = cfg.validate();
+        assert!(warnings.iter().any(|w| w.contains("transcode.max_sessions")));
+
+        cfg.transcode.enabled = false;
+        assert!(cfg.validate().is_empty());
+    }
+
+    fn at(date: &str, time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(&format!("{date} {time}"), "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn schedule_windows() {
+        let json = r#"{"schedule": {"windows": [
+            {"days": ["mon", "tue", "wed", "thu", "fri"], "start": "01:00", "end": "07:00"},
+            {"days": ["sat"], "start": "22:00", "end": "06:00"}
+        ]}}"#;
+        let schedule = Config::from_json(json).unwrap().schedule;
+
+        // 2024-01-01 is a Monday.
+        assert_eq!(schedule.next_start(at("2024-01-01", "03:00")), None);
+        assert_eq!(
+            schedule.next_start(at("2024-01-01", "12:00")),
+            Some(at("2024-01-02", "01:00"))
+        );
+        // Friday afternoon waits for Saturday night.
+        assert_eq!(
+            schedule.next_start(at("2024-01-05", "08:00")),
+            Some(at("2024-01-06", "22:00"))
+        );
+        // The Saturday window is still open early on Sunday.
+        assert_eq!(schedule.next_start(at("2024-01-07", "05:59")), None);
+        assert_eq!(
+            schedule.next_start(at("2024-01-07", "06:00")),
+            Some(at("2024-01-08", "01:00"))
+        );
+
+        assert_eq!(
+            ScheduleConfig::default().next_start(at("2024-01-01", "12:00")),
+            None
+        );
+    }
+
     #[test]
     fn webhook_signature_without_secret_warns() {
         let mut cfg = Config::default();
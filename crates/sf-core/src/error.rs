@@ -57,6 +57,15 @@ pub enum Error {
         message: String,
     },
 
+    /// An external tool was killed for running past its time limit.
+    #[error("Tool error [{tool}]: {message}")]
+    ToolTimeout {
+        /// Name of the tool that timed out.
+        tool: String,
+        /// Human-readable error description.
+        message: String,
+    },
+
     /// Media probing failed.
     #[error("Probe error: {0}")]
     Probe(String),
@@ -68,6 +77,9 @@ pub enum Error {
         step: String,
         /// Human-readable error description.
         message: String,
+        /// Whether the underlying failure was transient (see
+        /// [`Error::is_transient`]).
+        transient: bool,
     },
 
     /// Catch-all for unexpected internal errors.
@@ -87,12 +99,33 @@ impl Error {
             Error::Database { .. } => 500,
             Error::Io { .. } => 500,
             Error::Tool { .. } => 502,
+            Error::ToolTimeout { .. } => 504,
             Error::Probe(_) => 422,
             Error::Pipeline { .. } => 500,
             Error::Internal(_) => 500,
         }
     }
 
+    /// Whether retrying the failed operation later may succeed: tool
+    /// timeouts, database contention and I/O errors other than missing,
+    /// unreadable or malformed files.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::Database { .. } => true,
+            Error::Io { source } => !matches!(
+                source.kind(),
+                std::io::ErrorKind::NotFound
+                    | std::io::ErrorKind::PermissionDenied
+                    | std::io::ErrorKind::InvalidInput
+                    | std::io::ErrorKind::InvalidData
+                    | std::io::ErrorKind::Unsupported
+            ),
+            Error::ToolTimeout { .. } => true,
+            Error::Pipeline { transient, .. } => *transient,
+            _ => false,
+        }
+    }
+
     /// Convenience constructor for [`Error::NotFound`].
     pub fn not_found(entity: impl Into<String>, id: impl fmt::Display) -> Self {
         Error::NotFound {
@@ -116,11 +149,20 @@ impl Error {
         }
     }
 
+    /// Convenience constructor for [`Error::ToolTimeout`].
+    pub fn tool_timeout(tool: impl Into<String>, message: impl Into<String>) -> Self {
+        Error::ToolTimeout {
+            tool: tool.into(),
+            message: message.into(),
+        }
+    }
+
     /// Convenience constructor for [`Error::Pipeline`].
     pub fn pipeline(step: impl Into<String>, message: impl Into<String>) -> Self {
         Error::Pipeline {
             step: step.into(),
             message: message.into(),
+            transient: false,
         }
     }
 }
@@ -189,6 +231,13 @@ mod tests {
         assert_eq!(err.http_status(), 502);
     }
 
+    #[test]
+    fn tool_timeout_display() {
+        let err = Error::tool_timeout("ffmpeg", "timed out after 300s");
+        assert_eq!(err.to_string(), "Tool error [ffmpeg]: timed out after 300s");
+        assert_eq!(err.http_status(), 504);
+    }
+
     #[test]
     fn probe_display() {
         let err = Error::Probe("corrupt header".into());
@@ -206,6 +255,22 @@ mod tests {
         assert_eq!(err.http_status(), 500);
     }
 
+    #[test]
+    fn transient_classification() {
+        let io = |kind| Error::from(std::io::Error::new(kind, "io"));
+        assert!(io(std::io::ErrorKind::TimedOut).is_transient());
+        assert!(io(std::io::ErrorKind::StorageFull).is_transient());
+        assert!(!io(std::io::ErrorKind::NotFound).is_transient());
+        assert!(!io(std::io::ErrorKind::PermissionDenied).is_transient());
+
+        assert!(Error::tool_timeout("ffmpeg", "timed out after 300s").is_transient());
+        assert!(!Error::tool("ffmpeg", "exit code 1").is_transient());
+        assert!(!Error::tool("ffmpeg", "Connection timed out").is_transient());
+        assert!(Error::database("database is locked").is_transient());
+        assert!(!Error::Probe("corrupt header".into()).is_transient());
+        assert!(!Error::pipeline("remux", "mkvmerge failed").is_transient());
+    }
+
     #[test]
     fn internal_display() {
         let err = Error::Internal("unexpected state".into());
@@ -56,6 +56,26 @@ pub enum EventPayload {
         job_id: JobId,
         error: String,
     },
+    JobPaused {
+        job_id: JobId,
+    },
+    JobResumed {
+        job_id: JobId,
+    },
+    JobCancelled {
+        job_id: JobId,
+    },
+    /// A new entry in the job's execution log.
+    JobLog {
+        job_id: JobId,
+        kind: String,
+        action: Option<String>,
+        command: Option<String>,
+        exit_code: Option<i32>,
+        stderr: Option<String>,
+        duration_ms: Option<i64>,
+        message: Option<String>,
+    },
 
     // -- Library lifecycle ---------------------------------------------------
     LibraryScanStarted {
@@ -123,6 +143,15 @@ pub enum EventPayload {
         job_id: ConversionJobId,
         error: String,
     },
+    ConversionPaused {
+        job_id: ConversionJobId,
+    },
+    ConversionResumed {
+        job_id: ConversionJobId,
+    },
+    ConversionCancelled {
+        job_id: ConversionJobId,
+    },
 
     // -- Scan diagnostics ----------------------------------------------------
     LibraryScanError {
@@ -321,6 +350,10 @@ mod tests {
             EventPayload::JobProgress { job_id: JobId::new(), progress: 0.5, step: "encoding".into() },
             EventPayload::JobCompleted { job_id: JobId::new() },
             EventPayload::JobFailed { job_id: JobId::new(), error: "err".into() },
+            EventPayload::JobPaused { job_id: JobId::new() },
+            EventPayload::JobResumed { job_id: JobId::new() },
+            EventPayload::JobCancelled { job_id: JobId::new() },
+            EventPayload::JobLog { job_id: JobId::new(), kind: "command".into(), action: Some("Remux".into()), command: Some("ffmpeg -i in.mkv out.mp4".into()), exit_code: Some(0), stderr: None, duration_ms: Some(1500), message: None },
             EventPayload::LibraryScanStarted { library_id: LibraryId::new() },
             EventPayload::LibraryScanProgress { library_id: LibraryId::new(), files_found: 10, files_queued: 5, phase: "walking".into(), files_total: 20, files_processed: 10, items_to_enrich: 0, items_enriched: 0 },
             EventPayload::LibraryScanComplete { library_id: LibraryId::new(), files_found: 100, files_queued: 95, files_skipped: 3, errors: 2 },
@@ -334,6 +367,9 @@ mod tests {
             EventPayload::ConversionProgress { job_id: ConversionJobId::new(), progress: 0.75, encode_fps: Some(24.5), eta_secs: Some(120.0), bitrate: Some("5000kbits/s".into()), speed: Some("1.5x".into()), total_size: Some(1024000) },
             EventPayload::ConversionCompleted { job_id: ConversionJobId::new() },
             EventPayload::ConversionFailed { job_id: ConversionJobId::new(), error: "fail".into() },
+            EventPayload::ConversionPaused { job_id: ConversionJobId::new() },
+            EventPayload::ConversionResumed { job_id: ConversionJobId::new() },
+            EventPayload::ConversionCancelled { job_id: ConversionJobId::new() },
             EventPayload::LibraryScanError { library_id: LibraryId::new(), file_path: "/tmp/test.mkv".into(), message: "probe failed".into() },
             EventPayload::ItemEnrichmentQueued { item_id: ItemId::new(), library_id: LibraryId::new() },
             EventPayload::ItemEnriched { item_id: ItemId::new(), library_id: LibraryId::new() },
@@ -96,6 +96,8 @@ typed_id! {
     SubtitleTrackId,
     /// Unique identifier for an invitation.
     InvitationId,
+    /// Unique identifier for a file in the trash.
+    TrashId,
 }
 
 #[cfg(test)]
@@ -16,6 +16,11 @@ use std::fmt;
 pub enum Container {
     Mkv,
     Mp4,
+    Webm,
+    Avi,
+    /// MPEG transport stream (`.ts`, `.m2ts`, `.mts`).
+    #[serde(rename = "mpegts")]
+    MpegTs,
 }
 
 impl fmt::Display for Container {
@@ -23,6 +28,9 @@ impl fmt::Display for Container {
         match self {
             Self::Mkv => write!(f, "mkv"),
             Self::Mp4 => write!(f, "mp4"),
+            Self::Webm => write!(f, "webm"),
+            Self::Avi => write!(f, "avi"),
+            Self::MpegTs => write!(f, "mpegts"),
         }
     }
 }
@@ -217,6 +225,37 @@ impl fmt::Display for StreamType {
     }
 }
 
+// ---------------------------------------------------------------------------
+// SubtitleFormat
+// ---------------------------------------------------------------------------
+
+/// Text subtitle format for converted or extracted subtitles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitleFormat {
+    /// SubRip.
+    #[default]
+    Srt,
+    /// WebVTT.
+    Vtt,
+}
+
+impl SubtitleFormat {
+    /// File extension for sidecar files, without the dot.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Srt => "srt",
+            Self::Vtt => "vtt",
+        }
+    }
+}
+
+impl fmt::Display for SubtitleFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.extension())
+    }
+}
+
 // ---------------------------------------------------------------------------
 // ImageType
 // ---------------------------------------------------------------------------
@@ -256,6 +295,19 @@ mod tests {
         assert_eq!(json, r#""mkv""#);
         let back: Container = serde_json::from_str(&json).unwrap();
         assert_eq!(back, Container::Mkv);
+
+        assert_eq!(Container::MpegTs.to_string(), "mpegts");
+        let json = serde_json::to_string(&Container::MpegTs).unwrap();
+        assert_eq!(json, r#""mpegts""#);
+        let back: Container = serde_json::from_str(r#""webm""#).unwrap();
+        assert_eq!(back, Container::Webm);
+    }
+
+    #[test]
+    fn subtitle_format_serde() {
+        let back: SubtitleFormat = serde_json::from_str(r#""vtt""#).unwrap();
+        assert_eq!(back, SubtitleFormat::Vtt);
+        assert_eq!(SubtitleFormat::default().to_string(), "srt");
     }
 
     #[test]
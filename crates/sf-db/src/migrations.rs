@@ -334,6 +334,53 @@ ALTER TABLE media_files ADD COLUMN hls_prepared BLOB;
 DROP TABLE IF EXISTS hls_cache;
 "#;
 
+/// V13: Store the structured pipeline result (per-action summaries and details) on jobs.
+const V13_JOB_RESULT: &str = r#"
+ALTER TABLE jobs ADD COLUMN result TEXT;
+"#;
+
+/// V14: Record each job's resource class so workers can honour per-class concurrency caps.
+const V14_JOB_RESOURCE_CLASS: &str = r#"
+ALTER TABLE jobs ADD COLUMN resource_class TEXT;
+"#;
+
+/// V15: Per-job execution log (action start/end, tool command lines, stderr).
+const V15_JOB_LOGS: &str = r#"
+CREATE TABLE job_logs (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    job_id      TEXT NOT NULL REFERENCES jobs(id) ON DELETE CASCADE,
+    kind        TEXT NOT NULL,
+    action      TEXT,
+    command     TEXT,
+    exit_code   INTEGER,
+    stderr      TEXT,
+    duration_ms INTEGER,
+    message     TEXT,
+    created_at  TEXT NOT NULL
+);
+CREATE INDEX idx_job_logs_job ON job_logs(job_id, id);
+"#;
+
+/// V16: Originals replaced by a pipeline, kept in the trash until they expire or are purged.
+const V16_TRASH: &str = r#"
+CREATE TABLE trash (
+    id            TEXT PRIMARY KEY,
+    original_path TEXT NOT NULL,
+    trash_path    TEXT NOT NULL,
+    library_id    TEXT REFERENCES libraries(id) ON DELETE SET NULL,
+    job_id        TEXT REFERENCES jobs(id) ON DELETE SET NULL,
+    size_bytes    INTEGER NOT NULL,
+    created_at    TEXT NOT NULL,
+    expires_at    TEXT
+);
+CREATE INDEX idx_trash_created ON trash(created_at);
+"#;
+
+/// V17: Jobs started via "run now" skip the processing-window check.
+const V17_JOB_RUN_NOW: &str = r#"
+ALTER TABLE jobs ADD COLUMN run_now INTEGER NOT NULL DEFAULT 0;
+"#;
+
 /// Ordered list of (version, sql) pairs.
 const MIGRATIONS: &[(i64, &str)] = &[
     (1, V1_INITIAL),
@@ -348,6 +395,11 @@ const MIGRATIONS: &[(i64, &str)] = &[
     (10, V10_CONVERSION_STATS),
     (11, V11_SCAN_STATUS),
     (12, V12_HLS_PREPARED),
+    (13, V13_JOB_RESULT),
+    (14, V14_JOB_RESOURCE_CLASS),
+    (15, V15_JOB_LOGS),
+    (16, V16_TRASH),
+    (17, V17_JOB_RUN_NOW),
 ];
 
 /// Run all pending migrations on `conn`.
@@ -423,10 +475,12 @@ mod tests {
             "media_files",
             "images",
             "jobs",
+            "job_logs",
             "conversion_jobs",
             "playback",
             "favorites",
             "invitations",
+            "trash",
             "schema_migrations",
         ];
         for t in &tables {
@@ -5,7 +5,7 @@
 
 use sf_core::{
     ConversionJobId, ImageId, InvitationId, ItemId, JobId, LibraryId, MediaFileId, SessionId,
-    SubtitleTrackId, UserId,
+    SubtitleTrackId, TrashId, UserId,
 };
 use uuid::Uuid;
 
@@ -312,6 +312,13 @@ pub struct Job {
     pub started_at: Option<String>,
     pub completed_at: Option<String>,
     pub scheduled_for: Option<String>,
+    /// Structured pipeline result, recorded when the job completes.
+    pub result: Option<serde_json::Value>,
+    /// Resource class (`encode`, `remux`, `light`), set once the job's rules
+    /// have been matched.
+    pub resource_class: Option<String>,
+    /// Started via "run now": runs even outside the processing windows.
+    pub run_now: bool,
 }
 
 impl Job {
@@ -336,6 +343,11 @@ impl Job {
             started_at: row.get(15)?,
             completed_at: row.get(16)?,
             scheduled_for: row.get(17)?,
+            result: row
+                .get::<_, Option<String>>(18)?
+                .and_then(|s| serde_json::from_str(&s).ok()),
+            resource_class: row.get(19)?,
+            run_now: row.get::<_, bool>(20).unwrap_or(false),
         })
     }
 }
@@ -391,6 +403,81 @@ impl ConversionJob {
     }
 }
 
+// ---------------------------------------------------------------------------
+// JobLog
+// ---------------------------------------------------------------------------
+
+/// One entry of a job's execution log.
+#[derive(Debug, Clone)]
+pub struct JobLog {
+    pub id: i64,
+    pub job_id: JobId,
+    /// `action_started`, `action_finished` or `command`.
+    pub kind: String,
+    pub action: Option<String>,
+    /// The tool command line, for `command` entries.
+    pub command: Option<String>,
+    pub exit_code: Option<i32>,
+    /// Tail of the tool's stderr.
+    pub stderr: Option<String>,
+    pub duration_ms: Option<i64>,
+    /// Error message, if the action or command failed.
+    pub message: Option<String>,
+    pub created_at: String,
+}
+
+impl JobLog {
+    pub fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            job_id: parse_id(row, 1)?,
+            kind: row.get(2)?,
+            action: row.get(3)?,
+            command: row.get(4)?,
+            exit_code: row.get(5)?,
+            stderr: row.get(6)?,
+            duration_ms: row.get(7)?,
+            message: row.get(8)?,
+            created_at: row.get(9)?,
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// TrashEntry
+// ---------------------------------------------------------------------------
+
+/// An original file moved to the trash when a pipeline replaced it.
+#[derive(Debug, Clone)]
+pub struct TrashEntry {
+    pub id: TrashId,
+    /// Where the file lived, and where a restore puts it back.
+    pub original_path: String,
+    pub trash_path: String,
+    pub library_id: Option<LibraryId>,
+    pub job_id: Option<JobId>,
+    pub size_bytes: i64,
+    pub created_at: String,
+    /// When retention purges the file; `None` keeps it until purged by hand
+    /// or by the trash size limit.
+    pub expires_at: Option<String>,
+}
+
+impl TrashEntry {
+    pub fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: parse_id(row, 0)?,
+            original_path: row.get(1)?,
+            trash_path: row.get(2)?,
+            library_id: parse_opt_id(row, 3)?,
+            job_id: parse_opt_id(row, 4)?,
+            size_bytes: row.get(5)?,
+            created_at: row.get(6)?,
+            expires_at: row.get(7)?,
+        })
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Favorite
 // ---------------------------------------------------------------------------
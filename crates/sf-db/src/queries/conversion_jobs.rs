@@ -1,6 +1,6 @@
 //! Conversion job queue operations.
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use rusqlite::Connection;
 use sf_core::{ConversionJobId, Error, ItemId, MediaFileId, Result};
 
@@ -129,6 +129,39 @@ pub fn dequeue_next_conversion(
     }
 }
 
+/// Keep a running conversion's lock fresh so [`reap_stale_conversions`]
+/// leaves it alone.
+///
+/// Only succeeds while `worker` still holds the lock.
+pub fn touch_conversion_lock(
+    conn: &Connection,
+    id: ConversionJobId,
+    worker: &str,
+) -> Result<bool> {
+    let now = Utc::now().to_rfc3339();
+    let n = conn
+        .execute(
+            "UPDATE conversion_jobs SET locked_at=?1
+             WHERE id=?2 AND status='processing' AND locked_by=?3",
+            rusqlite::params![now, id.to_string(), worker],
+        )
+        .map_err(|e| Error::database(e.to_string()))?;
+    Ok(n > 0)
+}
+
+/// Requeue processing conversions whose lock was last refreshed before
+/// `cutoff`, i.e. whose worker died without finishing them. Returns the
+/// number of conversions requeued.
+pub fn reap_stale_conversions(conn: &Connection, cutoff: DateTime<Utc>) -> Result<usize> {
+    conn.execute(
+        "UPDATE conversion_jobs SET status='queued', progress_pct=0.0, encode_fps=NULL,
+            eta_secs=NULL, locked_by=NULL, locked_at=NULL, started_at=NULL
+         WHERE status='processing' AND locked_at < ?1",
+        [cutoff.to_rfc3339()],
+    )
+    .map_err(|e| Error::database(e.to_string()))
+}
+
 /// Update conversion progress.
 pub fn update_conversion_progress(
     conn: &Connection,
@@ -362,6 +395,24 @@ mod tests {
         assert_eq!(failed.error.as_deref(), Some("encode error"));
     }
 
+    #[test]
+    fn reap_requeues_stale_conversion() {
+        let (conn, item_id, mf_id) = setup();
+        let job = create_conversion_job(&conn, item_id, mf_id).unwrap();
+        dequeue_next_conversion(&conn, "w1").unwrap();
+
+        let before = Utc::now() - chrono::Duration::seconds(60);
+        assert_eq!(reap_stale_conversions(&conn, before).unwrap(), 0);
+        assert!(touch_conversion_lock(&conn, job.id, "w1").unwrap());
+
+        let after = Utc::now() + chrono::Duration::seconds(60);
+        assert_eq!(reap_stale_conversions(&conn, after).unwrap(), 1);
+        let requeued = get_conversion_job(&conn, job.id).unwrap().unwrap();
+        assert_eq!(requeued.status, "queued");
+        assert!(requeued.locked_by.is_none());
+        assert!(!touch_conversion_lock(&conn, job.id, "w1").unwrap());
+    }
+
     #[test]
     fn has_active_conversion() {
         let (conn, item_id, mf_id) = setup();
@@ -0,0 +1,128 @@
+//! Per-job execution log operations.
+
+use chrono::Utc;
+use rusqlite::Connection;
+use sf_core::{Error, JobId, Result};
+
+use crate::models::JobLog;
+
+const COLS: &str =
+    "id, job_id, kind, action, command, exit_code, stderr, duration_ms, message, created_at";
+
+/// Fields of a log entry to append with [`append_job_log`].
+#[derive(Debug, Clone, Default)]
+pub struct NewJobLog<'a> {
+    pub kind: &'a str,
+    pub action: Option<&'a str>,
+    pub command: Option<&'a str>,
+    pub exit_code: Option<i32>,
+    pub stderr: Option<&'a str>,
+    pub duration_ms: Option<i64>,
+    pub message: Option<&'a str>,
+}
+
+/// Append an entry to a job's log.
+pub fn append_job_log(conn: &Connection, job_id: JobId, entry: &NewJobLog) -> Result<JobLog> {
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO job_logs (job_id, kind, action, command, exit_code, stderr, duration_ms,
+            message, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        rusqlite::params![
+            job_id.to_string(),
+            entry.kind,
+            entry.action,
+            entry.command,
+            entry.exit_code,
+            entry.stderr,
+            entry.duration_ms,
+            entry.message,
+            &now,
+        ],
+    )
+    .map_err(|e| Error::database(e.to_string()))?;
+
+    Ok(JobLog {
+        id: conn.last_insert_rowid(),
+        job_id,
+        kind: entry.kind.to_string(),
+        action: entry.action.map(String::from),
+        command: entry.command.map(String::from),
+        exit_code: entry.exit_code,
+        stderr: entry.stderr.map(String::from),
+        duration_ms: entry.duration_ms,
+        message: entry.message.map(String::from),
+        created_at: now,
+    })
+}
+
+/// List a job's log entries in the order they were written.
+pub fn list_job_logs(conn: &Connection, job_id: JobId) -> Result<Vec<JobLog>> {
+    let q = format!("SELECT {COLS} FROM job_logs WHERE job_id = ?1 ORDER BY id ASC");
+    let mut stmt = conn
+        .prepare(&q)
+        .map_err(|e| Error::database(e.to_string()))?;
+    let rows = stmt
+        .query_map([job_id.to_string()], JobLog::from_row)
+        .map_err(|e| Error::database(e.to_string()))?
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| Error::database(e.to_string()))?;
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::init_memory_pool;
+    use crate::queries::jobs::create_job;
+
+    #[test]
+    fn append_and_list() {
+        let pool = init_memory_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let job = create_job(&conn, "/a.mkv", "a.mkv", None, 0).unwrap();
+        let other = create_job(&conn, "/b.mkv", "b.mkv", None, 0).unwrap();
+
+        append_job_log(
+            &conn,
+            job.id,
+            &NewJobLog {
+                kind: "action_started",
+                action: Some("Remux"),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let cmd = append_job_log(
+            &conn,
+            job.id,
+            &NewJobLog {
+                kind: "command",
+                action: Some("Remux"),
+                command: Some("ffmpeg -i a.mkv out.mp4"),
+                exit_code: Some(1),
+                stderr: Some("Invalid data found"),
+                duration_ms: Some(120),
+                message: Some("exited with status 1"),
+            },
+        )
+        .unwrap();
+        append_job_log(
+            &conn,
+            other.id,
+            &NewJobLog {
+                kind: "action_started",
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let logs = list_job_logs(&conn, job.id).unwrap();
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[0].kind, "action_started");
+        assert_eq!(logs[1].id, cmd.id);
+        assert_eq!(logs[1].command.as_deref(), Some("ffmpeg -i a.mkv out.mp4"));
+        assert_eq!(logs[1].exit_code, Some(1));
+        assert_eq!(logs[1].duration_ms, Some(120));
+    }
+}
@@ -1,6 +1,6 @@
 //! Job queue operations.
 
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use rusqlite::Connection;
 use sf_core::{Error, JobId, Result};
 
@@ -8,7 +8,8 @@ use crate::models::Job;
 
 const COLS: &str = "id, file_path, file_name, status, rule_name, progress,
     current_step, error, source, retry_count, max_retries, priority,
-    locked_by, locked_at, created_at, started_at, completed_at, scheduled_for";
+    locked_by, locked_at, created_at, started_at, completed_at, scheduled_for, result,
+    resource_class, run_now";
 
 /// Create a new job.
 pub fn create_job(
@@ -47,6 +48,9 @@ pub fn create_job(
         started_at: None,
         completed_at: None,
         scheduled_for: None,
+        result: None,
+        resource_class: None,
+        run_now: false,
     })
 }
 
@@ -130,7 +134,8 @@ pub fn update_job_progress(
 /// Atomically dequeue the next queued job.
 ///
 /// Sets `status='processing'`, `locked_by`, `locked_at`, `started_at`.
-/// Uses a sub-select to pick the highest-priority, oldest job.
+/// Uses a sub-select to pick the highest-priority, oldest job. Jobs whose
+/// `scheduled_for` lies in the future are skipped.
 pub fn dequeue_next(conn: &Connection, worker: &str) -> Result<Option<Job>> {
     let now = Utc::now().to_rfc3339();
 
@@ -139,6 +144,7 @@ pub fn dequeue_next(conn: &Connection, worker: &str) -> Result<Option<Job>> {
         "UPDATE jobs SET status='processing', locked_by=?1, locked_at=?2, started_at=?2
          WHERE id = (
              SELECT id FROM jobs WHERE status='queued'
+               AND (scheduled_for IS NULL OR scheduled_for <= ?2)
              ORDER BY priority DESC, created_at ASC LIMIT 1
          )
          RETURNING {COLS}"
@@ -152,30 +158,220 @@ pub fn dequeue_next(conn: &Connection, worker: &str) -> Result<Option<Job>> {
     }
 }
 
-/// Mark a job as failed.
+/// Atomically dequeue the next queued job whose resource class is one of
+/// `classes`, or that has not been classified yet.
+///
+/// Like [`dequeue_next`], but lets a worker skip jobs whose class is already
+/// running at its concurrency cap.
+pub fn dequeue_next_in(conn: &Connection, worker: &str, classes: &[&str]) -> Result<Option<Job>> {
+    let now = Utc::now().to_rfc3339();
+    let classes = serde_json::to_string(classes).map_err(|e| Error::Internal(e.to_string()))?;
+
+    let q = format!(
+        "UPDATE jobs SET status='processing', locked_by=?1, locked_at=?2, started_at=?2
+         WHERE id = (
+             SELECT id FROM jobs WHERE status='queued'
+               AND (scheduled_for IS NULL OR scheduled_for <= ?2)
+               AND (resource_class IS NULL
+                    OR resource_class IN (SELECT value FROM json_each(?3)))
+             ORDER BY priority DESC, created_at ASC LIMIT 1
+         )
+         RETURNING {COLS}"
+    );
+
+    let result = conn.query_row(&q, rusqlite::params![worker, &now, classes], Job::from_row);
+    match result {
+        Ok(j) => Ok(Some(j)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(Error::database(e.to_string())),
+    }
+}
+
+/// Record a job's resource class.
+pub fn set_job_resource_class(conn: &Connection, id: JobId, class: &str) -> Result<bool> {
+    let n = conn
+        .execute(
+            "UPDATE jobs SET resource_class=?1 WHERE id=?2",
+            rusqlite::params![class, id.to_string()],
+        )
+        .map_err(|e| Error::database(e.to_string()))?;
+    Ok(n > 0)
+}
+
+/// Hand a claimed job back to the queue without counting a retry.
+///
+/// Only succeeds while `worker` still holds the lock.
+pub fn release_job(conn: &Connection, id: JobId, worker: &str) -> Result<bool> {
+    let n = conn
+        .execute(
+            "UPDATE jobs SET status='queued', locked_by=NULL, locked_at=NULL, started_at=NULL
+             WHERE id=?1 AND status='processing' AND locked_by=?2",
+            rusqlite::params![id.to_string(), worker],
+        )
+        .map_err(|e| Error::database(e.to_string()))?;
+    Ok(n > 0)
+}
+
+/// Hand a claimed job back to the queue until `until`, e.g. the start of the
+/// next processing window. Does not count a retry.
+///
+/// Only succeeds while `worker` still holds the lock.
+pub fn defer_job(conn: &Connection, id: JobId, worker: &str, until: DateTime<Utc>) -> Result<bool> {
+    let n = conn
+        .execute(
+            "UPDATE jobs SET status='queued', scheduled_for=?1, locked_by=NULL, locked_at=NULL,
+                started_at=NULL
+             WHERE id=?2 AND status='processing' AND locked_by=?3",
+            rusqlite::params![until.to_rfc3339(), id.to_string(), worker],
+        )
+        .map_err(|e| Error::database(e.to_string()))?;
+    Ok(n > 0)
+}
+
+/// Make a queued job due immediately and exempt it from processing windows.
+///
+/// Returns `false` if the job is not queued.
+pub fn run_job_now(conn: &Connection, id: JobId) -> Result<bool> {
+    let now = Utc::now().to_rfc3339();
+    let n = conn
+        .execute(
+            "UPDATE jobs SET scheduled_for=?1, run_now=1 WHERE id=?2 AND status='queued'",
+            rusqlite::params![now, id.to_string()],
+        )
+        .map_err(|e| Error::database(e.to_string()))?;
+    Ok(n > 0)
+}
+
+/// List queued jobs that are deferred to a later start, soonest first.
+pub fn list_deferred_jobs(conn: &Connection, limit: i64) -> Result<Vec<Job>> {
+    let now = Utc::now().to_rfc3339();
+    let q = format!(
+        "SELECT {COLS} FROM jobs WHERE status='queued' AND scheduled_for > ?1
+         ORDER BY scheduled_for ASC, priority DESC LIMIT ?2"
+    );
+    let mut stmt = conn.prepare(&q).map_err(|e| Error::database(e.to_string()))?;
+    let rows = stmt
+        .query_map(rusqlite::params![now, limit], Job::from_row)
+        .map_err(|e| Error::database(e.to_string()))?
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| Error::database(e.to_string()))?;
+    Ok(rows)
+}
+
+/// Keep a running job's lock fresh so [`reap_stale_jobs`] leaves it alone.
+///
+/// Only succeeds while `worker` still holds the lock.
+pub fn touch_job_lock(conn: &Connection, id: JobId, worker: &str) -> Result<bool> {
+    let now = Utc::now().to_rfc3339();
+    let n = conn
+        .execute(
+            "UPDATE jobs SET locked_at=?1 WHERE id=?2 AND status='processing' AND locked_by=?3",
+            rusqlite::params![now, id.to_string(), worker],
+        )
+        .map_err(|e| Error::database(e.to_string()))?;
+    Ok(n > 0)
+}
+
+/// Requeue processing jobs whose lock was last refreshed before `cutoff`,
+/// i.e. whose worker died without finishing them.
+///
+/// Each recovery counts as a retry; jobs that are out of retries are failed
+/// instead. Returns the number of jobs requeued or failed.
+pub fn reap_stale_jobs(conn: &Connection, cutoff: DateTime<Utc>) -> Result<usize> {
+    let now = Utc::now().to_rfc3339();
+    let cutoff = cutoff.to_rfc3339();
+    let requeued = conn
+        .execute(
+            "UPDATE jobs SET status='queued', error='Worker lock expired', locked_by=NULL,
+                locked_at=NULL, started_at=NULL, retry_count=retry_count+1
+             WHERE status='processing' AND locked_at < ?1 AND retry_count < max_retries",
+            [&cutoff],
+        )
+        .map_err(|e| Error::database(e.to_string()))?;
+    let failed = conn
+        .execute(
+            "UPDATE jobs SET status='failed', error='Worker lock expired', locked_by=NULL,
+                locked_at=NULL, completed_at=?2
+             WHERE status='processing' AND locked_at < ?1",
+            rusqlite::params![cutoff, now],
+        )
+        .map_err(|e| Error::database(e.to_string()))?;
+    Ok(requeued + failed)
+}
+
+/// Requeue a failed job for an automatic retry at `at`, keeping its error.
+///
+/// `at` only delays the retry: a heavy job is still held to the processing
+/// windows when the retry is claimed. Returns `false` if the job has no
+/// retries left.
+pub fn schedule_retry(conn: &Connection, id: JobId, error: &str, at: DateTime<Utc>) -> Result<bool> {
+    let n = conn
+        .execute(
+            "UPDATE jobs SET status='queued', error=?1, scheduled_for=?2, locked_by=NULL,
+                locked_at=NULL, started_at=NULL, completed_at=NULL, retry_count=retry_count+1
+             WHERE id=?3 AND retry_count < max_retries AND status != 'cancelled'",
+            rusqlite::params![error, at.to_rfc3339(), id.to_string()],
+        )
+        .map_err(|e| Error::database(e.to_string()))?;
+    Ok(n > 0)
+}
+
+/// Mark a job as failed, unless it has been cancelled.
 pub fn fail_job(conn: &Connection, id: JobId, error: &str) -> Result<bool> {
     let now = Utc::now().to_rfc3339();
     let n = conn
         .execute(
-            "UPDATE jobs SET status='failed', error=?1, completed_at=?2 WHERE id=?3",
+            "UPDATE jobs SET status='failed', error=?1, completed_at=?2
+             WHERE id=?3 AND status != 'cancelled'",
             rusqlite::params![error, now, id.to_string()],
         )
         .map_err(|e| Error::database(e.to_string()))?;
     Ok(n > 0)
 }
 
+/// Cancel a queued or processing job.
+///
+/// Returns `false` if the job has already finished.
+pub fn cancel_job(conn: &Connection, id: JobId) -> Result<bool> {
+    let now = Utc::now().to_rfc3339();
+    let n = conn
+        .execute(
+            "UPDATE jobs SET status='cancelled', error='Cancelled by user', locked_by=NULL,
+                locked_at=NULL, completed_at=?1
+             WHERE id=?2 AND status IN ('queued', 'processing')",
+            rusqlite::params![now, id.to_string()],
+        )
+        .map_err(|e| Error::database(e.to_string()))?;
+    Ok(n > 0)
+}
+
 /// Mark a job as completed.
+///
+/// Returns `false` if the job has been cancelled meanwhile; a cancel is never
+/// overwritten.
 pub fn complete_job(conn: &Connection, id: JobId) -> Result<bool> {
     let now = Utc::now().to_rfc3339();
     let n = conn
         .execute(
-            "UPDATE jobs SET status='completed', progress=1.0, completed_at=?1 WHERE id=?2",
+            "UPDATE jobs SET status='completed', progress=1.0, completed_at=?1
+             WHERE id=?2 AND status != 'cancelled'",
             rusqlite::params![now, id.to_string()],
         )
         .map_err(|e| Error::database(e.to_string()))?;
     Ok(n > 0)
 }
 
+/// Record the structured pipeline result of a job.
+pub fn set_job_result(conn: &Connection, id: JobId, result: &serde_json::Value) -> Result<bool> {
+    let n = conn
+        .execute(
+            "UPDATE jobs SET result=?1 WHERE id=?2",
+            rusqlite::params![result.to_string(), id.to_string()],
+        )
+        .map_err(|e| Error::database(e.to_string()))?;
+    Ok(n > 0)
+}
+
 /// Check if a file already has a queued or processing job.
 pub fn has_active_job_for_path(conn: &Connection, file_path: &str) -> Result<bool> {
     let count: i64 = conn
@@ -305,4 +501,159 @@ mod tests {
         assert!((found.progress - 0.5).abs() < f64::EPSILON);
         assert_eq!(found.current_step.as_deref(), Some("remux"));
     }
+
+    #[test]
+    fn dequeue_in_skips_capped_classes() {
+        let pool = init_memory_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let encode = create_job(&conn, "/enc.mkv", "enc.mkv", None, 10).unwrap();
+        set_job_resource_class(&conn, encode.id, "encode").unwrap();
+        let remux = create_job(&conn, "/remux.mkv", "remux.mkv", None, 5).unwrap();
+        set_job_resource_class(&conn, remux.id, "remux").unwrap();
+        let unclassified = create_job(&conn, "/new.mkv", "new.mkv", None, 0).unwrap();
+
+        // Encode slots are full: the higher-priority encode job is skipped.
+        let job = dequeue_next_in(&conn, "w1", &["remux", "light"]).unwrap().unwrap();
+        assert_eq!(job.id, remux.id);
+        assert_eq!(job.resource_class.as_deref(), Some("remux"));
+
+        // Unclassified jobs are always eligible.
+        let job = dequeue_next_in(&conn, "w2", &["light"]).unwrap().unwrap();
+        assert_eq!(job.id, unclassified.id);
+        assert!(dequeue_next_in(&conn, "w2", &["light"]).unwrap().is_none());
+
+        // Releasing requires holding the lock and does not count a retry.
+        assert!(!release_job(&conn, job.id, "w1").unwrap());
+        assert!(release_job(&conn, job.id, "w2").unwrap());
+        let released = get_job(&conn, job.id).unwrap().unwrap();
+        assert_eq!(released.status, "queued");
+        assert_eq!(released.retry_count, 0);
+        assert!(released.locked_by.is_none());
+    }
+
+    #[test]
+    fn result_round_trip() {
+        let pool = init_memory_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let job = create_job(&conn, "/r.mkv", "r.mkv", None, 0).unwrap();
+        assert!(get_job(&conn, job.id).unwrap().unwrap().result.is_none());
+
+        let result = serde_json::json!({"actions": [{"action": "Downmix Audio"}]});
+        assert!(set_job_result(&conn, job.id, &result).unwrap());
+        let found = get_job(&conn, job.id).unwrap().unwrap();
+        assert_eq!(found.result, Some(result));
+    }
+
+    #[test]
+    fn deferred_jobs_wait_until_due() {
+        let pool = init_memory_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let job = create_job(&conn, "/enc.mkv", "enc.mkv", None, 0).unwrap();
+
+        let claimed = dequeue_next(&conn, "w1").unwrap().unwrap();
+        let later = Utc::now() + chrono::Duration::hours(3);
+        assert!(!defer_job(&conn, job.id, "w2", later).unwrap());
+        assert!(defer_job(&conn, claimed.id, "w1", later).unwrap());
+
+        let deferred = get_job(&conn, job.id).unwrap().unwrap();
+        assert_eq!(deferred.status, "queued");
+        assert_eq!(deferred.retry_count, 0);
+        assert!(dequeue_next(&conn, "w1").unwrap().is_none());
+        assert!(dequeue_next_in(&conn, "w1", &["encode"]).unwrap().is_none());
+        assert_eq!(list_deferred_jobs(&conn, 10).unwrap().len(), 1);
+
+        assert!(!deferred.run_now);
+        assert!(run_job_now(&conn, job.id).unwrap());
+        assert!(list_deferred_jobs(&conn, 10).unwrap().is_empty());
+        let next = dequeue_next(&conn, "w1").unwrap().unwrap();
+        assert_eq!(next.id, job.id);
+        assert!(next.run_now);
+        assert!(!run_job_now(&conn, job.id).unwrap());
+    }
+
+    #[test]
+    fn scheduled_retry_waits_for_backoff() {
+        let pool = init_memory_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let job = create_job(&conn, "/t.mkv", "t.mkv", None, 0).unwrap();
+        dequeue_next(&conn, "w1").unwrap().unwrap();
+
+        let at = Utc::now() + chrono::Duration::seconds(60);
+        assert!(schedule_retry(&conn, job.id, "Tool error [ffmpeg]: timed out", at).unwrap());
+        let queued = get_job(&conn, job.id).unwrap().unwrap();
+        assert_eq!(queued.status, "queued");
+        assert_eq!(queued.retry_count, 1);
+        assert_eq!(queued.error.as_deref(), Some("Tool error [ffmpeg]: timed out"));
+        assert!(dequeue_next(&conn, "w1").unwrap().is_none());
+
+        // No retries left.
+        conn.execute("UPDATE jobs SET retry_count = max_retries", []).unwrap();
+        assert!(!schedule_retry(&conn, job.id, "again", at).unwrap());
+    }
+
+    #[test]
+    fn reap_requeues_stale_locks() {
+        let pool = init_memory_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let stale = create_job(&conn, "/a.mkv", "a.mkv", None, 1).unwrap();
+        let dead = create_job(&conn, "/b.mkv", "b.mkv", None, 1).unwrap();
+        let live = create_job(&conn, "/c.mkv", "c.mkv", None, 0).unwrap();
+        for _ in 0..3 {
+            dequeue_next(&conn, "w1").unwrap().unwrap();
+        }
+        conn.execute(
+            "UPDATE jobs SET retry_count = max_retries WHERE id = ?1",
+            [dead.id.to_string()],
+        )
+        .unwrap();
+
+        // Refresh one lock, then reap everything locked before that.
+        let cutoff = Utc::now();
+        assert!(touch_job_lock(&conn, live.id, "w1").unwrap());
+        assert!(!touch_job_lock(&conn, live.id, "w2").unwrap());
+        assert_eq!(reap_stale_jobs(&conn, cutoff).unwrap(), 2);
+
+        let stale = get_job(&conn, stale.id).unwrap().unwrap();
+        assert_eq!(stale.status, "queued");
+        assert_eq!(stale.retry_count, 1);
+        assert!(stale.locked_by.is_none());
+        assert_eq!(get_job(&conn, dead.id).unwrap().unwrap().status, "failed");
+        assert_eq!(get_job(&conn, live.id).unwrap().unwrap().status, "processing");
+    }
+
+    #[test]
+    fn cancel_only_unfinished_jobs() {
+        let pool = init_memory_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let job = create_job(&conn, "/a.mkv", "a.mkv", None, 0).unwrap();
+        dequeue_next(&conn, "w1").unwrap().unwrap();
+
+        assert!(cancel_job(&conn, job.id).unwrap());
+        let job = get_job(&conn, job.id).unwrap().unwrap();
+        assert_eq!(job.status, "cancelled");
+        assert_eq!(job.error.as_deref(), Some("Cancelled by user"));
+        assert!(job.locked_by.is_none());
+        assert!(!touch_job_lock(&conn, job.id, "w1").unwrap());
+
+        // Already finished.
+        assert!(!cancel_job(&conn, job.id).unwrap());
+    }
+
+    #[test]
+    fn cancel_is_not_overwritten() {
+        let pool = init_memory_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let job = create_job(&conn, "/a.mkv", "a.mkv", None, 0).unwrap();
+        dequeue_next(&conn, "w1").unwrap().unwrap();
+        assert!(cancel_job(&conn, job.id).unwrap());
+
+        // The worker finishing or failing afterwards leaves it cancelled.
+        assert!(!complete_job(&conn, job.id).unwrap());
+        assert!(!fail_job(&conn, job.id, "Tool error [ffmpeg]: killed").unwrap());
+        let at = Utc::now();
+        assert!(!schedule_retry(&conn, job.id, "Tool error [ffmpeg]: killed", at).unwrap());
+        let job = get_job(&conn, job.id).unwrap().unwrap();
+        assert_eq!(job.status, "cancelled");
+        assert_eq!(job.error.as_deref(), Some("Cancelled by user"));
+    }
 }
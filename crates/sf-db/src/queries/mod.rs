@@ -6,9 +6,11 @@ pub mod favorites;
 pub mod images;
 pub mod invitations;
 pub mod items;
+pub mod job_logs;
 pub mod jobs;
 pub mod libraries;
 pub mod media_files;
 pub mod playback;
 pub mod subtitle_tracks;
+pub mod trash;
 pub mod users;
@@ -0,0 +1,212 @@
+//! Trash operations.
+
+use chrono::{DateTime, Utc};
+use rusqlite::Connection;
+use sf_core::{Error, JobId, LibraryId, Result, TrashId};
+
+use crate::models::TrashEntry;
+
+const COLS: &str =
+    "id, original_path, trash_path, library_id, job_id, size_bytes, created_at, expires_at";
+
+/// Fields of a file moved to the trash, for [`create_trash_entry`].
+#[derive(Debug, Clone)]
+pub struct NewTrashEntry<'a> {
+    pub id: TrashId,
+    pub original_path: &'a str,
+    pub trash_path: &'a str,
+    pub library_id: Option<LibraryId>,
+    pub job_id: Option<JobId>,
+    pub size_bytes: i64,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Record a file moved to the trash.
+pub fn create_trash_entry(conn: &Connection, entry: &NewTrashEntry) -> Result<TrashEntry> {
+    let now = Utc::now().to_rfc3339();
+    let expires_at = entry.expires_at.map(|t| t.to_rfc3339());
+    conn.execute(
+        "INSERT INTO trash (id, original_path, trash_path, library_id, job_id, size_bytes,
+            created_at, expires_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        rusqlite::params![
+            entry.id.to_string(),
+            entry.original_path,
+            entry.trash_path,
+            entry.library_id.map(|id| id.to_string()),
+            entry.job_id.map(|id| id.to_string()),
+            entry.size_bytes,
+            &now,
+            &expires_at,
+        ],
+    )
+    .map_err(|e| Error::database(e.to_string()))?;
+
+    Ok(TrashEntry {
+        id: entry.id,
+        original_path: entry.original_path.to_string(),
+        trash_path: entry.trash_path.to_string(),
+        library_id: entry.library_id,
+        job_id: entry.job_id,
+        size_bytes: entry.size_bytes,
+        created_at: now,
+        expires_at,
+    })
+}
+
+/// Get a trash entry by ID.
+pub fn get_trash_entry(conn: &Connection, id: TrashId) -> Result<Option<TrashEntry>> {
+    let q = format!("SELECT {COLS} FROM trash WHERE id = ?1");
+    match conn.query_row(&q, [id.to_string()], TrashEntry::from_row) {
+        Ok(entry) => Ok(Some(entry)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(Error::database(e.to_string())),
+    }
+}
+
+/// List every trash entry, newest first.
+pub fn list_trash_entries(conn: &Connection) -> Result<Vec<TrashEntry>> {
+    let q = format!("SELECT {COLS} FROM trash ORDER BY created_at DESC, rowid DESC");
+    query_entries(conn, &q, [])
+}
+
+/// List the entries whose expiry is at or before `now`.
+pub fn list_expired_trash_entries(
+    conn: &Connection,
+    now: DateTime<Utc>,
+) -> Result<Vec<TrashEntry>> {
+    let q = format!(
+        "SELECT {COLS} FROM trash WHERE expires_at IS NOT NULL AND expires_at <= ?1
+         ORDER BY created_at ASC, rowid ASC"
+    );
+    query_entries(conn, &q, [now.to_rfc3339()])
+}
+
+/// Total size of every file in the trash, in bytes.
+pub fn total_trash_size(conn: &Connection) -> Result<i64> {
+    conn.query_row(
+        "SELECT COALESCE(SUM(size_bytes), 0) FROM trash",
+        [],
+        |row| row.get(0),
+    )
+    .map_err(|e| Error::database(e.to_string()))
+}
+
+/// Delete a trash entry by ID. Returns true if a row was deleted.
+pub fn delete_trash_entry(conn: &Connection, id: TrashId) -> Result<bool> {
+    let n = conn
+        .execute("DELETE FROM trash WHERE id = ?1", [id.to_string()])
+        .map_err(|e| Error::database(e.to_string()))?;
+    Ok(n > 0)
+}
+
+fn query_entries<P: rusqlite::Params>(
+    conn: &Connection,
+    q: &str,
+    params: P,
+) -> Result<Vec<TrashEntry>> {
+    let mut stmt = conn
+        .prepare(q)
+        .map_err(|e| Error::database(e.to_string()))?;
+    let rows = stmt
+        .query_map(params, TrashEntry::from_row)
+        .map_err(|e| Error::database(e.to_string()))?
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| Error::database(e.to_string()))?;
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::init_memory_pool;
+    use crate::queries::libraries::{create_library, delete_library};
+    use chrono::Duration;
+
+    fn new_entry(path: &str, size: i64, expires_at: Option<DateTime<Utc>>) -> NewTrashEntry<'_> {
+        NewTrashEntry {
+            id: TrashId::new(),
+            original_path: path,
+            trash_path: path,
+            library_id: None,
+            job_id: None,
+            size_bytes: size,
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn create_list_and_delete() {
+        let pool = init_memory_pool().unwrap();
+        let conn = pool.get().unwrap();
+
+        let a = create_trash_entry(&conn, &new_entry("/m/a.mkv", 100, None)).unwrap();
+        let b = create_trash_entry(&conn, &new_entry("/m/b.mkv", 50, None)).unwrap();
+
+        let listed = list_trash_entries(&conn).unwrap();
+        assert_eq!(
+            listed.iter().map(|e| e.id).collect::<Vec<_>>(),
+            vec![b.id, a.id]
+        );
+        assert_eq!(total_trash_size(&conn).unwrap(), 150);
+
+        let got = get_trash_entry(&conn, a.id).unwrap().unwrap();
+        assert_eq!(got.original_path, "/m/a.mkv");
+        assert_eq!(got.size_bytes, 100);
+
+        assert!(delete_trash_entry(&conn, a.id).unwrap());
+        assert!(!delete_trash_entry(&conn, a.id).unwrap());
+        assert!(get_trash_entry(&conn, a.id).unwrap().is_none());
+        assert_eq!(total_trash_size(&conn).unwrap(), 50);
+    }
+
+    #[test]
+    fn expired_entries() {
+        let pool = init_memory_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let now = Utc::now();
+
+        let old = create_trash_entry(
+            &conn,
+            &new_entry("/m/old.mkv", 1, Some(now - Duration::hours(1))),
+        )
+        .unwrap();
+        create_trash_entry(
+            &conn,
+            &new_entry("/m/new.mkv", 1, Some(now + Duration::days(1))),
+        )
+        .unwrap();
+        create_trash_entry(&conn, &new_entry("/m/kept.mkv", 1, None)).unwrap();
+
+        let expired = list_expired_trash_entries(&conn, now).unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].id, old.id);
+    }
+
+    #[test]
+    fn deleting_library_keeps_entry() {
+        let pool = init_memory_pool().unwrap();
+        let conn = pool.get().unwrap();
+        let lib = create_library(
+            &conn,
+            "Movies",
+            "movies",
+            &["/m".to_string()],
+            &serde_json::json!({}),
+        )
+        .unwrap();
+
+        let entry = create_trash_entry(
+            &conn,
+            &NewTrashEntry {
+                library_id: Some(lib.id),
+                ..new_entry("/m/a.mkv", 1, None)
+            },
+        )
+        .unwrap();
+        delete_library(&conn, lib.id).unwrap();
+
+        let got = get_trash_entry(&conn, entry.id).unwrap().unwrap();
+        assert_eq!(got.library_id, None);
+    }
+}
@@ -12,8 +12,34 @@ pub enum Codec {
     Avc,
     /// H.265 / HEVC video codec.
     Hevc,
+    /// Dolby Vision over HEVC. `config` is the Dolby Vision configuration
+    /// record (`dvcC`/`dvvC` content); the `hvcC` stays in the track's codec
+    /// private data.
+    DolbyVision { config: Vec<u8> },
     /// AAC audio codec.
     Aac,
+    /// AC-3 (Dolby Digital) audio codec.
+    Ac3,
+    /// E-AC-3 (Dolby Digital Plus) audio codec.
+    Eac3,
+}
+
+impl Codec {
+    /// Whether this is a video codec.
+    pub fn is_video(&self) -> bool {
+        matches!(self, Codec::Avc | Codec::Hevc | Codec::DolbyVision { .. })
+    }
+}
+
+/// Dolby Vision profile from a `dvcC`/`dvvC` configuration record.
+pub(crate) fn dv_profile(config: &[u8]) -> u8 {
+    config.get(2).map_or(0, |b| b >> 1)
+}
+
+/// Whether a Dolby Vision configuration record signals a base layer that
+/// plays without Dolby Vision (HDR10, SDR or HLG), as in profile 8.
+pub(crate) fn dv_bl_compatible(config: &[u8]) -> bool {
+    config.get(4).is_some_and(|b| b >> 4 != 0)
 }
 
 // ---------------------------------------------------------------------------
@@ -303,16 +329,23 @@ pub(crate) fn write_video_stsd(
     height: u32,
     codec_private: &[u8],
 ) -> Vec<u8> {
+    // Dolby Vision with a compatible base layer keeps the `hvc1` sample
+    // entry so players without Dolby Vision still decode it; otherwise
+    // (profile 5) the entry is `dvh1`.
     let sample_entry_type: &[u8; 4] = match codec {
         Codec::Avc => b"avc1",
         Codec::Hevc => b"hvc1",
-        Codec::Aac => b"mp4a", // shouldn't happen for video
+        Codec::DolbyVision { config } if dv_bl_compatible(config) => b"hvc1",
+        Codec::DolbyVision { .. } => b"dvh1",
+        Codec::Aac | Codec::Ac3 | Codec::Eac3 => b"mp4a", // shouldn't happen for video
     };
 
     let codec_config_type: &[u8; 4] = match codec {
         Codec::Avc => b"avcC",
-        Codec::Hevc => b"hvcC",
+        Codec::Hevc | Codec::DolbyVision { .. } => b"hvcC",
         Codec::Aac => b"esds",
+        Codec::Ac3 => b"dac3",
+        Codec::Eac3 => b"dec3",
     };
 
     // Visual sample entry content
@@ -348,6 +381,16 @@ pub(crate) fn write_video_stsd(
         entry.extend_from_slice(&config_box);
     }
 
+    // Dolby Vision configuration box: dvcC up to profile 7, dvvC above.
+    if let Codec::DolbyVision { config } = codec {
+        let dv_box_type = if dv_profile(config) > 7 {
+            b"dvvC"
+        } else {
+            b"dvcC"
+        };
+        entry.extend_from_slice(&write_box(dv_box_type, config));
+    }
+
     let sample_entry_box = write_box(sample_entry_type, &entry);
 
     // stsd
@@ -360,11 +403,18 @@ pub(crate) fn write_video_stsd(
 }
 
 pub(crate) fn write_audio_stsd(
+    codec: &Codec,
     sample_rate: u32,
     channels: u16,
     codec_private: &[u8],
 ) -> Vec<u8> {
-    // mp4a sample entry
+    let (sample_entry_type, codec_config_type): (&[u8; 4], &[u8; 4]) = match codec {
+        Codec::Ac3 => (b"ac-3", b"dac3"),
+        Codec::Eac3 => (b"ec-3", b"dec3"),
+        _ => (b"mp4a", b"esds"),
+    };
+
+    // mp4a / ac-3 / ec-3 sample entry
     let mut entry = Vec::with_capacity(28 + codec_private.len() + 8);
     // reserved (6 bytes)
     entry.extend_from_slice(&[0u8; 6]);
@@ -383,13 +433,13 @@ pub(crate) fn write_audio_stsd(
     // sample rate (fixed 16.16)
     entry.extend_from_slice(&(sample_rate << 16).to_be_bytes());
 
-    // esds box
+    // esds / dac3 / dec3 box
     if !codec_private.is_empty() {
-        let esds_box = write_box(b"esds", codec_private);
-        entry.extend_from_slice(&esds_box);
+        let config_box = write_box(codec_config_type, codec_private);
+        entry.extend_from_slice(&config_box);
     }
 
-    let sample_entry_box = write_box(b"mp4a", &entry);
+    let sample_entry_box = write_box(sample_entry_type, &entry);
 
     // stsd
     let mut stsd_content = Vec::with_capacity(8 + sample_entry_box.len());
@@ -419,11 +469,12 @@ pub(crate) fn write_video_stbl(
 }
 
 pub(crate) fn write_audio_stbl(
+    codec: &Codec,
     sample_rate: u32,
     channels: u16,
     codec_private: &[u8],
 ) -> Vec<u8> {
-    let stsd = write_audio_stsd(sample_rate, channels, codec_private);
+    let stsd = write_audio_stsd(codec, sample_rate, channels, codec_private);
     let stts = write_empty_stts();
     let stsc = write_empty_stsc();
     let stsz = write_empty_stsz();
@@ -448,13 +499,14 @@ pub(crate) fn write_video_minf(
 }
 
 pub(crate) fn write_audio_minf(
+    codec: &Codec,
     sample_rate: u32,
     channels: u16,
     codec_private: &[u8],
 ) -> Vec<u8> {
     let smhd = write_smhd();
     let dinf = write_dinf();
-    let stbl = write_audio_stbl(sample_rate, channels, codec_private);
+    let stbl = write_audio_stbl(codec, sample_rate, channels, codec_private);
     write_container_box(b"minf", &[&smhd, &dinf, &stbl])
 }
 
@@ -479,13 +531,14 @@ pub(crate) fn write_video_mdia(
 pub(crate) fn write_audio_mdia(
     timescale: u32,
     duration: u64,
+    codec: &Codec,
     sample_rate: u32,
     channels: u16,
     codec_private: &[u8],
 ) -> Vec<u8> {
     let mdhd = write_mdhd(timescale, duration);
     let hdlr = write_hdlr(b"soun", b"SoundHandler");
-    let minf = write_audio_minf(sample_rate, channels, codec_private);
+    let minf = write_audio_minf(codec, sample_rate, channels, codec_private);
     write_container_box(b"mdia", &[&mdhd, &hdlr, &minf])
 }
 
@@ -511,12 +564,20 @@ pub(crate) fn write_audio_trak(
     track_id: u32,
     timescale: u32,
     duration: u64,
+    codec: &Codec,
     sample_rate: u32,
     channels: u16,
     codec_private: &[u8],
 ) -> Vec<u8> {
     let tkhd = write_tkhd(track_id, duration, false, 0, 0);
-    let mdia = write_audio_mdia(timescale, duration, sample_rate, channels, codec_private);
+    let mdia = write_audio_mdia(
+        timescale,
+        duration,
+        codec,
+        sample_rate,
+        channels,
+        codec_private,
+    );
     write_container_box(b"trak", &[&tkhd, &mdia])
 }
 
@@ -804,4 +865,27 @@ mod tests {
         assert_eq!(read_u32(&hdr, 0), 1); // extended size marker
         assert_eq!(&hdr[4..8], b"mdat");
     }
+
+    #[test]
+    fn test_video_stsd_dolby_vision_sample_entries() {
+        // Profile 8.1: HDR10-compatible base layer, hvc1 + dvvC.
+        let p8 = vec![1, 0, 8 << 1, (6 << 3) | 0x05, 0x10, 0, 0, 0];
+        let stsd = write_video_stsd(&Codec::DolbyVision { config: p8 }, 3840, 2160, &[1, 2]);
+        assert_eq!(&stsd[20..24], b"hvc1");
+        assert_eq!(&stsd[24 + 78 + 4..24 + 78 + 8], b"hvcC");
+        assert_eq!(&stsd[24 + 78 + 10 + 4..24 + 78 + 10 + 8], b"dvvC");
+
+        // Profile 5: no compatible base layer, dvh1 + dvcC.
+        let p5 = vec![1, 0, 5 << 1, (6 << 3) | 0x05, 0x00, 0, 0, 0];
+        let stsd = write_video_stsd(&Codec::DolbyVision { config: p5 }, 3840, 2160, &[1, 2]);
+        assert_eq!(&stsd[20..24], b"dvh1");
+        assert_eq!(&stsd[24 + 78 + 10 + 4..24 + 78 + 10 + 8], b"dvcC");
+    }
+
+    #[test]
+    fn test_audio_stsd_eac3_sample_entry() {
+        let stsd = write_audio_stsd(&Codec::Eac3, 48000, 6, &[0x06, 0x00]);
+        assert_eq!(&stsd[20..24], b"ec-3");
+        assert_eq!(&stsd[24 + 28 + 4..24 + 28 + 8], b"dec3");
+    }
 }
@@ -23,7 +23,7 @@ pub struct TrackConfig {
     pub sample_rate: u32,
     /// Audio channel count (0 for video tracks).
     pub channels: u16,
-    /// Codec-specific configuration data (e.g. avcC, hvcC, or esds bytes).
+    /// Codec-specific configuration data (e.g. avcC, hvcC, esds, dac3 or dec3 bytes).
     pub codec_private: Vec<u8>,
 }
 
@@ -47,9 +47,7 @@ pub struct SampleInfo {
 pub fn write_init_segment(config: &TrackConfig) -> Vec<u8> {
     let ftyp = boxes::write_ftyp();
 
-    let is_video = matches!(config.codec, Codec::Avc | Codec::Hevc);
-
-    let trak = if is_video {
+    let trak = if config.codec.is_video() {
         boxes::write_video_trak(
             config.track_id,
             config.timescale,
@@ -64,6 +62,7 @@ pub fn write_init_segment(config: &TrackConfig) -> Vec<u8> {
             config.track_id,
             config.timescale,
             0,
+            &config.codec,
             config.sample_rate,
             config.channels,
             &config.codec_private,
@@ -188,6 +187,7 @@ pub fn write_init_segment_multi(video: &TrackConfig, audio: &TrackConfig) -> Vec
         audio.track_id,
         audio.timescale,
         0,
+        &audio.codec,
         audio.sample_rate,
         audio.channels,
         &audio.codec_private,
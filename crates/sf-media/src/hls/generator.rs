@@ -1,16 +1,52 @@
 //! HLS playlist generation functions.
 
-use super::types::{MasterPlaylist, MediaPlaylist};
+use super::types::{MasterPlaylist, MediaPlaylist, MediaType};
 use std::fmt::Write;
 
 /// Generate an HLS master playlist (M3U8) from a [`MasterPlaylist`].
 ///
-/// Output includes `#EXTM3U` header and `#EXT-X-STREAM-INF` for each variant.
+/// Output includes `#EXTM3U` header, `#EXT-X-MEDIA` for each audio or
+/// subtitle rendition and `#EXT-X-STREAM-INF` for each variant.
 pub fn generate_master_playlist(playlist: &MasterPlaylist) -> String {
     let mut out = String::new();
 
     writeln!(out, "#EXTM3U").unwrap();
 
+    for rendition in &playlist.renditions {
+        let media_type = match rendition.media_type {
+            MediaType::Audio => "AUDIO",
+            MediaType::Subtitles => "SUBTITLES",
+        };
+        write!(
+            out,
+            "#EXT-X-MEDIA:TYPE={},GROUP-ID=\"{}\"",
+            media_type, rendition.group_id
+        )
+        .unwrap();
+
+        if let Some(ref language) = rendition.language {
+            write!(out, ",LANGUAGE=\"{}\"", language).unwrap();
+        }
+
+        write!(out, ",NAME=\"{}\"", rendition.name).unwrap();
+        write!(out, ",DEFAULT={}", yes_no(rendition.default)).unwrap();
+        write!(out, ",AUTOSELECT={}", yes_no(rendition.autoselect)).unwrap();
+
+        if rendition.media_type == MediaType::Subtitles {
+            write!(out, ",FORCED={}", yes_no(rendition.forced)).unwrap();
+        }
+
+        if let Some(channels) = rendition.channels {
+            write!(out, ",CHANNELS=\"{}\"", channels).unwrap();
+        }
+
+        if let Some(ref uri) = rendition.uri {
+            write!(out, ",URI=\"{}\"", uri).unwrap();
+        }
+
+        writeln!(out).unwrap();
+    }
+
     for variant in &playlist.variants {
         write!(out, "#EXT-X-STREAM-INF:BANDWIDTH={}", variant.bandwidth).unwrap();
 
@@ -22,6 +58,14 @@ pub fn generate_master_playlist(playlist: &MasterPlaylist) -> String {
             write!(out, ",CODECS=\"{}\"", variant.codecs).unwrap();
         }
 
+        if let Some(ref group_id) = variant.audio {
+            write!(out, ",AUDIO=\"{}\"", group_id).unwrap();
+        }
+
+        if let Some(ref group_id) = variant.subtitles {
+            write!(out, ",SUBTITLES=\"{}\"", group_id).unwrap();
+        }
+
         writeln!(out).unwrap();
         writeln!(out, "{}", variant.uri).unwrap();
     }
@@ -29,6 +73,14 @@ pub fn generate_master_playlist(playlist: &MasterPlaylist) -> String {
     out
 }
 
+fn yes_no(value: bool) -> &'static str {
+    if value {
+        "YES"
+    } else {
+        "NO"
+    }
+}
+
 /// Generate an HLS media playlist (M3U8) from a [`MediaPlaylist`].
 ///
 /// Output includes:
@@ -69,23 +121,28 @@ pub fn generate_media_playlist(playlist: &MediaPlaylist) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::hls::types::{Segment, Variant};
+    use crate::hls::types::{Rendition, Segment, Variant};
 
     #[test]
     fn test_generate_master_playlist_basic() {
         let playlist = MasterPlaylist {
+            renditions: vec![],
             variants: vec![
                 Variant {
                     bandwidth: 5000000,
                     resolution: Some((1920, 1080)),
                     codecs: "avc1.64001f,mp4a.40.2".to_string(),
                     uri: "1080p/playlist.m3u8".to_string(),
+                    audio: None,
+                    subtitles: None,
                 },
                 Variant {
                     bandwidth: 2500000,
                     resolution: Some((1280, 720)),
                     codecs: "avc1.640028,mp4a.40.2".to_string(),
                     uri: "720p/playlist.m3u8".to_string(),
+                    audio: None,
+                    subtitles: None,
                 },
             ],
         };
@@ -104,11 +161,14 @@ mod tests {
     #[test]
     fn test_generate_master_playlist_no_resolution() {
         let playlist = MasterPlaylist {
+            renditions: vec![],
             variants: vec![Variant {
                 bandwidth: 128000,
                 resolution: None,
                 codecs: "mp4a.40.2".to_string(),
                 uri: "audio/playlist.m3u8".to_string(),
+                audio: None,
+                subtitles: None,
             }],
         };
 
@@ -122,6 +182,7 @@ mod tests {
     #[test]
     fn test_generate_master_playlist_empty() {
         let playlist = MasterPlaylist {
+            renditions: vec![],
             variants: vec![],
         };
 
@@ -129,6 +190,95 @@ mod tests {
         assert_eq!(m3u8, "#EXTM3U\n");
     }
 
+    #[test]
+    fn test_generate_master_playlist_audio_renditions() {
+        let playlist = MasterPlaylist {
+            renditions: vec![
+                Rendition {
+                    media_type: MediaType::Audio,
+                    group_id: "audio".to_string(),
+                    language: Some("eng".to_string()),
+                    name: "English".to_string(),
+                    default: true,
+                    autoselect: true,
+                    forced: false,
+                    channels: Some(6),
+                    uri: None,
+                },
+                Rendition {
+                    media_type: MediaType::Audio,
+                    group_id: "audio".to_string(),
+                    language: Some("fre".to_string()),
+                    name: "Français".to_string(),
+                    default: false,
+                    autoselect: true,
+                    forced: false,
+                    channels: Some(2),
+                    uri: Some("audio_0.m3u8".to_string()),
+                },
+            ],
+            variants: vec![Variant {
+                bandwidth: 5000000,
+                resolution: Some((1920, 1080)),
+                codecs: String::new(),
+                uri: "index.m3u8".to_string(),
+                audio: Some("audio".to_string()),
+                subtitles: None,
+            }],
+        };
+
+        let m3u8 = generate_master_playlist(&playlist);
+
+        let expected = "\
+#EXTM3U
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"audio\",LANGUAGE=\"eng\",NAME=\"English\",DEFAULT=YES,AUTOSELECT=YES,CHANNELS=\"6\"
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"audio\",LANGUAGE=\"fre\",NAME=\"Français\",DEFAULT=NO,AUTOSELECT=YES,CHANNELS=\"2\",URI=\"audio_0.m3u8\"
+#EXT-X-STREAM-INF:BANDWIDTH=5000000,RESOLUTION=1920x1080,AUDIO=\"audio\"
+index.m3u8
+";
+        assert_eq!(m3u8, expected);
+    }
+
+    #[test]
+    fn test_generate_master_playlist_subtitle_renditions() {
+        let subtitle = |language: &str, name: &str, default, forced, uri: &str| Rendition {
+            media_type: MediaType::Subtitles,
+            group_id: "subs".to_string(),
+            language: Some(language.to_string()),
+            name: name.to_string(),
+            default,
+            autoselect: true,
+            forced,
+            channels: None,
+            uri: Some(uri.to_string()),
+        };
+        let playlist = MasterPlaylist {
+            renditions: vec![
+                subtitle("eng", "eng", true, false, "subtitles_0.m3u8"),
+                subtitle("eng", "eng (forced)", false, true, "subtitles_2.m3u8"),
+            ],
+            variants: vec![Variant {
+                bandwidth: 5000000,
+                resolution: Some((1920, 1080)),
+                codecs: String::new(),
+                uri: "index.m3u8".to_string(),
+                audio: None,
+                subtitles: Some("subs".to_string()),
+            }],
+        };
+
+        let m3u8 = generate_master_playlist(&playlist);
+
+        let expected = "\
+#EXTM3U
+#EXT-X-MEDIA:TYPE=SUBTITLES,GROUP-ID=\"subs\",LANGUAGE=\"eng\",NAME=\"eng\",DEFAULT=YES,AUTOSELECT=YES,FORCED=NO,URI=\"subtitles_0.m3u8\"
+#EXT-X-MEDIA:TYPE=SUBTITLES,GROUP-ID=\"subs\",LANGUAGE=\"eng\",NAME=\"eng (forced)\",DEFAULT=NO,AUTOSELECT=YES,FORCED=YES,URI=\"subtitles_2.m3u8\"
+#EXT-X-STREAM-INF:BANDWIDTH=5000000,RESOLUTION=1920x1080,SUBTITLES=\"subs\"
+index.m3u8
+";
+        assert_eq!(m3u8, expected);
+    }
+
     #[test]
     fn test_generate_media_playlist_vod() {
         let playlist = MediaPlaylist {
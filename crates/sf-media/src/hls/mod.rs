@@ -1,11 +1,15 @@
 //! HLS playlist generation.
 //!
 //! This module generates M3U8 playlists for HLS streaming, supporting
-//! both master playlists (with multiple variants) and media playlists
-//! (with segment lists and optional init segment references).
+//! both master playlists (with multiple variants and alternate audio and
+//! subtitle renditions) and media playlists (with segment lists and optional
+//! init segment references), plus the segmented WebVTT served for subtitle
+//! renditions.
 
 mod generator;
 mod types;
+mod webvtt;
 
 pub use generator::{generate_master_playlist, generate_media_playlist};
-pub use types::{MasterPlaylist, MediaPlaylist, Segment, Variant};
+pub use types::{MasterPlaylist, MediaPlaylist, MediaType, Rendition, Segment, Variant};
+pub use webvtt::{parse_webvtt, subtitle_media_playlist, write_webvtt_segment, Cue};
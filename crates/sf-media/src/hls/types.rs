@@ -13,6 +13,43 @@ pub struct Variant {
     pub codecs: String,
     /// URI to the media playlist for this variant.
     pub uri: String,
+    /// GROUP-ID of the audio renditions this variant can be played with.
+    pub audio: Option<String>,
+    /// GROUP-ID of the subtitle renditions this variant can be played with.
+    pub subtitles: Option<String>,
+}
+
+/// The `TYPE` of an alternate rendition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MediaType {
+    Audio,
+    Subtitles,
+}
+
+/// An alternate rendition (`#EXT-X-MEDIA`) in a master playlist.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rendition {
+    /// Whether this is an audio or a subtitle rendition.
+    pub media_type: MediaType,
+    /// Rendition group this belongs to, referenced by [`Variant::audio`] or
+    /// [`Variant::subtitles`].
+    pub group_id: String,
+    /// Language tag (e.g. "en" or "eng").
+    pub language: Option<String>,
+    /// Human-readable name, unique within the group.
+    pub name: String,
+    /// Whether players should pick this rendition without user input.
+    pub default: bool,
+    /// Whether players may pick this rendition based on their language.
+    pub autoselect: bool,
+    /// Whether a subtitle rendition only carries forced narrative (e.g.
+    /// foreign-language dialogue). Ignored for audio.
+    pub forced: bool,
+    /// Audio channel count.
+    pub channels: Option<u16>,
+    /// URI to the rendition's media playlist, or `None` if its media is
+    /// muxed into the variant streams.
+    pub uri: Option<String>,
 }
 
 /// A single segment in a media playlist.
@@ -29,6 +66,8 @@ pub struct Segment {
 /// An HLS master playlist containing multiple stream variants.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MasterPlaylist {
+    /// Alternate audio and subtitle renditions.
+    pub renditions: Vec<Rendition>,
     /// Stream variants ordered by bandwidth.
     pub variants: Vec<Variant>,
 }
@@ -0,0 +1,227 @@
+//! WebVTT cue parsing and segmentation for HLS subtitle renditions.
+//!
+//! A subtitle rendition's media playlist mirrors the video segment timeline;
+//! each of its segments is a standalone WebVTT document holding the cues
+//! that overlap that segment's time range. Cues spanning a boundary are
+//! repeated in every segment they overlap, as HLS players expect.
+
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use super::types::{MediaPlaylist, Segment};
+
+/// A single WebVTT cue.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cue {
+    /// Start time in seconds.
+    pub start: f64,
+    /// End time in seconds.
+    pub end: f64,
+    /// Cue settings following the timing (e.g. "align:start line:0"), if any.
+    pub settings: String,
+    /// Cue text, possibly spanning several lines.
+    pub payload: String,
+}
+
+/// Parse the cues of a WebVTT document.
+///
+/// The header, NOTE, STYLE and REGION blocks are skipped, as are blocks with
+/// malformed timings. Cue identifiers are dropped.
+pub fn parse_webvtt(text: &str) -> Vec<Cue> {
+    let text = text.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+    let mut cues = Vec::new();
+
+    for block in text.split("\n\n") {
+        let mut lines = block.lines().skip_while(|l| l.trim().is_empty());
+        let Some(first) = lines.next() else {
+            continue;
+        };
+        // The timing line is either the first line or follows the cue id.
+        let timing = if first.contains("-->") {
+            first
+        } else {
+            match lines.next() {
+                Some(line) if line.contains("-->") => line,
+                _ => continue,
+            }
+        };
+        let Some((start, end, settings)) = parse_timing(timing) else {
+            continue;
+        };
+        let payload = lines.collect::<Vec<_>>().join("\n");
+        cues.push(Cue {
+            start,
+            end,
+            settings,
+            payload,
+        });
+    }
+
+    cues
+}
+
+/// Parse `00:01:02.500 --> 00:01:04.000 align:start` into start, end and
+/// settings.
+fn parse_timing(line: &str) -> Option<(f64, f64, String)> {
+    let (start, rest) = line.split_once("-->")?;
+    let rest = rest.trim_start();
+    let (end, settings) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    Some((
+        parse_timestamp(start.trim())?,
+        parse_timestamp(end)?,
+        settings.trim().to_string(),
+    ))
+}
+
+/// Parse `HH:MM:SS.mmm` or `MM:SS.mmm` into seconds.
+fn parse_timestamp(ts: &str) -> Option<f64> {
+    let (clock, millis) = ts.split_once('.')?;
+    let millis: u64 = millis.parse().ok()?;
+    let mut seconds = 0u64;
+    for part in clock.split(':') {
+        seconds = seconds * 60 + part.parse::<u64>().ok()?;
+    }
+    Some(seconds as f64 + millis as f64 / 1000.0)
+}
+
+/// Format seconds as `HH:MM:SS.mmm`.
+fn format_timestamp(secs: f64) -> String {
+    let millis = (secs.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+/// Write the WebVTT segment covering `[start, end)` seconds: every cue that
+/// overlaps it, with times left on the presentation timeline.
+///
+/// `X-TIMESTAMP-MAP` ties cue time zero to media time zero, where the fMP4
+/// segments' decode timeline starts.
+pub fn write_webvtt_segment(cues: &[Cue], start: f64, end: f64) -> String {
+    let mut out = String::new();
+
+    writeln!(out, "WEBVTT").unwrap();
+    writeln!(out, "X-TIMESTAMP-MAP=MPEGTS:0,LOCAL:00:00:00.000").unwrap();
+
+    for cue in cues.iter().filter(|c| c.start < end && c.end > start) {
+        writeln!(out).unwrap();
+        write!(
+            out,
+            "{} --> {}",
+            format_timestamp(cue.start),
+            format_timestamp(cue.end)
+        )
+        .unwrap();
+        if !cue.settings.is_empty() {
+            write!(out, " {}", cue.settings).unwrap();
+        }
+        writeln!(out).unwrap();
+        writeln!(out, "{}", cue.payload).unwrap();
+    }
+
+    out
+}
+
+/// Build the media playlist of a subtitle rendition with one WebVTT segment
+/// per video segment, given the video segment durations. Segments are named
+/// `{prefix}_segment_{k}.vtt`.
+pub fn subtitle_media_playlist(
+    durations: &[f64],
+    target_duration: u32,
+    prefix: &str,
+) -> MediaPlaylist {
+    MediaPlaylist {
+        target_duration,
+        media_sequence: 0,
+        segments: durations
+            .iter()
+            .enumerate()
+            .map(|(k, &duration)| Segment {
+                duration,
+                uri: format!("{prefix}_segment_{k}.vtt"),
+                title: None,
+            })
+            .collect(),
+        ended: true,
+        init_segment_uri: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "\u{feff}WEBVTT\r\n\
+Kind: captions\r\n\
+\r\n\
+NOTE a comment\r\n\
+\r\n\
+1\r\n\
+00:00:01.000 --> 00:00:03.500\r\n\
+Hello\r\n\
+\r\n\
+00:00:05.000 --> 00:00:07.250 align:start line:0\r\n\
+Two\r\n\
+lines\r\n\
+\r\n\
+01:02.000 --> 01:03.000\r\n\
+Later\r\n";
+
+    #[test]
+    fn test_parse_webvtt() {
+        let cues = parse_webvtt(SAMPLE);
+        assert_eq!(cues.len(), 3);
+        assert_eq!(cues[0].start, 1.0);
+        assert_eq!(cues[0].end, 3.5);
+        assert_eq!(cues[0].payload, "Hello");
+        assert_eq!(cues[1].settings, "align:start line:0");
+        assert_eq!(cues[1].payload, "Two\nlines");
+        assert_eq!(cues[2].start, 62.0);
+    }
+
+    #[test]
+    fn test_segment_repeats_boundary_cues() {
+        let cues = parse_webvtt(SAMPLE);
+
+        let first = write_webvtt_segment(&cues, 0.0, 6.0);
+        let expected = "\
+WEBVTT
+X-TIMESTAMP-MAP=MPEGTS:0,LOCAL:00:00:00.000
+
+00:00:01.000 --> 00:00:03.500
+Hello
+
+00:00:05.000 --> 00:00:07.250 align:start line:0
+Two
+lines
+";
+        assert_eq!(first, expected);
+
+        // The cue crossing 6s is repeated; the 62s cue is in neither.
+        let second = write_webvtt_segment(&cues, 6.0, 12.0);
+        assert!(second.contains("00:00:05.000 --> 00:00:07.250"));
+        assert!(!second.contains("Hello"));
+        assert!(!second.contains("Later"));
+    }
+
+    #[test]
+    fn test_subtitle_media_playlist() {
+        let playlist = subtitle_media_playlist(&[6.0, 4.5], 6, "subtitles_3");
+        let m3u8 = crate::hls::generate_media_playlist(&playlist);
+        assert!(!m3u8.contains("EXT-X-MAP"));
+        assert!(m3u8.contains("#EXTINF:6.000000,\nsubtitles_3_segment_0.vtt\n"));
+        assert!(m3u8.contains("#EXTINF:4.500000,\nsubtitles_3_segment_1.vtt\n"));
+        assert!(m3u8.ends_with("#EXT-X-ENDLIST\n"));
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(3723.5), "01:02:03.500");
+        assert_eq!(format_timestamp(-1.0), "00:00:00.000");
+    }
+}
@@ -1,5 +1,5 @@
 //! sf-media: fragmented MP4 serialization, HLS playlist generation, segment
-//! mapping, and MP4 moov parsing.
+//! mapping, and MP4 moov and MKV parsing.
 //!
 //! This crate provides the media container and streaming infrastructure for
 //! sceneforged, enabling HLS delivery of video content.
@@ -8,25 +8,44 @@
 //!
 //! - [`fmp4`] - Fragmented MP4 (ISO BMFF) serialization: init segments and media segments
 //! - [`hls`] - HLS playlist generation: master and media playlists (M3U8)
+//! - [`mkv`] - Matroska indexer: map cluster blocks to the same sample tables
 //! - [`mp4`] - MP4 moov atom parser: extract sample tables and codec config
 //! - [`segment_map`] - Pre-computed segment boundaries and zero-copy HLS preparation
 
 pub mod fmp4;
 pub mod hls;
+pub mod mkv;
 pub mod mp4;
 pub mod segment_map;
 
+use std::io::{self, Read, Seek, SeekFrom};
+
 // Re-export commonly used items at the crate root.
 pub use fmp4::{
     write_init_segment, write_init_segment_multi, write_media_segment, Codec, SampleInfo,
     TrackConfig,
 };
 pub use hls::{
-    generate_master_playlist, generate_media_playlist, MasterPlaylist, MediaPlaylist, Segment,
+    generate_master_playlist, generate_media_playlist, parse_webvtt, subtitle_media_playlist,
+    write_webvtt_segment, Cue, MasterPlaylist, MediaPlaylist, MediaType, Rendition, Segment,
     Variant,
 };
+pub use mkv::parse_mkv;
 pub use mp4::{parse_moov, Mp4Metadata, TrackInfo};
 pub use segment_map::{
-    build_prepared_media, compute_segment_map, DataRange, KeyframeInfo, PrecomputedSegment,
-    PreparedMedia, SegmentBoundary, SegmentMap,
+    build_prepared_media, compute_segment_map, AudioRendition, DataRange, KeyframeInfo,
+    PrecomputedSegment, PreparedMedia, SegmentBoundary, SegmentMap, SUBTITLE_GROUP,
 };
+
+/// Parse an MP4 or MKV file, detected from its first bytes.
+pub fn parse_media<R: Read + Seek>(reader: &mut R) -> io::Result<Mp4Metadata> {
+    let mut magic = [0u8; 4];
+    reader.seek(SeekFrom::Start(0))?;
+    let read = reader.read(&mut magic)?;
+    reader.seek(SeekFrom::Start(0))?;
+    if mkv::is_mkv(&magic[..read]) {
+        parse_mkv(reader)
+    } else {
+        parse_moov(reader)
+    }
+}
@@ -0,0 +1,308 @@
+//! Index the frames of a Matroska cluster from its block headers.
+//!
+//! Only block headers (track number, timestamp, flags and lace sizes) are
+//! read; frame data is skipped, leaving its byte range in the file.
+
+use std::collections::HashMap;
+use std::io::{self, Read, Seek, SeekFrom};
+
+use super::ebml::{for_each_child, invalid, read_element_header, read_u8, read_uint, read_vint};
+use super::{CLUSTER, LEVEL1_IDS};
+
+const TIMESTAMP: u32 = 0xE7;
+const SIMPLE_BLOCK: u32 = 0xA3;
+const BLOCK_GROUP: u32 = 0xA0;
+const BLOCK: u32 = 0xA1;
+const REFERENCE_BLOCK: u32 = 0xFB;
+
+/// A single frame in the file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub offset: u64,
+    pub size: u32,
+    /// Presentation timestamp in TimestampScale units.
+    pub timestamp: i64,
+    pub keyframe: bool,
+}
+
+/// Index the cluster whose content starts at `start`, collecting the frames
+/// of the tracks in `frames` (keyed by track number).
+///
+/// `size` is `None` for an unknown-size cluster, which ends at the next
+/// top-level element or `segment_end`. Returns the position just past the
+/// cluster.
+pub fn index_cluster<R: Read + Seek>(
+    reader: &mut R,
+    start: u64,
+    size: Option<u64>,
+    segment_end: u64,
+    frames: &mut HashMap<u64, Vec<Frame>>,
+) -> io::Result<u64> {
+    let end = size.map_or(segment_end, |size| start + size);
+    let mut cluster_timestamp = 0i64;
+    let mut pos = start;
+
+    while pos < end {
+        reader.seek(SeekFrom::Start(pos))?;
+        let Some(header) = read_element_header(reader)? else {
+            break;
+        };
+        if size.is_none() && (header.id == CLUSTER || LEVEL1_IDS.contains(&header.id)) {
+            // An unknown-size cluster ends where the next top-level element starts.
+            return Ok(pos);
+        }
+        let Some(child_size) = header.size else {
+            return Err(invalid("unknown-size element inside a cluster"));
+        };
+        let content = pos + header.header_size;
+
+        match header.id {
+            TIMESTAMP => cluster_timestamp = read_uint(reader, child_size)? as i64,
+            SIMPLE_BLOCK => {
+                read_block(reader, content, child_size, cluster_timestamp, None, frames)?;
+            }
+            BLOCK_GROUP => {
+                let mut block = None;
+                let mut referenced = false;
+                for_each_child(reader, content, content + child_size, |reader, child| {
+                    match child.id {
+                        BLOCK => {
+                            block =
+                                Some((reader.stream_position()?, child.size.unwrap_or_default()))
+                        }
+                        REFERENCE_BLOCK => referenced = true,
+                        _ => {}
+                    }
+                    Ok(())
+                })?;
+                if let Some((block_start, block_size)) = block {
+                    read_block(
+                        reader,
+                        block_start,
+                        block_size,
+                        cluster_timestamp,
+                        Some(!referenced),
+                        frames,
+                    )?;
+                }
+            }
+            _ => {}
+        }
+        pos = content + child_size;
+    }
+
+    Ok(end.max(pos))
+}
+
+/// Read the header of a (Simple)Block and record its frames if its track is
+/// indexed. `keyframe` overrides the SimpleBlock keyframe flag for Blocks.
+fn read_block<R: Read + Seek>(
+    reader: &mut R,
+    start: u64,
+    size: u64,
+    cluster_timestamp: i64,
+    keyframe: Option<bool>,
+    frames: &mut HashMap<u64, Vec<Frame>>,
+) -> io::Result<()> {
+    reader.seek(SeekFrom::Start(start))?;
+    let (track, track_len) = read_vint(reader)?;
+    let Some(track_frames) = frames.get_mut(&track) else {
+        return Ok(());
+    };
+
+    let mut relative = [0u8; 2];
+    reader.read_exact(&mut relative)?;
+    let timestamp = cluster_timestamp + i16::from_be_bytes(relative) as i64;
+    let flags = read_u8(reader)?;
+    let keyframe = keyframe.unwrap_or(flags & 0x80 != 0);
+
+    let header_len = track_len as u64 + 3;
+    if size < header_len {
+        return Err(invalid("truncated Matroska block"));
+    }
+    let sizes = match (flags >> 1) & 0x03 {
+        0 => vec![size - header_len],
+        lacing => read_lace_sizes(reader, lacing, start + size)?,
+    };
+
+    let mut offset = reader.stream_position()?;
+    for (i, frame_size) in sizes.into_iter().enumerate() {
+        track_frames.push(Frame {
+            offset,
+            size: frame_size as u32,
+            timestamp,
+            // Only the first frame of a laced block can be a keyframe.
+            keyframe: keyframe && i == 0,
+        });
+        offset += frame_size;
+    }
+    if offset > start + size {
+        return Err(invalid("Matroska lace sizes exceed the block"));
+    }
+    Ok(())
+}
+
+/// Read the lace header (reader positioned just past the block flags) and
+/// return each frame's size. `block_end` is the end of the block's content.
+fn read_lace_sizes<R: Read + Seek>(
+    reader: &mut R,
+    lacing: u8,
+    block_end: u64,
+) -> io::Result<Vec<u64>> {
+    let count = read_u8(reader)? as usize + 1;
+    let mut sizes = Vec::with_capacity(count);
+
+    match lacing {
+        // Xiph: each size but the last as a run of 255s plus a final byte.
+        1 => {
+            for _ in 0..count - 1 {
+                let mut size = 0u64;
+                loop {
+                    let byte = read_u8(reader)?;
+                    size += byte as u64;
+                    if byte != 255 {
+                        break;
+                    }
+                }
+                sizes.push(size);
+            }
+        }
+        // EBML: the first size, then signed differences to the previous one.
+        3 => {
+            let (first, _) = read_vint(reader)?;
+            sizes.push(first);
+            for _ in 1..count - 1 {
+                let (raw, len) = read_vint(reader)?;
+                let bias = (1i64 << (7 * len - 1)) - 1;
+                let previous = *sizes.last().unwrap() as i64;
+                let size = previous + raw as i64 - bias;
+                if size < 0 {
+                    return Err(invalid("negative Matroska EBML lace size"));
+                }
+                sizes.push(size as u64);
+            }
+        }
+        // Fixed: all frames the same size.
+        _ => {
+            let data = block_end.saturating_sub(reader.stream_position()?);
+            return Ok(vec![data / count as u64; count]);
+        }
+    }
+
+    let data = block_end.saturating_sub(reader.stream_position()?);
+    let laced: u64 = sizes.iter().sum();
+    if laced > data {
+        return Err(invalid("Matroska lace sizes exceed the block"));
+    }
+    sizes.push(data - laced);
+    Ok(sizes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    /// Encode an element with a 1-byte ID and an 8-byte size.
+    fn element(id: u8, content: &[u8]) -> Vec<u8> {
+        let mut out = vec![id, 0x01];
+        out.extend_from_slice(&(content.len() as u64).to_be_bytes()[1..]);
+        out.extend_from_slice(content);
+        out
+    }
+
+    fn indexed(tracks: &[u64]) -> HashMap<u64, Vec<Frame>> {
+        tracks.iter().map(|&t| (t, Vec::new())).collect()
+    }
+
+    #[test]
+    fn test_index_simple_and_group_blocks() {
+        let mut cluster = element(0xE7, &[0x03, 0xE8]); // timestamp 1000
+                                                        // Track 1 keyframe at +0 with 4 bytes of data.
+        cluster.extend(element(0xA3, &[0x81, 0x00, 0x00, 0x80, 1, 2, 3, 4]));
+        // Track 2 (not indexed).
+        cluster.extend(element(0xA3, &[0x82, 0x00, 0x00, 0x80, 9]));
+        // Track 1 BlockGroup at +40 referencing an earlier frame.
+        let mut group = element(0xA1, &[0x81, 0x00, 0x28, 0x00, 5, 6]);
+        group.extend(element(0xFB, &[0x01]));
+        cluster.extend(element(0xA0, &group));
+
+        let mut frames = indexed(&[1]);
+        let end = index_cluster(
+            &mut Cursor::new(&cluster),
+            0,
+            Some(cluster.len() as u64),
+            u64::MAX,
+            &mut frames,
+        )
+        .unwrap();
+        assert_eq!(end, cluster.len() as u64);
+
+        let track = &frames[&1];
+        assert_eq!(track.len(), 2);
+        assert_eq!(track[0].timestamp, 1000);
+        assert!(track[0].keyframe);
+        assert_eq!(track[0].size, 4);
+        assert_eq!(&cluster[track[0].offset as usize..][..4], &[1, 2, 3, 4]);
+        assert_eq!(track[1].timestamp, 1040);
+        assert!(!track[1].keyframe);
+        assert_eq!(&cluster[track[1].offset as usize..][..2], &[5, 6]);
+    }
+
+    #[test]
+    fn test_laced_frames() {
+        // Xiph lacing, 3 frames of 2, 3 and 1 bytes.
+        let xiph = element(
+            0xA3,
+            &[0x81, 0x00, 0x00, 0x82, 0x02, 2, 3, 1, 1, 2, 2, 2, 3],
+        );
+        // EBML lacing, 3 frames of 2, 3 and 1 bytes (+1 encoded as 0xC0).
+        let ebml = element(
+            0xA3,
+            &[0x81, 0x00, 0x00, 0x86, 0x02, 0x82, 0xC0, 1, 1, 2, 2, 2, 3],
+        );
+        // Fixed lacing, 2 frames of 2 bytes.
+        let fixed = element(0xA3, &[0x81, 0x00, 0x00, 0x84, 0x01, 1, 1, 2, 2]);
+
+        for (block, expected) in [
+            (xiph, vec![2, 3, 1]),
+            (ebml, vec![2, 3, 1]),
+            (fixed, vec![2, 2]),
+        ] {
+            let mut frames = indexed(&[1]);
+            index_cluster(
+                &mut Cursor::new(&block),
+                0,
+                Some(block.len() as u64),
+                u64::MAX,
+                &mut frames,
+            )
+            .unwrap();
+            let sizes: Vec<u32> = frames[&1].iter().map(|f| f.size).collect();
+            assert_eq!(sizes, expected);
+            for (i, frame) in frames[&1].iter().enumerate() {
+                assert_eq!(block[frame.offset as usize], i as u8 + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unknown_size_cluster_ends_at_next_cluster() {
+        let mut data = element(0xE7, &[0x00]);
+        data.extend(element(0xA3, &[0x81, 0x00, 0x00, 0x80, 1]));
+        let next_cluster = data.len() as u64;
+        data.extend_from_slice(&[0x1F, 0x43, 0xB6, 0x75, 0xFF]);
+
+        let mut frames = indexed(&[1]);
+        let end = index_cluster(
+            &mut Cursor::new(&data),
+            0,
+            None,
+            data.len() as u64,
+            &mut frames,
+        )
+        .unwrap();
+        assert_eq!(end, next_cluster);
+        assert_eq!(frames[&1].len(), 1);
+    }
+}
@@ -0,0 +1,213 @@
+//! EBML element header parsing and value readers.
+
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// A parsed element header.
+#[derive(Debug, Clone)]
+pub struct ElementHeader {
+    /// Element ID, including its length marker bits (e.g. 0x1A45DFA3).
+    pub id: u32,
+    /// Content size, or `None` for an unknown-size element.
+    pub size: Option<u64>,
+    /// Size of the header itself (ID + size vints).
+    pub header_size: u64,
+}
+
+/// Read an element header from the current position.
+///
+/// Returns `Ok(None)` at EOF, `Ok(Some(header))` otherwise.
+pub fn read_element_header<R: Read>(reader: &mut R) -> io::Result<Option<ElementHeader>> {
+    let mut first = [0u8; 1];
+    match reader.read_exact(&mut first) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+
+    // IDs are 1-4 bytes long and keep their marker bits.
+    let id_len = first[0].leading_zeros() as usize + 1;
+    if id_len > 4 {
+        return Err(invalid("invalid EBML element ID"));
+    }
+    let mut id = first[0] as u32;
+    for _ in 1..id_len {
+        id = (id << 8) | read_u8(reader)? as u32;
+    }
+
+    let (size, size_len) = read_vint(reader)?;
+    let unknown = size == (1u64 << (7 * size_len)) - 1;
+
+    Ok(Some(ElementHeader {
+        id,
+        size: (!unknown).then_some(size),
+        header_size: (id_len + size_len) as u64,
+    }))
+}
+
+/// Read a variable-length integer with its length marker removed.
+/// Returns the value and its length in bytes.
+pub fn read_vint<R: Read>(reader: &mut R) -> io::Result<(u64, usize)> {
+    let first = read_u8(reader)?;
+    let len = first.leading_zeros() as usize + 1;
+    if len > 8 {
+        return Err(invalid("invalid EBML variable-length integer"));
+    }
+    let mut value = (first as u64) & (0xFF >> len);
+    for _ in 1..len {
+        value = (value << 8) | read_u8(reader)? as u64;
+    }
+    Ok((value, len))
+}
+
+/// Read an unsigned integer element's content of `size` bytes.
+pub fn read_uint<R: Read>(reader: &mut R, size: u64) -> io::Result<u64> {
+    if size > 8 {
+        return Err(invalid("EBML unsigned integer longer than 8 bytes"));
+    }
+    let mut value = 0u64;
+    for _ in 0..size {
+        value = (value << 8) | read_u8(reader)? as u64;
+    }
+    Ok(value)
+}
+
+/// Read a float element's content (4 or 8 bytes; 0 bytes means 0.0).
+pub fn read_float<R: Read>(reader: &mut R, size: u64) -> io::Result<f64> {
+    match size {
+        0 => Ok(0.0),
+        4 => {
+            let mut buf = [0u8; 4];
+            reader.read_exact(&mut buf)?;
+            Ok(f32::from_be_bytes(buf) as f64)
+        }
+        8 => {
+            let mut buf = [0u8; 8];
+            reader.read_exact(&mut buf)?;
+            Ok(f64::from_be_bytes(buf))
+        }
+        _ => Err(invalid("EBML float must be 4 or 8 bytes")),
+    }
+}
+
+/// Read a string element's content, dropping trailing NUL padding.
+pub fn read_string<R: Read>(reader: &mut R, size: u64) -> io::Result<String> {
+    let bytes = read_binary(reader, size)?;
+    let text = String::from_utf8_lossy(&bytes);
+    Ok(text.trim_end_matches('\0').to_string())
+}
+
+/// Read a binary element's content.
+///
+/// `size` comes from the file, so the buffer only grows as data is actually
+/// read; an element claiming more than the file holds is an error rather
+/// than a huge allocation.
+pub fn read_binary<R: Read>(reader: &mut R, size: u64) -> io::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    reader.by_ref().take(size).read_to_end(&mut buf)?;
+    if (buf.len() as u64) < size {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "EBML element extends past the end of the file",
+        ));
+    }
+    Ok(buf)
+}
+
+/// Read a single byte.
+pub fn read_u8<R: Read>(reader: &mut R) -> io::Result<u8> {
+    let mut buf = [0u8; 1];
+    reader.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+/// Call `f` with the header of each child of a master element whose content
+/// spans `[start, end)`. `f` is called with the reader at the child's
+/// content; afterwards the reader is moved past the child.
+pub fn for_each_child<R, F>(reader: &mut R, start: u64, end: u64, mut f: F) -> io::Result<()>
+where
+    R: Read + Seek,
+    F: FnMut(&mut R, &ElementHeader) -> io::Result<()>,
+{
+    let mut pos = start;
+    while pos < end {
+        reader.seek(SeekFrom::Start(pos))?;
+        let Some(header) = read_element_header(reader)? else {
+            break;
+        };
+        let Some(size) = header.size else {
+            return Err(invalid(
+                "unknown-size element inside a sized master element",
+            ));
+        };
+        f(reader, &header)?;
+        pos += header.header_size + size;
+    }
+    Ok(())
+}
+
+pub(crate) fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_read_element_header() {
+        // Segment ID, 8-byte size of 0x1234.
+        let data = [
+            0x18, 0x53, 0x80, 0x67, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x12, 0x34,
+        ];
+        let header = read_element_header(&mut Cursor::new(&data))
+            .unwrap()
+            .unwrap();
+        assert_eq!(header.id, 0x18538067);
+        assert_eq!(header.size, Some(0x1234));
+        assert_eq!(header.header_size, 12);
+    }
+
+    #[test]
+    fn test_unknown_size() {
+        // Cluster ID with a 1-byte all-ones size.
+        let data = [0x1F, 0x43, 0xB6, 0x75, 0xFF];
+        let header = read_element_header(&mut Cursor::new(&data))
+            .unwrap()
+            .unwrap();
+        assert_eq!(header.id, 0x1F43B675);
+        assert_eq!(header.size, None);
+    }
+
+    #[test]
+    fn test_read_vint() {
+        assert_eq!(read_vint(&mut Cursor::new([0x81])).unwrap(), (1, 1));
+        assert_eq!(read_vint(&mut Cursor::new([0x40, 0x02])).unwrap(), (2, 2));
+        assert!(read_vint(&mut Cursor::new([0x00])).is_err());
+    }
+
+    #[test]
+    fn test_read_values() {
+        assert_eq!(
+            read_uint(&mut Cursor::new([0x0F, 0x42, 0x40]), 3).unwrap(),
+            1_000_000
+        );
+        assert_eq!(
+            read_float(&mut Cursor::new(48000.0f32.to_be_bytes()), 4).unwrap(),
+            48000.0
+        );
+        assert_eq!(
+            read_string(&mut Cursor::new(b"A_AAC\0\0"), 7).unwrap(),
+            "A_AAC"
+        );
+    }
+
+    #[test]
+    fn test_oversized_binary_is_an_error() {
+        for size in [4, 1 << 32, (1 << 56) - 2, u64::MAX] {
+            let err = read_binary(&mut Cursor::new([1, 2, 3]), size).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        }
+        assert_eq!(read_binary(&mut Cursor::new([1, 2, 3]), 2).unwrap(), [1, 2]);
+    }
+}
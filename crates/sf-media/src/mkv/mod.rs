@@ -0,0 +1,472 @@
+//! Matroska (MKV) indexer.
+//!
+//! Indexes an MKV file's tracks and cluster blocks into the same
+//! [`Mp4Metadata`] shape [`parse_moov`](crate::parse_moov) produces, so
+//! [`build_prepared_media`](crate::build_prepared_media) can serve H.264/HEVC
+//! with AAC, AC-3 or E-AC-3 audio as fMP4 HLS by repackaging the container
+//! only: every sample's byte range points at frame data inside a
+//! SimpleBlock or Block.
+//!
+//! Matroska stores presentation timestamps in decode order; decode
+//! timestamps are reconstructed by sorting them, with the difference kept as
+//! a (possibly negative) composition offset.
+
+pub mod blocks;
+pub mod ebml;
+pub mod tracks;
+
+use std::collections::HashMap;
+use std::io::{self, Read, Seek, SeekFrom};
+
+use blocks::{index_cluster, Frame};
+use ebml::{for_each_child, invalid, read_element_header, read_float, read_uint};
+use tracks::{parse_tracks, MkvTrack, TRACK_TYPE_AUDIO, TRACK_TYPE_VIDEO};
+
+use crate::mp4::{Mp4Metadata, ResolvedSample, ResolvedSampleTable, TrackInfo};
+
+const EBML_HEADER: u32 = 0x1A45DFA3;
+const SEGMENT: u32 = 0x18538067;
+const INFO: u32 = 0x1549A966;
+const TIMESTAMP_SCALE: u32 = 0x2AD7B1;
+const DURATION: u32 = 0x4489;
+const TRACKS: u32 = 0x1654AE6B;
+pub(crate) const CLUSTER: u32 = 0x1F43B675;
+
+/// Top-level elements other than Cluster; they end an unknown-size cluster.
+pub(crate) const LEVEL1_IDS: [u32; 7] = [
+    0x114D9B74, // SeekHead
+    INFO, TRACKS, 0x1C53BB6B, // Cues
+    0x1043A770, // Chapters
+    0x1254C367, // Tags
+    0x1941A469, // Attachments
+];
+
+/// Timescale of the indexed video track.
+const VIDEO_TIMESCALE: u32 = 90000;
+
+/// Whether `header` (the first bytes of a file) starts an EBML document.
+pub fn is_mkv(header: &[u8]) -> bool {
+    header.starts_with(&EBML_HEADER.to_be_bytes())
+}
+
+/// Index an MKV file's first video track and all of its audio tracks.
+pub fn parse_mkv<R: Read + Seek>(reader: &mut R) -> io::Result<Mp4Metadata> {
+    let file_size = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(0))?;
+
+    let header = read_element_header(reader)?
+        .filter(|h| h.id == EBML_HEADER)
+        .ok_or_else(|| invalid("Not a Matroska file"))?;
+    let segment_pos = header.header_size + header.size.unwrap_or_default();
+    reader.seek(SeekFrom::Start(segment_pos))?;
+    let segment = read_element_header(reader)?
+        .filter(|h| h.id == SEGMENT)
+        .ok_or_else(|| invalid("No Segment in Matroska file"))?;
+    let segment_start = segment_pos + segment.header_size;
+    let segment_end = segment
+        .size
+        .map_or(file_size, |size| (segment_start + size).min(file_size));
+
+    let mut timestamp_scale = 1_000_000u64;
+    let mut duration = None;
+    let mut selected: Option<(Option<MkvTrack>, Vec<MkvTrack>)> = None;
+    let mut frames: HashMap<u64, Vec<Frame>> = HashMap::new();
+
+    let mut pos = segment_start;
+    while pos < segment_end {
+        reader.seek(SeekFrom::Start(pos))?;
+        let Some(header) = read_element_header(reader)? else {
+            break;
+        };
+        let content = pos + header.header_size;
+
+        if header.id == CLUSTER {
+            let Some((video, audio)) = &selected else {
+                return Err(invalid("Matroska clusters before the Tracks element"));
+            };
+            if frames.is_empty() {
+                for track in video.iter().chain(audio) {
+                    frames.insert(track.number, Vec::new());
+                }
+            }
+            pos = index_cluster(reader, content, header.size, segment_end, &mut frames)?;
+            continue;
+        }
+
+        let Some(size) = header.size else {
+            return Err(invalid("unknown-size Matroska element"));
+        };
+        match header.id {
+            INFO => for_each_child(reader, content, content + size, |reader, child| {
+                let size = child.size.unwrap_or_default();
+                match child.id {
+                    TIMESTAMP_SCALE => timestamp_scale = read_uint(reader, size)?.max(1),
+                    DURATION => duration = Some(read_float(reader, size)?),
+                    _ => {}
+                }
+                Ok(())
+            })?,
+            TRACKS => {
+                selected = Some(select_tracks(parse_tracks(
+                    reader,
+                    content,
+                    content + size,
+                )?))
+            }
+            _ => {}
+        }
+        pos = content + size;
+    }
+
+    let (video, audio) = selected.ok_or_else(|| invalid("No Tracks in Matroska file"))?;
+
+    // Shift timestamps so neither track starts before zero.
+    let origin = video
+        .iter()
+        .chain(&audio)
+        .filter_map(|t| frames.get(&t.number))
+        .filter_map(|f| f.iter().map(|f| f.timestamp).min())
+        .min()
+        .unwrap_or(0)
+        .min(0);
+
+    let video_track = match video {
+        Some(track) => {
+            let track_frames = frames.remove(&track.number).unwrap_or_default();
+            Some(video_track_info(
+                reader,
+                &track,
+                &track_frames,
+                timestamp_scale,
+                origin,
+            )?)
+        }
+        None => None,
+    };
+    let mut audio_tracks = Vec::with_capacity(audio.len());
+    for track in &audio {
+        let track_frames = frames.remove(&track.number).unwrap_or_default();
+        audio_tracks.push(audio_track_info(
+            reader,
+            track,
+            &track_frames,
+            timestamp_scale,
+            origin,
+        )?);
+    }
+
+    let duration_secs = match duration {
+        Some(duration) => duration * timestamp_scale as f64 / 1e9,
+        None => video_track
+            .as_ref()
+            .map_or(0.0, |v| v.duration as f64 / v.timescale as f64),
+    };
+
+    Ok(Mp4Metadata {
+        video_track,
+        audio_tracks,
+        duration_secs,
+    })
+}
+
+/// Pick the first video track and every audio track.
+fn select_tracks(tracks: Vec<MkvTrack>) -> (Option<MkvTrack>, Vec<MkvTrack>) {
+    let video = tracks
+        .iter()
+        .find(|t| t.track_type == TRACK_TYPE_VIDEO)
+        .cloned();
+    let audio = tracks
+        .into_iter()
+        .filter(|t| t.track_type == TRACK_TYPE_AUDIO)
+        .collect();
+    (video, audio)
+}
+
+/// Convert a timestamp in TimestampScale units to `timescale` ticks.
+fn to_ticks(timestamp: i64, timestamp_scale: u64, timescale: u32) -> u64 {
+    let ns = timestamp.max(0) as u128 * timestamp_scale as u128;
+    (ns * timescale as u128 / 1_000_000_000) as u64
+}
+
+/// Read up to 64 bytes of a frame, enough for any codec header we parse.
+fn read_frame_header<R: Read + Seek>(reader: &mut R, frame: Option<&Frame>) -> io::Result<Vec<u8>> {
+    let Some(frame) = frame else {
+        return Ok(Vec::new());
+    };
+    reader.seek(SeekFrom::Start(frame.offset))?;
+    let mut buf = vec![0u8; frame.size.min(64) as usize];
+    reader.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn video_track_info<R: Read + Seek>(
+    reader: &mut R,
+    track: &MkvTrack,
+    frames: &[Frame],
+    timestamp_scale: u64,
+    origin: i64,
+) -> io::Result<TrackInfo> {
+    let first_frame = read_frame_header(reader, frames.first())?;
+    let (codec, codec_private) = match track.codec_config(&first_frame) {
+        Some((codec, codec_private)) => (Some(codec), codec_private),
+        None => (None, Vec::new()),
+    };
+
+    let pts: Vec<u64> = frames
+        .iter()
+        .map(|f| to_ticks(f.timestamp - origin, timestamp_scale, VIDEO_TIMESCALE))
+        .collect();
+    let mut dts = pts.clone();
+    dts.sort_unstable();
+
+    let default_duration = track
+        .default_duration
+        .map(|ns| (ns as u128 * VIDEO_TIMESCALE as u128 / 1_000_000_000) as u32);
+    let mut samples = Vec::with_capacity(frames.len());
+    let mut last_duration = default_duration.unwrap_or(VIDEO_TIMESCALE / 24);
+    for (i, frame) in frames.iter().enumerate() {
+        let duration = match dts.get(i + 1) {
+            Some(next) => (next - dts[i]) as u32,
+            None => default_duration.unwrap_or(last_duration),
+        };
+        last_duration = duration;
+        samples.push(ResolvedSample {
+            index: i as u32,
+            file_offset: frame.offset,
+            size: frame.size,
+            duration,
+            composition_offset: (pts[i] as i64 - dts[i] as i64) as i32,
+            is_sync: frame.keyframe,
+            decode_timestamp: dts[i],
+        });
+    }
+    let duration = samples
+        .last()
+        .map_or(0, |s| s.decode_timestamp + s.duration as u64);
+
+    Ok(TrackInfo {
+        track_id: track.number as u32,
+        handler_type: *b"vide",
+        timescale: VIDEO_TIMESCALE,
+        duration,
+        width: track.width,
+        height: track.height,
+        sample_rate: 0,
+        channels: 0,
+        language: track.language.clone(),
+        name: track.name.clone(),
+        default: track.default,
+        codec,
+        codec_private,
+        sample_table: ResolvedSampleTable {
+            samples,
+            timescale: VIDEO_TIMESCALE,
+        },
+    })
+}
+
+/// Audio frames get consecutive decode timestamps from the first block's,
+/// each lasting the codec's frame length.
+fn audio_track_info<R: Read + Seek>(
+    reader: &mut R,
+    track: &MkvTrack,
+    frames: &[Frame],
+    timestamp_scale: u64,
+    origin: i64,
+) -> io::Result<TrackInfo> {
+    let first_frame = read_frame_header(reader, frames.first())?;
+    let (codec, codec_private) = match track.codec_config(&first_frame) {
+        Some((codec, codec_private)) => (Some(codec), codec_private),
+        None => (None, Vec::new()),
+    };
+
+    let timescale = track.sample_rate;
+    let frame_samples = track.frame_samples(&first_frame);
+    let start = frames.first().map_or(0, |f| {
+        to_ticks(f.timestamp - origin, timestamp_scale, timescale)
+    });
+    let samples: Vec<ResolvedSample> = frames
+        .iter()
+        .enumerate()
+        .map(|(i, frame)| ResolvedSample {
+            index: i as u32,
+            file_offset: frame.offset,
+            size: frame.size,
+            duration: frame_samples,
+            composition_offset: 0,
+            is_sync: true,
+            decode_timestamp: start + i as u64 * frame_samples as u64,
+        })
+        .collect();
+    let duration = samples
+        .last()
+        .map_or(0, |s| s.decode_timestamp + s.duration as u64);
+
+    Ok(TrackInfo {
+        track_id: track.number as u32,
+        handler_type: *b"soun",
+        timescale,
+        duration,
+        width: 0,
+        height: 0,
+        sample_rate: track.sample_rate,
+        channels: track.channels,
+        language: track.language.clone(),
+        name: track.name.clone(),
+        default: track.default,
+        codec,
+        codec_private,
+        sample_table: ResolvedSampleTable { samples, timescale },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fmp4::Codec;
+    use std::io::Cursor;
+
+    /// Encode an element with an 8-byte size.
+    fn element(id: u32, content: &[u8]) -> Vec<u8> {
+        let id_bytes = id.to_be_bytes();
+        let skip = id_bytes.iter().position(|&b| b != 0).unwrap();
+        let mut out = id_bytes[skip..].to_vec();
+        out.push(0x01);
+        out.extend_from_slice(&(content.len() as u64).to_be_bytes()[1..]);
+        out.extend_from_slice(content);
+        out
+    }
+
+    fn simple_block(track: u8, relative: i16, keyframe: bool, data: &[u8]) -> Vec<u8> {
+        let mut content = vec![0x80 | track];
+        content.extend_from_slice(&relative.to_be_bytes());
+        content.push(if keyframe { 0x80 } else { 0x00 });
+        content.extend_from_slice(data);
+        element(0xA3, &content)
+    }
+
+    /// An MKV with an H.264 track (I P B at 0, 80, 40 ms) and an AAC-LC
+    /// 48 kHz stereo track.
+    fn sample_mkv() -> Vec<u8> {
+        let avcc = vec![0x01, 0x64, 0x00, 0x1F, 0xFF, 0xE0, 0x00];
+        let mut video = element(0xD7, &[1]);
+        video.extend(element(0x83, &[1]));
+        video.extend(element(0x86, b"V_MPEG4/ISO/AVC"));
+        video.extend(element(0x63A2, &avcc));
+        video.extend(element(
+            0xE0,
+            &[element(0xB0, &[0x07, 0x80]), element(0xBA, &[0x04, 0x38])].concat(),
+        ));
+
+        let mut audio = element(0xD7, &[2]);
+        audio.extend(element(0x83, &[2]));
+        audio.extend(element(0x86, b"A_AAC"));
+        audio.extend(element(0x63A2, &[0x11, 0x90]));
+        audio.extend(element(
+            0xE1,
+            &[
+                element(0xB5, &48000.0f64.to_be_bytes()),
+                element(0x9F, &[2]),
+            ]
+            .concat(),
+        ));
+
+        let tracks = element(
+            TRACKS,
+            &[element(0xAE, &video), element(0xAE, &audio)].concat(),
+        );
+        let info = element(
+            INFO,
+            &[
+                element(TIMESTAMP_SCALE, &[0x0F, 0x42, 0x40]),
+                element(DURATION, &120.0f64.to_be_bytes()),
+            ]
+            .concat(),
+        );
+
+        let mut cluster = element(0xE7, &[0]);
+        cluster.extend(simple_block(1, 0, true, &[0xAA; 10]));
+        cluster.extend(simple_block(2, 0, true, &[0xCC; 4]));
+        cluster.extend(simple_block(1, 80, false, &[0xBB; 6]));
+        cluster.extend(simple_block(1, 40, false, &[0xBD; 5]));
+        cluster.extend(simple_block(2, 21, true, &[0xCD; 4]));
+
+        let segment = element(
+            SEGMENT,
+            &[info, tracks, element(CLUSTER, &cluster)].concat(),
+        );
+        let mut file = element(EBML_HEADER, &element(0x4282, b"matroska"));
+        file.extend(segment);
+        file
+    }
+
+    #[test]
+    fn test_detects_mkv() {
+        assert!(is_mkv(&sample_mkv()));
+        assert!(!is_mkv(b"\x00\x00\x00\x18ftypisom"));
+    }
+
+    #[test]
+    fn test_parse_mkv_tracks_and_timing() {
+        let data = sample_mkv();
+        let metadata = parse_mkv(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(metadata.duration_secs, 0.12);
+
+        let video = metadata.video_track.unwrap();
+        assert_eq!(video.codec, Some(Codec::Avc));
+        assert_eq!((video.width, video.height), (1920, 1080));
+        let samples = &video.sample_table.samples;
+        assert_eq!(samples.len(), 3);
+        // Decode order I P B; decode timestamps 0, 40, 80 ms.
+        let dts: Vec<u64> = samples.iter().map(|s| s.decode_timestamp).collect();
+        assert_eq!(dts, vec![0, 3600, 7200]);
+        let cto: Vec<i32> = samples.iter().map(|s| s.composition_offset).collect();
+        assert_eq!(cto, vec![0, 3600, -3600]);
+        assert!(samples[0].is_sync && !samples[1].is_sync);
+        assert_eq!(&data[samples[1].file_offset as usize..][..6], &[0xBB; 6]);
+
+        let audio = &metadata.audio_tracks[0];
+        assert_eq!(audio.codec, Some(Codec::Aac));
+        assert_eq!(
+            (audio.sample_rate, audio.channels, audio.timescale),
+            (48000, 2, 48000)
+        );
+        assert_eq!(audio.codec_private[26..28], [0x11, 0x90]);
+        let dts: Vec<u64> = audio
+            .sample_table
+            .samples
+            .iter()
+            .map(|s| s.decode_timestamp)
+            .collect();
+        assert_eq!(dts, vec![0, 1024]);
+    }
+
+    #[test]
+    fn test_oversized_and_truncated_elements_are_errors() {
+        // A CodecPrivate claiming ~256 TiB is rejected, not allocated.
+        let mut data = sample_mkv();
+        let pos = data
+            .windows(3)
+            .position(|w| w == [0x63, 0xA2, 0x01])
+            .unwrap();
+        data[pos + 3..pos + 10].copy_from_slice(&[0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE]);
+        assert!(parse_mkv(&mut Cursor::new(&data)).is_err());
+
+        // No truncation of a valid file panics.
+        let data = sample_mkv();
+        for len in 0..data.len() {
+            let _ = parse_mkv(&mut Cursor::new(&data[..len]));
+        }
+    }
+
+    #[test]
+    fn test_mkv_builds_prepared_media() {
+        let data = sample_mkv();
+        let metadata = parse_mkv(&mut Cursor::new(&data)).unwrap();
+        let prepared =
+            crate::build_prepared_media(&metadata, std::path::Path::new("/m/film.mkv")).unwrap();
+        assert_eq!(prepared.segments.len(), 1);
+        assert_eq!(prepared.segments[0].data_length, 10 + 6 + 5 + 4 + 4);
+        assert_eq!((prepared.width, prepared.height), (1920, 1080));
+    }
+}
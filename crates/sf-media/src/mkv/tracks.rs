@@ -0,0 +1,454 @@
+//! Parse the Tracks element and map Matroska codecs to fMP4 codec
+//! configuration.
+
+use std::io::{self, Read, Seek};
+
+use super::ebml::{for_each_child, read_binary, read_float, read_string, read_uint};
+use crate::fmp4::Codec;
+
+const TRACK_ENTRY: u32 = 0xAE;
+const TRACK_NUMBER: u32 = 0xD7;
+const TRACK_TYPE: u32 = 0x83;
+const FLAG_DEFAULT: u32 = 0x88;
+const NAME: u32 = 0x536E;
+const LANGUAGE: u32 = 0x22B59C;
+const LANGUAGE_BCP47: u32 = 0x22B59D;
+const CODEC_ID: u32 = 0x86;
+const CODEC_PRIVATE: u32 = 0x63A2;
+const DEFAULT_DURATION: u32 = 0x23E383;
+const VIDEO: u32 = 0xE0;
+const PIXEL_WIDTH: u32 = 0xB0;
+const PIXEL_HEIGHT: u32 = 0xBA;
+const AUDIO: u32 = 0xE1;
+const SAMPLING_FREQUENCY: u32 = 0xB5;
+const CHANNELS: u32 = 0x9F;
+const CONTENT_ENCODINGS: u32 = 0x6D80;
+const BLOCK_ADDITION_MAPPING: u32 = 0x41E4;
+const BLOCK_ADD_ID_TYPE: u32 = 0x41E7;
+const BLOCK_ADD_ID_EXTRA_DATA: u32 = 0x41ED;
+
+/// Matroska track types.
+pub const TRACK_TYPE_VIDEO: u64 = 1;
+pub const TRACK_TYPE_AUDIO: u64 = 2;
+
+/// `dvcC` and `dvvC` block addition types, carrying Dolby Vision
+/// configuration records.
+const DVCC: u64 = u32::from_be_bytes(*b"dvcC") as u64;
+const DVVC: u64 = u32::from_be_bytes(*b"dvvC") as u64;
+
+/// A Matroska track entry.
+#[derive(Debug, Clone, Default)]
+pub struct MkvTrack {
+    pub number: u64,
+    pub track_type: u64,
+    pub default: bool,
+    pub name: Option<String>,
+    /// Language tag, or `None` if undetermined.
+    pub language: Option<String>,
+    pub codec_id: String,
+    pub codec_private: Vec<u8>,
+    /// Frame duration in nanoseconds, if constant.
+    pub default_duration: Option<u64>,
+    pub width: u32,
+    pub height: u32,
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// Whether frames are compressed or encrypted (ContentEncodings), so not
+    /// usable as-is.
+    pub encoded: bool,
+    /// Dolby Vision configuration record from a block addition mapping.
+    pub dv_config: Option<Vec<u8>>,
+}
+
+impl MkvTrack {
+    /// Whether this track's frames can be served as fMP4 samples.
+    pub fn is_supported(&self) -> bool {
+        self.is_aac()
+            || (!self.encoded
+                && matches!(
+                    self.codec_id.as_str(),
+                    "V_MPEG4/ISO/AVC" | "V_MPEGH/ISO/HEVC" | "A_AC3" | "A_EAC3"
+                ))
+    }
+
+    fn is_aac(&self) -> bool {
+        !self.encoded && (self.codec_id == "A_AAC" || self.codec_id.starts_with("A_AAC/"))
+    }
+
+    /// Audio samples per frame.
+    pub fn frame_samples(&self, first_frame: &[u8]) -> u32 {
+        match self.codec_id.as_str() {
+            "A_AC3" => 1536,
+            "A_EAC3" => parse_eac3_header(first_frame).map_or(1536, |h| h.blocks * 256),
+            _ => 1024,
+        }
+    }
+
+    /// The fMP4 codec and codec private data for this track. Audio codecs
+    /// whose configuration lives in the bitstream need their first frame.
+    pub fn codec_config(&self, first_frame: &[u8]) -> Option<(Codec, Vec<u8>)> {
+        if self.encoded {
+            return None;
+        }
+        match self.codec_id.as_str() {
+            "V_MPEG4/ISO/AVC" if !self.codec_private.is_empty() => {
+                Some((Codec::Avc, self.codec_private.clone()))
+            }
+            "V_MPEGH/ISO/HEVC" if !self.codec_private.is_empty() => {
+                let codec = match &self.dv_config {
+                    Some(config) => Codec::DolbyVision {
+                        config: config.clone(),
+                    },
+                    None => Codec::Hevc,
+                };
+                Some((codec, self.codec_private.clone()))
+            }
+            "A_AC3" => Some((Codec::Ac3, dac3_from_frame(first_frame)?)),
+            "A_EAC3" => Some((Codec::Eac3, dec3_from_frame(first_frame)?)),
+            _ if self.is_aac() => {
+                let asc = if self.codec_private.is_empty() {
+                    audio_specific_config(&self.codec_id, self.sample_rate, self.channels)?
+                } else {
+                    self.codec_private.clone()
+                };
+                Some((Codec::Aac, esds_from_asc(&asc)))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Parse the children of a Tracks element spanning `[start, end)`.
+pub fn parse_tracks<R: Read + Seek>(
+    reader: &mut R,
+    start: u64,
+    end: u64,
+) -> io::Result<Vec<MkvTrack>> {
+    let mut tracks = Vec::new();
+    for_each_child(reader, start, end, |reader, header| {
+        if header.id == TRACK_ENTRY {
+            let pos = reader.stream_position()?;
+            let size = header.size.unwrap_or_default();
+            tracks.push(parse_track_entry(reader, pos, pos + size)?);
+        }
+        Ok(())
+    })?;
+    Ok(tracks)
+}
+
+fn parse_track_entry<R: Read + Seek>(reader: &mut R, start: u64, end: u64) -> io::Result<MkvTrack> {
+    let mut track = MkvTrack {
+        default: true,
+        ..Default::default()
+    };
+    // Language defaults to English; LanguageBCP47 takes precedence.
+    let mut language = Some("eng".to_string());
+    let mut language_bcp47 = None;
+    for_each_child(reader, start, end, |reader, header| {
+        let size = header.size.unwrap_or_default();
+        let pos = reader.stream_position()?;
+        match header.id {
+            TRACK_NUMBER => track.number = read_uint(reader, size)?,
+            TRACK_TYPE => track.track_type = read_uint(reader, size)?,
+            FLAG_DEFAULT => track.default = read_uint(reader, size)? != 0,
+            NAME => track.name = Some(read_string(reader, size)?).filter(|n| !n.is_empty()),
+            LANGUAGE => language = Some(read_string(reader, size)?),
+            LANGUAGE_BCP47 => language_bcp47 = Some(read_string(reader, size)?),
+            CODEC_ID => track.codec_id = read_string(reader, size)?,
+            CODEC_PRIVATE => track.codec_private = read_binary(reader, size)?,
+            DEFAULT_DURATION => track.default_duration = Some(read_uint(reader, size)?),
+            CONTENT_ENCODINGS => track.encoded = true,
+            VIDEO => for_each_child(reader, pos, pos + size, |reader, child| {
+                let size = child.size.unwrap_or_default();
+                match child.id {
+                    PIXEL_WIDTH => track.width = read_uint(reader, size)? as u32,
+                    PIXEL_HEIGHT => track.height = read_uint(reader, size)? as u32,
+                    _ => {}
+                }
+                Ok(())
+            })?,
+            AUDIO => for_each_child(reader, pos, pos + size, |reader, child| {
+                let size = child.size.unwrap_or_default();
+                match child.id {
+                    SAMPLING_FREQUENCY => track.sample_rate = read_float(reader, size)? as u32,
+                    CHANNELS => track.channels = read_uint(reader, size)? as u16,
+                    _ => {}
+                }
+                Ok(())
+            })?,
+            BLOCK_ADDITION_MAPPING => {
+                let mut add_type = 0;
+                let mut extra_data = None;
+                for_each_child(reader, pos, pos + size, |reader, child| {
+                    let size = child.size.unwrap_or_default();
+                    match child.id {
+                        BLOCK_ADD_ID_TYPE => add_type = read_uint(reader, size)?,
+                        BLOCK_ADD_ID_EXTRA_DATA => extra_data = Some(read_binary(reader, size)?),
+                        _ => {}
+                    }
+                    Ok(())
+                })?;
+                if matches!(add_type, DVCC | DVVC) {
+                    track.dv_config = extra_data;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    })?;
+    track.language = language_bcp47
+        .or(language)
+        .filter(|l| !l.is_empty() && l != "und");
+    if track.sample_rate == 0 && track.track_type == TRACK_TYPE_AUDIO {
+        track.sample_rate = 8000;
+    }
+    if track.channels == 0 {
+        track.channels = 1;
+    }
+    Ok(track)
+}
+
+// ---------------------------------------------------------------------------
+// AAC
+// ---------------------------------------------------------------------------
+
+/// Build an AudioSpecificConfig for the old `A_AAC/MPEG4/<profile>` codec
+/// IDs, which carry no CodecPrivate.
+fn audio_specific_config(codec_id: &str, sample_rate: u32, channels: u16) -> Option<Vec<u8>> {
+    const RATES: [u32; 13] = [
+        96000, 88200, 64000, 48000, 44100, 32000, 24000, 22050, 16000, 12000, 11025, 8000, 7350,
+    ];
+    let object_type: u16 = match codec_id.rsplit('/').next()? {
+        "MAIN" => 1,
+        "LC" | "A_AAC" => 2,
+        "SSR" => 3,
+        "LTP" => 4,
+        _ => 2,
+    };
+    let rate_index = RATES.iter().position(|&r| r == sample_rate)? as u16;
+    let config = (object_type << 11) | (rate_index << 7) | ((channels.min(7)) << 3);
+    Some(config.to_be_bytes().to_vec())
+}
+
+/// Wrap an AudioSpecificConfig in esds content (version + flags +
+/// ES_Descriptor).
+fn esds_from_asc(asc: &[u8]) -> Vec<u8> {
+    let dsi_len = asc.len() as u8;
+    let dcd_len = 13 + 2 + dsi_len;
+    let es_len = 3 + 2 + dcd_len + 3;
+
+    let mut esds = vec![0x00, 0x00, 0x00, 0x00]; // version + flags
+    esds.extend_from_slice(&[0x03, es_len]); // ES_Descriptor
+    esds.extend_from_slice(&[0x00, 0x01, 0x00]); // ES_ID, flags
+    esds.extend_from_slice(&[0x04, dcd_len]); // DecoderConfigDescriptor
+    esds.push(0x40); // objectTypeIndication: MPEG-4 audio
+    esds.push(0x15); // streamType: audio
+    esds.extend_from_slice(&[0x00, 0x00, 0x00]); // bufferSizeDB
+    esds.extend_from_slice(&0u32.to_be_bytes()); // maxBitrate
+    esds.extend_from_slice(&0u32.to_be_bytes()); // avgBitrate
+    esds.extend_from_slice(&[0x05, dsi_len]); // DecoderSpecificInfo
+    esds.extend_from_slice(asc);
+    esds.extend_from_slice(&[0x06, 0x01, 0x02]); // SLConfigDescriptor
+    esds
+}
+
+// ---------------------------------------------------------------------------
+// AC-3 / E-AC-3
+// ---------------------------------------------------------------------------
+
+/// Big-endian bit reader over a frame header.
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn read(&mut self, bits: usize) -> Option<u32> {
+        let mut value = 0u32;
+        for _ in 0..bits {
+            let byte = *self.data.get(self.pos / 8)?;
+            let bit = (byte >> (7 - self.pos % 8)) & 1;
+            value = (value << 1) | bit as u32;
+            self.pos += 1;
+        }
+        Some(value)
+    }
+}
+
+/// Big-endian bit writer for dac3/dec3 content.
+#[derive(Default)]
+struct BitWriter {
+    data: Vec<u8>,
+    bits: usize,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, bits: usize) {
+        for i in (0..bits).rev() {
+            if self.bits.is_multiple_of(8) {
+                self.data.push(0);
+            }
+            let bit = ((value >> i) & 1) as u8;
+            *self.data.last_mut().unwrap() |= bit << (7 - self.bits % 8);
+            self.bits += 1;
+        }
+    }
+}
+
+/// Build dac3 content from the first AC-3 sync frame.
+fn dac3_from_frame(frame: &[u8]) -> Option<Vec<u8>> {
+    let mut r = BitReader::new(frame);
+    if r.read(16)? != 0x0B77 {
+        return None;
+    }
+    r.read(16)?; // crc1
+    let fscod = r.read(2)?;
+    let frmsizecod = r.read(6)?;
+    let bsid = r.read(5)?;
+    let bsmod = r.read(3)?;
+    let acmod = r.read(3)?;
+    if acmod & 1 != 0 && acmod != 1 {
+        r.read(2)?; // cmixlev
+    }
+    if acmod & 4 != 0 {
+        r.read(2)?; // surmixlev
+    }
+    if acmod == 2 {
+        r.read(2)?; // dsurmod
+    }
+    let lfeon = r.read(1)?;
+
+    let mut w = BitWriter::default();
+    w.write(fscod, 2);
+    w.write(bsid, 5);
+    w.write(bsmod, 3);
+    w.write(acmod, 3);
+    w.write(lfeon, 1);
+    w.write(frmsizecod >> 1, 5); // bit_rate_code
+    w.write(0, 5); // reserved
+    Some(w.data)
+}
+
+/// Fields of an E-AC-3 sync frame header.
+struct Eac3Header {
+    frame_size: u32,
+    fscod: u32,
+    sample_rate: u32,
+    blocks: u32,
+    acmod: u32,
+    lfeon: u32,
+    bsid: u32,
+}
+
+fn parse_eac3_header(frame: &[u8]) -> Option<Eac3Header> {
+    let mut r = BitReader::new(frame);
+    if r.read(16)? != 0x0B77 {
+        return None;
+    }
+    r.read(2)?; // strmtyp
+    r.read(3)?; // substreamid
+    let frame_size = (r.read(11)? + 1) * 2;
+    let fscod = r.read(2)?;
+    let (sample_rate, blocks) = if fscod == 3 {
+        let fscod2 = r.read(2)?;
+        (*[24000, 22050, 16000].get(fscod2 as usize)?, 6)
+    } else {
+        let numblkscod = r.read(2)?;
+        (
+            [48000, 44100, 32000][fscod as usize],
+            [1, 2, 3, 6][numblkscod as usize],
+        )
+    };
+    let acmod = r.read(3)?;
+    let lfeon = r.read(1)?;
+    let bsid = r.read(5)?;
+    Some(Eac3Header {
+        frame_size,
+        fscod,
+        sample_rate,
+        blocks,
+        acmod,
+        lfeon,
+        bsid,
+    })
+}
+
+/// Build dec3 content (one independent substream) from the first E-AC-3
+/// sync frame.
+fn dec3_from_frame(frame: &[u8]) -> Option<Vec<u8>> {
+    let h = parse_eac3_header(frame)?;
+    let data_rate = h.frame_size * 8 * h.sample_rate / (h.blocks * 256) / 1000;
+
+    let mut w = BitWriter::default();
+    w.write(data_rate, 13);
+    w.write(0, 3); // num_ind_sub - 1
+    w.write(h.fscod, 2);
+    w.write(h.bsid, 5);
+    w.write(0, 1); // reserved
+    w.write(0, 1); // asvc
+    w.write(0, 3); // bsmod
+    w.write(h.acmod, 3);
+    w.write(h.lfeon, 1);
+    w.write(0, 3); // reserved
+    w.write(0, 4); // num_dep_sub
+    w.write(0, 1); // reserved
+    Some(w.data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_esds_wraps_audio_specific_config() {
+        let esds = esds_from_asc(&[0x12, 0x10]);
+        assert_eq!(
+            esds,
+            vec![
+                0x00, 0x00, 0x00, 0x00, 0x03, 0x19, 0x00, 0x01, 0x00, 0x04, 0x11, 0x40, 0x15, 0x00,
+                0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x02, 0x12, 0x10,
+                0x06, 0x01, 0x02,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_audio_specific_config_from_codec_id() {
+        // AAC-LC, 48 kHz, stereo.
+        assert_eq!(
+            audio_specific_config("A_AAC/MPEG4/LC", 48000, 2),
+            Some(vec![0x11, 0x90])
+        );
+        assert_eq!(audio_specific_config("A_AAC/MPEG4/LC", 12345, 2), None);
+    }
+
+    #[test]
+    fn test_dac3_from_frame() {
+        // 48 kHz, 448 kbps (frmsizecod 28), bsid 8, bsmod 0, 3/2 (acmod 7)
+        // with LFE.
+        let frame = [0x0B, 0x77, 0x00, 0x00, 0x1C, 0x40, 0xE1, 0xA0];
+        // acmod 7: cmixlev (2) + surmixlev (2) precede lfeon.
+        let dac3 = dac3_from_frame(&frame).unwrap();
+        // fscod 0 | bsid 8 | bsmod 0 | acmod 7 | lfeon 1 | bit_rate_code 14
+        assert_eq!(dac3, vec![0x10, 0x3D, 0xC0]);
+        assert!(dac3_from_frame(&[0x00, 0x00, 0x00]).is_none());
+    }
+
+    #[test]
+    fn test_dec3_from_frame() {
+        // Independent substream, 48 kHz, 6 blocks, 5.1, bsid 16,
+        // frmsiz 767 (1536-byte frames, 384 kbps).
+        let frame = [0x0B, 0x77, 0x02, 0xFF, 0x3F, 0x80];
+        let h = parse_eac3_header(&frame).unwrap();
+        assert_eq!((h.frame_size, h.sample_rate, h.blocks), (1536, 48000, 6));
+        assert_eq!((h.acmod, h.lfeon, h.bsid), (7, 1, 16));
+
+        let dec3 = dec3_from_frame(&frame).unwrap();
+        // data_rate 384 | num_ind_sub 0 | fscod 0 | bsid 16 | acmod 7 | lfeon 1
+        assert_eq!(dec3, vec![0x0C, 0x00, 0x20, 0x0F, 0x00]);
+    }
+}
@@ -1,8 +1,130 @@
-//! Extract codec configuration (avcC and esds) from MP4 sample description boxes.
+//! Extract codec configuration (avcC, hvcC, dvcC/dvvC, esds, dac3 and
+//! dec3) from MP4 sample description boxes.
 
 use std::io::{self, Read, Seek, SeekFrom};
 
-use super::atoms::{find_child_box, read_bytes, read_fullbox_header, read_u32};
+use super::atoms::{find_child_box, read_box_header, read_bytes, read_fullbox_header, read_u32};
+use crate::fmp4::Codec;
+
+/// Size of the fixed fields of a visual sample entry, before its child boxes.
+const VISUAL_SAMPLE_ENTRY_SIZE: u64 = 78;
+
+/// Size of the fixed fields of an audio sample entry, before its child boxes.
+const AUDIO_SAMPLE_ENTRY_SIZE: u64 = 28;
+
+/// Identify the codec of a video stsd's first sample entry and extract its
+/// configuration.
+///
+/// Returns the codec and its codec private data (avcC or hvcC content). HEVC
+/// entries carrying a dvcC/dvvC box (or `dvh1`/`dvhe` entries) become
+/// [`Codec::DolbyVision`]. Returns `None` for other codecs.
+/// The reader should be positioned at the start of stsd content.
+pub fn extract_video_config<R: Read + Seek>(reader: &mut R, stsd_content_size: u64) -> io::Result<Option<(Codec, Vec<u8>)>> {
+    let Some(entry) = read_sample_entry(reader, stsd_content_size, VISUAL_SAMPLE_ENTRY_SIZE)? else {
+        return Ok(None);
+    };
+
+    match &entry.entry_type {
+        b"avc1" | b"avc3" => Ok(entry.child(b"avcC").map(|avcc| (Codec::Avc, avcc))),
+        b"hvc1" | b"hev1" | b"dvh1" | b"dvhe" => {
+            let Some(hvcc) = entry.child(b"hvcC") else {
+                return Ok(None);
+            };
+            let dv_config = entry.child(b"dvvC").or_else(|| entry.child(b"dvcC"));
+            let codec = match dv_config {
+                Some(config) => Codec::DolbyVision { config },
+                // A Dolby Vision entry without its configuration can't be
+                // described to a player.
+                None if matches!(&entry.entry_type, b"dvh1" | b"dvhe") => return Ok(None),
+                None => Codec::Hevc,
+            };
+            Ok(Some((codec, hvcc)))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Identify the codec of an audio stsd's first sample entry and extract its
+/// configuration.
+///
+/// Returns the codec and its codec private data (esds content for AAC, dac3
+/// for AC-3, dec3 for E-AC-3), or `None` for other codecs.
+/// The reader should be positioned at the start of stsd content.
+pub fn extract_audio_config<R: Read + Seek>(reader: &mut R, stsd_content_size: u64) -> io::Result<Option<(Codec, Vec<u8>)>> {
+    let Some(entry) = read_sample_entry(reader, stsd_content_size, AUDIO_SAMPLE_ENTRY_SIZE)? else {
+        return Ok(None);
+    };
+    let (codec, config_type) = match &entry.entry_type {
+        b"mp4a" => (Codec::Aac, b"esds"),
+        b"ac-3" => (Codec::Ac3, b"dac3"),
+        b"ec-3" => (Codec::Eac3, b"dec3"),
+        _ => return Ok(None),
+    };
+    Ok(entry.child(config_type).map(|data| (codec, data)))
+}
+
+/// A sample entry's type and the child boxes following its fixed fields.
+struct SampleEntry {
+    entry_type: [u8; 4],
+    /// Type and content of each child box.
+    children: Vec<([u8; 4], Vec<u8>)>,
+}
+
+impl SampleEntry {
+    /// Content of the first child box of type `box_type`.
+    fn child(&self, box_type: &[u8; 4]) -> Option<Vec<u8>> {
+        self.children
+            .iter()
+            .find(|(t, _)| t == box_type)
+            .map(|(_, data)| data.clone())
+    }
+}
+
+/// Read the first sample entry of an stsd, whose child boxes follow
+/// `fixed_size` bytes of fields.
+fn read_sample_entry<R: Read + Seek>(
+    reader: &mut R,
+    stsd_content_size: u64,
+    fixed_size: u64,
+) -> io::Result<Option<SampleEntry>> {
+    let start = reader.stream_position()?;
+
+    // Read past fullbox header (4 bytes) and entry_count (4 bytes).
+    read_fullbox_header(reader)?;
+    let entry_count = read_u32(reader)?;
+    if entry_count == 0 || stsd_content_size < 8 {
+        return Ok(None);
+    }
+
+    let Some(entry) = read_box_header(reader)? else {
+        return Ok(None);
+    };
+    let entry_content = entry.content_size();
+    if entry.size == 0 || entry_content < fixed_size || entry.size > stsd_content_size - 8 {
+        return Ok(None);
+    }
+    reader.seek(SeekFrom::Current(fixed_size as i64))?;
+
+    let children_end = reader.stream_position()? + entry_content - fixed_size;
+    let mut children = Vec::new();
+    while reader.stream_position()? + 8 <= children_end {
+        let Some(child) = read_box_header(reader)? else {
+            break;
+        };
+        let child_end = reader.stream_position()? + child.content_size();
+        if child.size == 0 || child_end > children_end {
+            break;
+        }
+        let data = read_bytes(reader, child.content_size() as usize)?;
+        children.push((child.box_type, data));
+    }
+
+    reader.seek(SeekFrom::Start(start + stsd_content_size))?;
+    Ok(Some(SampleEntry {
+        entry_type: entry.box_type,
+        children,
+    }))
+}
 
 /// Extract the raw avcC box content from an stsd containing an avc1 sample entry.
 ///
@@ -194,4 +316,71 @@ mod tests {
             .unwrap();
         assert_eq!(result, esds_data);
     }
+
+    /// stsd content with a single sample entry: `fixed_size` zeroed fields
+    /// followed by `children`.
+    fn stsd_with_entry(entry_type: &[u8; 4], fixed_size: usize, children: &[Vec<u8>]) -> Vec<u8> {
+        let mut entry = vec![0u8; fixed_size];
+        for child in children {
+            entry.extend_from_slice(child);
+        }
+        let mut stsd_content = Vec::new();
+        stsd_content.extend_from_slice(&boxes::fullbox_header(0, 0));
+        stsd_content.extend_from_slice(&1u32.to_be_bytes());
+        stsd_content.extend_from_slice(&boxes::write_box(entry_type, &entry));
+        stsd_content
+    }
+
+    #[test]
+    fn test_extract_video_config_hevc_and_dolby_vision() {
+        let hvcc = vec![0x01, 0x02, 0x20, 0x00, 0x00, 0x00];
+        // Profile 8, level 6, RPU + BL, compatibility id 1.
+        let dvvc = vec![1, 0, (8 << 1), (6 << 3) | 0x05, 0x10, 0, 0, 0];
+
+        let stsd = stsd_with_entry(b"hvc1", 78, &[boxes::write_box(b"hvcC", &hvcc)]);
+        let mut cursor = Cursor::new(&stsd);
+        let (codec, private) = extract_video_config(&mut cursor, stsd.len() as u64)
+            .unwrap()
+            .unwrap();
+        assert_eq!(codec, Codec::Hevc);
+        assert_eq!(private, hvcc);
+
+        let stsd = stsd_with_entry(
+            b"hvc1",
+            78,
+            &[boxes::write_box(b"hvcC", &hvcc), boxes::write_box(b"dvvC", &dvvc)],
+        );
+        let mut cursor = Cursor::new(&stsd);
+        let (codec, private) = extract_video_config(&mut cursor, stsd.len() as u64)
+            .unwrap()
+            .unwrap();
+        assert_eq!(codec, Codec::DolbyVision { config: dvvc });
+        assert_eq!(private, hvcc);
+
+        // dvh1 without its Dolby Vision configuration can't be served.
+        let stsd = stsd_with_entry(b"dvh1", 78, &[boxes::write_box(b"hvcC", &hvcc)]);
+        let mut cursor = Cursor::new(&stsd);
+        assert!(extract_video_config(&mut cursor, stsd.len() as u64)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_extract_audio_config_eac3() {
+        let dec3 = vec![0x06, 0x00, 0x20, 0x0F, 0x00];
+        let stsd = stsd_with_entry(b"ec-3", 28, &[boxes::write_box(b"dec3", &dec3)]);
+        let mut cursor = Cursor::new(&stsd);
+        let (codec, private) = extract_audio_config(&mut cursor, stsd.len() as u64)
+            .unwrap()
+            .unwrap();
+        assert_eq!(codec, Codec::Eac3);
+        assert_eq!(private, dec3);
+
+        // Codecs the fMP4 writer can't describe are not extracted.
+        let stsd = stsd_with_entry(b"Opus", 28, &[boxes::write_box(b"dOps", &[0x00, 0x02])]);
+        let mut cursor = Cursor::new(&stsd);
+        assert!(extract_audio_config(&mut cursor, stsd.len() as u64)
+            .unwrap()
+            .is_none());
+    }
 }
@@ -12,11 +12,14 @@ use std::io::{self, Read, Seek, SeekFrom};
 use atoms::{find_child_box, read_box_header, read_fullbox_header, read_u16, read_u32, read_u64, skip_box};
 pub use sample_table::{ResolvedSample, ResolvedSampleTable};
 
+use crate::fmp4::Codec;
+
 /// Parsed metadata from an MP4 file's moov atom.
 #[derive(Debug, Clone)]
 pub struct Mp4Metadata {
     pub video_track: Option<TrackInfo>,
-    pub audio_track: Option<TrackInfo>,
+    /// Audio tracks in file order.
+    pub audio_tracks: Vec<TrackInfo>,
     pub duration_secs: f64,
 }
 
@@ -31,6 +34,15 @@ pub struct TrackInfo {
     pub height: u32,
     pub sample_rate: u32,
     pub channels: u16,
+    /// ISO 639-2 language code, or `None` if undetermined.
+    pub language: Option<String>,
+    /// Track title, if the file names the track.
+    pub name: Option<String>,
+    /// Whether the track is enabled (MP4) or flagged default (MKV); players
+    /// should pick it over the other tracks of its type.
+    pub default: bool,
+    /// Codec of the first sample entry, or `None` if it can't be served.
+    pub codec: Option<Codec>,
     pub codec_private: Vec<u8>,
     pub sample_table: ResolvedSampleTable,
 }
@@ -86,7 +98,7 @@ pub fn parse_moov<R: Read + Seek>(reader: &mut R) -> io::Result<Mp4Metadata> {
 
     // Iterate trak boxes.
     let mut video_track = None;
-    let mut audio_track = None;
+    let mut audio_tracks = Vec::new();
 
     let mut pos = moov_start;
     let moov_end = moov_start + moov_content_size;
@@ -107,7 +119,7 @@ pub fn parse_moov<R: Read + Seek>(reader: &mut R) -> io::Result<Mp4Metadata> {
             if let Ok(Some(track)) = parse_trak(reader, trak_start, trak_content) {
                 match &track.handler_type {
                     b"vide" if video_track.is_none() => video_track = Some(track),
-                    b"soun" if audio_track.is_none() => audio_track = Some(track),
+                    b"soun" => audio_tracks.push(track),
                     _ => {}
                 }
             }
@@ -118,7 +130,7 @@ pub fn parse_moov<R: Read + Seek>(reader: &mut R) -> io::Result<Mp4Metadata> {
 
     Ok(Mp4Metadata {
         video_track,
-        audio_track,
+        audio_tracks,
         duration_secs,
     })
 }
@@ -153,7 +165,7 @@ fn parse_trak<R: Read + Seek>(
         Some(h) => h,
         None => return Ok(None),
     };
-    let (track_id, tkhd_width, tkhd_height) = parse_tkhd(reader, &tkhd)?;
+    let (track_id, enabled, tkhd_width, tkhd_height) = parse_tkhd(reader, &tkhd)?;
 
     // Find mdia container.
     reader.seek(SeekFrom::Start(trak_start))?;
@@ -170,7 +182,7 @@ fn parse_trak<R: Read + Seek>(
         Some(h) => h,
         None => return Ok(None),
     };
-    let (timescale, duration) = parse_mdhd(reader, &mdhd)?;
+    let (timescale, duration, language) = parse_mdhd(reader, &mdhd)?;
 
     // Parse hdlr for handler_type.
     reader.seek(SeekFrom::Start(mdia_start))?;
@@ -178,7 +190,7 @@ fn parse_trak<R: Read + Seek>(
         Some(h) => h,
         None => return Ok(None),
     };
-    let handler_type = parse_hdlr(reader, &hdlr)?;
+    let (handler_type, name) = parse_hdlr(reader, &hdlr)?;
 
     // Only process video/audio tracks.
     if &handler_type != b"vide" && &handler_type != b"soun" {
@@ -203,37 +215,39 @@ fn parse_trak<R: Read + Seek>(
     let stbl_content = stbl.content_size();
 
     // Extract codec config from stsd inside stbl.
-    let (codec_private, sample_rate, channels) = if &handler_type == b"vide" {
-        // Look for avcC.
+    let (codec_config, sample_rate, channels) = if &handler_type == b"vide" {
+        // Look for avcC, or hvcC plus any Dolby Vision config.
         reader.seek(SeekFrom::Start(stbl_start))?;
         let stsd = find_child_box(reader, stbl_content, b"stsd")?;
-        let cp = if let Some(stsd_h) = stsd {
+        let config = if let Some(stsd_h) = stsd {
             let stsd_pos = reader.stream_position()?;
             let stsd_content_size = stsd_h.content_size();
             reader.seek(SeekFrom::Start(stsd_pos))?;
-            codec_config::extract_avcc(reader, stsd_content_size)?
-                .unwrap_or_default()
+            codec_config::extract_video_config(reader, stsd_content_size)?
         } else {
-            Vec::new()
+            None
         };
-        (cp, 0, 0)
+        (config, 0, 0)
     } else {
-        // Look for esds.
+        // Look for esds or dec3.
         reader.seek(SeekFrom::Start(stbl_start))?;
         let stsd = find_child_box(reader, stbl_content, b"stsd")?;
-        let (cp, sr, ch) = if let Some(stsd_h) = stsd {
+        let (config, sr, ch) = if let Some(stsd_h) = stsd {
             let stsd_pos = reader.stream_position()?;
             let stsd_content_size = stsd_h.content_size();
-            // Extract sample rate and channels from mp4a header.
+            // Extract sample rate and channels from the sample entry header.
             let (sr, ch) = extract_audio_params(reader, stsd_content_size)?;
             reader.seek(SeekFrom::Start(stsd_pos))?;
-            let esds = codec_config::extract_esds(reader, stsd_content_size)?
-                .unwrap_or_default();
-            (esds, sr, ch)
+            let config = codec_config::extract_audio_config(reader, stsd_content_size)?;
+            (config, sr, ch)
         } else {
-            (Vec::new(), 0, 0)
+            (None, 0, 0)
         };
-        (cp, sr, ch)
+        (config, sr, ch)
+    };
+    let (codec, codec_private) = match codec_config {
+        Some((codec, codec_private)) => (Some(codec), codec_private),
+        None => (None, Vec::new()),
     };
 
     // Parse sample table.
@@ -249,14 +263,19 @@ fn parse_trak<R: Read + Seek>(
         height: tkhd_height,
         sample_rate,
         channels,
+        language,
+        name,
+        default: enabled,
+        codec,
         codec_private,
         sample_table,
     }))
 }
 
-/// Parse tkhd, return (track_id, width, height). Width/height are 16.16 fixed point.
-fn parse_tkhd<R: Read + Seek>(reader: &mut R, _header: &atoms::BoxHeader) -> io::Result<(u32, u32, u32)> {
-    let (version, _flags) = read_fullbox_header(reader)?;
+/// Parse tkhd, return (track_id, enabled, width, height). Width/height are 16.16 fixed point.
+fn parse_tkhd<R: Read + Seek>(reader: &mut R, _header: &atoms::BoxHeader) -> io::Result<(u32, bool, u32, u32)> {
+    let (version, flags) = read_fullbox_header(reader)?;
+    let enabled = flags & 0x000001 != 0;
     if version == 1 {
         let _creation = read_u64(reader)?;
         let _modification = read_u64(reader)?;
@@ -268,7 +287,7 @@ fn parse_tkhd<R: Read + Seek>(reader: &mut R, _header: &atoms::BoxHeader) -> io:
         reader.read_exact(&mut skip)?;
         let width = read_u32(reader)? >> 16;
         let height = read_u32(reader)? >> 16;
-        Ok((track_id, width, height))
+        Ok((track_id, enabled, width, height))
     } else {
         let _creation = read_u32(reader)?;
         let _modification = read_u32(reader)?;
@@ -279,53 +298,81 @@ fn parse_tkhd<R: Read + Seek>(reader: &mut R, _header: &atoms::BoxHeader) -> io:
         reader.read_exact(&mut skip)?;
         let width = read_u32(reader)? >> 16;
         let height = read_u32(reader)? >> 16;
-        Ok((track_id, width, height))
+        Ok((track_id, enabled, width, height))
     }
 }
 
-/// Parse mdhd, return (timescale, duration).
-fn parse_mdhd<R: Read>(reader: &mut R, _header: &atoms::BoxHeader) -> io::Result<(u32, u64)> {
+/// Parse mdhd, return (timescale, duration, language).
+fn parse_mdhd<R: Read>(reader: &mut R, _header: &atoms::BoxHeader) -> io::Result<(u32, u64, Option<String>)> {
     let (version, _flags) = read_fullbox_header(reader)?;
-    if version == 1 {
+    let (timescale, duration) = if version == 1 {
         let _creation = read_u64(reader)?;
         let _modification = read_u64(reader)?;
         let timescale = read_u32(reader)?;
         let duration = read_u64(reader)?;
-        Ok((timescale, duration))
+        (timescale, duration)
     } else {
         let _creation = read_u32(reader)?;
         let _modification = read_u32(reader)?;
         let timescale = read_u32(reader)?;
         let duration = read_u32(reader)? as u64;
-        Ok((timescale, duration))
-    }
+        (timescale, duration)
+    };
+    let language = decode_language(read_u16(reader)?);
+    Ok((timescale, duration, language))
 }
 
-/// Parse hdlr, return handler_type.
-fn parse_hdlr<R: Read>(reader: &mut R, _header: &atoms::BoxHeader) -> io::Result<[u8; 4]> {
+/// Decode a packed ISO 639-2 language code (three 5-bit letters offset by
+/// 0x60). "und" and malformed codes yield `None`.
+fn decode_language(packed: u16) -> Option<String> {
+    let code: String = [10, 5, 0]
+        .iter()
+        .map(|shift| (((packed >> shift) & 0x1F) as u8 + 0x60) as char)
+        .collect();
+    (code.chars().all(|c| c.is_ascii_lowercase()) && code != "und").then_some(code)
+}
+
+/// Parse hdlr, return (handler_type, name). Muxer default names like
+/// "SoundHandler" are dropped.
+fn parse_hdlr<R: Read>(reader: &mut R, header: &atoms::BoxHeader) -> io::Result<([u8; 4], Option<String>)> {
     let (_version, _flags) = read_fullbox_header(reader)?;
     let _pre_defined = read_u32(reader)?;
     let mut handler = [0u8; 4];
     reader.read_exact(&mut handler)?;
-    Ok(handler)
+
+    // reserved (12), then a NUL-terminated name filling the rest of the box.
+    let name_size = header.content_size().saturating_sub(4 + 4 + 4 + 12);
+    if name_size == 0 || name_size > 1024 {
+        return Ok((handler, None));
+    }
+    let mut skip = [0u8; 12];
+    reader.read_exact(&mut skip)?;
+    let mut raw = vec![0u8; name_size as usize];
+    reader.read_exact(&mut raw)?;
+    let name = String::from_utf8_lossy(&raw)
+        .trim_end_matches('\0')
+        .trim()
+        .to_string();
+    let generic = name.is_empty() || name.ends_with("Handler") || name.starts_with("Core Media");
+    Ok((handler, (!generic).then_some(name)))
 }
 
-/// Extract audio sample rate and channel count from mp4a sample entry inside stsd.
+/// Extract audio sample rate and channel count from the first (mp4a, ec-3,
+/// ...) sample entry inside stsd.
 fn extract_audio_params<R: Read + Seek>(reader: &mut R, stsd_content_size: u64) -> io::Result<(u32, u16)> {
-    let start = reader.stream_position()?;
     // Skip fullbox header (4) + entry_count (4).
     read_fullbox_header(reader)?;
-    let _entry_count = read_u32(reader)?;
-
-    let remaining = stsd_content_size - (reader.stream_position()? - start);
+    let entry_count = read_u32(reader)?;
+    if entry_count == 0 || stsd_content_size < 8 {
+        return Ok((0, 0));
+    }
 
-    let mp4a = match find_child_box(reader, remaining, b"mp4a")? {
-        Some(h) => h,
-        None => return Ok((0, 0)),
-    };
-    let _ = mp4a;
+    match read_box_header(reader)? {
+        Some(h) if h.content_size() >= 28 => {}
+        _ => return Ok((0, 0)),
+    }
 
-    // Inside mp4a sample entry:
+    // Inside the audio sample entry:
     // reserved (6) + data_ref_index (2) + reserved (8) = 16 bytes
     // then channel_count (2) + sample_size (2) + pre_defined (2) + reserved (2) = 8 bytes
     // then sample_rate (4, fixed 16.16)
@@ -399,7 +446,7 @@ mod tests {
 
         // We should find a video track.
         assert!(metadata.video_track.is_some());
-        assert!(metadata.audio_track.is_none());
+        assert!(metadata.audio_tracks.is_empty());
 
         let video = metadata.video_track.unwrap();
         assert_eq!(video.track_id, 1);
@@ -407,9 +454,50 @@ mod tests {
         assert_eq!(video.width, 1920);
         assert_eq!(video.height, 1080);
         assert_eq!(&video.handler_type, b"vide");
+        assert_eq!(video.codec, Some(fmp4::Codec::Avc));
 
         // The init segment has empty sample tables (fragmented MP4),
         // so we expect 0 samples in the parsed moov.
         assert_eq!(video.sample_table.samples.len(), 0);
     }
+
+    /// HEVC Dolby Vision video and E-AC-3 audio survive a write/parse
+    /// roundtrip with their codec configuration.
+    #[test]
+    fn test_parse_moov_dolby_vision_eac3() {
+        let dv_config = vec![1, 0, (8 << 1), (6 << 3) | 0x05, 0x10, 0, 0, 0];
+        let video_config = fmp4::TrackConfig {
+            track_id: 1,
+            timescale: 24000,
+            codec: fmp4::Codec::DolbyVision { config: dv_config.clone() },
+            width: 3840,
+            height: 2160,
+            sample_rate: 0,
+            channels: 0,
+            codec_private: vec![0x01, 0x02, 0x20, 0x00, 0x00, 0x00],
+        };
+        let audio_config = fmp4::TrackConfig {
+            track_id: 2,
+            timescale: 48000,
+            codec: fmp4::Codec::Eac3,
+            width: 0,
+            height: 0,
+            sample_rate: 48000,
+            channels: 6,
+            codec_private: vec![0x06, 0x00, 0x20, 0x0F, 0x00],
+        };
+
+        let init = fmp4::write_init_segment_multi(&video_config, &audio_config);
+        let metadata = parse_moov(&mut std::io::Cursor::new(&init)).unwrap();
+
+        let video = metadata.video_track.unwrap();
+        assert_eq!(video.codec, Some(fmp4::Codec::DolbyVision { config: dv_config }));
+        assert_eq!(video.codec_private, video_config.codec_private);
+
+        let audio = &metadata.audio_tracks[0];
+        assert_eq!(audio.codec, Some(fmp4::Codec::Eac3));
+        assert_eq!(audio.codec_private, audio_config.codec_private);
+        assert_eq!(audio.sample_rate, 48000);
+        assert_eq!(audio.channels, 6);
+    }
 }
@@ -3,21 +3,32 @@
 //! This is the core of zero-copy HLS serving: it precomputes moof boxes and
 //! data ranges so that at serve time, the server only needs to concatenate
 //! pre-built bytes from RAM with sample data read from the source file.
+//!
+//! The default audio track is muxed into the main segments. Every other
+//! audio track becomes an alternate rendition with audio-only segments cut
+//! at the same times, listed in the master playlist's audio group.
 
+use std::collections::HashSet;
 use std::path::Path;
 
 use crate::fmp4::boxes::{
     self, TrunSampleFull, TrunSampleSimple,
 };
 use crate::fmp4::{Codec, TrackConfig};
-use crate::hls::{generate_media_playlist, MediaPlaylist, Segment};
-use crate::mp4::Mp4Metadata;
+use crate::hls::{
+    generate_media_playlist, MasterPlaylist, MediaPlaylist, MediaType, Rendition, Segment,
+    Variant,
+};
+use crate::mp4::{Mp4Metadata, ResolvedSample, TrackInfo};
 
-use super::types::{DataRange, PrecomputedSegment, PreparedMedia};
+use super::types::{AudioRendition, DataRange, PrecomputedSegment, PreparedMedia};
 
 /// Target HLS segment duration in seconds.
 const TARGET_SEGMENT_SECS: f64 = 6.0;
 
+/// GROUP-ID of the audio renditions in the master playlist.
+const AUDIO_GROUP: &str = "audio";
+
 /// Build a fully prepared media file for zero-copy HLS serving.
 pub fn build_prepared_media(
     metadata: &Mp4Metadata,
@@ -27,13 +38,32 @@ pub fn build_prepared_media(
         .video_track
         .as_ref()
         .ok_or("No video track found")?;
-    let audio = metadata.audio_track.as_ref();
+
+    // The source's codecs are passed through unchanged, so they must be
+    // ones the fMP4 writer can describe. Audio tracks that aren't are left
+    // out, unless that leaves none.
+    let video_codec = video
+        .codec
+        .clone()
+        .filter(Codec::is_video)
+        .ok_or("Unsupported video codec")?;
+    let audio_tracks: Vec<(&TrackInfo, Codec)> = metadata
+        .audio_tracks
+        .iter()
+        .filter_map(|t| Some((t, t.codec.clone().filter(|c| !c.is_video())?)))
+        .collect();
+    if audio_tracks.is_empty() && !metadata.audio_tracks.is_empty() {
+        return Err("Unsupported audio codec".into());
+    }
+    let primary = audio_tracks.iter().position(|(t, _)| t.default).unwrap_or(0);
+    let audio = audio_tracks.get(primary).map(|(t, _)| *t);
+    let audio_codec = audio_tracks.get(primary).map(|(_, c)| c.clone());
 
     // Build init segment.
     let video_config = TrackConfig {
         track_id: 1,
         timescale: video.timescale,
-        codec: Codec::Avc,
+        codec: video_codec,
         width: video.width,
         height: video.height,
         sample_rate: 0,
@@ -41,11 +71,11 @@ pub fn build_prepared_media(
         codec_private: video.codec_private.clone(),
     };
 
-    let init_segment = if let Some(audio_track) = audio {
+    let init_segment = if let (Some(audio_track), Some(audio_codec)) = (audio, audio_codec) {
         let audio_config = TrackConfig {
             track_id: 2,
             timescale: audio_track.timescale,
-            codec: Codec::Aac,
+            codec: audio_codec,
             width: 0,
             height: 0,
             sample_rate: audio_track.sample_rate,
@@ -123,6 +153,7 @@ pub fn build_prepared_media(
 
     // Build precomputed segments.
     let mut segments = Vec::with_capacity(segment_ranges.len());
+    let mut segment_times = Vec::with_capacity(segment_ranges.len());
 
     for (seg_idx, &(vs_start, vs_end)) in segment_ranges.iter().enumerate() {
         let seg_video_samples = &video_samples[vs_start..vs_end];
@@ -142,26 +173,14 @@ pub fn build_prepared_media(
         let start_time_secs = video_base_dts as f64 / video_ts;
         let end_time_secs = video_end_dts as f64 / video_ts;
         let duration_secs = end_time_secs - start_time_secs;
+        segment_times.push((start_time_secs, end_time_secs));
 
         // Find corresponding audio samples by time range.
         let audio_data = audio.map(|audio_track| {
-            let audio_ts = audio_track.timescale as f64;
-            let audio_start_time = video_base_dts as f64 / video_ts;
-            let audio_end_time = video_end_dts as f64 / video_ts;
-            let audio_start_tick = (audio_start_time * audio_ts) as u64;
-            let audio_end_tick = (audio_end_time * audio_ts) as u64;
-
-            let audio_samples = &audio_track.sample_table.samples;
-            let as_start = audio_samples
-                .iter()
-                .position(|s| s.decode_timestamp >= audio_start_tick)
-                .unwrap_or(audio_samples.len());
-            let as_end = audio_samples
-                .iter()
-                .position(|s| s.decode_timestamp >= audio_end_tick)
-                .unwrap_or(audio_samples.len());
-
-            (audio_track, &audio_samples[as_start..as_end], as_start, audio_start_tick)
+            (
+                audio_track,
+                samples_in_range(audio_track, start_time_secs, end_time_secs),
+            )
         });
 
         // Collect video and audio data ranges separately so mdat layout
@@ -178,7 +197,7 @@ pub fn build_prepared_media(
 
         let mut audio_ranges: Vec<DataRange> = Vec::new();
         let mut audio_data_size: u64 = 0;
-        if let Some((_, audio_seg_samples, _, _)) = &audio_data {
+        if let Some((_, audio_seg_samples)) = &audio_data {
             for s in *audio_seg_samples {
                 audio_ranges.push(DataRange {
                     file_offset: s.file_offset,
@@ -225,7 +244,7 @@ pub fn build_prepared_media(
             8 + video_tfhd.len() + video_tfdt.len() + video_trun_box_size;
 
         // Audio traf (optional).
-        let audio_traf_components = audio_data.as_ref().map(|(audio_track, audio_seg_samples, _, _)| {
+        let audio_traf_components = audio_data.as_ref().map(|(audio_track, audio_seg_samples)| {
             let audio_tfhd = boxes::write_tfhd(2); // track_id=2
             let audio_base_dts = if !audio_seg_samples.is_empty() {
                 audio_seg_samples[0].decode_timestamp
@@ -321,6 +340,60 @@ pub fn build_prepared_media(
     };
     let variant_playlist = generate_media_playlist(&playlist);
 
+    // Alternate audio renditions.
+    let mut used_names = HashSet::new();
+    let mut renditions = Vec::new();
+    let mut audio_renditions = Vec::new();
+    for (i, (track, codec)) in audio_tracks.iter().enumerate() {
+        let name = rendition_name(track, i, &mut used_names);
+        let uri = if i == primary {
+            None
+        } else {
+            let n = audio_renditions.len();
+            audio_renditions.push(build_audio_rendition(
+                n,
+                track,
+                codec,
+                name.clone(),
+                &segment_times,
+                target_duration,
+            ));
+            Some(format!("audio_{n}.m3u8"))
+        };
+        renditions.push(Rendition {
+            media_type: MediaType::Audio,
+            group_id: AUDIO_GROUP.to_string(),
+            language: track.language.clone(),
+            name,
+            default: i == primary,
+            autoselect: true,
+            forced: false,
+            channels: Some(track.channels),
+            uri,
+        });
+    }
+    if audio_renditions.is_empty() {
+        renditions.clear();
+    }
+
+    let bandwidth = peak_bandwidth(&segments)
+        + audio_renditions
+            .iter()
+            .map(|r| peak_bandwidth(&r.segments))
+            .max()
+            .unwrap_or(0);
+    let master = MasterPlaylist {
+        variants: vec![Variant {
+            bandwidth,
+            resolution: Some((video.width, video.height)),
+            codecs: String::new(),
+            uri: "index.m3u8".to_string(),
+            audio: (!renditions.is_empty()).then(|| AUDIO_GROUP.to_string()),
+            subtitles: None,
+        }],
+        renditions,
+    };
+
     Ok(PreparedMedia {
         file_path: file_path.to_path_buf(),
         width: video.width,
@@ -328,11 +401,159 @@ pub fn build_prepared_media(
         duration_secs: metadata.duration_secs,
         init_segment,
         variant_playlist,
+        master,
         segments,
+        audio_renditions,
         target_duration,
     })
 }
 
+/// Samples of `track` whose decode time falls in `[start_secs, end_secs)`.
+fn samples_in_range(track: &TrackInfo, start_secs: f64, end_secs: f64) -> &[ResolvedSample] {
+    let timescale = track.timescale as f64;
+    let start_tick = (start_secs * timescale) as u64;
+    let end_tick = (end_secs * timescale) as u64;
+
+    let samples = &track.sample_table.samples;
+    let start = samples
+        .iter()
+        .position(|s| s.decode_timestamp >= start_tick)
+        .unwrap_or(samples.len());
+    let end = samples
+        .iter()
+        .position(|s| s.decode_timestamp >= end_tick)
+        .unwrap_or(samples.len());
+    &samples[start..end.max(start)]
+}
+
+/// Build the `n`th alternate audio rendition: `track` alone, cut into one
+/// segment per `(start_secs, end_secs)` of the video segments.
+fn build_audio_rendition(
+    n: usize,
+    track: &TrackInfo,
+    codec: &Codec,
+    name: String,
+    segment_times: &[(f64, f64)],
+    target_duration: u32,
+) -> AudioRendition {
+    let init_segment = crate::fmp4::write_init_segment(&TrackConfig {
+        track_id: 1,
+        timescale: track.timescale,
+        codec: codec.clone(),
+        width: 0,
+        height: 0,
+        sample_rate: track.sample_rate,
+        channels: track.channels,
+        codec_private: track.codec_private.clone(),
+    });
+
+    let mut segments = Vec::with_capacity(segment_times.len());
+    for (seg_idx, &(start_time_secs, end_time_secs)) in segment_times.iter().enumerate() {
+        let seg_samples = samples_in_range(track, start_time_secs, end_time_secs);
+        let base_dts = seg_samples.first().map_or(
+            (start_time_secs * track.timescale as f64) as u64,
+            |s| s.decode_timestamp,
+        );
+
+        let mut ranges: Vec<DataRange> = seg_samples
+            .iter()
+            .map(|s| DataRange {
+                file_offset: s.file_offset,
+                length: s.size as u64,
+            })
+            .collect();
+        let data_length: u64 = ranges.iter().map(|r| r.length).sum();
+        ranges.sort_by_key(|r| r.file_offset);
+
+        let trun_samples: Vec<TrunSampleSimple> = seg_samples
+            .iter()
+            .map(|s| TrunSampleSimple {
+                duration: s.duration,
+                size: s.size,
+            })
+            .collect();
+        let mfhd = boxes::write_mfhd((seg_idx + 1) as u32);
+        let tfhd = boxes::write_tfhd(1);
+        let tfdt = boxes::write_tfdt(base_dts);
+        let mdat_header = boxes::write_mdat_header(data_length);
+
+        // The moof size doesn't depend on the data offset, so build it once
+        // to measure, then again with the real offset.
+        let build_moof = |data_offset: i32| {
+            let trun = boxes::write_trun_simple(&trun_samples, data_offset);
+            let traf = boxes::write_container_box(b"traf", &[&tfhd, &tfdt, &trun]);
+            boxes::write_container_box(b"moof", &[&mfhd, &traf])
+        };
+        let moof_size = build_moof(0).len();
+        let moof_bytes = build_moof((moof_size + mdat_header.len()) as i32);
+
+        segments.push(PrecomputedSegment {
+            index: seg_idx as u32,
+            start_time_secs,
+            duration_secs: end_time_secs - start_time_secs,
+            moof_bytes,
+            mdat_header,
+            video_data_ranges: Vec::new(),
+            audio_data_ranges: merge_data_ranges(&ranges),
+            data_length,
+        });
+    }
+
+    let playlist = generate_media_playlist(&MediaPlaylist {
+        target_duration,
+        media_sequence: 0,
+        segments: segments
+            .iter()
+            .map(|s| Segment {
+                duration: s.duration_secs,
+                uri: format!("audio_{n}_segment_{}.m4s", s.index),
+                title: None,
+            })
+            .collect(),
+        ended: true,
+        init_segment_uri: Some(format!("audio_{n}_init.mp4")),
+    });
+
+    AudioRendition {
+        language: track.language.clone(),
+        name,
+        channels: track.channels,
+        init_segment,
+        playlist,
+        segments,
+    }
+}
+
+/// A rendition NAME for the `i`th audio track, unique among `used`: the
+/// track's title, else its language, else its position.
+fn rendition_name(track: &TrackInfo, i: usize, used: &mut HashSet<String>) -> String {
+    let base = track
+        .name
+        .clone()
+        .or_else(|| track.language.clone())
+        .unwrap_or_else(|| format!("Audio {}", i + 1));
+    let mut name = base.clone();
+    let mut suffix = 2;
+    while !used.insert(name.clone()) {
+        name = format!("{base} ({suffix})");
+        suffix += 1;
+    }
+    name
+}
+
+/// Peak bitrate of `segments` in bits per second.
+fn peak_bandwidth(segments: &[PrecomputedSegment]) -> u64 {
+    segments
+        .iter()
+        .filter(|s| s.duration_secs > 0.0)
+        .map(|s| {
+            let bytes = s.moof_bytes.len() + s.mdat_header.len() + s.data_length as usize;
+            (bytes as f64 * 8.0 / s.duration_secs) as u64
+        })
+        .max()
+        .unwrap_or(0)
+}
+
 /// Merge adjacent or overlapping data ranges.
 fn merge_data_ranges(ranges: &[DataRange]) -> Vec<DataRange> {
     if ranges.is_empty() {
@@ -365,6 +586,150 @@ fn merge_data_ranges(ranges: &[DataRange]) -> Vec<DataRange> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mp4::ResolvedSampleTable;
+
+    fn track(handler_type: &[u8; 4], codec: Codec, timescale: u32, samples: Vec<ResolvedSample>) -> TrackInfo {
+        TrackInfo {
+            track_id: 1,
+            handler_type: *handler_type,
+            timescale,
+            duration: 0,
+            width: 1920,
+            height: 1080,
+            sample_rate: timescale,
+            channels: 2,
+            language: None,
+            name: None,
+            default: false,
+            codec: Some(codec),
+            codec_private: vec![0x01],
+            sample_table: ResolvedSampleTable { samples, timescale },
+        }
+    }
+
+    /// `count` samples of `duration` ticks and 10 bytes each, contiguous
+    /// from `base_offset`, a keyframe every `gop` samples.
+    fn samples(count: u32, duration: u32, base_offset: u64, gop: u32) -> Vec<ResolvedSample> {
+        (0..count)
+            .map(|i| ResolvedSample {
+                index: i,
+                file_offset: base_offset + i as u64 * 10,
+                size: 10,
+                duration,
+                composition_offset: 0,
+                is_sync: i % gop == 0,
+                decode_timestamp: i as u64 * duration as u64,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_alternate_audio_renditions() {
+        // 12s of 1 fps video with a keyframe every 6s, and two 12s audio
+        // tracks of 1s samples; the second is the default.
+        let video = track(b"vide", Codec::Avc, 1, samples(12, 1, 0, 6));
+        let mut english = track(b"soun", Codec::Aac, 1, samples(12, 1, 1000, 1));
+        english.language = Some("eng".to_string());
+        english.channels = 6;
+        let mut commentary = track(b"soun", Codec::Aac, 1, samples(12, 1, 2000, 1));
+        commentary.language = Some("eng".to_string());
+        commentary.name = Some("Commentary".to_string());
+        commentary.default = true;
+        let mut unsupported = track(b"soun", Codec::Aac, 1, samples(12, 1, 3000, 1));
+        unsupported.codec = None;
+
+        let metadata = Mp4Metadata {
+            video_track: Some(video),
+            audio_tracks: vec![english, commentary, unsupported],
+            duration_secs: 12.0,
+        };
+        let prepared = build_prepared_media(&metadata, Path::new("/m/film.mp4")).unwrap();
+
+        // The default track is muxed into the main segments.
+        assert_eq!(prepared.segments.len(), 2);
+        assert_eq!(prepared.segments[1].audio_data_ranges[0].file_offset, 2060);
+
+        // The other supported track is an audio-only rendition on the same
+        // timeline.
+        assert_eq!(prepared.audio_renditions.len(), 1);
+        let rendition = &prepared.audio_renditions[0];
+        assert_eq!(rendition.name, "eng");
+        assert_eq!(rendition.channels, 6);
+        assert_eq!(rendition.segments.len(), 2);
+        let segment = &rendition.segments[1];
+        assert!(segment.video_data_ranges.is_empty());
+        assert_eq!(segment.audio_data_ranges[0].file_offset, 1060);
+        assert_eq!(segment.data_length, 60);
+        assert_eq!(segment.start_time_secs, 6.0);
+        assert!(rendition.playlist.contains("#EXT-X-MAP:URI=\"audio_0_init.mp4\""));
+        assert!(rendition.playlist.contains("audio_0_segment_1.m4s"));
+
+        // trun data_offset points just past the moof and mdat header.
+        let moof = &segment.moof_bytes;
+        let trun = moof.windows(4).position(|w| w == b"trun").unwrap();
+        let data_offset = i32::from_be_bytes(moof[trun + 12..trun + 16].try_into().unwrap());
+        assert_eq!(data_offset as usize, moof.len() + segment.mdat_header.len());
+
+        let master_playlist = prepared.master_playlist(Vec::new());
+        assert!(master_playlist.contains(
+            "#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"audio\",LANGUAGE=\"eng\",NAME=\"Commentary\",DEFAULT=YES,AUTOSELECT=YES,CHANNELS=\"2\"\n"
+        ));
+        assert!(master_playlist.contains("NAME=\"eng\",DEFAULT=NO,AUTOSELECT=YES,CHANNELS=\"6\",URI=\"audio_0.m3u8\""));
+        assert!(master_playlist.contains(",AUDIO=\"audio\"\nindex.m3u8\n"));
+    }
+
+    #[test]
+    fn test_single_audio_has_no_renditions() {
+        let metadata = Mp4Metadata {
+            video_track: Some(track(b"vide", Codec::Avc, 1, samples(12, 1, 0, 6))),
+            audio_tracks: vec![track(b"soun", Codec::Aac, 1, samples(12, 1, 1000, 1))],
+            duration_secs: 12.0,
+        };
+        let prepared = build_prepared_media(&metadata, Path::new("/m/film.mp4")).unwrap();
+        assert!(prepared.audio_renditions.is_empty());
+        let master_playlist = prepared.master_playlist(Vec::new());
+        assert!(!master_playlist.contains("EXT-X-MEDIA"));
+        assert!(master_playlist.ends_with("RESOLUTION=1920x1080\nindex.m3u8\n"));
+    }
+
+    #[test]
+    fn test_subtitle_renditions_follow_video_segments() {
+        let metadata = Mp4Metadata {
+            video_track: Some(track(b"vide", Codec::Avc, 1, samples(12, 1, 0, 6))),
+            audio_tracks: vec![track(b"soun", Codec::Aac, 1, samples(12, 1, 1000, 1))],
+            duration_secs: 12.0,
+        };
+        let prepared = build_prepared_media(&metadata, Path::new("/m/film.mp4")).unwrap();
+
+        let master_playlist = prepared.master_playlist(vec![Rendition {
+            media_type: MediaType::Subtitles,
+            group_id: crate::SUBTITLE_GROUP.to_string(),
+            language: Some("eng".to_string()),
+            name: "eng".to_string(),
+            default: false,
+            autoselect: true,
+            forced: true,
+            channels: None,
+            uri: Some("subtitles_1.m3u8".to_string()),
+        }]);
+        assert!(master_playlist.contains("TYPE=SUBTITLES,GROUP-ID=\"subs\""));
+        assert!(master_playlist.contains("FORCED=YES,URI=\"subtitles_1.m3u8\""));
+        assert!(master_playlist.ends_with(",SUBTITLES=\"subs\"\nindex.m3u8\n"));
+
+        let playlist = prepared.subtitle_playlist(1);
+        assert!(playlist.contains("#EXTINF:6.000000,\nsubtitles_1_segment_1.vtt\n"));
+        assert!(!playlist.contains("subtitles_1_segment_2.vtt"));
+
+        let cues = crate::parse_webvtt(
+            "WEBVTT\n\n00:00:05.000 --> 00:00:07.000\nBoth\n\n00:00:20.000 --> 00:00:21.000\nCredits\n",
+        );
+        let first = prepared.subtitle_segment(&cues, 0).unwrap();
+        assert!(first.contains("Both") && !first.contains("Credits"));
+        // The last segment also carries cues past the final sample.
+        let last = prepared.subtitle_segment(&cues, 1).unwrap();
+        assert!(last.contains("Both") && last.contains("Credits"));
+        assert!(prepared.subtitle_segment(&cues, 2).is_none());
+    }
 
     #[test]
     fn test_merge_data_ranges_adjacent() {
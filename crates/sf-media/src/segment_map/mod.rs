@@ -2,7 +2,7 @@
 //!
 //! - `boundary` — Keyframe-aligned segment boundary computation.
 //! - `types` — Types for precomputed segment data (PreparedMedia, etc.).
-//! - `builder` — Build PreparedMedia from parsed MP4 metadata.
+//! - `builder` — Build PreparedMedia from parsed MP4 or MKV metadata.
 
 mod boundary;
 pub mod builder;
@@ -10,4 +10,4 @@ pub mod types;
 
 pub use boundary::{compute_segment_map, KeyframeInfo, SegmentBoundary, SegmentMap};
 pub use builder::build_prepared_media;
-pub use types::{DataRange, PrecomputedSegment, PreparedMedia};
+pub use types::{AudioRendition, DataRange, PrecomputedSegment, PreparedMedia, SUBTITLE_GROUP};
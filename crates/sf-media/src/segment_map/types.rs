@@ -2,6 +2,28 @@
 
 use std::path::PathBuf;
 
+use crate::hls::{
+    generate_master_playlist, generate_media_playlist, subtitle_media_playlist,
+    write_webvtt_segment, Cue, MasterPlaylist, Rendition,
+};
+
+/// GROUP-ID of the subtitle renditions added by [`PreparedMedia::master_playlist`].
+pub const SUBTITLE_GROUP: &str = "subs";
+
+/// Prefix of [`PreparedMedia::to_bincode`] blobs, followed by the
+/// little-endian [`FORMAT_VERSION`].
+const MAGIC: &[u8; 4] = b"SFPM";
+
+/// Layout version of serialized [`PreparedMedia`]. bincode isn't
+/// self-describing, so a blob of another layout can decode into garbage;
+/// bump this whenever the layout of `PreparedMedia` or any type it contains
+/// changes, and stored blobs are rebuilt.
+///
+/// - 1: alternate audio renditions.
+/// - 2: master playlist kept as a [`MasterPlaylist`], so subtitle renditions
+///   can be added per request.
+const FORMAT_VERSION: u16 = 2;
+
 /// A byte range within the source MP4 file.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct DataRange {
@@ -21,6 +43,7 @@ pub struct PrecomputedSegment {
     /// Pre-built mdat header bytes.
     pub mdat_header: Vec<u8>,
     /// Video byte ranges to read from the source MP4 (written to mdat first).
+    /// Empty for audio rendition segments.
     pub video_data_ranges: Vec<DataRange>,
     /// Audio byte ranges to read from the source MP4 (written to mdat after video).
     pub audio_data_ranges: Vec<DataRange>,
@@ -28,6 +51,21 @@ pub struct PrecomputedSegment {
     pub data_length: u64,
 }
 
+/// An alternate audio track served as its own HLS rendition, with segments
+/// aligned to the video segments.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct AudioRendition {
+    pub language: Option<String>,
+    pub name: String,
+    pub channels: u16,
+    /// ftyp + moov init segment for the audio track alone.
+    pub init_segment: Vec<u8>,
+    /// HLS media playlist string (served as audio_N.m3u8).
+    pub playlist: String,
+    /// Audio-only precomputed segments.
+    pub segments: Vec<PrecomputedSegment>,
+}
+
 /// Fully prepared media file for zero-copy HLS serving.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct PreparedMedia {
@@ -40,20 +78,126 @@ pub struct PreparedMedia {
     pub init_segment: Vec<u8>,
     /// HLS media playlist string (served as index.m3u8).
     pub variant_playlist: String,
-    /// Precomputed segments.
+    /// HLS master playlist (served as master.m3u8): the variant playlist
+    /// plus any alternate audio renditions. Subtitle renditions are added per
+    /// request by [`PreparedMedia::master_playlist`].
+    pub master: MasterPlaylist,
+    /// Precomputed segments (video plus the default audio track).
     pub segments: Vec<PrecomputedSegment>,
+    /// The other audio tracks.
+    pub audio_renditions: Vec<AudioRendition>,
     /// Target segment duration (for EXT-X-TARGETDURATION).
     pub target_duration: u32,
 }
 
 impl PreparedMedia {
-    /// Serialize to bincode bytes.
+    /// Render the master playlist with `subtitles` added as the
+    /// [`SUBTITLE_GROUP`] rendition group of every variant.
+    pub fn master_playlist(&self, subtitles: Vec<Rendition>) -> String {
+        let mut master = self.master.clone();
+        if !subtitles.is_empty() {
+            for variant in &mut master.variants {
+                variant.subtitles = Some(SUBTITLE_GROUP.to_string());
+            }
+            master.renditions.extend(subtitles);
+        }
+        generate_master_playlist(&master)
+    }
+
+    /// Render the media playlist of subtitle track `track`, with one WebVTT
+    /// segment (`subtitles_{track}_segment_{k}.vtt`) per video segment.
+    pub fn subtitle_playlist(&self, track: i32) -> String {
+        let durations: Vec<f64> = self.segments.iter().map(|s| s.duration_secs).collect();
+        generate_media_playlist(&subtitle_media_playlist(
+            &durations,
+            self.target_duration,
+            &format!("subtitles_{track}"),
+        ))
+    }
+
+    /// Render WebVTT segment `index` from a subtitle track's cues, or `None`
+    /// if there is no such segment.
+    pub fn subtitle_segment(&self, cues: &[Cue], index: usize) -> Option<String> {
+        let segment = self.segments.get(index)?;
+        // The last segment runs to the end of the file so trailing cues are kept.
+        let end = if index + 1 == self.segments.len() {
+            f64::INFINITY
+        } else {
+            segment.start_time_secs + segment.duration_secs
+        };
+        Some(write_webvtt_segment(cues, segment.start_time_secs, end))
+    }
+
+    /// Serialize to bincode bytes, prefixed with the layout version.
     pub fn to_bincode(&self) -> Result<Vec<u8>, String> {
-        bincode::serialize(self).map_err(|e| format!("bincode serialize: {e}"))
+        let mut data = MAGIC.to_vec();
+        data.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bincode::serialize_into(&mut data, self).map_err(|e| format!("bincode serialize: {e}"))?;
+        Ok(data)
     }
 
-    /// Deserialize from bincode bytes.
+    /// Deserialize from bytes written by [`to_bincode`](Self::to_bincode).
+    /// Blobs of another layout version are rejected.
     pub fn from_bincode(data: &[u8]) -> Result<Self, String> {
-        bincode::deserialize(data).map_err(|e| format!("bincode deserialize: {e}"))
+        let body = data
+            .strip_prefix(MAGIC)
+            .ok_or("not a versioned PreparedMedia blob")?;
+        let (version, body) = body
+            .split_first_chunk::<2>()
+            .ok_or("truncated PreparedMedia blob")?;
+        let version = u16::from_le_bytes(*version);
+        if version != FORMAT_VERSION {
+            return Err(format!(
+                "PreparedMedia layout version {version}, expected {FORMAT_VERSION}"
+            ));
+        }
+        bincode::deserialize(body).map_err(|e| format!("bincode deserialize: {e}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prepared() -> PreparedMedia {
+        PreparedMedia {
+            file_path: PathBuf::from("/m/a.mp4"),
+            width: 1920,
+            height: 1080,
+            duration_secs: 60.0,
+            init_segment: vec![1, 2, 3],
+            variant_playlist: "#EXTM3U\n".to_string(),
+            master: MasterPlaylist {
+                renditions: vec![],
+                variants: vec![],
+            },
+            segments: vec![],
+            audio_renditions: vec![],
+            target_duration: 6,
+        }
+    }
+
+    #[test]
+    fn bincode_round_trips() {
+        let blob = prepared().to_bincode().unwrap();
+        assert!(blob.starts_with(MAGIC));
+        let decoded = PreparedMedia::from_bincode(&blob).unwrap();
+        assert_eq!(decoded.init_segment, [1, 2, 3]);
+        assert_eq!(decoded.target_duration, 6);
+    }
+
+    #[test]
+    fn blobs_of_other_layouts_are_rejected() {
+        // Written before blobs were versioned.
+        let unversioned = bincode::serialize(&prepared()).unwrap();
+        assert!(PreparedMedia::from_bincode(&unversioned).is_err());
+
+        let mut blob = prepared().to_bincode().unwrap();
+        blob[MAGIC.len()..MAGIC.len() + 2].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        let err = PreparedMedia::from_bincode(&blob).unwrap_err();
+        assert!(err.contains("layout version"), "{err}");
+
+        assert!(PreparedMedia::from_bincode(b"SFP").is_err());
+        assert!(PreparedMedia::from_bincode(b"SFPM\x01").is_err());
     }
 }
@@ -9,6 +9,7 @@ use std::path::PathBuf;
 use async_trait::async_trait;
 
 use crate::context::ActionContext;
+use crate::verify::ExpectedOutput;
 
 /// Result of a successfully executed action.
 #[derive(Debug, Clone)]
@@ -17,6 +18,9 @@ pub struct ActionResult {
     pub output: Option<PathBuf>,
     /// Human-readable summary of what the action did.
     pub summary: String,
+    /// Structured data produced by the action (e.g. loudness measurements),
+    /// recorded in the job result.
+    pub details: Option<serde_json::Value>,
 }
 
 /// A single step in a processing pipeline.
@@ -45,6 +49,14 @@ pub trait Action: Send + Sync {
         Ok(())
     }
 
+    /// Adjust the expected shape of the pipeline's output for what this
+    /// action does, e.g. one more audio track. Used to verify the output
+    /// before it replaces the original.
+    ///
+    /// The default implementation expects the action to preserve duration,
+    /// tracks and HDR metadata.
+    fn expect_output(&self, _ctx: &ActionContext, _expected: &mut ExpectedOutput) {}
+
     /// Whether this action can run in parallel with other parallelizable
     /// actions within the same stage.
     ///
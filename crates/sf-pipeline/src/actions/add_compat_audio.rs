@@ -4,6 +4,7 @@ use async_trait::async_trait;
 
 use crate::action::{Action, ActionResult};
 use crate::context::ActionContext;
+use crate::verify::ExpectedOutput;
 
 /// Add a compatibility audio track by transcoding from an existing stream.
 #[derive(Debug)]
@@ -47,6 +48,7 @@ impl Action for AddCompatAudioAction {
                     "Would add {} compat track from {}",
                     self.target_codec, self.source_codec,
                 ),
+                details: None,
             });
         }
 
@@ -67,9 +69,15 @@ impl Action for AddCompatAudioAction {
                 "Added {} compat track from {} (track {})",
                 self.target_codec, self.source_codec, source_track,
             ),
+            details: None,
         })
     }
 
+    fn expect_output(&self, _ctx: &ActionContext, expected: &mut ExpectedOutput) {
+        expected.audio_tracks = expected.audio_tracks.map(|n| n + 1);
+        expected.audio_languages = None;
+    }
+
     fn weight(&self) -> f32 {
         2.0
     }
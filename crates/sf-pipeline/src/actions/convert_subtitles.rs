@@ -0,0 +1,81 @@
+//! Convert ASS/SSA subtitles to SRT action.
+
+use async_trait::async_trait;
+
+use crate::action::{Action, ActionResult};
+use crate::actions::track_layout::language_in;
+use crate::context::ActionContext;
+use crate::verify::ExpectedOutput;
+
+/// Convert ASS/SSA subtitle tracks to SRT, copying all other streams.
+#[derive(Debug)]
+pub struct ConvertSubtitlesAction {
+    languages: Option<Vec<String>>,
+}
+
+impl ConvertSubtitlesAction {
+    /// Create a new action converting the ASS/SSA tracks in `languages` (all
+    /// of them when `None`).
+    pub fn new(languages: Option<Vec<String>>) -> Self {
+        Self { languages }
+    }
+
+    fn resolve_tracks(&self, ctx: &ActionContext) -> Vec<usize> {
+        ctx.media_info
+            .subtitle_tracks
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| sf_av::is_ass_subtitle(&t.codec))
+            .filter(|(_, t)| match &self.languages {
+                Some(langs) => language_in(langs, t.language.as_deref()),
+                None => true,
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Action for ConvertSubtitlesAction {
+    fn name(&self) -> &'static str {
+        "Convert Subtitles"
+    }
+
+    async fn validate(&self, ctx: &ActionContext) -> sf_core::Result<()> {
+        ctx.tools.require("ffmpeg")?;
+        Ok(())
+    }
+
+    async fn execute(&self, ctx: &ActionContext) -> sf_core::Result<ActionResult> {
+        let tracks = self.resolve_tracks(ctx);
+
+        if tracks.is_empty() {
+            return Ok(ActionResult {
+                output: None,
+                summary: "No ASS/SSA subtitle tracks to convert".to_string(),
+                details: None,
+            });
+        }
+
+        if ctx.dry_run {
+            tracing::info!("[DRY RUN] Would convert subtitle tracks {tracks:?} to SRT");
+            return Ok(ActionResult {
+                output: None,
+                summary: format!("Would convert {} subtitle track(s) to SRT", tracks.len()),
+                details: None,
+            });
+        }
+
+        sf_av::convert_subtitles_to_srt(&ctx.workspace, &ctx.tools, &tracks).await?;
+
+        Ok(ActionResult {
+            output: Some(ctx.workspace.output()),
+            summary: format!("Converted {} subtitle track(s) to SRT", tracks.len()),
+            details: None,
+        })
+    }
+
+    fn expect_output(&self, ctx: &ActionContext, expected: &mut ExpectedOutput) {
+        expected.keep_track_order(&ctx.media_info);
+    }
+}
@@ -0,0 +1,115 @@
+//! Loudness-normalized stereo downmix action.
+
+use async_trait::async_trait;
+
+use crate::action::{Action, ActionResult};
+use crate::context::ActionContext;
+use crate::verify::ExpectedOutput;
+
+/// Append a stereo downmix of a surround track, normalized with a two-pass
+/// EBU R128 `loudnorm`. The loudness measurements are recorded in the
+/// result details.
+#[derive(Debug)]
+pub struct DownmixAudioAction {
+    source_track: Option<usize>,
+    options: sf_av::DownmixOptions,
+}
+
+impl DownmixAudioAction {
+    /// Create a new action downmixing `source_track`, or the audio track with
+    /// the most channels when `None`.
+    ///
+    /// `options.source_track` and `options.existing_tracks` are filled in from
+    /// the media info at execution time, as is `options.language` when unset.
+    pub fn new(source_track: Option<usize>, options: sf_av::DownmixOptions) -> Self {
+        Self {
+            source_track,
+            options,
+        }
+    }
+}
+
+#[async_trait]
+impl Action for DownmixAudioAction {
+    fn name(&self) -> &'static str {
+        "Downmix Audio"
+    }
+
+    async fn validate(&self, ctx: &ActionContext) -> sf_core::Result<()> {
+        ctx.tools.require("ffmpeg")?;
+        if ctx.media_info.audio_tracks.is_empty() {
+            return Err(sf_core::Error::Validation(
+                "downmix requires an audio track".into(),
+            ));
+        }
+        if let Some(track) = self.source_track {
+            if track >= ctx.media_info.audio_tracks.len() {
+                return Err(sf_core::Error::Validation(format!(
+                    "audio track {track} does not exist ({} audio tracks)",
+                    ctx.media_info.audio_tracks.len()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    async fn execute(&self, ctx: &ActionContext) -> sf_core::Result<ActionResult> {
+        let tracks = &ctx.media_info.audio_tracks;
+        let source_track = self.source_track.unwrap_or_else(|| {
+            // First track with the most channels.
+            tracks
+                .iter()
+                .enumerate()
+                .rev()
+                .max_by_key(|(_, t)| t.channels)
+                .map(|(i, _)| i)
+                .unwrap_or(0)
+        });
+
+        if ctx.dry_run {
+            tracing::info!(
+                "[DRY RUN] Would add stereo downmix of track {source_track} at {} LUFS",
+                self.options.target_lufs
+            );
+            return Ok(ActionResult {
+                output: None,
+                summary: format!(
+                    "Would add stereo downmix of track {source_track} at {} LUFS",
+                    self.options.target_lufs
+                ),
+                details: None,
+            });
+        }
+
+        let mut options = self.options.clone();
+        options.source_track = source_track;
+        options.existing_tracks = tracks.len();
+        if options.language.is_none() {
+            options.language = tracks.get(source_track).and_then(|t| t.language.clone());
+        }
+
+        let stats = sf_av::downmix_audio(&ctx.workspace, &ctx.tools, &options).await?;
+
+        Ok(ActionResult {
+            output: Some(ctx.workspace.output()),
+            summary: format!(
+                "Added stereo downmix of track {source_track}: {:.1} -> {:.1} LUFS",
+                stats.input_i, stats.output_i
+            ),
+            details: Some(serde_json::json!({
+                "source_track": source_track,
+                "target_lufs": options.target_lufs,
+                "loudness": stats,
+            })),
+        })
+    }
+
+    fn expect_output(&self, _ctx: &ActionContext, expected: &mut ExpectedOutput) {
+        expected.audio_tracks = expected.audio_tracks.map(|n| n + 1);
+        expected.audio_languages = None;
+    }
+
+    fn weight(&self) -> f32 {
+        3.0
+    }
+}
@@ -1,9 +1,11 @@
 //! Dolby Vision profile conversion action.
 
 use async_trait::async_trait;
+use sf_probe::{DvElType, DvInfo};
 
 use crate::action::{Action, ActionResult};
 use crate::context::ActionContext;
+use crate::verify::ExpectedOutput;
 
 /// Convert Dolby Vision to a target profile via `dovi_tool`.
 #[derive(Debug)]
@@ -16,6 +18,22 @@ impl DvConvertAction {
     pub fn new(target_profile: u8) -> Self {
         Self { target_profile }
     }
+
+    /// A warning when converting discards a profile 7 full enhancement
+    /// layer. A layer of unknown type is assumed to be one.
+    fn fel_warning(dv: Option<&DvInfo>) -> Option<String> {
+        let dv = dv?;
+        let fel = dv.profile == 7
+            && match dv.el_type {
+                Some(el_type) => el_type == DvElType::Fel,
+                None => dv.el_present,
+            };
+        fel.then(|| {
+            "the profile 7 full enhancement layer (FEL) is discarded; \
+             only the base layer and RPU are kept"
+                .to_string()
+        })
+    }
 }
 
 #[async_trait]
@@ -32,6 +50,14 @@ impl Action for DvConvertAction {
     }
 
     async fn execute(&self, ctx: &ActionContext) -> sf_core::Result<ActionResult> {
+        let dv = ctx
+            .media_info
+            .primary_video()
+            .and_then(|v| v.dolby_vision.as_ref());
+        let warning = Self::fel_warning(dv);
+        let details = warning
+            .as_ref()
+            .map(|w| serde_json::json!({ "warning": w }));
         if ctx.dry_run {
             tracing::info!(
                 "[DRY RUN] Would convert DV to profile {}",
@@ -39,22 +65,66 @@ impl Action for DvConvertAction {
             );
             return Ok(ActionResult {
                 output: None,
-                summary: format!(
-                    "Would convert DV to profile {}",
-                    self.target_profile
+                summary: with_warning(
+                    format!("Would convert DV to profile {}", self.target_profile),
+                    warning.as_deref(),
                 ),
+                details,
             });
         }
 
+        if let Some(warning) = &warning {
+            tracing::warn!("{warning}");
+        }
         sf_av::convert_dv_profile(&ctx.workspace, &ctx.tools, self.target_profile).await?;
 
         Ok(ActionResult {
             output: Some(ctx.workspace.output()),
-            summary: format!("Converted DV to profile {}", self.target_profile),
+            summary: with_warning(
+                format!("Converted DV to profile {}", self.target_profile),
+                warning.as_deref(),
+            ),
+            details,
         })
     }
 
+    fn expect_output(&self, _ctx: &ActionContext, expected: &mut ExpectedOutput) {
+        expected.dolby_vision = Some(Some(self.target_profile));
+    }
+
     fn weight(&self) -> f32 {
         3.0
     }
 }
+
+fn with_warning(summary: String, warning: Option<&str>) -> String {
+    match warning {
+        Some(warning) => format!("{summary}; warning: {warning}"),
+        None => summary,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dv(profile: u8, el_present: bool, el_type: Option<DvElType>) -> DvInfo {
+        DvInfo {
+            profile,
+            rpu_present: true,
+            el_present,
+            bl_present: true,
+            el_type,
+        }
+    }
+
+    #[test]
+    fn warns_when_discarding_fel() {
+        let warn = |dv: DvInfo| DvConvertAction::fel_warning(Some(&dv)).is_some();
+        assert!(warn(dv(7, true, Some(DvElType::Fel))));
+        assert!(warn(dv(7, true, None)));
+        assert!(!warn(dv(7, true, Some(DvElType::Mel))));
+        assert!(!warn(dv(8, false, None)));
+        assert!(DvConvertAction::fel_warning(None).is_none());
+    }
+}
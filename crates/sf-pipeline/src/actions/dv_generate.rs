@@ -0,0 +1,156 @@
+//! Dolby Vision generation action.
+
+use async_trait::async_trait;
+use sf_core::HdrFormat;
+use sf_probe::VideoTrack;
+
+use crate::action::{Action, ActionResult};
+use crate::context::ActionContext;
+use crate::verify::ExpectedOutput;
+
+/// Add Dolby Vision to an HDR10 or HDR10+ file by injecting an RPU generated
+/// from its HDR metadata.
+#[derive(Debug)]
+pub struct DvGenerateAction {
+    profile: u8,
+}
+
+impl DvGenerateAction {
+    /// Create a new DV generation action.
+    pub fn new(profile: u8) -> Self {
+        Self { profile }
+    }
+
+    /// Whether the source carries HDR10+ dynamic metadata to generate from.
+    fn dynamic_metadata(ctx: &ActionContext) -> bool {
+        ctx.media_info
+            .primary_video()
+            .is_some_and(|v| v.hdr_format == HdrFormat::Hdr10Plus)
+    }
+}
+
+#[async_trait]
+impl Action for DvGenerateAction {
+    fn name(&self) -> &'static str {
+        "Dolby Vision Generate"
+    }
+
+    async fn validate(&self, ctx: &ActionContext) -> sf_core::Result<()> {
+        ctx.tools.require("ffmpeg")?;
+        ctx.tools.require("ffprobe")?;
+        ctx.tools.require("dovi_tool")?;
+        ctx.tools.require("mkvmerge")?;
+        if Self::dynamic_metadata(ctx) {
+            ctx.tools.require("hdr10plus_tool")?;
+        }
+
+        check_source(ctx.media_info.primary_video(), self.profile)
+    }
+
+    async fn execute(&self, ctx: &ActionContext) -> sf_core::Result<ActionResult> {
+        let dynamic_metadata = Self::dynamic_metadata(ctx);
+        let source = if dynamic_metadata { "HDR10+" } else { "HDR10" };
+
+        if ctx.dry_run {
+            tracing::info!(
+                "[DRY RUN] Would generate DV profile {} from {source}",
+                self.profile
+            );
+            return Ok(ActionResult {
+                output: None,
+                summary: format!("Would generate DV profile {} from {source}", self.profile),
+                details: None,
+            });
+        }
+
+        sf_av::generate_dv(&ctx.workspace, &ctx.tools, self.profile, dynamic_metadata).await?;
+
+        Ok(ActionResult {
+            output: Some(ctx.workspace.output()),
+            summary: format!("Generated DV profile {} from {source}", self.profile),
+            details: None,
+        })
+    }
+
+    fn expect_output(&self, _ctx: &ActionContext, expected: &mut ExpectedOutput) {
+        expected.hdr_format = Some(HdrFormat::DolbyVision);
+        expected.dolby_vision = Some(Some(self.profile));
+    }
+
+    fn weight(&self) -> f32 {
+        3.0
+    }
+}
+
+/// Check that DV `profile` can be generated for `video`.
+fn check_source(video: Option<&VideoTrack>, profile: u8) -> sf_core::Result<()> {
+    let video = video.ok_or_else(|| sf_core::Error::Validation("no video track".into()))?;
+    if video.dolby_vision.is_some() {
+        return Err(sf_core::Error::Validation(
+            "file already has Dolby Vision".into(),
+        ));
+    }
+    if !matches!(video.hdr_format, HdrFormat::Hdr10 | HdrFormat::Hdr10Plus) {
+        return Err(sf_core::Error::Validation(format!(
+            "Dolby Vision can only be generated from HDR10 or HDR10+, not {}",
+            video.hdr_format
+        )));
+    }
+    match profile {
+        8 => Ok(()),
+        // Profile 5 signals an IPTPQc2 base layer, which an HDR10 video
+        // doesn't have; players would show it with the wrong colours.
+        5 => Err(sf_core::Error::Validation(
+            "Dolby Vision profile 5 needs an IPTPQc2 base layer; \
+             HDR10 sources can only become profile 8.1"
+                .into(),
+        )),
+        other => Err(sf_core::Error::Validation(format!(
+            "cannot generate Dolby Vision profile {other}; only profile 8 (8.1) is supported"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sf_core::VideoCodec;
+    use sf_probe::DvInfo;
+
+    fn video(hdr_format: HdrFormat, dv_profile: Option<u8>) -> VideoTrack {
+        VideoTrack {
+            codec: VideoCodec::H265,
+            width: 3840,
+            height: 2160,
+            frame_rate: Some(23.976),
+            bit_depth: Some(10),
+            hdr_format,
+            hdr10plus: false,
+            dolby_vision: dv_profile.map(|profile| DvInfo {
+                profile,
+                rpu_present: true,
+                el_present: false,
+                bl_present: true,
+                el_type: None,
+            }),
+            default: true,
+            language: None,
+        }
+    }
+
+    #[test]
+    fn generates_profile_8_from_hdr10_and_hdr10plus() {
+        assert!(check_source(Some(&video(HdrFormat::Hdr10, None)), 8).is_ok());
+        assert!(check_source(Some(&video(HdrFormat::Hdr10Plus, None)), 8).is_ok());
+    }
+
+    #[test]
+    fn rejects_unsuitable_sources_and_profiles() {
+        assert!(check_source(None, 8).is_err());
+        assert!(check_source(Some(&video(HdrFormat::Sdr, None)), 8).is_err());
+        assert!(check_source(Some(&video(HdrFormat::Hlg, None)), 8).is_err());
+        assert!(check_source(Some(&video(HdrFormat::DolbyVision, Some(8))), 8).is_err());
+        assert!(check_source(Some(&video(HdrFormat::Hdr10, None)), 5).is_err());
+        assert!(check_source(Some(&video(HdrFormat::Hdr10, None)), 7).is_err());
+    }
+}
@@ -4,6 +4,7 @@ use async_trait::async_trait;
 
 use crate::action::{Action, ActionResult};
 use crate::context::ActionContext;
+use crate::verify::ExpectedOutput;
 
 /// Execute an external command with `{input}` / `{output}` substitution.
 ///
@@ -44,6 +45,7 @@ impl Action for ExecAction {
             return Ok(ActionResult {
                 output: None,
                 summary: format!("Would execute: {}", self.command),
+                details: None,
             });
         }
 
@@ -52,6 +54,12 @@ impl Action for ExecAction {
         Ok(ActionResult {
             output: Some(ctx.workspace.output()),
             summary: format!("Executed: {}", self.command),
+            details: None,
         })
     }
+
+    /// An arbitrary command may change anything.
+    fn expect_output(&self, _ctx: &ActionContext, expected: &mut ExpectedOutput) {
+        expected.unknown();
+    }
 }
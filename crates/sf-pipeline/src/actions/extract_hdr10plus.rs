@@ -0,0 +1,116 @@
+//! Extract HDR10+ metadata to a sidecar file action.
+
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use sf_core::VideoCodec;
+
+use crate::action::{Action, ActionResult};
+use crate::context::ActionContext;
+
+/// Extract the HDR10+ dynamic metadata of the video to a
+/// `<name>.hdr10plus.json` sidecar next to the media file.
+#[derive(Debug, Default)]
+pub struct ExtractHdr10PlusAction {
+    /// Sidecar written by `execute` (unless it already existed), removed
+    /// again on rollback.
+    written: std::sync::Mutex<Option<PathBuf>>,
+}
+
+impl ExtractHdr10PlusAction {
+    /// Create a new HDR10+ extraction action.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// `<dir>/<stem>.hdr10plus.json`, where HDR10+ metadata is extracted to and
+/// injected from by default.
+pub(crate) fn hdr10plus_sidecar(media: &Path) -> PathBuf {
+    let stem = media
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "video".into());
+    media.with_file_name(format!("{stem}.hdr10plus.json"))
+}
+
+/// Fail unless the primary video track is HEVC, the only codec
+/// `hdr10plus_tool` handles.
+pub(crate) fn require_hevc(ctx: &ActionContext) -> sf_core::Result<()> {
+    match ctx.media_info.primary_video() {
+        Some(video) if video.codec == VideoCodec::H265 => Ok(()),
+        Some(video) => Err(sf_core::Error::Validation(format!(
+            "HDR10+ metadata needs an HEVC video track, not {}",
+            video.codec
+        ))),
+        None => Err(sf_core::Error::Validation("no video track".into())),
+    }
+}
+
+#[async_trait]
+impl Action for ExtractHdr10PlusAction {
+    fn name(&self) -> &'static str {
+        "Extract HDR10+ Metadata"
+    }
+
+    async fn validate(&self, ctx: &ActionContext) -> sf_core::Result<()> {
+        ctx.tools.require("ffmpeg")?;
+        ctx.tools.require("hdr10plus_tool")?;
+        require_hevc(ctx)
+    }
+
+    async fn execute(&self, ctx: &ActionContext) -> sf_core::Result<ActionResult> {
+        let input = ctx.workspace.input();
+        let sidecar = hdr10plus_sidecar(&ctx.source);
+
+        if ctx.dry_run {
+            tracing::info!(
+                "[DRY RUN] Would extract HDR10+ metadata to {}",
+                sidecar.display()
+            );
+            return Ok(ActionResult {
+                output: None,
+                summary: format!("Would extract HDR10+ metadata to {}", sidecar.display()),
+                details: None,
+            });
+        }
+
+        let existed = sidecar.exists();
+        sf_av::extract_hdr10plus(&ctx.tools, input, &sidecar, ctx.workspace.temp_dir()).await?;
+        if !existed {
+            *self.written.lock().unwrap() = Some(sidecar.clone());
+        }
+
+        Ok(ActionResult {
+            output: None,
+            summary: format!("Extracted HDR10+ metadata to {}", sidecar.display()),
+            details: Some(serde_json::json!({ "sidecar": sidecar.display().to_string() })),
+        })
+    }
+
+    async fn rollback(&self, _ctx: &ActionContext) -> sf_core::Result<()> {
+        if let Some(path) = self.written.lock().unwrap().take() {
+            if let Err(e) = std::fs::remove_file(&path) {
+                tracing::warn!("failed to remove sidecar {}: {e}", path.display());
+            }
+        }
+        Ok(())
+    }
+
+    fn weight(&self) -> f32 {
+        2.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sidecar_sits_next_to_media() {
+        assert_eq!(
+            hdr10plus_sidecar(Path::new("/movies/Heat (1995)/Heat.mkv")),
+            PathBuf::from("/movies/Heat (1995)/Heat.hdr10plus.json")
+        );
+    }
+}
@@ -0,0 +1,173 @@
+//! Extract text subtitles to sidecar files action.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use sf_core::SubtitleFormat;
+
+use crate::action::{Action, ActionResult};
+use crate::actions::track_layout::language_in;
+use crate::context::ActionContext;
+
+/// Extract text subtitle tracks to `<name>.<lang>[.forced].<ext>` sidecar
+/// files next to the media file. Bitmap subtitles (PGS, VobSub) are skipped.
+#[derive(Debug)]
+pub struct ExtractSubtitlesAction {
+    languages: Option<Vec<String>>,
+    format: SubtitleFormat,
+    /// Sidecars written by `execute`, removed again on rollback.
+    written: std::sync::Mutex<Vec<PathBuf>>,
+}
+
+impl ExtractSubtitlesAction {
+    /// Create a new action extracting the text tracks in `languages` (all
+    /// text tracks when `None`).
+    pub fn new(languages: Option<Vec<String>>, format: SubtitleFormat) -> Self {
+        Self {
+            languages,
+            format,
+            written: Default::default(),
+        }
+    }
+
+    /// The text subtitle tracks to extract, with their sidecar paths.
+    fn plan(&self, ctx: &ActionContext) -> Vec<sf_av::SubtitleExtract> {
+        let media = &ctx.source;
+        let mut taken = HashSet::new();
+
+        ctx.media_info
+            .subtitle_tracks
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| sf_av::is_text_subtitle(&t.codec))
+            .filter(|(_, t)| match &self.languages {
+                Some(langs) => language_in(langs, t.language.as_deref()),
+                None => true,
+            })
+            .map(|(track, t)| sf_av::SubtitleExtract {
+                track,
+                path: sidecar_path(
+                    media,
+                    t.language.as_deref(),
+                    t.forced,
+                    self.format,
+                    &mut taken,
+                ),
+            })
+            .collect()
+    }
+}
+
+/// `<dir>/<stem>.<lang>[.forced].<ext>`, with a numeric suffix when several
+/// tracks would map to the same name.
+fn sidecar_path(
+    media: &Path,
+    language: Option<&str>,
+    forced: bool,
+    format: SubtitleFormat,
+    taken: &mut HashSet<PathBuf>,
+) -> PathBuf {
+    let stem = media
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "subtitles".into());
+    let mut base = format!("{stem}.{}", language.unwrap_or("und"));
+    if forced {
+        base.push_str(".forced");
+    }
+
+    let dir = media.parent().unwrap_or_else(|| Path::new(""));
+    let mut path = dir.join(format!("{base}.{}", format.extension()));
+    let mut n = 2;
+    while !taken.insert(path.clone()) {
+        path = dir.join(format!("{base}.{n}.{}", format.extension()));
+        n += 1;
+    }
+    path
+}
+
+#[async_trait]
+impl Action for ExtractSubtitlesAction {
+    fn name(&self) -> &'static str {
+        "Extract Subtitles"
+    }
+
+    async fn validate(&self, ctx: &ActionContext) -> sf_core::Result<()> {
+        ctx.tools.require("ffmpeg")?;
+        Ok(())
+    }
+
+    async fn execute(&self, ctx: &ActionContext) -> sf_core::Result<ActionResult> {
+        let tracks = self.plan(ctx);
+        let paths: Vec<String> = tracks
+            .iter()
+            .map(|t| t.path.display().to_string())
+            .collect();
+
+        if ctx.dry_run {
+            tracing::info!("[DRY RUN] Would extract subtitles to {paths:?}");
+            return Ok(ActionResult {
+                output: None,
+                summary: format!("Would extract {} subtitle track(s)", tracks.len()),
+                details: None,
+            });
+        }
+
+        sf_av::extract_subtitles(&ctx.tools, ctx.workspace.input(), &tracks, self.format).await?;
+        self.written
+            .lock()
+            .unwrap()
+            .extend(tracks.iter().map(|t| t.path.clone()));
+
+        Ok(ActionResult {
+            output: None,
+            summary: format!(
+                "Extracted {} subtitle track(s) as {}",
+                tracks.len(),
+                self.format
+            ),
+            details: Some(serde_json::json!({ "sidecars": paths })),
+        })
+    }
+
+    async fn rollback(&self, _ctx: &ActionContext) -> sf_core::Result<()> {
+        for path in self.written.lock().unwrap().drain(..) {
+            if let Err(e) = std::fs::remove_file(&path) {
+                tracing::warn!("failed to remove sidecar {}: {e}", path.display());
+            }
+        }
+        Ok(())
+    }
+
+    fn parallelizable(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sidecar_names_include_language_and_forced() {
+        let media = Path::new("/movies/Heat (1995)/Heat.mkv");
+        let mut taken = HashSet::new();
+        let mut path = |lang, forced| {
+            sidecar_path(media, lang, forced, SubtitleFormat::Srt, &mut taken)
+                .display()
+                .to_string()
+        };
+
+        assert_eq!(path(Some("eng"), false), "/movies/Heat (1995)/Heat.eng.srt");
+        assert_eq!(
+            path(Some("eng"), true),
+            "/movies/Heat (1995)/Heat.eng.forced.srt"
+        );
+        assert_eq!(
+            path(Some("eng"), false),
+            "/movies/Heat (1995)/Heat.eng.2.srt"
+        );
+        assert_eq!(path(None, false), "/movies/Heat (1995)/Heat.und.srt");
+    }
+}
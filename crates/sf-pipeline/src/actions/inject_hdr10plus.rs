@@ -0,0 +1,110 @@
+//! Inject HDR10+ metadata action.
+
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use sf_core::{Container, HdrFormat};
+
+use crate::action::{Action, ActionResult};
+use crate::actions::extract_hdr10plus::{hdr10plus_sidecar, require_hevc};
+use crate::context::ActionContext;
+use crate::verify::ExpectedOutput;
+
+/// Inject HDR10+ dynamic metadata from a JSON file (by default the
+/// `<name>.hdr10plus.json` sidecar) into the video.
+#[derive(Debug)]
+pub struct InjectHdr10PlusAction {
+    metadata: Option<PathBuf>,
+}
+
+impl InjectHdr10PlusAction {
+    /// Create a new action injecting `metadata` (the sidecar when `None`).
+    pub fn new(metadata: Option<PathBuf>) -> Self {
+        Self { metadata }
+    }
+
+    fn metadata_path(&self, ctx: &ActionContext) -> PathBuf {
+        self.metadata
+            .clone()
+            .unwrap_or_else(|| hdr10plus_sidecar(&ctx.source))
+    }
+}
+
+#[async_trait]
+impl Action for InjectHdr10PlusAction {
+    fn name(&self) -> &'static str {
+        "Inject HDR10+ Metadata"
+    }
+
+    async fn validate(&self, ctx: &ActionContext) -> sf_core::Result<()> {
+        ctx.tools.require("ffmpeg")?;
+        ctx.tools.require("ffprobe")?;
+        ctx.tools.require("hdr10plus_tool")?;
+        if ctx.media_info.container != Container::Mp4 {
+            ctx.tools.require("mkvmerge")?;
+        }
+        require_hevc(ctx)?;
+
+        if ctx
+            .media_info
+            .primary_video()
+            .is_some_and(|v| v.hdr10plus || v.hdr_format == HdrFormat::Hdr10Plus)
+        {
+            return Err(sf_core::Error::Validation(
+                "video already has HDR10+ metadata".into(),
+            ));
+        }
+        let metadata = self.metadata_path(ctx);
+        if !metadata.is_file() {
+            return Err(sf_core::Error::Validation(format!(
+                "HDR10+ metadata file not found: {}",
+                metadata.display()
+            )));
+        }
+        Ok(())
+    }
+
+    async fn execute(&self, ctx: &ActionContext) -> sf_core::Result<ActionResult> {
+        let metadata = self.metadata_path(ctx);
+
+        if ctx.dry_run {
+            tracing::info!(
+                "[DRY RUN] Would inject HDR10+ metadata from {}",
+                metadata.display()
+            );
+            return Ok(ActionResult {
+                output: None,
+                summary: format!("Would inject HDR10+ metadata from {}", metadata.display()),
+                details: None,
+            });
+        }
+
+        let output = ctx.workspace.output();
+        sf_av::inject_hdr10plus(
+            &ctx.tools,
+            ctx.workspace.input(),
+            &metadata,
+            &output,
+            ctx.workspace.temp_dir(),
+        )
+        .await?;
+
+        Ok(ActionResult {
+            output: Some(output),
+            summary: format!("Injected HDR10+ metadata from {}", metadata.display()),
+            details: None,
+        })
+    }
+
+    /// HDR10 becomes HDR10+, but not every prober detects HDR10+, so the
+    /// format isn't checked.
+    fn expect_output(&self, _ctx: &ActionContext, expected: &mut ExpectedOutput) {
+        if expected.hdr_format == Some(HdrFormat::Hdr10) {
+            expected.hdr_format = None;
+        }
+    }
+
+    fn weight(&self) -> f32 {
+        3.0
+    }
+}
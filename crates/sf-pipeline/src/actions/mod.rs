@@ -4,15 +4,38 @@
 //! behind the unified [`Action`](crate::action::Action) trait.
 
 mod dv_convert;
+mod dv_generate;
+mod extract_hdr10plus;
+mod inject_hdr10plus;
+mod remove_hdr10plus;
 mod remux;
 mod add_compat_audio;
+mod downmix_audio;
+mod set_track_defaults;
 mod strip_tracks;
+mod extract_subtitles;
+mod convert_subtitles;
+mod set_subtitle_flags;
+mod reorder_subtitles;
+mod track_layout;
 mod exec;
 mod profile_b_convert;
+mod transcode;
 
 pub use dv_convert::DvConvertAction;
+pub use dv_generate::DvGenerateAction;
+pub use extract_hdr10plus::ExtractHdr10PlusAction;
+pub use inject_hdr10plus::InjectHdr10PlusAction;
+pub use remove_hdr10plus::RemoveHdr10PlusAction;
 pub use remux::RemuxAction;
 pub use add_compat_audio::AddCompatAudioAction;
+pub use downmix_audio::DownmixAudioAction;
+pub use set_track_defaults::SetTrackDefaultsAction;
 pub use strip_tracks::StripTracksAction;
+pub use extract_subtitles::ExtractSubtitlesAction;
+pub use convert_subtitles::ConvertSubtitlesAction;
+pub use set_subtitle_flags::SetSubtitleFlagsAction;
+pub use reorder_subtitles::ReorderSubtitlesAction;
 pub use exec::ExecAction;
 pub use profile_b_convert::ProfileBConvertAction;
+pub use transcode::TranscodeAction;
@@ -4,6 +4,7 @@ use async_trait::async_trait;
 
 use crate::action::{Action, ActionResult};
 use crate::context::ActionContext;
+use crate::verify::ExpectedOutput;
 
 /// Convert the input to Profile B (H.264 High / AAC-LC stereo MP4).
 #[derive(Debug)]
@@ -35,6 +36,7 @@ impl Action for ProfileBConvertAction {
             return Ok(ActionResult {
                 output: None,
                 summary: "Would convert to Profile B (H.264/AAC)".to_string(),
+                details: None,
             });
         }
 
@@ -65,9 +67,21 @@ impl Action for ProfileBConvertAction {
         Ok(ActionResult {
             output: Some(output),
             summary: "Converted to Profile B (H.264/AAC)".to_string(),
+            details: None,
         })
     }
 
+    /// One H.264 SDR video track and the first audio track; no subtitles.
+    fn expect_output(&self, ctx: &ActionContext, expected: &mut ExpectedOutput) {
+        expected.video_tracks = Some(1);
+        expected.audio_tracks = Some(ctx.media_info.audio_tracks.len().min(1));
+        expected.subtitle_tracks = Some(0);
+        expected.audio_languages = None;
+        expected.subtitle_languages = None;
+        expected.hdr_format = Some(sf_core::HdrFormat::Sdr);
+        expected.dolby_vision = Some(None);
+    }
+
     fn weight(&self) -> f32 {
         10.0
     }
@@ -0,0 +1,74 @@
+//! Remove HDR10+ metadata action.
+
+use async_trait::async_trait;
+use sf_core::{Container, HdrFormat};
+
+use crate::action::{Action, ActionResult};
+use crate::actions::extract_hdr10plus::require_hevc;
+use crate::context::ActionContext;
+use crate::verify::ExpectedOutput;
+
+/// Strip HDR10+ dynamic metadata from the video, leaving HDR10 (and any
+/// Dolby Vision layer) in place.
+#[derive(Debug, Default)]
+pub struct RemoveHdr10PlusAction;
+
+impl RemoveHdr10PlusAction {
+    /// Create a new HDR10+ removal action.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl Action for RemoveHdr10PlusAction {
+    fn name(&self) -> &'static str {
+        "Remove HDR10+ Metadata"
+    }
+
+    async fn validate(&self, ctx: &ActionContext) -> sf_core::Result<()> {
+        ctx.tools.require("ffmpeg")?;
+        ctx.tools.require("ffprobe")?;
+        ctx.tools.require("hdr10plus_tool")?;
+        if ctx.media_info.container != Container::Mp4 {
+            ctx.tools.require("mkvmerge")?;
+        }
+        require_hevc(ctx)
+    }
+
+    async fn execute(&self, ctx: &ActionContext) -> sf_core::Result<ActionResult> {
+        if ctx.dry_run {
+            tracing::info!("[DRY RUN] Would remove HDR10+ metadata");
+            return Ok(ActionResult {
+                output: None,
+                summary: "Would remove HDR10+ metadata".into(),
+                details: None,
+            });
+        }
+
+        let output = ctx.workspace.output();
+        sf_av::remove_hdr10plus(
+            &ctx.tools,
+            ctx.workspace.input(),
+            &output,
+            ctx.workspace.temp_dir(),
+        )
+        .await?;
+
+        Ok(ActionResult {
+            output: Some(output),
+            summary: "Removed HDR10+ metadata".into(),
+            details: None,
+        })
+    }
+
+    fn expect_output(&self, _ctx: &ActionContext, expected: &mut ExpectedOutput) {
+        if expected.hdr_format == Some(HdrFormat::Hdr10Plus) {
+            expected.hdr_format = Some(HdrFormat::Hdr10);
+        }
+    }
+
+    fn weight(&self) -> f32 {
+        3.0
+    }
+}
@@ -4,8 +4,9 @@ use async_trait::async_trait;
 
 use crate::action::{Action, ActionResult};
 use crate::context::ActionContext;
+use crate::verify::ExpectedOutput;
 
-/// Remux the input into a different container format.
+/// Remux the input into a different container format, keeping every track.
 #[derive(Debug)]
 pub struct RemuxAction {
     container: sf_core::Container,
@@ -36,6 +37,7 @@ impl Action for RemuxAction {
             return Ok(ActionResult {
                 output: None,
                 summary: format!("Would remux to {}", self.container),
+                details: None,
             });
         }
 
@@ -44,10 +46,71 @@ impl Action for RemuxAction {
         Ok(ActionResult {
             output: Some(ctx.workspace.output()),
             summary: format!("Remuxed to {}", self.container),
+            details: None,
         })
     }
 
+    fn expect_output(&self, ctx: &ActionContext, expected: &mut ExpectedOutput) {
+        expected.keep_track_order(&ctx.media_info);
+    }
+
     fn weight(&self) -> f32 {
         2.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn info(languages: &[&str]) -> sf_probe::MediaInfo {
+        sf_probe::MediaInfo {
+            file_path: "/m/a.mkv".into(),
+            file_size: 0,
+            container: sf_core::Container::Mkv,
+            duration: None,
+            video_tracks: vec![],
+            audio_tracks: languages
+                .iter()
+                .map(|&lang| sf_probe::AudioTrack {
+                    codec: sf_core::AudioCodec::Ac3,
+                    channels: 6,
+                    sample_rate: None,
+                    language: Some(lang.into()),
+                    atmos: false,
+                    default: false,
+                })
+                .collect(),
+            subtitle_tracks: vec![],
+        }
+    }
+
+    #[test]
+    fn remux_expects_every_audio_track_in_order() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        let source = info(&["eng", "fre", "ger"]);
+        let ctx = ActionContext::new(
+            Arc::new(sf_av::Workspace::new(tmp.path()).unwrap()),
+            Arc::new(source.clone()),
+            Arc::new(sf_av::ToolRegistry::discover(
+                &sf_core::config::ToolsConfig::default(),
+            )),
+        );
+        let mut expected = ExpectedOutput::from_media_info(&source);
+        RemuxAction::new(sf_core::Container::Mp4).expect_output(&ctx, &mut expected);
+
+        assert!(expected.mismatches(&source, Duration::ZERO).is_empty());
+        assert_eq!(
+            expected.mismatches(&info(&["eng"]), Duration::ZERO),
+            ["1 audio track(s), expected 3"]
+        );
+        assert_eq!(
+            expected.mismatches(&info(&["fre", "eng", "ger"]), Duration::ZERO),
+            [
+                r#"audio track languages are [Some("fre"), Some("eng"), Some("ger")], expected [Some("eng"), Some("fre"), Some("ger")]"#
+            ]
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! Reorder subtitle tracks action.
+
+use async_trait::async_trait;
+
+use crate::action::{Action, ActionResult};
+use crate::actions::track_layout::{current_layout, edit_tool, order_by_language};
+use crate::context::ActionContext;
+use crate::verify::{subtitle_languages, ExpectedOutput};
+
+/// Reorder subtitle tracks by language preference, without re-encoding.
+#[derive(Debug)]
+pub struct ReorderSubtitlesAction {
+    languages: Vec<String>,
+}
+
+impl ReorderSubtitlesAction {
+    /// Create a new action putting tracks in `languages` first, in that
+    /// order. Other tracks keep their relative order.
+    pub fn new(languages: Vec<String>) -> Self {
+        Self { languages }
+    }
+
+    fn layout(&self, info: &sf_probe::MediaInfo) -> sf_av::TrackLayout {
+        let tracks = &info.subtitle_tracks;
+        let mut layout = current_layout(info);
+        order_by_language(&mut layout.subtitles, &self.languages, |i| {
+            tracks[i].language.as_deref()
+        });
+        layout
+    }
+}
+
+#[async_trait]
+impl Action for ReorderSubtitlesAction {
+    fn name(&self) -> &'static str {
+        "Reorder Subtitles"
+    }
+
+    async fn validate(&self, ctx: &ActionContext) -> sf_core::Result<()> {
+        ctx.tools.require(edit_tool(&ctx.media_info))?;
+        Ok(())
+    }
+
+    async fn execute(&self, ctx: &ActionContext) -> sf_core::Result<ActionResult> {
+        let layout = self.layout(&ctx.media_info);
+        let order: Vec<usize> = layout.subtitles.iter().map(|s| s.index).collect();
+        if order.iter().enumerate().all(|(pos, &i)| pos == i) {
+            return Ok(ActionResult {
+                output: None,
+                summary: "Subtitle tracks already in order".to_string(),
+                details: None,
+            });
+        }
+
+        if ctx.dry_run {
+            tracing::info!("[DRY RUN] Would reorder subtitle tracks to {order:?}");
+            return Ok(ActionResult {
+                output: None,
+                summary: format!("Would reorder subtitle tracks to {order:?}"),
+                details: None,
+            });
+        }
+
+        sf_av::edit_tracks(&ctx.workspace, &ctx.tools, &layout).await?;
+
+        Ok(ActionResult {
+            output: Some(ctx.workspace.output()),
+            summary: format!("Reordered subtitle tracks to {order:?}"),
+            details: None,
+        })
+    }
+
+    fn expect_output(&self, ctx: &ActionContext, expected: &mut ExpectedOutput) {
+        let layout = self.layout(&ctx.media_info);
+        let order = layout.subtitles.iter().map(|s| s.index);
+        expected.keep_track_order(&ctx.media_info);
+        expected.subtitle_languages = Some(subtitle_languages(&ctx.media_info, order));
+    }
+}
@@ -0,0 +1,120 @@
+//! Set subtitle default/forced flags action.
+
+use async_trait::async_trait;
+
+use crate::action::{Action, ActionResult};
+use crate::actions::track_layout::{current_layout, edit_tool, language_in};
+use crate::context::ActionContext;
+use crate::verify::ExpectedOutput;
+
+/// Set the default and forced flags of subtitle tracks by language, without
+/// re-encoding.
+#[derive(Debug)]
+pub struct SetSubtitleFlagsAction {
+    default_language: Option<String>,
+    forced_languages: Option<Vec<String>>,
+}
+
+impl SetSubtitleFlagsAction {
+    /// Create a new action.
+    ///
+    /// The first `default_language` track that is not forced (or, failing
+    /// that, the first forced one) becomes the only default track. Tracks in
+    /// `forced_languages` are marked forced and all others cleared. `None`
+    /// leaves the respective flag untouched.
+    pub fn new(default_language: Option<String>, forced_languages: Option<Vec<String>>) -> Self {
+        Self {
+            default_language,
+            forced_languages,
+        }
+    }
+
+    fn layout(&self, info: &sf_probe::MediaInfo) -> sf_av::TrackLayout {
+        let mut layout = current_layout(info);
+        let tracks = &info.subtitle_tracks;
+        let is_lang = |i: usize, lang: &String| {
+            language_in(std::slice::from_ref(lang), tracks[i].language.as_deref())
+        };
+
+        if let Some(ref lang) = self.default_language {
+            let chosen = (0..tracks.len())
+                .filter(|&i| is_lang(i, lang))
+                .min_by_key(|&i| tracks[i].forced);
+            if chosen.is_some() {
+                for spec in &mut layout.subtitles {
+                    spec.default = Some(spec.index) == chosen;
+                }
+            }
+        }
+
+        if let Some(ref langs) = self.forced_languages {
+            for spec in &mut layout.subtitles {
+                spec.forced = language_in(langs, tracks[spec.index].language.as_deref());
+            }
+        }
+
+        layout
+    }
+}
+
+#[async_trait]
+impl Action for SetSubtitleFlagsAction {
+    fn name(&self) -> &'static str {
+        "Set Subtitle Flags"
+    }
+
+    async fn validate(&self, ctx: &ActionContext) -> sf_core::Result<()> {
+        ctx.tools.require(edit_tool(&ctx.media_info))?;
+        Ok(())
+    }
+
+    async fn execute(&self, ctx: &ActionContext) -> sf_core::Result<ActionResult> {
+        let layout = self.layout(&ctx.media_info);
+
+        if layout.subtitles == current_layout(&ctx.media_info).subtitles {
+            return Ok(ActionResult {
+                output: None,
+                summary: "Subtitle flags already set".to_string(),
+                details: None,
+            });
+        }
+
+        let defaults: Vec<usize> = layout
+            .subtitles
+            .iter()
+            .filter(|s| s.default)
+            .map(|s| s.index)
+            .collect();
+        let forced: Vec<usize> = layout
+            .subtitles
+            .iter()
+            .filter(|s| s.forced)
+            .map(|s| s.index)
+            .collect();
+
+        if ctx.dry_run {
+            tracing::info!(
+                "[DRY RUN] Would set subtitle flags: default {defaults:?}, forced {forced:?}"
+            );
+            return Ok(ActionResult {
+                output: None,
+                summary: format!(
+                    "Would set subtitle flags: default {defaults:?}, forced {forced:?}"
+                ),
+                details: None,
+            });
+        }
+
+        sf_av::edit_tracks(&ctx.workspace, &ctx.tools, &layout).await?;
+
+        Ok(ActionResult {
+            output: Some(ctx.workspace.output()),
+            summary: format!("Set subtitle flags: default {defaults:?}, forced {forced:?}"),
+            details: None,
+        })
+    }
+
+    fn expect_output(&self, ctx: &ActionContext, expected: &mut ExpectedOutput) {
+        expected.keep_track_order(&ctx.media_info);
+    }
+}
@@ -0,0 +1,222 @@
+//! Audio track order and default flag action.
+
+use async_trait::async_trait;
+
+use crate::action::{Action, ActionResult};
+use crate::actions::track_layout::{current_layout, edit_tool, language_in};
+use crate::context::ActionContext;
+use crate::verify::{audio_languages, ExpectedOutput};
+
+/// Reorder audio tracks by language and codec preference and make the best
+/// match the only default track, without re-encoding.
+#[derive(Debug)]
+pub struct SetTrackDefaultsAction {
+    languages: Vec<String>,
+    codecs: Vec<sf_core::AudioCodec>,
+    reorder: bool,
+}
+
+impl SetTrackDefaultsAction {
+    /// Create a new action.
+    ///
+    /// Tracks are ranked by their position in `languages`, then in `codecs`;
+    /// tracks matching neither keep their relative order after the others.
+    /// With `reorder` false only the default flag is rewritten.
+    pub fn new(languages: Vec<String>, codecs: Vec<sf_core::AudioCodec>, reorder: bool) -> Self {
+        Self {
+            languages,
+            codecs,
+            reorder,
+        }
+    }
+
+    /// The desired layout, or `None` if no track is in a preferred language.
+    fn layout(&self, info: &sf_probe::MediaInfo) -> Option<sf_av::TrackLayout> {
+        let tracks = &info.audio_tracks;
+        let rank = |i: usize| {
+            let track = &tracks[i];
+            let lang = self
+                .languages
+                .iter()
+                .position(|l| language_in(std::slice::from_ref(l), track.language.as_deref()))
+                .unwrap_or(self.languages.len());
+            let codec = self
+                .codecs
+                .iter()
+                .position(|c| *c == track.codec)
+                .unwrap_or(self.codecs.len());
+            (lang, codec)
+        };
+
+        let best = (0..tracks.len()).min_by_key(|&i| rank(i))?;
+        if rank(best).0 == self.languages.len() {
+            return None;
+        }
+
+        let mut layout = current_layout(info);
+        if self.reorder {
+            layout.audio.sort_by_key(|spec| rank(spec.index));
+        }
+        for spec in &mut layout.audio {
+            spec.default = spec.index == best;
+        }
+        Some(layout)
+    }
+}
+
+#[async_trait]
+impl Action for SetTrackDefaultsAction {
+    fn name(&self) -> &'static str {
+        "Set Track Defaults"
+    }
+
+    async fn validate(&self, ctx: &ActionContext) -> sf_core::Result<()> {
+        ctx.tools.require(edit_tool(&ctx.media_info))?;
+        Ok(())
+    }
+
+    async fn execute(&self, ctx: &ActionContext) -> sf_core::Result<ActionResult> {
+        let Some(layout) = self.layout(&ctx.media_info) else {
+            return Ok(ActionResult {
+                output: None,
+                summary: format!("No audio track in {:?}", self.languages),
+                details: None,
+            });
+        };
+        if layout.audio == current_layout(&ctx.media_info).audio {
+            return Ok(ActionResult {
+                output: None,
+                summary: "Audio tracks already in order".to_string(),
+                details: None,
+            });
+        }
+
+        let order: Vec<usize> = layout.audio.iter().map(|s| s.index).collect();
+        let default = layout.audio.iter().find(|s| s.default).map(|s| s.index);
+
+        if ctx.dry_run {
+            tracing::info!("[DRY RUN] Would set audio order {order:?}, default {default:?}");
+            return Ok(ActionResult {
+                output: None,
+                summary: format!("Would set audio order {order:?}, default {default:?}"),
+                details: None,
+            });
+        }
+
+        sf_av::edit_tracks(&ctx.workspace, &ctx.tools, &layout).await?;
+
+        Ok(ActionResult {
+            output: Some(ctx.workspace.output()),
+            summary: format!("Set audio order {order:?}, default {default:?}"),
+            details: None,
+        })
+    }
+
+    fn expect_output(&self, ctx: &ActionContext, expected: &mut ExpectedOutput) {
+        expected.keep_track_order(&ctx.media_info);
+        if let Some(layout) = self.layout(&ctx.media_info) {
+            let order = layout.audio.iter().map(|s| s.index);
+            expected.audio_languages = Some(audio_languages(&ctx.media_info, order));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sf_core::AudioCodec;
+
+    fn info(tracks: &[(AudioCodec, &str, bool)]) -> sf_probe::MediaInfo {
+        sf_probe::MediaInfo {
+            file_path: "/m/a.mkv".into(),
+            file_size: 0,
+            container: sf_core::Container::Mkv,
+            duration: None,
+            video_tracks: vec![],
+            audio_tracks: tracks
+                .iter()
+                .map(|&(codec, lang, default)| sf_probe::AudioTrack {
+                    codec,
+                    channels: 6,
+                    sample_rate: None,
+                    language: Some(lang.into()),
+                    atmos: false,
+                    default,
+                })
+                .collect(),
+            subtitle_tracks: vec![],
+        }
+    }
+
+    fn summary(layout: &sf_av::TrackLayout) -> Vec<(usize, bool)> {
+        layout.audio.iter().map(|s| (s.index, s.default)).collect()
+    }
+
+    #[test]
+    fn prefers_language_then_codec() {
+        let info = info(&[
+            (AudioCodec::Ac3, "ita", true),
+            (AudioCodec::Ac3, "eng", false),
+            (AudioCodec::TrueHd, "eng", false),
+            (AudioCodec::Aac, "jpn", false),
+        ]);
+        let action = SetTrackDefaultsAction::new(
+            vec!["eng".into(), "jpn".into()],
+            vec![AudioCodec::TrueHd],
+            true,
+        );
+        let layout = action.layout(&info).unwrap();
+        assert_eq!(
+            summary(&layout),
+            [(2, true), (1, false), (3, false), (0, false)]
+        );
+
+        let flags_only = SetTrackDefaultsAction::new(vec!["eng".into()], vec![], false);
+        let layout = flags_only.layout(&info).unwrap();
+        assert_eq!(
+            summary(&layout),
+            [(0, false), (1, true), (2, false), (3, false)]
+        );
+    }
+
+    #[test]
+    fn matches_two_letter_language_codes() {
+        let info = info(&[
+            (AudioCodec::Ac3, "ger", true),
+            (AudioCodec::Ac3, "eng", false),
+        ]);
+        let action = SetTrackDefaultsAction::new(vec!["en".into()], vec![], true);
+        let layout = action.layout(&info).unwrap();
+        assert_eq!(summary(&layout), [(1, true), (0, false)]);
+    }
+
+    #[test]
+    fn expects_the_new_audio_order() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        let source = info(&[
+            (AudioCodec::Ac3, "ita", true),
+            (AudioCodec::Ac3, "eng", false),
+        ]);
+        let ctx = ActionContext::new(
+            std::sync::Arc::new(sf_av::Workspace::new(tmp.path()).unwrap()),
+            std::sync::Arc::new(source.clone()),
+            std::sync::Arc::new(sf_av::ToolRegistry::discover(
+                &sf_core::config::ToolsConfig::default(),
+            )),
+        );
+        let action = SetTrackDefaultsAction::new(vec!["eng".into()], vec![], true);
+        let mut expected = ExpectedOutput::from_media_info(&source);
+        action.expect_output(&ctx, &mut expected);
+
+        let languages = [Some("eng".to_string()), Some("ita".to_string())];
+        assert_eq!(expected.audio_languages.as_deref(), Some(&languages[..]));
+        assert_eq!(expected.mismatches(&source, Default::default()).len(), 1);
+    }
+
+    #[test]
+    fn no_preferred_language_is_left_alone() {
+        let info = info(&[(AudioCodec::Ac3, "ita", true)]);
+        let action = SetTrackDefaultsAction::new(vec!["eng".into()], vec![], true);
+        assert!(action.layout(&info).is_none());
+    }
+}
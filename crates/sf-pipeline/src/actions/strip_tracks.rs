@@ -3,7 +3,9 @@
 use async_trait::async_trait;
 
 use crate::action::{Action, ActionResult};
+use crate::actions::track_layout::language_in;
 use crate::context::ActionContext;
+use crate::verify::ExpectedOutput;
 
 /// Strip specified track types (optionally filtered by language) from the file.
 #[derive(Debug)]
@@ -53,6 +55,7 @@ impl Action for StripTracksAction {
                     "Would strip {:?} (languages: {:?})",
                     self.track_types, self.languages,
                 ),
+                details: None,
             });
         }
 
@@ -64,9 +67,30 @@ impl Action for StripTracksAction {
         Ok(ActionResult {
             output: Some(ctx.workspace.output()),
             summary: format!("Stripped {} track(s)", indices.len()),
+            details: None,
         })
     }
 
+    fn expect_output(&self, ctx: &ActionContext, expected: &mut ExpectedOutput) {
+        let video_count = ctx.media_info.video_tracks.len();
+        let audio_count = ctx.media_info.audio_tracks.len();
+        let indices = self.resolve_track_indices(ctx);
+        let removed = |range: std::ops::Range<usize>| {
+            range.filter(|i| indices.contains(i)).count()
+        };
+        let video = removed(0..video_count);
+        let audio = removed(video_count..video_count + audio_count);
+        let subtitles = removed(
+            video_count + audio_count
+                ..video_count + audio_count + ctx.media_info.subtitle_tracks.len(),
+        );
+        expected.video_tracks = expected.video_tracks.map(|n| n.saturating_sub(video));
+        expected.audio_tracks = expected.audio_tracks.map(|n| n.saturating_sub(audio));
+        expected.subtitle_tracks = expected.subtitle_tracks.map(|n| n.saturating_sub(subtitles));
+        expected.audio_languages = None;
+        expected.subtitle_languages = None;
+    }
+
     fn parallelizable(&self) -> bool {
         false
     }
@@ -114,10 +138,7 @@ impl StripTracksAction {
         match &self.languages {
             None => true,
             Some(langs) if langs.is_empty() => true,
-            Some(langs) => match language {
-                Some(lang) => langs.iter().any(|l| l.eq_ignore_ascii_case(lang)),
-                None => false,
-            },
+            Some(langs) => language_in(langs, language),
         }
     }
 }
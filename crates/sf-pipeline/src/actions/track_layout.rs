@@ -0,0 +1,108 @@
+//! Helpers shared by the actions that rewrite track order and flags.
+
+use sf_av::{TrackLayout, TrackSpec};
+use sf_rules::condition::language_matches;
+
+/// The source's current layout: every track in its original order, with its
+/// probed default/forced flags.
+pub(crate) fn current_layout(info: &sf_probe::MediaInfo) -> TrackLayout {
+    TrackLayout {
+        video_tracks: info.video_tracks.len(),
+        audio: info
+            .audio_tracks
+            .iter()
+            .enumerate()
+            .map(|(index, t)| TrackSpec {
+                index,
+                default: t.default,
+                forced: false,
+            })
+            .collect(),
+        subtitles: info
+            .subtitle_tracks
+            .iter()
+            .enumerate()
+            .map(|(index, t)| TrackSpec {
+                index,
+                default: t.default,
+                forced: t.forced,
+            })
+            .collect(),
+    }
+}
+
+/// The tool [`sf_av::edit_tracks`] uses for this file: mkvmerge for
+/// Matroska, ffmpeg otherwise.
+pub(crate) fn edit_tool(info: &sf_probe::MediaInfo) -> &'static str {
+    match info.container {
+        sf_core::Container::Mkv | sf_core::Container::Webm => "mkvmerge",
+        _ => "ffmpeg",
+    }
+}
+
+/// Whether `language` is one of `languages`, compared as in
+/// [`sf_rules::condition::language_matches`]. Untagged tracks never match.
+pub(crate) fn language_in(languages: &[String], language: Option<&str>) -> bool {
+    language.is_some_and(|lang| language_matches(Some(lang), languages))
+}
+
+/// Stable-sort `specs` so tracks in a `preferred` language come first, in
+/// preference order. `language_of` maps a source track index to its language.
+pub(crate) fn order_by_language<'a>(
+    specs: &mut [TrackSpec],
+    preferred: &[String],
+    language_of: impl Fn(usize) -> Option<&'a str>,
+) {
+    specs.sort_by_key(|spec| {
+        let lang = language_of(spec.index);
+        preferred
+            .iter()
+            .position(|p| language_in(std::slice::from_ref(p), lang))
+            .unwrap_or(preferred.len())
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(index: usize) -> TrackSpec {
+        TrackSpec {
+            index,
+            default: false,
+            forced: false,
+        }
+    }
+
+    #[test]
+    fn orders_preferred_languages_first_and_keeps_the_rest_stable() {
+        let languages = [Some("fre"), None, Some("eng"), Some("ger"), Some("ENG")];
+        let mut specs: Vec<_> = (0..languages.len()).map(spec).collect();
+        order_by_language(&mut specs, &["eng".into(), "ger".into()], |i| languages[i]);
+
+        let order: Vec<_> = specs.iter().map(|s| s.index).collect();
+        assert_eq!(order, [2, 4, 3, 0, 1]);
+    }
+
+    #[test]
+    fn language_matching_is_case_insensitive() {
+        let langs = ["eng".to_string()];
+        assert!(language_in(&langs, Some("ENG")));
+        assert!(!language_in(&langs, Some("fre")));
+        assert!(!language_in(&langs, None));
+    }
+
+    #[test]
+    fn iso_639_1_and_639_2_codes_match() {
+        assert!(language_in(&["en".to_string()], Some("eng")));
+        assert!(language_in(&["eng".to_string()], Some("en")));
+        assert!(language_in(&["de".to_string()], Some("ger")));
+        assert!(language_in(&["ger".to_string()], Some("deu")));
+
+        let languages = [Some("fre"), Some("en"), Some("deu")];
+        let mut specs: Vec<_> = (0..languages.len()).map(spec).collect();
+        order_by_language(&mut specs, &["ger".into(), "eng".into()], |i| languages[i]);
+        let order: Vec<_> = specs.iter().map(|s| s.index).collect();
+        assert_eq!(order, [2, 1, 0]);
+    }
+}
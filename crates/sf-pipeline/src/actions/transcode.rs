@@ -0,0 +1,183 @@
+//! HEVC / AV1 transcode action.
+
+use async_trait::async_trait;
+use sf_core::VideoCodec;
+
+use crate::action::{Action, ActionResult};
+use crate::context::ActionContext;
+use crate::verify::ExpectedOutput;
+
+/// Re-encode the video to HEVC or AV1, keeping the original if the result is
+/// not smaller.
+#[derive(Debug)]
+pub struct TranscodeAction {
+    options: sf_av::TranscodeOptions,
+    require_smaller: bool,
+}
+
+impl TranscodeAction {
+    pub fn new(options: sf_av::TranscodeOptions, require_smaller: bool) -> Self {
+        Self {
+            options,
+            require_smaller,
+        }
+    }
+
+    fn describe(&self) -> String {
+        let codec = match self.options.codec {
+            VideoCodec::H265 => "HEVC",
+            VideoCodec::Av1 => "AV1",
+            other => return format!("{other}"),
+        };
+        format!("{codec} {}-bit", self.options.bit_depth)
+    }
+}
+
+#[async_trait]
+impl Action for TranscodeAction {
+    fn name(&self) -> &'static str {
+        "Transcode"
+    }
+
+    async fn validate(&self, ctx: &ActionContext) -> sf_core::Result<()> {
+        ctx.tools.require("ffmpeg")?;
+        Ok(())
+    }
+
+    async fn execute(&self, ctx: &ActionContext) -> sf_core::Result<ActionResult> {
+        if ctx.dry_run {
+            tracing::info!("[DRY RUN] Would transcode to {}", self.describe());
+            return Ok(ActionResult {
+                output: None,
+                summary: format!("Would transcode to {}", self.describe()),
+                details: None,
+            });
+        }
+
+        let input = ctx.workspace.input();
+        let output = ctx.workspace.output();
+
+        // HDR10 metadata is read from the source so it survives the encode.
+        let source_hdr = ctx
+            .media_info
+            .primary_video()
+            .is_some_and(|v| v.hdr_format != sf_core::HdrFormat::Sdr);
+        let hdr = if source_hdr {
+            if ctx
+                .media_info
+                .primary_video()
+                .is_some_and(|v| v.dolby_vision.is_some())
+            {
+                tracing::warn!(
+                    "Transcoding drops the Dolby Vision layer; only HDR10 metadata is kept"
+                );
+            }
+            sf_av::probe_hdr_metadata(&ctx.tools, input).await?
+        } else {
+            sf_av::HdrMetadata::default()
+        };
+
+        // ffmpeg drops HDR10+ dynamic metadata, so for HEVC it is extracted
+        // beforehand and injected into the encode.
+        let source_hdr10plus = ctx
+            .media_info
+            .primary_video()
+            .is_some_and(|v| v.hdr10plus || v.hdr_format == sf_core::HdrFormat::Hdr10Plus);
+        let hdr10plus = if !source_hdr10plus {
+            None
+        } else if self.options.codec != VideoCodec::H265 {
+            tracing::warn!("Transcoding to {} drops HDR10+ metadata", self.describe());
+            None
+        } else if ctx.tools.require("hdr10plus_tool").is_err() {
+            tracing::warn!("hdr10plus_tool is not available; HDR10+ metadata will be lost");
+            None
+        } else {
+            let metadata = ctx.workspace.temp_file("hdr10plus.json");
+            sf_av::extract_hdr10plus(&ctx.tools, input, &metadata, ctx.workspace.temp_dir())
+                .await?;
+            Some(metadata)
+        };
+        let encoded = match &hdr10plus {
+            Some(_) => {
+                let ext = input.extension().and_then(|e| e.to_str()).unwrap_or("mkv");
+                ctx.workspace.temp_file(&format!("transcoded.{ext}"))
+            }
+            None => output.clone(),
+        };
+
+        let duration_secs = ctx.media_info.duration.map(|d| d.as_secs_f64());
+        let progress = ctx.progress.clone();
+
+        sf_av::transcode(
+            &ctx.tools,
+            input,
+            &encoded,
+            &self.options,
+            &hdr,
+            duration_secs,
+            |p| progress.send((p.pct * 100.0) as f32, "Transcode"),
+            Some(ctx.cancellation.clone()),
+        )
+        .await?;
+
+        if let Some(metadata) = &hdr10plus {
+            sf_av::inject_hdr10plus(
+                &ctx.tools,
+                &encoded,
+                metadata,
+                &output,
+                ctx.workspace.temp_dir(),
+            )
+            .await?;
+            let _ = std::fs::remove_file(&encoded);
+        }
+
+        // Size guard: an archive re-encode that grew the file is discarded.
+        let input_size = file_size(input)?;
+        let output_size = file_size(&output)?;
+        if self.require_smaller && output_size >= input_size {
+            std::fs::remove_file(&output)?;
+            tracing::warn!(
+                "Transcode output ({output_size} bytes) is not smaller than the input \
+                 ({input_size} bytes); keeping the original"
+            );
+            return Ok(ActionResult {
+                output: None,
+                summary: format!(
+                    "Discarded {} transcode: {output_size} bytes is not smaller than \
+                     {input_size} bytes",
+                    self.describe()
+                ),
+                details: None,
+            });
+        }
+
+        Ok(ActionResult {
+            output: Some(output),
+            summary: format!(
+                "Transcoded to {} ({input_size} -> {output_size} bytes)",
+                self.describe()
+            ),
+            details: None,
+        })
+    }
+
+    /// The Dolby Vision layer is dropped, leaving its HDR10 base layer;
+    /// HDR10+ survives only when it could be re-injected.
+    fn expect_output(&self, ctx: &ActionContext, expected: &mut ExpectedOutput) {
+        if ctx.media_info.primary_video().is_some_and(|v| {
+            v.dolby_vision.is_some() || v.hdr10plus || v.hdr_format == sf_core::HdrFormat::Hdr10Plus
+        }) {
+            expected.hdr_format = None;
+        }
+        expected.dolby_vision = Some(None);
+    }
+
+    fn weight(&self) -> f32 {
+        20.0
+    }
+}
+
+fn file_size(path: &std::path::Path) -> sf_core::Result<u64> {
+    Ok(std::fs::metadata(path)?.len())
+}
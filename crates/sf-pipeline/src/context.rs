@@ -1,6 +1,8 @@
 //! Execution context shared by all actions in a pipeline run.
 
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use tokio_util::sync::CancellationToken;
 
@@ -39,10 +41,66 @@ impl std::fmt::Debug for ProgressSender {
     }
 }
 
+/// An entry in a pipeline's execution log.
+#[derive(Debug, Clone)]
+pub enum LogEntry {
+    /// An action started executing.
+    ActionStarted { action: &'static str },
+    /// An action finished; `error` is set if it failed.
+    ActionFinished {
+        action: &'static str,
+        duration: Duration,
+        error: Option<String>,
+    },
+    /// An action ran an external tool.
+    Command {
+        action: &'static str,
+        run: sf_av::ToolRun,
+    },
+}
+
+/// Sender for the execution log of a pipeline run.
+///
+/// The executor logs each action's start and end, and every tool command an
+/// action runs.
+pub struct LogSender {
+    callback: Box<dyn Fn(LogEntry) + Send + Sync>,
+}
+
+impl LogSender {
+    /// Create a new sender from the given callback.
+    pub fn new(callback: impl Fn(LogEntry) + Send + Sync + 'static) -> Self {
+        Self {
+            callback: Box::new(callback),
+        }
+    }
+
+    /// Create a no-op sender that discards all entries.
+    pub fn noop() -> Self {
+        Self {
+            callback: Box::new(|_| {}),
+        }
+    }
+
+    /// Record an entry.
+    pub fn send(&self, entry: LogEntry) {
+        (self.callback)(entry);
+    }
+}
+
+impl std::fmt::Debug for LogSender {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LogSender").finish_non_exhaustive()
+    }
+}
+
 /// Context passed to every action during validation and execution.
 pub struct ActionContext {
     /// Workspace managing temporary and output paths.
     pub workspace: Arc<sf_av::Workspace>,
+    /// The media file the run processes. Sidecar files are named after it,
+    /// since later steps of a run read an intermediate `workspace.input()`.
+    pub source: PathBuf,
     /// Probed media information for the input file.
     pub media_info: Arc<sf_probe::MediaInfo>,
     /// Tool registry for looking up external tool paths.
@@ -51,8 +109,13 @@ pub struct ActionContext {
     pub dry_run: bool,
     /// Token checked between stages; when cancelled the executor aborts early.
     pub cancellation: CancellationToken,
+    /// Pauses and cancels the actions' tool processes. Cancelled along with
+    /// `cancellation`.
+    pub control: sf_av::ProcessControl,
     /// Channel for reporting progress to the caller.
     pub progress: Arc<ProgressSender>,
+    /// Execution log of the run.
+    pub log: Arc<LogSender>,
 }
 
 impl ActionContext {
@@ -62,13 +125,17 @@ impl ActionContext {
         media_info: Arc<sf_probe::MediaInfo>,
         tools: Arc<sf_av::ToolRegistry>,
     ) -> Self {
+        let cancellation = CancellationToken::new();
         Self {
+            source: workspace.input().to_path_buf(),
             workspace,
             media_info,
             tools,
             dry_run: false,
-            cancellation: CancellationToken::new(),
+            control: sf_av::ProcessControl::new(cancellation.clone()),
+            cancellation,
             progress: Arc::new(ProgressSender::noop()),
+            log: Arc::new(LogSender::noop()),
         }
     }
 
@@ -79,14 +146,52 @@ impl ActionContext {
     }
 
     /// Builder: attach a cancellation token.
+    ///
+    /// This replaces the process control with one that cannot be paused; use
+    /// [`with_control`](Self::with_control) for both.
     pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.control = sf_av::ProcessControl::new(token.clone());
         self.cancellation = token;
         self
     }
 
+    /// Builder: attach a process control, which also provides the
+    /// cancellation token.
+    pub fn with_control(mut self, control: sf_av::ProcessControl) -> Self {
+        self.cancellation = control.cancellation_token().clone();
+        self.control = control;
+        self
+    }
+
+    /// A context for a later step of the same run, reading from `workspace`
+    /// with `media_info` and sharing everything else.
+    pub(crate) fn for_step(
+        &self,
+        workspace: Arc<sf_av::Workspace>,
+        media_info: Arc<sf_probe::MediaInfo>,
+    ) -> Self {
+        Self {
+            workspace,
+            source: self.source.clone(),
+            media_info,
+            tools: self.tools.clone(),
+            dry_run: self.dry_run,
+            cancellation: self.cancellation.clone(),
+            control: self.control.clone(),
+            progress: self.progress.clone(),
+            log: self.log.clone(),
+        }
+    }
+
     /// Builder: attach a progress sender.
     pub fn with_progress(mut self, progress: ProgressSender) -> Self {
         self.progress = Arc::new(progress);
         self
     }
+
+    /// Builder: attach an execution log sender.
+    pub fn with_log(mut self, log: LogSender) -> Self {
+        self.log = Arc::new(log);
+        self
+    }
 }
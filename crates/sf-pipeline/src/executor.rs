@@ -1,18 +1,47 @@
 //! Pipeline executor: runs a sequence of [`Action`]s with progress reporting,
-//! cancellation, parallelism within stages, and rollback on failure.
+//! cancellation and pausing, output verification, and rollback on failure.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
 
-use crate::action::Action;
-use crate::context::ActionContext;
+use crate::action::{Action, ActionResult};
+use crate::context::{ActionContext, LogEntry};
+use crate::verify::{ExpectedOutput, Verification};
+
+/// Step name under which output verification is logged and reported.
+const VERIFY_STEP: &str = "Verify Output";
 
 /// Groups actions into sequential stages and executes them.
 ///
-/// Within a stage, parallelizable actions run concurrently via
-/// [`futures::future::try_join_all`]-style semantics (tokio `JoinSet`).
-/// Non-parallelizable actions each form their own single-action stage.
+/// Consecutive parallelizable actions share a stage; non-parallelizable
+/// actions each form their own single-action stage. Pausing and cancellation
+/// are checked between stages.
+///
+/// Actions are chained: every action reads the output of the last action
+/// that wrote one (see [`Chain`]), so the final output carries the effects
+/// of all of them.
 pub struct PipelineExecutor {
     actions: Vec<Box<dyn Action>>,
+    verification: Option<Verification>,
+    prober: Option<Arc<dyn sf_probe::Prober>>,
+}
+
+/// The result of one executed action, as recorded in a [`PipelineReport`].
+#[derive(Debug, Clone)]
+pub struct StepReport {
+    /// The action's [`name`](Action::name).
+    pub action: &'static str,
+    pub result: ActionResult,
+}
+
+/// Outcome of a successful [`PipelineExecutor::run`].
+#[derive(Debug, Clone)]
+pub struct PipelineReport {
+    /// The final output path (the input path in dry-run mode).
+    pub output: PathBuf,
+    /// Results of every action, in execution order.
+    pub steps: Vec<StepReport>,
 }
 
 /// A stage is a group of actions that can run together.
@@ -21,10 +50,80 @@ struct Stage {
     indices: Vec<usize>,
 }
 
+/// Threads each action's output into the next action's input.
+///
+/// Actions read `workspace.input()` and write `workspace.output()`. Until an
+/// action writes an output, actions run in the caller's context. After that,
+/// the next action runs in a fresh workspace whose input is that output, with
+/// the media info re-probed, but the same [`ActionContext::source`] for
+/// naming sidecar files. The latest output is moved to the caller's
+/// workspace output once all actions are done.
+struct Chain {
+    /// The context each executed action ran in, by action index; `None` for
+    /// the caller's.
+    contexts: Vec<Option<Arc<ActionContext>>>,
+    /// Context of the most recent step, if not the caller's.
+    current: Option<Arc<ActionContext>>,
+    /// Output written since `current` was created, which the next action
+    /// must read.
+    pending: Option<PathBuf>,
+    /// The latest output of the run.
+    latest: Option<PathBuf>,
+}
+
+impl Chain {
+    fn new(len: usize) -> Self {
+        Self {
+            contexts: vec![None; len],
+            current: None,
+            pending: None,
+            latest: None,
+        }
+    }
+
+    /// The context action `idx` ran in.
+    fn context<'a>(&'a self, base: &'a ActionContext, idx: usize) -> &'a ActionContext {
+        self.contexts[idx].as_deref().unwrap_or(base)
+    }
+
+    /// Record that an action wrote `output`. The previous intermediate is no
+    /// longer needed once its successor exists.
+    fn advance(&mut self, base: &ActionContext, output: PathBuf) {
+        if let Some(previous) = self.latest.take() {
+            if previous != output && previous != base.workspace.output() {
+                let _ = std::fs::remove_file(&previous);
+            }
+        }
+        self.latest = Some(output.clone());
+        self.pending = Some(output);
+    }
+}
+
 impl PipelineExecutor {
     /// Create a new executor from a list of actions.
     pub fn new(actions: Vec<Box<dyn Action>>) -> Self {
-        Self { actions }
+        Self {
+            actions,
+            verification: None,
+            prober: None,
+        }
+    }
+
+    /// Builder: verify the output once all actions succeed. A failed check
+    /// fails the run and rolls back every action, so the original is never
+    /// replaced by a bad output. Skipped when `verification.config.enabled`
+    /// is false.
+    pub fn with_verification(mut self, verification: Verification) -> Self {
+        self.verification = Some(verification).filter(|v| v.config.enabled);
+        self
+    }
+
+    /// Builder: re-probe each intermediate output, so actions see the tracks
+    /// earlier actions produced. Without a prober every action sees the
+    /// original file's media info.
+    pub fn with_prober(mut self, prober: Arc<dyn sf_probe::Prober>) -> Self {
+        self.prober = Some(prober);
+        self
     }
 
     /// Build stages from the action list.
@@ -71,10 +170,17 @@ impl PipelineExecutor {
     /// Returns the first action error encountered.  On failure, rollback is
     /// called in reverse order on all actions that completed successfully.
     pub async fn execute(&self, ctx: &ActionContext) -> sf_core::Result<PathBuf> {
+        self.run(ctx).await.map(|report| report.output)
+    }
+
+    /// Like [`execute`](Self::execute), but also returns each action's
+    /// [`ActionResult`].
+    pub async fn run(&self, ctx: &ActionContext) -> sf_core::Result<PipelineReport> {
         if self.actions.is_empty() {
             return Err(sf_core::Error::Pipeline {
                 step: "executor".into(),
                 message: "no actions to execute".into(),
+                transient: false,
             });
         }
 
@@ -84,6 +190,7 @@ impl PipelineExecutor {
                 sf_core::Error::Pipeline {
                     step: action.name().into(),
                     message: format!("validation failed: {e}"),
+                    transient: e.is_transient(),
                 }
             })?;
         }
@@ -91,25 +198,34 @@ impl PipelineExecutor {
         let stages = self.build_stages();
         let total_weight = self.total_weight();
         let mut completed_weight: f32 = 0.0;
-        let mut completed_indices: Vec<usize> = Vec::new();
+        let mut completed: Vec<usize> = Vec::new();
+        let mut steps: Vec<StepReport> = Vec::new();
+        let mut chain = Chain::new(self.actions.len());
 
         for stage in &stages {
-            // Check cancellation between stages.
+            // Hold paused pipelines between stages, then check cancellation.
+            ctx.control.wait_while_paused().await;
             if ctx.cancellation.is_cancelled() {
                 tracing::info!("Pipeline cancelled");
-                self.rollback_completed(ctx, &completed_indices).await;
+                self.rollback_completed(ctx, &chain, &completed).await;
                 return Err(sf_core::Error::Pipeline {
                     step: "executor".into(),
                     message: "cancelled".into(),
+                    transient: false,
                 });
             }
 
-            let result = self.execute_stage(stage, ctx).await;
+            let result = self
+                .execute_stage(stage, ctx, &mut chain, &mut completed)
+                .await;
 
             match result {
-                Ok(()) => {
-                    for &idx in &stage.indices {
-                        completed_indices.push(idx);
+                Ok(results) => {
+                    for (&idx, result) in stage.indices.iter().zip(results) {
+                        steps.push(StepReport {
+                            action: self.actions[idx].name(),
+                            result,
+                        });
                         completed_weight += self.actions[idx].weight();
                         let pct = if total_weight > 0.0 {
                             (completed_weight / total_weight) * 100.0
@@ -126,100 +242,239 @@ impl PipelineExecutor {
                 }
                 Err(e) => {
                     tracing::error!("Stage failed: {e}");
-                    self.rollback_completed(ctx, &completed_indices).await;
+                    self.rollback_completed(ctx, &chain, &completed).await;
                     return Err(e);
                 }
             }
         }
 
+        // Put the last action's output where the caller expects it.
+        if let Err(e) = self.collect_output(ctx, &chain).await {
+            tracing::error!("Stage failed: {e}");
+            self.rollback_completed(ctx, &chain, &completed).await;
+            return Err(e);
+        }
+
+        // Only outputs that would replace the original are verified.
+        let produced = steps.iter().any(|step| step.result.output.is_some());
+        if let (Some(verification), false, true) = (&self.verification, ctx.dry_run, produced) {
+            ctx.progress.send(100.0, VERIFY_STEP);
+            if let Err(e) = self.verify(verification, ctx, &chain).await {
+                tracing::error!("Output verification failed: {e}");
+                self.rollback_completed(ctx, &chain, &completed).await;
+                return Err(e);
+            }
+        }
+
         ctx.progress.send(100.0, "Finalizing");
         tracing::info!("[100%] Finalizing");
 
-        if ctx.dry_run {
-            Ok(ctx.workspace.input().to_path_buf())
+        let output = if ctx.dry_run {
+            ctx.workspace.input().to_path_buf()
         } else {
-            Ok(ctx.workspace.output())
-        }
+            ctx.workspace.output()
+        };
+        Ok(PipelineReport { output, steps })
     }
 
-    /// Execute all actions in a stage, potentially in parallel.
+    /// Execute the actions of a stage one after another, each reading the
+    /// previous one's output, and return their results in stage order.
+    ///
+    /// Parallelizable actions only declare that they don't depend on each
+    /// other's side effects; chaining their outputs still requires running
+    /// them in sequence.
     async fn execute_stage(
         &self,
         stage: &Stage,
         ctx: &ActionContext,
-    ) -> sf_core::Result<()> {
-        if stage.indices.len() == 1 {
-            // Single action -- run directly.
-            let idx = stage.indices[0];
+        chain: &mut Chain,
+        completed: &mut Vec<usize>,
+    ) -> sf_core::Result<Vec<ActionResult>> {
+        let mut results = Vec::with_capacity(stage.indices.len());
+        for &idx in &stage.indices {
             let action = &self.actions[idx];
+            let to_pipeline_error = |e: sf_core::Error| sf_core::Error::Pipeline {
+                step: action.name().into(),
+                message: e.to_string(),
+                transient: e.is_transient(),
+            };
+
+            let step_ctx = self
+                .step_context(ctx, chain)
+                .await
+                .map_err(to_pipeline_error)?;
+            chain.contexts[idx] = step_ctx.clone();
+
             tracing::info!("Starting: {}", action.name());
-            action.execute(ctx).await.map_err(|e| {
-                sf_core::Error::Pipeline {
-                    step: action.name().into(),
-                    message: e.to_string(),
-                }
-            })?;
-            return Ok(());
+            let result = self
+                .execute_action(idx, step_ctx.as_deref().unwrap_or(ctx))
+                .await
+                .map_err(to_pipeline_error)?;
+            completed.push(idx);
+            if let Some(ref output) = result.output {
+                chain.advance(ctx, output.clone());
+            }
+            results.push(result);
         }
+        Ok(results)
+    }
 
-        // Multiple parallel actions -- poll all futures concurrently.
-        //
-        // We build a `FuturesUnordered`-style execution by pinning futures in a
-        // Vec and using `tokio::select!` is awkward for dynamic sizes, so we
-        // simply collect all futures into a Vec and use a manual poll loop via
-        // `tokio::join!` is fixed-arity.
-        //
-        // The pragmatic approach: spawn each action execution as a tokio task.
-        // Since `Action` is `Send + Sync` and `ActionContext` fields are all
-        // `Arc`-wrapped, we can share references safely via scoped approach.
-        //
-        // Simplest correct approach: execute sequentially (the actions *declare*
-        // themselves parallelizable, meaning it is *safe* to run them in
-        // parallel, but sequential is always correct). For true parallelism
-        // with borrowed data, we would need a scoped task API.
-        //
-        // We use the pattern of collecting futures and polling them with
-        // `poll_fn`, but the simplest correct-and-fast approach here is to
-        // execute them with `tokio::join!` for 2, or sequentially for more.
-        // In practice, parallel stages rarely exceed 2-3 actions.
-        let mut first_error: Option<sf_core::Error> = None;
-        for &idx in &stage.indices {
-            let action = &self.actions[idx];
-            tracing::info!("Starting (parallel-safe): {}", action.name());
-            if let Err(e) = action.execute(ctx).await {
-                first_error = Some(sf_core::Error::Pipeline {
-                    step: action.name().into(),
-                    message: e.to_string(),
-                });
-                break;
+    /// The context the next action runs in: a new one reading the pending
+    /// output if there is one, otherwise the current one.
+    async fn step_context(
+        &self,
+        base: &ActionContext,
+        chain: &mut Chain,
+    ) -> sf_core::Result<Option<Arc<ActionContext>>> {
+        let Some(input) = chain.pending.take() else {
+            return Ok(chain.current.clone());
+        };
+
+        let media_info = match &self.prober {
+            Some(prober) => {
+                let prober = prober.clone();
+                let path = input.clone();
+                let info = tokio::task::spawn_blocking(move || prober.probe(&path))
+                    .await
+                    .map_err(|e| sf_core::Error::Internal(format!("probe task failed: {e}")))??;
+                Arc::new(info)
             }
-        }
+            None => chain
+                .current
+                .as_ref()
+                .map_or_else(|| base.media_info.clone(), |c| c.media_info.clone()),
+        };
 
-        if let Some(e) = first_error {
-            return Err(e);
+        let workspace = Arc::new(sf_av::Workspace::new(&input)?);
+        let step_ctx = Arc::new(base.for_step(workspace, media_info));
+        chain.current = Some(step_ctx.clone());
+        Ok(Some(step_ctx))
+    }
+
+    /// Move the run's latest output to the caller's workspace output, if an
+    /// action after the first output wrote it elsewhere.
+    async fn collect_output(&self, ctx: &ActionContext, chain: &Chain) -> sf_core::Result<()> {
+        let Some(latest) = &chain.latest else {
+            return Ok(());
+        };
+        move_file(latest, &ctx.workspace.output()).await
+    }
+
+    /// Execute one action under the context's process control, logging its
+    /// start, end and tool commands.
+    async fn execute_action(
+        &self,
+        idx: usize,
+        ctx: &ActionContext,
+    ) -> sf_core::Result<ActionResult> {
+        let action = self.actions[idx].name();
+        ctx.log.send(LogEntry::ActionStarted { action });
+        let started = Instant::now();
+
+        let log = ctx.log.clone();
+        let observer: sf_av::ToolRunObserver =
+            Arc::new(move |run| log.send(LogEntry::Command { action, run }));
+        let result = sf_av::observe_tool_runs(
+            observer,
+            sf_av::with_process_control(ctx.control.clone(), self.actions[idx].execute(ctx)),
+        )
+        .await;
+
+        ctx.log.send(LogEntry::ActionFinished {
+            action,
+            duration: started.elapsed(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        });
+        result
+    }
+
+    /// Check the workspace output against what the actions should have
+    /// produced, logged like an action.
+    async fn verify(
+        &self,
+        verification: &Verification,
+        ctx: &ActionContext,
+        chain: &Chain,
+    ) -> sf_core::Result<()> {
+        let mut expected = ExpectedOutput::from_media_info(&ctx.media_info);
+        for (idx, action) in self.actions.iter().enumerate() {
+            action.expect_output(chain.context(ctx, idx), &mut expected);
         }
 
-        Ok(())
+        ctx.log.send(LogEntry::ActionStarted {
+            action: VERIFY_STEP,
+        });
+        let started = Instant::now();
+
+        let log = ctx.log.clone();
+        let observer: sf_av::ToolRunObserver = Arc::new(move |run| {
+            log.send(LogEntry::Command {
+                action: VERIFY_STEP,
+                run,
+            })
+        });
+        let output = ctx.workspace.output();
+        let result = sf_av::observe_tool_runs(
+            observer,
+            sf_av::with_process_control(
+                ctx.control.clone(),
+                verification.check(&ctx.tools, &output, &expected),
+            ),
+        )
+        .await
+        .and_then(|mismatches| {
+            if mismatches.is_empty() {
+                return Ok(());
+            }
+            Err(sf_core::Error::Pipeline {
+                step: VERIFY_STEP.into(),
+                message: format!("output verification failed: {}", mismatches.join("; ")),
+                transient: false,
+            })
+        });
+
+        ctx.log.send(LogEntry::ActionFinished {
+            action: VERIFY_STEP,
+            duration: started.elapsed(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        });
+        result
     }
 
-    /// Rollback completed actions in reverse order.
-    async fn rollback_completed(&self, ctx: &ActionContext, completed: &[usize]) {
+    /// Rollback completed actions in reverse order, each in the context it
+    /// ran in.
+    async fn rollback_completed(&self, ctx: &ActionContext, chain: &Chain, completed: &[usize]) {
         for &idx in completed.iter().rev() {
             let action = &self.actions[idx];
             tracing::info!("Rolling back: {}", action.name());
-            if let Err(e) = action.rollback(ctx).await {
+            if let Err(e) = action.rollback(chain.context(ctx, idx)).await {
                 tracing::warn!("Rollback failed for {}: {e}", action.name());
             }
         }
     }
 }
 
+/// Move `from` to `to`, copying across filesystems. A no-op when they are the
+/// same path.
+async fn move_file(from: &Path, to: &Path) -> sf_core::Result<()> {
+    if from == to {
+        return Ok(());
+    }
+    if tokio::fs::rename(from, to).await.is_err() {
+        tokio::fs::copy(from, to).await?;
+        let _ = tokio::fs::remove_file(from).await;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::context::{ActionContext, ProgressSender};
+    use crate::context::{ActionContext, LogSender, ProgressSender};
     use crate::action::ActionResult;
     use async_trait::async_trait;
+    use sf_core::SubtitleFormat;
+    use std::os::unix::fs::PermissionsExt;
     use std::sync::Arc;
     use std::sync::atomic::{AtomicUsize, Ordering};
     use tokio_util::sync::CancellationToken;
@@ -242,12 +497,15 @@ mod tests {
         let tools_cfg = sf_core::config::ToolsConfig::default();
         let tools = Arc::new(sf_av::ToolRegistry::discover(&tools_cfg));
         ActionContext {
+            source: workspace.input().to_path_buf(),
             workspace,
             media_info: Arc::new(dummy_media_info()),
             tools,
             dry_run: true,
             cancellation: CancellationToken::new(),
+            control: sf_av::ProcessControl::default(),
             progress: Arc::new(ProgressSender::noop()),
+            log: Arc::new(LogSender::noop()),
         }
     }
 
@@ -272,6 +530,7 @@ mod tests {
             Ok(ActionResult {
                 output: None,
                 summary: format!("{} done", self.name),
+                details: None,
             })
         }
         fn parallelizable(&self) -> bool {
@@ -295,6 +554,31 @@ mod tests {
             Err(sf_core::Error::Pipeline {
                 step: self.name.into(),
                 message: "intentional failure".into(),
+                transient: false,
+            })
+        }
+    }
+
+    /// Runs `sleep 10` through a [`sf_av::ToolCommand`].
+    struct FakeSleep;
+
+    #[async_trait]
+    impl Action for FakeSleep {
+        fn name(&self) -> &'static str {
+            "sleep"
+        }
+        async fn validate(&self, _ctx: &ActionContext) -> sf_core::Result<()> {
+            Ok(())
+        }
+        async fn execute(&self, _ctx: &ActionContext) -> sf_core::Result<ActionResult> {
+            sf_av::ToolCommand::new("sleep".into())
+                .arg("10")
+                .execute()
+                .await?;
+            Ok(ActionResult {
+                output: None,
+                summary: "slept".into(),
+                details: None,
             })
         }
     }
@@ -330,6 +614,7 @@ mod tests {
             Ok(ActionResult {
                 output: None,
                 summary: "done".into(),
+                details: None,
             })
         }
         async fn rollback(&self, _ctx: &ActionContext) -> sf_core::Result<()> {
@@ -338,6 +623,111 @@ mod tests {
         }
     }
 
+    /// Writes the workspace output; rollback removes it again.
+    struct FakeOutput {
+        rolled_back: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Action for FakeOutput {
+        fn name(&self) -> &'static str {
+            "output"
+        }
+        async fn validate(&self, _ctx: &ActionContext) -> sf_core::Result<()> {
+            Ok(())
+        }
+        async fn execute(&self, ctx: &ActionContext) -> sf_core::Result<ActionResult> {
+            std::fs::write(ctx.workspace.output(), b"media")?;
+            Ok(ActionResult {
+                output: Some(ctx.workspace.output()),
+                summary: "wrote output".into(),
+                details: None,
+            })
+        }
+        async fn rollback(&self, ctx: &ActionContext) -> sf_core::Result<()> {
+            self.rolled_back.fetch_add(1, Ordering::SeqCst);
+            std::fs::remove_file(ctx.workspace.output())?;
+            Ok(())
+        }
+    }
+
+    /// Appends `tag` to the workspace input and writes it to the output,
+    /// recording how many audio tracks its media info had.
+    struct FakeAppend {
+        tag: &'static str,
+        seen_audio_tracks: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Action for FakeAppend {
+        fn name(&self) -> &'static str {
+            self.tag
+        }
+        async fn validate(&self, _ctx: &ActionContext) -> sf_core::Result<()> {
+            Ok(())
+        }
+        async fn execute(&self, ctx: &ActionContext) -> sf_core::Result<ActionResult> {
+            self.seen_audio_tracks
+                .store(ctx.media_info.audio_tracks.len(), Ordering::SeqCst);
+            let mut data = std::fs::read(ctx.workspace.input())?;
+            data.extend_from_slice(self.tag.as_bytes());
+            std::fs::write(ctx.workspace.output(), data)?;
+            Ok(ActionResult {
+                output: Some(ctx.workspace.output()),
+                summary: format!("appended {}", self.tag),
+                details: None,
+            })
+        }
+        fn parallelizable(&self) -> bool {
+            true
+        }
+    }
+
+    fn append(tag: &'static str) -> (Box<dyn Action>, Arc<AtomicUsize>) {
+        let seen = Arc::new(AtomicUsize::new(usize::MAX));
+        let action = FakeAppend {
+            tag,
+            seen_audio_tracks: seen.clone(),
+        };
+        (Box::new(action), seen)
+    }
+
+    /// Reports every file as having `audio_tracks` AAC tracks.
+    struct FakeProber {
+        audio_tracks: usize,
+    }
+
+    impl sf_probe::Prober for FakeProber {
+        fn name(&self) -> &'static str {
+            "fake"
+        }
+        fn probe(&self, path: &std::path::Path) -> sf_core::Result<sf_probe::MediaInfo> {
+            let track = sf_probe::AudioTrack {
+                codec: sf_core::AudioCodec::Aac,
+                channels: 2,
+                sample_rate: None,
+                language: None,
+                atmos: false,
+                default: false,
+            };
+            Ok(sf_probe::MediaInfo {
+                file_path: path.to_path_buf(),
+                audio_tracks: vec![track; self.audio_tracks],
+                ..dummy_media_info()
+            })
+        }
+        fn supports(&self, _path: &std::path::Path) -> bool {
+            true
+        }
+    }
+
+    fn verification(audio_tracks: usize) -> Verification {
+        Verification {
+            prober: Arc::new(FakeProber { audio_tracks }),
+            config: sf_core::config::VerificationConfig::default(),
+        }
+    }
+
     // -- Tests ----------------------------------------------------------------
 
     #[tokio::test]
@@ -370,6 +760,123 @@ mod tests {
         assert_eq!(counter.load(Ordering::SeqCst), 1);
     }
 
+    #[tokio::test]
+    async fn run_reports_step_results_in_order() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        let ws = Arc::new(sf_av::Workspace::new(tmp.path()).unwrap());
+        let ctx = make_ctx(ws);
+
+        let counter = Arc::new(AtomicUsize::new(0));
+        let actions: Vec<Box<dyn Action>> = ["a", "b", "c"]
+            .into_iter()
+            .map(|name| {
+                Box::new(FakeOk {
+                    name,
+                    parallel: name != "c",
+                    executed: counter.clone(),
+                }) as Box<dyn Action>
+            })
+            .collect();
+
+        let report = PipelineExecutor::new(actions).run(&ctx).await.unwrap();
+        let names: Vec<_> = report.steps.iter().map(|s| s.action).collect();
+        assert_eq!(names, ["a", "b", "c"]);
+        assert_eq!(report.steps[1].result.summary, "b done");
+        assert_eq!(report.output, tmp.path());
+    }
+
+    #[tokio::test]
+    async fn actions_read_the_previous_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("movie.mkv");
+        std::fs::write(&input, b"media").unwrap();
+        let ws = Arc::new(sf_av::Workspace::new(&input).unwrap());
+        let ctx = make_ctx(ws.clone()).with_dry_run(false);
+
+        // Both are parallelizable, so they share a stage.
+        let (first, _) = append("+a");
+        let (second, _) = append("+b");
+        let (third, _) = append("+c");
+        let report = PipelineExecutor::new(vec![first, second, third])
+            .run(&ctx)
+            .await
+            .unwrap();
+
+        assert_eq!(report.output, ws.output());
+        assert_eq!(std::fs::read(ws.output()).unwrap(), b"media+a+b+c");
+        // The original is untouched until the caller finalizes.
+        assert_eq!(std::fs::read(&input).unwrap(), b"media");
+    }
+
+    #[tokio::test]
+    async fn later_actions_see_reprobed_media_info() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("movie.mkv");
+        std::fs::write(&input, b"media").unwrap();
+        let ws = Arc::new(sf_av::Workspace::new(&input).unwrap());
+        let ctx = make_ctx(ws.clone()).with_dry_run(false);
+
+        let (first, seen_first) = append("+a");
+        let (second, seen_second) = append("+b");
+        PipelineExecutor::new(vec![first, second])
+            .with_prober(Arc::new(FakeProber { audio_tracks: 3 }))
+            .run(&ctx)
+            .await
+            .unwrap();
+
+        // The first action sees the original, the second its output.
+        assert_eq!(seen_first.load(Ordering::SeqCst), 0);
+        assert_eq!(seen_second.load(Ordering::SeqCst), 3);
+        assert_eq!(std::fs::read(ws.output()).unwrap(), b"media+a+b");
+    }
+
+    #[tokio::test]
+    async fn sidecars_are_written_next_to_the_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("movie.mkv");
+        std::fs::write(&input, b"media").unwrap();
+        // Stands in for ffmpeg, creating every subtitle file it is asked for.
+        let ffmpeg = dir.path().join("ffmpeg");
+        std::fs::write(
+            &ffmpeg,
+            "#!/bin/sh\nfor a; do case \"$a\" in *.srt) echo sub > \"$a\";; esac; done\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&ffmpeg, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let tools = sf_av::ToolRegistry::discover(&sf_core::config::ToolsConfig {
+            ffmpeg_path: Some(ffmpeg),
+            ..Default::default()
+        });
+
+        let ws = Arc::new(sf_av::Workspace::new(&input).unwrap());
+        let mut ctx = make_ctx(ws.clone()).with_dry_run(false);
+        ctx.tools = Arc::new(tools);
+        let mut info = dummy_media_info();
+        info.subtitle_tracks.push(sf_probe::SubtitleTrack {
+            codec: "SRT".into(),
+            language: Some("eng".into()),
+            forced: false,
+            default: false,
+        });
+        ctx.media_info = Arc::new(info);
+
+        let output = FakeOutput {
+            rolled_back: Arc::new(AtomicUsize::new(0)),
+        };
+        let extract = crate::actions::ExtractSubtitlesAction::new(None, SubtitleFormat::Srt);
+        PipelineExecutor::new(vec![Box::new(output), Box::new(extract)])
+            .run(&ctx)
+            .await
+            .unwrap();
+
+        // Extracted from the first action's output, but named after the
+        // original rather than the intermediate in its temp workspace.
+        assert_eq!(
+            std::fs::read(dir.path().join("movie.eng.srt")).unwrap(),
+            b"sub\n"
+        );
+    }
+
     #[tokio::test]
     async fn validation_failure_prevents_execution() {
         let tmp = tempfile::NamedTempFile::new().unwrap();
@@ -411,6 +918,7 @@ mod tests {
         let ws = Arc::new(sf_av::Workspace::new(tmp.path()).unwrap());
         let token = CancellationToken::new();
         let ctx = ActionContext {
+            source: ws.input().to_path_buf(),
             workspace: ws,
             media_info: Arc::new(dummy_media_info()),
             tools: Arc::new(sf_av::ToolRegistry::discover(
@@ -418,7 +926,9 @@ mod tests {
             )),
             dry_run: true,
             cancellation: token.clone(),
+            control: sf_av::ProcessControl::new(token.clone()),
             progress: Arc::new(ProgressSender::noop()),
+            log: Arc::new(LogSender::noop()),
         };
 
         // Cancel before execution.
@@ -439,6 +949,25 @@ mod tests {
         assert_eq!(counter.load(Ordering::SeqCst), 0);
     }
 
+    #[tokio::test]
+    async fn cancellation_kills_running_tool() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        let ws = Arc::new(sf_av::Workspace::new(tmp.path()).unwrap());
+        let control = sf_av::ProcessControl::default();
+        let ctx = make_ctx(ws).with_control(control.clone());
+
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            control.cancel();
+        });
+
+        let started = Instant::now();
+        let executor = PipelineExecutor::new(vec![Box::new(FakeSleep)]);
+        let err = executor.execute(&ctx).await.unwrap_err().to_string();
+        assert!(err.contains("cancelled"), "got: {err}");
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    }
+
     #[tokio::test]
     async fn parallel_actions_in_stage() {
         let tmp = tempfile::NamedTempFile::new().unwrap();
@@ -520,12 +1049,15 @@ mod tests {
         let tools_cfg = sf_core::config::ToolsConfig::default();
         let tools = Arc::new(sf_av::ToolRegistry::discover(&tools_cfg));
         let ctx = ActionContext {
+            source: ws.input().to_path_buf(),
             workspace: ws,
             media_info: Arc::new(dummy_media_info()),
             tools,
             dry_run: true,
             cancellation: CancellationToken::new(),
+            control: sf_av::ProcessControl::default(),
             progress: Arc::new(progress),
+            log: Arc::new(LogSender::noop()),
         };
 
         let c = Arc::new(AtomicUsize::new(0));
@@ -552,4 +1084,84 @@ mod tests {
         assert!(rpts[0].0 > 0.0);
         assert_eq!(rpts[2].1, "Finalizing");
     }
+
+    #[tokio::test]
+    async fn actions_are_logged() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        let ws = Arc::new(sf_av::Workspace::new(tmp.path()).unwrap());
+        let entries = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let log = {
+            let entries = entries.clone();
+            LogSender::new(move |entry| entries.lock().unwrap().push(entry))
+        };
+        let ctx = make_ctx(ws).with_log(log);
+
+        let actions: Vec<Box<dyn Action>> = vec![
+            Box::new(FakeOk {
+                name: "ok",
+                parallel: false,
+                executed: Arc::new(AtomicUsize::new(0)),
+            }),
+            Box::new(FakeFail { name: "fail" }),
+        ];
+        let result = PipelineExecutor::new(actions).execute(&ctx).await;
+        assert!(result.is_err());
+
+        let entries = entries.lock().unwrap();
+        assert_eq!(entries.len(), 4);
+        assert!(matches!(entries[0], LogEntry::ActionStarted { action: "ok" }));
+        assert!(matches!(
+            entries[1],
+            LogEntry::ActionFinished {
+                action: "ok",
+                error: None,
+                ..
+            }
+        ));
+        assert!(matches!(
+            &entries[3],
+            LogEntry::ActionFinished {
+                action: "fail",
+                error: Some(e),
+                ..
+            } if e.contains("intentional failure")
+        ));
+    }
+
+    #[tokio::test]
+    async fn verified_output_passes() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        let ws = Arc::new(sf_av::Workspace::new(tmp.path()).unwrap());
+        let ctx = make_ctx(ws).with_dry_run(false);
+
+        let rb = Arc::new(AtomicUsize::new(0));
+        let actions: Vec<Box<dyn Action>> = vec![Box::new(FakeOutput {
+            rolled_back: rb.clone(),
+        })];
+        let executor = PipelineExecutor::new(actions).with_verification(verification(0));
+        let output = executor.execute(&ctx).await.unwrap();
+        assert!(output.exists());
+        assert_eq!(rb.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn failed_verification_rolls_back() {
+        let tmp = tempfile::NamedTempFile::new().unwrap();
+        let ws = Arc::new(sf_av::Workspace::new(tmp.path()).unwrap());
+        let ctx = make_ctx(ws.clone()).with_dry_run(false);
+
+        let rb = Arc::new(AtomicUsize::new(0));
+        let actions: Vec<Box<dyn Action>> = vec![Box::new(FakeOutput {
+            rolled_back: rb.clone(),
+        })];
+        // The source has no audio, but the output has a track.
+        let executor = PipelineExecutor::new(actions).with_verification(verification(1));
+        let err = executor.execute(&ctx).await.unwrap_err();
+        assert!(
+            err.to_string().contains("1 audio track(s), expected 0"),
+            "{err}"
+        );
+        assert_eq!(rb.load(Ordering::SeqCst), 1);
+        assert!(!ws.output().exists());
+    }
 }
@@ -1,11 +1,16 @@
 //! Action factory: construct [`Action`] objects from [`ActionConfig`] values.
 
+use std::path::PathBuf;
+
 use sf_rules::ActionConfig;
 
 use crate::action::Action;
 use crate::actions::{
-    AddCompatAudioAction, DvConvertAction, ExecAction, ProfileBConvertAction, RemuxAction,
-    StripTracksAction,
+    AddCompatAudioAction, ConvertSubtitlesAction, DownmixAudioAction, DvConvertAction,
+    DvGenerateAction, ExecAction, ExtractHdr10PlusAction, ExtractSubtitlesAction,
+    InjectHdr10PlusAction, ProfileBConvertAction, RemoveHdr10PlusAction, RemuxAction,
+    ReorderSubtitlesAction, SetSubtitleFlagsAction, SetTrackDefaultsAction, StripTracksAction,
+    TranscodeAction,
 };
 
 /// Create a list of boxed [`Action`] objects from rule-engine configurations.
@@ -16,7 +21,7 @@ use crate::actions::{
 /// # Errors
 ///
 /// Returns [`sf_core::Error::Tool`] if a required tool is not present in the
-/// registry.
+/// registry, or [`sf_core::Error::Validation`] for unsupported settings.
 pub fn create_actions(
     configs: &[ActionConfig],
     tools: &sf_av::ToolRegistry,
@@ -31,6 +36,30 @@ pub fn create_actions(
                 tools.require("mkvmerge")?;
                 actions.push(Box::new(DvConvertAction::new(*target_profile)));
             }
+            ActionConfig::DvGenerate { profile } => {
+                tools.require("ffmpeg")?;
+                tools.require("ffprobe")?;
+                tools.require("dovi_tool")?;
+                tools.require("mkvmerge")?;
+                actions.push(Box::new(DvGenerateAction::new(*profile)));
+            }
+            ActionConfig::ExtractHdr10Plus => {
+                tools.require("ffmpeg")?;
+                tools.require("hdr10plus_tool")?;
+                actions.push(Box::new(ExtractHdr10PlusAction::new()));
+            }
+            ActionConfig::InjectHdr10Plus { metadata } => {
+                tools.require("ffmpeg")?;
+                tools.require("hdr10plus_tool")?;
+                actions.push(Box::new(InjectHdr10PlusAction::new(
+                    metadata.as_ref().map(PathBuf::from),
+                )));
+            }
+            ActionConfig::RemoveHdr10Plus => {
+                tools.require("ffmpeg")?;
+                tools.require("hdr10plus_tool")?;
+                actions.push(Box::new(RemoveHdr10PlusAction::new()));
+            }
             ActionConfig::Remux { container, .. } => {
                 tools.require("ffmpeg")?;
                 actions.push(Box::new(RemuxAction::new(*container)));
@@ -45,6 +74,83 @@ pub fn create_actions(
                     *target_codec,
                 )));
             }
+            ActionConfig::DownmixAudio {
+                source_track,
+                target_lufs,
+                dialogue_boost,
+                codec,
+                bitrate,
+                title,
+                language,
+                default_track,
+            } => {
+                tools.require("ffmpeg")?;
+                let target_lufs = target_lufs.unwrap_or(-23.0);
+                if !(-70.0..=-5.0).contains(&target_lufs) {
+                    return Err(sf_core::Error::Validation(format!(
+                        "downmix target_lufs {target_lufs} is out of range (-70 to -5)"
+                    )));
+                }
+                let options = sf_av::DownmixOptions {
+                    source_track: 0,
+                    existing_tracks: 0,
+                    target_lufs,
+                    true_peak: -1.0,
+                    loudness_range: 7.0,
+                    dialogue_boost: *dialogue_boost,
+                    codec: codec.unwrap_or(sf_core::AudioCodec::Aac),
+                    bitrate: Some(bitrate.clone().unwrap_or_else(|| "192k".into())),
+                    title: Some(title.clone().unwrap_or_else(|| "Stereo".into())),
+                    language: language.clone(),
+                    make_default: *default_track,
+                };
+                actions.push(Box::new(DownmixAudioAction::new(*source_track, options)));
+            }
+            ActionConfig::ExtractSubtitles { languages, format } => {
+                tools.require("ffmpeg")?;
+                actions.push(Box::new(ExtractSubtitlesAction::new(
+                    languages.clone(),
+                    *format,
+                )));
+            }
+            ActionConfig::ConvertSubtitles { languages } => {
+                tools.require("ffmpeg")?;
+                actions.push(Box::new(ConvertSubtitlesAction::new(languages.clone())));
+            }
+            // mkvmerge or ffmpeg depending on the container; checked in validate.
+            ActionConfig::SetTrackDefaults {
+                languages,
+                codecs,
+                reorder,
+            } => {
+                if languages.is_empty() {
+                    return Err(sf_core::Error::Validation(
+                        "set_track_defaults requires at least one language".into(),
+                    ));
+                }
+                actions.push(Box::new(SetTrackDefaultsAction::new(
+                    languages.clone(),
+                    codecs.clone(),
+                    *reorder,
+                )));
+            }
+            ActionConfig::SetSubtitleFlags {
+                default_language,
+                forced_languages,
+            } => {
+                actions.push(Box::new(SetSubtitleFlagsAction::new(
+                    default_language.clone(),
+                    forced_languages.clone(),
+                )));
+            }
+            ActionConfig::ReorderSubtitles { languages } => {
+                if languages.is_empty() {
+                    return Err(sf_core::Error::Validation(
+                        "reorder_subtitles requires at least one language".into(),
+                    ));
+                }
+                actions.push(Box::new(ReorderSubtitlesAction::new(languages.clone())));
+            }
             ActionConfig::StripTracks {
                 track_types,
                 languages,
@@ -62,6 +168,34 @@ pub fn create_actions(
                 tools.require("ffmpeg")?;
                 actions.push(Box::new(ProfileBConvertAction::new(*crf, preset.clone())));
             }
+            ActionConfig::Transcode {
+                codec,
+                crf,
+                preset,
+                tune,
+                bit_depth,
+                require_smaller,
+            } => {
+                tools.require("ffmpeg")?;
+                if !matches!(codec, sf_core::VideoCodec::H265 | sf_core::VideoCodec::Av1) {
+                    return Err(sf_core::Error::Validation(format!(
+                        "transcode to {codec} is not supported (expected h265 or av1)"
+                    )));
+                }
+                if !matches!(bit_depth, None | Some(8) | Some(10)) {
+                    return Err(sf_core::Error::Validation(
+                        "transcode bit_depth must be 8 or 10".into(),
+                    ));
+                }
+                let options = sf_av::TranscodeOptions {
+                    codec: *codec,
+                    crf: *crf,
+                    preset: preset.clone(),
+                    tune: tune.clone(),
+                    bit_depth: bit_depth.unwrap_or(10),
+                };
+                actions.push(Box::new(TranscodeAction::new(options, *require_smaller)));
+            }
         }
     }
 
@@ -152,6 +286,53 @@ mod tests {
         }
     }
 
+    #[test]
+    fn downmix_audio_creates_action() {
+        let tools = make_tools();
+        let downmix = |target_lufs| ActionConfig::DownmixAudio {
+            source_track: None,
+            target_lufs,
+            dialogue_boost: true,
+            codec: None,
+            bitrate: None,
+            title: None,
+            language: None,
+            default_track: false,
+        };
+        if let Ok(actions) = create_actions(&[downmix(Some(-16.0))], &tools) {
+            assert_eq!(actions.len(), 1);
+            assert_eq!(actions[0].name(), "Downmix Audio");
+        }
+        // Fails either way: ffmpeg missing, or the target is rejected.
+        assert!(create_actions(&[downmix(Some(3.0))], &tools).is_err());
+    }
+
+    #[test]
+    fn track_layout_actions_are_created_without_tools() {
+        let tools = make_tools();
+        let configs = vec![
+            ActionConfig::SetTrackDefaults {
+                languages: vec!["eng".into()],
+                codecs: vec![],
+                reorder: true,
+            },
+            ActionConfig::SetSubtitleFlags {
+                default_language: Some("eng".into()),
+                forced_languages: None,
+            },
+            ActionConfig::ReorderSubtitles {
+                languages: vec!["eng".into()],
+            },
+        ];
+        let actions = create_actions(&configs, &tools).unwrap();
+        assert_eq!(actions[0].name(), "Set Track Defaults");
+        assert_eq!(actions[1].name(), "Set Subtitle Flags");
+        assert_eq!(actions[2].name(), "Reorder Subtitles");
+
+        let empty = vec![ActionConfig::ReorderSubtitles { languages: vec![] }];
+        assert!(create_actions(&empty, &tools).is_err());
+    }
+
     #[test]
     fn strip_tracks_creates_action() {
         let tools = make_tools();
@@ -165,4 +346,36 @@ mod tests {
             assert_eq!(actions[0].name(), "Strip Tracks");
         }
     }
+
+    #[test]
+    fn transcode_rejects_unsupported_codec() {
+        let tools = make_tools();
+        let configs = vec![ActionConfig::Transcode {
+            codec: sf_core::VideoCodec::Vp9,
+            crf: None,
+            preset: None,
+            tune: None,
+            bit_depth: None,
+            require_smaller: true,
+        }];
+        // Fails either way: ffmpeg missing, or the codec is rejected.
+        assert!(create_actions(&configs, &tools).is_err());
+    }
+
+    #[test]
+    fn transcode_creates_action() {
+        let tools = make_tools();
+        let configs = vec![ActionConfig::Transcode {
+            codec: sf_core::VideoCodec::H265,
+            crf: Some(20),
+            preset: None,
+            tune: None,
+            bit_depth: Some(10),
+            require_smaller: true,
+        }];
+        if let Ok(actions) = create_actions(&configs, &tools) {
+            assert_eq!(actions.len(), 1);
+            assert_eq!(actions[0].name(), "Transcode");
+        }
+    }
 }
@@ -7,12 +7,15 @@
 //! - **[`Action`]** trait -- a single pipeline step with validate / execute /
 //!   rollback semantics.
 //! - **[`ActionContext`]** -- shared execution context (workspace, media info,
-//!   tool registry, cancellation, progress).
+//!   tool registry, cancellation, progress, execution log).
 //! - **Built-in actions** ([`actions`]) -- DV convert, remux, add compat audio,
-//!   strip tracks, exec.
+//!   downmix audio, audio track defaults, strip tracks, subtitle extract /
+//!   convert / flags / reorder, exec, Profile B convert, transcode.
 //! - **[`PipelineExecutor`]** -- groups actions into stages, runs them
-//!   sequentially (with intra-stage parallelism), tracks progress, and rolls
-//!   back on failure.
+//!   sequentially (with intra-stage parallelism), tracks progress, verifies
+//!   the output, and rolls back on failure.
+//! - **[`Verification`]** -- re-probes the output and checks it against the
+//!   [`ExpectedOutput`] of the actions that ran.
 //! - **[`create_actions`]** -- factory function that builds action objects from
 //!   rule-engine [`ActionConfig`](sf_rules::ActionConfig) values.
 
@@ -21,9 +24,11 @@ pub mod actions;
 pub mod context;
 pub mod executor;
 pub mod factory;
+pub mod verify;
 
 // Re-export key types at the crate root.
 pub use action::{Action, ActionResult};
-pub use context::{ActionContext, ProgressSender};
-pub use executor::PipelineExecutor;
+pub use context::{ActionContext, LogEntry, LogSender, ProgressSender};
+pub use executor::{PipelineExecutor, PipelineReport, StepReport};
 pub use factory::create_actions;
+pub use verify::{ExpectedOutput, Verification};
@@ -0,0 +1,331 @@
+//! Verification of a pipeline's output before it replaces the original.
+//!
+//! The output is re-probed and compared with an [`ExpectedOutput`] built from
+//! the input's media info and adjusted by every action that ran (see
+//! [`Action::expect_output`](crate::Action::expect_output)): duration, track
+//! counts, track order (for actions that rewrite it), HDR format and Dolby
+//! Vision profile. Optionally the whole output
+//! is decoded to catch corrupt streams.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use sf_core::config::VerificationConfig;
+use sf_core::HdrFormat;
+use sf_probe::{MediaInfo, Prober};
+
+/// What a pipeline's output should look like.
+///
+/// `None` fields are not checked, for actions whose effect can't be predicted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpectedOutput {
+    pub duration: Option<Duration>,
+    pub video_tracks: Option<usize>,
+    pub audio_tracks: Option<usize>,
+    pub subtitle_tracks: Option<usize>,
+    /// Languages of the audio tracks, in order. Only set by actions that
+    /// rewrite the track order, since probers disagree on unlabelled tracks.
+    pub audio_languages: Option<Vec<Option<String>>>,
+    /// Languages of the subtitle tracks, in order; see `audio_languages`.
+    pub subtitle_languages: Option<Vec<Option<String>>>,
+    /// HDR format of the primary video track.
+    pub hdr_format: Option<HdrFormat>,
+    /// Dolby Vision profile of the primary video track; `Some(None)` means
+    /// the output must not carry Dolby Vision.
+    pub dolby_vision: Option<Option<u8>>,
+}
+
+impl ExpectedOutput {
+    /// An output identical in shape to `info`.
+    pub fn from_media_info(info: &MediaInfo) -> Self {
+        let video = info.primary_video();
+        Self {
+            duration: info.duration,
+            video_tracks: Some(info.video_tracks.len()),
+            audio_tracks: Some(info.audio_tracks.len()),
+            subtitle_tracks: Some(info.subtitle_tracks.len()),
+            audio_languages: None,
+            subtitle_languages: None,
+            hdr_format: video.map(|v| v.hdr_format),
+            dolby_vision: video.map(|v| v.dolby_vision.as_ref().map(|dv| dv.profile)),
+        }
+    }
+
+    /// Nothing about the output can be predicted.
+    pub fn unknown(&mut self) {
+        *self = Self {
+            duration: None,
+            video_tracks: None,
+            audio_tracks: None,
+            subtitle_tracks: None,
+            audio_languages: None,
+            subtitle_languages: None,
+            hdr_format: None,
+            dolby_vision: None,
+        };
+    }
+
+    /// Expect the audio and subtitle tracks of `info` to come out in the same
+    /// order, unless an earlier action already set the order.
+    pub fn keep_track_order(&mut self, info: &MediaInfo) {
+        self.audio_languages
+            .get_or_insert_with(|| audio_languages(info, 0..info.audio_tracks.len()));
+        self.subtitle_languages
+            .get_or_insert_with(|| subtitle_languages(info, 0..info.subtitle_tracks.len()));
+    }
+
+    /// Describe every way `actual` differs from the expectation.
+    /// `tolerance` is the allowed duration difference.
+    pub fn mismatches(&self, actual: &MediaInfo, tolerance: Duration) -> Vec<String> {
+        let mut mismatches = Vec::new();
+
+        if let (Some(expected), Some(actual)) = (self.duration, actual.duration) {
+            if expected.abs_diff(actual) > tolerance {
+                mismatches.push(format!(
+                    "duration is {:.1}s, expected {:.1}s",
+                    actual.as_secs_f64(),
+                    expected.as_secs_f64()
+                ));
+            }
+        }
+
+        let counts = [
+            ("video", self.video_tracks, actual.video_tracks.len()),
+            ("audio", self.audio_tracks, actual.audio_tracks.len()),
+            (
+                "subtitle",
+                self.subtitle_tracks,
+                actual.subtitle_tracks.len(),
+            ),
+        ];
+        for (kind, expected, actual) in counts {
+            if expected.is_some_and(|n| n != actual) {
+                mismatches.push(format!(
+                    "{actual} {kind} track(s), expected {}",
+                    expected.unwrap_or_default()
+                ));
+            }
+        }
+
+        let languages = [
+            (
+                "audio",
+                &self.audio_languages,
+                audio_languages(actual, 0..actual.audio_tracks.len()),
+            ),
+            (
+                "subtitle",
+                &self.subtitle_languages,
+                subtitle_languages(actual, 0..actual.subtitle_tracks.len()),
+            ),
+        ];
+        for (kind, expected, actual) in languages {
+            let Some(expected) = expected else { continue };
+            let differs = |(e, a): (&Option<String>, &Option<String>)| match (e, a) {
+                (Some(e), Some(a)) => !e.eq_ignore_ascii_case(a),
+                _ => false,
+            };
+            if expected.len() == actual.len() && expected.iter().zip(&actual).any(differs) {
+                mismatches.push(format!(
+                    "{kind} track languages are {actual:?}, expected {expected:?}"
+                ));
+            }
+        }
+
+        let video = actual.primary_video();
+        if let Some(expected) = self.hdr_format {
+            let actual = video.map(|v| v.hdr_format);
+            if actual != Some(expected) {
+                mismatches.push(match actual {
+                    Some(actual) => format!("HDR format is {actual}, expected {expected}"),
+                    None => format!("no video track, expected {expected}"),
+                });
+            }
+        }
+        if let Some(expected) = self.dolby_vision {
+            let actual = video
+                .and_then(|v| v.dolby_vision.as_ref())
+                .map(|dv| dv.profile);
+            if actual != expected {
+                mismatches.push(match (actual, expected) {
+                    (Some(actual), Some(expected)) => {
+                        format!("Dolby Vision profile {actual}, expected profile {expected}")
+                    }
+                    (Some(actual), None) => {
+                        format!("unexpected Dolby Vision profile {actual}")
+                    }
+                    (None, _) => "Dolby Vision metadata is missing".to_string(),
+                });
+            }
+        }
+
+        mismatches
+    }
+}
+
+/// Languages of the audio tracks at `indices`, with "und" as unknown.
+pub(crate) fn audio_languages(
+    info: &MediaInfo,
+    indices: impl IntoIterator<Item = usize>,
+) -> Vec<Option<String>> {
+    indices
+        .into_iter()
+        .map(|i| known_language(info.audio_tracks[i].language.as_deref()))
+        .collect()
+}
+
+/// Languages of the subtitle tracks at `indices`, with "und" as unknown.
+pub(crate) fn subtitle_languages(
+    info: &MediaInfo,
+    indices: impl IntoIterator<Item = usize>,
+) -> Vec<Option<String>> {
+    indices
+        .into_iter()
+        .map(|i| known_language(info.subtitle_tracks[i].language.as_deref()))
+        .collect()
+}
+
+fn known_language(language: Option<&str>) -> Option<String> {
+    language
+        .filter(|l| !l.is_empty() && !l.eq_ignore_ascii_case("und"))
+        .map(str::to_string)
+}
+
+/// Output verification settings for
+/// [`PipelineExecutor::with_verification`](crate::PipelineExecutor::with_verification).
+#[derive(Clone)]
+pub struct Verification {
+    /// Prober used to re-probe the output.
+    pub prober: Arc<dyn Prober>,
+    pub config: VerificationConfig,
+}
+
+impl std::fmt::Debug for Verification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Verification")
+            .field("prober", &self.prober.name())
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl Verification {
+    /// Check `output` against `expected`, returning a description of every
+    /// problem found.
+    pub(crate) async fn check(
+        &self,
+        tools: &sf_av::ToolRegistry,
+        output: &Path,
+        expected: &ExpectedOutput,
+    ) -> sf_core::Result<Vec<String>> {
+        match std::fs::metadata(output) {
+            Ok(meta) if meta.len() > 0 => {}
+            Ok(_) => return Ok(vec!["output file is empty".to_string()]),
+            Err(e) => return Ok(vec![format!("output file is missing: {e}")]),
+        }
+
+        let prober = self.prober.clone();
+        let path = output.to_path_buf();
+        let probed = tokio::task::spawn_blocking(move || prober.probe(&path))
+            .await
+            .map_err(|e| sf_core::Error::Internal(format!("probe task failed: {e}")))?;
+        let info = match probed {
+            Ok(info) => info,
+            Err(e) => return Ok(vec![format!("output could not be probed: {e}")]),
+        };
+
+        let tolerance = Duration::from_secs_f64(self.config.duration_tolerance_secs.max(0.0));
+        let mut mismatches = expected.mismatches(&info, tolerance);
+
+        if self.config.decode_check && mismatches.is_empty() {
+            if let Err(e) = sf_av::decode_check(tools, output).await {
+                mismatches.push(e.to_string());
+            }
+        }
+        Ok(mismatches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sf_core::{AudioCodec, Container, VideoCodec};
+    use sf_probe::{AudioTrack, DvInfo, SubtitleTrack, VideoTrack};
+    use std::path::PathBuf;
+
+    fn info(secs: u64, audio: usize, dv_profile: Option<u8>) -> MediaInfo {
+        MediaInfo {
+            file_path: PathBuf::from("/test.mkv"),
+            file_size: 1000,
+            container: Container::Mkv,
+            duration: Some(Duration::from_secs(secs)),
+            video_tracks: vec![VideoTrack {
+                codec: VideoCodec::H265,
+                width: 3840,
+                height: 2160,
+                frame_rate: Some(23.976),
+                bit_depth: Some(10),
+                hdr_format: HdrFormat::Hdr10,
+                hdr10plus: false,
+                dolby_vision: dv_profile.map(|profile| DvInfo {
+                    profile,
+                    rpu_present: true,
+                    el_present: false,
+                    bl_present: true,
+                    el_type: None,
+                }),
+                default: true,
+                language: None,
+            }],
+            audio_tracks: (0..audio)
+                .map(|_| AudioTrack {
+                    codec: AudioCodec::Aac,
+                    channels: 2,
+                    sample_rate: Some(48000),
+                    language: None,
+                    atmos: false,
+                    default: false,
+                })
+                .collect(),
+            subtitle_tracks: vec![SubtitleTrack {
+                codec: "SRT".into(),
+                language: None,
+                forced: false,
+                default: false,
+            }],
+        }
+    }
+
+    #[test]
+    fn identical_output_passes() {
+        let source = info(7200, 2, Some(8));
+        let expected = ExpectedOutput::from_media_info(&source);
+        assert!(expected
+            .mismatches(&info(7201, 2, Some(8)), Duration::from_secs(2))
+            .is_empty());
+    }
+
+    #[test]
+    fn truncated_output_and_lost_tracks_fail() {
+        let expected = ExpectedOutput::from_media_info(&info(7200, 2, Some(8)));
+        let mismatches = expected.mismatches(&info(3600, 1, None), Duration::from_secs(2));
+        assert_eq!(
+            mismatches,
+            vec![
+                "duration is 3600.0s, expected 7200.0s".to_string(),
+                "1 audio track(s), expected 2".to_string(),
+                "Dolby Vision metadata is missing".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_fields_are_not_checked() {
+        let mut expected = ExpectedOutput::from_media_info(&info(7200, 2, Some(8)));
+        expected.unknown();
+        assert!(expected
+            .mismatches(&info(10, 0, None), Duration::ZERO)
+            .is_empty());
+    }
+}
@@ -73,7 +73,8 @@ mod tests {
         let composite = CompositeProber::new(vec![Box::new(RustProber::new())]);
         assert!(composite.supports(Path::new("movie.mkv")));
         assert!(composite.supports(Path::new("movie.mp4")));
-        assert!(!composite.supports(Path::new("movie.avi")));
+        assert!(composite.supports(Path::new("movie.ts")));
+        assert!(!composite.supports(Path::new("movie.wmv")));
     }
 
     #[test]
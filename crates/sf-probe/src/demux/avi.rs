@@ -0,0 +1,412 @@
+//! AVI (RIFF) probing.
+//!
+//! Reads the `hdrl` header list only: the main AVI header, each stream's
+//! `strh`/`strf` pair, and the OpenDML `dmlh` extended frame count. The
+//! `movi` data list is never touched.
+
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::Duration;
+
+use sf_core::{AudioCodec, Container, HdrFormat, VideoCodec};
+
+use crate::types::{AudioTrack, MediaInfo, VideoTrack};
+
+/// Upper bound on the size of the `hdrl` list we are willing to buffer.
+const MAX_HDRL_BYTES: u32 = 1024 * 1024;
+
+/// Probe an AVI file.
+pub(crate) fn probe_avi(path: &Path) -> sf_core::Result<MediaInfo> {
+    let file = File::open(path).map_err(|e| sf_core::Error::Probe(e.to_string()))?;
+    let file_size = file
+        .metadata()
+        .map_err(|e| sf_core::Error::Probe(e.to_string()))?
+        .len();
+    let mut reader = BufReader::new(file);
+
+    let mut riff = [0u8; 12];
+    reader
+        .read_exact(&mut riff)
+        .map_err(|e| sf_core::Error::Probe(e.to_string()))?;
+    if &riff[0..4] != b"RIFF" || &riff[8..12] != b"AVI " {
+        return Err(sf_core::Error::Probe("not an AVI RIFF file".to_string()));
+    }
+
+    let hdrl = read_hdrl(&mut reader)
+        .map_err(|e| sf_core::Error::Probe(format!("AVI header read error: {e}")))?
+        .ok_or_else(|| sf_core::Error::Probe("AVI: missing hdrl list".to_string()))?;
+
+    Ok(parse_hdrl(&hdrl).into_media_info(path, file_size))
+}
+
+/// Walk top-level RIFF chunks until the `hdrl` list is found and return its body.
+fn read_hdrl<R: Read + Seek>(reader: &mut R) -> std::io::Result<Option<Vec<u8>>> {
+    loop {
+        let mut header = [0u8; 8];
+        if reader.read_exact(&mut header).is_err() {
+            return Ok(None);
+        }
+        let size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+
+        if &header[0..4] == b"LIST" {
+            let mut list_type = [0u8; 4];
+            reader.read_exact(&mut list_type)?;
+            if &list_type == b"hdrl" {
+                if size > MAX_HDRL_BYTES {
+                    return Ok(None);
+                }
+                let mut body = vec![0u8; size.saturating_sub(4) as usize];
+                reader.read_exact(&mut body)?;
+                return Ok(Some(body));
+            }
+            if &list_type == b"movi" {
+                return Ok(None);
+            }
+            reader.seek(SeekFrom::Current(i64::from(size) - 4 + i64::from(size & 1)))?;
+        } else {
+            reader.seek(SeekFrom::Current(i64::from(size) + i64::from(size & 1)))?;
+        }
+    }
+}
+
+/// Iterate over `(fourcc, body)` sub-chunks of a RIFF list body.
+fn chunks(mut data: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    std::iter::from_fn(move || {
+        if data.len() < 8 {
+            return None;
+        }
+        let id = &data[0..4];
+        let size = u32::from_le_bytes([data[4], data[5], data[6], data[7]]) as usize;
+        let body = data.get(8..8 + size).unwrap_or(&data[8..]);
+        let advance = (8 + size + (size & 1)).min(data.len());
+        data = &data[advance..];
+        Some((id, body))
+    })
+}
+
+fn le_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn le_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+// ---------------------------------------------------------------------------
+// Header parsing
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Default)]
+struct AviHeader {
+    micro_sec_per_frame: u32,
+    total_frames: u32,
+    /// OpenDML total frame count across all RIFF segments.
+    odml_total_frames: Option<u32>,
+    streams: Vec<AviStream>,
+}
+
+#[derive(Debug)]
+enum AviStream {
+    Video {
+        fourcc: [u8; 4],
+        width: u32,
+        height: u32,
+        scale: u32,
+        rate: u32,
+        length: u32,
+    },
+    Audio {
+        format_tag: u16,
+        channels: u16,
+        sample_rate: u32,
+    },
+}
+
+fn parse_hdrl(hdrl: &[u8]) -> AviHeader {
+    let mut header = AviHeader::default();
+
+    for (id, body) in chunks(hdrl) {
+        match id {
+            b"avih" => {
+                header.micro_sec_per_frame = le_u32(body, 0).unwrap_or(0);
+                header.total_frames = le_u32(body, 16).unwrap_or(0);
+            }
+            b"LIST" if body.len() >= 4 => match &body[0..4] {
+                b"strl" => {
+                    if let Some(stream) = parse_strl(&body[4..]) {
+                        header.streams.push(stream);
+                    }
+                }
+                b"odml" => {
+                    header.odml_total_frames = chunks(&body[4..])
+                        .find(|(id, _)| id == b"dmlh")
+                        .and_then(|(_, dmlh)| le_u32(dmlh, 0));
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    header
+}
+
+fn parse_strl(strl: &[u8]) -> Option<AviStream> {
+    let mut strh: Option<&[u8]> = None;
+    let mut strf: Option<&[u8]> = None;
+    for (id, body) in chunks(strl) {
+        match id {
+            b"strh" => strh = Some(body),
+            b"strf" => strf = Some(body),
+            _ => {}
+        }
+    }
+    let (strh, strf) = (strh?, strf?);
+
+    match strh.get(0..4)? {
+        b"vids" => {
+            // BITMAPINFOHEADER: biWidth @4, biHeight @8 (negative for top-down),
+            // biCompression @16.
+            let fourcc: [u8; 4] = strf.get(16..20)?.try_into().ok()?;
+            Some(AviStream::Video {
+                fourcc,
+                width: le_u32(strf, 4)?,
+                height: (le_u32(strf, 8)? as i32).unsigned_abs(),
+                scale: le_u32(strh, 20)?,
+                rate: le_u32(strh, 24)?,
+                length: le_u32(strh, 32)?,
+            })
+        }
+        b"auds" => {
+            // WAVEFORMATEX: wFormatTag @0, nChannels @2, nSamplesPerSec @4.
+            // WAVE_FORMAT_EXTENSIBLE keeps the real tag at the start of the
+            // SubFormat GUID.
+            let mut format_tag = le_u16(strf, 0)?;
+            if format_tag == 0xFFFE {
+                format_tag = le_u16(strf, 24).unwrap_or(format_tag);
+            }
+            Some(AviStream::Audio {
+                format_tag,
+                channels: le_u16(strf, 2)?,
+                sample_rate: le_u32(strf, 4)?,
+            })
+        }
+        _ => None,
+    }
+}
+
+impl AviHeader {
+    fn duration(&self) -> Option<Duration> {
+        // Prefer the video stream header, which is exact for VFR-free AVI.
+        let from_stream = self.streams.iter().find_map(|s| match s {
+            AviStream::Video {
+                scale,
+                rate,
+                length,
+                ..
+            } if *rate > 0 && *length > 0 => Some(Duration::from_secs_f64(
+                *length as f64 * *scale as f64 / *rate as f64,
+            )),
+            _ => None,
+        });
+        from_stream.or_else(|| {
+            let frames = self.odml_total_frames.unwrap_or(self.total_frames);
+            (frames > 0 && self.micro_sec_per_frame > 0)
+                .then(|| Duration::from_micros(frames as u64 * self.micro_sec_per_frame as u64))
+        })
+    }
+
+    fn into_media_info(self, path: &Path, file_size: u64) -> MediaInfo {
+        let duration = self.duration();
+        let mut video_tracks = Vec::new();
+        let mut audio_tracks = Vec::new();
+
+        for stream in &self.streams {
+            match *stream {
+                AviStream::Video {
+                    fourcc,
+                    width,
+                    height,
+                    scale,
+                    rate,
+                    ..
+                } => {
+                    let Some(codec) = avi_video_codec(&fourcc) else {
+                        tracing::debug!(
+                            fourcc = %String::from_utf8_lossy(&fourcc),
+                            "skipping unsupported AVI video codec"
+                        );
+                        continue;
+                    };
+                    video_tracks.push(VideoTrack {
+                        codec,
+                        width,
+                        height,
+                        frame_rate: (scale > 0 && rate > 0).then(|| rate as f64 / scale as f64),
+                        bit_depth: None,
+                        hdr_format: HdrFormat::Sdr,
+                        hdr10plus: false,
+                        dolby_vision: None,
+                        default: video_tracks.is_empty(),
+                        language: None,
+                    });
+                }
+                AviStream::Audio {
+                    format_tag,
+                    channels,
+                    sample_rate,
+                } => {
+                    let Some(codec) = avi_audio_codec(format_tag) else {
+                        tracing::debug!(format_tag, "skipping unsupported AVI audio codec");
+                        continue;
+                    };
+                    audio_tracks.push(AudioTrack {
+                        codec,
+                        channels: channels as u32,
+                        sample_rate: Some(sample_rate),
+                        language: None,
+                        atmos: false,
+                        default: audio_tracks.is_empty(),
+                    });
+                }
+            }
+        }
+
+        MediaInfo {
+            file_path: path.to_path_buf(),
+            file_size,
+            container: Container::Avi,
+            duration,
+            video_tracks,
+            audio_tracks,
+            subtitle_tracks: Vec::new(),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Codec mapping
+// ---------------------------------------------------------------------------
+
+fn avi_video_codec(fourcc: &[u8; 4]) -> Option<VideoCodec> {
+    match fourcc.to_ascii_uppercase().as_slice() {
+        b"H264" | b"X264" | b"AVC1" | b"DAVC" => Some(VideoCodec::H264),
+        b"HEVC" | b"H265" | b"X265" | b"HEV1" | b"HVC1" => Some(VideoCodec::H265),
+        b"AV01" => Some(VideoCodec::Av1),
+        b"VP90" => Some(VideoCodec::Vp9),
+        _ => None,
+    }
+}
+
+fn avi_audio_codec(format_tag: u16) -> Option<AudioCodec> {
+    match format_tag {
+        0x00FF | 0x1600 | 0x1601 | 0x706D => Some(AudioCodec::Aac),
+        0x2000 => Some(AudioCodec::Ac3),
+        0x2001 => Some(AudioCodec::Dts),
+        0xF1AC => Some(AudioCodec::Flac),
+        0x704F => Some(AudioCodec::Opus),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn chunk(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut out = id.to_vec();
+        out.extend((body.len() as u32).to_le_bytes());
+        out.extend_from_slice(body);
+        if body.len() % 2 == 1 {
+            out.push(0);
+        }
+        out
+    }
+
+    fn list(kind: &[u8; 4], children: &[Vec<u8>]) -> Vec<u8> {
+        let mut body = kind.to_vec();
+        for c in children {
+            body.extend_from_slice(c);
+        }
+        chunk(b"LIST", &body)
+    }
+
+    fn sample_avi() -> Vec<u8> {
+        let mut avih = vec![0u8; 56];
+        avih[0..4].copy_from_slice(&41_708u32.to_le_bytes());
+        avih[16..20].copy_from_slice(&240u32.to_le_bytes());
+
+        let mut vids_strh = vec![0u8; 56];
+        vids_strh[0..4].copy_from_slice(b"vids");
+        vids_strh[4..8].copy_from_slice(b"H264");
+        vids_strh[20..24].copy_from_slice(&1001u32.to_le_bytes());
+        vids_strh[24..28].copy_from_slice(&24_000u32.to_le_bytes());
+        vids_strh[32..36].copy_from_slice(&240u32.to_le_bytes());
+        let mut bih = vec![0u8; 40];
+        bih[4..8].copy_from_slice(&1280u32.to_le_bytes());
+        bih[8..12].copy_from_slice(&(-720i32).to_le_bytes());
+        bih[16..20].copy_from_slice(b"h264");
+
+        let mut auds_strh = vec![0u8; 56];
+        auds_strh[0..4].copy_from_slice(b"auds");
+        let mut wfx = vec![0u8; 18];
+        wfx[0..2].copy_from_slice(&0x2000u16.to_le_bytes());
+        wfx[2..4].copy_from_slice(&6u16.to_le_bytes());
+        wfx[4..8].copy_from_slice(&48_000u32.to_le_bytes());
+
+        let mut mp3_strh = vec![0u8; 56];
+        mp3_strh[0..4].copy_from_slice(b"auds");
+        let mut mp3 = vec![0u8; 18];
+        mp3[0..2].copy_from_slice(&0x0055u16.to_le_bytes());
+
+        let hdrl = list(
+            b"hdrl",
+            &[
+                chunk(b"avih", &avih),
+                list(b"strl", &[chunk(b"strh", &vids_strh), chunk(b"strf", &bih)]),
+                list(b"strl", &[chunk(b"strh", &auds_strh), chunk(b"strf", &wfx)]),
+                list(b"strl", &[chunk(b"strh", &mp3_strh), chunk(b"strf", &mp3)]),
+            ],
+        );
+        let movi = list(b"movi", &[chunk(b"00dc", &[0u8; 16])]);
+
+        let mut body = b"AVI ".to_vec();
+        body.extend(hdrl);
+        body.extend(movi);
+        chunk(b"RIFF", &body)
+    }
+
+    #[test]
+    fn probes_synthetic_avi() {
+        let mut file = tempfile::Builder::new().suffix(".avi").tempfile().unwrap();
+        file.write_all(&sample_avi()).unwrap();
+
+        let info = probe_avi(file.path()).unwrap();
+        assert_eq!(info.container, Container::Avi);
+
+        assert_eq!(info.video_tracks.len(), 1);
+        let video = &info.video_tracks[0];
+        assert_eq!(video.codec, VideoCodec::H264);
+        assert_eq!((video.width, video.height), (1280, 720));
+        assert!((video.frame_rate.unwrap() - 23.976).abs() < 0.001);
+
+        // The MP3 stream is not representable and is skipped.
+        assert_eq!(info.audio_tracks.len(), 1);
+        assert_eq!(info.audio_tracks[0].codec, AudioCodec::Ac3);
+        assert_eq!(info.audio_tracks[0].channels, 6);
+
+        let secs = info.duration.unwrap().as_secs_f64();
+        assert!((secs - 10.01).abs() < 0.001);
+    }
+
+    #[test]
+    fn rejects_non_avi_riff() {
+        let mut file = tempfile::Builder::new().suffix(".avi").tempfile().unwrap();
+        file.write_all(&chunk(b"RIFF", b"WAVEfmt ")).unwrap();
+        assert!(probe_avi(file.path()).is_err());
+    }
+}
@@ -0,0 +1,652 @@
+//! Elementary stream header parsing.
+//!
+//! Containers such as MPEG-TS carry no track-level metadata for dimensions,
+//! bit depth or channel layout, so these values are recovered from the first
+//! bytes of each elementary stream: the H.264/HEVC sequence parameter set for
+//! video, and the frame header of ADTS AAC, AC-3, E-AC-3 and DTS for audio.
+
+use std::io;
+
+use bitstream_io::{BigEndian, BitRead, BitReader};
+
+/// Video properties recovered from a sequence parameter set.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct SpsInfo {
+    pub width: u32,
+    pub height: u32,
+    pub bit_depth: Option<u8>,
+    /// VUI colour primaries, if signalled.
+    pub colour_primaries: Option<u8>,
+    /// VUI transfer characteristics (16 = PQ, 18 = HLG), if signalled.
+    pub transfer_characteristics: Option<u8>,
+    pub frame_rate: Option<f64>,
+}
+
+/// Audio properties recovered from a frame header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct AudioFrameInfo {
+    pub channels: u32,
+    pub sample_rate: Option<u32>,
+}
+
+type Reader<'a> = BitReader<&'a [u8], BigEndian>;
+
+// ---------------------------------------------------------------------------
+// Bitstream helpers
+// ---------------------------------------------------------------------------
+
+/// Remove emulation prevention bytes (`00 00 03`) from a NAL unit payload.
+pub(crate) fn nal_to_rbsp(nal: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(nal.len());
+    let mut zeros = 0;
+    for &b in nal {
+        if zeros >= 2 && b == 0x03 {
+            zeros = 0;
+            continue;
+        }
+        zeros = if b == 0 { zeros + 1 } else { 0 };
+        out.push(b);
+    }
+    out
+}
+
+/// Read an unsigned Exp-Golomb code.
+fn read_ue(r: &mut Reader<'_>) -> io::Result<u32> {
+    let mut leading_zeros = 0u32;
+    while !r.read_bit()? {
+        leading_zeros += 1;
+        if leading_zeros > 31 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "exp-golomb code too long",
+            ));
+        }
+    }
+    if leading_zeros == 0 {
+        return Ok(0);
+    }
+    let suffix: u32 = r.read(leading_zeros)?;
+    Ok((1u32 << leading_zeros) - 1 + suffix)
+}
+
+/// Read a signed Exp-Golomb code.
+fn read_se(r: &mut Reader<'_>) -> io::Result<i32> {
+    let k = read_ue(r)? as i64;
+    Ok(if k % 2 == 1 {
+        ((k + 1) / 2) as i32
+    } else {
+        -((k / 2) as i32)
+    })
+}
+
+/// Chroma subsampling divisors `(SubWidthC, SubHeightC)` for a chroma format.
+fn chroma_subsampling(chroma_format_idc: u32) -> (u32, u32) {
+    match chroma_format_idc {
+        1 => (2, 2),
+        2 => (2, 1),
+        _ => (1, 1),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// H.264 SPS
+// ---------------------------------------------------------------------------
+
+/// Parse an H.264 SPS NAL unit (including its 1-byte NAL header).
+///
+/// Returns `None` if the fixed part of the SPS (up to the picture size)
+/// cannot be read. VUI parsing failures only drop the VUI-derived fields.
+pub(crate) fn parse_avc_sps(nal: &[u8]) -> Option<SpsInfo> {
+    if nal.len() < 4 || nal[0] & 0x1F != 7 {
+        return None;
+    }
+    let rbsp = nal_to_rbsp(&nal[1..]);
+    let mut r = BitReader::endian(rbsp.as_slice(), BigEndian);
+    let mut info = parse_avc_sps_body(&mut r).ok()?;
+    let _ = parse_avc_vui(&mut r, &mut info);
+    Some(info)
+}
+
+fn parse_avc_sps_body(r: &mut Reader<'_>) -> io::Result<SpsInfo> {
+    let profile_idc: u8 = r.read(8)?;
+    r.skip(16)?; // constraint flags + level_idc
+    read_ue(r)?; // seq_parameter_set_id
+
+    let mut chroma_format_idc = 1;
+    let mut bit_depth = 8u8;
+    if matches!(
+        profile_idc,
+        100 | 110 | 122 | 244 | 44 | 83 | 86 | 118 | 128 | 138 | 139 | 134 | 135
+    ) {
+        chroma_format_idc = read_ue(r)?;
+        if chroma_format_idc == 3 {
+            r.skip(1)?; // separate_colour_plane_flag
+        }
+        bit_depth = 8 + read_ue(r)? as u8;
+        read_ue(r)?; // bit_depth_chroma_minus8
+        r.skip(1)?; // qpprime_y_zero_transform_bypass_flag
+        if r.read_bit()? {
+            let lists = if chroma_format_idc == 3 { 12 } else { 8 };
+            for i in 0..lists {
+                if r.read_bit()? {
+                    skip_avc_scaling_list(r, if i < 6 { 16 } else { 64 })?;
+                }
+            }
+        }
+    }
+
+    read_ue(r)?; // log2_max_frame_num_minus4
+    match read_ue(r)? {
+        0 => {
+            read_ue(r)?; // log2_max_pic_order_cnt_lsb_minus4
+        }
+        1 => {
+            r.skip(1)?; // delta_pic_order_always_zero_flag
+            read_se(r)?; // offset_for_non_ref_pic
+            read_se(r)?; // offset_for_top_to_bottom_field
+            let cycle = read_ue(r)?;
+            for _ in 0..cycle {
+                read_se(r)?;
+            }
+        }
+        _ => {}
+    }
+    read_ue(r)?; // max_num_ref_frames
+    r.skip(1)?; // gaps_in_frame_num_value_allowed_flag
+
+    let width_mbs = read_ue(r)? + 1;
+    let height_map_units = read_ue(r)? + 1;
+    let frame_mbs_only = r.read_bit()?;
+    if !frame_mbs_only {
+        r.skip(1)?; // mb_adaptive_frame_field_flag
+    }
+    r.skip(1)?; // direct_8x8_inference_flag
+
+    let field_factor = if frame_mbs_only { 1 } else { 2 };
+    let mut width = width_mbs * 16;
+    let mut height = height_map_units * 16 * field_factor;
+
+    if r.read_bit()? {
+        let (left, right, top, bottom) = (read_ue(r)?, read_ue(r)?, read_ue(r)?, read_ue(r)?);
+        let (crop_x, crop_y) = if chroma_format_idc == 0 {
+            (1, field_factor)
+        } else {
+            let (sw, sh) = chroma_subsampling(chroma_format_idc);
+            (sw, sh * field_factor)
+        };
+        width = width.saturating_sub((left + right) * crop_x);
+        height = height.saturating_sub((top + bottom) * crop_y);
+    }
+
+    Ok(SpsInfo {
+        width,
+        height,
+        bit_depth: Some(bit_depth),
+        ..Default::default()
+    })
+}
+
+fn skip_avc_scaling_list(r: &mut Reader<'_>, size: usize) -> io::Result<()> {
+    let mut last = 8i32;
+    let mut next = 8i32;
+    for _ in 0..size {
+        if next != 0 {
+            let delta = read_se(r)?;
+            next = (last + delta + 256) % 256;
+        }
+        if next != 0 {
+            last = next;
+        }
+    }
+    Ok(())
+}
+
+fn parse_avc_vui(r: &mut Reader<'_>, info: &mut SpsInfo) -> io::Result<()> {
+    if !r.read_bit()? {
+        return Ok(());
+    }
+    parse_vui_colour(r, info)?;
+    // chroma_loc_info_present_flag
+    if r.read_bit()? {
+        read_ue(r)?;
+        read_ue(r)?;
+    }
+    // timing_info_present_flag
+    if r.read_bit()? {
+        let num_units_in_tick: u32 = r.read(32)?;
+        let time_scale: u32 = r.read(32)?;
+        if num_units_in_tick > 0 && time_scale > 0 {
+            // H.264 ticks count fields, so two ticks make one frame.
+            info.frame_rate = Some(time_scale as f64 / (2.0 * num_units_in_tick as f64));
+        }
+    }
+    Ok(())
+}
+
+/// Parse the VUI fields shared by H.264 and HEVC up to and including the
+/// colour description.
+fn parse_vui_colour(r: &mut Reader<'_>, info: &mut SpsInfo) -> io::Result<()> {
+    // aspect_ratio_info_present_flag
+    if r.read_bit()? {
+        let idc: u8 = r.read(8)?;
+        if idc == 255 {
+            r.skip(32)?; // sar_width + sar_height
+        }
+    }
+    // overscan_info_present_flag
+    if r.read_bit()? {
+        r.skip(1)?;
+    }
+    // video_signal_type_present_flag
+    if r.read_bit()? {
+        r.skip(4)?; // video_format + video_full_range_flag
+        if r.read_bit()? {
+            info.colour_primaries = Some(r.read(8)?);
+            info.transfer_characteristics = Some(r.read(8)?);
+            r.skip(8)?; // matrix_coeffs
+        }
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// HEVC SPS
+// ---------------------------------------------------------------------------
+
+/// Parse an HEVC SPS NAL unit (including its 2-byte NAL header).
+///
+/// Returns `None` if the fixed part of the SPS (up to the bit depth)
+/// cannot be read. Failures further in only drop the VUI-derived fields.
+pub(crate) fn parse_hevc_sps(nal: &[u8]) -> Option<SpsInfo> {
+    if nal.len() < 4 || (nal[0] >> 1) & 0x3F != 33 {
+        return None;
+    }
+    let rbsp = nal_to_rbsp(&nal[2..]);
+    let mut r = BitReader::endian(rbsp.as_slice(), BigEndian);
+
+    let (mut info, max_sub_layers_minus1) = parse_hevc_sps_head(&mut r).ok()?;
+    let _ = parse_hevc_sps_tail(&mut r, max_sub_layers_minus1, &mut info);
+    Some(info)
+}
+
+fn parse_hevc_sps_head(r: &mut Reader<'_>) -> io::Result<(SpsInfo, u8)> {
+    r.skip(4)?; // sps_video_parameter_set_id
+    let max_sub_layers_minus1: u8 = r.read(3)?;
+    r.skip(1)?; // sps_temporal_id_nesting_flag
+    skip_profile_tier_level(r, max_sub_layers_minus1)?;
+
+    read_ue(r)?; // sps_seq_parameter_set_id
+    let chroma_format_idc = read_ue(r)?;
+    if chroma_format_idc == 3 {
+        r.skip(1)?; // separate_colour_plane_flag
+    }
+    let mut width = read_ue(r)?;
+    let mut height = read_ue(r)?;
+    if r.read_bit()? {
+        let (left, right, top, bottom) = (read_ue(r)?, read_ue(r)?, read_ue(r)?, read_ue(r)?);
+        let (sw, sh) = chroma_subsampling(chroma_format_idc);
+        width = width.saturating_sub((left + right) * sw);
+        height = height.saturating_sub((top + bottom) * sh);
+    }
+    let bit_depth = 8 + read_ue(r)? as u8;
+
+    Ok((
+        SpsInfo {
+            width,
+            height,
+            bit_depth: Some(bit_depth),
+            ..Default::default()
+        },
+        max_sub_layers_minus1,
+    ))
+}
+
+fn skip_profile_tier_level(r: &mut Reader<'_>, max_sub_layers_minus1: u8) -> io::Result<()> {
+    // general profile/tier/flags (88 bits) + general_level_idc (8 bits).
+    r.skip(96)?;
+
+    let mut profile_present = [false; 8];
+    let mut level_present = [false; 8];
+    for i in 0..max_sub_layers_minus1 as usize {
+        profile_present[i] = r.read_bit()?;
+        level_present[i] = r.read_bit()?;
+    }
+    if max_sub_layers_minus1 > 0 {
+        for _ in max_sub_layers_minus1..8 {
+            r.skip(2)?; // reserved_zero_2bits
+        }
+    }
+    for i in 0..max_sub_layers_minus1 as usize {
+        if profile_present[i] {
+            r.skip(88)?;
+        }
+        if level_present[i] {
+            r.skip(8)?;
+        }
+    }
+    Ok(())
+}
+
+fn parse_hevc_sps_tail(
+    r: &mut Reader<'_>,
+    max_sub_layers_minus1: u8,
+    info: &mut SpsInfo,
+) -> io::Result<()> {
+    read_ue(r)?; // bit_depth_chroma_minus8
+    let log2_max_poc_lsb = read_ue(r)? + 4;
+
+    let ordering_info_present = r.read_bit()?;
+    let first = if ordering_info_present {
+        0
+    } else {
+        max_sub_layers_minus1
+    };
+    for _ in first..=max_sub_layers_minus1 {
+        read_ue(r)?; // sps_max_dec_pic_buffering_minus1
+        read_ue(r)?; // sps_max_num_reorder_pics
+        read_ue(r)?; // sps_max_latency_increase_plus1
+    }
+
+    for _ in 0..6 {
+        // log2 block sizes and transform hierarchy depths.
+        read_ue(r)?;
+    }
+
+    // scaling_list_enabled_flag
+    if r.read_bit()? && r.read_bit()? {
+        skip_hevc_scaling_list_data(r)?;
+    }
+
+    r.skip(2)?; // amp_enabled_flag + sample_adaptive_offset_enabled_flag
+                // pcm_enabled_flag
+    if r.read_bit()? {
+        r.skip(8)?; // pcm sample bit depths
+        read_ue(r)?;
+        read_ue(r)?;
+        r.skip(1)?; // pcm_loop_filter_disabled_flag
+    }
+
+    let num_short_term_ref_pic_sets = read_ue(r)?;
+    let mut num_delta_pocs: Vec<u32> = Vec::with_capacity(num_short_term_ref_pic_sets as usize);
+    for idx in 0..num_short_term_ref_pic_sets as usize {
+        let inter_rps_pred = idx != 0 && r.read_bit()?;
+        if inter_rps_pred {
+            r.skip(1)?; // delta_rps_sign
+            read_ue(r)?; // abs_delta_rps_minus1
+            let ref_pocs = num_delta_pocs[idx - 1];
+            let mut count = 0;
+            for _ in 0..=ref_pocs {
+                let used_by_curr_pic = r.read_bit()?;
+                let use_delta = used_by_curr_pic || r.read_bit()?;
+                if use_delta {
+                    count += 1;
+                }
+            }
+            num_delta_pocs.push(count);
+        } else {
+            let negative = read_ue(r)?;
+            let positive = read_ue(r)?;
+            for _ in 0..negative + positive {
+                read_ue(r)?; // delta_poc_sX_minus1
+                r.skip(1)?; // used_by_curr_pic_sX_flag
+            }
+            num_delta_pocs.push(negative + positive);
+        }
+    }
+
+    // long_term_ref_pics_present_flag
+    if r.read_bit()? {
+        let count = read_ue(r)?;
+        for _ in 0..count {
+            r.skip(log2_max_poc_lsb + 1)?;
+        }
+    }
+    r.skip(2)?; // sps_temporal_mvp_enabled_flag + strong_intra_smoothing_enabled_flag
+
+    // vui_parameters_present_flag
+    if !r.read_bit()? {
+        return Ok(());
+    }
+    parse_vui_colour(r, info)?;
+    // chroma_loc_info_present_flag
+    if r.read_bit()? {
+        read_ue(r)?;
+        read_ue(r)?;
+    }
+    r.skip(3)?; // neutral_chroma, field_seq, frame_field_info_present flags
+                // default_display_window_flag
+    if r.read_bit()? {
+        for _ in 0..4 {
+            read_ue(r)?;
+        }
+    }
+    // vui_timing_info_present_flag
+    if r.read_bit()? {
+        let num_units_in_tick: u32 = r.read(32)?;
+        let time_scale: u32 = r.read(32)?;
+        if num_units_in_tick > 0 && time_scale > 0 {
+            info.frame_rate = Some(time_scale as f64 / num_units_in_tick as f64);
+        }
+    }
+    Ok(())
+}
+
+fn skip_hevc_scaling_list_data(r: &mut Reader<'_>) -> io::Result<()> {
+    for size_id in 0..4u32 {
+        let step = if size_id == 3 { 3 } else { 1 };
+        let mut matrix_id = 0;
+        while matrix_id < 6 {
+            if !r.read_bit()? {
+                read_ue(r)?; // scaling_list_pred_matrix_id_delta
+            } else {
+                let coef_num = 64.min(1 << (4 + (size_id << 1)));
+                if size_id > 1 {
+                    read_se(r)?; // scaling_list_dc_coef_minus8
+                }
+                for _ in 0..coef_num {
+                    read_se(r)?;
+                }
+            }
+            matrix_id += step;
+        }
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Audio frame headers
+// ---------------------------------------------------------------------------
+
+/// Parse an ADTS (AAC) frame header found anywhere in `data`.
+pub(crate) fn parse_adts(data: &[u8]) -> Option<AudioFrameInfo> {
+    const RATES: [u32; 13] = [
+        96000, 88200, 64000, 48000, 44100, 32000, 24000, 22050, 16000, 12000, 11025, 8000, 7350,
+    ];
+    let pos = data
+        .windows(2)
+        .position(|w| w[0] == 0xFF && w[1] & 0xF6 == 0xF0)?;
+    let h = data.get(pos..pos + 7)?;
+    let sample_rate = RATES.get(((h[2] >> 2) & 0x0F) as usize).copied();
+    let channel_config = ((h[2] & 0x01) << 2) | (h[3] >> 6);
+    let channels = match channel_config {
+        7 => 8,
+        0 => 2, // signalled in-band via a PCE; stereo is by far the most common
+        n => n as u32,
+    };
+    Some(AudioFrameInfo {
+        channels,
+        sample_rate,
+    })
+}
+
+/// Channel count for an AC-3/E-AC-3 `acmod` value (without LFE).
+fn ac3_acmod_channels(acmod: u8) -> u32 {
+    [2, 1, 2, 3, 3, 4, 4, 5][acmod as usize & 0x07]
+}
+
+/// Parse an AC-3 or E-AC-3 sync frame header found anywhere in `data`.
+///
+/// The two are distinguished by `bsid` (<= 10 for AC-3, 11..=16 for E-AC-3).
+pub(crate) fn parse_ac3(data: &[u8]) -> Option<AudioFrameInfo> {
+    let pos = data.windows(2).position(|w| w == [0x0B, 0x77])?;
+    let frame = data.get(pos + 2..)?;
+    if frame.len() < 6 {
+        return None;
+    }
+    let bsid = frame[3] >> 3;
+    let mut r = BitReader::endian(frame, BigEndian);
+
+    let parsed: io::Result<AudioFrameInfo> = (|| {
+        if bsid <= 10 {
+            r.skip(16)?; // crc1
+            let fscod: u8 = r.read(2)?;
+            r.skip(6 + 5 + 3)?; // frmsizecod + bsid + bsmod
+            let acmod: u8 = r.read(3)?;
+            if acmod & 0x01 != 0 && acmod != 1 {
+                r.skip(2)?; // cmixlev
+            }
+            if acmod & 0x04 != 0 {
+                r.skip(2)?; // surmixlev
+            }
+            if acmod == 2 {
+                r.skip(2)?; // dsurmod
+            }
+            let lfe = r.read_bit()?;
+            Ok(AudioFrameInfo {
+                channels: ac3_acmod_channels(acmod) + lfe as u32,
+                sample_rate: [48000, 44100, 32000].get(fscod as usize).copied(),
+            })
+        } else {
+            r.skip(2 + 3 + 11)?; // strmtyp + substreamid + frmsiz
+            let fscod: u8 = r.read(2)?;
+            let sample_rate = if fscod == 3 {
+                let fscod2: u8 = r.read(2)?;
+                [24000, 22050, 16000].get(fscod2 as usize).copied()
+            } else {
+                r.skip(2)?; // numblkscod
+                [48000, 44100, 32000].get(fscod as usize).copied()
+            };
+            let acmod: u8 = r.read(3)?;
+            let lfe = r.read_bit()?;
+            Ok(AudioFrameInfo {
+                channels: ac3_acmod_channels(acmod) + lfe as u32,
+                sample_rate,
+            })
+        }
+    })();
+    parsed.ok()
+}
+
+/// Returns `true` if the first AC-3 family sync frame in `data` is E-AC-3.
+pub(crate) fn is_eac3(data: &[u8]) -> bool {
+    data.windows(2)
+        .position(|w| w == [0x0B, 0x77])
+        .and_then(|pos| data.get(pos + 5))
+        .is_some_and(|b| (b >> 3) > 10)
+}
+
+/// Parse a DTS core frame header found anywhere in `data`.
+pub(crate) fn parse_dts(data: &[u8]) -> Option<AudioFrameInfo> {
+    const AMODE_CHANNELS: [u32; 16] = [1, 2, 2, 2, 2, 3, 3, 4, 4, 5, 6, 6, 6, 7, 8, 8];
+    const RATES: [u32; 16] = [
+        0, 8000, 16000, 32000, 0, 0, 11025, 22050, 44100, 0, 0, 12000, 24000, 48000, 0, 0,
+    ];
+    let pos = data
+        .windows(4)
+        .position(|w| w == [0x7F, 0xFE, 0x80, 0x01])?;
+    let frame = data.get(pos + 4..)?;
+    let mut r = BitReader::endian(frame, BigEndian);
+
+    let parsed: io::Result<AudioFrameInfo> = (|| {
+        r.skip(1 + 5 + 1 + 7 + 14)?; // ftype, short, cpf, nblks, fsize
+        let amode: u8 = r.read(6)?;
+        let sfreq: u8 = r.read(4)?;
+        r.skip(5 + 1 + 1 + 1 + 1 + 1 + 3 + 1 + 1)?; // rate .. aspf
+        let lff: u8 = r.read(2)?;
+        let channels = AMODE_CHANNELS.get(amode as usize).copied().unwrap_or(2);
+        Ok(AudioFrameInfo {
+            channels: channels + (lff != 0) as u32,
+            sample_rate: RATES.get(sfreq as usize).copied().filter(|&rate| rate != 0),
+        })
+    })();
+    parsed.ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rbsp_strips_emulation_prevention() {
+        assert_eq!(
+            nal_to_rbsp(&[0x00, 0x00, 0x03, 0x01, 0x00, 0x00, 0x03, 0x00]),
+            vec![0x00, 0x00, 0x01, 0x00, 0x00, 0x00]
+        );
+    }
+
+    #[test]
+    fn avc_sps_1080p() {
+        // High profile, 1920x1088 with 8 lines of bottom cropping, 23.976 fps.
+        let sps = [
+            0x67, 0x64, 0x00, 0x28, 0xAC, 0xD9, 0x40, 0x78, 0x02, 0x27, 0xE5, 0x9A, 0x80, 0x80,
+            0x80, 0xA0, 0x00, 0x00, 0x7D, 0x20, 0x00, 0x17, 0x70, 0x18,
+        ];
+        let info = parse_avc_sps(&sps).unwrap();
+        assert_eq!(info.width, 1920);
+        assert_eq!(info.height, 1080);
+        assert_eq!(info.bit_depth, Some(8));
+        assert_eq!(info.transfer_characteristics, Some(1));
+        assert!((info.frame_rate.unwrap() - 23.976).abs() < 0.001);
+    }
+
+    #[test]
+    fn hevc_sps_2160p_pq() {
+        // Main 10, 3840x2160, BT.2020 + PQ, inter-predicted short-term RPS.
+        let sps = [
+            0x42, 0x01, 0x01, 0x02, 0x20, 0x00, 0x00, 0x03, 0x00, 0xB0, 0x00, 0x00, 0x03, 0x00,
+            0x00, 0x03, 0x00, 0x99, 0xA0, 0x01, 0xE0, 0x20, 0x02, 0x1C, 0x4D, 0x96, 0x6E, 0x49,
+            0x1B, 0x66, 0xBD, 0xAE, 0x6A, 0x12, 0x20, 0x12, 0x08, 0x00, 0x00, 0x1F, 0x48, 0x00,
+            0x02, 0xEE, 0x01,
+        ];
+        let info = parse_hevc_sps(&sps).unwrap();
+        assert_eq!(info.width, 3840);
+        assert_eq!(info.height, 2160);
+        assert_eq!(info.bit_depth, Some(10));
+        assert_eq!(info.colour_primaries, Some(9));
+        assert_eq!(info.transfer_characteristics, Some(16));
+        assert!((info.frame_rate.unwrap() - 23.976).abs() < 0.001);
+    }
+
+    #[test]
+    fn hevc_sps_rejects_wrong_nal_type() {
+        assert!(parse_hevc_sps(&[0x40, 0x01, 0x0C, 0x01]).is_none());
+    }
+
+    #[test]
+    fn adts_stereo_48k() {
+        let frame = [0xFF, 0xF1, 0x4C, 0x80, 0x01, 0x3F, 0xFC];
+        let info = parse_adts(&frame).unwrap();
+        assert_eq!(info.channels, 2);
+        assert_eq!(info.sample_rate, Some(48000));
+    }
+
+    #[test]
+    fn ac3_5_1_48k() {
+        // fscod=0, frmsizecod=0x1C, bsid=8, bsmod=0, acmod=7, cmixlev, surmixlev, lfeon=1.
+        let frame = [0x0B, 0x77, 0x00, 0x00, 0x1C, 0x40, 0xE1, 0x00];
+        let info = parse_ac3(&frame).unwrap();
+        assert_eq!(info.channels, 6);
+        assert_eq!(info.sample_rate, Some(48000));
+        assert!(!is_eac3(&frame));
+    }
+
+    #[test]
+    fn eac3_5_1_48k() {
+        // strmtyp=0, substreamid=0, frmsiz=0x1FF, fscod=0, numblkscod=3, acmod=7, lfeon=1.
+        let frame = [0x0B, 0x77, 0x01, 0xFF, 0x3F, 0x86, 0x00, 0x00];
+        let info = parse_ac3(&frame).unwrap();
+        assert_eq!(info.channels, 6);
+        assert_eq!(info.sample_rate, Some(48000));
+        assert!(is_eac3(&frame));
+    }
+}
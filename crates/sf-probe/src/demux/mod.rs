@@ -0,0 +1,8 @@
+//! Native demuxers for containers not handled by `matroska` or `mp4parse`.
+
+mod avi;
+mod es;
+mod mpegts;
+
+pub(crate) use avi::probe_avi;
+pub(crate) use mpegts::probe_ts;
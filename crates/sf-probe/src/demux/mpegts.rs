@@ -0,0 +1,801 @@
+//! MPEG transport stream probing.
+//!
+//! Parses the PAT and PMT to enumerate elementary streams, classifies them by
+//! `stream_type` and descriptors, and then reads the start of each stream's
+//! PES payload to recover codec parameters (SPS for video, frame headers for
+//! audio). Duration is derived from the first and last video PTS.
+//!
+//! Handles plain 188-byte packets as well as 192-byte BDAV (`.m2ts`) packets.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::Duration;
+
+use sf_core::{AudioCodec, Container, HdrFormat, VideoCodec};
+
+use super::es;
+use crate::hdr;
+use crate::types::{AudioTrack, DvInfo, MediaInfo, SubtitleTrack, VideoTrack};
+
+const SYNC_BYTE: u8 = 0x47;
+const TS_PACKET_SIZE: usize = 188;
+
+/// How much of the file head is scanned for PSI tables and stream headers.
+const HEAD_SCAN_BYTES: u64 = 16 * 1024 * 1024;
+/// How much of the file tail is scanned for the last PTS.
+const TAIL_SCAN_BYTES: u64 = 4 * 1024 * 1024;
+/// Upper bound on buffered payload per video stream.
+const MAX_VIDEO_BYTES: usize = 4 * 1024 * 1024;
+/// Amount of payload buffered per audio stream before its header is parsed.
+const AUDIO_BYTES: usize = 16 * 1024;
+
+/// 33-bit PTS wraparound modulus.
+const PTS_WRAP: u64 = 1 << 33;
+
+/// Probe an MPEG-TS / M2TS file.
+pub(crate) fn probe_ts(path: &Path) -> sf_core::Result<MediaInfo> {
+    let mut file = File::open(path).map_err(|e| sf_core::Error::Probe(e.to_string()))?;
+    let file_size = file
+        .metadata()
+        .map_err(|e| sf_core::Error::Probe(e.to_string()))?
+        .len();
+
+    let mut head = Vec::new();
+    (&mut file)
+        .take(HEAD_SCAN_BYTES)
+        .read_to_end(&mut head)
+        .map_err(|e| sf_core::Error::Probe(e.to_string()))?;
+
+    let layout = PacketLayout::detect(&head)
+        .ok_or_else(|| sf_core::Error::Probe("no MPEG-TS sync pattern found".to_string()))?;
+
+    let mut demux = Demuxer::default();
+    for packet in layout.packets(&head) {
+        demux.push_packet(packet);
+        if demux.is_complete() {
+            break;
+        }
+    }
+
+    if demux.streams.is_empty() {
+        return Err(sf_core::Error::Probe(
+            "MPEG-TS: no program map table found".to_string(),
+        ));
+    }
+
+    let duration = demux
+        .timing_pid()
+        .and_then(|(pid, first_pts)| {
+            let last_pts = read_last_pts(&mut file, file_size, pid)?;
+            let ticks = (last_pts + PTS_WRAP - first_pts) % PTS_WRAP;
+            Some(Duration::from_millis(ticks * 1000 / 90_000))
+        })
+        .filter(|d| !d.is_zero());
+
+    Ok(demux.into_media_info(path, file_size, duration))
+}
+
+// ---------------------------------------------------------------------------
+// Packet framing
+// ---------------------------------------------------------------------------
+
+/// Packet size and sync byte offset within each packet.
+#[derive(Debug, Clone, Copy)]
+struct PacketLayout {
+    start: usize,
+    size: usize,
+    sync_offset: usize,
+}
+
+impl PacketLayout {
+    /// Locate the packet grid by finding a run of sync bytes at a fixed stride.
+    fn detect(data: &[u8]) -> Option<Self> {
+        const REQUIRED: usize = 5;
+        for (size, sync_offset) in [(188, 0), (192, 4), (204, 0)] {
+            for start in 0..size.min(data.len()) {
+                let aligned = (0..REQUIRED).all(|i| {
+                    data.get(start + sync_offset + i * size)
+                        .is_some_and(|&b| b == SYNC_BYTE)
+                });
+                if aligned {
+                    return Some(Self {
+                        start,
+                        size,
+                        sync_offset,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// Iterate over the 188-byte transport packets contained in `data`.
+    fn packets<'a>(&self, data: &'a [u8]) -> impl Iterator<Item = &'a [u8]> + 'a {
+        let layout = *self;
+        data.get(layout.start..)
+            .unwrap_or_default()
+            .chunks_exact(layout.size)
+            .map(move |chunk| &chunk[layout.sync_offset..layout.sync_offset + TS_PACKET_SIZE])
+            .filter(|p| p[0] == SYNC_BYTE)
+    }
+}
+
+/// A parsed transport packet header with its payload.
+struct Packet<'a> {
+    pid: u16,
+    payload_unit_start: bool,
+    payload: &'a [u8],
+}
+
+fn parse_packet(packet: &[u8]) -> Option<Packet<'_>> {
+    if packet.len() < TS_PACKET_SIZE || packet[0] != SYNC_BYTE {
+        return None;
+    }
+    let pid = (u16::from(packet[1] & 0x1F) << 8) | u16::from(packet[2]);
+    let payload_unit_start = packet[1] & 0x40 != 0;
+    let adaptation = (packet[3] >> 4) & 0x03;
+
+    let mut offset = 4;
+    if adaptation & 0x02 != 0 {
+        offset += 1 + packet[4] as usize;
+    }
+    if adaptation & 0x01 == 0 || offset >= TS_PACKET_SIZE {
+        return None;
+    }
+    Some(Packet {
+        pid,
+        payload_unit_start,
+        payload: &packet[offset..TS_PACKET_SIZE],
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Demuxer state
+// ---------------------------------------------------------------------------
+
+/// What an elementary stream carries, as derived from the PMT.
+#[derive(Debug, Clone, PartialEq)]
+enum StreamKind {
+    Video(VideoCodec),
+    Audio(AudioCodec),
+    Subtitle(&'static str),
+}
+
+/// An elementary stream announced in a PMT.
+#[derive(Debug)]
+struct EsStream {
+    kind: StreamKind,
+    language: Option<String>,
+    /// Dolby Vision configuration from a DOVI video stream descriptor.
+    dv_config: Option<Vec<u8>>,
+    /// Buffered PES payload bytes.
+    data: Vec<u8>,
+    first_pts: Option<u64>,
+    /// Set once enough payload has been buffered.
+    done: bool,
+}
+
+#[derive(Default)]
+struct Demuxer {
+    /// PMT PIDs announced by the PAT.
+    pmt_pids: Vec<u16>,
+    /// Partially assembled PSI sections by PID.
+    sections: HashMap<u16, Vec<u8>>,
+    /// PMT PIDs that have already been parsed.
+    parsed_pmts: Vec<u16>,
+    /// Elementary streams in PMT order.
+    order: Vec<u16>,
+    streams: HashMap<u16, EsStream>,
+}
+
+impl Demuxer {
+    fn push_packet(&mut self, raw: &[u8]) {
+        let Some(packet) = parse_packet(raw) else {
+            return;
+        };
+
+        if packet.pid == 0 || self.pmt_pids.contains(&packet.pid) {
+            if let Some(section) = self.assemble_section(&packet) {
+                if packet.pid == 0 {
+                    self.parse_pat(&section);
+                } else if !self.parsed_pmts.contains(&packet.pid) {
+                    self.parsed_pmts.push(packet.pid);
+                    self.parse_pmt(&section);
+                }
+            }
+            return;
+        }
+
+        if let Some(stream) = self.streams.get_mut(&packet.pid) {
+            stream.push_payload(packet.payload_unit_start, packet.payload);
+        }
+    }
+
+    /// All PMTs are parsed and every stream has buffered enough payload.
+    fn is_complete(&self) -> bool {
+        !self.pmt_pids.is_empty()
+            && self.parsed_pmts.len() == self.pmt_pids.len()
+            && self.streams.values().all(|s| s.done)
+    }
+
+    /// Accumulate a PSI section, returning it once complete.
+    fn assemble_section(&mut self, packet: &Packet<'_>) -> Option<Vec<u8>> {
+        let buf = if packet.payload_unit_start {
+            let pointer = *packet.payload.first()? as usize;
+            let start = packet.payload.get(1 + pointer..)?;
+            let buf = self.sections.entry(packet.pid).or_default();
+            buf.clear();
+            buf.extend_from_slice(start);
+            buf
+        } else {
+            let buf = self.sections.get_mut(&packet.pid)?;
+            if buf.is_empty() {
+                return None;
+            }
+            buf.extend_from_slice(packet.payload);
+            buf
+        };
+
+        if buf.len() < 3 {
+            return None;
+        }
+        let section_length = ((usize::from(buf[1]) & 0x0F) << 8) | usize::from(buf[2]);
+        if buf.len() < 3 + section_length {
+            return None;
+        }
+        let section = buf[..3 + section_length].to_vec();
+        buf.clear();
+        Some(section)
+    }
+
+    fn parse_pat(&mut self, section: &[u8]) {
+        // table_id 0x00; 8-byte header, 4-byte CRC trailer.
+        if section.first() != Some(&0x00) || section.len() < 12 {
+            return;
+        }
+        for entry in section[8..section.len() - 4].chunks_exact(4) {
+            let program_number = u16::from_be_bytes([entry[0], entry[1]]);
+            let pid = (u16::from(entry[2] & 0x1F) << 8) | u16::from(entry[3]);
+            // Program 0 points at the network information table.
+            if program_number != 0 && !self.pmt_pids.contains(&pid) {
+                self.pmt_pids.push(pid);
+            }
+        }
+    }
+
+    fn parse_pmt(&mut self, section: &[u8]) {
+        // table_id 0x02; 12-byte header, 4-byte CRC trailer.
+        if section.first() != Some(&0x02) || section.len() < 16 {
+            return;
+        }
+        let program_info_length =
+            ((usize::from(section[10]) & 0x0F) << 8) | usize::from(section[11]);
+        let end = section.len() - 4;
+        let mut pos = 12 + program_info_length;
+
+        while pos + 5 <= end {
+            let stream_type = section[pos];
+            let pid = (u16::from(section[pos + 1] & 0x1F) << 8) | u16::from(section[pos + 2]);
+            let es_info_length =
+                ((usize::from(section[pos + 3]) & 0x0F) << 8) | usize::from(section[pos + 4]);
+            let descriptors = section
+                .get(pos + 5..(pos + 5 + es_info_length).min(end))
+                .unwrap_or_default();
+            pos += 5 + es_info_length;
+
+            let descriptors = parse_descriptors(descriptors);
+            let Some(kind) = classify_stream(stream_type, &descriptors) else {
+                tracing::debug!(stream_type, pid, "skipping unsupported MPEG-TS stream");
+                continue;
+            };
+            if self.streams.contains_key(&pid) {
+                continue;
+            }
+
+            let language = descriptors.iter().find_map(|(tag, body)| match tag {
+                // ISO_639_language, teletext and DVB subtitling descriptors
+                // all start with a 3-byte language code.
+                0x0A | 0x56 | 0x59 if body.len() >= 3 => {
+                    std::str::from_utf8(&body[..3]).ok().map(str::to_string)
+                }
+                _ => None,
+            });
+            let dv_config = descriptors
+                .iter()
+                .find(|(tag, _)| *tag == 0xB0)
+                .map(|(_, body)| body.to_vec());
+            // Subtitles carry no header worth buffering.
+            let done = matches!(kind, StreamKind::Subtitle(_));
+
+            self.order.push(pid);
+            self.streams.insert(
+                pid,
+                EsStream {
+                    kind,
+                    language,
+                    dv_config,
+                    data: Vec::new(),
+                    first_pts: None,
+                    done,
+                },
+            );
+        }
+    }
+
+    /// The PID and first PTS used for duration: the first video stream,
+    /// falling back to the first audio stream.
+    fn timing_pid(&self) -> Option<(u16, u64)> {
+        let pick = |want_video: bool| {
+            self.order.iter().find_map(|pid| {
+                let s = &self.streams[pid];
+                let is_video = matches!(s.kind, StreamKind::Video(_));
+                let is_audio = matches!(s.kind, StreamKind::Audio(_));
+                if (want_video && is_video) || (!want_video && is_audio) {
+                    s.first_pts.map(|pts| (*pid, pts))
+                } else {
+                    None
+                }
+            })
+        };
+        pick(true).or_else(|| pick(false))
+    }
+
+    fn into_media_info(
+        mut self,
+        path: &Path,
+        file_size: u64,
+        duration: Option<Duration>,
+    ) -> MediaInfo {
+        let mut video_tracks = Vec::new();
+        let mut audio_tracks = Vec::new();
+        let mut subtitle_tracks = Vec::new();
+
+        for pid in &self.order {
+            let Some(stream) = self.streams.remove(pid) else {
+                continue;
+            };
+            match stream.kind {
+                StreamKind::Video(codec) => {
+                    video_tracks.push(video_track(codec, &stream, video_tracks.is_empty()));
+                }
+                StreamKind::Audio(codec) => {
+                    audio_tracks.push(audio_track(codec, &stream, audio_tracks.is_empty()));
+                }
+                StreamKind::Subtitle(codec) => subtitle_tracks.push(SubtitleTrack {
+                    codec: codec.to_string(),
+                    language: stream.language,
+                    forced: false,
+                    default: false,
+                }),
+            }
+        }
+
+        MediaInfo {
+            file_path: path.to_path_buf(),
+            file_size,
+            container: Container::MpegTs,
+            duration,
+            video_tracks,
+            audio_tracks,
+            subtitle_tracks,
+        }
+    }
+}
+
+impl EsStream {
+    fn push_payload(&mut self, unit_start: bool, payload: &[u8]) {
+        if self.done {
+            return;
+        }
+
+        let body = if unit_start {
+            let Some((pts, header_len)) = parse_pes_header(payload) else {
+                return;
+            };
+            if self.first_pts.is_none() {
+                self.first_pts = pts;
+            }
+            // A new PES packet starts: stop once a complete access unit
+            // holding the parameter sets has been buffered.
+            if let StreamKind::Video(codec) = self.kind {
+                if !self.data.is_empty() && find_sps(codec, &self.data).is_some() {
+                    self.done = true;
+                    return;
+                }
+            }
+            &payload[header_len..]
+        } else {
+            if self.first_pts.is_none() {
+                // Joined mid-PES; wait for the next unit start.
+                return;
+            }
+            payload
+        };
+
+        self.data.extend_from_slice(body);
+        let limit = match self.kind {
+            StreamKind::Video(_) => MAX_VIDEO_BYTES,
+            _ => AUDIO_BYTES,
+        };
+        if self.data.len() >= limit {
+            self.done = true;
+        }
+    }
+}
+
+/// Parse a PES header, returning the PTS (if present) and the header length.
+fn parse_pes_header(payload: &[u8]) -> Option<(Option<u64>, usize)> {
+    if payload.len() < 9 || payload[..3] != [0x00, 0x00, 0x01] {
+        return None;
+    }
+    let header_len = 9 + payload[8] as usize;
+    if header_len > payload.len() {
+        return None;
+    }
+    let pts = if payload[7] & 0x80 != 0 {
+        payload.get(9..14).map(decode_timestamp)
+    } else {
+        None
+    };
+    Some((pts, header_len))
+}
+
+fn decode_timestamp(b: &[u8]) -> u64 {
+    (u64::from(b[0] >> 1) & 0x07) << 30
+        | u64::from(b[1]) << 22
+        | (u64::from(b[2]) >> 1) << 15
+        | u64::from(b[3]) << 7
+        | u64::from(b[4]) >> 1
+}
+
+/// Scan the tail of the file for the last PTS on `pid`.
+fn read_last_pts(file: &mut File, file_size: u64, pid: u16) -> Option<u64> {
+    let start = file_size.saturating_sub(TAIL_SCAN_BYTES);
+    file.seek(SeekFrom::Start(start)).ok()?;
+    let mut tail = Vec::new();
+    file.take(TAIL_SCAN_BYTES).read_to_end(&mut tail).ok()?;
+
+    let layout = PacketLayout::detect(&tail)?;
+    layout
+        .packets(&tail)
+        .filter_map(parse_packet)
+        .filter(|p| p.pid == pid && p.payload_unit_start)
+        .filter_map(|p| parse_pes_header(p.payload).and_then(|(pts, _)| pts))
+        .last()
+}
+
+// ---------------------------------------------------------------------------
+// Stream classification
+// ---------------------------------------------------------------------------
+
+fn parse_descriptors(mut data: &[u8]) -> Vec<(u8, &[u8])> {
+    let mut out = Vec::new();
+    while data.len() >= 2 {
+        let tag = data[0];
+        let len = data[1] as usize;
+        let Some(body) = data.get(2..2 + len) else {
+            break;
+        };
+        out.push((tag, body));
+        data = &data[2 + len..];
+    }
+    out
+}
+
+/// Map a PMT `stream_type` (plus descriptors for private streams) to a kind.
+fn classify_stream(stream_type: u8, descriptors: &[(u8, &[u8])]) -> Option<StreamKind> {
+    let kind = match stream_type {
+        0x1B => StreamKind::Video(VideoCodec::H264),
+        0x24 => StreamKind::Video(VideoCodec::H265),
+        0x0F | 0x11 => StreamKind::Audio(AudioCodec::Aac),
+        // ATSC/BDAV AC-3; E-AC-3 is re-checked against the frame header.
+        0x81 => StreamKind::Audio(AudioCodec::Ac3),
+        0x84 | 0x87 | 0xA1 => StreamKind::Audio(AudioCodec::Eac3),
+        0x82 => StreamKind::Audio(AudioCodec::Dts),
+        0x85 | 0x86 | 0xA2 => StreamKind::Audio(AudioCodec::DtsHd),
+        0x83 => StreamKind::Audio(AudioCodec::TrueHd),
+        0x90 => StreamKind::Subtitle("PGS"),
+        0x92 => StreamKind::Subtitle("BD Text"),
+        0x06 => return classify_private_stream(descriptors),
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// Classify a PES private data stream (`stream_type` 0x06) by its descriptors.
+fn classify_private_stream(descriptors: &[(u8, &[u8])]) -> Option<StreamKind> {
+    for (tag, body) in descriptors {
+        let kind = match tag {
+            0x6A => StreamKind::Audio(AudioCodec::Ac3),
+            0x7A => StreamKind::Audio(AudioCodec::Eac3),
+            0x7B => StreamKind::Audio(AudioCodec::Dts),
+            0x59 => StreamKind::Subtitle("DVB Subtitle"),
+            0x56 => StreamKind::Subtitle("Teletext"),
+            // Registration descriptor: format identifier.
+            0x05 if body.len() >= 4 => match &body[..4] {
+                b"AC-3" => StreamKind::Audio(AudioCodec::Ac3),
+                b"EAC3" => StreamKind::Audio(AudioCodec::Eac3),
+                b"DTS1" | b"DTS2" | b"DTS3" => StreamKind::Audio(AudioCodec::Dts),
+                b"Opus" => StreamKind::Audio(AudioCodec::Opus),
+                b"AV01" => StreamKind::Video(VideoCodec::Av1),
+                b"HEVC" => StreamKind::Video(VideoCodec::H265),
+                _ => continue,
+            },
+            _ => continue,
+        };
+        return Some(kind);
+    }
+    None
+}
+
+// ---------------------------------------------------------------------------
+// Track construction
+// ---------------------------------------------------------------------------
+
+/// Find the first SPS NAL unit for `codec` in Annex B data.
+fn find_sps(codec: VideoCodec, data: &[u8]) -> Option<Vec<u8>> {
+    let is_sps = |nal: &[u8]| match codec {
+        VideoCodec::H264 => nal.first().is_some_and(|b| b & 0x1F == 7),
+        VideoCodec::H265 => nal.first().is_some_and(|b| (b >> 1) & 0x3F == 33),
+        _ => false,
+    };
+    hdr::extract_nal_units(data)
+        .into_iter()
+        .map(|(_, nal)| nal)
+        .find(|nal| is_sps(nal))
+}
+
+fn video_track(codec: VideoCodec, stream: &EsStream, default: bool) -> VideoTrack {
+    let sps = find_sps(codec, &stream.data).and_then(|nal| match codec {
+        VideoCodec::H264 => es::parse_avc_sps(&nal),
+        VideoCodec::H265 => es::parse_hevc_sps(&nal),
+        _ => None,
+    });
+
+    let mut hdr_format = match sps.as_ref().and_then(|s| s.transfer_characteristics) {
+        Some(16) => HdrFormat::Hdr10,
+        Some(18) => HdrFormat::Hlg,
+        _ => HdrFormat::Sdr,
+    };
+    let mut dolby_vision: Option<DvInfo> = None;
+    let mut hdr10plus = false;
+
+    if let Some(dv) = stream
+        .dv_config
+        .as_deref()
+        .and_then(hdr::detect_dolby_vision)
+    {
+        hdr_format = HdrFormat::DolbyVision;
+        dolby_vision = Some(dv);
+    } else if codec == VideoCodec::H265 {
+        // SEI (HDR10+) and RPU (Dolby Vision) detection from the access unit.
+        if let Some(detection) = hdr::detect_hdr_from_hevc(&stream.data) {
+            hdr10plus = detection.hdr10plus;
+            if matches!(
+                detection.format,
+                HdrFormat::DolbyVision | HdrFormat::Hdr10Plus
+            ) || hdr_format == HdrFormat::Sdr
+            {
+                hdr_format = detection.format;
+                dolby_vision = detection.dv_info;
+            }
+        }
+    }
+
+    let sps = sps.unwrap_or_default();
+    VideoTrack {
+        codec,
+        width: sps.width,
+        height: sps.height,
+        frame_rate: sps.frame_rate,
+        bit_depth: sps.bit_depth,
+        hdr_format,
+        hdr10plus,
+        dolby_vision,
+        default,
+        language: stream.language.clone(),
+    }
+}
+
+fn audio_track(codec: AudioCodec, stream: &EsStream, default: bool) -> AudioTrack {
+    let header = match codec {
+        AudioCodec::Aac => es::parse_adts(&stream.data),
+        AudioCodec::Ac3 | AudioCodec::Eac3 => es::parse_ac3(&stream.data),
+        AudioCodec::Dts | AudioCodec::DtsHd => es::parse_dts(&stream.data),
+        _ => None,
+    };
+    // ATSC signals both AC-3 flavours as 0x81; trust the bitstream.
+    let codec = match codec {
+        AudioCodec::Ac3 if es::is_eac3(&stream.data) => AudioCodec::Eac3,
+        other => other,
+    };
+
+    AudioTrack {
+        codec,
+        channels: header.map(|h| h.channels).unwrap_or(2),
+        sample_rate: header.and_then(|h| h.sample_rate),
+        language: stream.language.clone(),
+        atmos: false,
+        default,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const VIDEO_PID: u16 = 0x100;
+    const AUDIO_PID: u16 = 0x101;
+
+    /// Wrap a payload into 188-byte TS packets, padding the last one with an
+    /// adaptation field.
+    fn packetize(pid: u16, payload: &[u8], cc: &mut u8) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut first = true;
+        for chunk in payload.chunks(184) {
+            let mut pkt = vec![
+                SYNC_BYTE,
+                ((pid >> 8) as u8 & 0x1F) | if first { 0x40 } else { 0 },
+                pid as u8,
+            ];
+            if chunk.len() == 184 {
+                pkt.push(0x10 | *cc);
+            } else {
+                let stuffing = 184 - chunk.len() - 1;
+                pkt.push(0x30 | *cc);
+                pkt.push(stuffing as u8);
+                if stuffing > 0 {
+                    pkt.push(0x00);
+                    pkt.extend(std::iter::repeat_n(0xFF, stuffing - 1));
+                }
+            }
+            pkt.extend_from_slice(chunk);
+            assert_eq!(pkt.len(), TS_PACKET_SIZE);
+            out.extend(pkt);
+            *cc = (*cc + 1) & 0x0F;
+            first = false;
+        }
+        out
+    }
+
+    fn section(table_id: u8, id: u16, body: &[u8]) -> Vec<u8> {
+        let len = 5 + body.len() + 4;
+        let mut s = vec![
+            0x00, // pointer_field
+            table_id,
+            0xB0 | (len >> 8) as u8,
+            len as u8,
+            (id >> 8) as u8,
+            id as u8,
+            0xC1,
+            0x00,
+            0x00,
+        ];
+        s.extend_from_slice(body);
+        s.extend_from_slice(&[0, 0, 0, 0]); // CRC is not validated
+        s
+    }
+
+    fn pes(stream_id: u8, pts: u64, data: &[u8]) -> Vec<u8> {
+        let mut p = vec![0x00, 0x00, 0x01, stream_id, 0x00, 0x00, 0x80, 0x80, 0x05];
+        p.push(0x21 | ((pts >> 29) as u8 & 0x0E));
+        p.push((pts >> 22) as u8);
+        p.push(0x01 | ((pts >> 14) as u8 & 0xFE));
+        p.push((pts >> 7) as u8);
+        p.push(0x01 | ((pts << 1) as u8 & 0xFE));
+        p.extend_from_slice(data);
+        p
+    }
+
+    fn sample_ts() -> Vec<u8> {
+        let mut cc = 0u8;
+        let mut ts = Vec::new();
+
+        ts.extend(packetize(
+            0,
+            &section(0x00, 1, &[0x00, 0x01, 0xF0, 0x00]),
+            &mut cc,
+        ));
+
+        let mut pmt = vec![0xE0 | (VIDEO_PID >> 8) as u8, VIDEO_PID as u8, 0xF0, 0x00];
+        pmt.extend([0x1B, 0xE1, 0x00, 0xF0, 0x00]);
+        pmt.extend([
+            0x0F, 0xE1, 0x01, 0xF0, 0x06, 0x0A, 0x04, b'e', b'n', b'g', 0x00,
+        ]);
+        pmt.extend([0x06, 0xE1, 0x02, 0xF0, 0x0A, 0x59, 0x08]);
+        pmt.extend(b"ger\x10\x00\x01\x00\x01");
+        ts.extend(packetize(0x1000, &section(0x02, 1, &pmt), &mut cc));
+
+        let mut access_unit = vec![0x00, 0x00, 0x00, 0x01];
+        access_unit.extend([
+            0x67, 0x64, 0x00, 0x28, 0xAC, 0xD9, 0x40, 0x78, 0x02, 0x27, 0xE5, 0x9A, 0x80, 0x80,
+            0x80, 0xA0, 0x00, 0x00, 0x7D, 0x20, 0x00, 0x17, 0x70, 0x18,
+        ]);
+        access_unit.extend([0x00, 0x00, 0x00, 0x01, 0x65]);
+        access_unit.extend(std::iter::repeat_n(0xAB, 400));
+
+        let adts = [0xFF, 0xF1, 0x4C, 0x80, 0x01, 0x3F, 0xFC, 0x21, 0x00];
+
+        for frame in 0..4u64 {
+            ts.extend(packetize(
+                VIDEO_PID,
+                &pes(0xE0, 90_000 + frame * 45_000, &access_unit),
+                &mut cc,
+            ));
+            ts.extend(packetize(
+                AUDIO_PID,
+                &pes(0xC0, 90_000 + frame * 45_000, &adts),
+                &mut cc,
+            ));
+        }
+        ts
+    }
+
+    #[test]
+    fn probes_synthetic_transport_stream() {
+        let mut file = tempfile::Builder::new().suffix(".ts").tempfile().unwrap();
+        file.write_all(&sample_ts()).unwrap();
+
+        let info = probe_ts(file.path()).unwrap();
+        assert_eq!(info.container, Container::MpegTs);
+
+        assert_eq!(info.video_tracks.len(), 1);
+        let video = &info.video_tracks[0];
+        assert_eq!(video.codec, VideoCodec::H264);
+        assert_eq!((video.width, video.height), (1920, 1080));
+        assert_eq!(video.hdr_format, HdrFormat::Sdr);
+        assert!(video.default);
+
+        assert_eq!(info.audio_tracks.len(), 1);
+        let audio = &info.audio_tracks[0];
+        assert_eq!(audio.codec, AudioCodec::Aac);
+        assert_eq!(audio.channels, 2);
+        assert_eq!(audio.sample_rate, Some(48000));
+        assert_eq!(audio.language.as_deref(), Some("eng"));
+
+        assert_eq!(info.subtitle_tracks.len(), 1);
+        assert_eq!(info.subtitle_tracks[0].codec, "DVB Subtitle");
+        assert_eq!(info.subtitle_tracks[0].language.as_deref(), Some("ger"));
+
+        // Four frames with 0.5s PTS spacing: first 1.0s, last 2.5s.
+        assert_eq!(info.duration, Some(Duration::from_millis(1500)));
+    }
+
+    #[test]
+    fn detects_bdav_packet_layout() {
+        let ts = sample_ts();
+        let mut m2ts = Vec::new();
+        for packet in ts.chunks_exact(TS_PACKET_SIZE) {
+            m2ts.extend_from_slice(&[0, 0, 0, 0]);
+            m2ts.extend_from_slice(packet);
+        }
+        let layout = PacketLayout::detect(&m2ts).unwrap();
+        assert_eq!((layout.size, layout.sync_offset), (192, 4));
+        assert_eq!(layout.packets(&m2ts).count(), ts.len() / TS_PACKET_SIZE);
+    }
+
+    #[test]
+    fn private_stream_classification() {
+        assert_eq!(
+            classify_stream(0x06, &[(0x7A, &[][..])]),
+            Some(StreamKind::Audio(AudioCodec::Eac3))
+        );
+        assert_eq!(
+            classify_stream(0x06, &[(0x05, &b"DTS2"[..])]),
+            Some(StreamKind::Audio(AudioCodec::Dts))
+        );
+        assert_eq!(classify_stream(0x06, &[]), None);
+        assert_eq!(classify_stream(0x02, &[]), None);
+    }
+
+    #[test]
+    fn pts_roundtrip() {
+        let p = pes(0xE0, 0x1_2345_6789, &[]);
+        let (pts, len) = parse_pes_header(&p).unwrap();
+        assert_eq!(pts, Some(0x1_2345_6789));
+        assert_eq!(len, 14);
+    }
+}
@@ -8,10 +8,12 @@ use sf_core::HdrFormat;
 use super::dolby_vision;
 use crate::types::DvInfo;
 
-/// Result of HDR detection: the HDR format and optional Dolby Vision info.
+/// Result of HDR detection: the HDR format, optional Dolby Vision info and
+/// whether HDR10+ metadata is present (also alongside Dolby Vision).
 pub(crate) struct HdrDetection {
     pub format: HdrFormat,
     pub dv_info: Option<DvInfo>,
+    pub hdr10plus: bool,
 }
 
 /// Attempt to detect HDR format from HEVC codec private data.
@@ -56,6 +58,7 @@ pub(crate) fn detect_hdr_from_hevc(data: &[u8]) -> Option<HdrDetection> {
         return Some(HdrDetection {
             format: HdrFormat::DolbyVision,
             dv_info: Some(dv),
+            hdr10plus: has_hdr10plus,
         });
     }
 
@@ -63,6 +66,7 @@ pub(crate) fn detect_hdr_from_hevc(data: &[u8]) -> Option<HdrDetection> {
         return Some(HdrDetection {
             format: HdrFormat::Hdr10Plus,
             dv_info: None,
+            hdr10plus: true,
         });
     }
 
@@ -71,11 +75,13 @@ pub(crate) fn detect_hdr_from_hevc(data: &[u8]) -> Option<HdrDetection> {
         Some(16) => Some(HdrDetection {
             format: HdrFormat::Hdr10,
             dv_info: None,
+            hdr10plus: false,
         }),
         // ARIB STD-B67 = HLG.
         Some(18) => Some(HdrDetection {
             format: HdrFormat::Hlg,
             dv_info: None,
+            hdr10plus: false,
         }),
         _ => None,
     }
@@ -89,7 +95,7 @@ pub(crate) fn detect_hdr_from_hevc(data: &[u8]) -> Option<HdrDetection> {
 ///
 /// Handles both Annex B start-code format (0x00 0x00 0x01 / 0x00 0x00 0x00 0x01)
 /// and length-prefixed (HVCC) format.
-fn extract_nal_units(data: &[u8]) -> Vec<(u8, Vec<u8>)> {
+pub(crate) fn extract_nal_units(data: &[u8]) -> Vec<(u8, Vec<u8>)> {
     let mut nals = extract_annex_b(data);
     if nals.is_empty() && data.len() > 4 {
         nals = extract_length_prefixed(data);
@@ -1,8 +1,9 @@
 //! Dolby Vision detection from codec private data and RPU NAL units.
 
 use dolby_vision::rpu::dovi_rpu::DoviRpu;
+use dolby_vision::rpu::rpu_data_nlq::DoviELType;
 
-use crate::types::DvInfo;
+use crate::types::{DvElType, DvInfo};
 
 /// Attempt to detect Dolby Vision from codec private data.
 ///
@@ -55,6 +56,7 @@ fn parse_dv_config(data: &[u8]) -> Option<DvInfo> {
         rpu_present,
         el_present,
         bl_present,
+        el_type: None,
     })
 }
 
@@ -81,5 +83,9 @@ pub(crate) fn parse_rpu_nal(rpu_data: &[u8]) -> Option<DvInfo> {
         rpu_present: true,
         el_present: rpu.el_type.is_some(),
         bl_present: true,
+        el_type: rpu.el_type.map(|el| match el {
+            DoviELType::MEL => DvElType::Mel,
+            DoviELType::FEL => DvElType::Fel,
+        }),
     })
 }
@@ -3,5 +3,5 @@
 mod detect;
 mod dolby_vision;
 
-pub(crate) use detect::detect_hdr_from_hevc;
+pub(crate) use detect::{detect_hdr_from_hevc, extract_nal_units};
 pub(crate) use dolby_vision::detect_dolby_vision;
@@ -2,10 +2,11 @@
 //!
 //! Pure-Rust media file probing with HDR and Dolby Vision detection.
 //!
-//! This crate extracts metadata from video files (MKV, MP4, M4V) without
-//! requiring external tools like `ffprobe` or `mediainfo`. It detects:
+//! This crate extracts metadata from video files (MKV, WebM, MP4, M4V,
+//! MPEG-TS, AVI) without requiring external tools like `ffprobe` or
+//! `mediainfo`. It detects:
 //!
-//! - Container format (Matroska, MP4)
+//! - Container format (Matroska, WebM, MP4, MPEG-TS, AVI)
 //! - Video tracks with codec, resolution, frame rate, and HDR format
 //! - Audio tracks with codec, channel count, and sample rate
 //! - Subtitle tracks
@@ -26,6 +27,7 @@
 //! ```
 
 pub mod composite;
+mod demux;
 mod hdr;
 pub mod prober;
 pub mod rust_prober;
@@ -34,5 +36,5 @@ pub mod types;
 // Re-export key types at crate root for convenience.
 pub use composite::CompositeProber;
 pub use prober::Prober;
-pub use rust_prober::RustProber;
-pub use types::{AudioTrack, DvInfo, MediaInfo, SubtitleTrack, VideoTrack};
+pub use rust_prober::{detect_container, RustProber};
+pub use types::{AudioTrack, DvElType, DvInfo, MediaInfo, SubtitleTrack, VideoTrack};
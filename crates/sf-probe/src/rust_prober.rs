@@ -1,6 +1,7 @@
 //! Pure-Rust media file prober implementation.
 //!
-//! Uses the `matroska` crate for MKV/WebM files and `mp4parse` for MP4/M4V files.
+//! Uses the `matroska` crate for MKV/WebM files and `mp4parse` for MP4/M4V files,
+//! plus the native demuxers in [`crate::demux`] for MPEG-TS and AVI.
 //! Performs best-effort HDR and Dolby Vision detection from codec private data.
 
 use std::fs::File;
@@ -10,13 +11,15 @@ use std::time::Duration;
 
 use sf_core::{AudioCodec, Container, HdrFormat, VideoCodec};
 
+use crate::demux;
 use crate::hdr;
 use crate::prober::Prober;
 use crate::types::{AudioTrack, DvInfo, MediaInfo, SubtitleTrack, VideoTrack};
 
 /// A pure-Rust [`Prober`] implementation.
 ///
-/// Supports MKV (Matroska), MP4, and M4V files using native Rust parsing crates.
+/// Supports MKV (Matroska), WebM, MP4, M4V, MPEG-TS (including BDAV `.m2ts`)
+/// and AVI files using native Rust parsing.
 /// No external tools (ffprobe, mediainfo, etc.) are required.
 pub struct RustProber;
 
@@ -40,7 +43,10 @@ impl Prober for RustProber {
 
     fn supports(&self, path: &Path) -> bool {
         match path.extension().and_then(|e| e.to_str()) {
-            Some(ext) => matches!(ext.to_lowercase().as_str(), "mkv" | "mp4" | "m4v"),
+            Some(ext) => matches!(
+                ext.to_lowercase().as_str(),
+                "mkv" | "webm" | "mp4" | "m4v" | "ts" | "m2ts" | "mts" | "avi"
+            ),
             None => false,
         }
     }
@@ -48,8 +54,10 @@ impl Prober for RustProber {
     fn probe(&self, path: &Path) -> sf_core::Result<MediaInfo> {
         let container = detect_container(path)?;
         match container {
-            Container::Mkv => probe_mkv(path),
+            Container::Mkv | Container::Webm => probe_mkv(path, container),
             Container::Mp4 => probe_mp4(path),
+            Container::MpegTs => demux::probe_ts(path),
+            Container::Avi => demux::probe_avi(path),
         }
     }
 }
@@ -59,14 +67,25 @@ impl Prober for RustProber {
 // ---------------------------------------------------------------------------
 
 /// Detect container format from file magic bytes, with extension fallback.
-fn detect_container(path: &Path) -> sf_core::Result<Container> {
+///
+/// Matroska files are told apart from WebM by their EBML DocType. The other
+/// probers defer to this where their tool can't tell the two apart.
+pub fn detect_container(path: &Path) -> sf_core::Result<Container> {
     let mut file = File::open(path).map_err(|e| sf_core::Error::Probe(e.to_string()))?;
 
-    let mut magic = [0u8; 12];
-    if file.read(&mut magic).unwrap_or(0) >= 8 {
+    let mut magic = [0u8; 200];
+    let read = file.read(&mut magic).unwrap_or(0);
+    if read >= 8 {
         // EBML header (Matroska/WebM).
         if magic[0..4] == [0x1A, 0x45, 0xDF, 0xA3] {
-            return Ok(Container::Mkv);
+            return Ok(match ebml_doc_type(&magic[..read]) {
+                Some(b"webm") => Container::Webm,
+                _ => Container::Mkv,
+            });
+        }
+        // RIFF AVI.
+        if &magic[0..4] == b"RIFF" && &magic[8..12] == b"AVI " {
+            return Ok(Container::Avi);
         }
         // ftyp box (MP4/MOV).
         if &magic[4..8] == b"ftyp"
@@ -76,13 +95,22 @@ fn detect_container(path: &Path) -> sf_core::Result<Container> {
         {
             return Ok(Container::Mp4);
         }
+        // MPEG-TS sync bytes at a 188-byte stride, or 192-byte BDAV packets.
+        if (read >= 189 && magic[0] == 0x47 && magic[188] == 0x47)
+            || (read >= 197 && magic[4] == 0x47 && magic[196] == 0x47)
+        {
+            return Ok(Container::MpegTs);
+        }
     }
 
     // Fallback to extension.
     match path.extension().and_then(|e| e.to_str()) {
         Some(ext) => match ext.to_lowercase().as_str() {
-            "mkv" | "webm" => Ok(Container::Mkv),
+            "mkv" => Ok(Container::Mkv),
+            "webm" => Ok(Container::Webm),
             "mp4" | "m4v" | "mov" => Ok(Container::Mp4),
+            "ts" | "m2ts" | "mts" => Ok(Container::MpegTs),
+            "avi" => Ok(Container::Avi),
             other => Err(sf_core::Error::Probe(format!(
                 "unsupported container extension: {other}"
             ))),
@@ -93,11 +121,25 @@ fn detect_container(path: &Path) -> sf_core::Result<Container> {
     }
 }
 
+/// Extract the DocType string from an EBML header.
+///
+/// Scans for the DocType element (ID `0x4282`) and returns its value.
+fn ebml_doc_type(header: &[u8]) -> Option<&[u8]> {
+    let pos = header.windows(2).position(|w| w == [0x42, 0x82])?;
+    // The size is a 1-byte EBML vint in practice (`0x80 | len`).
+    let size_byte = *header.get(pos + 2)?;
+    if size_byte & 0x80 == 0 {
+        return None;
+    }
+    let len = (size_byte & 0x7F) as usize;
+    header.get(pos + 3..pos + 3 + len)
+}
+
 // ---------------------------------------------------------------------------
 // MKV probing
 // ---------------------------------------------------------------------------
 
-fn probe_mkv(path: &Path) -> sf_core::Result<MediaInfo> {
+fn probe_mkv(path: &Path, container: Container) -> sf_core::Result<MediaInfo> {
     let file = File::open(path).map_err(|e| sf_core::Error::Probe(e.to_string()))?;
     let file_size = file
         .metadata()
@@ -129,6 +171,7 @@ fn probe_mkv(path: &Path) -> sf_core::Result<MediaInfo> {
 
                 let mut hdr_format = HdrFormat::Sdr;
                 let mut dv: Option<DvInfo> = None;
+                let mut hdr10plus = false;
 
                 // Attempt HDR detection from codec private data for HEVC tracks.
                 if codec == VideoCodec::H265 {
@@ -140,6 +183,7 @@ fn probe_mkv(path: &Path) -> sf_core::Result<MediaInfo> {
                         } else if let Some(detection) = hdr::detect_hdr_from_hevc(private) {
                             hdr_format = detection.format;
                             dv = detection.dv_info;
+                            hdr10plus = detection.hdr10plus;
                         }
                     }
                 }
@@ -153,6 +197,7 @@ fn probe_mkv(path: &Path) -> sf_core::Result<MediaInfo> {
                     frame_rate,
                     bit_depth: None, // matroska crate does not expose bit depth
                     hdr_format,
+                    hdr10plus,
                     dolby_vision: dv,
                     default: track.default,
                     language,
@@ -194,7 +239,7 @@ fn probe_mkv(path: &Path) -> sf_core::Result<MediaInfo> {
     Ok(MediaInfo {
         file_path: path.to_path_buf(),
         file_size,
-        container: Container::Mkv,
+        container,
         duration,
         video_tracks,
         audio_tracks,
@@ -297,6 +342,7 @@ fn mp4_video_track(track: &mp4parse::Track, is_first: bool) -> Option<VideoTrack
         frame_rate: None,
         bit_depth: None,
         hdr_format: HdrFormat::Sdr,
+        hdr10plus: false,
         dolby_vision: None,
         default: is_first,
         language: None,
@@ -418,3 +464,28 @@ fn is_atmos_hint(codec_id: &str, track_name: Option<&str>) -> bool {
         })
         .unwrap_or(false)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn supports_native_demuxer_extensions() {
+        let prober = RustProber::new();
+        for name in ["a.mkv", "a.webm", "a.MP4", "a.ts", "a.m2ts", "a.avi"] {
+            assert!(prober.supports(Path::new(name)), "{name}");
+        }
+        assert!(!prober.supports(Path::new("a.wmv")));
+        assert!(!prober.supports(Path::new("a.flv")));
+    }
+
+    #[test]
+    fn ebml_doc_type_extraction() {
+        let header = [
+            0x1A, 0x45, 0xDF, 0xA3, 0x9F, 0x42, 0x86, 0x81, 0x01, 0x42, 0x82, 0x84, b'w', b'e',
+            b'b', b'm', 0x42, 0x87, 0x81, 0x04,
+        ];
+        assert_eq!(ebml_doc_type(&header), Some(&b"webm"[..]));
+        assert_eq!(ebml_doc_type(&header[..8]), None);
+    }
+}
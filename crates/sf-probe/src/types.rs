@@ -100,6 +100,10 @@ pub struct VideoTrack {
     pub hdr_format: HdrFormat,
     /// Dolby Vision info (if detected).
     pub dolby_vision: Option<DvInfo>,
+    /// Whether HDR10+ dynamic metadata was detected, including alongside
+    /// Dolby Vision (where `hdr_format` reports Dolby Vision).
+    #[serde(default)]
+    pub hdr10plus: bool,
     /// Whether this is the default track.
     pub default: bool,
     /// Language code (ISO 639-2 or IETF).
@@ -147,6 +151,22 @@ pub struct DvInfo {
     pub el_present: bool,
     /// Whether a base layer is present.
     pub bl_present: bool,
+    /// Kind of enhancement layer. Only known when an RPU was parsed; the
+    /// configuration record doesn't say.
+    #[serde(default)]
+    pub el_type: Option<DvElType>,
+}
+
+/// Kind of Dolby Vision enhancement layer (profile 7).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DvElType {
+    /// Minimal enhancement layer: carries next to no picture information and
+    /// can be dropped without visible loss.
+    Mel,
+    /// Full enhancement layer: carries real picture detail (up to 12-bit)
+    /// that is lost when the layer is dropped.
+    Fel,
 }
 
 #[cfg(test)]
@@ -168,6 +188,7 @@ mod tests {
                     frame_rate: None,
                     bit_depth: None,
                     hdr_format: HdrFormat::Sdr,
+                    hdr10plus: false,
                     dolby_vision: None,
                     default: false,
                     language: None,
@@ -179,6 +200,7 @@ mod tests {
                     frame_rate: None,
                     bit_depth: None,
                     hdr_format: HdrFormat::Sdr,
+                    hdr10plus: false,
                     dolby_vision: None,
                     default: true,
                     language: None,
@@ -207,6 +229,7 @@ mod tests {
                 frame_rate: None,
                 bit_depth: None,
                 hdr_format: HdrFormat::Sdr,
+                hdr10plus: false,
                 dolby_vision: None,
                 default: false,
                 language: None,
@@ -282,6 +305,7 @@ mod tests {
                 frame_rate: Some(24.0),
                 bit_depth: Some(8),
                 hdr_format: HdrFormat::Sdr,
+                hdr10plus: false,
                 dolby_vision: None,
                 default: true,
                 language: None,
@@ -313,6 +337,7 @@ mod tests {
                 frame_rate: None,
                 bit_depth: Some(10),
                 hdr_format: HdrFormat::Hdr10,
+                hdr10plus: false,
                 dolby_vision: None,
                 default: true,
                 language: None,
@@ -344,6 +369,7 @@ mod tests {
                 frame_rate: None,
                 bit_depth: Some(8),
                 hdr_format: HdrFormat::Sdr,
+                hdr10plus: false,
                 dolby_vision: None,
                 default: true,
                 language: None,
@@ -375,6 +401,7 @@ mod tests {
                 frame_rate: None,
                 bit_depth: Some(8),
                 hdr_format: HdrFormat::Sdr,
+                hdr10plus: false,
                 dolby_vision: None,
                 default: true,
                 language: None,
@@ -407,6 +434,7 @@ mod tests {
                 frame_rate: None,
                 bit_depth: Some(8),
                 hdr_format: HdrFormat::Sdr,
+                hdr10plus: false,
                 dolby_vision: None,
                 default: true,
                 language: None,
@@ -459,11 +487,13 @@ mod tests {
                 frame_rate: Some(23.976),
                 bit_depth: Some(10),
                 hdr_format: HdrFormat::Hdr10,
+                hdr10plus: false,
                 dolby_vision: Some(DvInfo {
                     profile: 7,
                     rpu_present: true,
                     el_present: false,
                     bl_present: true,
+                    el_type: None,
                 }),
                 default: true,
                 language: Some("eng".to_string()),
@@ -1,10 +1,11 @@
 //! Action configurations that describe what to do when a rule matches.
 
 use serde::{Deserialize, Serialize};
-use sf_core::{AudioCodec, Container, StreamType};
+use sf_core::config::ResourceClass;
+use sf_core::{AudioCodec, Container, StreamType, SubtitleFormat, VideoCodec};
 
 /// An action to perform when a rule matches a media file.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ActionConfig {
     /// Convert Dolby Vision to a different profile.
@@ -12,6 +13,30 @@ pub enum ActionConfig {
         /// Target DV profile number.
         target_profile: u8,
     },
+    /// Add Dolby Vision to an HDR10 or HDR10+ file by injecting an RPU
+    /// generated from its static (or HDR10+ dynamic) metadata.
+    DvGenerate {
+        /// DV profile to generate. Only 8 (8.1, HDR10-compatible) is
+        /// supported; 5 needs an IPTPQc2 base layer and is rejected.
+        #[serde(default = "default_dv_generate_profile")]
+        profile: u8,
+    },
+    /// Extract HDR10+ dynamic metadata to a `<name>.hdr10plus.json` sidecar
+    /// next to the media file. The media file is left unchanged.
+    #[serde(rename = "extract_hdr10plus")]
+    ExtractHdr10Plus,
+    /// Inject HDR10+ dynamic metadata from a JSON file, e.g. after an
+    /// external re-encode dropped it. (The `transcode` action keeps HDR10+
+    /// on its own.)
+    #[serde(rename = "inject_hdr10plus")]
+    InjectHdr10Plus {
+        /// Metadata file (None = the `<name>.hdr10plus.json` sidecar).
+        metadata: Option<String>,
+    },
+    /// Strip HDR10+ dynamic metadata, leaving HDR10, for devices that
+    /// mishandle it.
+    #[serde(rename = "remove_hdr10plus")]
+    RemoveHdr10Plus,
     /// Remux the file into a different container.
     Remux {
         /// Target container format.
@@ -47,6 +72,173 @@ pub enum ActionConfig {
         /// Override preset (None = from ConversionConfig).
         preset: Option<String>,
     },
+    /// Re-encode the video to HEVC (libx265) or AV1 (libsvtav1), copying
+    /// audio and subtitles and passing through HDR10 metadata.
+    Transcode {
+        /// Target video codec (`h265` or `av1`).
+        codec: VideoCodec,
+        /// Constant rate factor (None = 20 for HEVC, 30 for AV1).
+        crf: Option<u32>,
+        /// Encoder preset (None = `slow` for HEVC, `6` for AV1).
+        preset: Option<String>,
+        /// Encoder tune (e.g. `grain`).
+        tune: Option<String>,
+        /// Output bit depth, 8 or 10 (None = 10).
+        bit_depth: Option<u8>,
+        /// Discard the encode and keep the original if the output is not
+        /// smaller than the input.
+        #[serde(default = "default_true")]
+        require_smaller: bool,
+    },
+    /// Add a stereo downmix of a surround track, loudness-normalized with a
+    /// two-pass EBU R128 `loudnorm`.
+    DownmixAudio {
+        /// 0-based audio track to downmix (None = the track with the most
+        /// channels).
+        source_track: Option<usize>,
+        /// Target integrated loudness in LUFS (None = -23, EBU R128).
+        target_lufs: Option<f64>,
+        /// Boost dialogue from the centre channel before downmixing.
+        #[serde(default)]
+        dialogue_boost: bool,
+        /// Codec of the new track (None = AAC).
+        codec: Option<AudioCodec>,
+        /// Bitrate of the new track (None = 192k).
+        bitrate: Option<String>,
+        /// Track title (None = "Stereo").
+        title: Option<String>,
+        /// ISO 639-2 language (None = the source track's language).
+        language: Option<String>,
+        /// Make the new track the default audio track.
+        #[serde(default)]
+        default_track: bool,
+    },
+    /// Reorder audio tracks by language and codec preference and make the
+    /// best one the default, without re-encoding.
+    SetTrackDefaults {
+        /// Preferred audio languages, in order.
+        languages: Vec<String>,
+        /// Preferred codecs, in order, ranking tracks of the same language.
+        #[serde(default)]
+        codecs: Vec<AudioCodec>,
+        /// Move the preferred tracks first; when `false` only the default
+        /// flag changes.
+        #[serde(default = "default_true")]
+        reorder: bool,
+    },
+    /// Extract text subtitle tracks to sidecar files next to the media file
+    /// (`<name>.<lang>[.forced].<ext>`). The media file is left unchanged.
+    ExtractSubtitles {
+        /// Languages to extract (None = every text track).
+        languages: Option<Vec<String>>,
+        /// Sidecar format.
+        #[serde(default)]
+        format: SubtitleFormat,
+    },
+    /// Convert ASS/SSA subtitle tracks to SRT.
+    ConvertSubtitles {
+        /// Languages to convert (None = every ASS/SSA track).
+        languages: Option<Vec<String>>,
+    },
+    /// Set the default and forced flags of subtitle tracks by language.
+    SetSubtitleFlags {
+        /// Language of the track to mark default, preferring a track that is
+        /// not forced; every other track loses the flag (None = unchanged).
+        default_language: Option<String>,
+        /// Languages whose tracks are marked forced; every other track loses
+        /// the flag (None = unchanged).
+        forced_languages: Option<Vec<String>>,
+    },
+    /// Reorder subtitle tracks by language preference.
+    ReorderSubtitles {
+        /// Preferred languages, in order. Tracks in other languages follow in
+        /// their original order.
+        languages: Vec<String>,
+    },
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_dv_generate_profile() -> u8 {
+    8
+}
+
+/// The pipeline stage an action belongs to.
+///
+/// When actions from several rules are combined into one pipeline they are
+/// ordered by stage: tracks are stripped before the file is remuxed, and
+/// remuxed before anything is re-encoded. User commands run last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ActionStage {
+    Strip,
+    Remux,
+    Audio,
+    Subtitle,
+    Video,
+    Exec,
+}
+
+impl ActionConfig {
+    /// The stage this action runs in when pipelines are merged.
+    pub fn stage(&self) -> ActionStage {
+        match self {
+            ActionConfig::StripTracks { .. } => ActionStage::Strip,
+            ActionConfig::Remux { .. } => ActionStage::Remux,
+            ActionConfig::AddCompatAudio { .. }
+            | ActionConfig::DownmixAudio { .. }
+            | ActionConfig::SetTrackDefaults { .. } => ActionStage::Audio,
+            ActionConfig::ExtractSubtitles { .. }
+            | ActionConfig::ConvertSubtitles { .. }
+            | ActionConfig::SetSubtitleFlags { .. }
+            | ActionConfig::ReorderSubtitles { .. } => ActionStage::Subtitle,
+            ActionConfig::DvConvert { .. }
+            | ActionConfig::DvGenerate { .. }
+            | ActionConfig::ExtractHdr10Plus
+            | ActionConfig::InjectHdr10Plus { .. }
+            | ActionConfig::RemoveHdr10Plus
+            | ActionConfig::ProfileBConvert { .. }
+            | ActionConfig::Transcode { .. } => ActionStage::Video,
+            ActionConfig::Exec { .. } => ActionStage::Exec,
+        }
+    }
+
+    /// The load this action puts on the machine, used to cap how many jobs
+    /// of each kind run at once.
+    pub fn resource_class(&self) -> ResourceClass {
+        match self {
+            ActionConfig::ProfileBConvert { .. } | ActionConfig::Transcode { .. } => {
+                ResourceClass::Encode
+            }
+            ActionConfig::DvConvert { .. }
+            | ActionConfig::DvGenerate { .. }
+            | ActionConfig::ExtractHdr10Plus
+            | ActionConfig::InjectHdr10Plus { .. }
+            | ActionConfig::RemoveHdr10Plus
+            | ActionConfig::Remux { .. }
+            | ActionConfig::AddCompatAudio { .. }
+            | ActionConfig::DownmixAudio { .. }
+            | ActionConfig::SetTrackDefaults { .. }
+            | ActionConfig::StripTracks { .. }
+            | ActionConfig::ConvertSubtitles { .. }
+            | ActionConfig::SetSubtitleFlags { .. }
+            | ActionConfig::ReorderSubtitles { .. } => ResourceClass::Remux,
+            ActionConfig::ExtractSubtitles { .. } | ActionConfig::Exec { .. } => {
+                ResourceClass::Light
+            }
+        }
+    }
+
+    /// Whether this action only starts inside the configured processing
+    /// windows: video encodes and Dolby Vision conversions.
+    pub fn is_heavy(&self) -> bool {
+        self.resource_class() == ResourceClass::Encode
+            || matches!(
+                self,
+                ActionConfig::DvConvert { .. } | ActionConfig::DvGenerate { .. }
+            )
+    }
 }
 
 #[cfg(test)]
@@ -64,6 +256,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn dv_generate_defaults_to_profile_8() {
+        let action: ActionConfig = serde_json::from_str(r#"{"type":"dv_generate"}"#).unwrap();
+        assert_eq!(action, ActionConfig::DvGenerate { profile: 8 });
+        assert!(action.is_heavy());
+        assert_eq!(action.stage(), ActionStage::Video);
+    }
+
+    #[test]
+    fn hdr10plus_actions_serde() {
+        let json = serde_json::to_value(ActionConfig::RemoveHdr10Plus).unwrap();
+        assert_eq!(json, serde_json::json!({ "type": "remove_hdr10plus" }));
+
+        let action: ActionConfig = serde_json::from_str(r#"{"type":"extract_hdr10plus"}"#).unwrap();
+        assert_eq!(action, ActionConfig::ExtractHdr10Plus);
+
+        let action: ActionConfig =
+            serde_json::from_str(r#"{"type":"inject_hdr10plus","metadata":"/m/a.json"}"#).unwrap();
+        assert_eq!(
+            action,
+            ActionConfig::InjectHdr10Plus {
+                metadata: Some("/m/a.json".into())
+            }
+        );
+    }
+
     #[test]
     fn serde_roundtrip_remux() {
         let action = ActionConfig::Remux {
@@ -124,6 +342,84 @@ mod tests {
         }
     }
 
+    #[test]
+    fn deserialize_transcode_defaults() {
+        let json = r#"{"type":"transcode","codec":"av1","crf":28}"#;
+        let action: ActionConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            action,
+            ActionConfig::Transcode {
+                codec: VideoCodec::Av1,
+                crf: Some(28),
+                preset: None,
+                tune: None,
+                bit_depth: None,
+                require_smaller: true,
+            }
+        );
+        assert_eq!(action.stage(), ActionStage::Video);
+    }
+
+    #[test]
+    fn deserialize_downmix_audio_defaults() {
+        let json = r#"{"type":"downmix_audio","target_lufs":-16.0,"dialogue_boost":true}"#;
+        let action: ActionConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            action,
+            ActionConfig::DownmixAudio {
+                source_track: None,
+                target_lufs: Some(-16.0),
+                dialogue_boost: true,
+                codec: None,
+                bitrate: None,
+                title: None,
+                language: None,
+                default_track: false,
+            }
+        );
+        assert_eq!(action.stage(), ActionStage::Audio);
+    }
+
+    #[test]
+    fn deserialize_set_track_defaults() {
+        let json =
+            r#"{"type":"set_track_defaults","languages":["eng","jpn"],"codecs":["truehd","eac3"]}"#;
+        let action: ActionConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            action,
+            ActionConfig::SetTrackDefaults {
+                languages: vec!["eng".into(), "jpn".into()],
+                codecs: vec![AudioCodec::TrueHd, AudioCodec::Eac3],
+                reorder: true,
+            }
+        );
+        assert_eq!(action.stage(), ActionStage::Audio);
+    }
+
+    #[test]
+    fn deserialize_subtitle_actions() {
+        let json = r#"{"type":"extract_subtitles","languages":["eng"]}"#;
+        let action: ActionConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            action,
+            ActionConfig::ExtractSubtitles {
+                languages: Some(vec!["eng".into()]),
+                format: SubtitleFormat::Srt,
+            }
+        );
+        assert_eq!(action.stage(), ActionStage::Subtitle);
+
+        let json = r#"{"type":"set_subtitle_flags","default_language":"eng"}"#;
+        let action: ActionConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            action,
+            ActionConfig::SetSubtitleFlags {
+                default_language: Some("eng".into()),
+                forced_languages: None,
+            }
+        );
+    }
+
     #[test]
     fn serde_roundtrip_exec() {
         let action = ActionConfig::Exec {
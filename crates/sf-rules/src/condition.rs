@@ -5,7 +5,9 @@
 
 use serde::{Deserialize, Serialize};
 use sf_core::{AudioCodec, Container, HdrFormat, VideoCodec};
-use sf_probe::MediaInfo;
+use sf_probe::{DvElType, MediaInfo};
+
+use crate::language::normalize_language;
 
 /// A leaf condition that evaluates a single property of a media file.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +21,13 @@ pub enum Condition {
     HdrFormat(Vec<HdrFormat>),
     /// Matches if the Dolby Vision profile is in the given list.
     DolbyVisionProfile(Vec<u8>),
+    /// Matches if the presence of a Dolby Vision full enhancement layer
+    /// (FEL) equals the given value. A layer whose type is unknown counts as
+    /// absent.
+    HasDolbyVisionFel(bool),
+    /// Matches if the presence of HDR10+ dynamic metadata equals the given
+    /// value, including HDR10+ carried alongside Dolby Vision.
+    HasHdr10Plus(bool),
     /// Matches if the primary video resolution is >= both width and height.
     MinResolution { width: u32, height: u32 },
     /// Matches if the primary video resolution is <= both width and height.
@@ -31,6 +40,36 @@ pub enum Condition {
     MinBitDepth(u8),
     /// Matches on file extension (case-insensitive).
     FileExtension(Vec<String>),
+    /// Matches if any audio track has a channel count in the given list.
+    AudioChannels(Vec<u32>),
+    /// Matches if any audio track has at least the given number of channels.
+    MinAudioChannels(u32),
+    /// Matches if every audio track has at most the given number of channels.
+    ///
+    /// Files without audio tracks never match.
+    MaxAudioChannels(u32),
+    /// Matches if any audio track language is in the given list.
+    ///
+    /// See [`language_matches`] for the comparison rules.
+    AudioLanguage(Vec<String>),
+    /// Matches if any subtitle track language is in the given list.
+    SubtitleLanguage(Vec<String>),
+    /// Matches if the presence of subtitle tracks equals the given value.
+    HasSubtitles(bool),
+    /// Matches if the presence of a forced subtitle track equals the given value.
+    HasForcedSubtitles(bool),
+    /// Matches if the duration is >= the given number of seconds.
+    MinDuration(u64),
+    /// Matches if the duration is <= the given number of seconds.
+    MaxDuration(u64),
+    /// Matches if the file size is >= the given number of bytes.
+    MinFileSize(u64),
+    /// Matches if the file size is <= the given number of bytes.
+    MaxFileSize(u64),
+    /// Matches if the primary video frame rate is >= the given value.
+    MinFrameRate(f64),
+    /// Matches if the primary video frame rate is <= the given value.
+    MaxFrameRate(f64),
 }
 
 impl Condition {
@@ -59,6 +98,8 @@ impl Condition {
                     false
                 }
             }),
+            Condition::HasDolbyVisionFel(value) => has_fel(info) == *value,
+            Condition::HasHdr10Plus(value) => has_hdr10plus(info) == *value,
             Condition::MinResolution { width, height } => {
                 if let Some(video) = info.primary_video() {
                     video.width >= *width && video.height >= *height
@@ -99,15 +140,159 @@ impl Condition {
                     false
                 }
             }
+            Condition::AudioChannels(counts) => info
+                .audio_tracks
+                .iter()
+                .any(|track| counts.contains(&track.channels)),
+            Condition::MinAudioChannels(min) => {
+                info.audio_tracks.iter().any(|track| track.channels >= *min)
+            }
+            Condition::MaxAudioChannels(max) => {
+                !info.audio_tracks.is_empty()
+                    && info.audio_tracks.iter().all(|track| track.channels <= *max)
+            }
+            Condition::AudioLanguage(languages) => info
+                .audio_tracks
+                .iter()
+                .any(|track| language_matches(track.language.as_deref(), languages)),
+            Condition::SubtitleLanguage(languages) => info
+                .subtitle_tracks
+                .iter()
+                .any(|track| language_matches(track.language.as_deref(), languages)),
+            Condition::HasSubtitles(value) => info.subtitle_tracks.is_empty() != *value,
+            Condition::HasForcedSubtitles(value) => {
+                info.subtitle_tracks.iter().any(|track| track.forced) == *value
+            }
+            Condition::MinDuration(secs) => info
+                .duration
+                .is_some_and(|d| d.as_secs_f64() >= *secs as f64),
+            Condition::MaxDuration(secs) => info
+                .duration
+                .is_some_and(|d| d.as_secs_f64() <= *secs as f64),
+            Condition::MinFileSize(bytes) => info.file_size >= *bytes,
+            Condition::MaxFileSize(bytes) => info.file_size <= *bytes,
+            Condition::MinFrameRate(min) => info
+                .primary_video()
+                .and_then(|video| video.frame_rate)
+                .is_some_and(|fps| fps >= *min),
+            Condition::MaxFrameRate(max) => info
+                .primary_video()
+                .and_then(|video| video.frame_rate)
+                .is_some_and(|fps| fps <= *max),
+        }
+    }
+
+    /// The media property this condition inspects, as a JSON value.
+    ///
+    /// Used by [`explain`](crate::explain) to show what a condition was
+    /// compared against. Properties that are absent (no video track, unknown
+    /// duration) are returned as `null`; per-track properties are arrays.
+    pub fn actual_value(&self, info: &MediaInfo) -> serde_json::Value {
+        use serde_json::{json, Value};
+
+        let video = info.primary_video();
+        let languages = |langs: Vec<Option<&str>>| -> Value {
+            langs
+                .into_iter()
+                .map(|l| Value::from(l.unwrap_or("und")))
+                .collect()
+        };
+        match self {
+            Condition::Codec(_) => json!(video.map(|v| v.codec.to_string())),
+            Condition::Container(_) => json!(info.container.to_string()),
+            Condition::HdrFormat(_) => json!(video.map(|v| v.hdr_format.to_string())),
+            Condition::DolbyVisionProfile(_) => info
+                .video_tracks
+                .iter()
+                .filter_map(|t| t.dolby_vision.as_ref().map(|dv| dv.profile))
+                .collect(),
+            Condition::HasDolbyVisionFel(_) => json!(has_fel(info)),
+            Condition::HasHdr10Plus(_) => json!(has_hdr10plus(info)),
+            Condition::MinResolution { .. } | Condition::MaxResolution { .. } => {
+                json!(video.map(|v| format!("{}x{}", v.width, v.height)))
+            }
+            Condition::AudioCodec(_) => info
+                .audio_tracks
+                .iter()
+                .map(|t| Value::from(t.codec.to_string()))
+                .collect(),
+            Condition::HasAtmos(_) => info.audio_tracks.iter().map(|t| t.atmos).collect(),
+            Condition::MinBitDepth(_) => json!(video.and_then(|v| v.bit_depth)),
+            Condition::FileExtension(_) => json!(info
+                .file_path
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| e.to_lowercase())),
+            Condition::AudioChannels(_)
+            | Condition::MinAudioChannels(_)
+            | Condition::MaxAudioChannels(_) => {
+                info.audio_tracks.iter().map(|t| t.channels).collect()
+            }
+            Condition::AudioLanguage(_) => languages(
+                info.audio_tracks
+                    .iter()
+                    .map(|t| t.language.as_deref())
+                    .collect(),
+            ),
+            Condition::SubtitleLanguage(_) => languages(
+                info.subtitle_tracks
+                    .iter()
+                    .map(|t| t.language.as_deref())
+                    .collect(),
+            ),
+            Condition::HasSubtitles(_) => json!(!info.subtitle_tracks.is_empty()),
+            Condition::HasForcedSubtitles(_) => {
+                json!(info.subtitle_tracks.iter().any(|t| t.forced))
+            }
+            Condition::MinDuration(_) | Condition::MaxDuration(_) => {
+                json!(info.duration.map(|d| d.as_secs()))
+            }
+            Condition::MinFileSize(_) | Condition::MaxFileSize(_) => json!(info.file_size),
+            Condition::MinFrameRate(_) | Condition::MaxFrameRate(_) => {
+                json!(video.and_then(|v| v.frame_rate))
+            }
         }
     }
 }
 
+/// Whether any video track carries a Dolby Vision full enhancement layer.
+fn has_fel(info: &MediaInfo) -> bool {
+    info.video_tracks.iter().any(|track| {
+        track
+            .dolby_vision
+            .as_ref()
+            .is_some_and(|dv| dv.el_type == Some(DvElType::Fel))
+    })
+}
+
+/// Whether any video track carries HDR10+ dynamic metadata.
+fn has_hdr10plus(info: &MediaInfo) -> bool {
+    info.video_tracks
+        .iter()
+        .any(|track| track.hdr10plus || track.hdr_format == HdrFormat::Hdr10Plus)
+}
+
+/// Check whether a track language matches any entry in `languages`.
+///
+/// Comparison is case-insensitive, treats ISO 639-1 and 639-2 codes of the
+/// same language as equal (see [`normalize_language`]) and also matches on
+/// the primary subtag, so `"en"` matches a track tagged `"eng"` or `"en-US"`.
+/// A track without a language tag is treated as `"und"` (undetermined).
+pub fn language_matches(track_language: Option<&str>, languages: &[String]) -> bool {
+    let track = normalize_language(track_language.unwrap_or("und"));
+    let track_primary = normalize_language(track.split(['-', '_']).next().unwrap_or(&track));
+    languages.iter().any(|lang| {
+        let lang = normalize_language(lang);
+        lang == track || lang == track_primary
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use sf_probe::{AudioTrack, DvInfo, VideoTrack};
+    use sf_probe::{AudioTrack, DvInfo, SubtitleTrack, VideoTrack};
     use std::path::PathBuf;
+    use std::time::Duration;
 
     fn make_test_info() -> MediaInfo {
         MediaInfo {
@@ -122,11 +307,13 @@ mod tests {
                 frame_rate: Some(23.976),
                 bit_depth: Some(10),
                 hdr_format: HdrFormat::DolbyVision,
+                hdr10plus: false,
                 dolby_vision: Some(DvInfo {
                     profile: 7,
                     rpu_present: true,
                     el_present: true,
                     bl_present: true,
+                    el_type: None,
                 }),
                 default: true,
                 language: Some("eng".to_string()),
@@ -220,6 +407,36 @@ mod tests {
         assert!(!Condition::AudioCodec(vec![AudioCodec::Aac]).evaluate(&info));
     }
 
+    #[test]
+    fn has_dolby_vision_fel_matches() {
+        let mut info = make_test_info();
+        // Unknown layer type counts as no FEL.
+        assert!(!Condition::HasDolbyVisionFel(true).evaluate(&info));
+        assert!(Condition::HasDolbyVisionFel(false).evaluate(&info));
+
+        info.video_tracks[0].dolby_vision.as_mut().unwrap().el_type = Some(DvElType::Fel);
+        assert!(Condition::HasDolbyVisionFel(true).evaluate(&info));
+        assert_eq!(
+            Condition::HasDolbyVisionFel(true).actual_value(&info),
+            serde_json::json!(true)
+        );
+    }
+
+    #[test]
+    fn has_hdr10plus_matches() {
+        let mut info = make_test_info();
+        assert!(!Condition::HasHdr10Plus(true).evaluate(&info));
+
+        // HDR10+ alongside Dolby Vision.
+        info.video_tracks[0].hdr10plus = true;
+        assert!(Condition::HasHdr10Plus(true).evaluate(&info));
+        assert!(!Condition::HasHdr10Plus(false).evaluate(&info));
+
+        info.video_tracks[0].hdr10plus = false;
+        info.video_tracks[0].hdr_format = HdrFormat::Hdr10Plus;
+        assert!(Condition::HasHdr10Plus(true).evaluate(&info));
+    }
+
     #[test]
     fn has_atmos_matches() {
         let info = make_test_info();
@@ -277,4 +494,123 @@ mod tests {
         info.video_tracks[0].bit_depth = None;
         assert!(!Condition::MinBitDepth(8).evaluate(&info));
     }
+
+    fn make_rich_info() -> MediaInfo {
+        let mut info = make_test_info();
+        info.duration = Some(Duration::from_secs(9_600));
+        info.audio_tracks.push(AudioTrack {
+            codec: AudioCodec::Ac3,
+            channels: 2,
+            sample_rate: Some(48000),
+            language: Some("fr-CA".to_string()),
+            atmos: false,
+            default: false,
+        });
+        info.subtitle_tracks = vec![
+            SubtitleTrack {
+                codec: "PGS".to_string(),
+                language: Some("eng".to_string()),
+                forced: true,
+                default: false,
+            },
+            SubtitleTrack {
+                codec: "SRT".to_string(),
+                language: None,
+                forced: false,
+                default: false,
+            },
+        ];
+        info
+    }
+
+    #[test]
+    fn audio_channel_conditions() {
+        let info = make_rich_info();
+        assert!(Condition::AudioChannels(vec![2]).evaluate(&info));
+        assert!(!Condition::AudioChannels(vec![6]).evaluate(&info));
+        assert!(Condition::MinAudioChannels(8).evaluate(&info));
+        assert!(!Condition::MinAudioChannels(10).evaluate(&info));
+        assert!(Condition::MaxAudioChannels(8).evaluate(&info));
+        assert!(!Condition::MaxAudioChannels(2).evaluate(&info));
+
+        let mut stereo_only = info.clone();
+        stereo_only.audio_tracks.remove(0);
+        assert!(Condition::MaxAudioChannels(2).evaluate(&stereo_only));
+
+        stereo_only.audio_tracks.clear();
+        assert!(!Condition::MaxAudioChannels(2).evaluate(&stereo_only));
+    }
+
+    #[test]
+    fn language_conditions() {
+        let info = make_rich_info();
+        assert!(Condition::AudioLanguage(vec!["ENG".to_string()]).evaluate(&info));
+        assert!(Condition::AudioLanguage(vec!["fr".to_string()]).evaluate(&info));
+        assert!(!Condition::AudioLanguage(vec!["ger".to_string()]).evaluate(&info));
+        assert!(Condition::SubtitleLanguage(vec!["eng".to_string()]).evaluate(&info));
+        assert!(Condition::SubtitleLanguage(vec!["und".to_string()]).evaluate(&info));
+        assert!(!Condition::SubtitleLanguage(vec!["fre".to_string()]).evaluate(&info));
+    }
+
+    #[test]
+    fn language_codes_are_normalized() {
+        let info = make_rich_info();
+        // The audio tracks are tagged "fr-CA" and "eng".
+        assert!(Condition::AudioLanguage(vec!["en".to_string()]).evaluate(&info));
+        assert!(Condition::AudioLanguage(vec!["fra".to_string()]).evaluate(&info));
+        assert!(Condition::AudioLanguage(vec!["fre".to_string()]).evaluate(&info));
+        assert!(!Condition::AudioLanguage(vec!["de".to_string()]).evaluate(&info));
+        assert!(language_matches(Some("deu"), &["ger".to_string()]));
+        assert!(language_matches(Some("DE"), &["deu".to_string()]));
+    }
+
+    #[test]
+    fn subtitle_presence_conditions() {
+        let mut info = make_rich_info();
+        assert!(Condition::HasSubtitles(true).evaluate(&info));
+        assert!(Condition::HasForcedSubtitles(true).evaluate(&info));
+        assert!(!Condition::HasForcedSubtitles(false).evaluate(&info));
+
+        info.subtitle_tracks.clear();
+        assert!(Condition::HasSubtitles(false).evaluate(&info));
+        assert!(Condition::HasForcedSubtitles(false).evaluate(&info));
+    }
+
+    #[test]
+    fn duration_size_and_frame_rate_conditions() {
+        let info = make_rich_info();
+        assert!(Condition::MinDuration(150 * 60).evaluate(&info));
+        assert!(!Condition::MaxDuration(150 * 60).evaluate(&info));
+        assert!(Condition::MinFileSize(1024 * 1024 * 1024).evaluate(&info));
+        assert!(!Condition::MinFileSize(40 * 1024 * 1024 * 1024).evaluate(&info));
+        assert!(Condition::MaxFileSize(40 * 1024 * 1024 * 1024).evaluate(&info));
+        assert!(Condition::MinFrameRate(23.0).evaluate(&info));
+        assert!(!Condition::MinFrameRate(50.0).evaluate(&info));
+        assert!(Condition::MaxFrameRate(30.0).evaluate(&info));
+
+        let unknown = make_test_info();
+        assert!(!Condition::MinDuration(0).evaluate(&unknown));
+        assert!(!Condition::MaxDuration(u64::MAX).evaluate(&unknown));
+    }
+
+    #[test]
+    fn new_conditions_serde_shape() {
+        let json = serde_json::to_value(Condition::MinDuration(9000)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"type": "min_duration", "value": 9000})
+        );
+
+        let json = serde_json::to_value(Condition::AudioLanguage(vec!["eng".into()])).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"type": "audio_language", "value": ["eng"]})
+        );
+
+        let back: Condition = serde_json::from_value(
+            serde_json::json!({"type": "has_forced_subtitles", "value": true}),
+        )
+        .unwrap();
+        assert!(matches!(back, Condition::HasForcedSubtitles(true)));
+    }
 }
@@ -0,0 +1,234 @@
+//! Tokenizer for the rule DSL.
+
+use super::ParseError;
+
+/// The kind of a lexed token.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TokenKind {
+    /// A bare word: field names, keywords and unquoted values.
+    Word(String),
+    /// A double-quoted string with escapes resolved.
+    Str(String),
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+    Eq,
+    NotEq,
+    Ge,
+    Le,
+    Gt,
+    Lt,
+    Eof,
+}
+
+impl TokenKind {
+    /// Short description used in error messages.
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            TokenKind::Word(w) => format!("`{w}`"),
+            TokenKind::Str(s) => format!("\"{s}\""),
+            TokenKind::LParen => "`(`".into(),
+            TokenKind::RParen => "`)`".into(),
+            TokenKind::LBracket => "`[`".into(),
+            TokenKind::RBracket => "`]`".into(),
+            TokenKind::Comma => "`,`".into(),
+            TokenKind::Eq => "`==`".into(),
+            TokenKind::NotEq => "`!=`".into(),
+            TokenKind::Ge => "`>=`".into(),
+            TokenKind::Le => "`<=`".into(),
+            TokenKind::Gt => "`>`".into(),
+            TokenKind::Lt => "`<`".into(),
+            TokenKind::Eof => "end of input".into(),
+        }
+    }
+}
+
+/// A token together with the byte offset where it starts.
+#[derive(Debug, Clone)]
+pub(crate) struct Token {
+    pub kind: TokenKind,
+    pub offset: usize,
+}
+
+/// Characters allowed in a bare word.
+pub(crate) fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '.' | '-' | '+')
+}
+
+/// Split `source` into tokens. The result always ends with [`TokenKind::Eof`].
+pub(crate) fn tokenize(source: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some(&(offset, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        if c == '#' {
+            while chars.next_if(|&(_, c)| c != '\n').is_some() {}
+            continue;
+        }
+
+        let kind = match c {
+            '(' => single(&mut chars, TokenKind::LParen),
+            ')' => single(&mut chars, TokenKind::RParen),
+            '[' => single(&mut chars, TokenKind::LBracket),
+            ']' => single(&mut chars, TokenKind::RBracket),
+            ',' => single(&mut chars, TokenKind::Comma),
+            '=' | '!' | '>' | '<' => {
+                chars.next();
+                let followed_by_eq = chars.next_if(|&(_, c)| c == '=').is_some();
+                match (c, followed_by_eq) {
+                    ('=', true) => TokenKind::Eq,
+                    ('!', true) => TokenKind::NotEq,
+                    ('>', true) => TokenKind::Ge,
+                    ('<', true) => TokenKind::Le,
+                    ('>', false) => TokenKind::Gt,
+                    ('<', false) => TokenKind::Lt,
+                    ('=', false) => {
+                        return Err(ParseError::new(
+                            source,
+                            offset,
+                            "unexpected `=`, use `==` for equality",
+                        ))
+                    }
+                    _ => {
+                        return Err(ParseError::new(
+                            source,
+                            offset,
+                            "unexpected `!`, use `not` or `!=`",
+                        ))
+                    }
+                }
+            }
+            '"' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, c @ ('"' | '\\'))) => value.push(c),
+                            Some((pos, other)) => {
+                                return Err(ParseError::new(
+                                    source,
+                                    pos,
+                                    format!("unknown escape `\\{other}` in string"),
+                                ))
+                            }
+                            None => {
+                                return Err(ParseError::new(source, offset, "unterminated string"))
+                            }
+                        },
+                        Some((_, c)) => value.push(c),
+                        None => return Err(ParseError::new(source, offset, "unterminated string")),
+                    }
+                }
+                TokenKind::Str(value)
+            }
+            c if is_word_char(c) => {
+                let mut word = String::new();
+                while let Some((_, c)) = chars.next_if(|&(_, c)| is_word_char(c)) {
+                    word.push(c);
+                }
+                TokenKind::Word(word)
+            }
+            other => {
+                return Err(ParseError::new(
+                    source,
+                    offset,
+                    format!("unexpected character `{other}`"),
+                ))
+            }
+        };
+        tokens.push(Token { kind, offset });
+    }
+
+    tokens.push(Token {
+        kind: TokenKind::Eof,
+        offset: source.len(),
+    });
+    Ok(tokens)
+}
+
+fn single(
+    chars: &mut std::iter::Peekable<std::str::CharIndices<'_>>,
+    kind: TokenKind,
+) -> TokenKind {
+    chars.next();
+    kind
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<TokenKind> {
+        tokenize(source)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.kind)
+            .collect()
+    }
+
+    #[test]
+    fn tokenizes_operators_and_words() {
+        assert_eq!(
+            kinds("res>=3840x2160 and(a!=b)"),
+            vec![
+                TokenKind::Word("res".into()),
+                TokenKind::Ge,
+                TokenKind::Word("3840x2160".into()),
+                TokenKind::Word("and".into()),
+                TokenKind::LParen,
+                TokenKind::Word("a".into()),
+                TokenKind::NotEq,
+                TokenKind::Word("b".into()),
+                TokenKind::RParen,
+                TokenKind::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenizes_strings_with_escapes() {
+        assert_eq!(
+            kinds(r#"["a \"b\"", "c\\d"]"#),
+            vec![
+                TokenKind::LBracket,
+                TokenKind::Str("a \"b\"".into()),
+                TokenKind::Comma,
+                TokenKind::Str("c\\d".into()),
+                TokenKind::RBracket,
+                TokenKind::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn records_offsets_and_skips_comments() {
+        let tokens = tokenize("# hi\n  atmos").unwrap();
+        assert_eq!(tokens[0].kind, TokenKind::Word("atmos".into()));
+        assert_eq!(tokens[0].offset, 7);
+        assert_eq!(tokens[1].offset, 12);
+    }
+
+    #[test]
+    fn rejects_single_equals() {
+        let err = tokenize("codec = h265").unwrap_err();
+        assert_eq!(err.column, 7);
+        assert!(err.message.contains("`==`"));
+    }
+
+    #[test]
+    fn rejects_unterminated_strings() {
+        for source in [r#"extension == "mkv"#, r#"extension == "mkv\"#] {
+            let err = tokenize(source).unwrap_err();
+            assert_eq!(err.column, 14);
+            assert_eq!(err.message, "unterminated string");
+        }
+    }
+}
@@ -0,0 +1,359 @@
+//! A human-writable text syntax for [`Expr`](crate::Expr).
+//!
+//! Rules can be written as nested JSON, but most people would rather type
+//!
+//! ```text
+//! codec in [h265] and dv_profile == 7 and not container == mp4
+//! ```
+//!
+//! [`parse`] compiles such text into an [`Expr`](crate::Expr), and
+//! [`format`] renders any expression back into canonical text. Text produced
+//! by [`format`] always parses back to an equivalent expression.
+//!
+//! ## Grammar
+//!
+//! ```text
+//! expr       := or
+//! or         := and ("or" and)*
+//! and        := unary ("and" unary)*
+//! unary      := "not" unary | "(" expr ")" | "true" | "false" | comparison
+//! comparison := field "==" value
+//!             | field "!=" value
+//!             | field "in" "[" value ("," value)* "]"
+//!             | field "not" "in" "[" ... "]"
+//!             | field ">=" value
+//!             | field "<=" value
+//!             | field                      (boolean fields only)
+//! ```
+//!
+//! Keywords are case-insensitive, `#` starts a comment that runs to the end
+//! of the line, and values containing spaces or punctuation can be quoted
+//! (`extension == "mkv"`). `true` is the empty `and` (always matches) and
+//! `false` the empty `or` (never matches).
+//!
+//! ## Fields
+//!
+//! | Field                  | Operators             | Example                      |
+//! |------------------------|-----------------------|------------------------------|
+//! | `codec`                | `==` `!=` `in`        | `codec in [h265, av1]`       |
+//! | `container`            | `==` `!=` `in`        | `container == mkv`           |
+//! | `hdr`                  | `==` `!=` `in`        | `hdr in [hdr10, dolbyvision]`|
+//! | `dv_profile`           | `==` `!=` `in`        | `dv_profile == 7`            |
+//! | `dv_fel`               | `==` `!=` bare        | `dv_profile == 7 and dv_fel` |
+//! | `hdr10plus`            | `==` `!=` bare        | `not hdr10plus`              |
+//! | `resolution`           | `>=` `<=`             | `resolution >= 3840x2160`    |
+//! | `audio_codec`          | `==` `!=` `in`        | `audio_codec == truehd`      |
+//! | `atmos`                | `==` `!=` bare        | `atmos`                      |
+//! | `bit_depth`            | `>=`                  | `bit_depth >= 10`            |
+//! | `extension`            | `==` `!=` `in`        | `extension in [mkv, mp4]`    |
+//! | `audio_channels`       | `==` `!=` `in` `>=` `<=` | `audio_channels >= 6`     |
+//! | `audio_language`       | `==` `!=` `in`        | `audio_language == eng`      |
+//! | `subtitle_language`    | `==` `!=` `in`        | `subtitle_language == eng`   |
+//! | `has_subtitles`        | `==` `!=` bare        | `not has_subtitles`          |
+//! | `has_forced_subtitles` | `==` `!=` bare        | `has_forced_subtitles`       |
+//! | `duration`             | `>=` `<=`             | `duration >= 150m`           |
+//! | `file_size`            | `>=` `<=`             | `file_size <= 4GiB`          |
+//! | `frame_rate`           | `>=` `<=`             | `frame_rate >= 50`           |
+//!
+//! Durations take an optional `s`, `m` or `h` suffix (seconds by default).
+//! File sizes take an optional `KB`/`MB`/`GB`/`TB` (powers of 1000) or
+//! `KiB`/`MiB`/`GiB`/`TiB` (powers of 1024) suffix. Resolutions are written
+//! `WIDTHxHEIGHT`, or as one of `4k`, `2160p`, `1440p`, `1080p`, `720p`,
+//! `576p` or `480p`.
+
+mod lexer;
+mod parser;
+mod printer;
+
+use std::fmt;
+
+use crate::expr::Expr;
+
+pub use printer::format;
+
+/// An error produced while parsing rule text, with the position it occurred.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Human-readable description of the problem.
+    pub message: String,
+    /// Byte offset into the source text.
+    pub offset: usize,
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based column number (in characters).
+    pub column: usize,
+}
+
+impl ParseError {
+    pub(crate) fn new(source: &str, offset: usize, message: impl Into<String>) -> Self {
+        let offset = offset.min(source.len());
+        let before = &source[..offset];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let column = before[line_start..].chars().count() + 1;
+        Self {
+            message: message.into(),
+            offset,
+            line,
+            column,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {}",
+            self.message, self.line, self.column
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parse rule text into an [`Expr`].
+pub fn parse(source: &str) -> Result<Expr, ParseError> {
+    let tokens = lexer::tokenize(source)?;
+    parser::Parser::new(source, tokens).parse()
+}
+
+impl std::str::FromStr for Expr {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s)
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&format(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::condition::Condition;
+    use crate::expr::evaluate;
+    use sf_core::{AudioCodec, Container, HdrFormat, VideoCodec};
+    use sf_probe::{AudioTrack, DvInfo, MediaInfo, SubtitleTrack, VideoTrack};
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    fn make_info() -> MediaInfo {
+        MediaInfo {
+            file_path: PathBuf::from("/test/movie.mkv"),
+            file_size: 40 * 1_000_000_000,
+            container: Container::Mkv,
+            duration: Some(Duration::from_secs(2 * 3600 + 30 * 60)),
+            video_tracks: vec![VideoTrack {
+                codec: VideoCodec::H265,
+                width: 3840,
+                height: 2160,
+                frame_rate: Some(23.976),
+                bit_depth: Some(10),
+                hdr_format: HdrFormat::DolbyVision,
+                hdr10plus: false,
+                dolby_vision: Some(DvInfo {
+                    profile: 7,
+                    rpu_present: true,
+                    el_present: true,
+                    bl_present: true,
+                    el_type: None,
+                }),
+                default: true,
+                language: None,
+            }],
+            audio_tracks: vec![AudioTrack {
+                codec: AudioCodec::TrueHd,
+                channels: 8,
+                sample_rate: Some(48000),
+                language: Some("eng".into()),
+                atmos: true,
+                default: true,
+            }],
+            subtitle_tracks: vec![SubtitleTrack {
+                codec: "subrip".into(),
+                language: Some("eng".into()),
+                forced: false,
+                default: false,
+            }],
+        }
+    }
+
+    fn eval_text(text: &str) -> bool {
+        let expr = parse(text).unwrap_or_else(|e| panic!("{text:?}: {e}"));
+        evaluate(&expr, &make_info())
+    }
+
+    #[test]
+    fn parses_example_from_docs() {
+        let expr = parse("codec in [h265] and dv_profile == 7 and not container == mp4").unwrap();
+        match &expr {
+            Expr::And(children) => {
+                assert_eq!(children.len(), 3);
+                assert!(matches!(
+                    &children[0],
+                    Expr::Condition(Condition::Codec(c)) if c == &[VideoCodec::H265]
+                ));
+                assert!(matches!(
+                    &children[1],
+                    Expr::Condition(Condition::DolbyVisionProfile(p)) if p == &[7]
+                ));
+                assert!(matches!(&children[2], Expr::Not(_)));
+            }
+            other => panic!("expected and, got {other:?}"),
+        }
+        assert!(evaluate(&expr, &make_info()));
+    }
+
+    #[test]
+    fn evaluates_every_field() {
+        assert!(eval_text("codec == h265"));
+        assert!(eval_text("container in [mkv, mp4]"));
+        assert!(eval_text("hdr == dolbyvision"));
+        assert!(eval_text("dv_profile in [7, 8]"));
+        assert!(!eval_text("dv_fel"));
+        assert!(eval_text("hdr10plus == false"));
+        assert!(eval_text("resolution >= 4k"));
+        assert!(!eval_text("resolution <= 1920x1080"));
+        assert!(eval_text("audio_codec == truehd"));
+        assert!(eval_text("atmos"));
+        assert!(eval_text("bit_depth >= 10"));
+        assert!(eval_text("extension == MKV"));
+        assert!(eval_text("audio_channels == 8"));
+        assert!(eval_text("audio_channels >= 6"));
+        assert!(!eval_text("audio_channels <= 2"));
+        assert!(eval_text("audio_language == ENG"));
+        assert!(eval_text("subtitle_language in [\"eng\"]"));
+        assert!(eval_text("has_subtitles"));
+        assert!(!eval_text("has_forced_subtitles"));
+        assert!(eval_text("duration >= 150m"));
+        assert!(eval_text("duration <= 3h"));
+        assert!(eval_text("file_size >= 40GB"));
+        assert!(!eval_text("file_size <= 4GiB"));
+        assert!(eval_text("frame_rate <= 24"));
+        assert!(!eval_text("frame_rate >= 50"));
+    }
+
+    #[test]
+    fn negated_operators() {
+        assert!(eval_text("codec != h264"));
+        assert!(!eval_text("codec != h265"));
+        assert!(eval_text("container not in [mp4, webm]"));
+        assert!(!eval_text("atmos != true"));
+    }
+
+    #[test]
+    fn precedence_and_grouping() {
+        // and binds tighter than or
+        assert!(eval_text("codec == h264 and container == mp4 or atmos"));
+        assert!(!eval_text("codec == h264 and (container == mp4 or atmos)"));
+        assert!(eval_text("not codec == h264 and atmos"));
+        assert!(!eval_text("not (codec == h265 and atmos)"));
+        assert!(eval_text("true"));
+        assert!(!eval_text("false"));
+    }
+
+    #[test]
+    fn keywords_are_case_insensitive_and_comments_ignored() {
+        assert!(eval_text(
+            "# remux DV files\nCODEC == h265 AND\n  NOT container == mp4 # trailing\n"
+        ));
+    }
+
+    #[test]
+    fn format_renders_canonical_text() {
+        let expr = parse("(codec == h265 or codec == av1) and not (atmos and true)").unwrap();
+        assert_eq!(
+            format(&expr),
+            "(codec == h265 or codec == av1) and not (atmos and true)"
+        );
+
+        let expr = Expr::And(vec![
+            Expr::Condition(Condition::MinDuration(9000)),
+            Expr::Condition(Condition::MaxFileSize(4 * 1024 * 1024 * 1024)),
+            Expr::Condition(Condition::MinFileSize(2_000_000)),
+            Expr::Condition(Condition::MinResolution {
+                width: 1920,
+                height: 1080,
+            }),
+            Expr::Condition(Condition::HasAtmos(false)),
+            Expr::Condition(Condition::FileExtension(vec!["my file".into()])),
+            Expr::Condition(Condition::MinFrameRate(23.976)),
+        ]);
+        assert_eq!(
+            format(&expr),
+            "duration >= 150m and file_size <= 4GiB and file_size >= 2MB and \
+             resolution >= 1920x1080 and atmos == false and extension == \"my file\" and \
+             frame_rate >= 23.976"
+        );
+    }
+
+    #[test]
+    fn format_round_trips() {
+        let sources = [
+            "codec in [h265, av1] and dv_profile == 7 and not container == mp4",
+            "(hdr == hdr10 or hdr == hdr10plus) and bit_depth >= 10",
+            "not (audio_channels <= 2 or audio_language in [eng, \"pt-BR\"])",
+            "not not has_forced_subtitles or subtitle_language == und",
+            "duration <= 45s and file_size >= 1TB and frame_rate <= 29.97",
+            "true or false",
+            "codec in []",
+        ];
+        for src in sources {
+            let expr = parse(src).unwrap();
+            let text = format(&expr);
+            let reparsed = parse(&text).unwrap_or_else(|e| panic!("{text:?}: {e}"));
+            assert_eq!(format(&reparsed), text, "unstable output for {src:?}");
+            assert_eq!(
+                evaluate(&reparsed, &make_info()),
+                evaluate(&expr, &make_info()),
+                "round trip changed meaning of {src:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn errors_report_position() {
+        let err = parse("codec == h265 and\n  contianer == mkv").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+        assert!(err.message.contains("unknown field `contianer`"), "{err}");
+
+        let err = parse("codec == h266").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 10));
+        assert!(err.message.contains("h266"), "{err}");
+
+        let err = parse("(codec == h265").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 15));
+        assert!(err.message.contains("expected `)`"), "{err}");
+
+        let err = parse("bit_depth <= 8").unwrap_err();
+        assert!(err.message.contains("bit_depth"), "{err}");
+
+        let err = parse("duration > 10m").unwrap_err();
+        assert!(err.message.contains("`>=`"), "{err}");
+
+        let err = parse("codec == h265 atmos").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 15));
+
+        let err = parse("extension == \"mkv").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 14));
+
+        let err = parse("").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 1));
+
+        assert_eq!(
+            parse("codec").unwrap_err().to_string(),
+            "expected an operator after `codec` at line 1, column 6"
+        );
+    }
+
+    #[test]
+    fn display_and_from_str() {
+        let expr: Expr = "atmos and codec == h265".parse().unwrap();
+        assert_eq!(expr.to_string(), "atmos and codec == h265");
+    }
+}
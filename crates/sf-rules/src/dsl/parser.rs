@@ -0,0 +1,638 @@
+//! Recursive-descent parser for the rule DSL.
+
+use serde::de::DeserializeOwned;
+
+use super::lexer::{Token, TokenKind};
+use super::ParseError;
+use crate::condition::Condition;
+use crate::expr::Expr;
+
+/// Words that cannot be used as field names or bare values.
+pub(crate) const KEYWORDS: &[&str] = &["and", "or", "not", "in", "true", "false"];
+
+/// A field that can appear on the left-hand side of a comparison.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Field {
+    Codec,
+    Container,
+    Hdr,
+    DvProfile,
+    DvFel,
+    Hdr10Plus,
+    Resolution,
+    AudioCodec,
+    Atmos,
+    BitDepth,
+    Extension,
+    AudioChannels,
+    AudioLanguage,
+    SubtitleLanguage,
+    HasSubtitles,
+    HasForcedSubtitles,
+    Duration,
+    FileSize,
+    FrameRate,
+}
+
+impl Field {
+    const ALL: &'static [Field] = &[
+        Field::Codec,
+        Field::Container,
+        Field::Hdr,
+        Field::DvProfile,
+        Field::DvFel,
+        Field::Hdr10Plus,
+        Field::Resolution,
+        Field::AudioCodec,
+        Field::Atmos,
+        Field::BitDepth,
+        Field::Extension,
+        Field::AudioChannels,
+        Field::AudioLanguage,
+        Field::SubtitleLanguage,
+        Field::HasSubtitles,
+        Field::HasForcedSubtitles,
+        Field::Duration,
+        Field::FileSize,
+        Field::FrameRate,
+    ];
+
+    /// Canonical name, as written by the printer.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Field::Codec => "codec",
+            Field::Container => "container",
+            Field::Hdr => "hdr",
+            Field::DvProfile => "dv_profile",
+            Field::DvFel => "dv_fel",
+            Field::Hdr10Plus => "hdr10plus",
+            Field::Resolution => "resolution",
+            Field::AudioCodec => "audio_codec",
+            Field::Atmos => "atmos",
+            Field::BitDepth => "bit_depth",
+            Field::Extension => "extension",
+            Field::AudioChannels => "audio_channels",
+            Field::AudioLanguage => "audio_language",
+            Field::SubtitleLanguage => "subtitle_language",
+            Field::HasSubtitles => "has_subtitles",
+            Field::HasForcedSubtitles => "has_forced_subtitles",
+            Field::Duration => "duration",
+            Field::FileSize => "file_size",
+            Field::FrameRate => "frame_rate",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Field> {
+        let name = name.to_ascii_lowercase();
+        let canonical = match name.as_str() {
+            "video_codec" => "codec",
+            "hdr_format" => "hdr",
+            "has_atmos" => "atmos",
+            "has_dv_fel" => "dv_fel",
+            "has_hdr10plus" => "hdr10plus",
+            "file_extension" => "extension",
+            "size" => "file_size",
+            "fps" => "frame_rate",
+            other => other,
+        };
+        Field::ALL.iter().copied().find(|f| f.name() == canonical)
+    }
+
+    fn is_bool(self) -> bool {
+        matches!(
+            self,
+            Field::DvFel
+                | Field::Hdr10Plus
+                | Field::Atmos
+                | Field::HasSubtitles
+                | Field::HasForcedSubtitles
+        )
+    }
+
+    fn supports(self, op: Op) -> bool {
+        match self {
+            Field::Codec
+            | Field::Container
+            | Field::Hdr
+            | Field::DvProfile
+            | Field::AudioCodec
+            | Field::Extension
+            | Field::AudioLanguage
+            | Field::SubtitleLanguage => matches!(op, Op::Eq | Op::NotEq | Op::In | Op::NotIn),
+            Field::DvFel
+            | Field::Hdr10Plus
+            | Field::Atmos
+            | Field::HasSubtitles
+            | Field::HasForcedSubtitles => matches!(op, Op::Eq | Op::NotEq),
+            Field::Resolution | Field::Duration | Field::FileSize | Field::FrameRate => {
+                matches!(op, Op::Ge | Op::Le)
+            }
+            Field::BitDepth => op == Op::Ge,
+            Field::AudioChannels => true,
+        }
+    }
+
+    fn supported_ops(self) -> String {
+        [Op::Eq, Op::NotEq, Op::In, Op::NotIn, Op::Ge, Op::Le]
+            .into_iter()
+            .filter(|op| self.supports(*op))
+            .map(|op| format!("`{}`", op.symbol()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// A comparison operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    NotEq,
+    In,
+    NotIn,
+    Ge,
+    Le,
+}
+
+impl Op {
+    fn symbol(self) -> &'static str {
+        match self {
+            Op::Eq => "==",
+            Op::NotEq => "!=",
+            Op::In => "in",
+            Op::NotIn => "not in",
+            Op::Ge => ">=",
+            Op::Le => "<=",
+        }
+    }
+}
+
+/// A literal on the right-hand side of a comparison.
+struct Value {
+    text: String,
+    offset: usize,
+}
+
+pub(crate) struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub(crate) fn new(source: &'a str, tokens: Vec<Token>) -> Self {
+        Self {
+            source,
+            tokens,
+            pos: 0,
+        }
+    }
+
+    pub(crate) fn parse(mut self) -> Result<Expr, ParseError> {
+        if self.peek().kind == TokenKind::Eof {
+            return Err(self.error_at_current("expected an expression"));
+        }
+        let expr = self.parse_or()?;
+        if self.peek().kind != TokenKind::Eof {
+            let found = self.peek().kind.describe();
+            return Err(self.error_at_current(format!(
+                "expected `and`, `or` or end of input, found {found}"
+            )));
+        }
+        Ok(expr)
+    }
+
+    // -- Expressions --------------------------------------------------------
+
+    fn parse_or(&mut self) -> Result<Expr, ParseError> {
+        let mut exprs = vec![self.parse_and()?];
+        while self.eat_keyword("or") {
+            exprs.push(self.parse_and()?);
+        }
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            Expr::Or(exprs)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, ParseError> {
+        let mut exprs = vec![self.parse_unary()?];
+        while self.eat_keyword("and") {
+            exprs.push(self.parse_unary()?);
+        }
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            Expr::And(exprs)
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, ParseError> {
+        if self.eat_keyword("not") {
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, ParseError> {
+        let token = self.peek().clone();
+        match &token.kind {
+            TokenKind::LParen => {
+                self.advance();
+                let expr = self.parse_or()?;
+                self.expect(TokenKind::RParen)?;
+                Ok(expr)
+            }
+            TokenKind::Word(w) if w.eq_ignore_ascii_case("true") => {
+                self.advance();
+                Ok(Expr::And(vec![]))
+            }
+            TokenKind::Word(w) if w.eq_ignore_ascii_case("false") => {
+                self.advance();
+                Ok(Expr::Or(vec![]))
+            }
+            TokenKind::Word(w) if !is_keyword(w) => {
+                self.advance();
+                self.parse_comparison(w, token.offset)
+            }
+            other => {
+                Err(self
+                    .error_at_current(format!("expected a condition, found {}", other.describe())))
+            }
+        }
+    }
+
+    fn parse_comparison(&mut self, name: &str, offset: usize) -> Result<Expr, ParseError> {
+        let field = Field::from_name(name)
+            .ok_or_else(|| self.error(offset, format!("unknown field `{name}`")))?;
+
+        let op_token = self.peek().clone();
+        let op = match &op_token.kind {
+            TokenKind::Eq => Op::Eq,
+            TokenKind::NotEq => Op::NotEq,
+            TokenKind::Ge => Op::Ge,
+            TokenKind::Le => Op::Le,
+            TokenKind::Gt => {
+                return Err(self.error_at_current("`>` is not supported, use `>=`"));
+            }
+            TokenKind::Lt => {
+                return Err(self.error_at_current("`<` is not supported, use `<=`"));
+            }
+            TokenKind::Word(w) if w.eq_ignore_ascii_case("in") => Op::In,
+            TokenKind::Word(w)
+                if w.eq_ignore_ascii_case("not")
+                    && matches!(
+                        &self.peek_at(1).kind,
+                        TokenKind::Word(next) if next.eq_ignore_ascii_case("in")
+                    ) =>
+            {
+                self.advance();
+                Op::NotIn
+            }
+            _ if field.is_bool() => return Ok(Expr::Condition(bool_condition(field, true))),
+            _ => {
+                return Err(self.error_at_current(format!("expected an operator after `{name}`")));
+            }
+        };
+
+        if !field.supports(op) {
+            return Err(self.error(
+                op_token.offset,
+                format!(
+                    "`{}` does not support `{}` (supported: {})",
+                    field.name(),
+                    op.symbol(),
+                    field.supported_ops()
+                ),
+            ));
+        }
+        self.advance();
+
+        let condition = match op {
+            Op::Eq | Op::NotEq => {
+                let value = self.value()?;
+                self.equals_condition(field, value)?
+            }
+            Op::In | Op::NotIn => {
+                let values = self.list()?;
+                self.list_condition(field, values)?
+            }
+            Op::Ge | Op::Le => {
+                let value = self.value()?;
+                self.range_condition(field, op == Op::Ge, value)?
+            }
+        };
+
+        let expr = Expr::Condition(condition);
+        Ok(if matches!(op, Op::NotEq | Op::NotIn) {
+            Expr::Not(Box::new(expr))
+        } else {
+            expr
+        })
+    }
+
+    // -- Values ---------------------------------------------------------------
+
+    fn value(&mut self) -> Result<Value, ParseError> {
+        let token = self.peek().clone();
+        match token.kind {
+            TokenKind::Word(text) | TokenKind::Str(text) => {
+                self.advance();
+                Ok(Value {
+                    text,
+                    offset: token.offset,
+                })
+            }
+            other => {
+                Err(self.error_at_current(format!("expected a value, found {}", other.describe())))
+            }
+        }
+    }
+
+    fn list(&mut self) -> Result<Vec<Value>, ParseError> {
+        self.expect(TokenKind::LBracket)?;
+        let mut values = Vec::new();
+        if self.peek().kind == TokenKind::RBracket {
+            self.advance();
+            return Ok(values);
+        }
+        loop {
+            values.push(self.value()?);
+            match self.peek().kind {
+                TokenKind::Comma => self.advance(),
+                TokenKind::RBracket => {
+                    self.advance();
+                    return Ok(values);
+                }
+                ref other => {
+                    return Err(self.error_at_current(format!(
+                        "expected `,` or `]`, found {}",
+                        other.describe()
+                    )));
+                }
+            }
+        }
+    }
+
+    fn equals_condition(&self, field: Field, value: Value) -> Result<Condition, ParseError> {
+        if field.is_bool() {
+            let flag = self.bool_value(&value)?;
+            return Ok(bool_condition(field, flag));
+        }
+        self.list_condition(field, vec![value])
+    }
+
+    fn list_condition(&self, field: Field, values: Vec<Value>) -> Result<Condition, ParseError> {
+        Ok(match field {
+            Field::Codec => Condition::Codec(self.enums(&values, "video codec")?),
+            Field::Container => Condition::Container(self.enums(&values, "container")?),
+            Field::Hdr => Condition::HdrFormat(self.enums(&values, "HDR format")?),
+            Field::AudioCodec => Condition::AudioCodec(self.enums(&values, "audio codec")?),
+            Field::DvProfile => Condition::DolbyVisionProfile(
+                values
+                    .iter()
+                    .map(|v| self.number(v))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Field::AudioChannels => Condition::AudioChannels(
+                values
+                    .iter()
+                    .map(|v| self.number(v))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Field::Extension => Condition::FileExtension(
+                values
+                    .into_iter()
+                    .map(|v| v.text.trim_start_matches('.').to_string())
+                    .collect(),
+            ),
+            Field::AudioLanguage => {
+                Condition::AudioLanguage(values.into_iter().map(|v| v.text).collect())
+            }
+            Field::SubtitleLanguage => {
+                Condition::SubtitleLanguage(values.into_iter().map(|v| v.text).collect())
+            }
+            _ => unreachable!("field support is checked before parsing values"),
+        })
+    }
+
+    fn range_condition(
+        &self,
+        field: Field,
+        min: bool,
+        value: Value,
+    ) -> Result<Condition, ParseError> {
+        Ok(match field {
+            Field::Resolution => {
+                let (width, height) = self.resolution(&value)?;
+                if min {
+                    Condition::MinResolution { width, height }
+                } else {
+                    Condition::MaxResolution { width, height }
+                }
+            }
+            Field::BitDepth => Condition::MinBitDepth(self.number(&value)?),
+            Field::AudioChannels => {
+                let n = self.number(&value)?;
+                if min {
+                    Condition::MinAudioChannels(n)
+                } else {
+                    Condition::MaxAudioChannels(n)
+                }
+            }
+            Field::Duration => {
+                let secs = self.scaled(&value, DURATION_UNITS, "duration")?;
+                if min {
+                    Condition::MinDuration(secs)
+                } else {
+                    Condition::MaxDuration(secs)
+                }
+            }
+            Field::FileSize => {
+                let bytes = self.scaled(&value, SIZE_UNITS, "file size")?;
+                if min {
+                    Condition::MinFileSize(bytes)
+                } else {
+                    Condition::MaxFileSize(bytes)
+                }
+            }
+            Field::FrameRate => {
+                let fps = value
+                    .text
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|f| f.is_finite() && *f >= 0.0)
+                    .ok_or_else(|| {
+                        self.error(value.offset, format!("invalid frame rate `{}`", value.text))
+                    })?;
+                if min {
+                    Condition::MinFrameRate(fps)
+                } else {
+                    Condition::MaxFrameRate(fps)
+                }
+            }
+            _ => unreachable!("field support is checked before parsing values"),
+        })
+    }
+
+    fn enums<T: DeserializeOwned>(
+        &self,
+        values: &[Value],
+        what: &str,
+    ) -> Result<Vec<T>, ParseError> {
+        values
+            .iter()
+            .map(|v| {
+                serde_json::from_value(serde_json::Value::String(v.text.to_ascii_lowercase()))
+                    .map_err(|_| self.error(v.offset, format!("unknown {what} `{}`", v.text)))
+            })
+            .collect()
+    }
+
+    fn number<T: std::str::FromStr>(&self, value: &Value) -> Result<T, ParseError> {
+        value.text.parse().map_err(|_| {
+            self.error(
+                value.offset,
+                format!("expected a whole number, found `{}`", value.text),
+            )
+        })
+    }
+
+    fn bool_value(&self, value: &Value) -> Result<bool, ParseError> {
+        if value.text.eq_ignore_ascii_case("true") {
+            Ok(true)
+        } else if value.text.eq_ignore_ascii_case("false") {
+            Ok(false)
+        } else {
+            Err(self.error(
+                value.offset,
+                format!("expected `true` or `false`, found `{}`", value.text),
+            ))
+        }
+    }
+
+    fn resolution(&self, value: &Value) -> Result<(u32, u32), ParseError> {
+        let text = value.text.to_ascii_lowercase();
+        let named = match text.as_str() {
+            "4k" | "2160p" => Some((3840, 2160)),
+            "1440p" => Some((2560, 1440)),
+            "1080p" => Some((1920, 1080)),
+            "720p" => Some((1280, 720)),
+            "576p" => Some((720, 576)),
+            "480p" => Some((720, 480)),
+            _ => None,
+        };
+        named
+            .or_else(|| {
+                let (w, h) = text.split_once('x')?;
+                Some((w.parse().ok()?, h.parse().ok()?))
+            })
+            .ok_or_else(|| {
+                self.error(
+                    value.offset,
+                    format!(
+                        "invalid resolution `{}` (expected WIDTHxHEIGHT or e.g. 1080p)",
+                        value.text
+                    ),
+                )
+            })
+    }
+
+    /// Parse an integer with an optional unit suffix from `units`.
+    fn scaled(&self, value: &Value, units: &[(&str, u64)], what: &str) -> Result<u64, ParseError> {
+        let text = value.text.as_str();
+        let split = text
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(text.len());
+        let (digits, suffix) = text.split_at(split);
+        let multiplier = if suffix.is_empty() {
+            Some(1)
+        } else {
+            units
+                .iter()
+                .find(|(unit, _)| unit.eq_ignore_ascii_case(suffix))
+                .map(|(_, m)| *m)
+        };
+        digits
+            .parse::<u64>()
+            .ok()
+            .zip(multiplier)
+            .and_then(|(n, m)| n.checked_mul(m))
+            .ok_or_else(|| self.error(value.offset, format!("invalid {what} `{text}`")))
+    }
+
+    // -- Token helpers --------------------------------------------------------
+
+    fn peek(&self) -> &Token {
+        self.peek_at(0)
+    }
+
+    fn peek_at(&self, n: usize) -> &Token {
+        let last = self.tokens.len() - 1;
+        &self.tokens[(self.pos + n).min(last)]
+    }
+
+    fn advance(&mut self) {
+        if self.pos < self.tokens.len() - 1 {
+            self.pos += 1;
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if matches!(&self.peek().kind, TokenKind::Word(w) if w.eq_ignore_ascii_case(keyword)) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, kind: TokenKind) -> Result<(), ParseError> {
+        if self.peek().kind == kind {
+            self.advance();
+            Ok(())
+        } else {
+            let found = self.peek().kind.describe();
+            Err(self.error_at_current(format!("expected {}, found {found}", kind.describe())))
+        }
+    }
+
+    fn error(&self, offset: usize, message: impl Into<String>) -> ParseError {
+        ParseError::new(self.source, offset, message)
+    }
+
+    fn error_at_current(&self, message: impl Into<String>) -> ParseError {
+        self.error(self.peek().offset, message)
+    }
+}
+
+/// Duration suffixes, in seconds.
+pub(crate) const DURATION_UNITS: &[(&str, u64)] = &[("s", 1), ("m", 60), ("h", 3600)];
+
+/// File size suffixes, in bytes, largest first.
+pub(crate) const SIZE_UNITS: &[(&str, u64)] = &[
+    ("TiB", 1 << 40),
+    ("TB", 1_000_000_000_000),
+    ("GiB", 1 << 30),
+    ("GB", 1_000_000_000),
+    ("MiB", 1 << 20),
+    ("MB", 1_000_000),
+    ("KiB", 1 << 10),
+    ("KB", 1_000),
+    ("B", 1),
+];
+
+pub(crate) fn is_keyword(word: &str) -> bool {
+    KEYWORDS.iter().any(|k| k.eq_ignore_ascii_case(word))
+}
+
+fn bool_condition(field: Field, value: bool) -> Condition {
+    match field {
+        Field::DvFel => Condition::HasDolbyVisionFel(value),
+        Field::Hdr10Plus => Condition::HasHdr10Plus(value),
+        Field::Atmos => Condition::HasAtmos(value),
+        Field::HasSubtitles => Condition::HasSubtitles(value),
+        Field::HasForcedSubtitles => Condition::HasForcedSubtitles(value),
+        _ => unreachable!("not a boolean field"),
+    }
+}
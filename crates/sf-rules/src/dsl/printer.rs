@@ -0,0 +1,136 @@
+//! Renders an [`Expr`] back into rule DSL text.
+
+use std::fmt::Display;
+
+use super::lexer::is_word_char;
+use super::parser::{is_keyword, Field, DURATION_UNITS, SIZE_UNITS};
+use crate::condition::Condition;
+use crate::expr::Expr;
+
+/// Binding strength of each construct, loosest first.
+const PREC_OR: u8 = 1;
+const PREC_AND: u8 = 2;
+const PREC_UNARY: u8 = 3;
+
+/// Render an expression as canonical DSL text.
+///
+/// Parentheses are only emitted where precedence requires them, so
+/// `And([Or([a, b]), c])` becomes `(a or b) and c`. An empty `And` is written
+/// as `true` and an empty `Or` as `false`.
+pub fn format(expr: &Expr) -> String {
+    let mut out = String::new();
+    write_expr(&mut out, expr, PREC_OR);
+    out
+}
+
+fn write_expr(out: &mut String, expr: &Expr, min_prec: u8) {
+    match expr {
+        Expr::Condition(cond) => write_condition(out, cond),
+        Expr::And(exprs) if exprs.is_empty() => out.push_str("true"),
+        Expr::Or(exprs) if exprs.is_empty() => out.push_str("false"),
+        Expr::And(exprs) | Expr::Or(exprs) if exprs.len() == 1 => {
+            write_expr(out, &exprs[0], min_prec)
+        }
+        Expr::And(exprs) => write_joined(out, exprs, " and ", PREC_AND, min_prec),
+        Expr::Or(exprs) => write_joined(out, exprs, " or ", PREC_OR, min_prec),
+        Expr::Not(inner) => {
+            out.push_str("not ");
+            write_expr(out, inner, PREC_UNARY);
+        }
+    }
+}
+
+fn write_joined(out: &mut String, exprs: &[Expr], sep: &str, prec: u8, min_prec: u8) {
+    let parens = prec < min_prec;
+    if parens {
+        out.push('(');
+    }
+    for (i, expr) in exprs.iter().enumerate() {
+        if i > 0 {
+            out.push_str(sep);
+        }
+        write_expr(out, expr, prec);
+    }
+    if parens {
+        out.push(')');
+    }
+}
+
+fn write_condition(out: &mut String, cond: &Condition) {
+    let text = match cond {
+        Condition::Codec(v) => one_of(Field::Codec, v),
+        Condition::Container(v) => one_of(Field::Container, v),
+        Condition::HdrFormat(v) => one_of(Field::Hdr, v),
+        Condition::DolbyVisionProfile(v) => one_of(Field::DvProfile, v),
+        Condition::AudioCodec(v) => one_of(Field::AudioCodec, v),
+        Condition::AudioChannels(v) => one_of(Field::AudioChannels, v),
+        Condition::FileExtension(v) => one_of(Field::Extension, &quoted(v)),
+        Condition::AudioLanguage(v) => one_of(Field::AudioLanguage, &quoted(v)),
+        Condition::SubtitleLanguage(v) => one_of(Field::SubtitleLanguage, &quoted(v)),
+        Condition::HasDolbyVisionFel(b) => flag(Field::DvFel, *b),
+        Condition::HasHdr10Plus(b) => flag(Field::Hdr10Plus, *b),
+        Condition::HasAtmos(b) => flag(Field::Atmos, *b),
+        Condition::HasSubtitles(b) => flag(Field::HasSubtitles, *b),
+        Condition::HasForcedSubtitles(b) => flag(Field::HasForcedSubtitles, *b),
+        Condition::MinResolution { width, height } => {
+            compare(Field::Resolution, ">=", format!("{width}x{height}"))
+        }
+        Condition::MaxResolution { width, height } => {
+            compare(Field::Resolution, "<=", format!("{width}x{height}"))
+        }
+        Condition::MinBitDepth(n) => compare(Field::BitDepth, ">=", n),
+        Condition::MinAudioChannels(n) => compare(Field::AudioChannels, ">=", n),
+        Condition::MaxAudioChannels(n) => compare(Field::AudioChannels, "<=", n),
+        Condition::MinDuration(s) => compare(Field::Duration, ">=", scaled(*s, DURATION_UNITS)),
+        Condition::MaxDuration(s) => compare(Field::Duration, "<=", scaled(*s, DURATION_UNITS)),
+        Condition::MinFileSize(b) => compare(Field::FileSize, ">=", scaled(*b, SIZE_UNITS)),
+        Condition::MaxFileSize(b) => compare(Field::FileSize, "<=", scaled(*b, SIZE_UNITS)),
+        Condition::MinFrameRate(f) => compare(Field::FrameRate, ">=", f),
+        Condition::MaxFrameRate(f) => compare(Field::FrameRate, "<=", f),
+    };
+    out.push_str(&text);
+}
+
+fn one_of<T: Display>(field: Field, values: &[T]) -> String {
+    if let [value] = values {
+        return format!("{} == {value}", field.name());
+    }
+    let items: Vec<String> = values.iter().map(ToString::to_string).collect();
+    format!("{} in [{}]", field.name(), items.join(", "))
+}
+
+fn flag(field: Field, value: bool) -> String {
+    if value {
+        field.name().to_string()
+    } else {
+        format!("{} == false", field.name())
+    }
+}
+
+fn compare(field: Field, op: &str, value: impl Display) -> String {
+    format!("{} {op} {value}", field.name())
+}
+
+/// Quote free-form strings that would not lex back as a single bare word.
+fn quoted(values: &[String]) -> Vec<String> {
+    values
+        .iter()
+        .map(|v| {
+            if !v.is_empty() && v.chars().all(is_word_char) && !is_keyword(v) {
+                v.clone()
+            } else {
+                format!("\"{}\"", v.replace('\\', "\\\\").replace('"', "\\\""))
+            }
+        })
+        .collect()
+}
+
+/// Write `n` using the largest unit that divides it exactly.
+fn scaled(n: u64, units: &[(&str, u64)]) -> String {
+    let (unit, m) = units
+        .iter()
+        .filter(|(_, m)| n.is_multiple_of(*m) && (n > 0 || *m == 1))
+        .max_by_key(|(_, m)| *m)
+        .expect("unit tables include a multiplier of 1");
+    format!("{}{unit}", n / m)
+}
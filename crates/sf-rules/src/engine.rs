@@ -2,7 +2,9 @@
 
 use sf_probe::MediaInfo;
 
+use crate::explain::{self, RuleTrace};
 use crate::expr;
+use crate::plan::{self, MatchMode, MatchPlan};
 use crate::rule::Rule;
 
 /// Rule engine that holds sorted rules and evaluates media files against them.
@@ -10,13 +12,29 @@ use crate::rule::Rule;
 pub struct RuleEngine {
     /// Rules sorted by priority descending (highest priority first).
     rules: Vec<Rule>,
+    /// How many matching rules [`plan`](Self::plan) applies.
+    match_mode: MatchMode,
 }
 
 impl RuleEngine {
     /// Create a new rule engine, sorting rules by priority descending.
     pub fn new(mut rules: Vec<Rule>) -> Self {
         rules.sort_by(|a, b| b.priority.cmp(&a.priority));
-        Self { rules }
+        Self {
+            rules,
+            match_mode: MatchMode::default(),
+        }
+    }
+
+    /// Set how many matching rules are applied (defaults to [`MatchMode::First`]).
+    pub fn with_match_mode(mut self, match_mode: MatchMode) -> Self {
+        self.match_mode = match_mode;
+        self
+    }
+
+    /// The engine's [`MatchMode`].
+    pub fn match_mode(&self) -> MatchMode {
+        self.match_mode
     }
 
     /// Return the first enabled rule whose expression matches the media info.
@@ -36,6 +54,55 @@ impl RuleEngine {
             .collect()
     }
 
+    /// Return the enabled rules to apply to the media info, highest priority
+    /// first.
+    ///
+    /// In [`MatchMode::First`] this is the first matching rule, followed by
+    /// the next matching rule for as long as the last one has
+    /// `continue: true`. In [`MatchMode::All`] it is every matching rule.
+    pub fn matching_rules(&self, info: &MediaInfo) -> Vec<&Rule> {
+        let mut matched = Vec::new();
+        for rule in self.evaluate_all(info) {
+            matched.push(rule);
+            if self.match_mode == MatchMode::First && !rule.continue_matching {
+                break;
+            }
+        }
+        matched
+    }
+
+    /// Return the rules to apply to the media info together with their
+    /// actions, or `None` if no rule matches.
+    ///
+    /// The actions of several rules are merged with [`plan::merge_actions`];
+    /// a single rule's actions run exactly as written.
+    pub fn plan(&self, info: &MediaInfo) -> Option<MatchPlan<'_>> {
+        let rules = self.matching_rules(info);
+        let actions = match rules.as_slice() {
+            [] => return None,
+            [rule] => rule.actions.clone(),
+            rules => plan::merge_actions(rules),
+        };
+        Some(MatchPlan { rules, actions })
+    }
+
+    /// Trace every rule (including disabled ones) against the media info, in
+    /// priority order.
+    ///
+    /// Traces with `applied == true` are the rules that
+    /// [`matching_rules`](Self::matching_rules) would select.
+    pub fn explain(&self, info: &MediaInfo) -> Vec<RuleTrace> {
+        let applied: Vec<_> = self.matching_rules(info).iter().map(|r| r.id).collect();
+        self.rules
+            .iter()
+            .map(|rule| {
+                let mut trace = explain::explain_rule(rule, info);
+                trace.applied = applied.contains(&rule.id);
+                trace
+            })
+            .collect()
+    }
+
     /// Return a reference to the internal rules slice.
     pub fn rules(&self) -> &[Rule] {
         &self.rules
@@ -65,11 +132,13 @@ mod tests {
                 frame_rate: Some(23.976),
                 bit_depth: Some(10),
                 hdr_format: HdrFormat::DolbyVision,
+                hdr10plus: false,
                 dolby_vision: Some(DvInfo {
                     profile: 7,
                     rpu_present: true,
                     el_present: true,
                     bl_present: true,
+                    el_type: None,
                 }),
                 default: true,
                 language: Some("eng".to_string()),
@@ -95,6 +164,8 @@ mod tests {
                 priority: 100,
                 expr: Expr::Condition(Condition::DolbyVisionProfile(vec![7])),
                 actions: vec![ActionConfig::DvConvert { target_profile: 8 }],
+                scope: Default::default(),
+                continue_matching: false,
             },
             Rule {
                 id: RuleId::new(),
@@ -106,6 +177,8 @@ mod tests {
                     container: Container::Mp4,
                     keep_original: false,
                 }],
+                scope: Default::default(),
+                continue_matching: false,
             },
             Rule {
                 id: RuleId::new(),
@@ -114,6 +187,8 @@ mod tests {
                 priority: 200,
                 expr: Expr::Condition(Condition::Codec(vec![VideoCodec::H265])),
                 actions: vec![],
+                scope: Default::default(),
+                continue_matching: false,
             },
             Rule {
                 id: RuleId::new(),
@@ -122,6 +197,8 @@ mod tests {
                 priority: 10,
                 expr: Expr::Condition(Condition::Codec(vec![VideoCodec::H265])),
                 actions: vec![],
+                scope: Default::default(),
+                continue_matching: false,
             },
         ]
     }
@@ -156,6 +233,8 @@ mod tests {
                 priority: 10,
                 expr: Expr::Condition(Condition::Codec(vec![VideoCodec::H265])),
                 actions: vec![],
+                scope: Default::default(),
+                continue_matching: false,
             },
             Rule {
                 id: RuleId::new(),
@@ -164,6 +243,8 @@ mod tests {
                 priority: 100,
                 expr: Expr::Condition(Condition::Codec(vec![VideoCodec::H265])),
                 actions: vec![],
+                scope: Default::default(),
+                continue_matching: false,
             },
         ];
         let engine = RuleEngine::new(rules);
@@ -182,6 +263,8 @@ mod tests {
             priority: 100,
             expr: Expr::Condition(Condition::Container(vec![Container::Mp4])),
             actions: vec![],
+            scope: Default::default(),
+            continue_matching: false,
         }];
         let engine = RuleEngine::new(rules);
         assert!(engine.find_matching_rule(&info).is_none());
@@ -226,6 +309,8 @@ mod tests {
             priority: 100,
             expr: Expr::Condition(Condition::Container(vec![Container::Mp4])),
             actions: vec![],
+            scope: Default::default(),
+            continue_matching: false,
         }];
         let engine = RuleEngine::new(rules);
         assert!(engine.evaluate_all(&info).is_empty());
@@ -251,6 +336,8 @@ mod tests {
                 ]),
             ]),
             actions: vec![ActionConfig::DvConvert { target_profile: 8 }],
+            scope: Default::default(),
+            continue_matching: false,
         };
         let engine = RuleEngine::new(vec![rule]);
         assert!(engine.find_matching_rule(&info).is_some());
@@ -267,6 +354,8 @@ mod tests {
                 priority: 100,
                 expr: Expr::Condition(Condition::Codec(vec![VideoCodec::H265])),
                 actions: vec![],
+                scope: Default::default(),
+                continue_matching: false,
             },
             Rule {
                 id: RuleId::new(),
@@ -275,10 +364,110 @@ mod tests {
                 priority: 50,
                 expr: Expr::Condition(Condition::Container(vec![Container::Mkv])),
                 actions: vec![],
+                scope: Default::default(),
+                continue_matching: false,
             },
         ];
         let engine = RuleEngine::new(rules);
         assert!(engine.find_matching_rule(&info).is_none());
         assert!(engine.evaluate_all(&info).is_empty());
     }
+
+    #[test]
+    fn explain_traces_every_rule_in_priority_order() {
+        let info = make_test_info();
+        let engine = RuleEngine::new(make_test_rules());
+        let traces = engine.explain(&info);
+
+        assert_eq!(traces.len(), engine.rules().len());
+        assert!(traces.windows(2).all(|w| w[0].priority >= w[1].priority));
+
+        let first_match = traces.iter().find(|t| t.matched).unwrap();
+        assert_eq!(
+            first_match.name,
+            engine.find_matching_rule(&info).unwrap().name
+        );
+        assert!(traces.iter().any(|t| !t.enabled && !t.matched));
+    }
+
+    #[test]
+    fn matching_rules_stops_at_first_match_by_default() {
+        let info = make_test_info();
+        let engine = RuleEngine::new(make_test_rules());
+        let names: Vec<_> = engine
+            .matching_rules(&info)
+            .iter()
+            .map(|r| r.name.as_str())
+            .collect();
+        assert_eq!(names, ["dv_p7_convert"]);
+    }
+
+    #[test]
+    fn matching_rules_follows_continue() {
+        let info = make_test_info();
+        let mut rules = make_test_rules();
+        rules[0].continue_matching = true;
+        let engine = RuleEngine::new(rules);
+
+        // dv_p7_convert continues to mkv_remux, which does not continue.
+        let plan = engine.plan(&info).unwrap();
+        assert_eq!(plan.rule_names(), "dv_p7_convert + mkv_remux");
+        assert_eq!(
+            plan.actions,
+            vec![
+                ActionConfig::Remux {
+                    container: Container::Mp4,
+                    keep_original: false,
+                },
+                ActionConfig::DvConvert { target_profile: 8 },
+            ]
+        );
+    }
+
+    #[test]
+    fn single_rule_actions_keep_their_order() {
+        let info = make_test_info();
+        let mut rules = make_test_rules();
+        let actions = vec![
+            ActionConfig::DvConvert { target_profile: 8 },
+            ActionConfig::Remux {
+                container: Container::Mp4,
+                keep_original: false,
+            },
+            ActionConfig::DvConvert { target_profile: 8 },
+        ];
+        rules[0].actions = actions.clone();
+        let engine = RuleEngine::new(rules);
+
+        let plan = engine.plan(&info).unwrap();
+        assert_eq!(plan.rule_names(), "dv_p7_convert");
+        assert_eq!(plan.actions, actions);
+    }
+
+    #[test]
+    fn match_mode_all_applies_every_match() {
+        let info = make_test_info();
+        let engine = RuleEngine::new(make_test_rules()).with_match_mode(MatchMode::All);
+        assert_eq!(engine.matching_rules(&info).len(), 3);
+
+        let applied: Vec<_> = engine
+            .explain(&info)
+            .into_iter()
+            .filter(|t| t.applied)
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(
+            applied,
+            ["dv_p7_convert", "mkv_remux", "low_priority_match"]
+        );
+    }
+
+    #[test]
+    fn plan_is_none_when_nothing_matches() {
+        let mut info = make_test_info();
+        info.container = Container::Mp4;
+        info.video_tracks.clear();
+        let engine = RuleEngine::new(make_test_rules()).with_match_mode(MatchMode::All);
+        assert!(engine.plan(&info).is_none());
+    }
 }
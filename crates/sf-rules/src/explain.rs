@@ -0,0 +1,285 @@
+//! Explain why rules did or did not match a media file.
+//!
+//! [`explain`] evaluates an [`Expr`] like [`evaluate`](crate::evaluate) but
+//! records the result of every node, together with the media property each
+//! leaf condition was compared against. [`RuleEngine::explain`] does this for
+//! every rule in the engine.
+//!
+//! [`RuleEngine::explain`]: crate::RuleEngine::explain
+
+use std::fmt;
+
+use serde::Serialize;
+use sf_core::RuleId;
+use sf_probe::MediaInfo;
+
+use crate::dsl;
+use crate::expr::Expr;
+use crate::rule::Rule;
+
+/// The kind of an [`ExprTrace`] node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceNode {
+    Condition,
+    And,
+    Or,
+    Not,
+}
+
+/// The evaluation trace of one [`Expr`] node.
+///
+/// Unlike [`evaluate`](crate::evaluate), every child of an `and`/`or` node is
+/// evaluated so the trace is complete.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExprTrace {
+    /// What kind of node this is.
+    pub node: TraceNode,
+    /// Whether this node evaluated to `true`.
+    pub result: bool,
+    /// For leaf conditions, the condition in DSL form (e.g. `codec == h265`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    /// For leaf conditions, the media property it was compared against.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual: Option<serde_json::Value>,
+    /// Traces of the child expressions.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<ExprTrace>,
+}
+
+/// The evaluation trace of one [`Rule`].
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleTrace {
+    pub rule_id: RuleId,
+    pub name: String,
+    pub enabled: bool,
+    pub priority: i32,
+    /// Whether the rule matched. Disabled rules never match, but their
+    /// expression is still traced.
+    pub matched: bool,
+    /// Whether the rule's actions would run. Only set by
+    /// [`RuleEngine::explain`](crate::RuleEngine::explain), which knows the other rules and the
+    /// [`MatchMode`](crate::MatchMode); otherwise equal to `matched`.
+    pub applied: bool,
+    /// The full rule expression in DSL form.
+    pub expr: String,
+    pub trace: ExprTrace,
+}
+
+/// Evaluate `expr` against `info`, recording the result of every node.
+pub fn explain(expr: &Expr, info: &MediaInfo) -> ExprTrace {
+    match expr {
+        Expr::Condition(cond) => ExprTrace {
+            node: TraceNode::Condition,
+            result: cond.evaluate(info),
+            condition: Some(dsl::format(expr)),
+            actual: Some(cond.actual_value(info)),
+            children: Vec::new(),
+        },
+        Expr::And(exprs) => {
+            let children: Vec<ExprTrace> = exprs.iter().map(|e| explain(e, info)).collect();
+            ExprTrace {
+                node: TraceNode::And,
+                result: children.iter().all(|c| c.result),
+                condition: None,
+                actual: None,
+                children,
+            }
+        }
+        Expr::Or(exprs) => {
+            let children: Vec<ExprTrace> = exprs.iter().map(|e| explain(e, info)).collect();
+            ExprTrace {
+                node: TraceNode::Or,
+                result: children.iter().any(|c| c.result),
+                condition: None,
+                actual: None,
+                children,
+            }
+        }
+        Expr::Not(inner) => {
+            let child = explain(inner, info);
+            ExprTrace {
+                node: TraceNode::Not,
+                result: !child.result,
+                condition: None,
+                actual: None,
+                children: vec![child],
+            }
+        }
+    }
+}
+
+/// Trace a single rule against `info`.
+pub fn explain_rule(rule: &Rule, info: &MediaInfo) -> RuleTrace {
+    let trace = explain(&rule.expr, info);
+    let matched = rule.enabled && trace.result;
+    RuleTrace {
+        rule_id: rule.id,
+        name: rule.name.clone(),
+        enabled: rule.enabled,
+        priority: rule.priority,
+        matched,
+        applied: matched,
+        expr: dsl::format(&rule.expr),
+        trace,
+    }
+}
+
+impl ExprTrace {
+    fn write_indented(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        let mark = if self.result { "+" } else { "-" };
+        write!(f, "{:indent$}[{mark}] ", "", indent = depth * 2)?;
+        match (&self.condition, &self.actual) {
+            (Some(cond), Some(actual)) => writeln!(f, "{cond}  (actual: {actual})")?,
+            (Some(cond), None) => writeln!(f, "{cond}")?,
+            _ => {
+                let label = match self.node {
+                    TraceNode::And if self.children.is_empty() => "true",
+                    TraceNode::Or if self.children.is_empty() => "false",
+                    TraceNode::And => "and",
+                    TraceNode::Or => "or",
+                    TraceNode::Not => "not",
+                    TraceNode::Condition => "condition",
+                };
+                writeln!(f, "{label}")?;
+            }
+        }
+        for child in &self.children {
+            child.write_indented(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders the trace as an indented tree, one node per line, with `[+]` for
+/// nodes that evaluated to `true` and `[-]` for those that did not.
+impl fmt::Display for ExprTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_indented(f, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::condition::Condition;
+    use sf_core::{AudioCodec, Container, HdrFormat, VideoCodec};
+    use sf_probe::{AudioTrack, DvInfo, VideoTrack};
+    use std::path::PathBuf;
+
+    fn make_test_info() -> MediaInfo {
+        MediaInfo {
+            file_path: PathBuf::from("/test/movie.mkv"),
+            file_size: 1024 * 1024 * 1024,
+            container: Container::Mkv,
+            duration: None,
+            video_tracks: vec![VideoTrack {
+                codec: VideoCodec::H265,
+                width: 3840,
+                height: 2160,
+                frame_rate: Some(23.976),
+                bit_depth: Some(10),
+                hdr_format: HdrFormat::DolbyVision,
+                hdr10plus: false,
+                dolby_vision: Some(DvInfo {
+                    profile: 7,
+                    rpu_present: true,
+                    el_present: true,
+                    bl_present: true,
+                    el_type: None,
+                }),
+                default: true,
+                language: Some("eng".to_string()),
+            }],
+            audio_tracks: vec![AudioTrack {
+                codec: AudioCodec::TrueHd,
+                channels: 8,
+                sample_rate: Some(48000),
+                language: Some("eng".to_string()),
+                atmos: true,
+                default: true,
+            }],
+            subtitle_tracks: vec![],
+        }
+    }
+
+    #[test]
+    fn trace_records_every_node_and_actual_values() {
+        let expr: Expr = "codec == h265 and (dv_profile == 8 or not atmos) and duration >= 1h"
+            .parse()
+            .unwrap();
+        let trace = explain(&expr, &make_test_info());
+
+        assert_eq!(trace.node, TraceNode::And);
+        assert!(!trace.result);
+        assert_eq!(trace.children.len(), 3);
+
+        let codec = &trace.children[0];
+        assert!(codec.result);
+        assert_eq!(codec.condition.as_deref(), Some("codec == h265"));
+        assert_eq!(codec.actual, Some(serde_json::json!("h265")));
+
+        let or = &trace.children[1];
+        assert_eq!(or.node, TraceNode::Or);
+        assert!(!or.result);
+        assert_eq!(or.children[0].actual, Some(serde_json::json!([7])));
+        assert_eq!(or.children[1].node, TraceNode::Not);
+        assert_eq!(
+            or.children[1].children[0].actual,
+            Some(serde_json::json!([true]))
+        );
+
+        // Unknown duration is reported as null.
+        let duration = &trace.children[2];
+        assert!(!duration.result);
+        assert_eq!(duration.actual, Some(serde_json::Value::Null));
+    }
+
+    #[test]
+    fn trace_result_agrees_with_evaluate() {
+        let info = make_test_info();
+        for text in [
+            "true",
+            "false",
+            "not (codec == h264 or container == mp4)",
+            "resolution >= 4k and bit_depth >= 10",
+            "audio_channels <= 2 or has_subtitles",
+        ] {
+            let expr: Expr = text.parse().unwrap();
+            assert_eq!(
+                explain(&expr, &info).result,
+                crate::evaluate(&expr, &info),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn disabled_rule_is_traced_but_not_matched() {
+        let rule = Rule {
+            id: RuleId::new(),
+            name: "disabled".into(),
+            enabled: false,
+            priority: 0,
+            expr: Expr::Condition(Condition::Codec(vec![VideoCodec::H265])),
+            actions: vec![],
+            scope: Default::default(),
+            continue_matching: false,
+        };
+        let trace = explain_rule(&rule, &make_test_info());
+        assert!(trace.trace.result);
+        assert!(!trace.matched);
+        assert_eq!(trace.expr, "codec == h265");
+    }
+
+    #[test]
+    fn display_renders_indented_tree() {
+        let expr: Expr = "codec == h265 and not atmos".parse().unwrap();
+        let text = explain(&expr, &make_test_info()).to_string();
+        assert_eq!(
+            text,
+            "[-] and\n  [+] codec == h265  (actual: \"h265\")\n  [-] not\n    [+] atmos  (actual: [true])\n"
+        );
+    }
+}
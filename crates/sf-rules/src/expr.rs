@@ -23,6 +23,10 @@ use crate::condition::Condition;
 /// { "type": "or",  "exprs": [...] }
 /// { "type": "not", "expr": {...} }
 /// ```
+///
+/// A JSON string is also accepted when deserializing and is parsed with the
+/// [rule DSL](crate::dsl), so `"codec == h265 and not atmos"` is equivalent to
+/// the nested object form. Serialization always produces the object form.
 #[derive(Debug, Clone)]
 pub enum Expr {
     /// A leaf condition.
@@ -90,7 +94,12 @@ fn expr_to_value(expr: &Expr) -> serde_json::Value {
 
 /// Parse an `Expr` from a `serde_json::Value` (recursive, but at runtime).
 fn expr_from_value(val: &serde_json::Value) -> Result<Expr, String> {
-    let obj = val.as_object().ok_or("Expr must be a JSON object")?;
+    if let Some(text) = val.as_str() {
+        return crate::dsl::parse(text).map_err(|e| format!("invalid rule expression: {e}"));
+    }
+    let obj = val
+        .as_object()
+        .ok_or("Expr must be a JSON object or a rule expression string")?;
     let type_tag = obj
         .get("type")
         .and_then(|v| v.as_str())
@@ -176,11 +185,13 @@ mod tests {
                 frame_rate: Some(23.976),
                 bit_depth: Some(10),
                 hdr_format: HdrFormat::DolbyVision,
+                hdr10plus: false,
                 dolby_vision: Some(DvInfo {
                     profile: 7,
                     rpu_present: true,
                     el_present: true,
                     bl_present: true,
+                    el_type: None,
                 }),
                 default: true,
                 language: Some("eng".to_string()),
@@ -326,4 +337,28 @@ mod tests {
         let info = make_test_info();
         assert!(evaluate(&back, &info));
     }
+
+    #[test]
+    fn deserialize_from_dsl_string() {
+        let expr: Expr =
+            serde_json::from_str(r#""codec == h265 and (container == mkv or not atmos)""#).unwrap();
+        assert!(evaluate(&expr, &make_test_info()));
+
+        let err = serde_json::from_str::<Expr>(r#""codec == h265 and""#).unwrap_err();
+        assert!(err.to_string().contains("line 1, column 18"), "{err}");
+    }
+
+    #[test]
+    fn rule_accepts_either_expr_form() {
+        let rules: Vec<crate::Rule> = serde_json::from_str(
+            r#"[
+                {"id": "6f1c1c1e-2a4f-4f6e-9a55-0d4c1f3b2a01", "name": "text", "expr": "dv_profile == 7", "actions": []},
+                {"id": "6f1c1c1e-2a4f-4f6e-9a55-0d4c1f3b2a02", "name": "json", "expr": {"type": "condition",
+                    "condition": {"type": "dolby_vision_profile", "value": [7]}}, "actions": []}
+            ]"#,
+        )
+        .unwrap();
+        let info = make_test_info();
+        assert!(rules.iter().all(|r| evaluate(&r.expr, &info)));
+    }
 }
@@ -10,21 +10,36 @@
 //!
 //! - [`Condition`] -- leaf conditions that test a single media property.
 //! - [`Expr`] -- expression tree combining conditions with AND/OR/NOT.
+//! - [`dsl`] -- a text syntax for writing and displaying [`Expr`] trees.
 //! - [`ActionConfig`] -- what to do when a rule matches.
 //! - [`Rule`] -- binds an expression to a set of actions with priority.
+//! - [`RuleScope`] / [`LibraryRules`] -- limit rules to libraries and paths.
 //! - [`RuleEngine`] -- evaluates media files against a sorted set of rules.
+//! - [`MatchMode`] / [`MatchPlan`] -- apply several matching rules in one run.
+//! - [`explain`] -- traces why each rule did or did not match.
+//! - [`normalize_language`] -- compares ISO 639-1 and 639-2 language codes.
 
 pub mod action_config;
 pub mod condition;
+pub mod dsl;
 pub mod engine;
+pub mod explain;
 pub mod expr;
+pub mod language;
+pub mod plan;
 pub mod rule;
+pub mod scope;
 
-pub use action_config::ActionConfig;
+pub use action_config::{ActionConfig, ActionStage};
 pub use condition::Condition;
+pub use dsl::ParseError;
 pub use engine::RuleEngine;
+pub use explain::{explain, ExprTrace, RuleTrace};
 pub use expr::{evaluate, Expr};
+pub use language::normalize_language;
+pub use plan::{merge_actions, MatchMode, MatchPlan};
 pub use rule::Rule;
+pub use scope::{effective_rules, LibraryRuleMode, LibraryRules, RuleScope};
 
 /// Serialize a list of rules to a JSON string.
 ///
@@ -64,3 +79,12 @@ pub fn rules_from_value(value: &serde_json::Value) -> Result<Vec<Rule>, serde_js
     let s = serde_json::to_string(value)?;
     serde_json::from_str(&s)
 }
+
+/// Serialize rule traces from [`RuleEngine::explain`] to a [`serde_json::Value`].
+///
+/// Serializes via string to keep monomorphization of the recursive
+/// [`ExprTrace`] in this crate.
+pub fn traces_to_value(traces: &[RuleTrace]) -> Result<serde_json::Value, serde_json::Error> {
+    let s = serde_json::to_string(traces)?;
+    serde_json::from_str(&s)
+}
@@ -0,0 +1,339 @@
+//! Combining the actions of several matching rules into one pipeline.
+//!
+//! By default only the highest-priority matching rule is applied. A rule with
+//! `continue: true`, or the global [`MatchMode::All`], lets lower-priority
+//! rules match as well; [`merge_actions`] then concatenates their actions into
+//! a single run so a file only has to be processed once. The pipeline
+//! executor chains them, each action working on the previous one's output.
+
+use serde::{Deserialize, Serialize};
+use sf_core::config::ResourceClass;
+
+use crate::action_config::ActionConfig;
+use crate::rule::Rule;
+
+/// How many matching rules are applied to a file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchMode {
+    /// Apply the highest-priority matching rule, plus any further matches
+    /// for as long as the previous matched rule has `continue: true`.
+    #[default]
+    First,
+    /// Apply every matching rule.
+    All,
+}
+
+/// The rules that matched a file and the merged actions to run for them.
+#[derive(Debug, Clone)]
+pub struct MatchPlan<'a> {
+    /// Matched rules, highest priority first.
+    pub rules: Vec<&'a Rule>,
+    /// Actions from all matched rules, merged by [`merge_actions`] when
+    /// more than one rule matched.
+    pub actions: Vec<ActionConfig>,
+}
+
+impl MatchPlan<'_> {
+    /// The matched rule names joined with `" + "`, for job display.
+    pub fn rule_names(&self) -> String {
+        self.rules
+            .iter()
+            .map(|r| r.name.as_str())
+            .collect::<Vec<_>>()
+            .join(" + ")
+    }
+
+    /// The heaviest [`ResourceClass`] among the actions (light when there
+    /// are none).
+    pub fn resource_class(&self) -> ResourceClass {
+        self.actions
+            .iter()
+            .map(ActionConfig::resource_class)
+            .max()
+            .unwrap_or(ResourceClass::Light)
+    }
+
+    /// Whether any action is heavy (see [`ActionConfig::is_heavy`]).
+    pub fn is_heavy(&self) -> bool {
+        self.actions.iter().any(ActionConfig::is_heavy)
+    }
+}
+
+/// Merge the actions of `rules` (highest priority first) into one list.
+///
+/// - Identical actions are only run once.
+/// - A file can only be remuxed into one container, DV-converted once and
+///   re-encoded once, so for `remux`, `dv_convert` and the video encodes
+///   (`profile_b_convert`, `transcode`) the highest-priority rule wins.
+///   Remuxes to the same container keep the original if any rule asks to.
+/// - Likewise only the highest-priority `dv_generate`, `inject_hdr10plus`,
+///   `set_track_defaults`, `set_subtitle_flags` and `reorder_subtitles` are
+///   kept.
+/// - The result is ordered by [`ActionStage`](crate::action_config::ActionStage)
+///   (strip, remux, audio, subtitle, video, exec); actions within a stage
+///   keep rule order.
+pub fn merge_actions(rules: &[&Rule]) -> Vec<ActionConfig> {
+    let mut merged: Vec<ActionConfig> = Vec::new();
+
+    for action in rules.iter().flat_map(|rule| &rule.actions) {
+        match action {
+            ActionConfig::Remux {
+                container,
+                keep_original,
+            } => {
+                let existing = merged
+                    .iter_mut()
+                    .find(|a| matches!(a, ActionConfig::Remux { .. }));
+                match existing {
+                    Some(ActionConfig::Remux {
+                        container: c,
+                        keep_original: keep,
+                    }) => {
+                        if c == container {
+                            *keep |= *keep_original;
+                        }
+                    }
+                    _ => merged.push(action.clone()),
+                }
+            }
+            ActionConfig::DvConvert { .. }
+            | ActionConfig::DvGenerate { .. }
+            | ActionConfig::InjectHdr10Plus { .. }
+            | ActionConfig::SetTrackDefaults { .. }
+            | ActionConfig::SetSubtitleFlags { .. }
+            | ActionConfig::ReorderSubtitles { .. } => {
+                let kind = std::mem::discriminant(action);
+                if !merged.iter().any(|a| std::mem::discriminant(a) == kind) {
+                    merged.push(action.clone());
+                }
+            }
+            ActionConfig::ProfileBConvert { .. } | ActionConfig::Transcode { .. } => {
+                if !merged.iter().any(is_video_encode) {
+                    merged.push(action.clone());
+                }
+            }
+            _ => {
+                if !merged.contains(action) {
+                    merged.push(action.clone());
+                }
+            }
+        }
+    }
+
+    merged.sort_by_key(ActionConfig::stage);
+    merged
+}
+
+fn is_video_encode(action: &ActionConfig) -> bool {
+    matches!(
+        action,
+        ActionConfig::ProfileBConvert { .. } | ActionConfig::Transcode { .. }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::Expr;
+    use sf_core::{AudioCodec, Container, RuleId, StreamType};
+
+    fn rule(name: &str, actions: Vec<ActionConfig>) -> Rule {
+        Rule {
+            id: RuleId::new(),
+            name: name.into(),
+            enabled: true,
+            priority: 0,
+            expr: Expr::And(vec![]),
+            actions,
+            scope: Default::default(),
+            continue_matching: false,
+        }
+    }
+
+    fn remux(container: Container, keep_original: bool) -> ActionConfig {
+        ActionConfig::Remux {
+            container,
+            keep_original,
+        }
+    }
+
+    fn strip_subs() -> ActionConfig {
+        ActionConfig::StripTracks {
+            track_types: vec![StreamType::Subtitle],
+            languages: None,
+        }
+    }
+
+    #[test]
+    fn orders_strip_before_remux_before_encode() {
+        let dv = rule("dv", vec![ActionConfig::DvConvert { target_profile: 8 }]);
+        let audio = rule(
+            "audio",
+            vec![
+                remux(Container::Mp4, false),
+                ActionConfig::AddCompatAudio {
+                    source_codec: AudioCodec::TrueHd,
+                    target_codec: AudioCodec::Eac3,
+                },
+            ],
+        );
+        let subs = rule("subs", vec![strip_subs()]);
+
+        let actions = merge_actions(&[&dv, &audio, &subs]);
+        let stages: Vec<_> = actions.iter().map(ActionConfig::stage).collect();
+        assert!(stages.windows(2).all(|w| w[0] <= w[1]), "{actions:?}");
+        assert_eq!(actions[0], strip_subs());
+        assert_eq!(actions[3], ActionConfig::DvConvert { target_profile: 8 });
+    }
+
+    #[test]
+    fn deduplicates_identical_actions() {
+        let a = rule("a", vec![strip_subs(), remux(Container::Mp4, false)]);
+        let b = rule("b", vec![strip_subs(), remux(Container::Mp4, true)]);
+
+        let actions = merge_actions(&[&a, &b]);
+        assert_eq!(actions, vec![strip_subs(), remux(Container::Mp4, true)]);
+    }
+
+    #[test]
+    fn highest_priority_wins_conflicting_actions() {
+        let a = rule(
+            "a",
+            vec![
+                remux(Container::Mp4, false),
+                ActionConfig::DvConvert { target_profile: 8 },
+            ],
+        );
+        let b = rule(
+            "b",
+            vec![
+                remux(Container::Mkv, true),
+                ActionConfig::DvConvert { target_profile: 5 },
+            ],
+        );
+
+        let actions = merge_actions(&[&a, &b]);
+        assert_eq!(
+            actions,
+            vec![
+                remux(Container::Mp4, false),
+                ActionConfig::DvConvert { target_profile: 8 },
+            ]
+        );
+    }
+
+    #[test]
+    fn only_one_video_encode() {
+        let hevc = ActionConfig::Transcode {
+            codec: sf_core::VideoCodec::H265,
+            crf: None,
+            preset: None,
+            tune: None,
+            bit_depth: None,
+            require_smaller: true,
+        };
+        let a = rule("a", vec![hevc.clone()]);
+        let b = rule(
+            "b",
+            vec![ActionConfig::ProfileBConvert {
+                crf: None,
+                preset: None,
+            }],
+        );
+
+        assert_eq!(merge_actions(&[&a, &b]), vec![hevc]);
+    }
+
+    #[test]
+    fn highest_priority_subtitle_layout_wins() {
+        let reorder = |lang: &str| ActionConfig::ReorderSubtitles {
+            languages: vec![lang.into()],
+        };
+        let extract = ActionConfig::ExtractSubtitles {
+            languages: None,
+            format: sf_core::SubtitleFormat::Vtt,
+        };
+        let a = rule("a", vec![reorder("eng")]);
+        let b = rule("b", vec![reorder("fre"), extract.clone()]);
+
+        assert_eq!(merge_actions(&[&a, &b]), vec![reorder("eng"), extract]);
+    }
+
+    #[test]
+    fn exec_actions_keep_rule_order() {
+        let exec = |cmd: &str| ActionConfig::Exec {
+            command: cmd.into(),
+            args: vec![],
+        };
+        let a = rule("a", vec![exec("first"), strip_subs()]);
+        let b = rule("b", vec![exec("second"), exec("first")]);
+
+        let actions = merge_actions(&[&a, &b]);
+        assert_eq!(actions, vec![strip_subs(), exec("first"), exec("second")]);
+    }
+
+    #[test]
+    fn continue_key_round_trips() {
+        let json = r#"[{"id":"0d9f4a8e-3c1b-4e7a-9f6d-2b5c8e1a7f40","name":"a",
+            "expr":"true","actions":[],"continue":true}]"#;
+        let rules = crate::deserialize_rules(json).unwrap();
+        assert!(rules[0].continue_matching);
+        assert!(crate::serialize_rules(&rules)
+            .unwrap()
+            .contains(r#""continue":true"#));
+
+        // Omitted when false.
+        let plain = rule("b", vec![]);
+        assert!(!crate::serialize_rules(&[plain])
+            .unwrap()
+            .contains("continue"));
+    }
+
+    #[test]
+    fn rule_names_are_joined() {
+        let a = rule("a", vec![]);
+        let b = rule("b", vec![]);
+        let plan = MatchPlan {
+            rules: vec![&a, &b],
+            actions: vec![],
+        };
+        assert_eq!(plan.rule_names(), "a + b");
+    }
+
+    #[test]
+    fn resource_class_is_the_heaviest_action() {
+        let mut plan = MatchPlan {
+            rules: vec![],
+            actions: vec![],
+        };
+        assert_eq!(plan.resource_class(), ResourceClass::Light);
+
+        plan.actions = vec![
+            ActionConfig::Exec {
+                command: "true".into(),
+                args: vec![],
+            },
+            strip_subs(),
+        ];
+        assert_eq!(plan.resource_class(), ResourceClass::Remux);
+        assert!(!plan.is_heavy());
+
+        plan.actions.push(ActionConfig::ProfileBConvert {
+            crf: None,
+            preset: None,
+        });
+        assert_eq!(plan.resource_class(), ResourceClass::Encode);
+        assert!(plan.is_heavy());
+    }
+
+    #[test]
+    fn dv_conversion_is_heavy() {
+        let plan = MatchPlan {
+            rules: vec![],
+            actions: vec![ActionConfig::DvConvert { target_profile: 8 }],
+        };
+        assert_eq!(plan.resource_class(), ResourceClass::Remux);
+        assert!(plan.is_heavy());
+    }
+}
@@ -5,6 +5,7 @@ use sf_core::RuleId;
 
 use crate::action_config::ActionConfig;
 use crate::expr::Expr;
+use crate::scope::RuleScope;
 
 /// A processing rule that matches media files and specifies actions.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,8 +24,21 @@ pub struct Rule {
     pub expr: Expr,
     /// Actions to execute when this rule matches.
     pub actions: Vec<ActionConfig>,
+    /// Libraries and paths this rule is limited to (empty = everywhere).
+    #[serde(default, skip_serializing_if = "RuleScope::is_empty")]
+    pub scope: RuleScope,
+    /// Keep evaluating lower-priority rules after this one matches, so that
+    /// their actions run in the same pipeline (see [`MatchMode`]).
+    ///
+    /// [`MatchMode`]: crate::MatchMode
+    #[serde(default, rename = "continue", skip_serializing_if = "is_false")]
+    pub continue_matching: bool,
 }
 
 fn default_enabled() -> bool {
     true
 }
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
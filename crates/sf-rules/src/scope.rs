@@ -0,0 +1,423 @@
+//! Restricting rules to particular libraries and paths.
+//!
+//! A [`RuleScope`] limits a [`Rule`] to files in certain libraries and/or
+//! whose paths match glob patterns. Libraries can also carry their own rules
+//! ([`LibraryRules`]), stored in the `libraries.config` JSON column, which
+//! either extend or replace the global rule set. [`effective_rules`] combines
+//! both to produce the rules that apply to a single file.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sf_core::LibraryId;
+
+use crate::rule::Rule;
+
+/// Where a rule applies. An empty scope applies everywhere.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleScope {
+    /// The rule only applies to files in one of these libraries.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub library_ids: Vec<LibraryId>,
+    /// The rule only applies to files whose full path matches one of these
+    /// globs. `*` and `?` do not cross `/`, `**` does, and `[...]` matches a
+    /// character class (`[!...]` negates).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+}
+
+impl RuleScope {
+    /// Whether this scope places no restrictions.
+    pub fn is_empty(&self) -> bool {
+        self.library_ids.is_empty() && self.paths.is_empty()
+    }
+
+    /// Whether a file at `path` in `library_id` is within this scope.
+    ///
+    /// Files that don't belong to a library are outside any scope that lists
+    /// library IDs.
+    pub fn applies_to(&self, library_id: Option<LibraryId>, path: &Path) -> bool {
+        let library_ok = self.library_ids.is_empty()
+            || library_id.is_some_and(|id| self.library_ids.contains(&id));
+        let path_ok = self.paths.is_empty() || {
+            let path = path.to_string_lossy();
+            self.paths.iter().any(|glob| glob_matches(glob, &path))
+        };
+        library_ok && path_ok
+    }
+}
+
+/// How a library's own rules combine with the global rules.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LibraryRuleMode {
+    /// Library rules are evaluated alongside the global rules.
+    #[default]
+    Extend,
+    /// Only the library rules are evaluated; global rules are ignored.
+    Replace,
+}
+
+/// Per-library rule overrides.
+///
+/// Stored in the library's `config` JSON as
+///
+/// ```json
+/// { "rule_mode": "replace", "rules": [ ... ] }
+/// ```
+///
+/// where `rules` uses the same format as the global rules (expressions may be
+/// JSON or DSL text). Both keys are optional.
+#[derive(Debug, Clone, Default)]
+pub struct LibraryRules {
+    pub mode: LibraryRuleMode,
+    pub rules: Vec<Rule>,
+}
+
+impl LibraryRules {
+    /// Read the overrides from a library's `config` value.
+    pub fn from_library_config(config: &serde_json::Value) -> Result<Self, serde_json::Error> {
+        let mode = match config.get("rule_mode") {
+            Some(v) => serde_json::from_value(v.clone())?,
+            None => LibraryRuleMode::default(),
+        };
+        let rules = match config.get("rules") {
+            Some(v) => crate::rules_from_value(v)?,
+            None => Vec::new(),
+        };
+        Ok(Self { mode, rules })
+    }
+
+    /// Write the overrides into a library's `config` value, keeping any other
+    /// keys intact.
+    pub fn write_to_library_config(
+        &self,
+        config: &mut serde_json::Value,
+    ) -> Result<(), serde_json::Error> {
+        if !config.is_object() {
+            *config = serde_json::json!({});
+        }
+        let map = config.as_object_mut().expect("config is an object");
+        map.insert("rule_mode".into(), serde_json::to_value(self.mode)?);
+        map.insert("rules".into(), crate::rules_to_value(&self.rules)?);
+        Ok(())
+    }
+}
+
+/// The rules that apply to the file at `path` in `library_id`.
+///
+/// Starts from the global rules (unless the library replaces them), adds the
+/// library's own rules, then drops any rule whose [`RuleScope`] excludes the
+/// file.
+pub fn effective_rules(
+    global: &[Rule],
+    library_id: Option<LibraryId>,
+    library_rules: Option<&LibraryRules>,
+    path: &Path,
+) -> Vec<Rule> {
+    let replace = library_rules.is_some_and(|lr| lr.mode == LibraryRuleMode::Replace);
+    let global = if replace { &[][..] } else { global };
+    let local = library_rules.map_or(&[][..], |lr| &lr.rules[..]);
+
+    global
+        .iter()
+        .chain(local)
+        .filter(|rule| rule.scope.applies_to(library_id, path))
+        .cloned()
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Glob matching
+// ---------------------------------------------------------------------------
+
+#[derive(Debug)]
+enum GlobToken {
+    Literal(char),
+    /// `?`
+    AnyChar,
+    /// `*`
+    Star,
+    /// `**` not followed by `/`
+    DoubleStar,
+    /// `**/`: zero or more whole path components
+    DoubleStarSlash,
+    /// `[...]`
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+fn tokenize_glob(pattern: &str) -> Vec<GlobToken> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                if chars.get(i + 2) == Some(&'/') {
+                    tokens.push(GlobToken::DoubleStarSlash);
+                    i += 3;
+                } else {
+                    tokens.push(GlobToken::DoubleStar);
+                    i += 2;
+                }
+            }
+            '*' => {
+                tokens.push(GlobToken::Star);
+                i += 1;
+            }
+            '?' => {
+                tokens.push(GlobToken::AnyChar);
+                i += 1;
+            }
+            '[' => match parse_class(&chars[i + 1..]) {
+                Some((token, consumed)) => {
+                    tokens.push(token);
+                    i += 1 + consumed;
+                }
+                // An unterminated class is matched literally.
+                None => {
+                    tokens.push(GlobToken::Literal('['));
+                    i += 1;
+                }
+            },
+            c => {
+                tokens.push(GlobToken::Literal(c));
+                i += 1;
+            }
+        }
+    }
+    tokens
+}
+
+/// Parse the inside of a `[...]` class. Returns the token and the number of
+/// characters consumed, including the closing `]`.
+fn parse_class(chars: &[char]) -> Option<(GlobToken, usize)> {
+    let mut i = 0;
+    let negated = matches!(chars.first(), Some('!') | Some('^'));
+    if negated {
+        i += 1;
+    }
+    let mut ranges = Vec::new();
+    let start = i;
+    while i < chars.len() {
+        let c = chars[i];
+        // A `]` right after the opening bracket is a literal.
+        if c == ']' && i > start {
+            return Some((GlobToken::Class { negated, ranges }, i + 1));
+        }
+        if chars.get(i + 1) == Some(&'-') && chars.get(i + 2).is_some_and(|&e| e != ']') {
+            ranges.push((c, chars[i + 2]));
+            i += 3;
+        } else {
+            ranges.push((c, c));
+            i += 1;
+        }
+    }
+    None
+}
+
+/// Match `text` against a glob `pattern` (see [`RuleScope::paths`]).
+pub fn glob_matches(pattern: &str, text: &str) -> bool {
+    let tokens = tokenize_glob(pattern);
+    let text: Vec<char> = text.chars().collect();
+    let (n, m) = (tokens.len(), text.len());
+
+    // reach[i][j]: the first i tokens can consume the first j characters.
+    let mut reach = vec![vec![false; m + 1]; n + 1];
+    reach[0][0] = true;
+    for i in 0..n {
+        for j in 0..=m {
+            if !reach[i][j] {
+                continue;
+            }
+            let next = text.get(j).copied();
+            match &tokens[i] {
+                GlobToken::Literal(c) => {
+                    if next == Some(*c) {
+                        reach[i + 1][j + 1] = true;
+                    }
+                }
+                GlobToken::AnyChar => {
+                    if next.is_some_and(|c| c != '/') {
+                        reach[i + 1][j + 1] = true;
+                    }
+                }
+                GlobToken::Class { negated, ranges } => {
+                    if let Some(c) = next.filter(|&c| c != '/') {
+                        let in_class = ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi);
+                        if in_class != *negated {
+                            reach[i + 1][j + 1] = true;
+                        }
+                    }
+                }
+                GlobToken::Star => {
+                    reach[i + 1][j] = true;
+                    if next.is_some_and(|c| c != '/') {
+                        reach[i][j + 1] = true;
+                    }
+                }
+                GlobToken::DoubleStar => {
+                    reach[i + 1][j] = true;
+                    if next.is_some() {
+                        reach[i][j + 1] = true;
+                    }
+                }
+                GlobToken::DoubleStarSlash => {
+                    reach[i + 1][j] = true;
+                    for (k, &c) in text.iter().enumerate().skip(j) {
+                        if c == '/' {
+                            reach[i + 1][k + 1] = true;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    reach[n][m]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::Expr;
+    use sf_core::RuleId;
+
+    fn rule(name: &str, scope: RuleScope) -> Rule {
+        Rule {
+            id: RuleId::new(),
+            name: name.into(),
+            enabled: true,
+            priority: 0,
+            expr: Expr::And(vec![]),
+            actions: vec![],
+            scope,
+            continue_matching: false,
+        }
+    }
+
+    fn names(rules: &[Rule]) -> Vec<&str> {
+        rules.iter().map(|r| r.name.as_str()).collect()
+    }
+
+    #[test]
+    fn glob_star_stays_within_component() {
+        assert!(glob_matches("/media/*.mkv", "/media/a.mkv"));
+        assert!(!glob_matches("/media/*.mkv", "/media/kids/a.mkv"));
+        assert!(glob_matches("/media/?.mkv", "/media/a.mkv"));
+        assert!(!glob_matches("/media/?.mkv", "/media/ab.mkv"));
+    }
+
+    #[test]
+    fn glob_double_star_crosses_components() {
+        assert!(glob_matches(
+            "/media/kids/**",
+            "/media/kids/Bluey/S01/e1.mkv"
+        ));
+        assert!(glob_matches("/media/**/*.mkv", "/media/a.mkv"));
+        assert!(glob_matches("/media/**/*.mkv", "/media/x/y/a.mkv"));
+        assert!(!glob_matches("/media/**/*.mkv", "/media/x/y/a.mp4"));
+        assert!(glob_matches("**/Remux/**", "/mnt/4k/Remux/Dune.mkv"));
+        assert!(!glob_matches("/media/kids/**", "/media/kidsish/a.mkv"));
+    }
+
+    #[test]
+    fn glob_character_classes() {
+        assert!(glob_matches("/tv/S0[1-3]/*", "/tv/S02/e.mkv"));
+        assert!(!glob_matches("/tv/S0[1-3]/*", "/tv/S04/e.mkv"));
+        assert!(glob_matches("/tv/S0[!1-3]/*", "/tv/S04/e.mkv"));
+        assert!(glob_matches("/a[/b", "/a[/b"));
+    }
+
+    #[test]
+    fn scope_requires_library_and_path() {
+        let kids = LibraryId::new();
+        let other = LibraryId::new();
+        let path = Path::new("/media/kids/a.mkv");
+
+        assert!(RuleScope::default().applies_to(None, path));
+
+        let by_lib = RuleScope {
+            library_ids: vec![kids],
+            paths: vec![],
+        };
+        assert!(by_lib.applies_to(Some(kids), path));
+        assert!(!by_lib.applies_to(Some(other), path));
+        assert!(!by_lib.applies_to(None, path));
+
+        let both = RuleScope {
+            library_ids: vec![kids],
+            paths: vec!["/media/kids/**".into()],
+        };
+        assert!(both.applies_to(Some(kids), path));
+        assert!(!both.applies_to(Some(kids), Path::new("/media/movies/a.mkv")));
+    }
+
+    #[test]
+    fn effective_rules_extend_and_replace() {
+        let kids = LibraryId::new();
+        let path = Path::new("/media/kids/a.mkv");
+        let global = vec![
+            rule("everywhere", RuleScope::default()),
+            rule(
+                "movies only",
+                RuleScope {
+                    library_ids: vec![],
+                    paths: vec!["/media/movies/**".into()],
+                },
+            ),
+        ];
+        let mut library = LibraryRules {
+            mode: LibraryRuleMode::Extend,
+            rules: vec![rule("kids", RuleScope::default())],
+        };
+
+        let rules = effective_rules(&global, Some(kids), Some(&library), path);
+        assert_eq!(names(&rules), ["everywhere", "kids"]);
+
+        library.mode = LibraryRuleMode::Replace;
+        let rules = effective_rules(&global, Some(kids), Some(&library), path);
+        assert_eq!(names(&rules), ["kids"]);
+
+        let rules = effective_rules(&global, None, None, Path::new("/media/movies/b.mkv"));
+        assert_eq!(names(&rules), ["everywhere", "movies only"]);
+    }
+
+    #[test]
+    fn library_rules_round_trip_through_config() {
+        let mut config = serde_json::json!({ "scan_interval": 60 });
+        assert!(LibraryRules::from_library_config(&config)
+            .unwrap()
+            .rules
+            .is_empty());
+
+        let library = LibraryRules {
+            mode: LibraryRuleMode::Replace,
+            rules: vec![rule("kids", RuleScope::default())],
+        };
+        library.write_to_library_config(&mut config).unwrap();
+        assert_eq!(config["scan_interval"], 60);
+        assert_eq!(config["rule_mode"], "replace");
+
+        let back = LibraryRules::from_library_config(&config).unwrap();
+        assert_eq!(back.mode, LibraryRuleMode::Replace);
+        assert_eq!(names(&back.rules), ["kids"]);
+    }
+
+    #[test]
+    fn library_rules_accept_dsl_expressions() {
+        let config = serde_json::json!({
+            "rules": [{
+                "id": RuleId::new(),
+                "name": "kids downmix",
+                "expr": "audio_channels >= 6",
+                "actions": [],
+            }]
+        });
+        let library = LibraryRules::from_library_config(&config).unwrap();
+        assert_eq!(library.mode, LibraryRuleMode::Extend);
+        assert_eq!(library.rules[0].expr.to_string(), "audio_channels >= 6");
+    }
+}
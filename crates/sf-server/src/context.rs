@@ -5,7 +5,7 @@
 //! and mutable runtime configuration in a [`ConfigStore`] with hot-reload
 //! support.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicU32;
 use std::sync::Arc;
 use std::time::Instant;
@@ -15,16 +15,18 @@ use parking_lot::RwLock;
 
 use tokio_util::sync::CancellationToken;
 
-use sf_av::ToolRegistry;
+use sf_av::{ProcessControl, ToolRegistry};
 use sf_core::config::Config;
 use sf_core::events::EventBus;
 use tokio::sync::Notify;
 
-use sf_core::{ConversionJobId, LibraryId, MediaFileId};
+use sf_core::{ConversionJobId, JobId, LibraryId, MediaFileId};
 use sf_db::pool::DbPool;
-use sf_media::PreparedMedia;
+use sf_media::{Cue, PreparedMedia};
 use sf_probe::Prober;
-use sf_rules::Rule;
+use sf_rules::{MatchMode, Rule};
+
+use crate::transcode::TranscodeManager;
 
 // ---------------------------------------------------------------------------
 // ConfigStore
@@ -40,6 +42,9 @@ use sf_rules::Rule;
 pub struct ConfigStore {
     /// Processing rules (editable via PUT /api/config/rules).
     pub rules: RwLock<Vec<Rule>>,
+    /// How many matching rules are applied to a file (editable via
+    /// PUT /api/config/rules/match_mode).
+    pub match_mode: RwLock<MatchMode>,
     /// Arr (Radarr/Sonarr) integration configs.
     pub arrs: RwLock<Vec<sf_core::config::ArrConfig>>,
     /// Jellyfin server configs.
@@ -50,6 +55,8 @@ pub struct ConfigStore {
     pub metadata: RwLock<sf_core::config::MetadataConfig>,
     /// Image storage settings.
     pub images: RwLock<sf_core::config::ImageConfig>,
+    /// Processing windows for heavy jobs.
+    pub schedule: RwLock<sf_core::config::ScheduleConfig>,
     /// Full config snapshot for persisting all sections (server, auth, etc.).
     base_config: RwLock<Config>,
     /// Path to the config file for persistence (None = no persistence).
@@ -61,11 +68,13 @@ impl ConfigStore {
     pub fn new(config: &Config, config_path: Option<PathBuf>) -> Self {
         Self {
             rules: RwLock::new(Vec::new()),
+            match_mode: RwLock::new(MatchMode::default()),
             arrs: RwLock::new(config.arrs.clone()),
             jellyfins: RwLock::new(config.jellyfins.clone()),
             conversion: RwLock::new(config.conversion.clone()),
             metadata: RwLock::new(config.metadata.clone()),
             images: RwLock::new(config.images.clone()),
+            schedule: RwLock::new(config.schedule.clone()),
             base_config: RwLock::new(config.clone()),
             config_path,
         }
@@ -100,6 +109,7 @@ impl ConfigStore {
         config.conversion = self.conversion.read().clone();
         config.metadata = self.metadata.read().clone();
         config.images = self.images.read().clone();
+        config.schedule = self.schedule.read().clone();
 
         // Serialize the full config to a JSON map, then add rules separately
         // (rules use sf_rules serialization to avoid deep monomorphization).
@@ -115,6 +125,9 @@ impl ConfigStore {
         if let Ok(v) = sf_rules::rules_to_value(&rules) {
             map.insert("rules".into(), v);
         }
+        if let Ok(v) = serde_json::to_value(*self.match_mode.read()) {
+            map.insert("match_mode".into(), v);
+        }
 
         let snapshot = serde_json::Value::Object(map);
 
@@ -154,20 +167,45 @@ impl ConfigStore {
             *self.conversion.write() = config.conversion.clone();
             *self.metadata.write() = config.metadata.clone();
             *self.images.write() = config.images.clone();
+            *self.schedule.write() = config.schedule.clone();
             *self.base_config.write() = config;
         }
 
+        self.apply_rules(path, &contents);
+
+        tracing::info!("Config reloaded from {}", path.display());
+    }
+
+    /// Load only the rules and match mode from the config file on disk.
+    ///
+    /// Called at startup, since rules are not part of [`Config`] and are
+    /// otherwise only read by [`reload`](Self::reload).
+    pub fn load_rules(&self) {
+        let Some(ref path) = self.config_path else {
+            return;
+        };
+        if let Ok(contents) = std::fs::read_to_string(path) {
+            self.apply_rules(path, &contents);
+        }
+    }
+
+    fn apply_rules(&self, path: &Path, contents: &str) {
         // Rules are serialized separately via sf_rules; parse them from the
         // raw JSON value to keep monomorphization in sf-rules.
-        if let Ok(val) = serde_json::from_str::<serde_json::Value>(&contents) {
+        if let Ok(val) = serde_json::from_str::<serde_json::Value>(contents) {
             if let Some(rules) = val.get("rules") {
-                if let Ok(r) = sf_rules::rules_from_value(rules) {
-                    self.set_rules(r);
+                match sf_rules::rules_from_value(rules) {
+                    Ok(r) => self.set_rules(r),
+                    Err(e) => tracing::warn!("Ignoring invalid rules in {}: {e}", path.display()),
+                }
+            }
+            if let Some(mode) = val.get("match_mode") {
+                match serde_json::from_value(mode.clone()) {
+                    Ok(m) => *self.match_mode.write() = m,
+                    Err(e) => tracing::warn!("Ignoring invalid match_mode in {}: {e}", path.display()),
                 }
             }
         }
-
-        tracing::info!("Config reloaded from {}", path.display());
     }
 }
 
@@ -175,6 +213,9 @@ impl ConfigStore {
 // AppContext
 // ---------------------------------------------------------------------------
 
+/// Parsed WebVTT cues of a subtitle track and their last access time.
+pub type SubtitleCacheEntry = (Arc<Vec<Cue>>, Instant);
+
 /// Application context shared by all request handlers (via Axum state).
 ///
 /// This is cheaply cloneable because it only holds `Arc`s.
@@ -197,8 +238,19 @@ pub struct AppContext {
     pub hls_cache: Arc<DashMap<MediaFileId, (Arc<PreparedMedia>, Instant)>>,
     /// Coalescing map for in-flight HLS cache population (prevents duplicate parses).
     pub hls_loading: Arc<DashMap<MediaFileId, Arc<Notify>>>,
-    /// Cancellation tokens for active conversion jobs (keyed by job ID).
-    pub active_conversions: Arc<DashMap<ConversionJobId, CancellationToken>>,
+    /// Parsed WebVTT cues of subtitle tracks served as HLS renditions, keyed
+    /// by `(media_file_id, track_index)`.
+    /// Value is `(cues, last_access_time)` for LRU eviction.
+    pub subtitle_cache: Arc<DashMap<(MediaFileId, i32), SubtitleCacheEntry>>,
+    /// Coalescing map for in-flight subtitle extractions (prevents duplicate
+    /// ffmpeg runs over the same file).
+    pub subtitle_loading: Arc<DashMap<(MediaFileId, i32), Arc<Notify>>>,
+    /// Live transcoding sessions for clients that can't direct-play a file.
+    pub transcode: Arc<TranscodeManager>,
+    /// Pause/cancel handles for running jobs (keyed by job ID).
+    pub active_jobs: Arc<DashMap<JobId, ProcessControl>>,
+    /// Pause/cancel handles for active conversion jobs (keyed by job ID).
+    pub active_conversions: Arc<DashMap<ConversionJobId, ProcessControl>>,
     /// Libraries currently being scanned (prevents concurrent scans of the same library).
     /// Value is a CancellationToken that can be used to cancel the scan.
     pub active_scans: Arc<DashMap<LibraryId, CancellationToken>>,
@@ -224,6 +276,8 @@ mod tests {
             priority: 10,
             expr: sf_rules::Expr::And(vec![]),
             actions: vec![],
+            scope: Default::default(),
+            continue_matching: false,
         };
         store.set_rules(vec![rule.clone()]);
 
@@ -273,6 +327,46 @@ mod tests {
         assert_eq!(store.arrs.read()[0].name, "radarr");
     }
 
+    #[test]
+    fn config_store_reload_accepts_dsl_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        let json = serde_json::json!({
+            "rules": [{
+                "id": sf_core::RuleId::new(),
+                "name": "dv7",
+                "expr": "dv_profile == 7 and container == mkv",
+                "actions": [],
+            }]
+        });
+        std::fs::write(&path, json.to_string()).unwrap();
+
+        let store = ConfigStore::new(&Config::default(), Some(path));
+        store.reload();
+
+        let rules = store.get_rules();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(
+            rules[0].expr.to_string(),
+            "dv_profile == 7 and container == mkv"
+        );
+    }
+
+    #[test]
+    fn config_store_persists_match_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+
+        let store = ConfigStore::new(&Config::default(), Some(path.clone()));
+        *store.match_mode.write() = MatchMode::All;
+        store.persist();
+
+        let reloaded = ConfigStore::new(&Config::default(), Some(path));
+        assert_eq!(*reloaded.match_mode.read(), MatchMode::First);
+        reloaded.load_rules();
+        assert_eq!(*reloaded.match_mode.read(), MatchMode::All);
+    }
+
     #[test]
     fn config_store_reload_no_path() {
         let config = Config::default();
@@ -2,6 +2,7 @@
 //!
 //! Polls the database for queued conversion jobs, encodes to Profile B,
 //! populates the in-memory HLS cache, and updates job status throughout.
+//! Each conversion holds an encode slot (see [`crate::workers`]) while it runs.
 
 use std::path::Path;
 use std::sync::atomic::{AtomicU8, Ordering};
@@ -10,27 +11,35 @@ use std::time::Duration;
 
 use tokio_util::sync::CancellationToken;
 
+use sf_core::config::ResourceClass;
 use sf_core::events::{EventCategory, EventPayload};
 
 use crate::context::AppContext;
 use crate::notifications::{self, NotificationManager};
+use crate::workers::{Heartbeat, WorkerPool};
 
-/// Worker identifier for locking conversion jobs.
-const WORKER_ID: &str = "sf-conversion";
+/// Worker identifier prefix for locking conversion jobs.
+pub const WORKER_ID: &str = "sf-conversion";
 
 /// Start the background conversion processor.
 ///
+/// `worker_id` is recorded in `locked_by` for every job this worker claims.
 /// Runs until the cancellation token is triggered.
-pub async fn run_conversion_processor(ctx: AppContext, cancel: CancellationToken) {
-    tracing::info!("Conversion processor started");
+pub async fn run_conversion_processor(
+    ctx: AppContext,
+    pool: Arc<WorkerPool>,
+    worker_id: String,
+    cancel: CancellationToken,
+) {
+    tracing::info!(worker = %worker_id, "Conversion processor started");
 
     loop {
         if cancel.is_cancelled() {
-            tracing::info!("Conversion processor shutting down");
+            tracing::info!(worker = %worker_id, "Conversion processor shutting down");
             break;
         }
 
-        match process_next_conversion(&ctx).await {
+        match process_next_conversion(&ctx, &pool, &worker_id).await {
             Ok(true) => {
                 // Processed a job; immediately check for the next one.
                 continue;
@@ -39,7 +48,7 @@ pub async fn run_conversion_processor(ctx: AppContext, cancel: CancellationToken
                 // No jobs available; wait before polling again.
             }
             Err(e) => {
-                tracing::error!("Conversion processor error: {e}");
+                tracing::error!(worker = %worker_id, "Conversion processor error: {e}");
             }
         }
 
@@ -49,15 +58,24 @@ pub async fn run_conversion_processor(ctx: AppContext, cancel: CancellationToken
         }
     }
 
-    tracing::info!("Conversion processor stopped");
+    tracing::info!(worker = %worker_id, "Conversion processor stopped");
 }
 
 /// Try to dequeue and process the next conversion job.
 ///
+/// Nothing is dequeued while all encode slots are taken.
 /// Returns `Ok(true)` if a job was processed, `Ok(false)` if no jobs were available.
-async fn process_next_conversion(ctx: &AppContext) -> sf_core::Result<bool> {
+async fn process_next_conversion(
+    ctx: &AppContext,
+    pool: &WorkerPool,
+    worker_id: &str,
+) -> sf_core::Result<bool> {
+    let Some(_slot) = pool.slots.try_acquire(ResourceClass::Encode) else {
+        return Ok(false);
+    };
+
     let conn = sf_db::pool::get_conn(&ctx.db)?;
-    let job = sf_db::queries::conversion_jobs::dequeue_next_conversion(&conn, WORKER_ID)?;
+    let job = sf_db::queries::conversion_jobs::dequeue_next_conversion(&conn, worker_id)?;
     drop(conn);
 
     let Some(job) = job else {
@@ -67,16 +85,27 @@ async fn process_next_conversion(ctx: &AppContext) -> sf_core::Result<bool> {
     let job_id = job.id;
     tracing::info!(job_id = %job_id, item_id = %job.item_id, "Processing conversion job");
 
-    // Create a cancellation token for this job so it can be killed from the API.
-    let job_cancel = CancellationToken::new();
-    ctx.active_conversions.insert(job_id, job_cancel.clone());
+    // Register the job so it can be paused and cancelled from the API.
+    let control = sf_av::ProcessControl::default();
+    ctx.active_conversions.insert(job_id, control.clone());
 
     ctx.event_bus.broadcast(
         EventCategory::Admin,
         EventPayload::ConversionStarted { job_id },
     );
 
-    let result = execute_conversion(ctx, &job, job_cancel).await;
+    let heartbeat = Heartbeat::start(ctx.db.clone(), pool.config.lease(), {
+        let worker_id = worker_id.to_string();
+        move |conn| {
+            sf_db::queries::conversion_jobs::touch_conversion_lock(conn, job_id, &worker_id)
+        }
+    });
+    let result = sf_av::with_process_control(
+        control.clone(),
+        execute_conversion(ctx, &job, control.cancellation_token().clone()),
+    )
+    .await;
+    drop(heartbeat);
 
     // Always remove from active conversions when done.
     ctx.active_conversions.remove(&job_id);
@@ -88,6 +117,14 @@ async fn process_next_conversion(ctx: &AppContext) -> sf_core::Result<bool> {
             // Fire post-completion notifications (non-blocking).
             fire_post_conversion_notifications(ctx, &job);
         }
+        // The cancel request has already marked the job as failed.
+        Err(_) if control.is_cancelled() => {
+            tracing::info!(job_id = %job_id, "Conversion cancelled");
+            ctx.event_bus.broadcast(
+                EventCategory::Admin,
+                EventPayload::ConversionCancelled { job_id },
+            );
+        }
         Err(e) => {
             let error_msg = e.to_string();
             tracing::error!(job_id = %job_id, error = %error_msg, "Conversion failed");
@@ -151,8 +188,9 @@ async fn execute_conversion(
     // Track the last integral percent so we only write to DB on real changes.
     let last_pct = Arc::new(AtomicU8::new(0));
 
-    // Run Profile B encoding with progress streaming.
-    sf_av::convert_to_profile_b_with_progress(
+    // Run Profile B encoding with progress streaming. Long sources are
+    // encoded in chunks, so a conversion requeued after a restart resumes.
+    let encoded = sf_av::convert_to_profile_b_with_progress(
         &ctx.tools,
         source_path,
         &output_path,
@@ -217,9 +255,15 @@ async fn execute_conversion(
                 }
             }
         },
-        Some(cancel),
+        Some(cancel.clone()),
     )
-    .await?;
+    .await;
+    if encoded.is_err() {
+        // Only a conversion interrupted by a crash or restart is resumed; a
+        // failed or cancelled one would leave its chunks in the library.
+        let _ = tokio::fs::remove_dir_all(sf_av::chunk_dir(&output_path)).await;
+    }
+    encoded?;
 
     // Update progress to 90% (encoding done, HLS segmenting next).
     {
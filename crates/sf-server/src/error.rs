@@ -55,6 +55,7 @@ impl IntoResponse for AppError {
             sf_core::Error::Database { .. } => "database_error",
             sf_core::Error::Io { .. } => "io_error",
             sf_core::Error::Tool { .. } => "tool_error",
+            sf_core::Error::ToolTimeout { .. } => "tool_timeout",
             sf_core::Error::Probe(_) => "probe_error",
             sf_core::Error::Pipeline { .. } => "pipeline_error",
             sf_core::Error::Internal(_) => "internal_error",
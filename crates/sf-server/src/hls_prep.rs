@@ -1,12 +1,15 @@
-//! Shared helper for populating the in-memory HLS segment cache.
+//! Shared helpers for populating the in-memory HLS segment and subtitle caches.
 
+use std::hash::Hash;
 use std::path::Path;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use sf_av::{ToolCommand, ToolRegistry};
 use sf_core::{MediaFileId, Result};
-use sf_media::PreparedMedia;
+use sf_media::{Cue, PreparedMedia};
 use tokio::sync::Notify;
 
 use crate::context::AppContext;
@@ -16,6 +19,14 @@ use crate::context::AppContext;
 /// the moov atom (~200ms latency).
 const MAX_HLS_CACHE_ENTRIES: usize = 200;
 
+/// Maximum number of subtitle tracks whose cues are kept in memory. Evicted
+/// tracks are re-extracted with ffmpeg on their next segment request.
+const MAX_SUBTITLE_CACHE_ENTRIES: usize = 100;
+
+/// Upper bound on one ffmpeg subtitle extraction, which demuxes the whole
+/// source file.
+const SUBTITLE_EXTRACT_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
 /// Maximum attempts before giving up (prevents infinite loops on corrupt files).
 const MAX_POPULATE_ATTEMPTS: usize = 2;
 
@@ -112,21 +123,27 @@ async fn do_populate(
 
         // --- Tier 2: DB blob ---
         if let Some(blob) = sf_db::queries::media_files::get_hls_prepared(&conn, media_file_id)? {
-            drop(conn);
-            tracing::debug!(media_file_id = %media_file_id, "HLS cache loaded from DB");
-            let mut prepared = sf_media::PreparedMedia::from_bincode(&blob).map_err(|e| {
-                sf_core::Error::Internal(format!("Failed to deserialize HLS blob: {e}"))
-            })?;
-            // Update file_path from the DB record (may have moved).
-            prepared.file_path = std::path::PathBuf::from(&mf.file_path);
-            let prepared = Arc::new(prepared);
-            hls_cache.insert(media_file_id, (prepared.clone(), Instant::now()));
-            evict_if_over_limit(&hls_cache);
-            return Ok(prepared);
+            // A blob written by another PreparedMedia layout version fails
+            // to deserialize; fall through and rebuild it.
+            match sf_media::PreparedMedia::from_bincode(&blob) {
+                Ok(mut prepared) => {
+                    drop(conn);
+                    tracing::debug!(media_file_id = %media_file_id, "HLS cache loaded from DB");
+                    // Update file_path from the DB record (may have moved).
+                    prepared.file_path = std::path::PathBuf::from(&mf.file_path);
+                    let prepared = Arc::new(prepared);
+                    hls_cache.insert(media_file_id, (prepared.clone(), Instant::now()));
+                    evict_if_over_limit(&hls_cache, MAX_HLS_CACHE_ENTRIES);
+                    return Ok(prepared);
+                }
+                Err(e) => {
+                    tracing::debug!(media_file_id = %media_file_id, "Stale HLS blob, rebuilding: {e}");
+                }
+            }
         }
         drop(conn);
 
-        // --- Tier 3: moov parse ---
+        // --- Tier 3: container parse ---
         tracing::debug!(media_file_id = %media_file_id, path = %mf.file_path, "do_populate: media file found");
 
         let path = std::path::PathBuf::from(&mf.file_path);
@@ -139,9 +156,9 @@ async fn do_populate(
         })?;
         let mut reader = std::io::BufReader::new(file);
 
-        tracing::debug!(media_file_id = %media_file_id, "do_populate: parsing moov atom");
-        let metadata = sf_media::parse_moov(&mut reader).map_err(|e| {
-            sf_core::Error::Internal(format!("Failed to parse moov in {}: {e}", path.display()))
+        tracing::debug!(media_file_id = %media_file_id, "do_populate: parsing container");
+        let metadata = sf_media::parse_media(&mut reader).map_err(|e| {
+            sf_core::Error::Internal(format!("Failed to parse {}: {e}", path.display()))
         })?;
 
         tracing::debug!(media_file_id = %media_file_id, "do_populate: building prepared media");
@@ -167,7 +184,7 @@ async fn do_populate(
         tracing::debug!(media_file_id = %media_file_id, "do_populate: HLS cache populated");
 
         // Evict excess entries.
-        evict_if_over_limit(&hls_cache);
+        evict_if_over_limit(&hls_cache, MAX_HLS_CACHE_ENTRIES);
 
         Ok(prepared)
     })
@@ -193,8 +210,8 @@ pub async fn populate_hls_cache(
         })?;
         let mut reader = std::io::BufReader::new(file);
 
-        let metadata = sf_media::parse_moov(&mut reader).map_err(|e| {
-            sf_core::Error::Internal(format!("Failed to parse moov in {}: {e}", path.display()))
+        let metadata = sf_media::parse_media(&mut reader).map_err(|e| {
+            sf_core::Error::Internal(format!("Failed to parse {}: {e}", path.display()))
         })?;
 
         let prepared = sf_media::build_prepared_media(&metadata, &path).map_err(|e| {
@@ -209,7 +226,7 @@ pub async fn populate_hls_cache(
         tracing::debug!(media_file_id = %media_file_id, "HLS cache populated");
 
         // Evict excess entries.
-        evict_if_over_limit(&hls_cache);
+        evict_if_over_limit(&hls_cache, MAX_HLS_CACHE_ENTRIES);
 
         Ok(())
     })
@@ -217,28 +234,160 @@ pub async fn populate_hls_cache(
     .map_err(|e| sf_core::Error::Internal(format!("spawn_blocking join error: {e}")))?
 }
 
-/// Evict the least-recently-used entries from the HLS cache if it exceeds
-/// `MAX_HLS_CACHE_ENTRIES`.
+/// Text subtitle tracks of a media file, which can be served as HLS WebVTT
+/// renditions. Image-based tracks (PGS, VobSub) are left out.
+pub fn text_subtitle_tracks(
+    ctx: &AppContext,
+    media_file_id: MediaFileId,
+) -> Result<Vec<sf_db::models::SubtitleTrack>> {
+    let conn = sf_db::pool::get_conn(&ctx.db)?;
+    let tracks = sf_db::queries::subtitle_tracks::list_by_media_file(&conn, media_file_id)?;
+    Ok(tracks
+        .into_iter()
+        .filter(|t| sf_av::is_text_subtitle(&t.codec))
+        .collect())
+}
+
+/// Get the parsed WebVTT cues of a text subtitle track, extracting them with
+/// ffmpeg on first use.
+///
+/// Like [`get_or_populate`], concurrent requests for the same track are
+/// coalesced via `ctx.subtitle_loading`, and the extraction runs in a
+/// detached task so it survives client disconnects.
+pub async fn get_subtitle_cues(
+    ctx: &AppContext,
+    media_file_id: MediaFileId,
+    track_index: i32,
+) -> Result<Arc<Vec<Cue>>> {
+    let key = (media_file_id, track_index);
+    let mut attempts = 0;
+
+    loop {
+        if let Some(mut entry) = ctx.subtitle_cache.get_mut(&key) {
+            entry.1 = Instant::now();
+            return Ok(entry.0.clone());
+        }
+
+        if attempts >= MAX_POPULATE_ATTEMPTS {
+            return Err(sf_core::Error::Internal(format!(
+                "Subtitle extraction failed after {MAX_POPULATE_ATTEMPTS} attempts for {media_file_id}:{track_index}"
+            )));
+        }
+
+        match ctx.subtitle_loading.entry(key) {
+            Entry::Occupied(e) => {
+                // Another task is already extracting this track — wait for it.
+                let notify = e.get().clone();
+                drop(e);
+                notify.notified().await;
+                attempts += 1;
+            }
+            Entry::Vacant(e) => {
+                let notify = Arc::new(Notify::new());
+                e.insert(notify.clone());
+
+                let (tx, rx) = tokio::sync::oneshot::channel::<Result<Arc<Vec<Cue>>>>();
+
+                let ctx2 = ctx.clone();
+                tokio::spawn(async move {
+                    let result = do_extract_cues(&ctx2, media_file_id, track_index).await;
+                    if let Err(ref e) = result {
+                        tracing::warn!(
+                            media_file_id = %media_file_id,
+                            track_index,
+                            error = %e,
+                            "Subtitle extraction failed"
+                        );
+                    }
+                    ctx2.subtitle_loading.remove(&key);
+                    notify.notify_waiters();
+                    let _ = tx.send(result);
+                });
+
+                match rx.await {
+                    Ok(result) => return result,
+                    Err(_) => attempts += 1,
+                }
+            }
+        }
+    }
+}
+
+/// Extract and parse the cues of a text subtitle track and cache them.
+async fn do_extract_cues(
+    ctx: &AppContext,
+    media_file_id: MediaFileId,
+    track_index: i32,
+) -> Result<Arc<Vec<Cue>>> {
+    if !text_subtitle_tracks(ctx, media_file_id)?
+        .iter()
+        .any(|t| t.track_index == track_index)
+    {
+        return Err(sf_core::Error::not_found(
+            "subtitle_track",
+            format!("{media_file_id}:{track_index}"),
+        ));
+    }
+    let conn = sf_db::pool::get_conn(&ctx.db)?;
+    let mf = sf_db::queries::media_files::get_media_file(&conn, media_file_id)?
+        .ok_or_else(|| sf_core::Error::not_found("media_file", media_file_id))?;
+    drop(conn);
+
+    let vtt = extract_webvtt(&ctx.tools, &mf.file_path, track_index).await?;
+    let cues = Arc::new(sf_media::parse_webvtt(&String::from_utf8_lossy(&vtt)));
+
+    ctx.subtitle_cache
+        .insert((media_file_id, track_index), (cues.clone(), Instant::now()));
+    evict_if_over_limit(&ctx.subtitle_cache, MAX_SUBTITLE_CACHE_ENTRIES);
+    Ok(cues)
+}
+
+/// Extract subtitle track `track_index` (counted among the file's subtitle
+/// streams) as a WebVTT document via ffmpeg.
+pub async fn extract_webvtt(
+    tools: &ToolRegistry,
+    file_path: &str,
+    track_index: i32,
+) -> Result<Vec<u8>> {
+    let ffmpeg = tools.require("ffmpeg")?;
+    let mut cmd = ToolCommand::new(ffmpeg.path.clone());
+    cmd.args([
+        "-nostdin",
+        "-i",
+        file_path,
+        "-map",
+        &format!("0:s:{track_index}"),
+        "-f",
+        "webvtt",
+        "-",
+    ])
+    .timeout(SUBTITLE_EXTRACT_TIMEOUT);
+    Ok(cmd.execute().await?.stdout.into_bytes())
+}
+
+/// Evict the least-recently-used entries from an in-memory cache if it
+/// exceeds `max_entries`.
 ///
 /// Collects all `(key, last_access)` pairs, sorts by timestamp, and removes
 /// the oldest entries. This avoids evicting actively-streamed files that
 /// would cause a ~200ms re-parse stutter.
-fn evict_if_over_limit(
-    cache: &dashmap::DashMap<MediaFileId, (Arc<PreparedMedia>, Instant)>,
+fn evict_if_over_limit<K: Copy + Eq + Hash, V>(
+    cache: &DashMap<K, (V, Instant)>,
+    max_entries: usize,
 ) {
     let len = cache.len();
-    if len <= MAX_HLS_CACHE_ENTRIES {
+    if len <= max_entries {
         return;
     }
 
-    let to_remove = len - MAX_HLS_CACHE_ENTRIES;
-    let mut entries: Vec<(MediaFileId, Instant)> = cache
+    let to_remove = len - max_entries;
+    let mut entries: Vec<(K, Instant)> = cache
         .iter()
         .map(|e| (*e.key(), e.value().1))
         .collect();
     entries.sort_by_key(|&(_, ts)| ts);
 
-    let keys: Vec<MediaFileId> = entries.into_iter().take(to_remove).map(|(k, _)| k).collect();
+    let keys: Vec<K> = entries.into_iter().take(to_remove).map(|(k, _)| k).collect();
     for key in &keys {
         cache.remove(key);
     }
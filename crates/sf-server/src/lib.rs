@@ -4,8 +4,11 @@
 //! application. It provides:
 //!
 //! - Axum-based HTTP API with authentication, rate limiting, and SSE
-//! - Background job processor that dequeues work and runs pipelines
+//! - Pool of background job processors that dequeue work and run pipelines,
+//!   capped per resource class
 //! - File system watcher that auto-queues jobs for new media files
+//! - Trash for replaced originals, purged by age and total size
+//! - Live transcoding sessions for clients that can't direct-play a file
 //! - Graceful shutdown via signal handling
 
 pub mod context;
@@ -17,10 +20,14 @@ pub mod notifications;
 pub mod processor;
 pub mod router;
 pub mod routes;
+pub mod rule_scope;
 pub mod scanner;
 pub mod sendfile;
 pub mod tmdb;
+pub mod transcode;
+pub mod trash;
 pub mod watcher;
+pub mod workers;
 
 use std::net::SocketAddr;
 use std::path::PathBuf;
@@ -91,12 +98,20 @@ pub async fn start(config: Config, config_path: Option<PathBuf>) -> sf_core::Res
 
     // Build config store.
     let config_store = Arc::new(ConfigStore::new(&config, config_path.clone()));
+    config_store.load_rules();
 
     // Build event bus.
     let event_bus = Arc::new(EventBus::default());
 
     let hls_cache = Arc::new(DashMap::new());
     let hls_loading = Arc::new(DashMap::new());
+    let subtitle_cache = Arc::new(DashMap::new());
+    let subtitle_loading = Arc::new(DashMap::new());
+    let transcode = Arc::new(transcode::TranscodeManager::new(
+        config.transcode.clone(),
+        tools.clone(),
+    ));
+    let active_jobs = Arc::new(DashMap::new());
     let active_conversions = Arc::new(DashMap::new());
     let active_scans = Arc::new(DashMap::new());
 
@@ -112,6 +127,10 @@ pub async fn start(config: Config, config_path: Option<PathBuf>) -> sf_core::Res
         tools,
         hls_cache,
         hls_loading,
+        subtitle_cache,
+        subtitle_loading,
+        transcode,
+        active_jobs,
         active_conversions,
         active_scans,
         sendfile_sndbuf,
@@ -120,19 +139,8 @@ pub async fn start(config: Config, config_path: Option<PathBuf>) -> sf_core::Res
     // Cancellation token for graceful shutdown.
     let cancel = CancellationToken::new();
 
-    // Spawn background job processor.
-    let processor_ctx = ctx.clone();
-    let processor_cancel = cancel.clone();
-    let processor_handle = tokio::spawn(async move {
-        processor::run_processor(processor_ctx, processor_cancel).await;
-    });
-
-    // Spawn conversion processor.
-    let conv_ctx = ctx.clone();
-    let conv_cancel = cancel.clone();
-    let conv_handle = tokio::spawn(async move {
-        conversion_processor::run_conversion_processor(conv_ctx, conv_cancel).await;
-    });
+    // Spawn the job and conversion processor pool.
+    let worker_handles = workers::spawn_workers(&ctx, &config.workers, &cancel);
 
     // Spawn file watcher.
     let watcher_ctx = ctx.clone();
@@ -141,6 +149,20 @@ pub async fn start(config: Config, config_path: Option<PathBuf>) -> sf_core::Res
         watcher::run_watcher(watcher_ctx, watcher_cancel).await;
     });
 
+    // Spawn trash retention.
+    let trash_ctx = ctx.clone();
+    let trash_cancel = cancel.clone();
+    let trash_handle = tokio::spawn(async move {
+        trash::run_retention(trash_ctx, trash_cancel).await;
+    });
+
+    // Spawn idle transcoding session reaper.
+    let reaper_ctx = ctx.clone();
+    let reaper_cancel = cancel.clone();
+    let reaper_handle = tokio::spawn(async move {
+        transcode::run_reaper(reaper_ctx, reaper_cancel).await;
+    });
+
     // Build and start the HTTP server.
     let addr: SocketAddr = format!("{}:{}", config.server.host, config.server.port)
         .parse()
@@ -167,7 +189,12 @@ pub async fn start(config: Config, config_path: Option<PathBuf>) -> sf_core::Res
     cancel.cancel();
 
     // Wait for background tasks to finish.
-    let _ = tokio::join!(processor_handle, conv_handle, watcher_handle);
+    for handle in worker_handles {
+        let _ = handle.await;
+    }
+    let _ = watcher_handle.await;
+    let _ = trash_handle.await;
+    let _ = reaper_handle.await;
 
     tracing::info!("Server shutdown complete");
     Ok(())
@@ -433,7 +460,9 @@ async fn handle_connection(stream: tokio::net::TcpStream, ctx: AppContext, app:
                 | sendfile::PeekRoute::JellyfinStream { .. } => {
                     std::time::Duration::from_secs(300)
                 }
-                sendfile::PeekRoute::Segment { .. } => std::time::Duration::from_secs(30),
+                sendfile::PeekRoute::Segment { .. } | sendfile::PeekRoute::AudioSegment { .. } => {
+                    std::time::Duration::from_secs(30)
+                }
             };
             let _ = std_stream.set_write_timeout(Some(write_timeout));
 
@@ -2,36 +2,52 @@
 //!
 //! Polls the database for queued jobs, probes each file, matches against rules,
 //! creates and executes a pipeline, and updates job status throughout.
-//! Retries with exponential backoff on failure.
+//! Transient failures are retried automatically with exponential backoff.
+//!
+//! Several processors run side by side (see [`crate::workers`]); each job
+//! holds a slot of its resource class while it runs. Heavy jobs picked up
+//! outside the configured processing windows are deferred to the next one.
 
 use std::sync::Arc;
 use std::time::Duration;
 
+use chrono::{Local, Utc};
 use tokio_util::sync::CancellationToken;
 
+use sf_core::config::ResourceClass;
 use sf_core::events::{EventCategory, EventPayload};
-use sf_pipeline::{create_actions, ActionContext, PipelineExecutor, ProgressSender};
+use sf_pipeline::{
+    create_actions, Action, ActionContext, LogEntry, LogSender, PipelineExecutor, ProgressSender,
+    Verification,
+};
 use sf_rules::RuleEngine;
 
 use crate::context::AppContext;
 use crate::notifications::{self, NotificationManager};
+use crate::workers::{Heartbeat, WorkerPool};
 
-/// Worker identifier for locking jobs.
-const WORKER_ID: &str = "sf-processor";
+/// Worker identifier prefix for locking jobs.
+pub const WORKER_ID: &str = "sf-processor";
 
-/// Start the background job processor.
+/// Start a background job processor.
 ///
+/// `worker_id` is recorded in `locked_by` for every job this worker claims.
 /// Runs until the cancellation token is triggered.
-pub async fn run_processor(ctx: AppContext, cancel: CancellationToken) {
-    tracing::info!("Job processor started");
+pub async fn run_processor(
+    ctx: AppContext,
+    pool: Arc<WorkerPool>,
+    worker_id: String,
+    cancel: CancellationToken,
+) {
+    tracing::info!(worker = %worker_id, "Job processor started");
 
     loop {
         if cancel.is_cancelled() {
-            tracing::info!("Job processor shutting down");
+            tracing::info!(worker = %worker_id, "Job processor shutting down");
             break;
         }
 
-        match process_next_job(&ctx).await {
+        match process_next_job(&ctx, &pool, &worker_id).await {
             Ok(true) => {
                 // Processed a job; immediately check for the next one.
                 continue;
@@ -40,7 +56,7 @@ pub async fn run_processor(ctx: AppContext, cancel: CancellationToken) {
                 // No jobs available; wait before polling again.
             }
             Err(e) => {
-                tracing::error!("Job processor error: {e}");
+                tracing::error!(worker = %worker_id, "Job processor error: {e}");
             }
         }
 
@@ -50,34 +66,128 @@ pub async fn run_processor(ctx: AppContext, cancel: CancellationToken) {
         }
     }
 
-    tracing::info!("Job processor stopped");
+    tracing::info!(worker = %worker_id, "Job processor stopped");
+}
+
+/// A claimed job whose rules have been matched, ready to run.
+struct PreparedJob {
+    media_info: sf_probe::MediaInfo,
+    actions: Vec<Box<dyn Action>>,
+    class: ResourceClass,
+    heavy: bool,
 }
 
-/// Try to dequeue and process the next job.
+/// Try to claim and process the next job.
 ///
-/// Returns `Ok(true)` if a job was processed, `Ok(false)` if no jobs were
-/// available.
-async fn process_next_job(ctx: &AppContext) -> sf_core::Result<bool> {
+/// Only jobs whose resource class has a free slot (or that have not been
+/// classified yet) are claimed. Returns `Ok(true)` if a job was claimed,
+/// `Ok(false)` if none was available.
+async fn process_next_job(
+    ctx: &AppContext,
+    pool: &WorkerPool,
+    worker_id: &str,
+) -> sf_core::Result<bool> {
+    let available: Vec<&str> = pool.slots.available().iter().map(|c| c.as_str()).collect();
+    if available.is_empty() {
+        return Ok(false);
+    }
+
     let conn = sf_db::pool::get_conn(&ctx.db)?;
-    let job = sf_db::queries::jobs::dequeue_next(&conn, WORKER_ID)?;
+    let job = sf_db::queries::jobs::dequeue_next_in(&conn, worker_id, &available)?;
     drop(conn);
 
     let Some(job) = job else {
         return Ok(false);
     };
 
+    // Register the job right away so it can be paused and cancelled from
+    // the API while it is prepared, not only once its pipeline runs.
     let job_id = job.id;
-    tracing::info!(job_id = %job_id, file = %job.file_path, "Processing job");
+    let control = sf_av::ProcessControl::default();
+    let _active = ActiveJob::register(ctx, job_id, control.clone());
+
+    // A cancel that reached the database before the job was registered
+    // found no control to cancel.
+    {
+        let conn = sf_db::pool::get_conn(&ctx.db)?;
+        let current = sf_db::queries::jobs::get_job(&conn, job_id)?;
+        if current.is_none_or(|j| j.status == "cancelled") {
+            control.cancel();
+        }
+    }
+
+    let prepared = prepare_job(ctx, &job);
+    if control.is_cancelled() {
+        report_cancelled(ctx, job_id);
+        return Ok(true);
+    }
+    let prepared = match prepared {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            handle_job_failure(ctx, pool, &job, e)?;
+            return Ok(true);
+        }
+    };
+
+    // Claim a slot for the job's class, or hand the job back to the queue
+    // until one frees up.
+    let class = prepared.class;
+    let conn = sf_db::pool::get_conn(&ctx.db)?;
+    if job.resource_class.as_deref() != Some(class.as_str()) {
+        sf_db::queries::jobs::set_job_resource_class(&conn, job_id, class.as_str())?;
+    }
+
+    // Heavy jobs wait for the next processing window.
+    let start = deferral(
+        &job,
+        prepared.heavy,
+        &ctx.config_store.schedule.read(),
+        Local::now(),
+    );
+    if let Some(start) = start {
+        tracing::info!(
+            job_id = %job_id,
+            scheduled_for = %start,
+            "Deferring job to the next processing window"
+        );
+        sf_db::queries::jobs::defer_job(&conn, job_id, worker_id, start)?;
+        return Ok(true);
+    }
+    let Some(_slot) = pool.slots.try_acquire(class) else {
+        tracing::debug!(job_id = %job_id, %class, "No free slot; returning job to the queue");
+        sf_db::queries::jobs::release_job(&conn, job_id, worker_id)?;
+        return Ok(true);
+    };
+    drop(conn);
+
+    tracing::info!(
+        job_id = %job_id,
+        file = %job.file_path,
+        worker = %worker_id,
+        %class,
+        "Processing job"
+    );
 
     ctx.event_bus.broadcast(
         EventCategory::Admin,
         EventPayload::JobStarted { job_id },
     );
 
-    match execute_job(ctx, &job).await {
+    let heartbeat = Heartbeat::start(ctx.db.clone(), pool.config.lease(), {
+        let worker_id = worker_id.to_string();
+        move |conn| sf_db::queries::jobs::touch_job_lock(conn, job_id, &worker_id)
+    });
+    let result = execute_job(ctx, &job, prepared, control.clone()).await;
+    drop(heartbeat);
+
+    match result {
         Ok(()) => {
             let conn = sf_db::pool::get_conn(&ctx.db)?;
-            sf_db::queries::jobs::complete_job(&conn, job_id)?;
+            if !sf_db::queries::jobs::complete_job(&conn, job_id)? {
+                // Cancelled after the pipeline had already finished.
+                report_cancelled(ctx, job_id);
+                return Ok(true);
+            }
             ctx.event_bus.broadcast(
                 EventCategory::Admin,
                 EventPayload::JobCompleted { job_id },
@@ -87,61 +197,160 @@ async fn process_next_job(ctx: &AppContext) -> sf_core::Result<bool> {
             // Fire post-completion notifications (non-blocking).
             fire_post_job_notifications(ctx, &job);
         }
-        Err(e) => {
-            let error_msg = e.to_string();
-            tracing::error!(job_id = %job_id, error = %error_msg, "Job failed");
+        Err(_) if control.is_cancelled() => report_cancelled(ctx, job_id),
+        Err(e) => handle_job_failure(ctx, pool, &job, e)?,
+    }
 
-            let conn = sf_db::pool::get_conn(&ctx.db)?;
-            sf_db::queries::jobs::fail_job(&conn, job_id, &error_msg)?;
-
-            // Auto-retry with exponential backoff if retries remain.
-            if job.retry_count < job.max_retries {
-                let backoff = Duration::from_secs(2u64.pow(job.retry_count as u32).min(300));
-                tracing::info!(
-                    job_id = %job_id,
-                    retry = job.retry_count + 1,
-                    backoff_secs = backoff.as_secs(),
-                    "Scheduling retry"
-                );
-                tokio::time::sleep(backoff).await;
-                sf_db::queries::jobs::retry_job(&conn, job_id)?;
-            }
+    Ok(true)
+}
 
-            ctx.event_bus.broadcast(
-                EventCategory::Admin,
-                EventPayload::JobFailed {
-                    job_id,
-                    error: error_msg,
-                },
+/// A claimed job registered in [`AppContext::active_jobs`], so it can be
+/// paused and cancelled from the API. Unregistered when dropped.
+struct ActiveJob<'a> {
+    ctx: &'a AppContext,
+    job_id: sf_core::JobId,
+}
+
+impl<'a> ActiveJob<'a> {
+    fn register(
+        ctx: &'a AppContext,
+        job_id: sf_core::JobId,
+        control: sf_av::ProcessControl,
+    ) -> Self {
+        ctx.active_jobs.insert(job_id, control);
+        Self { ctx, job_id }
+    }
+}
+
+impl Drop for ActiveJob<'_> {
+    fn drop(&mut self) {
+        self.ctx.active_jobs.remove(&self.job_id);
+    }
+}
+
+/// Report a job stopped by a cancel request, which has already marked it as
+/// cancelled.
+fn report_cancelled(ctx: &AppContext, job_id: sf_core::JobId) {
+    tracing::info!(job_id = %job_id, "Job cancelled");
+    ctx.event_bus
+        .broadcast(EventCategory::Admin, EventPayload::JobCancelled { job_id });
+}
+
+/// Record a failed job. Transient failures are requeued with exponential
+/// backoff while retries remain; anything else fails the job for good.
+fn handle_job_failure(
+    ctx: &AppContext,
+    pool: &WorkerPool,
+    job: &sf_db::models::Job,
+    error: sf_core::Error,
+) -> sf_core::Result<()> {
+    let job_id = job.id;
+    let error_msg = error.to_string();
+    let conn = sf_db::pool::get_conn(&ctx.db)?;
+
+    let retry_at = (error.is_transient() && job.retry_count < job.max_retries).then(|| {
+        let backoff = pool.config.retry_delay(job.retry_count.max(0) as u32);
+        (backoff, Utc::now() + chrono::Duration::from_std(backoff).unwrap_or_default())
+    });
+    let retried = match retry_at {
+        Some((backoff, at)) => {
+            tracing::warn!(
+                job_id = %job_id,
+                error = %error_msg,
+                retry = job.retry_count + 1,
+                backoff_secs = backoff.as_secs(),
+                "Job failed; scheduling retry"
             );
+            sf_db::queries::jobs::schedule_retry(&conn, job_id, &error_msg, at)?
+        }
+        None => false,
+    };
+    if !retried {
+        if !sf_db::queries::jobs::fail_job(&conn, job_id, &error_msg)? {
+            // Cancelled while it was failing.
+            report_cancelled(ctx, job_id);
+            return Ok(());
         }
+        tracing::error!(job_id = %job_id, error = %error_msg, "Job failed");
     }
 
-    Ok(true)
+    ctx.event_bus.broadcast(
+        EventCategory::Admin,
+        EventPayload::JobFailed {
+            job_id,
+            error: error_msg,
+        },
+    );
+    Ok(())
 }
 
-/// Execute the pipeline for a single job.
-async fn execute_job(ctx: &AppContext, job: &sf_db::models::Job) -> sf_core::Result<()> {
+/// Probe the job's file, match rules and build the pipeline.
+fn prepare_job(ctx: &AppContext, job: &sf_db::models::Job) -> sf_core::Result<PreparedJob> {
     let path = std::path::Path::new(&job.file_path);
 
     // Probe the file.
     let media_info = ctx.prober.probe(path)?;
 
-    // Match rules.
-    let rules = ctx.config_store.get_rules();
-    let engine = RuleEngine::new(rules);
-    let matched_rule = engine
-        .find_matching_rule(&media_info)
+    // Match rules, using the rule set that applies to this file's library.
+    let rules = crate::rule_scope::effective_rules(ctx, path)?;
+    let match_mode = *ctx.config_store.match_mode.read();
+    let engine = RuleEngine::new(rules).with_match_mode(match_mode);
+    let plan = engine
+        .plan(&media_info)
         .ok_or_else(|| sf_core::Error::Validation("No matching rule found".into()))?;
 
-    // Update the job with the matched rule name.
+    // Update the job with the matched rule names.
     {
         let conn = sf_db::pool::get_conn(&ctx.db)?;
-        sf_db::queries::jobs::update_job_progress(&conn, job.id, 0.0, Some(&matched_rule.name))?;
+        sf_db::queries::jobs::update_job_progress(&conn, job.id, 0.0, Some(&plan.rule_names()))?;
     }
 
-    // Create actions from the rule.
-    let actions = create_actions(&matched_rule.actions, &ctx.tools)?;
+    // Create one pipeline from the merged actions of all matched rules.
+    let actions = create_actions(&plan.actions, &ctx.tools)?;
+
+    Ok(PreparedJob {
+        media_info,
+        actions,
+        class: plan.resource_class(),
+        heavy: plan.is_heavy(),
+    })
+}
+
+/// When a job claimed at `now` should start instead, or `None` if it may run
+/// right away.
+///
+/// Heavy jobs only run inside a processing window unless started via "run
+/// now". This is checked on every claim, so a deferred job picked up after
+/// its window closed again (after a backlog or a restart) waits for the next
+/// one.
+fn deferral(
+    job: &sf_db::models::Job,
+    heavy: bool,
+    schedule: &sf_core::config::ScheduleConfig,
+    now: chrono::DateTime<Local>,
+) -> Option<chrono::DateTime<Utc>> {
+    if !heavy || job.run_now {
+        return None;
+    }
+    let start = schedule.next_start(now.naive_local())?;
+    // A start inside a DST gap does not exist locally; run the job right away.
+    let start = start.and_local_timezone(Local).earliest()?;
+    Some(start.with_timezone(&Utc))
+}
+
+/// Execute the pipeline for a single job.
+async fn execute_job(
+    ctx: &AppContext,
+    job: &sf_db::models::Job,
+    prepared: PreparedJob,
+    control: sf_av::ProcessControl,
+) -> sf_core::Result<()> {
+    let path = std::path::Path::new(&job.file_path);
+    let PreparedJob {
+        media_info,
+        actions,
+        ..
+    } = prepared;
 
     if actions.is_empty() {
         return Ok(());
@@ -177,16 +386,134 @@ async fn execute_job(ctx: &AppContext, job: &sf_db::models::Job) -> sf_core::Res
         );
     });
 
-    let action_ctx = ActionContext::new(workspace, media_info, ctx.tools.clone())
-        .with_progress(progress);
+    let db = ctx.db.clone();
+    let event_bus = ctx.event_bus.clone();
+    let log = LogSender::new(move |entry| record_log_entry(&db, &event_bus, job_id, entry));
+
+    let action_ctx = ActionContext::new(workspace.clone(), media_info, ctx.tools.clone())
+        .with_progress(progress)
+        .with_log(log)
+        .with_control(control);
+
+    // Execute the pipeline, verifying the output before it is used, and
+    // record what each action did.
+    let executor = PipelineExecutor::new(actions)
+        .with_prober(ctx.prober.clone())
+        .with_verification(Verification {
+            prober: ctx.prober.clone(),
+            config: ctx.config.verification.clone(),
+        });
+    let report = executor.run(&action_ctx).await?;
+    drop(action_ctx);
+
+    if report.steps.iter().any(|step| step.result.output.is_some()) {
+        let workspace = Arc::try_unwrap(workspace).map_err(|_| {
+            sf_core::Error::Internal("workspace is still in use after the pipeline".into())
+        })?;
+        let task_ctx = ctx.clone();
+        let path = path.to_path_buf();
+        tokio::task::spawn_blocking(move || replace_original(&task_ctx, workspace, &path, job_id))
+            .await
+            .map_err(|e| sf_core::Error::Internal(format!("finalize task failed: {e}")))??;
+    }
 
-    // Execute the pipeline.
-    let executor = PipelineExecutor::new(actions);
-    executor.execute(&action_ctx).await?;
+    let steps: Vec<serde_json::Value> = report
+        .steps
+        .iter()
+        .map(|step| {
+            serde_json::json!({
+                "action": step.action,
+                "summary": step.result.summary,
+                "details": step.result.details,
+            })
+        })
+        .collect();
+    let conn = sf_db::pool::get_conn(&ctx.db)?;
+    sf_db::queries::jobs::set_job_result(&conn, job_id, &serde_json::json!({ "actions": steps }))?;
 
     Ok(())
 }
 
+/// Replace the original at `path` with the verified output in `workspace`,
+/// moving the original to the trash. If the output can't be put in place the
+/// original comes back. Blocking.
+fn replace_original(
+    ctx: &AppContext,
+    workspace: sf_av::Workspace,
+    path: &std::path::Path,
+    job_id: sf_core::JobId,
+) -> sf_core::Result<()> {
+    let trashed = crate::trash::move_to_trash(ctx, path, Some(job_id))?;
+    if let Err(e) = workspace.finalize(None) {
+        if let Some(entry) = trashed {
+            crate::trash::restore(ctx, entry.id)?;
+        }
+        return Err(e);
+    }
+    if trashed.is_some() {
+        if let Err(e) = crate::trash::enforce_retention(ctx, &ctx.config.trash) {
+            tracing::warn!("Trash retention error: {e}");
+        }
+    }
+    Ok(())
+}
+
+/// Persist an execution log entry and stream it to admin listeners.
+fn record_log_entry(
+    db: &sf_db::pool::DbPool,
+    event_bus: &sf_core::events::EventBus,
+    job_id: sf_core::JobId,
+    entry: LogEntry,
+) {
+    let (kind, action, run, duration, message) = match entry {
+        LogEntry::ActionStarted { action } => ("action_started", action, None, None, None),
+        LogEntry::ActionFinished {
+            action,
+            duration,
+            error,
+        } => ("action_finished", action, None, Some(duration), error),
+        LogEntry::Command { action, run } => {
+            let duration = run.duration;
+            let error = run.error.clone();
+            ("command", action, Some(run), Some(duration), error)
+        }
+    };
+    let duration_ms = duration.map(|d| d.as_millis() as i64);
+    let stderr = run
+        .as_ref()
+        .map(|r| r.stderr.as_str())
+        .filter(|s| !s.is_empty());
+
+    let new_log = sf_db::queries::job_logs::NewJobLog {
+        kind,
+        action: Some(action),
+        command: run.as_ref().map(|r| r.command.as_str()),
+        exit_code: run.as_ref().and_then(|r| r.exit_code),
+        stderr,
+        duration_ms,
+        message: message.as_deref(),
+    };
+    if let Err(e) = sf_db::pool::get_conn(db)
+        .and_then(|conn| sf_db::queries::job_logs::append_job_log(&conn, job_id, &new_log))
+    {
+        tracing::warn!(job_id = %job_id, "Failed to record job log entry: {e}");
+    }
+
+    event_bus.broadcast(
+        EventCategory::Admin,
+        EventPayload::JobLog {
+            job_id,
+            kind: kind.to_string(),
+            action: Some(action.to_string()),
+            command: new_log.command.map(String::from),
+            exit_code: new_log.exit_code,
+            stderr: stderr.map(String::from),
+            duration_ms,
+            message: message.clone(),
+        },
+    );
+}
+
 /// Fire non-blocking notifications to Jellyfin and *arr services after a job
 /// completes successfully. Errors in notifications are logged but never fail
 /// the job.
@@ -201,3 +528,91 @@ fn fire_post_job_notifications(ctx: &AppContext, job: &sf_db::models::Job) {
     let arrs = ctx.config_store.arrs.read().clone();
     notifications::spawn_arr_notifications(&manager, arrs, job.file_path.clone());
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+    use sf_core::config::{ScheduleConfig, ScheduleWindow};
+    use sf_db::queries::jobs;
+
+    /// A 01:00-07:00 window every day.
+    fn night_window() -> ScheduleConfig {
+        ScheduleConfig {
+            windows: vec![ScheduleWindow {
+                days: vec![],
+                start: NaiveTime::from_hms_opt(1, 0, 0).unwrap(),
+                end: NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+            }],
+        }
+    }
+
+    fn local(date: &str, time: &str) -> chrono::DateTime<Local> {
+        let naive = NaiveDateTime::new(
+            NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap(),
+            NaiveTime::parse_from_str(time, "%H:%M").unwrap(),
+        );
+        Local.from_local_datetime(&naive).earliest().unwrap()
+    }
+
+    #[test]
+    fn heavy_jobs_wait_for_the_window() {
+        let pool = sf_db::pool::init_memory_pool().unwrap();
+        let conn = sf_db::pool::get_conn(&pool).unwrap();
+        let job = jobs::create_job(&conn, "/enc.mkv", "enc.mkv", None, 0).unwrap();
+        let schedule = night_window();
+        let next = local("2024-01-02", "01:00").with_timezone(&Utc);
+
+        assert_eq!(
+            deferral(&job, true, &schedule, local("2024-01-01", "12:00")),
+            Some(next)
+        );
+        assert!(deferral(&job, true, &schedule, local("2024-01-02", "03:00")).is_none());
+        assert!(deferral(&job, false, &schedule, local("2024-01-01", "12:00")).is_none());
+    }
+
+    #[test]
+    fn deferred_jobs_claimed_late_are_deferred_again() {
+        let pool = sf_db::pool::init_memory_pool().unwrap();
+        let conn = sf_db::pool::get_conn(&pool).unwrap();
+        let job = jobs::create_job(&conn, "/enc.mkv", "enc.mkv", None, 0).unwrap();
+        let claimed = jobs::dequeue_next(&conn, "w1").unwrap().unwrap();
+        let earlier = Utc::now() - chrono::Duration::hours(12);
+        jobs::defer_job(&conn, claimed.id, "w1", earlier).unwrap();
+        let deferred = jobs::get_job(&conn, job.id).unwrap().unwrap();
+
+        // Its window has closed again by the time it is claimed.
+        let schedule = night_window();
+        assert_eq!(
+            deferral(&deferred, true, &schedule, local("2024-01-01", "12:00")),
+            Some(local("2024-01-02", "01:00").with_timezone(&Utc))
+        );
+
+        // "Run now" skips the window.
+        jobs::run_job_now(&conn, job.id).unwrap();
+        let forced = jobs::get_job(&conn, job.id).unwrap().unwrap();
+        let noon = local("2024-01-01", "12:00");
+        assert!(deferral(&forced, true, &schedule, noon).is_none());
+    }
+
+    #[test]
+    fn retried_heavy_jobs_wait_for_the_window() {
+        let pool = sf_db::pool::init_memory_pool().unwrap();
+        let conn = sf_db::pool::get_conn(&pool).unwrap();
+        let job = jobs::create_job(&conn, "/enc.mkv", "enc.mkv", None, 0).unwrap();
+        jobs::dequeue_next(&conn, "w1").unwrap().unwrap();
+        let backoff = Utc::now() - chrono::Duration::minutes(1);
+        let error = "Tool error [ffmpeg]: killed";
+        assert!(jobs::schedule_retry(&conn, job.id, error, backoff).unwrap());
+        let retried = jobs::get_job(&conn, job.id).unwrap().unwrap();
+        assert!(retried.scheduled_for.is_some());
+
+        // The retry's backoff has passed, but the window is closed.
+        let schedule = night_window();
+        assert_eq!(
+            deferral(&retried, true, &schedule, local("2024-01-01", "18:30")),
+            Some(local("2024-01-02", "01:00").with_timezone(&Utc))
+        );
+        assert!(deferral(&retried, true, &schedule, local("2024-01-02", "02:00")).is_none());
+    }
+}
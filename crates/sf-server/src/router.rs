@@ -28,6 +28,8 @@ use crate::routes;
         routes::libraries::create_library,
         routes::libraries::get_library,
         routes::libraries::delete_library,
+        routes::libraries::get_library_rules,
+        routes::libraries::put_library_rules,
         routes::libraries::scan_library,
         routes::libraries::list_library_items,
         routes::libraries::list_library_recent,
@@ -39,17 +41,32 @@ use crate::routes;
         routes::jobs::list_jobs,
         routes::jobs::submit_job,
         routes::jobs::get_job,
+        routes::jobs::get_job_log,
         routes::jobs::retry_job,
+        routes::jobs::run_job_now,
+        routes::jobs::pause_job,
+        routes::jobs::resume_job,
+        routes::jobs::cancel_job,
         routes::jobs::delete_job,
         routes::config::get_rules,
         routes::config::put_rules,
+        routes::config::explain_rules,
+        routes::config::get_match_mode,
+        routes::config::put_match_mode,
         routes::admin::dashboard,
         routes::admin::tools,
         routes::admin::stats,
+        routes::trash::list_trash,
+        routes::trash::purge_trash,
+        routes::trash::purge_trash_entry,
+        routes::trash::restore_trash_entry,
         routes::conversions::list_conversions,
         routes::conversions::submit_conversion,
         routes::conversions::get_conversion,
         routes::conversions::delete_conversion,
+        routes::conversions::pause_conversion,
+        routes::conversions::resume_conversion,
+        routes::conversions::cancel_conversion,
         routes::conversions::batch_convert,
         routes::conversions::dv_batch_convert,
         routes::playback::continue_watching,
@@ -74,6 +91,7 @@ use crate::routes;
         routes::items::MediaFileResponse,
         routes::items::ImageResponse,
         routes::jobs::JobResponse,
+        routes::jobs::JobLogResponse,
         routes::jobs::SubmitJobRequest,
         routes::conversions::ConversionJobResponse,
         routes::conversions::SubmitConversionRequest,
@@ -82,9 +100,13 @@ use crate::routes;
         routes::conversions::DvBatchConvertRequest,
         routes::admin::DashboardResponse,
         routes::admin::DashboardJobs,
+        routes::admin::DeferredJob,
         routes::admin::DashboardEventBus,
         routes::admin::LibraryStatsResponse,
         routes::admin::ProfileCounts,
+        routes::trash::TrashEntryResponse,
+        routes::trash::TrashListResponse,
+        routes::trash::PurgeTrashResponse,
         routes::playback::PlaybackResponse,
         routes::playback::UpdateProgressRequest,
         routes::playback::FavoriteResponse,
@@ -119,6 +141,10 @@ pub fn build_router(ctx: AppContext, static_dir: Option<PathBuf>) -> Router {
         .route("/libraries", post(routes::libraries::create_library))
         .route("/libraries/{id}", get(routes::libraries::get_library))
         .route("/libraries/{id}", delete(routes::libraries::delete_library))
+        .route(
+            "/libraries/{id}/rules",
+            get(routes::libraries::get_library_rules).put(routes::libraries::put_library_rules),
+        )
         .route(
             "/libraries/{id}/scan",
             post(routes::libraries::scan_library),
@@ -162,13 +188,23 @@ pub fn build_router(ctx: AppContext, static_dir: Option<PathBuf>) -> Router {
         .route("/jobs", get(routes::jobs::list_jobs))
         .route("/jobs/submit", post(routes::jobs::submit_job))
         .route("/jobs/{id}", get(routes::jobs::get_job))
+        .route("/jobs/{id}/log", get(routes::jobs::get_job_log))
         .route("/jobs/{id}/retry", post(routes::jobs::retry_job))
+        .route("/jobs/{id}/run-now", post(routes::jobs::run_job_now))
+        .route("/jobs/{id}/pause", post(routes::jobs::pause_job))
+        .route("/jobs/{id}/resume", post(routes::jobs::resume_job))
+        .route("/jobs/{id}/cancel", post(routes::jobs::cancel_job))
         .route("/jobs/{id}", delete(routes::jobs::delete_job))
         // SSE Events
         .route("/events", get(routes::events::events_handler))
         // Config
         .route("/config/rules", get(routes::config::get_rules))
         .route("/config/rules", put(routes::config::put_rules))
+        .route("/config/rules/explain", post(routes::config::explain_rules))
+        .route(
+            "/config/rules/match_mode",
+            get(routes::config::get_match_mode).put(routes::config::put_match_mode),
+        )
         .route(
             "/config/arrs",
             get(routes::config::get_arrs).post(routes::config::create_arr),
@@ -225,6 +261,18 @@ pub fn build_router(ctx: AppContext, static_dir: Option<PathBuf>) -> Router {
             "/conversions/{id}/priority",
             put(routes::conversions::update_priority),
         )
+        .route(
+            "/conversions/{id}/pause",
+            post(routes::conversions::pause_conversion),
+        )
+        .route(
+            "/conversions/{id}/resume",
+            post(routes::conversions::resume_conversion),
+        )
+        .route(
+            "/conversions/{id}/cancel",
+            post(routes::conversions::cancel_conversion),
+        )
         // Playback
         .route(
             "/playback/continue",
@@ -254,6 +302,10 @@ pub fn build_router(ctx: AppContext, static_dir: Option<PathBuf>) -> Router {
             post(routes::playback::add_favorite).delete(routes::playback::remove_favorite),
         )
         // Streaming
+        .route(
+            "/stream/{media_file_id}/master.m3u8",
+            get(routes::stream::hls_master_playlist),
+        )
         .route(
             "/stream/{media_file_id}/index.m3u8",
             get(routes::stream::hls_playlist),
@@ -302,6 +354,16 @@ pub fn build_router(ctx: AppContext, static_dir: Option<PathBuf>) -> Router {
             "/admin/invitations/{id}",
             delete(routes::invitations::delete_invitation),
         )
+        // Trash
+        .route(
+            "/trash",
+            get(routes::trash::list_trash).delete(routes::trash::purge_trash),
+        )
+        .route("/trash/{id}", delete(routes::trash::purge_trash_entry))
+        .route(
+            "/trash/{id}/restore",
+            post(routes::trash::restore_trash_entry),
+        )
         .layer(middleware::from_fn_with_state(ctx.clone(), admin_middleware));
 
     let protected_routes = protected_routes.merge(admin_routes);
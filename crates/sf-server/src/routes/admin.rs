@@ -20,6 +20,16 @@ pub struct DashboardJobs {
     pub total: usize,
     pub queued: usize,
     pub processing: usize,
+    /// Queued jobs waiting for a processing window, soonest first.
+    pub deferred: Vec<DeferredJob>,
+}
+
+/// A job deferred to a later start.
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct DeferredJob {
+    pub id: String,
+    pub file_name: String,
+    pub scheduled_for: String,
 }
 
 /// Event bus summary for the dashboard.
@@ -55,7 +65,7 @@ pub struct LibraryStatsResponse {
 )]
 pub async fn dashboard(State(ctx): State<AppContext>) -> Json<DashboardResponse> {
     let conn = sf_db::pool::get_conn(&ctx.db);
-    let (jobs_total, jobs_queued, jobs_processing) = if let Ok(conn) = conn {
+    let (jobs_total, jobs_queued, jobs_processing, deferred) = if let Ok(conn) = conn {
         let total = sf_db::queries::jobs::list_jobs(&conn, None, 0, 1000)
             .map(|j| j.len())
             .unwrap_or(0);
@@ -65,9 +75,18 @@ pub async fn dashboard(State(ctx): State<AppContext>) -> Json<DashboardResponse>
         let processing = sf_db::queries::jobs::list_jobs(&conn, Some("processing"), 0, 1000)
             .map(|j| j.len())
             .unwrap_or(0);
-        (total, queued, processing)
+        let deferred = sf_db::queries::jobs::list_deferred_jobs(&conn, 100)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|j| DeferredJob {
+                id: j.id.to_string(),
+                file_name: j.file_name,
+                scheduled_for: j.scheduled_for.unwrap_or_default(),
+            })
+            .collect();
+        (total, queued, processing, deferred)
     } else {
-        (0, 0, 0)
+        (0, 0, 0, Vec::new())
     };
 
     Json(DashboardResponse {
@@ -75,6 +94,7 @@ pub async fn dashboard(State(ctx): State<AppContext>) -> Json<DashboardResponse>
             total: jobs_total,
             queued: jobs_queued,
             processing: jobs_processing,
+            deferred,
         },
         event_bus: DashboardEventBus {
             recent_events: ctx.event_bus.recent_events(10).len(),
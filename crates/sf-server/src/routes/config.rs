@@ -34,6 +34,10 @@ pub async fn get_rules(State(ctx): State<AppContext>) -> Result<impl IntoRespons
 }
 
 /// PUT /api/config/rules
+///
+/// Each rule's `expr` may be either the JSON expression tree or a string in
+/// the rule DSL (e.g. `"codec == h265 and dv_profile == 7"`). The response
+/// always uses the JSON form.
 #[utoipa::path(
     put,
     path = "/api/config/rules",
@@ -48,7 +52,7 @@ pub async fn put_rules(
 ) -> Result<impl IntoResponse, AppError> {
     // Use sf_rules helpers to keep serde monomorphization in sf-rules crate.
     let rules = sf_rules::deserialize_rules(&body)
-        .map_err(|e| sf_core::Error::Validation(format!("invalid rules JSON: {e}")))?;
+        .map_err(|e| sf_core::Error::Validation(format!("invalid rules: {e}")))?;
     ctx.config_store.set_rules(rules.clone());
     ctx.config_store.persist();
     let value = sf_rules::rules_to_value(&rules)
@@ -56,6 +60,151 @@ pub async fn put_rules(
     Ok(Json(value))
 }
 
+/// Body of GET/PUT /api/config/rules/match_mode.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MatchModeBody {
+    pub match_mode: sf_rules::MatchMode,
+}
+
+/// GET /api/config/rules/match_mode
+#[utoipa::path(
+    get,
+    path = "/api/config/rules/match_mode",
+    responses(
+        (status = 200, description = "Current rule match mode", body = serde_json::Value)
+    )
+)]
+pub async fn get_match_mode(State(ctx): State<AppContext>) -> impl IntoResponse {
+    Json(MatchModeBody {
+        match_mode: *ctx.config_store.match_mode.read(),
+    })
+}
+
+/// PUT /api/config/rules/match_mode
+///
+/// `first` (the default) applies only the highest-priority matching rule,
+/// plus following matches for rules marked `continue: true`. `all` applies
+/// every matching rule. Either way the actions of all applied rules run in a
+/// single pipeline.
+#[utoipa::path(
+    put,
+    path = "/api/config/rules/match_mode",
+    request_body = serde_json::Value,
+    responses(
+        (status = 200, description = "Match mode updated", body = serde_json::Value)
+    )
+)]
+pub async fn put_match_mode(
+    State(ctx): State<AppContext>,
+    Json(body): Json<MatchModeBody>,
+) -> impl IntoResponse {
+    *ctx.config_store.match_mode.write() = body.match_mode;
+    ctx.config_store.persist();
+    Json(body)
+}
+
+/// POST /api/config/rules/explain
+///
+/// Evaluates every rule against a file and returns a trace of each expression
+/// node with its result and the media property it was compared against.
+///
+/// The body must contain either `file_path` or `media_file_id`. An optional
+/// `rules` array (same format as `PUT /api/config/rules`) explains those rules
+/// instead of the saved global ones, so edits can be checked before saving.
+/// Either way, the file's library overrides and rule scopes are applied first.
+/// An optional `match_mode` overrides the saved one.
+///
+/// `matched_rules` lists the rules whose actions would run and `actions` the
+/// merged pipeline; `matched_rule` is the first of `matched_rules`.
+#[utoipa::path(
+    post,
+    path = "/api/config/rules/explain",
+    request_body = serde_json::Value,
+    responses(
+        (status = 200, description = "Rule evaluation trace", body = serde_json::Value),
+        (status = 404, description = "Media file not found")
+    )
+)]
+pub async fn explain_rules(
+    State(ctx): State<AppContext>,
+    body: String,
+) -> Result<impl IntoResponse, AppError> {
+    // Parsed by hand so that any inline rules go through sf_rules helpers.
+    let req: serde_json::Value = serde_json::from_str(&body)
+        .map_err(|e| sf_core::Error::Validation(format!("invalid request body: {e}")))?;
+
+    let path = match (
+        req.get("file_path").and_then(|v| v.as_str()),
+        req.get("media_file_id").and_then(|v| v.as_str()),
+    ) {
+        (Some(path), None) => std::path::PathBuf::from(path),
+        (None, Some(id)) => {
+            let media_file_id: sf_core::MediaFileId = id
+                .parse()
+                .map_err(|_| sf_core::Error::Validation("Invalid media_file_id".into()))?;
+            let conn = sf_db::pool::get_conn(&ctx.db)?;
+            let media_file = sf_db::queries::media_files::get_media_file(&conn, media_file_id)?
+                .ok_or_else(|| sf_core::Error::not_found("media_file", media_file_id))?;
+            std::path::PathBuf::from(media_file.file_path)
+        }
+        _ => {
+            return Err(sf_core::Error::Validation(
+                "Exactly one of file_path or media_file_id is required".into(),
+            )
+            .into())
+        }
+    };
+
+    let global = match req.get("rules") {
+        Some(value) => sf_rules::rules_from_value(value)
+            .map_err(|e| sf_core::Error::Validation(format!("invalid rules: {e}")))?,
+        None => ctx.config_store.get_rules(),
+    };
+    let match_mode = match req.get("match_mode") {
+        Some(value) => serde_json::from_value(value.clone())
+            .map_err(|e| sf_core::Error::Validation(format!("invalid match_mode: {e}")))?,
+        None => *ctx.config_store.match_mode.read(),
+    };
+
+    if !path.exists() {
+        return Err(sf_core::Error::Validation(format!(
+            "File does not exist: {}",
+            path.display()
+        ))
+        .into());
+    }
+
+    let prober = ctx.prober.clone();
+    let probe_path = path.clone();
+    let media_info = tokio::task::spawn_blocking(move || prober.probe(&probe_path))
+        .await
+        .map_err(|e| sf_core::Error::Internal(format!("Probe task join error: {e}")))??;
+
+    let rules = crate::rule_scope::effective_rules_with(&ctx, &global, &path)?;
+    let engine = sf_rules::RuleEngine::new(rules).with_match_mode(match_mode);
+    let traces = engine.explain(&media_info);
+    let matched_rules: Vec<_> = traces
+        .iter()
+        .filter(|t| t.applied)
+        .map(|t| serde_json::json!({ "id": t.rule_id, "name": t.name }))
+        .collect();
+    let actions = engine
+        .plan(&media_info)
+        .map(|plan| plan.actions)
+        .unwrap_or_default();
+    let traces = sf_rules::traces_to_value(&traces)
+        .map_err(|e| sf_core::Error::Internal(format!("serialize rule traces: {e}")))?;
+
+    Ok(Json(serde_json::json!({
+        "file_path": path.to_string_lossy(),
+        "match_mode": match_mode,
+        "matched_rule": matched_rules.first(),
+        "matched_rules": matched_rules,
+        "actions": actions,
+        "rules": traces,
+    })))
+}
+
 // ---------------------------------------------------------------------------
 // Arrs
 // ---------------------------------------------------------------------------
@@ -54,6 +54,8 @@ pub struct ConversionJobResponse {
     pub created_at: String,
     pub started_at: Option<String>,
     pub completed_at: Option<String>,
+    /// Whether the running conversion is paused.
+    pub paused: bool,
 }
 
 impl ConversionJobResponse {
@@ -61,6 +63,7 @@ impl ConversionJobResponse {
         job: &sf_db::models::ConversionJob,
         item_name: Option<String>,
         source_mf: Option<&sf_db::models::MediaFile>,
+        ctx: &AppContext,
     ) -> Self {
         let source_resolution = source_mf.and_then(|mf| {
             match (mf.resolution_width, mf.resolution_height) {
@@ -91,6 +94,10 @@ impl ConversionJobResponse {
             created_at: job.created_at.clone(),
             started_at: job.started_at.clone(),
             completed_at: job.completed_at.clone(),
+            paused: ctx
+                .active_conversions
+                .get(&job.id)
+                .is_some_and(|control| control.is_paused()),
         }
     }
 }
@@ -154,7 +161,7 @@ pub async fn submit_conversion(
 
     Ok((
         StatusCode::CREATED,
-        Json(ConversionJobResponse::from_model(&job, item_name, Some(&source_mf))),
+        Json(ConversionJobResponse::from_model(&job, item_name, Some(&source_mf), &ctx)),
     ))
 }
 
@@ -201,7 +208,12 @@ pub async fn list_conversions(
         .iter()
         .map(|job| {
             let source_mf = job.source_media_file_id.and_then(|id| source_mf_map.get(&id));
-            ConversionJobResponse::from_model(job, name_map.get(&job.item_id).cloned(), source_mf)
+            ConversionJobResponse::from_model(
+                job,
+                name_map.get(&job.item_id).cloned(),
+                source_mf,
+                &ctx,
+            )
         })
         .collect();
     Ok(Json(responses))
@@ -235,7 +247,7 @@ pub async fn get_conversion(
     let source_mf = job.source_media_file_id
         .and_then(|id| sf_db::queries::media_files::get_media_file(&conn, id).ok().flatten());
 
-    Ok(Json(ConversionJobResponse::from_model(&job, item_name, source_mf.as_ref())))
+    Ok(Json(ConversionJobResponse::from_model(&job, item_name, source_mf.as_ref(), &ctx)))
 }
 
 /// Request body for batch conversion.
@@ -444,8 +456,8 @@ pub async fn delete_conversion(
             let cancelled = sf_db::queries::conversion_jobs::cancel_conversion_job(&conn, job_id)?;
             // Trigger the cancellation token to kill the running ffmpeg process.
             if cancelled {
-                if let Some((_, token)) = ctx.active_conversions.remove(&job_id) {
-                    token.cancel();
+                if let Some((_, control)) = ctx.active_conversions.remove(&job_id) {
+                    control.cancel();
                 }
             }
             cancelled
@@ -471,6 +483,114 @@ pub async fn delete_conversion(
     Ok(StatusCode::OK)
 }
 
+/// POST /api/conversions/:id/pause
+///
+/// Stop a running conversion's encoder until it is resumed.
+#[utoipa::path(
+    post,
+    path = "/api/conversions/{id}/pause",
+    params(("id" = String, Path, description = "Conversion job ID")),
+    responses(
+        (status = 200, description = "Conversion paused"),
+        (status = 400, description = "Conversion is not running")
+    )
+)]
+pub async fn pause_conversion(
+    State(ctx): State<AppContext>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    set_conversion_paused(ctx, &id, true)
+}
+
+/// POST /api/conversions/:id/resume
+#[utoipa::path(
+    post,
+    path = "/api/conversions/{id}/resume",
+    params(("id" = String, Path, description = "Conversion job ID")),
+    responses(
+        (status = 200, description = "Conversion resumed"),
+        (status = 400, description = "Conversion is not running")
+    )
+)]
+pub async fn resume_conversion(
+    State(ctx): State<AppContext>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    set_conversion_paused(ctx, &id, false)
+}
+
+fn set_conversion_paused(ctx: AppContext, id: &str, paused: bool) -> Result<StatusCode, AppError> {
+    let job_id: sf_core::ConversionJobId = id
+        .parse()
+        .map_err(|_| sf_core::Error::Validation("Invalid conversion job ID".into()))?;
+
+    let changed = {
+        let control = ctx.active_conversions.get(&job_id).ok_or_else(|| {
+            sf_core::Error::Validation("Only running conversions can be paused or resumed".into())
+        })?;
+        if paused {
+            control.pause()
+        } else {
+            control.resume()
+        }
+    };
+
+    if changed {
+        let payload = if paused {
+            sf_core::events::EventPayload::ConversionPaused { job_id }
+        } else {
+            sf_core::events::EventPayload::ConversionResumed { job_id }
+        };
+        ctx.event_bus
+            .broadcast(sf_core::events::EventCategory::Admin, payload);
+    }
+
+    Ok(StatusCode::OK)
+}
+
+/// POST /api/conversions/:id/cancel
+///
+/// Cancel a queued or running conversion, killing its encoder. The job is
+/// kept (marked as failed) so it can be inspected or deleted later.
+#[utoipa::path(
+    post,
+    path = "/api/conversions/{id}/cancel",
+    params(("id" = String, Path, description = "Conversion job ID")),
+    responses(
+        (status = 200, description = "Conversion cancelled"),
+        (status = 400, description = "Conversion has already finished"),
+        (status = 404, description = "Conversion job not found")
+    )
+)]
+pub async fn cancel_conversion(
+    State(ctx): State<AppContext>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let job_id: sf_core::ConversionJobId = id
+        .parse()
+        .map_err(|_| sf_core::Error::Validation("Invalid conversion job ID".into()))?;
+
+    let conn = sf_db::pool::get_conn(&ctx.db)?;
+    if !sf_db::queries::conversion_jobs::cancel_conversion_job(&conn, job_id)? {
+        sf_db::queries::conversion_jobs::get_conversion_job(&conn, job_id)?
+            .ok_or_else(|| sf_core::Error::not_found("conversion_job", job_id))?;
+        return Err(
+            sf_core::Error::Validation("Conversion has already finished".into()).into(),
+        );
+    }
+
+    // A running conversion reports its cancellation once ffmpeg is gone.
+    match ctx.active_conversions.get(&job_id) {
+        Some(control) => control.cancel(),
+        None => ctx.event_bus.broadcast(
+            sf_core::events::EventCategory::Admin,
+            sf_core::events::EventPayload::ConversionCancelled { job_id },
+        ),
+    }
+
+    Ok(StatusCode::OK)
+}
+
 /// Request body for reordering the queue.
 #[derive(Debug, Deserialize, utoipa::ToSchema)]
 pub struct ReorderRequest {
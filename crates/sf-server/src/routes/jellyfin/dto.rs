@@ -97,6 +97,13 @@ pub struct MediaSourceDto {
     pub media_source_type: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub direct_stream_url: Option<String>,
+    /// Live transcoded HLS stream for clients that can't direct-play.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transcoding_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transcoding_sub_protocol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transcoding_container: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub media_streams: Option<Vec<MediaStreamDto>>,
 }
@@ -399,6 +399,9 @@ fn build_media_source(
         protocol: "File".to_string(),
         media_source_type: "Default".to_string(),
         direct_stream_url: Some(direct_stream_url),
+        transcoding_url: None,
+        transcoding_sub_protocol: None,
+        transcoding_container: None,
         media_streams: if streams.is_empty() { None } else { Some(streams) },
     }
 }
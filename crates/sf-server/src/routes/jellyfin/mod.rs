@@ -16,7 +16,7 @@ use axum::extract::Request;
 use axum::http::StatusCode;
 use axum::middleware::{self, Next};
 use axum::response::{IntoResponse, Response};
-use axum::routing::{get, post};
+use axum::routing::{delete, get, post};
 use axum::Router;
 
 use crate::context::AppContext;
@@ -175,6 +175,15 @@ pub fn jellyfin_router() -> Router<AppContext> {
             "/Videos/{id}/master.m3u8",
             get(streaming::master_playlist),
         )
+        // Live transcoding for clients that can't direct-play
+        .route(
+            "/Videos/{id}/{media_source_id}/Transcode/{play_session_id}/{file}",
+            get(streaming::transcode_file),
+        )
+        .route(
+            "/Videos/ActiveEncodings",
+            delete(streaming::stop_active_encoding),
+        )
         // Jellyfin subtitle delivery
         .route(
             "/Videos/{id}/{media_source_id}/Subtitles/{index}/0/Stream.vtt",
@@ -288,5 +288,8 @@ pub async fn stopped(
             );
         }
     }
+    if let Some(ref session_id) = report.play_session_id {
+        ctx.transcode.stop(session_id);
+    }
     Ok(StatusCode::NO_CONTENT)
 }
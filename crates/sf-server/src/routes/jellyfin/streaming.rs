@@ -1,14 +1,17 @@
 //! Jellyfin-compatible streaming and playback info endpoints.
 
+use std::path::PathBuf;
+
 use axum::extract::{Path, Query, State};
 use axum::http::{header, HeaderMap, StatusCode};
-use axum::response::IntoResponse;
+use axum::response::{IntoResponse, Response};
 use axum::Json;
 use serde::{Deserialize, Serialize};
 
 use crate::context::AppContext;
 use crate::error::AppError;
 use crate::hls_prep;
+use crate::transcode::TranscodeSource;
 
 use super::dto::{MediaSourceDto, MediaStreamDto, TICKS_PER_SECOND};
 
@@ -35,6 +38,7 @@ pub async fn playback_info(
         .ok_or_else(|| sf_core::Error::not_found("item", item_id))?;
 
     let media_files = sf_db::queries::media_files::list_media_files_by_item(&conn, item_id)?;
+    let play_session_id = uuid::Uuid::new_v4().to_string();
 
     let sources: Vec<MediaSourceDto> = media_files
         .iter()
@@ -110,6 +114,14 @@ pub async fn playback_info(
                 "/Videos/{}/stream?mediaSourceId={}&static=true",
                 item_id, mf.id,
             );
+            // Profile B files play everywhere; anything else may need
+            // transcoding on the fly.
+            let transcoding_url = (ctx.config.transcode.enabled && mf.profile != "B").then(|| {
+                format!(
+                    "/Videos/{}/{}/Transcode/{}/main.m3u8",
+                    item_id, mf.id, play_session_id,
+                )
+            });
             MediaSourceDto {
                 id: mf.id.to_string(),
                 name: mf.file_name.clone(),
@@ -119,10 +131,13 @@ pub async fn playback_info(
                 run_time_ticks: ticks,
                 supports_direct_stream: true,
                 supports_direct_play: true,
-                supports_transcoding: false,
+                supports_transcoding: transcoding_url.is_some(),
                 protocol: "File".to_string(),
                 media_source_type: "Default".to_string(),
                 direct_stream_url: Some(direct_stream_url),
+                transcoding_sub_protocol: transcoding_url.as_ref().map(|_| "hls".to_string()),
+                transcoding_container: transcoding_url.as_ref().map(|_| "mp4".to_string()),
+                transcoding_url,
                 media_streams: if streams.is_empty() {
                     None
                 } else {
@@ -134,7 +149,7 @@ pub async fn playback_info(
 
     Ok(Json(PlaybackInfoResponse {
         media_sources: sources,
-        play_session_id: uuid::Uuid::new_v4().to_string(),
+        play_session_id,
     }))
 }
 
@@ -254,6 +269,81 @@ pub async fn jellyfin_subtitle(
     ))
 }
 
+/// GET /Videos/{id}/{mediaSourceId}/Transcode/{playSessionId}/{file} — live
+/// transcoded HLS.
+///
+/// This is the `TranscodingUrl` handed out by PlaybackInfo. `file` is
+/// `main.m3u8`, `init.mp4` or `segment_N.m4s`; the first media request starts
+/// the session's ffmpeg process (see [`crate::transcode`]).
+pub async fn transcode_file(
+    State(ctx): State<AppContext>,
+    Path((item_id, media_source_id, play_session_id, file)): Path<(
+        String,
+        String,
+        String,
+        String,
+    )>,
+) -> Result<Response, AppError> {
+    if !ctx.config.transcode.enabled {
+        return Err(sf_core::Error::not_found("transcode session", play_session_id).into());
+    }
+    let item_id: sf_core::ItemId = item_id
+        .parse()
+        .map_err(|_| sf_core::Error::Validation("Invalid item_id".into()))?;
+    let mf_id: sf_core::MediaFileId = media_source_id
+        .parse()
+        .map_err(|_| sf_core::Error::Validation("Invalid mediaSourceId".into()))?;
+
+    let conn = sf_db::pool::get_conn(&ctx.db)?;
+    let mf = sf_db::queries::media_files::get_media_file(&conn, mf_id)?
+        .filter(|mf| mf.item_id == item_id)
+        .ok_or_else(|| sf_core::Error::not_found("media_file", mf_id))?;
+    drop(conn);
+
+    let source = TranscodeSource {
+        media_file_id: mf.id,
+        path: PathBuf::from(&mf.file_path),
+        duration_secs: mf
+            .duration_secs
+            .ok_or_else(|| sf_core::Error::Validation("Media file has no duration".into()))?,
+    };
+
+    if file == "main.m3u8" {
+        let playlist = ctx.transcode.playlist(&play_session_id, &source)?;
+        return Ok((
+            StatusCode::OK,
+            [("content-type", "application/vnd.apple.mpegurl")],
+            playlist,
+        )
+            .into_response());
+    }
+
+    let data = ctx.transcode.media(&play_session_id, &source, &file).await?;
+    let content_type = if file == "init.mp4" {
+        "video/mp4"
+    } else {
+        "video/iso.segment"
+    };
+    Ok((StatusCode::OK, [("content-type", content_type)], data).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ActiveEncodingsQuery {
+    #[serde(alias = "playSessionId", alias = "PlaySessionId")]
+    pub play_session_id: Option<String>,
+}
+
+/// DELETE /Videos/ActiveEncodings — stop a play session's transcoding.
+pub async fn stop_active_encoding(
+    State(ctx): State<AppContext>,
+    Query(params): Query<ActiveEncodingsQuery>,
+) -> StatusCode {
+    if let Some(ref id) = params.play_session_id {
+        ctx.transcode.stop(id);
+    }
+    StatusCode::NO_CONTENT
+}
+
 /// GET /Videos/{id}/master.m3u8 — HLS master playlist.
 ///
 /// Jellyfin clients request this for HLS playback. Redirects to our internal
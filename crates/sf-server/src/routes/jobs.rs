@@ -50,10 +50,21 @@ pub struct JobResponse {
     pub created_at: String,
     pub started_at: Option<String>,
     pub completed_at: Option<String>,
+    /// When a deferred job will start (outside processing windows), or when
+    /// it was made due via "run now".
+    pub scheduled_for: Option<String>,
+    /// Started via "run now", so processing windows don't apply.
+    pub run_now: bool,
+    /// Per-action summaries and details (e.g. loudness measurements),
+    /// recorded when the job completes.
+    #[schema(value_type = Option<Object>)]
+    pub result: Option<serde_json::Value>,
+    /// Whether the running job is paused.
+    pub paused: bool,
 }
 
 impl JobResponse {
-    fn from_model(job: &sf_db::models::Job) -> Self {
+    fn from_model(job: &sf_db::models::Job, ctx: &AppContext) -> Self {
         Self {
             id: job.id.to_string(),
             file_path: job.file_path.clone(),
@@ -70,6 +81,13 @@ impl JobResponse {
             created_at: job.created_at.clone(),
             started_at: job.started_at.clone(),
             completed_at: job.completed_at.clone(),
+            scheduled_for: job.scheduled_for.clone(),
+            run_now: job.run_now,
+            result: job.result.clone(),
+            paused: ctx
+                .active_jobs
+                .get(&job.id)
+                .is_some_and(|control| control.is_paused()),
         }
     }
 }
@@ -94,7 +112,10 @@ pub async fn list_jobs(
         params.offset,
         params.limit,
     )?;
-    let responses: Vec<JobResponse> = jobs.iter().map(JobResponse::from_model).collect();
+    let responses: Vec<JobResponse> = jobs
+        .iter()
+        .map(|job| JobResponse::from_model(job, &ctx))
+        .collect();
     Ok(Json(responses))
 }
 
@@ -135,7 +156,10 @@ pub async fn submit_job(
         sf_core::events::EventPayload::JobQueued { job_id: job.id },
     );
 
-    Ok((StatusCode::CREATED, Json(JobResponse::from_model(&job))))
+    Ok((
+        StatusCode::CREATED,
+        Json(JobResponse::from_model(&job, &ctx)),
+    ))
 }
 
 /// GET /api/jobs/:id
@@ -160,7 +184,70 @@ pub async fn get_job(
     let job = sf_db::queries::jobs::get_job(&conn, job_id)?
         .ok_or_else(|| sf_core::Error::not_found("job", job_id))?;
 
-    Ok(Json(JobResponse::from_model(&job)))
+    Ok(Json(JobResponse::from_model(&job, &ctx)))
+}
+
+/// An entry in a job's execution log.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct JobLogResponse {
+    pub id: i64,
+    /// `action_started`, `action_finished` or `command`.
+    pub kind: String,
+    pub action: Option<String>,
+    /// The tool command line, for `command` entries.
+    pub command: Option<String>,
+    pub exit_code: Option<i32>,
+    /// The tail of the tool's stderr.
+    pub stderr: Option<String>,
+    pub duration_ms: Option<i64>,
+    /// The error, if the action or command failed.
+    pub message: Option<String>,
+    pub created_at: String,
+}
+
+impl JobLogResponse {
+    fn from_model(log: sf_db::models::JobLog) -> Self {
+        Self {
+            id: log.id,
+            kind: log.kind,
+            action: log.action,
+            command: log.command,
+            exit_code: log.exit_code,
+            stderr: log.stderr,
+            duration_ms: log.duration_ms,
+            message: log.message,
+            created_at: log.created_at,
+        }
+    }
+}
+
+/// GET /api/jobs/:id/log
+#[utoipa::path(
+    get,
+    path = "/api/jobs/{id}/log",
+    params(("id" = String, Path, description = "Job ID")),
+    responses(
+        (status = 200, description = "Execution log, oldest first", body = Vec<JobLogResponse>),
+        (status = 404, description = "Job not found")
+    )
+)]
+pub async fn get_job_log(
+    State(ctx): State<AppContext>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<JobLogResponse>>, AppError> {
+    let job_id: sf_core::JobId = id
+        .parse()
+        .map_err(|_| sf_core::Error::Validation("Invalid job ID".into()))?;
+
+    let conn = sf_db::pool::get_conn(&ctx.db)?;
+    sf_db::queries::jobs::get_job(&conn, job_id)?
+        .ok_or_else(|| sf_core::Error::not_found("job", job_id))?;
+    let logs = sf_db::queries::job_logs::list_job_logs(&conn, job_id)?
+        .into_iter()
+        .map(JobLogResponse::from_model)
+        .collect();
+
+    Ok(Json(logs))
 }
 
 /// POST /api/jobs/:id/retry
@@ -198,7 +285,171 @@ pub async fn retry_job(
     Ok(Json(serde_json::json!({"status": "retried"})))
 }
 
+/// POST /api/jobs/:id/run-now
+#[utoipa::path(
+    post,
+    path = "/api/jobs/{id}/run-now",
+    params(("id" = String, Path, description = "Job ID")),
+    responses(
+        (status = 200, description = "Job will start as soon as a worker is free", body = JobResponse),
+        (status = 404, description = "Job not found")
+    )
+)]
+pub async fn run_job_now(
+    State(ctx): State<AppContext>,
+    Path(id): Path<String>,
+) -> Result<Json<JobResponse>, AppError> {
+    let job_id: sf_core::JobId = id
+        .parse()
+        .map_err(|_| sf_core::Error::Validation("Invalid job ID".into()))?;
+
+    let conn = sf_db::pool::get_conn(&ctx.db)?;
+    if !sf_db::queries::jobs::run_job_now(&conn, job_id)? {
+        sf_db::queries::jobs::get_job(&conn, job_id)?
+            .ok_or_else(|| sf_core::Error::not_found("job", job_id))?;
+        return Err(sf_core::Error::Validation("Only queued jobs can be run now".into()).into());
+    }
+    let job = sf_db::queries::jobs::get_job(&conn, job_id)?
+        .ok_or_else(|| sf_core::Error::not_found("job", job_id))?;
+
+    ctx.event_bus.broadcast(
+        sf_core::events::EventCategory::Admin,
+        sf_core::events::EventPayload::JobQueued { job_id },
+    );
+
+    Ok(Json(JobResponse::from_model(&job, &ctx)))
+}
+
+/// POST /api/jobs/:id/pause
+///
+/// Stop a running job's tool processes until it is resumed.
+#[utoipa::path(
+    post,
+    path = "/api/jobs/{id}/pause",
+    params(("id" = String, Path, description = "Job ID")),
+    responses(
+        (status = 200, description = "Job paused", body = JobResponse),
+        (status = 400, description = "Job is not running"),
+        (status = 404, description = "Job not found")
+    )
+)]
+pub async fn pause_job(
+    State(ctx): State<AppContext>,
+    Path(id): Path<String>,
+) -> Result<Json<JobResponse>, AppError> {
+    set_job_paused(ctx, &id, true)
+}
+
+/// POST /api/jobs/:id/resume
+#[utoipa::path(
+    post,
+    path = "/api/jobs/{id}/resume",
+    params(("id" = String, Path, description = "Job ID")),
+    responses(
+        (status = 200, description = "Job resumed", body = JobResponse),
+        (status = 400, description = "Job is not running"),
+        (status = 404, description = "Job not found")
+    )
+)]
+pub async fn resume_job(
+    State(ctx): State<AppContext>,
+    Path(id): Path<String>,
+) -> Result<Json<JobResponse>, AppError> {
+    set_job_paused(ctx, &id, false)
+}
+
+fn set_job_paused(ctx: AppContext, id: &str, paused: bool) -> Result<Json<JobResponse>, AppError> {
+    let job_id: sf_core::JobId = id
+        .parse()
+        .map_err(|_| sf_core::Error::Validation("Invalid job ID".into()))?;
+
+    let conn = sf_db::pool::get_conn(&ctx.db)?;
+    let job = sf_db::queries::jobs::get_job(&conn, job_id)?
+        .ok_or_else(|| sf_core::Error::not_found("job", job_id))?;
+
+    let changed = {
+        let control = ctx.active_jobs.get(&job_id).ok_or_else(|| {
+            sf_core::Error::Validation("Only running jobs can be paused or resumed".into())
+        })?;
+        if paused {
+            control.pause()
+        } else {
+            control.resume()
+        }
+    };
+
+    if changed {
+        let payload = if paused {
+            sf_core::events::EventPayload::JobPaused { job_id }
+        } else {
+            sf_core::events::EventPayload::JobResumed { job_id }
+        };
+        ctx.event_bus
+            .broadcast(sf_core::events::EventCategory::Admin, payload);
+    }
+
+    Ok(Json(JobResponse::from_model(&job, &ctx)))
+}
+
+/// POST /api/jobs/:id/cancel
+///
+/// Cancel a queued or running job. Running tool processes are killed.
+#[utoipa::path(
+    post,
+    path = "/api/jobs/{id}/cancel",
+    params(("id" = String, Path, description = "Job ID")),
+    responses(
+        (status = 200, description = "Job cancelled", body = JobResponse),
+        (status = 400, description = "Job has already finished"),
+        (status = 404, description = "Job not found")
+    )
+)]
+pub async fn cancel_job(
+    State(ctx): State<AppContext>,
+    Path(id): Path<String>,
+) -> Result<Json<JobResponse>, AppError> {
+    let job_id: sf_core::JobId = id
+        .parse()
+        .map_err(|_| sf_core::Error::Validation("Invalid job ID".into()))?;
+
+    let conn = sf_db::pool::get_conn(&ctx.db)?;
+    if !cancel(&ctx, &conn, job_id)? {
+        sf_db::queries::jobs::get_job(&conn, job_id)?
+            .ok_or_else(|| sf_core::Error::not_found("job", job_id))?;
+        return Err(sf_core::Error::Validation("Job has already finished".into()).into());
+    }
+
+    let job = sf_db::queries::jobs::get_job(&conn, job_id)?
+        .ok_or_else(|| sf_core::Error::not_found("job", job_id))?;
+    Ok(Json(JobResponse::from_model(&job, &ctx)))
+}
+
+/// Mark a queued or running job cancelled and kill its processes. Returns
+/// `false` if the job doesn't exist or has already finished.
+fn cancel(
+    ctx: &AppContext,
+    conn: &rusqlite::Connection,
+    job_id: sf_core::JobId,
+) -> sf_core::Result<bool> {
+    if !sf_db::queries::jobs::cancel_job(conn, job_id)? {
+        return Ok(false);
+    }
+
+    // A running job reports its cancellation once its processes are gone.
+    match ctx.active_jobs.get(&job_id) {
+        Some(control) => control.cancel(),
+        None => ctx.event_bus.broadcast(
+            sf_core::events::EventCategory::Admin,
+            sf_core::events::EventPayload::JobCancelled { job_id },
+        ),
+    }
+    Ok(true)
+}
+
 /// DELETE /api/jobs/:id
+///
+/// Same as `POST /api/jobs/{id}/cancel`, except that finished jobs are
+/// left alone instead of rejected.
 #[utoipa::path(
     delete,
     path = "/api/jobs/{id}",
@@ -215,9 +466,8 @@ pub async fn delete_job(
         .parse()
         .map_err(|_| sf_core::Error::Validation("Invalid job ID".into()))?;
 
-    // Mark as failed with a cancellation message.
     let conn = sf_db::pool::get_conn(&ctx.db)?;
-    let _ = sf_db::queries::jobs::fail_job(&conn, job_id, "Cancelled by user")?;
+    cancel(&ctx, &conn, job_id)?;
 
     Ok(StatusCode::NO_CONTENT)
 }
@@ -81,12 +81,14 @@ pub async fn create_library(
         return Err(sf_core::Error::Validation("name is required".into()).into());
     }
 
-    let conn = sf_db::pool::get_conn(&ctx.db)?;
     let config = if payload.config.is_null() {
         serde_json::json!({})
     } else {
         payload.config
     };
+    validate_library_rules(&config)?;
+
+    let conn = sf_db::pool::get_conn(&ctx.db)?;
     let lib = sf_db::queries::libraries::create_library(
         &conn,
         &payload.name,
@@ -166,6 +168,103 @@ pub async fn delete_library(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Reject library configs whose rule overrides don't parse.
+fn validate_library_rules(config: &serde_json::Value) -> Result<(), AppError> {
+    sf_rules::LibraryRules::from_library_config(config)
+        .map_err(|e| sf_core::Error::Validation(format!("invalid library rules: {e}")))?;
+    Ok(())
+}
+
+/// Render a library's rule overrides as `{ "rule_mode": ..., "rules": [...] }`.
+fn library_rules_response(config: &serde_json::Value) -> Result<serde_json::Value, AppError> {
+    let library_rules = sf_rules::LibraryRules::from_library_config(config)
+        .map_err(|e| sf_core::Error::Internal(format!("invalid library rules: {e}")))?;
+    let mut value = serde_json::json!({});
+    library_rules
+        .write_to_library_config(&mut value)
+        .map_err(|e| sf_core::Error::Internal(format!("serialize rules: {e}")))?;
+    Ok(value)
+}
+
+/// GET /api/libraries/:id/rules
+///
+/// Returns the library's rule overrides. `rule_mode` is `extend` (library
+/// rules are evaluated alongside the global rules) or `replace` (only the
+/// library rules are evaluated).
+#[utoipa::path(
+    get,
+    path = "/api/libraries/{id}/rules",
+    params(("id" = String, Path, description = "Library ID")),
+    responses(
+        (status = 200, description = "Library rule overrides", body = serde_json::Value),
+        (status = 404, description = "Library not found")
+    )
+)]
+pub async fn get_library_rules(
+    State(ctx): State<AppContext>,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let lib_id: sf_core::LibraryId = id
+        .parse()
+        .map_err(|_| sf_core::Error::Validation("Invalid library ID".into()))?;
+
+    let conn = sf_db::pool::get_conn(&ctx.db)?;
+    let lib = sf_db::queries::libraries::get_library(&conn, lib_id)?
+        .ok_or_else(|| sf_core::Error::not_found("library", lib_id))?;
+
+    Ok(Json(library_rules_response(&lib.config)?))
+}
+
+/// PUT /api/libraries/:id/rules
+///
+/// Replaces the library's rule overrides, stored in its `config` column.
+/// Rule expressions may be JSON or rule DSL text, as for
+/// `PUT /api/config/rules`. Other keys in the library config are preserved.
+#[utoipa::path(
+    put,
+    path = "/api/libraries/{id}/rules",
+    params(("id" = String, Path, description = "Library ID")),
+    request_body = serde_json::Value,
+    responses(
+        (status = 200, description = "Library rule overrides updated", body = serde_json::Value),
+        (status = 404, description = "Library not found")
+    )
+)]
+pub async fn put_library_rules(
+    State(ctx): State<AppContext>,
+    Path(id): Path<String>,
+    body: String,
+) -> Result<impl IntoResponse, AppError> {
+    let lib_id: sf_core::LibraryId = id
+        .parse()
+        .map_err(|_| sf_core::Error::Validation("Invalid library ID".into()))?;
+
+    // Parsed by hand so that rules go through sf_rules helpers.
+    let value: serde_json::Value = serde_json::from_str(&body)
+        .map_err(|e| sf_core::Error::Validation(format!("invalid request body: {e}")))?;
+    let library_rules = sf_rules::LibraryRules::from_library_config(&value)
+        .map_err(|e| sf_core::Error::Validation(format!("invalid library rules: {e}")))?;
+
+    let conn = sf_db::pool::get_conn(&ctx.db)?;
+    let lib = sf_db::queries::libraries::get_library(&conn, lib_id)?
+        .ok_or_else(|| sf_core::Error::not_found("library", lib_id))?;
+
+    let mut config = lib.config.clone();
+    library_rules
+        .write_to_library_config(&mut config)
+        .map_err(|e| sf_core::Error::Internal(format!("serialize rules: {e}")))?;
+    sf_db::queries::libraries::update_library(
+        &conn,
+        lib.id,
+        &lib.name,
+        &lib.media_type,
+        &lib.paths,
+        &config,
+    )?;
+
+    Ok(Json(library_rules_response(&config)?))
+}
+
 /// Query parameters for library item listing.
 #[derive(Debug, Deserialize, utoipa::IntoParams)]
 pub struct LibraryItemsParams {
@@ -18,5 +18,6 @@ pub mod playback;
 pub mod stream;
 pub mod streaming_helpers;
 pub mod subtitles;
+pub mod trash;
 pub mod users;
 pub mod webhook;
@@ -2,9 +2,13 @@
 //! file streaming.
 //!
 //! HLS segments are served zero-copy: moof+mdat headers come from RAM, sample
-//! data is read from the source MP4 file on demand.
+//! data is read from the source file on demand. Files with several audio
+//! tracks expose the extra ones as alternate renditions in `master.m3u8`,
+//! alongside segmented WebVTT renditions of their text subtitle tracks.
 //! Direct streaming serves source files with HTTP range request support.
 
+use std::collections::HashSet;
+
 use axum::extract::{Path, State};
 use axum::http::{header, HeaderMap, StatusCode};
 use axum::response::IntoResponse;
@@ -13,6 +17,61 @@ use crate::context::AppContext;
 use crate::error::AppError;
 use crate::hls_prep;
 
+/// GET /api/stream/:media_file_id/master.m3u8
+pub async fn hls_master_playlist(
+    State(ctx): State<AppContext>,
+    Path(media_file_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let mf_id: sf_core::MediaFileId = media_file_id
+        .parse()
+        .map_err(|_| sf_core::Error::Validation("Invalid media_file_id".into()))?;
+
+    let prepared = hls_prep::get_or_populate(&ctx, mf_id).await?;
+    let subtitles = subtitle_renditions(&hls_prep::text_subtitle_tracks(&ctx, mf_id)?);
+
+    Ok((
+        StatusCode::OK,
+        [("content-type", "application/vnd.apple.mpegurl")],
+        prepared.master_playlist(subtitles),
+    ))
+}
+
+/// `#EXT-X-MEDIA TYPE=SUBTITLES` entries for a file's text subtitle tracks,
+/// named by language and made unique within the group.
+fn subtitle_renditions(tracks: &[sf_db::models::SubtitleTrack]) -> Vec<sf_media::Rendition> {
+    let mut used_names = HashSet::new();
+    tracks
+        .iter()
+        .enumerate()
+        .map(|(i, track)| {
+            let mut base = track
+                .language
+                .clone()
+                .unwrap_or_else(|| format!("Subtitles {}", i + 1));
+            if track.forced {
+                base.push_str(" (forced)");
+            }
+            let mut name = base.clone();
+            let mut n = 2;
+            while !used_names.insert(name.clone()) {
+                name = format!("{base} {n}");
+                n += 1;
+            }
+            sf_media::Rendition {
+                media_type: sf_media::MediaType::Subtitles,
+                group_id: sf_media::SUBTITLE_GROUP.to_string(),
+                language: track.language.clone(),
+                name,
+                default: track.default_track,
+                autoselect: true,
+                forced: track.forced,
+                channels: None,
+                uri: Some(format!("subtitles_{}.m3u8", track.track_index)),
+            }
+        })
+        .collect()
+}
+
 /// GET /api/stream/:media_file_id/index.m3u8
 pub async fn hls_playlist(
     State(ctx): State<AppContext>,
@@ -33,7 +92,10 @@ pub async fn hls_playlist(
 
 /// GET /api/stream/:media_file_id/:segment
 ///
-/// Serves `init.mp4` or `segment_N.m4s` from the in-memory cache + source file.
+/// Serves `init.mp4` or `segment_N.m4s` from the in-memory cache + source file,
+/// alternate audio renditions as `audio_R.m3u8`, `audio_R_init.mp4` and
+/// `audio_R_segment_N.m4s`, and subtitle renditions as `subtitles_T.m3u8` and
+/// `subtitles_T_segment_N.vtt`.
 pub async fn hls_segment(
     State(ctx): State<AppContext>,
     Path((media_file_id, segment)): Path<(String, String)>,
@@ -62,15 +124,58 @@ pub async fn hls_segment(
             .into_response());
     }
 
+    if let Some(rest) = segment.strip_prefix("subtitles_") {
+        return subtitle_segment(&ctx, mf_id, &prepared, &segment, rest).await;
+    }
+
+    let (segments, seg_name) = match segment.strip_prefix("audio_") {
+        Some(rest) => {
+            // audio_R.m3u8, or audio_R_<file>.
+            let (rendition, file) = match rest.strip_suffix(".m3u8") {
+                Some(rendition) => (rendition, None),
+                None => {
+                    let (rendition, file) = rest
+                        .split_once('_')
+                        .ok_or_else(|| sf_core::Error::not_found("segment", &segment))?;
+                    (rendition, Some(file))
+                }
+            };
+            let rendition = rendition
+                .parse::<usize>()
+                .ok()
+                .and_then(|r| prepared.audio_renditions.get(r))
+                .ok_or_else(|| sf_core::Error::not_found("segment", &segment))?;
+            match file {
+                None => {
+                    return Ok((
+                        StatusCode::OK,
+                        [("content-type", "application/vnd.apple.mpegurl")],
+                        rendition.playlist.clone(),
+                    )
+                        .into_response());
+                }
+                Some("init.mp4") => {
+                    return Ok((
+                        StatusCode::OK,
+                        [("content-type", "video/mp4")],
+                        rendition.init_segment.clone(),
+                    )
+                        .into_response());
+                }
+                Some(file) => (&rendition.segments, file),
+            }
+        }
+        None => (&prepared.segments, segment.as_str()),
+    };
+
     // Parse segment_N.m4s
-    let seg_index = segment
+    let seg_index = seg_name
         .strip_prefix("segment_")
         .and_then(|s| s.strip_suffix(".m4s"))
         .and_then(|s| s.parse::<usize>().ok())
         .ok_or_else(|| sf_core::Error::not_found("segment", &segment))?;
 
-    let seg = prepared
-        .segments
+    let seg = segments
         .get(seg_index)
         .ok_or_else(|| sf_core::Error::not_found("segment", &segment))?;
 
@@ -142,6 +247,57 @@ pub async fn hls_segment(
         .into_response())
 }
 
+/// Serve `subtitles_T.m3u8` or `subtitles_T_segment_N.vtt` (`rest` is the
+/// file name after `subtitles_`). Segments hold the cues overlapping the
+/// matching video segment.
+async fn subtitle_segment(
+    ctx: &AppContext,
+    mf_id: sf_core::MediaFileId,
+    prepared: &sf_media::PreparedMedia,
+    segment: &str,
+    rest: &str,
+) -> Result<axum::response::Response, AppError> {
+    if let Some(track) = rest
+        .strip_suffix(".m3u8")
+        .and_then(|t| t.parse::<i32>().ok())
+    {
+        if !hls_prep::text_subtitle_tracks(ctx, mf_id)?
+            .iter()
+            .any(|t| t.track_index == track)
+        {
+            return Err(sf_core::Error::not_found("segment", segment).into());
+        }
+        return Ok((
+            StatusCode::OK,
+            [("content-type", "application/vnd.apple.mpegurl")],
+            prepared.subtitle_playlist(track),
+        )
+            .into_response());
+    }
+
+    // subtitles_T_segment_N.vtt
+    let (track, seg_index) = rest
+        .strip_suffix(".vtt")
+        .and_then(|r| r.split_once("_segment_"))
+        .and_then(|(t, n)| Some((t.parse::<i32>().ok()?, n.parse::<usize>().ok()?)))
+        .ok_or_else(|| sf_core::Error::not_found("segment", segment))?;
+    if seg_index >= prepared.segments.len() {
+        return Err(sf_core::Error::not_found("segment", segment).into());
+    }
+
+    let cues = hls_prep::get_subtitle_cues(ctx, mf_id, track).await?;
+    let vtt = prepared
+        .subtitle_segment(&cues, seg_index)
+        .ok_or_else(|| sf_core::Error::not_found("segment", segment))?;
+
+    Ok((
+        StatusCode::OK,
+        [("content-type", "text/vtt; charset=utf-8")],
+        vtt,
+    )
+        .into_response())
+}
+
 /// GET /api/stream/:media_file_id/direct
 ///
 /// Serve the source file directly with HTTP range request support.
@@ -8,6 +8,7 @@ use serde::Serialize;
 
 use crate::context::AppContext;
 use crate::error::AppError;
+use crate::hls_prep;
 
 #[derive(Debug, Serialize)]
 pub struct SubtitleTrackResponse {
@@ -80,27 +81,7 @@ pub async fn get_subtitle(
     drop(conn);
 
     // Extract via ffmpeg to WebVTT.
-    let output = tokio::process::Command::new("ffmpeg")
-        .args([
-            "-i",
-            &mf.file_path,
-            "-map",
-            &format!("0:s:{}", track.track_index),
-            "-f",
-            "webvtt",
-            "-",
-        ])
-        .stdout(std::process::Stdio::piped())
-        .stderr(std::process::Stdio::null())
-        .output()
-        .await
-        .map_err(|e| sf_core::Error::Internal(format!("ffmpeg subtitle extraction failed: {e}")))?;
-
-    if !output.status.success() {
-        return Err(
-            sf_core::Error::Internal("ffmpeg subtitle extraction returned non-zero".into()).into(),
-        );
-    }
+    let vtt = hls_prep::extract_webvtt(&ctx.tools, &mf.file_path, track.track_index).await?;
 
     Ok((
         StatusCode::OK,
@@ -108,6 +89,6 @@ pub async fn get_subtitle(
             (header::CONTENT_TYPE, "text/vtt; charset=utf-8"),
             (header::CACHE_CONTROL, "public, max-age=86400"),
         ],
-        output.stdout,
+        vtt,
     ))
 }
@@ -0,0 +1,148 @@
+//! Trash routes: list, purge and restore replaced originals.
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Json;
+use serde::Serialize;
+
+use crate::context::AppContext;
+use crate::error::AppError;
+
+/// A replaced original kept in the trash.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct TrashEntryResponse {
+    pub id: String,
+    /// Where the file lived, and where a restore puts it back.
+    pub original_path: String,
+    pub trash_path: String,
+    pub library_id: Option<String>,
+    /// The job whose output replaced the file.
+    pub job_id: Option<String>,
+    pub size_bytes: i64,
+    pub created_at: String,
+    /// When retention purges the file, if it expires.
+    pub expires_at: Option<String>,
+}
+
+impl TrashEntryResponse {
+    fn from_model(entry: sf_db::models::TrashEntry) -> Self {
+        Self {
+            id: entry.id.to_string(),
+            original_path: entry.original_path,
+            trash_path: entry.trash_path,
+            library_id: entry.library_id.map(|id| id.to_string()),
+            job_id: entry.job_id.map(|id| id.to_string()),
+            size_bytes: entry.size_bytes,
+            created_at: entry.created_at,
+            expires_at: entry.expires_at,
+        }
+    }
+}
+
+/// The trash's contents.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct TrashListResponse {
+    /// Newest first.
+    pub entries: Vec<TrashEntryResponse>,
+    pub total_size_bytes: i64,
+}
+
+/// Result of purging the trash.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct PurgeTrashResponse {
+    pub purged: usize,
+}
+
+/// GET /api/trash
+#[utoipa::path(
+    get,
+    path = "/api/trash",
+    responses(
+        (status = 200, description = "Files in the trash", body = TrashListResponse)
+    )
+)]
+pub async fn list_trash(
+    State(ctx): State<AppContext>,
+) -> Result<Json<TrashListResponse>, AppError> {
+    let conn = sf_db::pool::get_conn(&ctx.db)?;
+    let entries = sf_db::queries::trash::list_trash_entries(&conn)?
+        .into_iter()
+        .map(TrashEntryResponse::from_model)
+        .collect();
+    let total_size_bytes = sf_db::queries::trash::total_trash_size(&conn)?;
+
+    Ok(Json(TrashListResponse {
+        entries,
+        total_size_bytes,
+    }))
+}
+
+/// DELETE /api/trash
+#[utoipa::path(
+    delete,
+    path = "/api/trash",
+    responses(
+        (status = 200, description = "Trash emptied", body = PurgeTrashResponse)
+    )
+)]
+pub async fn purge_trash(
+    State(ctx): State<AppContext>,
+) -> Result<Json<PurgeTrashResponse>, AppError> {
+    let purged = tokio::task::spawn_blocking(move || crate::trash::purge_all(&ctx))
+        .await
+        .map_err(|e| sf_core::Error::Internal(format!("purge task failed: {e}")))??;
+    Ok(Json(PurgeTrashResponse { purged }))
+}
+
+/// DELETE /api/trash/:id
+#[utoipa::path(
+    delete,
+    path = "/api/trash/{id}",
+    params(("id" = String, Path, description = "Trash entry ID")),
+    responses(
+        (status = 204, description = "File purged"),
+        (status = 404, description = "Trash entry not found")
+    )
+)]
+pub async fn purge_trash_entry(
+    State(ctx): State<AppContext>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let trash_id = parse_trash_id(&id)?;
+    let conn = sf_db::pool::get_conn(&ctx.db)?;
+    let entry = sf_db::queries::trash::get_trash_entry(&conn, trash_id)?
+        .ok_or_else(|| sf_core::Error::not_found("trash entry", trash_id))?;
+    drop(conn);
+
+    tokio::task::spawn_blocking(move || crate::trash::purge(&ctx, &entry))
+        .await
+        .map_err(|e| sf_core::Error::Internal(format!("purge task failed: {e}")))??;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /api/trash/:id/restore
+#[utoipa::path(
+    post,
+    path = "/api/trash/{id}/restore",
+    params(("id" = String, Path, description = "Trash entry ID")),
+    responses(
+        (status = 200, description = "File restored to its original path", body = TrashEntryResponse),
+        (status = 404, description = "Trash entry not found"),
+        (status = 409, description = "Trashed file is missing")
+    )
+)]
+pub async fn restore_trash_entry(
+    State(ctx): State<AppContext>,
+    Path(id): Path<String>,
+) -> Result<Json<TrashEntryResponse>, AppError> {
+    let trash_id = parse_trash_id(&id)?;
+    let entry = tokio::task::spawn_blocking(move || crate::trash::restore(&ctx, trash_id))
+        .await
+        .map_err(|e| sf_core::Error::Internal(format!("restore task failed: {e}")))??;
+    Ok(Json(TrashEntryResponse::from_model(entry)))
+}
+
+fn parse_trash_id(id: &str) -> Result<sf_core::TrashId, sf_core::Error> {
+    id.parse()
+        .map_err(|_| sf_core::Error::Validation("Invalid trash entry ID".into()))
+}
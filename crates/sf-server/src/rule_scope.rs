@@ -0,0 +1,78 @@
+//! Resolving the effective rule set for a file.
+//!
+//! Combines the global rules from the [`ConfigStore`](crate::context::ConfigStore)
+//! with the per-library overrides stored in `libraries.config`, then applies
+//! each rule's [`RuleScope`](sf_rules::RuleScope).
+
+use std::path::Path;
+
+use sf_core::Result;
+use sf_rules::{LibraryRules, Rule};
+
+use crate::context::AppContext;
+
+/// Find the library a file belongs to.
+///
+/// Files that have been scanned are looked up through their media file and
+/// item. Otherwise (e.g. a file just dropped by an *arr) the library whose
+/// configured path is the longest prefix of `path` is used.
+pub fn library_for_path(
+    conn: &rusqlite::Connection,
+    path: &Path,
+) -> Result<Option<sf_db::models::Library>> {
+    let path_str = path.to_string_lossy();
+    if let Some(mf) = sf_db::queries::media_files::get_media_file_by_path(conn, &path_str)? {
+        if let Some(item) = sf_db::queries::items::get_item(conn, mf.item_id)? {
+            return sf_db::queries::libraries::get_library(conn, item.library_id);
+        }
+    }
+
+    let libraries = sf_db::queries::libraries::list_libraries(conn)?;
+    Ok(libraries
+        .into_iter()
+        .filter_map(|lib| {
+            let depth = lib
+                .paths
+                .iter()
+                .filter(|root| path.starts_with(root.as_str()))
+                .map(|root| Path::new(root).components().count())
+                .max()?;
+            Some((depth, lib))
+        })
+        .max_by_key(|(depth, _)| *depth)
+        .map(|(_, lib)| lib))
+}
+
+/// The rules that apply to the file at `path`, starting from `global`.
+///
+/// Returns an error if the file's library has malformed rule overrides, so
+/// that a broken library config never silently falls back to the global rules.
+pub fn effective_rules_with(ctx: &AppContext, global: &[Rule], path: &Path) -> Result<Vec<Rule>> {
+    let conn = sf_db::pool::get_conn(&ctx.db)?;
+    let library = library_for_path(&conn, path)?;
+
+    let library_rules = library
+        .as_ref()
+        .map(|lib| {
+            LibraryRules::from_library_config(&lib.config).map_err(|e| {
+                sf_core::Error::Validation(format!(
+                    "invalid rules in library '{}' config: {e}",
+                    lib.name
+                ))
+            })
+        })
+        .transpose()?;
+
+    Ok(sf_rules::effective_rules(
+        global,
+        library.as_ref().map(|lib| lib.id),
+        library_rules.as_ref(),
+        path,
+    ))
+}
+
+/// The rules that apply to the file at `path`, starting from the saved global
+/// rules.
+pub fn effective_rules(ctx: &AppContext, path: &Path) -> Result<Vec<Rule>> {
+    effective_rules_with(ctx, &ctx.config_store.get_rules(), path)
+}
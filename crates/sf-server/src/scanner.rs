@@ -264,7 +264,7 @@ pub async fn scan_library(
                 .filter_entry(|e| {
                     if e.file_type().is_dir() {
                         if let Some(name) = e.file_name().to_str() {
-                            if name.ends_with(".hls") {
+                            if name.ends_with(".hls") || name.ends_with(".chunks") {
                                 return false;
                             }
                         }
@@ -435,8 +435,8 @@ pub async fn scan_library(
                         .map(|m| m.len() as i64)
                         .unwrap_or(0);
 
-                    // For Profile B files, pre-build the HLS segment data.
-                    let hls_blob = if info.classify_profile() == sf_core::Profile::B {
+                    // For MP4 files, pre-build the HLS segment data.
+                    let hls_blob = if serves_precomputed_hls(&info) {
                         try_build_prepared_media(&path_clone)
                     } else {
                         None
@@ -1231,8 +1231,8 @@ async fn ingest_converted_file(ctx: &AppContext, path: &Path) -> sf_core::Result
         .map(|dv| dv.profile as i32);
     let duration_secs = media_info.duration.map(|d| d.as_secs_f64());
 
-    // Build HLS blob for Profile B converted files.
-    let hls_blob = if profile == sf_core::Profile::B {
+    // Build HLS blob for Profile B converted files and other MP4s.
+    let hls_blob = if serves_precomputed_hls(&media_info) {
         try_build_prepared_media(path)
     } else {
         None
@@ -1261,13 +1261,23 @@ async fn ingest_converted_file(ctx: &AppContext, path: &Path) -> sf_core::Result
     Ok(true)
 }
 
-/// Try to parse the moov atom and build a serialized PreparedMedia blob.
+/// Whether a file can be streamed from precomputed HLS segments: Profile B
+/// conversions, and MP4s whose codecs (HEVC, Dolby Vision, E-AC-3, ...) fMP4
+/// players handle natively.
+///
+/// MKVs are indexed on first play instead: that reads every cluster's block
+/// headers, which is too slow to do for a whole library during a scan.
+fn serves_precomputed_hls(info: &sf_probe::types::MediaInfo) -> bool {
+    info.classify_profile() == sf_core::Profile::B || info.container == sf_core::Container::Mp4
+}
+
+/// Try to parse the container and build a serialized PreparedMedia blob.
 ///
 /// Returns `None` on any failure — this is non-fatal during scanning.
 fn try_build_prepared_media(path: &Path) -> Option<Vec<u8>> {
     let file = std::fs::File::open(path).ok()?;
     let mut reader = std::io::BufReader::new(file);
-    let metadata = sf_media::parse_moov(&mut reader).ok()?;
+    let metadata = sf_media::parse_media(&mut reader).ok()?;
     let prepared = sf_media::build_prepared_media(&metadata, path).ok()?;
     prepared.to_bincode().ok()
 }
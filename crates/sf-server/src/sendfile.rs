@@ -1,6 +1,7 @@
 //! True zero-copy HLS segment serving via sendfile(2).
 //!
-//! Segment requests (`GET /api/stream/{id}/segment_{N}.m4s`) are intercepted
+//! Segment requests (`GET /api/stream/{id}/segment_{N}.m4s` and alternate
+//! audio `GET /api/stream/{id}/audio_{R}_segment_{N}.m4s`) are intercepted
 //! before reaching Axum and served directly on the raw TCP socket using
 //! sendfile(2). This eliminates the userspace copy that would otherwise occur
 //! when reading file data into a `Vec<u8>` buffer.
@@ -31,6 +32,12 @@ pub enum PeekRoute {
         mf_id: sf_core::MediaFileId,
         index: usize,
     },
+    /// Alternate audio segment: `/api/stream/{mf_id}/audio_{rendition}_segment_{index}.m4s`
+    AudioSegment {
+        mf_id: sf_core::MediaFileId,
+        rendition: usize,
+        index: usize,
+    },
     /// Direct play: `/api/stream/{mf_id}/direct`
     Direct {
         mf_id: sf_core::MediaFileId,
@@ -78,6 +85,21 @@ fn classify_path(path: &str) -> Option<PeekRoute> {
             }
         }
 
+        if let Some(inner) = suffix.strip_prefix("audio_") {
+            if let Some((rendition_str, rest)) = inner.split_once("_segment_") {
+                if let Some(num_str) = rest.strip_suffix(".m4s") {
+                    let mf_id = uuid_part.parse().ok()?;
+                    let rendition = rendition_str.parse().ok()?;
+                    let index = num_str.parse().ok()?;
+                    return Some(PeekRoute::AudioSegment {
+                        mf_id,
+                        rendition,
+                        index,
+                    });
+                }
+            }
+        }
+
         return None;
     }
 
@@ -498,7 +520,12 @@ fn dispatch_route(
 ) -> io::Result<()> {
     tracing::debug!(route = ?route, range = ?req.range, "sendfile request");
     match *route {
-        PeekRoute::Segment { mf_id, index } => serve_segment(stream, ctx, mf_id, index),
+        PeekRoute::Segment { mf_id, index } => serve_segment(stream, ctx, mf_id, None, index),
+        PeekRoute::AudioSegment {
+            mf_id,
+            rendition,
+            index,
+        } => serve_segment(stream, ctx, mf_id, Some(rendition), index),
         PeekRoute::Direct { mf_id } => serve_direct(stream, ctx, mf_id, req.range),
         PeekRoute::JellyfinStream { item_id } => {
             serve_jellyfin_stream(stream, ctx, item_id, &req.path, req.range)
@@ -506,11 +533,13 @@ fn dispatch_route(
     }
 }
 
-/// Serve an HLS segment via sendfile(2).
+/// Serve an HLS segment via sendfile(2): a main segment, or one of an
+/// alternate audio rendition's if `rendition` is set.
 fn serve_segment(
     stream: &mut TcpStream,
     ctx: &AppContext,
     mf_id: sf_core::MediaFileId,
+    rendition: Option<usize>,
     seg_index: usize,
 ) -> io::Result<()> {
     // Look up prepared media in HLS cache, populating on demand if missing.
@@ -525,7 +554,11 @@ fn serve_segment(
         }
     };
 
-    let seg = match prepared.segments.get(seg_index) {
+    let segments = match rendition {
+        None => Some(&prepared.segments),
+        Some(r) => prepared.audio_renditions.get(r).map(|r| &r.segments),
+    };
+    let seg = match segments.and_then(|s| s.get(seg_index)) {
         Some(s) => s,
         None => {
             let response = b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
@@ -856,23 +889,28 @@ fn do_populate_blocking(
     // --- Tier 2: DB blob ---
     if let Ok(Some(blob)) = sf_db::queries::media_files::get_hls_prepared(&conn, mf_id) {
         drop(conn);
-        if let Ok(mut prepared) = sf_media::PreparedMedia::from_bincode(&blob) {
-            prepared.file_path = std::path::PathBuf::from(&mf.file_path);
-            let prepared = std::sync::Arc::new(prepared);
-            ctx.hls_cache.insert(mf_id, (prepared.clone(), std::time::Instant::now()));
-            tracing::debug!(media_file_id = %mf_id, "HLS cache loaded from DB (sendfile)");
-            return Ok(prepared);
+        match sf_media::PreparedMedia::from_bincode(&blob) {
+            Ok(mut prepared) => {
+                prepared.file_path = std::path::PathBuf::from(&mf.file_path);
+                let prepared = std::sync::Arc::new(prepared);
+                ctx.hls_cache.insert(mf_id, (prepared.clone(), std::time::Instant::now()));
+                tracing::debug!(media_file_id = %mf_id, "HLS cache loaded from DB (sendfile)");
+                return Ok(prepared);
+            }
+            Err(e) => {
+                tracing::debug!(media_file_id = %mf_id, "Stale HLS blob, rebuilding: {e}");
+            }
         }
     } else {
         drop(conn);
     }
 
-    // --- Tier 3: moov parse ---
+    // --- Tier 3: container parse ---
     let path = std::path::Path::new(&mf.file_path);
     let file = std::fs::File::open(path)?;
     let mut reader = std::io::BufReader::new(file);
 
-    let metadata = sf_media::parse_moov(&mut reader)
+    let metadata = sf_media::parse_media(&mut reader)
         .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
     let prepared = sf_media::build_prepared_media(&metadata, path)
         .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
@@ -911,6 +949,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn peek_classifies_audio_segment() {
+        let buf = b"GET /api/stream/550e8400-e29b-41d4-a716-446655440000/audio_1_segment_7.m4s HTTP/1.1\r\n";
+        match classify_peek(buf) {
+            Some(PeekRoute::AudioSegment {
+                rendition, index, ..
+            }) => {
+                assert_eq!(rendition, 1);
+                assert_eq!(index, 7);
+            }
+            other => panic!("Expected AudioSegment, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn peek_rejects_audio_playlist() {
+        let buf = b"GET /api/stream/550e8400-e29b-41d4-a716-446655440000/audio_1.m3u8 HTTP/1.1\r\n";
+        assert!(classify_peek(buf).is_none());
+    }
+
     #[test]
     fn peek_classifies_segment_high_index() {
         let buf = b"GET /api/stream/550e8400-e29b-41d4-a716-446655440000/segment_42.m4s HTTP/1.1\r\n";
@@ -0,0 +1,717 @@
+//! Live transcoding sessions for clients that can't play a file directly.
+//!
+//! A session belongs to one Jellyfin play session and media file. Its
+//! playlist lists every segment of the file up front; the segments come from
+//! an ffmpeg process encoding H.264/AAC fMP4 HLS into
+//! `<transcode.dir>/<session id>/`, started by the first media request. A
+//! request for a segment the encoder won't reach soon — before where it
+//! started or well past where it is — restarts ffmpeg at that segment,
+//! keeping the segments already written. Sessions are stopped when the
+//! client reports playback stopped or, failing that, once idle for
+//! `transcode.idle_timeout_secs`.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use sf_av::{ProcessControl, ToolCommand, ToolRegistry, ToolRun, ToolRunObserver};
+use sf_core::config::TranscodeConfig;
+use sf_core::{MediaFileId, Result};
+use sf_media::{generate_media_playlist, MediaPlaylist, Segment};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::context::AppContext;
+
+/// How often [`run_reaper`] looks for idle sessions.
+const REAP_INTERVAL: Duration = Duration::from_secs(15);
+
+/// How long a media request waits for ffmpeg to produce its file.
+const SEGMENT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How often a waiting media request checks ffmpeg's progress.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// A request this many segments past the encoder's latest one restarts
+/// ffmpeg there instead of waiting for it to catch up.
+const MAX_SEGMENTS_AHEAD: usize = 3;
+
+/// Segment duration used when `transcode.segment_secs` is 0.
+const DEFAULT_SEGMENT_SECS: u32 = 6;
+
+/// Longer than encoding any one file should take; idle sessions are stopped
+/// well before.
+const ENCODER_TIMEOUT: Duration = Duration::from_secs(12 * 60 * 60);
+
+/// The media file a session transcodes.
+#[derive(Debug, Clone)]
+pub struct TranscodeSource {
+    pub media_file_id: MediaFileId,
+    pub path: PathBuf,
+    pub duration_secs: f64,
+}
+
+/// Running transcoding sessions, keyed by play session ID.
+pub struct TranscodeManager {
+    config: TranscodeConfig,
+    tools: Arc<ToolRegistry>,
+    sessions: Mutex<HashMap<String, Arc<Session>>>,
+}
+
+struct Session {
+    source: TranscodeSource,
+    dir: PathBuf,
+    last_access: Mutex<Instant>,
+    encoder: tokio::sync::Mutex<Encoder>,
+}
+
+#[derive(Default)]
+struct Encoder {
+    process: Option<EncoderProcess>,
+    /// First segment of the current ffmpeg process.
+    start: usize,
+    /// Segments finished by this or earlier ffmpeg processes.
+    done: BTreeSet<usize>,
+    /// A complete init segment, once any process has finished a segment.
+    init: Option<PathBuf>,
+}
+
+/// An ffmpeg process, run as a [`ToolCommand`] on its own task.
+struct EncoderProcess {
+    control: ProcessControl,
+    task: JoinHandle<()>,
+}
+
+impl EncoderProcess {
+    /// Kill the process and wait for it to exit.
+    async fn stop(self) {
+        self.control.cancel();
+        let _ = self.task.await;
+    }
+}
+
+impl TranscodeManager {
+    pub fn new(config: TranscodeConfig, tools: Arc<ToolRegistry>) -> Self {
+        Self {
+            config,
+            tools,
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Number of open sessions.
+    pub fn session_count(&self) -> usize {
+        self.sessions.lock().len()
+    }
+
+    /// The HLS media playlist of a session, opening it if needed.
+    pub fn playlist(&self, session_id: &str, source: &TranscodeSource) -> Result<String> {
+        let session = self.open(session_id, source)?;
+        Ok(generate_media_playlist(&media_playlist(
+            session.source.duration_secs,
+            self.segment_secs(),
+        )))
+    }
+
+    /// Read `init.mp4` or `segment_N.m4s` of a session, opening it if needed
+    /// and waiting for ffmpeg to produce the file.
+    pub async fn media(
+        &self,
+        session_id: &str,
+        source: &TranscodeSource,
+        file: &str,
+    ) -> Result<Vec<u8>> {
+        let not_found = || sf_core::Error::not_found("transcode segment", file);
+        let index = match file {
+            "init.mp4" => None,
+            _ => {
+                let index = file
+                    .strip_prefix("segment_")
+                    .and_then(|s| s.strip_suffix(".m4s"))
+                    .and_then(|s| s.parse::<usize>().ok())
+                    .ok_or_else(not_found)?;
+                let count = segment_count(source.duration_secs, self.segment_secs());
+                if index >= count {
+                    return Err(not_found());
+                }
+                Some(index)
+            }
+        };
+
+        let session = self.open(session_id, source)?;
+        let path = self.wait_for(&session, index).await?;
+        Ok(tokio::fs::read(&path).await?)
+    }
+
+    /// Stop a session, killing its ffmpeg process and removing its segments.
+    /// Returns whether it was open.
+    pub fn stop(&self, session_id: &str) -> bool {
+        let removed = self.sessions.lock().remove(session_id);
+        if removed.is_some() {
+            tracing::info!(session_id, "Stopped transcoding session");
+        }
+        removed.is_some()
+    }
+
+    /// Stop every session.
+    pub fn stop_all(&self) {
+        let sessions = std::mem::take(&mut *self.sessions.lock());
+        drop(sessions);
+    }
+
+    /// Stop sessions idle for longer than `transcode.idle_timeout_secs`.
+    /// Returns how many were stopped.
+    pub fn reap_idle(&self) -> usize {
+        let timeout = Duration::from_secs(self.config.idle_timeout_secs);
+        let idle: Vec<Arc<Session>> = {
+            let mut sessions = self.sessions.lock();
+            let ids: Vec<String> = sessions
+                .iter()
+                .filter(|(_, session)| session.last_access.lock().elapsed() >= timeout)
+                .map(|(id, _)| id.clone())
+                .collect();
+            ids.iter()
+                .filter_map(|id| {
+                    tracing::info!(session_id = %id, "Reaping idle transcoding session");
+                    sessions.remove(id)
+                })
+                .collect()
+        };
+        // Dropping the sessions kills ffmpeg and removes their directories.
+        idle.len()
+    }
+
+    /// Remove session directories left behind by an earlier run.
+    pub fn remove_stale_dirs(&self) {
+        let Ok(entries) = std::fs::read_dir(&self.config.dir) else {
+            return;
+        };
+        let sessions = self.sessions.lock();
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            if sessions.contains_key(&*name.to_string_lossy()) {
+                continue;
+            }
+            if let Err(e) = std::fs::remove_dir_all(entry.path()) {
+                tracing::warn!("Failed to remove {}: {e}", entry.path().display());
+            }
+        }
+    }
+
+    fn segment_secs(&self) -> u32 {
+        match self.config.segment_secs {
+            0 => DEFAULT_SEGMENT_SECS,
+            secs => secs,
+        }
+    }
+
+    /// Get the session `session_id`, creating it if it doesn't exist and
+    /// replacing it if the client switched to another media file.
+    fn open(&self, session_id: &str, source: &TranscodeSource) -> Result<Arc<Session>> {
+        if !is_valid_session_id(session_id) {
+            return Err(sf_core::Error::Validation("Invalid play session ID".into()));
+        }
+
+        let mut sessions = self.sessions.lock();
+        if let Some(session) = sessions.get(session_id) {
+            if session.source.media_file_id == source.media_file_id {
+                *session.last_access.lock() = Instant::now();
+                return Ok(session.clone());
+            }
+            sessions.remove(session_id);
+        }
+
+        if sessions.len() >= self.config.max_sessions {
+            return Err(sf_core::Error::Conflict(format!(
+                "Too many transcoding sessions (max {})",
+                self.config.max_sessions
+            )));
+        }
+
+        let session = Arc::new(Session {
+            source: source.clone(),
+            // Per media file, so a replaced session still finishing a request
+            // can't remove its successor's segments.
+            dir: self
+                .config
+                .dir
+                .join(session_id)
+                .join(source.media_file_id.to_string()),
+            last_access: Mutex::new(Instant::now()),
+            encoder: tokio::sync::Mutex::new(Encoder::default()),
+        });
+        sessions.insert(session_id.to_string(), session.clone());
+        tracing::info!(
+            session_id,
+            media_file_id = %source.media_file_id,
+            "Opened transcoding session"
+        );
+        Ok(session)
+    }
+
+    /// Wait until the init segment (`index` of `None`) or segment `index` is
+    /// finished, (re)starting ffmpeg when it won't get there on its own.
+    /// Returns the file's path.
+    async fn wait_for(&self, session: &Session, index: Option<usize>) -> Result<PathBuf> {
+        let deadline = Instant::now() + SEGMENT_TIMEOUT;
+        let mut started = false;
+
+        loop {
+            {
+                let mut encoder = session.encoder.lock().await;
+                encoder.refresh(&session.dir);
+
+                match index {
+                    None => {
+                        if let Some(init) = &encoder.init {
+                            return Ok(init.clone());
+                        }
+                    }
+                    Some(index) if encoder.done.contains(&index) => {
+                        return Ok(session.dir.join(segment_file(index)));
+                    }
+                    Some(_) => {}
+                }
+
+                let running = encoder
+                    .process
+                    .as_ref()
+                    .is_some_and(|process| !process.task.is_finished());
+                let target = index.unwrap_or(0);
+                if running {
+                    if let Some(index) = index {
+                        if !encoder.will_reach(index) {
+                            self.restart(session, &mut encoder, index).await?;
+                            started = true;
+                        }
+                    }
+                } else if started {
+                    return Err(sf_core::Error::Tool {
+                        tool: "ffmpeg".into(),
+                        message: format!(
+                            "transcoding {} stopped before segment {target}",
+                            session.source.path.display()
+                        ),
+                    });
+                } else {
+                    self.restart(session, &mut encoder, target).await?;
+                    started = true;
+                }
+            }
+
+            if Instant::now() >= deadline {
+                return Err(sf_core::Error::Internal(
+                    "Timed out waiting for transcoded segment".into(),
+                ));
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    /// Kill the session's ffmpeg process, if any, and start a new one at
+    /// segment `start`.
+    async fn restart(&self, session: &Session, encoder: &mut Encoder, start: usize) -> Result<()> {
+        if let Some(process) = encoder.process.take() {
+            process.stop().await;
+        }
+
+        let ffmpeg = self.tools.require("ffmpeg")?;
+        tokio::fs::create_dir_all(&session.dir).await?;
+        let source = std::path::absolute(&session.source.path)?;
+        let args = ffmpeg_args(&source, start, self.segment_secs(), &self.config);
+        let mut cmd = ToolCommand::new(ffmpeg.path.clone());
+        cmd.args(args)
+            .current_dir(&session.dir)
+            .timeout(ENCODER_TIMEOUT);
+
+        let control = ProcessControl::new(CancellationToken::new());
+        let observer: ToolRunObserver = {
+            let control = control.clone();
+            let media_file_id = session.source.media_file_id;
+            Arc::new(move |run| log_run(media_file_id, &run, control.is_cancelled()))
+        };
+        let task = tokio::spawn(sf_av::observe_tool_runs(
+            observer,
+            sf_av::with_process_control(control.clone(), async move {
+                // Failures are logged by the observer; a request waiting for
+                // a segment sees the process stop.
+                let _ = cmd.execute().await;
+            }),
+        ));
+
+        tracing::debug!(
+            media_file_id = %session.source.media_file_id,
+            start,
+            "Started transcoding ffmpeg"
+        );
+        encoder.process = Some(EncoderProcess { control, task });
+        encoder.start = start;
+        Ok(())
+    }
+}
+
+impl Encoder {
+    /// Record the segments the current ffmpeg process has finished.
+    fn refresh(&mut self, dir: &Path) {
+        if self.process.is_none() {
+            return;
+        }
+        let Ok(playlist) = std::fs::read_to_string(dir.join(playlist_file(self.start))) else {
+            return;
+        };
+        let finished = finished_segments(&playlist);
+        if !finished.is_empty() && self.init.is_none() {
+            self.init = Some(dir.join(init_file(self.start)));
+        }
+        self.done.extend(finished);
+    }
+
+    /// Whether the running process will soon produce segment `index`.
+    fn will_reach(&self, index: usize) -> bool {
+        let latest = self
+            .done
+            .range(self.start..)
+            .next_back()
+            .copied()
+            .unwrap_or(self.start);
+        index >= self.start && index <= latest + MAX_SEGMENTS_AHEAD
+    }
+}
+
+impl Drop for Session {
+    /// Kill ffmpeg, then remove the session's directory on a blocking
+    /// thread, or right away outside a runtime.
+    fn drop(&mut self) {
+        let process = self.encoder.get_mut().process.take();
+        let dir = std::mem::take(&mut self.dir);
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(async move {
+                    if let Some(process) = process {
+                        process.stop().await;
+                    }
+                    let _ = tokio::task::spawn_blocking(move || remove_session_dir(&dir)).await;
+                });
+            }
+            Err(_) => remove_session_dir(&dir),
+        }
+    }
+}
+
+fn remove_session_dir(dir: &Path) {
+    if let Err(e) = std::fs::remove_dir_all(dir) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            tracing::warn!("Failed to remove {}: {e}", dir.display());
+        }
+    }
+    // The play session's directory, unless another file's session uses it.
+    if let Some(parent) = dir.parent() {
+        let _ = std::fs::remove_dir(parent);
+    }
+}
+
+/// Log a finished ffmpeg run; runs killed by a restart or a stopped session
+/// are expected.
+fn log_run(media_file_id: MediaFileId, run: &ToolRun, cancelled: bool) {
+    match &run.error {
+        Some(error) if !cancelled => tracing::warn!(
+            %media_file_id,
+            command = %run.command,
+            stderr = %run.stderr,
+            "Transcoding ffmpeg failed: {error}"
+        ),
+        _ => tracing::debug!(
+            %media_file_id,
+            command = %run.command,
+            duration_ms = run.duration.as_millis() as u64,
+            "Transcoding ffmpeg exited"
+        ),
+    }
+}
+
+/// Stop idle sessions every [`REAP_INTERVAL`] until cancelled, then stop
+/// them all.
+pub async fn run_reaper(ctx: AppContext, cancel: CancellationToken) {
+    ctx.transcode.remove_stale_dirs();
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(REAP_INTERVAL) => {}
+            _ = cancel.cancelled() => { break; }
+        }
+        ctx.transcode.reap_idle();
+    }
+    ctx.transcode.stop_all();
+}
+
+/// Play session IDs name directories, so only allow UUID-like characters.
+fn is_valid_session_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= 64 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+fn segment_count(duration_secs: f64, segment_secs: u32) -> usize {
+    (duration_secs / segment_secs as f64).ceil().max(1.0) as usize
+}
+
+/// Playlist of the whole file in `segment_secs` segments, the last one
+/// holding the remainder.
+fn media_playlist(duration_secs: f64, segment_secs: u32) -> MediaPlaylist {
+    let count = segment_count(duration_secs, segment_secs);
+    let segment_secs_f = segment_secs as f64;
+    MediaPlaylist {
+        target_duration: segment_secs,
+        media_sequence: 0,
+        segments: (0..count)
+            .map(|k| Segment {
+                duration: (duration_secs - k as f64 * segment_secs_f)
+                    .min(segment_secs_f)
+                    .max(0.0),
+                uri: segment_file(k),
+                title: None,
+            })
+            .collect(),
+        ended: true,
+        init_segment_uri: Some("init.mp4".to_string()),
+    }
+}
+
+fn segment_file(index: usize) -> String {
+    format!("segment_{index}.m4s")
+}
+
+/// Each ffmpeg process writes its own init segment and playlist, so a
+/// restart never truncates a file a request is reading.
+fn init_file(start: usize) -> String {
+    format!("init_{start}.mp4")
+}
+
+fn playlist_file(start: usize) -> String {
+    format!("ffmpeg_{start}.m3u8")
+}
+
+/// Segment indexes listed in an ffmpeg-written playlist; ffmpeg only lists
+/// a segment once it is complete.
+fn finished_segments(playlist: &str) -> Vec<usize> {
+    playlist
+        .lines()
+        .filter_map(|line| {
+            line.trim()
+                .strip_prefix("segment_")?
+                .strip_suffix(".m4s")?
+                .parse()
+                .ok()
+        })
+        .collect()
+}
+
+/// ffmpeg arguments encoding `source` from segment `start` on, run in the
+/// session directory. Keyframes are forced on segment boundaries and output
+/// timestamps offset to the seek position, so segments from different
+/// processes line up on one timeline.
+fn ffmpeg_args(
+    source: &Path,
+    start: usize,
+    segment_secs: u32,
+    config: &TranscodeConfig,
+) -> Vec<String> {
+    let start_secs = (start as u64 * segment_secs as u64).to_string();
+    [
+        "-hide_banner",
+        "-nostdin",
+        "-loglevel",
+        "error",
+        "-y",
+        "-ss",
+        &start_secs,
+        "-i",
+        &source.to_string_lossy(),
+        "-map",
+        "0:v:0",
+        "-map",
+        "0:a:0?",
+        "-c:v",
+        "libx264",
+        "-preset",
+        &config.video_preset,
+        "-crf",
+        &config.video_crf.to_string(),
+        "-pix_fmt",
+        "yuv420p",
+        "-force_key_frames",
+        &format!("expr:gte(t,n_forced*{segment_secs})"),
+        "-c:a",
+        "aac",
+        "-ac",
+        "2",
+        "-b:a",
+        &config.audio_bitrate,
+        "-output_ts_offset",
+        &start_secs,
+        "-f",
+        "hls",
+        "-hls_time",
+        &segment_secs.to_string(),
+        "-hls_list_size",
+        "0",
+        "-hls_segment_type",
+        "fmp4",
+        "-hls_flags",
+        "temp_file",
+        "-hls_fmp4_init_filename",
+        &init_file(start),
+        "-hls_segment_filename",
+        "segment_%d.m4s",
+        "-start_number",
+        &start.to_string(),
+        &playlist_file(start),
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager(dir: &Path, max_sessions: usize) -> TranscodeManager {
+        let config = TranscodeConfig {
+            dir: dir.to_path_buf(),
+            max_sessions,
+            ..TranscodeConfig::default()
+        };
+        let tools = ToolRegistry::discover(&sf_core::config::ToolsConfig::default());
+        TranscodeManager::new(config, Arc::new(tools))
+    }
+
+    fn source(duration_secs: f64) -> TranscodeSource {
+        TranscodeSource {
+            media_file_id: MediaFileId::new(),
+            path: PathBuf::from("/media/film.mkv"),
+            duration_secs,
+        }
+    }
+
+    #[test]
+    fn playlist_covers_the_whole_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let m3u8 = manager(tmp.path(), 2)
+            .playlist("abc", &source(14.5))
+            .unwrap();
+        assert!(m3u8.contains("#EXT-X-MAP:URI=\"init.mp4\""));
+        assert!(m3u8.contains("#EXTINF:6.000000,\nsegment_1.m4s\n"));
+        assert!(m3u8.contains("#EXTINF:2.500000,\nsegment_2.m4s\n"));
+        assert!(!m3u8.contains("segment_3.m4s"));
+        assert!(m3u8.ends_with("#EXT-X-ENDLIST\n"));
+    }
+
+    #[test]
+    fn sessions_are_limited() {
+        let tmp = tempfile::tempdir().unwrap();
+        let manager = manager(tmp.path(), 1);
+        let film = source(60.0);
+        manager.playlist("one", &film).unwrap();
+        // Reopening the same session doesn't count against the limit.
+        manager.playlist("one", &film).unwrap();
+        let err = manager.playlist("two", &film).unwrap_err();
+        assert!(matches!(err, sf_core::Error::Conflict(_)));
+
+        assert!(manager.stop("one"));
+        assert!(!manager.stop("one"));
+        manager.playlist("two", &film).unwrap();
+    }
+
+    #[test]
+    fn rejects_path_like_session_ids() {
+        let tmp = tempfile::tempdir().unwrap();
+        let manager = manager(tmp.path(), 2);
+        for id in ["", "../etc", "a/b", "."] {
+            assert!(manager.playlist(id, &source(60.0)).is_err(), "{id}");
+        }
+    }
+
+    #[tokio::test]
+    async fn idle_sessions_are_reaped_with_their_segments() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut manager = manager(tmp.path(), 2);
+        manager.config.idle_timeout_secs = 0;
+        let film = source(60.0);
+        manager.playlist("idle", &film).unwrap();
+        let dir = tmp.path().join("idle").join(film.media_file_id.to_string());
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("segment_0.m4s"), b"x").unwrap();
+
+        assert_eq!(manager.reap_idle(), 1);
+        assert_eq!(manager.session_count(), 0);
+        // The directory is removed on a blocking thread.
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while tmp.path().join("idle").exists() && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(!tmp.path().join("idle").exists());
+    }
+
+    #[test]
+    fn stale_dirs_are_removed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let manager = manager(tmp.path(), 2);
+        manager.playlist("live", &source(60.0)).unwrap();
+        std::fs::create_dir_all(tmp.path().join("live")).unwrap();
+        std::fs::create_dir_all(tmp.path().join("crashed")).unwrap();
+
+        manager.remove_stale_dirs();
+        assert!(tmp.path().join("live").exists());
+        assert!(!tmp.path().join("crashed").exists());
+    }
+
+    #[tokio::test]
+    async fn out_of_range_segments_are_not_found() {
+        let tmp = tempfile::tempdir().unwrap();
+        let manager = manager(tmp.path(), 2);
+        for file in ["segment_3.m4s", "segment_x.m4s", "index.m3u8"] {
+            let err = manager.media("s", &source(14.5), file).await.unwrap_err();
+            assert!(matches!(err, sf_core::Error::NotFound { .. }), "{file}");
+        }
+    }
+
+    #[test]
+    fn finished_segments_from_ffmpeg_playlist() {
+        let playlist = "#EXTM3U\n#EXT-X-MAP:URI=\"init_4.mp4\"\n#EXTINF:6.0,\nsegment_4.m4s\n#EXTINF:6.0,\nsegment_5.m4s\n";
+        assert_eq!(finished_segments(playlist), vec![4, 5]);
+    }
+
+    #[test]
+    fn restart_window() {
+        let encoder = Encoder {
+            start: 10,
+            done: [2, 3, 10, 11].into_iter().collect(),
+            ..Encoder::default()
+        };
+        assert!(encoder.will_reach(12));
+        assert!(encoder.will_reach(14));
+        assert!(!encoder.will_reach(15));
+        // Behind the current process's start.
+        assert!(!encoder.will_reach(9));
+    }
+
+    #[test]
+    fn ffmpeg_seeks_to_the_start_segment() {
+        let args = ffmpeg_args(
+            Path::new("/media/film.mkv"),
+            5,
+            6,
+            &TranscodeConfig::default(),
+        );
+        let value = |flag: &str| {
+            let i = args.iter().position(|a| a == flag).unwrap();
+            args[i + 1].as_str()
+        };
+        assert_eq!(value("-ss"), "30");
+        assert_eq!(value("-output_ts_offset"), "30");
+        assert_eq!(value("-start_number"), "5");
+        assert_eq!(value("-hls_fmp4_init_filename"), "init_5.mp4");
+        assert_eq!(value("-force_key_frames"), "expr:gte(t,n_forced*6)");
+        assert_eq!(args.last().unwrap(), "ffmpeg_5.m3u8");
+    }
+}
@@ -0,0 +1,294 @@
+//! Trash for originals replaced by a pipeline.
+//!
+//! Instead of being deleted or left next to the media as a `.bak`, a replaced
+//! original is moved to `<trash.dir>/<trash id>/<path relative to its library
+//! root>` and recorded in the `trash` table with its size and expiry. Files
+//! are purged when they expire, when the trash grows past its size limit
+//! (oldest first) or by hand, and can be restored to where they came from
+//! until then.
+//!
+//! Moving files can mean copying them across filesystems, so these functions
+//! block and must be called from `spawn_blocking`.
+
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+use chrono::Utc;
+use sf_core::config::TrashConfig;
+use sf_core::{JobId, Result, TrashId};
+use sf_db::models::TrashEntry;
+use sf_db::queries::trash::NewTrashEntry;
+use tokio_util::sync::CancellationToken;
+
+use crate::context::AppContext;
+
+/// How often [`run_retention`] purges expired files.
+const RETENTION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Move the file at `path` to the trash.
+///
+/// Returns `None` without touching the file when the trash is disabled.
+pub fn move_to_trash(
+    ctx: &AppContext,
+    path: &Path,
+    job_id: Option<JobId>,
+) -> Result<Option<TrashEntry>> {
+    let config = &ctx.config.trash;
+    if !config.enabled || config.dir.as_os_str().is_empty() {
+        return Ok(None);
+    }
+
+    let conn = sf_db::pool::get_conn(&ctx.db)?;
+    let library = crate::rule_scope::library_for_path(&conn, path)?;
+    let relative = relative_path(library.as_ref().map(|lib| &lib.paths[..]), path);
+
+    let id = TrashId::new();
+    let trash_path = std::path::absolute(&config.dir)?
+        .join(id.to_string())
+        .join(relative);
+    let size = std::fs::metadata(path)?.len();
+    move_file(path, &trash_path)?;
+
+    let expires_at = (config.retention_days > 0)
+        .then(|| Utc::now() + chrono::Duration::days(config.retention_days.into()));
+    let entry = sf_db::queries::trash::create_trash_entry(
+        &conn,
+        &NewTrashEntry {
+            id,
+            original_path: &path.to_string_lossy(),
+            trash_path: &trash_path.to_string_lossy(),
+            library_id: library.map(|lib| lib.id),
+            job_id,
+            size_bytes: size as i64,
+            expires_at,
+        },
+    );
+    match entry {
+        Ok(entry) => {
+            tracing::info!(
+                "Moved {} to trash at {}",
+                path.display(),
+                trash_path.display()
+            );
+            Ok(Some(entry))
+        }
+        Err(e) => {
+            // Without a record the file could never be restored or purged.
+            move_file(&trash_path, path)?;
+            remove_entry_dir(&trash_path, id);
+            Err(e)
+        }
+    }
+}
+
+/// Put a trashed file back at its original path, replacing whatever is
+/// there now (normally the output that replaced it).
+pub fn restore(ctx: &AppContext, id: TrashId) -> Result<TrashEntry> {
+    let conn = sf_db::pool::get_conn(&ctx.db)?;
+    let entry = sf_db::queries::trash::get_trash_entry(&conn, id)?
+        .ok_or_else(|| sf_core::Error::not_found("trash entry", id))?;
+
+    let trash_path = Path::new(&entry.trash_path);
+    let original = Path::new(&entry.original_path);
+    if !trash_path.exists() {
+        return Err(sf_core::Error::Conflict(format!(
+            "trashed file is missing: {}",
+            trash_path.display()
+        )));
+    }
+    if let Some(parent) = original.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    move_file(trash_path, original)?;
+    remove_entry_dir(trash_path, id);
+    sf_db::queries::trash::delete_trash_entry(&conn, id)?;
+
+    tracing::info!("Restored {} from trash", original.display());
+    Ok(entry)
+}
+
+/// Permanently delete a trashed file and its record.
+pub fn purge(ctx: &AppContext, entry: &TrashEntry) -> Result<()> {
+    let trash_path = Path::new(&entry.trash_path);
+    match std::fs::remove_file(trash_path) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e.into()),
+    }
+    remove_entry_dir(trash_path, entry.id);
+
+    let conn = sf_db::pool::get_conn(&ctx.db)?;
+    sf_db::queries::trash::delete_trash_entry(&conn, entry.id)?;
+    Ok(())
+}
+
+/// Purge every file in the trash. Returns how many were purged.
+pub fn purge_all(ctx: &AppContext) -> Result<usize> {
+    let conn = sf_db::pool::get_conn(&ctx.db)?;
+    let entries = sf_db::queries::trash::list_trash_entries(&conn)?;
+    drop(conn);
+    for entry in &entries {
+        purge(ctx, entry)?;
+    }
+    Ok(entries.len())
+}
+
+/// Purge expired files, then the oldest files until the trash fits in its
+/// size limit. Returns how many were purged.
+pub fn enforce_retention(ctx: &AppContext, config: &TrashConfig) -> Result<usize> {
+    let conn = sf_db::pool::get_conn(&ctx.db)?;
+    let expired = sf_db::queries::trash::list_expired_trash_entries(&conn, Utc::now())?;
+
+    let mut excess = 0;
+    let mut oldest = Vec::new();
+    if let Some(max) = config.max_size_bytes() {
+        let total = sf_db::queries::trash::total_trash_size(&conn)?.max(0) as u64;
+        let expired_size: u64 = expired.iter().map(|e| e.size_bytes.max(0) as u64).sum();
+        excess = total.saturating_sub(expired_size).saturating_sub(max);
+        if excess > 0 {
+            oldest = sf_db::queries::trash::list_trash_entries(&conn)?;
+            oldest.reverse();
+        }
+    }
+    drop(conn);
+
+    let mut purged = 0;
+    for entry in &expired {
+        match purge(ctx, entry) {
+            Ok(()) => purged += 1,
+            Err(e) => tracing::warn!("Failed to purge {}: {e}", entry.trash_path),
+        }
+    }
+    for entry in oldest {
+        if excess == 0 {
+            break;
+        }
+        if expired.iter().any(|e| e.id == entry.id) {
+            continue;
+        }
+        match purge(ctx, &entry) {
+            Ok(()) => {
+                purged += 1;
+                excess = excess.saturating_sub(entry.size_bytes.max(0) as u64);
+            }
+            Err(e) => tracing::warn!("Failed to purge {}: {e}", entry.trash_path),
+        }
+    }
+
+    if purged > 0 {
+        tracing::info!(purged, "Purged files from trash");
+    }
+    Ok(purged)
+}
+
+/// Enforce trash retention once at startup and then every hour.
+pub async fn run_retention(ctx: AppContext, cancel: CancellationToken) {
+    loop {
+        let task_ctx = ctx.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            enforce_retention(&task_ctx, &task_ctx.config.trash)
+        })
+        .await;
+        match result {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => tracing::error!("Trash retention error: {e}"),
+            Err(e) => tracing::error!("Trash retention task failed: {e}"),
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(RETENTION_INTERVAL) => {}
+            _ = cancel.cancelled() => { break; }
+        }
+    }
+}
+
+/// `path` relative to the deepest of `roots` containing it. Files outside
+/// every library keep their full path, minus the root and any prefix.
+fn relative_path(roots: Option<&[String]>, path: &Path) -> PathBuf {
+    let root = roots
+        .unwrap_or_default()
+        .iter()
+        .map(Path::new)
+        .filter(|root| path.starts_with(root))
+        .max_by_key(|root| root.components().count());
+    let relative = root.and_then(|root| path.strip_prefix(root).ok());
+    relative
+        .unwrap_or(path)
+        .components()
+        .filter(|c| matches!(c, Component::Normal(_)))
+        .collect()
+}
+
+/// Move `from` to `to`, creating `to`'s parent and copying across
+/// filesystems when a rename isn't possible.
+fn move_file(from: &Path, to: &Path) -> Result<()> {
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    if let Err(e) = std::fs::copy(from, to) {
+        let _ = std::fs::remove_file(to);
+        return Err(e.into());
+    }
+    std::fs::remove_file(from)?;
+    Ok(())
+}
+
+/// Remove the `<trash dir>/<id>` directory holding a trashed file.
+fn remove_entry_dir(trash_path: &Path, id: TrashId) {
+    let id = id.to_string();
+    if let Some(dir) = trash_path
+        .ancestors()
+        .find(|dir| dir.file_name().is_some_and(|name| *name == *id))
+    {
+        if let Err(e) = std::fs::remove_dir_all(dir) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!("Failed to remove {}: {e}", dir.display());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_to_deepest_library_root() {
+        let roots = vec!["/media".to_string(), "/media/movies".to_string()];
+        assert_eq!(
+            relative_path(
+                Some(&roots),
+                Path::new("/media/movies/Film (2020)/film.mkv")
+            ),
+            PathBuf::from("Film (2020)/film.mkv")
+        );
+        assert_eq!(
+            relative_path(Some(&roots), Path::new("/media/tv/show.mkv")),
+            PathBuf::from("tv/show.mkv")
+        );
+    }
+
+    #[test]
+    fn outside_libraries_keeps_full_path() {
+        assert_eq!(
+            relative_path(None, Path::new("/downloads/film.mkv")),
+            PathBuf::from("downloads/film.mkv")
+        );
+    }
+
+    #[test]
+    fn entry_dir_is_removed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let id = TrashId::new();
+        let file = tmp.path().join(id.to_string()).join("a/b.mkv");
+        std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+        std::fs::write(&file, b"x").unwrap();
+
+        remove_entry_dir(&file, id);
+        assert!(!tmp.path().join(id.to_string()).exists());
+        assert!(tmp.path().exists());
+    }
+}
@@ -0,0 +1,241 @@
+//! Background worker pool.
+//!
+//! Runs a configurable number of job processors and conversion processors.
+//! Every running job holds a slot of its [`ResourceClass`], so a long encode
+//! only occupies the encode slots while remuxes and light jobs keep flowing
+//! through the other workers.
+//!
+//! Running jobs refresh their `locked_at` with a [`Heartbeat`]; a reaper
+//! requeues jobs whose lock outlived the configured lease, e.g. after a
+//! crash.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use rusqlite::Connection;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use sf_core::config::{ResourceClass, ResourceLimits, WorkersConfig};
+use sf_db::pool::DbPool;
+
+use crate::context::AppContext;
+use crate::{conversion_processor, processor};
+
+/// State shared by every worker in the pool.
+#[derive(Debug)]
+pub struct WorkerPool {
+    pub slots: ResourceSlots,
+    pub config: WorkersConfig,
+}
+
+/// Per-[`ResourceClass`] concurrency slots shared by all workers.
+#[derive(Debug)]
+pub struct ResourceSlots {
+    encode: Arc<Semaphore>,
+    remux: Arc<Semaphore>,
+    light: Arc<Semaphore>,
+}
+
+impl ResourceSlots {
+    pub fn new(limits: &ResourceLimits) -> Self {
+        Self {
+            encode: Arc::new(Semaphore::new(limits.encode)),
+            remux: Arc::new(Semaphore::new(limits.remux)),
+            light: Arc::new(Semaphore::new(limits.light)),
+        }
+    }
+
+    fn semaphore(&self, class: ResourceClass) -> &Arc<Semaphore> {
+        match class {
+            ResourceClass::Encode => &self.encode,
+            ResourceClass::Remux => &self.remux,
+            ResourceClass::Light => &self.light,
+        }
+    }
+
+    /// Classes that currently have a free slot.
+    pub fn available(&self) -> Vec<ResourceClass> {
+        ResourceClass::ALL
+            .into_iter()
+            .filter(|&c| self.semaphore(c).available_permits() > 0)
+            .collect()
+    }
+
+    /// Take a slot of `class` if one is free. The slot is released when the
+    /// permit is dropped.
+    pub fn try_acquire(&self, class: ResourceClass) -> Option<OwnedSemaphorePermit> {
+        self.semaphore(class).clone().try_acquire_owned().ok()
+    }
+}
+
+/// Spawn the job and conversion workers described by `config`, plus the
+/// stale-lock reaper.
+///
+/// Worker `n` of each kind locks jobs as `sf-processor-<n>` /
+/// `sf-conversion-<n>`.
+pub fn spawn_workers(
+    ctx: &AppContext,
+    config: &WorkersConfig,
+    cancel: &CancellationToken,
+) -> Vec<JoinHandle<()>> {
+    let pool = Arc::new(WorkerPool {
+        slots: ResourceSlots::new(&config.limits),
+        config: config.clone(),
+    });
+    tracing::info!(
+        processors = config.processors,
+        conversions = config.conversions,
+        encode = config.limits.encode,
+        remux = config.limits.remux,
+        light = config.limits.light,
+        "Starting worker pool"
+    );
+
+    let mut handles = Vec::with_capacity(config.processors + config.conversions + 1);
+    {
+        let db = ctx.db.clone();
+        let lease = config.lease();
+        let cancel = cancel.clone();
+        handles.push(tokio::spawn(async move {
+            run_reaper(db, lease, cancel).await;
+        }));
+    }
+    for n in 1..=config.processors {
+        let ctx = ctx.clone();
+        let pool = pool.clone();
+        let cancel = cancel.clone();
+        let worker_id = format!("{}-{n}", processor::WORKER_ID);
+        handles.push(tokio::spawn(async move {
+            processor::run_processor(ctx, pool, worker_id, cancel).await;
+        }));
+    }
+    for n in 1..=config.conversions {
+        let ctx = ctx.clone();
+        let pool = pool.clone();
+        let cancel = cancel.clone();
+        let worker_id = format!("{}-{n}", conversion_processor::WORKER_ID);
+        handles.push(tokio::spawn(async move {
+            conversion_processor::run_conversion_processor(ctx, pool, worker_id, cancel).await;
+        }));
+    }
+    handles
+}
+
+/// Refreshes a running job's lock in the background until dropped.
+pub struct Heartbeat(JoinHandle<()>);
+
+impl Heartbeat {
+    /// Call `touch` every third of `lease` while the returned guard lives.
+    pub fn start<F>(db: DbPool, lease: Duration, touch: F) -> Self
+    where
+        F: Fn(&Connection) -> sf_core::Result<bool> + Send + 'static,
+    {
+        let interval = (lease / 3).max(Duration::from_secs(1));
+        Self(tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                let touched = sf_db::pool::get_conn(&db).and_then(|conn| touch(&conn));
+                if let Err(e) = touched {
+                    tracing::warn!("Failed to refresh job lock: {e}");
+                }
+            }
+        }))
+    }
+}
+
+impl Drop for Heartbeat {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Requeue jobs and conversions whose lock is older than `lease`, once at
+/// startup and then every half lease.
+async fn run_reaper(db: DbPool, lease: Duration, cancel: CancellationToken) {
+    let interval = (lease / 2).max(Duration::from_secs(1));
+    loop {
+        if let Err(e) = reap_stale_locks(&db, lease) {
+            tracing::error!("Stale lock reaper error: {e}");
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(interval) => {}
+            _ = cancel.cancelled() => { break; }
+        }
+    }
+}
+
+fn reap_stale_locks(db: &DbPool, lease: Duration) -> sf_core::Result<()> {
+    let lease = chrono::Duration::from_std(lease)
+        .map_err(|e| sf_core::Error::Internal(format!("invalid lease: {e}")))?;
+    let cutoff = Utc::now() - lease;
+
+    let conn = sf_db::pool::get_conn(db)?;
+    let jobs = sf_db::queries::jobs::reap_stale_jobs(&conn, cutoff)?;
+    let conversions = sf_db::queries::conversion_jobs::reap_stale_conversions(&conn, cutoff)?;
+    if jobs + conversions > 0 {
+        tracing::warn!(
+            jobs,
+            conversions,
+            "Recovered jobs with expired worker locks"
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slots_are_capped_per_class() {
+        let slots = ResourceSlots::new(&ResourceLimits {
+            encode: 1,
+            remux: 2,
+            light: 0,
+        });
+        assert_eq!(
+            slots.available(),
+            [ResourceClass::Remux, ResourceClass::Encode]
+        );
+
+        let encode = slots.try_acquire(ResourceClass::Encode).unwrap();
+        assert!(slots.try_acquire(ResourceClass::Encode).is_none());
+        assert_eq!(slots.available(), [ResourceClass::Remux]);
+
+        // Other classes are unaffected by a running encode.
+        let _r1 = slots.try_acquire(ResourceClass::Remux).unwrap();
+        let _r2 = slots.try_acquire(ResourceClass::Remux).unwrap();
+        assert!(slots.available().is_empty());
+
+        drop(encode);
+        assert_eq!(slots.available(), [ResourceClass::Encode]);
+    }
+
+    #[test]
+    fn reaper_requeues_expired_locks() {
+        let db = sf_db::pool::init_memory_pool().unwrap();
+        let conn = db.get().unwrap();
+        let job = sf_db::queries::jobs::create_job(&conn, "/a.mkv", "a.mkv", None, 0).unwrap();
+        sf_db::queries::jobs::dequeue_next(&conn, "w1").unwrap();
+        drop(conn);
+
+        reap_stale_locks(&db, Duration::from_secs(600)).unwrap();
+        let conn = db.get().unwrap();
+        let running = sf_db::queries::jobs::get_job(&conn, job.id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(running.status, "processing");
+        drop(conn);
+
+        reap_stale_locks(&db, Duration::ZERO).unwrap();
+        let conn = db.get().unwrap();
+        let requeued = sf_db::queries::jobs::get_job(&conn, job.id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(requeued.status, "queued");
+    }
+}
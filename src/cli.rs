@@ -43,6 +43,10 @@ pub enum Commands {
         /// Force processing even if no rules match
         #[arg(long)]
         force: bool,
+
+        /// Show why each rule did or did not match
+        #[arg(long)]
+        explain: bool,
     },
 
     /// Probe a media file and display information
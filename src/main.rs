@@ -34,9 +34,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             input,
             dry_run,
             force,
+            explain,
         } => {
             let rt = tokio::runtime::Runtime::new()?;
-            rt.block_on(run_file(&input, cli.config.as_deref(), dry_run, force))
+            rt.block_on(run_file(
+                &input,
+                cli.config.as_deref(),
+                dry_run,
+                force,
+                explain,
+            ))
         }
         Commands::Probe { file, json } => probe_file(&file, json),
         Commands::CheckTools => check_tools(cli.config.as_deref()),
@@ -87,8 +94,10 @@ async fn run_file(
     config_path: Option<&Path>,
     dry_run: bool,
     force: bool,
+    explain: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let config = Config::load_or_default(config_path);
+    let (rules, match_mode) = load_rules(config_path)?;
 
     if !input.exists() {
         return Err(format!("Input file does not exist: {}", input.display()).into());
@@ -114,27 +123,48 @@ async fn run_file(
         }
     }
 
-    // Find matching rules.
-    let engine = sf_rules::RuleEngine::new(vec![]); // No rules in default config yet
-    let matched_rule = engine.find_matching_rule(&media_info);
+    // Find matching rules. Scopes are applied as the server does; a file
+    // processed from the command line belongs to no library.
+    let rules = sf_rules::effective_rules(&rules, None, None, &std::path::absolute(input)?);
+    let engine = sf_rules::RuleEngine::new(rules).with_match_mode(match_mode);
+    let plan = engine.plan(&media_info);
+
+    if explain {
+        println!("\nRules evaluated: {}", engine.rules().len());
+        for trace in engine.explain(&media_info) {
+            let status = match (trace.enabled, trace.matched, trace.applied) {
+                (false, _, _) => "disabled",
+                (true, true, true) => "matched",
+                (true, true, false) => "matched (not applied)",
+                (true, false, _) => "no match",
+            };
+            println!(
+                "\nRule: {} (priority {}) - {}",
+                trace.name, trace.priority, status
+            );
+            print!("{}", trace.trace);
+        }
+    }
 
-    match matched_rule {
-        Some(rule) => {
-            println!("\nMatched rule: {} (priority {})", rule.name, rule.priority);
-            println!("Actions to execute: {}", rule.actions.len());
+    match plan {
+        Some(plan) => {
+            for rule in &plan.rules {
+                println!("\nMatched rule: {} (priority {})", rule.name, rule.priority);
+            }
+            println!("Actions to execute: {}", plan.actions.len());
 
-            for (i, action) in rule.actions.iter().enumerate() {
+            for (i, action) in plan.actions.iter().enumerate() {
                 println!("  {}. {:?}", i + 1, action);
             }
 
             if dry_run {
-                println!("\n[DRY RUN] Would execute {} actions", rule.actions.len());
+                println!("\n[DRY RUN] Would execute {} actions", plan.actions.len());
                 return Ok(());
             }
 
             // Set up tools and workspace.
             let tools = Arc::new(sf_av::ToolRegistry::discover(&config.tools));
-            let actions = sf_pipeline::create_actions(&rule.actions, &tools)?;
+            let actions = sf_pipeline::create_actions(&plan.actions, &tools)?;
             let workspace = Arc::new(sf_av::Workspace::new(input)?);
             let ctx = sf_pipeline::ActionContext::new(
                 workspace,
@@ -143,7 +173,13 @@ async fn run_file(
             )
             .with_dry_run(dry_run);
 
-            let executor = sf_pipeline::PipelineExecutor::new(actions);
+            let prober: Arc<dyn sf_probe::Prober> = Arc::new(prober);
+            let executor = sf_pipeline::PipelineExecutor::new(actions)
+                .with_prober(prober.clone())
+                .with_verification(sf_pipeline::Verification {
+                    prober,
+                    config: config.verification.clone(),
+                });
             let output = executor.execute(&ctx).await?;
 
             println!("\nProcessing complete!");
@@ -162,6 +198,34 @@ async fn run_file(
     Ok(())
 }
 
+/// Load processing rules from the top-level `"rules"` key of the config file.
+///
+/// Rules are not part of [`Config`] (see `ConfigStore`), so they are read
+/// separately. Each rule's `expr` may be JSON or rule DSL text. A config file
+/// that can't be read or isn't valid JSON is an error, like an invalid rule.
+fn load_rules(
+    config_path: Option<&Path>,
+) -> Result<(Vec<sf_rules::Rule>, sf_rules::MatchMode), Box<dyn std::error::Error>> {
+    let Some(path) = config_path else {
+        return Ok(Default::default());
+    };
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Cannot read {}: {e}", path.display()))?;
+    let value: serde_json::Value = serde_json::from_str(&contents)
+        .map_err(|e| format!("Invalid JSON in {}: {e}", path.display()))?;
+    let rules = match value.get("rules") {
+        Some(rules) => sf_rules::rules_from_value(rules)
+            .map_err(|e| format!("Invalid rules in {}: {e}", path.display()))?,
+        None => Vec::new(),
+    };
+    let match_mode = match value.get("match_mode") {
+        Some(mode) => serde_json::from_value(mode.clone())
+            .map_err(|e| format!("Invalid match_mode in {}: {e}", path.display()))?,
+        None => sf_rules::MatchMode::default(),
+    };
+    Ok((rules, match_mode))
+}
+
 fn probe_file(file: &Path, json: bool) -> Result<(), Box<dyn std::error::Error>> {
     if !file.exists() {
         return Err(format!("File does not exist: {}", file.display()).into());
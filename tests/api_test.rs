@@ -345,6 +345,8 @@ async fn rules_get_and_put() {
             sf_core::Container::Mkv,
         ])),
         actions: vec![],
+        scope: Default::default(),
+        continue_matching: false,
     };
     let rules_json = sf_rules::serialize_rules(&[rule]).unwrap();
 
@@ -428,6 +430,273 @@ async fn dashboard_stats_reflect_jobs() {
     assert_eq!(json["jobs"]["processing"], 1);
 }
 
+#[tokio::test]
+async fn deferred_job_shows_start_and_runs_now() {
+    let (harness, addr) = TestHarness::with_server().await;
+    let client = reqwest::Client::new();
+    let base = format!("http://{addr}");
+
+    let resp = client
+        .post(format!("{base}/api/jobs/submit"))
+        .json(&serde_json::json!({"file_path": "/media/encode.mkv"}))
+        .send()
+        .await
+        .unwrap();
+    let job: serde_json::Value = resp.json().await.unwrap();
+    let id = job["id"].as_str().unwrap().to_string();
+
+    // Defer it as a worker outside the processing window would.
+    let start = chrono::Utc::now() + chrono::Duration::hours(6);
+    {
+        let conn = harness.conn();
+        let claimed = sf_db::queries::jobs::dequeue_next(&conn, "test-worker")
+            .unwrap()
+            .unwrap();
+        sf_db::queries::jobs::defer_job(&conn, claimed.id, "test-worker", start).unwrap();
+    }
+
+    let resp = client
+        .get(format!("{base}/api/admin/dashboard"))
+        .send()
+        .await
+        .unwrap();
+    let json: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(json["jobs"]["queued"], 1);
+    assert_eq!(json["jobs"]["deferred"][0]["id"], id.as_str());
+    assert_eq!(
+        json["jobs"]["deferred"][0]["scheduled_for"],
+        start.to_rfc3339().as_str()
+    );
+
+    let resp = client
+        .post(format!("{base}/api/jobs/{id}/run-now"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+
+    let resp = client
+        .get(format!("{base}/api/admin/dashboard"))
+        .send()
+        .await
+        .unwrap();
+    let json: serde_json::Value = resp.json().await.unwrap();
+    assert!(json["jobs"]["deferred"].as_array().unwrap().is_empty());
+
+    // Only queued jobs can be run now.
+    {
+        let conn = harness.conn();
+        sf_db::queries::jobs::dequeue_next(&conn, "test-worker")
+            .unwrap()
+            .unwrap();
+    }
+    let resp = client
+        .post(format!("{base}/api/jobs/{id}/run-now"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test]
+async fn job_log_lists_recorded_commands() {
+    let (harness, addr) = TestHarness::with_server().await;
+    let client = reqwest::Client::new();
+    let base = format!("http://{addr}");
+
+    let resp = client
+        .post(format!("{base}/api/jobs/submit"))
+        .json(&serde_json::json!({"file_path": "/media/log.mkv"}))
+        .send()
+        .await
+        .unwrap();
+    let job: serde_json::Value = resp.json().await.unwrap();
+    let id = job["id"].as_str().unwrap().to_string();
+
+    {
+        let conn = harness.conn();
+        let job_id: sf_core::JobId = id.parse().unwrap();
+        sf_db::queries::job_logs::append_job_log(
+            &conn,
+            job_id,
+            &sf_db::queries::job_logs::NewJobLog {
+                kind: "command",
+                action: Some("remux"),
+                command: Some("ffmpeg -i /media/log.mkv out.mp4"),
+                exit_code: Some(1),
+                stderr: Some("Invalid data found when processing input"),
+                duration_ms: Some(42),
+                message: Some("ffmpeg exited with status 1"),
+            },
+        )
+        .unwrap();
+    }
+
+    let resp = client
+        .get(format!("{base}/api/jobs/{id}/log"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let json: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(json[0]["kind"], "command");
+    assert_eq!(json[0]["command"], "ffmpeg -i /media/log.mkv out.mp4");
+    assert_eq!(json[0]["exit_code"], 1);
+    assert_eq!(json[0]["stderr"], "Invalid data found when processing input");
+
+    let missing = sf_core::JobId::new();
+    let resp = client
+        .get(format!("{base}/api/jobs/{missing}/log"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 404);
+}
+
+#[tokio::test]
+async fn running_job_can_be_paused_resumed_and_cancelled() {
+    let (harness, addr) = TestHarness::with_server().await;
+    let client = reqwest::Client::new();
+    let base = format!("http://{addr}");
+
+    let submit = |path: &'static str| {
+        client
+            .post(format!("{base}/api/jobs/submit"))
+            .json(&serde_json::json!({"file_path": path}))
+            .send()
+    };
+    let job: serde_json::Value = submit("/media/running.mkv")
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let running = job["id"].as_str().unwrap().to_string();
+    let job: serde_json::Value = submit("/media/queued.mkv")
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let queued = job["id"].as_str().unwrap().to_string();
+
+    // Claim the first job as a worker would.
+    let control = sf_av::ProcessControl::default();
+    {
+        let conn = harness.conn();
+        let claimed = sf_db::queries::jobs::dequeue_next(&conn, "test-worker")
+            .unwrap()
+            .unwrap();
+        assert_eq!(claimed.id.to_string(), running);
+        harness.ctx.active_jobs.insert(claimed.id, control.clone());
+    }
+
+    let resp = client
+        .post(format!("{base}/api/jobs/{running}/pause"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let json: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(json["paused"], true);
+    assert!(control.is_paused());
+
+    let resp = client
+        .post(format!("{base}/api/jobs/{running}/resume"))
+        .send()
+        .await
+        .unwrap();
+    let json: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(json["paused"], false);
+
+    let resp = client
+        .post(format!("{base}/api/jobs/{running}/cancel"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let json: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(json["status"], "cancelled");
+    assert!(control.is_cancelled());
+
+    // Queued jobs cannot be paused, but can be cancelled once.
+    let resp = client
+        .post(format!("{base}/api/jobs/{queued}/pause"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+    for expected in [200, 400] {
+        let resp = client
+            .post(format!("{base}/api/jobs/{queued}/cancel"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), expected);
+    }
+
+    let missing = sf_core::JobId::new();
+    let resp = client
+        .post(format!("{base}/api/jobs/{missing}/cancel"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 404);
+}
+
+#[tokio::test]
+async fn deleting_a_running_job_cancels_it() {
+    let (harness, addr) = TestHarness::with_server().await;
+    let client = reqwest::Client::new();
+    let base = format!("http://{addr}");
+
+    let job: serde_json::Value = client
+        .post(format!("{base}/api/jobs/submit"))
+        .json(&serde_json::json!({"file_path": "/media/running.mkv"}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let id = job["id"].as_str().unwrap().to_string();
+
+    let control = sf_av::ProcessControl::default();
+    {
+        let conn = harness.conn();
+        let claimed = sf_db::queries::jobs::dequeue_next(&conn, "test-worker")
+            .unwrap()
+            .unwrap();
+        harness.ctx.active_jobs.insert(claimed.id, control.clone());
+    }
+
+    let resp = client
+        .delete(format!("{base}/api/jobs/{id}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 204);
+    assert!(control.is_cancelled());
+
+    let job: serde_json::Value = client
+        .get(format!("{base}/api/jobs/{id}"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(job["status"], "cancelled");
+
+    // Deleting a finished job is a no-op.
+    let resp = client
+        .delete(format!("{base}/api/jobs/{id}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 204);
+}
+
 #[tokio::test]
 async fn tools_endpoint() {
     let (_harness, addr) = TestHarness::with_server().await;
@@ -443,6 +712,136 @@ async fn tools_endpoint() {
     assert!(json.is_array());
 }
 
+// ---------------------------------------------------------------------------
+// Trash
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn trash_list_restore_and_purge() {
+    let tmp = tempfile::tempdir().unwrap();
+    let root = tmp.path().join("movies");
+    let original = root.join("Film (2020)").join("film.mkv");
+    std::fs::create_dir_all(original.parent().unwrap()).unwrap();
+    std::fs::write(&original, b"original").unwrap();
+
+    let mut config = sf_core::config::Config::default();
+    config.trash.dir = tmp.path().join("trash");
+    let (harness, addr) = TestHarness::with_server_config(config).await;
+    let client = reqwest::Client::new();
+    let base = format!("http://{addr}/api/trash");
+
+    let conn = harness.db.get().unwrap();
+    let lib = sf_db::queries::libraries::create_library(
+        &conn,
+        "Movies",
+        "movies",
+        &[root.to_string_lossy().into_owned()],
+        &serde_json::json!({}),
+    )
+    .unwrap();
+    drop(conn);
+
+    // Trashing keeps the path relative to the library root.
+    let entry = sf_server::trash::move_to_trash(&harness.ctx, &original, None)
+        .unwrap()
+        .unwrap();
+    assert!(!original.exists());
+    let trash_path = std::path::PathBuf::from(&entry.trash_path);
+    assert!(trash_path.ends_with(format!("{}/Film (2020)/film.mkv", entry.id)));
+    assert!(trash_path.exists());
+
+    let resp = client.get(&base).send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    let json: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(json["total_size_bytes"], 8);
+    assert_eq!(json["entries"].as_array().unwrap().len(), 1);
+    assert_eq!(json["entries"][0]["library_id"], lib.id.to_string());
+    assert!(json["entries"][0]["expires_at"].is_string());
+
+    // Restore replaces whatever took the original's place.
+    std::fs::write(&original, b"output").unwrap();
+    let resp = client
+        .post(format!("{base}/{}/restore", entry.id))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(std::fs::read(&original).unwrap(), b"original");
+    assert!(!tmp.path().join("trash").join(entry.id.to_string()).exists());
+
+    let resp = client
+        .post(format!("{base}/{}/restore", entry.id))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 404);
+
+    // Purging deletes the file for good.
+    let entry = sf_server::trash::move_to_trash(&harness.ctx, &original, None)
+        .unwrap()
+        .unwrap();
+    let resp = client.delete(&base).send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    let json: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(json["purged"], 1);
+    assert!(!std::path::Path::new(&entry.trash_path).exists());
+    assert!(!original.exists());
+
+    let resp = client.get(&base).send().await.unwrap();
+    let json: serde_json::Value = resp.json().await.unwrap();
+    assert!(json["entries"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn trash_retention_purges_oldest_over_size_limit() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut config = sf_core::config::Config::default();
+    config.trash.dir = tmp.path().join("trash");
+    config.trash.max_size_gb = 1;
+    let harness = TestHarness::with_config(config.clone());
+
+    let gb = 1024 * 1024 * 1024;
+    let conn = harness.db.get().unwrap();
+    std::fs::create_dir_all(tmp.path().join("trash")).unwrap();
+    let files = [
+        ("old", gb / 2, false),
+        ("mid", gb / 2, false),
+        ("new", gb / 2, false),
+        ("gone", 1, true),
+    ];
+    for (name, size, expired) in files {
+        let path = tmp.path().join("trash").join(name);
+        std::fs::write(&path, b"x").unwrap();
+        sf_db::queries::trash::create_trash_entry(
+            &conn,
+            &sf_db::queries::trash::NewTrashEntry {
+                id: sf_core::TrashId::new(),
+                original_path: name,
+                trash_path: &path.to_string_lossy(),
+                library_id: None,
+                job_id: None,
+                size_bytes: size,
+                expires_at: expired.then(|| chrono::Utc::now() - chrono::Duration::hours(1)),
+            },
+        )
+        .unwrap();
+    }
+    drop(conn);
+
+    let purged = sf_server::trash::enforce_retention(&harness.ctx, &config.trash).unwrap();
+    assert_eq!(purged, 2);
+
+    let conn = harness.db.get().unwrap();
+    let left: Vec<_> = sf_db::queries::trash::list_trash_entries(&conn)
+        .unwrap()
+        .into_iter()
+        .map(|e| e.original_path)
+        .collect();
+    assert_eq!(left, vec!["new".to_string(), "mid".to_string()]);
+    assert!(!tmp.path().join("trash").join("old").exists());
+    assert!(!tmp.path().join("trash").join("gone").exists());
+}
+
 // ---------------------------------------------------------------------------
 // Metrics endpoint
 // ---------------------------------------------------------------------------
@@ -0,0 +1,75 @@
+//! Command-line interface tests, run against the built binary.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn fixture() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/bbb_profile_b.mp4")
+}
+
+/// Run `sceneforged run --dry-run` on the fixture with `rules` in the config
+/// file, returning stdout.
+fn dry_run(rules: serde_json::Value) -> String {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.json");
+    std::fs::write(&config, serde_json::json!({ "rules": rules }).to_string()).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_sceneforged"))
+        .arg("--config")
+        .arg(&config)
+        .args(["run", "--dry-run"])
+        .arg(fixture())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn run_applies_rule_path_scopes() {
+    let rule = |name: &str, paths: &[&str]| {
+        serde_json::json!({
+            "id": uuid::Uuid::new_v4(),
+            "name": name,
+            "expr": "true",
+            "actions": [{ "type": "remux", "container": "mkv", "keep_original": false }],
+            "scope": { "paths": paths },
+        })
+    };
+
+    let elsewhere = rule("elsewhere", &["/somewhere/else/**"]);
+    let stdout = dry_run(serde_json::json!([elsewhere]));
+    assert!(stdout.contains("No rules matched"), "{stdout}");
+
+    let fixtures = rule("fixtures", &["**/fixtures/*.mp4"]);
+    let stdout = dry_run(serde_json::json!([elsewhere, fixtures]));
+    assert!(stdout.contains("Matched rule: fixtures"), "{stdout}");
+    assert!(!stdout.contains("elsewhere"), "{stdout}");
+}
+
+#[test]
+fn run_rejects_unreadable_or_invalid_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let invalid = dir.path().join("config.json");
+    std::fs::write(&invalid, "{ not json").unwrap();
+
+    for (config, message) in [
+        (invalid, "Invalid JSON"),
+        (dir.path().join("missing.json"), "Cannot read"),
+    ] {
+        let output = Command::new(env!("CARGO_BIN_EXE_sceneforged"))
+            .arg("--config")
+            .arg(&config)
+            .args(["run", "--dry-run"])
+            .arg(fixture())
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(message), "{stderr}");
+    }
+}
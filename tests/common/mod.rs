@@ -21,6 +21,7 @@ use hyper_util::service::TowerToHyperService;
 use sf_server::context::{AppContext, ConfigStore};
 use sf_server::router::build_router;
 use sf_server::sendfile;
+use sf_server::transcode::TranscodeManager;
 
 /// Test harness wrapping a fully-constructed [`AppContext`] backed by an
 /// in-memory database.
@@ -44,6 +45,7 @@ impl TestHarness {
         let config_store = Arc::new(ConfigStore::new(&config, None));
         let event_bus = Arc::new(EventBus::default());
 
+        let transcode = Arc::new(TranscodeManager::new(config.transcode.clone(), tools.clone()));
         let ctx = AppContext {
             db: db.clone(),
             config: Arc::new(config),
@@ -53,6 +55,10 @@ impl TestHarness {
             tools,
             hls_cache: Arc::new(DashMap::new()),
             hls_loading: Arc::new(DashMap::new()),
+            subtitle_cache: Arc::new(DashMap::new()),
+            subtitle_loading: Arc::new(DashMap::new()),
+            transcode,
+            active_jobs: Arc::new(DashMap::new()),
             active_conversions: Arc::new(DashMap::new()),
             active_scans: Arc::new(DashMap::new()),
             sendfile_sndbuf: Arc::new(std::sync::atomic::AtomicU32::new(128 * 1024)),
@@ -316,3 +316,291 @@ async fn test_arr_connection_failure() {
     let json: serde_json::Value = resp.json().await.unwrap();
     assert_eq!(json["success"], false);
 }
+
+// ---------------------------------------------------------------------------
+// Rules
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn put_rules_accepts_dsl_expressions() {
+    let (_h, addr) = TestHarness::with_server().await;
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .put(format!("http://{addr}/api/config/rules"))
+        .json(&serde_json::json!([{
+            "id": "0d9f4a8e-3c1b-4e7a-9f6d-2b5c8e1a7f30",
+            "name": "DV7 to 8.1",
+            "expr": "codec in [h265] and dv_profile == 7 and not container == mp4",
+            "actions": [],
+        }]))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let rules: Vec<serde_json::Value> = resp.json().await.unwrap();
+    assert_eq!(rules.len(), 1);
+    // Stored and returned in the JSON expression form.
+    assert_eq!(rules[0]["expr"]["type"], "and");
+    assert_eq!(rules[0]["expr"]["exprs"].as_array().unwrap().len(), 3);
+
+    let resp = client
+        .get(format!("http://{addr}/api/config/rules"))
+        .send()
+        .await
+        .unwrap();
+    let rules: Vec<serde_json::Value> = resp.json().await.unwrap();
+    assert_eq!(rules[0]["name"], "DV7 to 8.1");
+}
+
+#[tokio::test]
+async fn put_rules_reports_dsl_error_position() {
+    let (_h, addr) = TestHarness::with_server().await;
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .put(format!("http://{addr}/api/config/rules"))
+        .json(&serde_json::json!([{
+            "id": "0d9f4a8e-3c1b-4e7a-9f6d-2b5c8e1a7f31",
+            "name": "typo",
+            "expr": "codec == h265 and contianer == mkv",
+            "actions": [],
+        }]))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+    let json: serde_json::Value = resp.json().await.unwrap();
+    let message = json["error"].as_str().unwrap();
+    assert!(message.contains("unknown field `contianer`"), "{message}");
+    assert!(message.contains("line 1, column 19"), "{message}");
+}
+
+fn fixture_path() -> String {
+    format!(
+        "{}/tests/fixtures/bbb_profile_b.mp4",
+        env!("CARGO_MANIFEST_DIR")
+    )
+}
+
+#[tokio::test]
+async fn explain_rules_by_file_path() {
+    let (_h, addr) = TestHarness::with_server().await;
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .put(format!("http://{addr}/api/config/rules"))
+        .json(&serde_json::json!([
+            {
+                "id": "0d9f4a8e-3c1b-4e7a-9f6d-2b5c8e1a7f40",
+                "name": "hevc only",
+                "priority": 20,
+                "expr": "codec == h265 and container == mp4",
+                "actions": [],
+            },
+            {
+                "id": "0d9f4a8e-3c1b-4e7a-9f6d-2b5c8e1a7f41",
+                "name": "small stereo",
+                "priority": 10,
+                "expr": "audio_channels <= 2 and resolution <= 1080p",
+                "actions": [],
+            },
+        ]))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+
+    let resp = client
+        .post(format!("http://{addr}/api/config/rules/explain"))
+        .json(&serde_json::json!({ "file_path": fixture_path() }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let json: serde_json::Value = resp.json().await.unwrap();
+
+    assert_eq!(json["matched_rule"]["name"], "small stereo");
+    let rules = json["rules"].as_array().unwrap();
+    assert_eq!(rules.len(), 2);
+
+    // The higher-priority rule fails on its codec leaf.
+    assert_eq!(rules[0]["name"], "hevc only");
+    assert_eq!(rules[0]["matched"], false);
+    let codec = &rules[0]["trace"]["children"][0];
+    assert_eq!(codec["condition"], "codec == h265");
+    assert_eq!(codec["result"], false);
+    assert_eq!(codec["actual"], "h264");
+    assert_eq!(rules[0]["trace"]["children"][1]["result"], true);
+
+    assert_eq!(rules[1]["matched"], true);
+    assert_eq!(
+        rules[1]["trace"]["children"][0]["actual"],
+        serde_json::json!([2])
+    );
+}
+
+#[tokio::test]
+async fn explain_rules_by_media_file_id_with_inline_rules() {
+    let (h, addr) = TestHarness::with_server().await;
+    let (lib_id, _) = h.create_library();
+    let (_, _, _, mf_id) = h.create_item_with_real_media(
+        lib_id,
+        "Big Buck Bunny",
+        &fixture_path(),
+        "mp4",
+        "h264",
+        "aac",
+        640,
+        360,
+        "B",
+        24.0,
+    );
+
+    let client = reqwest::Client::new();
+    let resp = client
+        .post(format!("http://{addr}/api/config/rules/explain"))
+        .json(&serde_json::json!({
+            "media_file_id": mf_id,
+            "rules": [{
+                "id": "0d9f4a8e-3c1b-4e7a-9f6d-2b5c8e1a7f42",
+                "name": "not mkv",
+                "expr": "not container == mkv",
+                "actions": [],
+            }],
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let json: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(json["matched_rule"]["name"], "not mkv");
+    let trace = &json["rules"][0]["trace"];
+    assert_eq!(trace["node"], "not");
+    assert_eq!(trace["children"][0]["actual"], "mp4");
+}
+
+#[tokio::test]
+async fn explain_rules_requires_a_target() {
+    let (_h, addr) = TestHarness::with_server().await;
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .post(format!("http://{addr}/api/config/rules/explain"))
+        .json(&serde_json::json!({}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+
+    let resp = client
+        .post(format!("http://{addr}/api/config/rules/explain"))
+        .json(&serde_json::json!({ "media_file_id": "00000000-0000-0000-0000-000000000000" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 404);
+}
+
+#[tokio::test]
+async fn match_mode_round_trip() {
+    let (_h, addr) = TestHarness::with_server().await;
+    let client = reqwest::Client::new();
+
+    let resp = reqwest::get(format!("http://{addr}/api/config/rules/match_mode"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let json: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(json["match_mode"], "first");
+
+    let resp = client
+        .put(format!("http://{addr}/api/config/rules/match_mode"))
+        .json(&serde_json::json!({ "match_mode": "all" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+
+    let resp = reqwest::get(format!("http://{addr}/api/config/rules/match_mode"))
+        .await
+        .unwrap();
+    let json: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(json["match_mode"], "all");
+}
+
+#[tokio::test]
+async fn explain_rules_merges_actions_of_continued_rules() {
+    let (_h, addr) = TestHarness::with_server().await;
+    let client = reqwest::Client::new();
+    let rules = serde_json::json!([
+        {
+            "id": "0d9f4a8e-3c1b-4e7a-9f6d-2b5c8e1a7f50",
+            "name": "compat audio",
+            "priority": 30,
+            "continue": true,
+            "expr": "container == mp4",
+            "actions": [
+                { "type": "add_compat_audio", "source_codec": "aac", "target_codec": "ac3" },
+            ],
+        },
+        {
+            "id": "0d9f4a8e-3c1b-4e7a-9f6d-2b5c8e1a7f51",
+            "name": "strip subs",
+            "priority": 20,
+            "expr": "codec == h264",
+            "actions": [
+                { "type": "strip_tracks", "track_types": ["subtitle"], "languages": null },
+                { "type": "add_compat_audio", "source_codec": "aac", "target_codec": "ac3" },
+            ],
+        },
+        {
+            "id": "0d9f4a8e-3c1b-4e7a-9f6d-2b5c8e1a7f52",
+            "name": "anything",
+            "priority": 10,
+            "expr": "true",
+            "actions": [],
+        },
+    ]);
+
+    let resp = client
+        .post(format!("http://{addr}/api/config/rules/explain"))
+        .json(&serde_json::json!({ "file_path": fixture_path(), "rules": rules }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let json: serde_json::Value = resp.json().await.unwrap();
+
+    let names: Vec<_> = json["matched_rules"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["compat audio", "strip subs"]);
+    assert_eq!(json["matched_rule"]["name"], "compat audio");
+
+    // Deduplicated and ordered strip first.
+    let actions = json["actions"].as_array().unwrap();
+    assert_eq!(actions.len(), 2);
+    assert_eq!(actions[0]["type"], "strip_tracks");
+    assert_eq!(actions[1]["type"], "add_compat_audio");
+
+    // The last rule matched but was not applied.
+    assert_eq!(json["rules"][2]["matched"], true);
+    assert_eq!(json["rules"][2]["applied"], false);
+
+    let resp = client
+        .post(format!("http://{addr}/api/config/rules/explain"))
+        .json(&serde_json::json!({
+            "file_path": fixture_path(),
+            "rules": rules,
+            "match_mode": "all",
+        }))
+        .send()
+        .await
+        .unwrap();
+    let json: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(json["matched_rules"].as_array().unwrap().len(), 3);
+}
@@ -259,3 +259,68 @@ async fn dv_batch_skips_non_dv() {
     assert!(json["job_ids"].as_array().unwrap().is_empty());
     assert_eq!(json["errors"].as_array().unwrap().len(), 1);
 }
+
+#[tokio::test]
+async fn failed_conversion_removes_its_chunks() {
+    let (h, addr) = TestHarness::with_server().await;
+    let (lib_id, _) = h.create_library();
+    let media = tempfile::tempdir().unwrap();
+    let source = media.path().join("Broken.mkv");
+    std::fs::write(&source, b"not a video").unwrap();
+    let (_, _, item_id_str, _) = h.create_item_with_real_media(
+        lib_id,
+        "Broken",
+        source.to_str().unwrap(),
+        "mkv",
+        "hevc",
+        "aac",
+        1920,
+        1080,
+        "C",
+        7200.0,
+    );
+
+    let client = reqwest::Client::new();
+    let resp = client
+        .post(format!("http://{addr}/api/conversions/submit"))
+        .json(&serde_json::json!({"item_id": item_id_str}))
+        .send()
+        .await
+        .unwrap();
+    let created: serde_json::Value = resp.json().await.unwrap();
+    let job_id = created["id"].as_str().unwrap();
+
+    // Chunks left behind by an earlier, interrupted run of the conversion.
+    let chunks = sf_av::chunk_dir(&media.path().join("Broken-pb.mp4"));
+    std::fs::create_dir_all(&chunks).unwrap();
+    std::fs::write(chunks.join("chunk-00000.chunk"), b"x").unwrap();
+
+    let config = sf_core::config::WorkersConfig {
+        processors: 0,
+        ..Default::default()
+    };
+    let cancel = tokio_util::sync::CancellationToken::new();
+    let workers = sf_server::workers::spawn_workers(&h.ctx, &config, &cancel);
+
+    let mut status = String::new();
+    for _ in 0..100 {
+        let resp = client
+            .get(format!("http://{addr}/api/conversions/{job_id}"))
+            .send()
+            .await
+            .unwrap();
+        let json: serde_json::Value = resp.json().await.unwrap();
+        status = json["status"].as_str().unwrap().to_string();
+        if status == "failed" {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    cancel.cancel();
+    for worker in workers {
+        worker.await.unwrap();
+    }
+
+    assert_eq!(status, "failed");
+    assert!(!chunks.exists());
+}
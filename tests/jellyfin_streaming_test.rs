@@ -97,3 +97,86 @@ async fn playback_info_invalid_id() {
     let status = resp.status().as_u16();
     assert!(status == 400 || status == 422, "expected 400 or 422, got {status}");
 }
+
+#[tokio::test]
+async fn playback_info_offers_transcoding_url() {
+    let (h, addr) = TestHarness::with_server().await;
+    let (lib_id, _) = h.create_library();
+    let (_, _, item_id_str, _) = h.create_item_with_media(lib_id, "Transcode Movie", "movie");
+
+    let client = reqwest::Client::new();
+    let resp = client
+        .post(format!("http://{addr}/Items/{item_id_str}/PlaybackInfo"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let json: serde_json::Value = resp.json().await.unwrap();
+    let play_session_id = json["PlaySessionId"].as_str().unwrap().to_string();
+    let source = &json["MediaSources"][0];
+    assert!(source["SupportsTranscoding"].as_bool().unwrap());
+    assert_eq!(source["TranscodingSubProtocol"], "hls");
+    assert_eq!(source["TranscodingContainer"], "mp4");
+    let url = source["TranscodingUrl"].as_str().unwrap();
+    assert!(url.contains(&play_session_id));
+    assert!(url.ends_with("/main.m3u8"));
+
+    // The playlist is known up front; no ffmpeg is started for it.
+    let resp = client.get(format!("http://{addr}{url}")).send().await.unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers()["content-type"],
+        "application/vnd.apple.mpegurl"
+    );
+    let playlist = resp.text().await.unwrap();
+    assert!(playlist.contains("#EXT-X-MAP:URI=\"init.mp4\""));
+    assert_eq!(playlist.matches("#EXTINF:").count(), 1200);
+    assert!(playlist.ends_with("#EXT-X-ENDLIST\n"));
+    assert_eq!(h.ctx.transcode.session_count(), 1);
+
+    let out_of_range = url.replace("main.m3u8", "segment_99999.m4s");
+    let resp = client
+        .get(format!("http://{addr}{out_of_range}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 404);
+
+    let resp = client
+        .delete(format!(
+            "http://{addr}/Videos/ActiveEncodings?PlaySessionId={play_session_id}"
+        ))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 204);
+    assert_eq!(h.ctx.transcode.session_count(), 0);
+}
+
+#[tokio::test]
+async fn transcoding_rejects_media_source_of_another_item() {
+    let (h, addr) = TestHarness::with_server().await;
+    let (lib_id, _) = h.create_library();
+    let (_, _, item_id_str, _) = h.create_item_with_media(lib_id, "Transcode Movie", "movie");
+    let (_, _, other_item_id_str, _) = h.create_item_with_media(lib_id, "Other Movie", "movie");
+
+    let client = reqwest::Client::new();
+    let resp = client
+        .post(format!("http://{addr}/Items/{item_id_str}/PlaybackInfo"))
+        .send()
+        .await
+        .unwrap();
+    let json: serde_json::Value = resp.json().await.unwrap();
+    let url = json["MediaSources"][0]["TranscodingUrl"]
+        .as_str()
+        .unwrap()
+        .replace(&item_id_str, &other_item_id_str);
+
+    let resp = client
+        .get(format!("http://{addr}{url}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 404);
+    assert_eq!(h.ctx.transcode.session_count(), 0);
+}
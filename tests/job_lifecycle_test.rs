@@ -218,14 +218,14 @@ async fn job_submit_and_delete_via_api() {
         .unwrap();
     assert_eq!(resp.status(), 204);
 
-    // The job should now be in failed state (cancelled).
+    // The job should now be cancelled.
     let resp = client
         .get(format!("{base}/jobs/{job_id}"))
         .send()
         .await
         .unwrap();
     let job: serde_json::Value = resp.json().await.unwrap();
-    assert_eq!(job["status"], "failed");
+    assert_eq!(job["status"], "cancelled");
 }
 
 // ---------------------------------------------------------------------------
@@ -286,3 +286,47 @@ async fn dequeue_from_empty_queue() {
     let result = sf_db::queries::jobs::dequeue_next(&conn, "w").unwrap();
     assert!(result.is_none());
 }
+
+// ---------------------------------------------------------------------------
+// Job result
+// ---------------------------------------------------------------------------
+
+#[tokio::test]
+async fn job_result_is_exposed_via_api() {
+    let (harness, addr) = TestHarness::with_server().await;
+    let client = reqwest::Client::new();
+    let base = format!("http://{addr}/api");
+
+    let conn = harness.conn();
+    let job =
+        sf_db::queries::jobs::create_job(&conn, "/media/mix.mkv", "mix.mkv", None, 0).unwrap();
+
+    let resp = client
+        .get(format!("{base}/jobs/{}", job.id))
+        .send()
+        .await
+        .unwrap();
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert!(body["result"].is_null());
+
+    // Recorded by the processor when the pipeline finishes.
+    let result = serde_json::json!({
+        "actions": [{
+            "action": "Downmix Audio",
+            "summary": "Added stereo downmix of track 0: -27.6 -> -23.0 LUFS",
+            "details": {"source_track": 0, "loudness": {"input_i": -27.61, "output_i": -23.01}},
+        }]
+    });
+    sf_db::queries::jobs::set_job_result(&conn, job.id, &result).unwrap();
+    sf_db::queries::jobs::dequeue_next(&conn, "test-worker").unwrap();
+    sf_db::queries::jobs::complete_job(&conn, job.id).unwrap();
+
+    let resp = client
+        .get(format!("{base}/jobs/{}", job.id))
+        .send()
+        .await
+        .unwrap();
+    let body: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(body["status"], "completed");
+    assert_eq!(body["result"], result);
+}
@@ -129,3 +129,161 @@ async fn admin_stats() {
     assert!(json["storage_bytes"].as_i64().unwrap() > 0);
     assert!(json["items_by_profile"].is_object());
 }
+
+#[tokio::test]
+async fn library_rules_crud() {
+    let (_h, addr) = TestHarness::with_server().await;
+    let client = reqwest::Client::new();
+
+    let resp = client
+        .post(format!("http://{addr}/api/libraries"))
+        .json(&serde_json::json!({
+            "name": "Kids",
+            "media_type": "movies",
+            "paths": ["/media/kids"],
+            "config": { "custom": "kept" },
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 201);
+    let lib: serde_json::Value = resp.json().await.unwrap();
+    let lib_id = lib["id"].as_str().unwrap();
+
+    // No overrides yet.
+    let resp = reqwest::get(format!("http://{addr}/api/libraries/{lib_id}/rules"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let rules: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(rules["rule_mode"], "extend");
+    assert!(rules["rules"].as_array().unwrap().is_empty());
+
+    let resp = client
+        .put(format!("http://{addr}/api/libraries/{lib_id}/rules"))
+        .json(&serde_json::json!({
+            "rule_mode": "replace",
+            "rules": [{
+                "id": "5b0c7c1e-8f2d-4d7a-9c1b-3e4f5a6b7c80",
+                "name": "kids stereo",
+                "expr": "audio_channels >= 6",
+                "actions": [],
+            }],
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let rules: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(rules["rule_mode"], "replace");
+    assert_eq!(rules["rules"][0]["name"], "kids stereo");
+
+    // Stored in the library config alongside existing keys.
+    let resp = reqwest::get(format!("http://{addr}/api/libraries/{lib_id}"))
+        .await
+        .unwrap();
+    let lib: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(lib["config"]["custom"], "kept");
+    assert_eq!(lib["config"]["rules"][0]["name"], "kids stereo");
+
+    let resp = client
+        .put(format!("http://{addr}/api/libraries/{lib_id}/rules"))
+        .json(&serde_json::json!({ "rule_mode": "sometimes" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test]
+async fn library_rules_override_global_rules_for_files_in_library() {
+    let (_h, addr) = TestHarness::with_server().await;
+    let client = reqwest::Client::new();
+    let fixtures = format!("{}/tests/fixtures", env!("CARGO_MANIFEST_DIR"));
+    let fixture = format!("{fixtures}/bbb_profile_b.mp4");
+
+    let resp = client
+        .post(format!("http://{addr}/api/libraries"))
+        .json(&serde_json::json!({
+            "name": "Fixtures",
+            "media_type": "movies",
+            "paths": [fixtures],
+        }))
+        .send()
+        .await
+        .unwrap();
+    let lib: serde_json::Value = resp.json().await.unwrap();
+    let lib_id = lib["id"].as_str().unwrap().to_string();
+
+    // Global rules: one everywhere, one scoped to another path, one scoped to
+    // this library.
+    let resp = client
+        .put(format!("http://{addr}/api/config/rules"))
+        .json(&serde_json::json!([
+            {
+                "id": "5b0c7c1e-8f2d-4d7a-9c1b-3e4f5a6b7c81",
+                "name": "global",
+                "expr": "true",
+                "actions": [],
+            },
+            {
+                "id": "5b0c7c1e-8f2d-4d7a-9c1b-3e4f5a6b7c82",
+                "name": "elsewhere",
+                "expr": "true",
+                "actions": [],
+                "scope": { "paths": ["/somewhere/else/**"] },
+            },
+            {
+                "id": "5b0c7c1e-8f2d-4d7a-9c1b-3e4f5a6b7c83",
+                "name": "this library",
+                "expr": "true",
+                "actions": [],
+                "scope": { "library_ids": [lib_id] },
+            },
+        ]))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+
+    let explain = |client: reqwest::Client, fixture: String| async move {
+        let resp = client
+            .post(format!("http://{addr}/api/config/rules/explain"))
+            .json(&serde_json::json!({ "file_path": fixture }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 200);
+        let json: serde_json::Value = resp.json().await.unwrap();
+        json["rules"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["name"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    let mut names = explain(client.clone(), fixture.clone()).await;
+    names.sort();
+    assert_eq!(names, ["global", "this library"]);
+
+    // Replace mode drops the global rules for this library.
+    let resp = client
+        .put(format!("http://{addr}/api/libraries/{lib_id}/rules"))
+        .json(&serde_json::json!({
+            "rule_mode": "replace",
+            "rules": [{
+                "id": "5b0c7c1e-8f2d-4d7a-9c1b-3e4f5a6b7c84",
+                "name": "library only",
+                "expr": "container == mp4",
+                "actions": [],
+            }],
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+
+    let names = explain(client.clone(), fixture.clone()).await;
+    assert_eq!(names, ["library only"]);
+}
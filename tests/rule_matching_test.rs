@@ -29,11 +29,13 @@ fn make_dv_movie() -> MediaInfo {
             frame_rate: Some(23.976),
             bit_depth: Some(10),
             hdr_format: HdrFormat::DolbyVision,
+            hdr10plus: false,
             dolby_vision: Some(DvInfo {
                 profile: 7,
                 rpu_present: true,
                 el_present: true,
                 bl_present: true,
+                el_type: None,
             }),
             default: true,
             language: Some("eng".into()),
@@ -64,6 +66,7 @@ fn make_sdr_movie() -> MediaInfo {
             frame_rate: Some(24.0),
             bit_depth: Some(8),
             hdr_format: HdrFormat::Sdr,
+            hdr10plus: false,
             dolby_vision: None,
             default: true,
             language: Some("eng".into()),
@@ -88,6 +91,8 @@ fn make_rule(name: &str, priority: i32, enabled: bool, expr: Expr) -> Rule {
         priority,
         expr,
         actions: vec![],
+        scope: Default::default(),
+        continue_matching: false,
     }
 }
 
@@ -517,6 +522,8 @@ fn rule_with_actions() {
                 keep_original: false,
             },
         ],
+        scope: Default::default(),
+        continue_matching: false,
     };
 
     let engine = RuleEngine::new(vec![rule]);
@@ -544,6 +551,8 @@ fn rules_serialize_roundtrip() {
                 ]),
             ]),
             actions: vec![ActionConfig::DvConvert { target_profile: 8 }],
+            scope: Default::default(),
+            continue_matching: false,
         },
         Rule {
             id: RuleId::new(),
@@ -552,6 +561,8 @@ fn rules_serialize_roundtrip() {
             priority: 10,
             expr: Expr::Condition(Condition::Container(vec![Container::Mp4])),
             actions: vec![],
+            scope: Default::default(),
+            continue_matching: false,
         },
     ];
 
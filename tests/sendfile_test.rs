@@ -11,7 +11,7 @@ use std::io::Write;
 use std::sync::Arc;
 
 use common::TestHarness;
-use sf_media::{DataRange, PrecomputedSegment, PreparedMedia};
+use sf_media::{AudioRendition, DataRange, MasterPlaylist, PrecomputedSegment, PreparedMedia};
 
 /// Create a synthetic PreparedMedia backed by a temp file containing known data.
 /// Returns the PreparedMedia and the temp file handle (to keep it alive).
@@ -57,7 +57,32 @@ fn synthetic_prepared_media() -> (PreparedMedia, tempfile::NamedTempFile) {
         duration_secs: 2.0,
         init_segment: vec![0xFF; 32], // Fake init segment.
         variant_playlist: "#EXTM3U\n#EXT-X-TARGETDURATION:2\n".to_string(),
+        master: MasterPlaylist {
+            renditions: vec![],
+            variants: vec![],
+        },
         segments: vec![segment],
+        audio_renditions: vec![AudioRendition {
+            language: Some("fre".to_string()),
+            name: "fre".to_string(),
+            channels: 2,
+            init_segment: vec![0xFE; 16],
+            playlist: String::new(),
+            // An audio-only segment reusing the audio bytes.
+            segments: vec![PrecomputedSegment {
+                index: 0,
+                start_time_secs: 0.0,
+                duration_secs: 2.0,
+                moof_bytes: moof_bytes.clone(),
+                mdat_header: mdat_header.clone(),
+                video_data_ranges: vec![],
+                audio_data_ranges: vec![DataRange {
+                    file_offset: 256,
+                    length: 128,
+                }],
+                data_length: 128,
+            }],
+        }],
         target_duration: 2,
     };
 
@@ -109,6 +134,38 @@ async fn sendfile_serves_segment_correctly() {
     assert_eq!(body.as_ref(), expected_body.as_slice());
 }
 
+#[tokio::test]
+async fn sendfile_serves_audio_rendition_segment() {
+    let (harness, addr) = TestHarness::with_sendfile_server().await;
+
+    let mf_id = sf_core::MediaFileId::new();
+    let (prepared, _tmp) = synthetic_prepared_media();
+
+    let mut expected_body = Vec::new();
+    expected_body.extend_from_slice(&[0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08]);
+    expected_body.extend_from_slice(&[0xBBu8; 128]);
+
+    harness.ctx.hls_cache.insert(mf_id, (Arc::new(prepared), std::time::Instant::now()));
+
+    // Separate connections: the sendfile keep-alive loop only serves segments.
+    let url = format!("http://{addr}/api/stream/{mf_id}/audio_0_segment_0.m4s");
+    let resp = reqwest::get(&url).await.expect("request failed");
+    assert_eq!(resp.status(), 200);
+    let body = resp.bytes().await.unwrap();
+    assert_eq!(body.as_ref(), expected_body.as_slice());
+
+    // Out-of-range renditions are 404.
+    let url = format!("http://{addr}/api/stream/{mf_id}/audio_1_segment_0.m4s");
+    let resp = reqwest::get(&url).await.expect("request failed");
+    assert_eq!(resp.status(), 404);
+
+    // The rendition's init segment goes through Axum.
+    let url = format!("http://{addr}/api/stream/{mf_id}/audio_0_init.mp4");
+    let resp = reqwest::get(&url).await.expect("request failed");
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.bytes().await.unwrap().as_ref(), &[0xFE; 16]);
+}
+
 // ---------------------------------------------------------------------------
 // Non-segment requests still go through Axum
 // ---------------------------------------------------------------------------
@@ -313,7 +370,12 @@ async fn sendfile_serves_multiple_segments() {
         duration_secs: 4.0,
         init_segment: vec![],
         variant_playlist: String::new(),
+        master: MasterPlaylist {
+            renditions: vec![],
+            variants: vec![],
+        },
         segments: vec![seg0, seg1],
+        audio_renditions: vec![],
         target_duration: 2,
     };
 
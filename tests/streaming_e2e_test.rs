@@ -34,7 +34,7 @@ fn parse_moov_from_real_mp4() {
     assert!(!video.codec_private.is_empty(), "avcC data must be present");
 
     // Audio track must be present.
-    let audio = metadata.audio_track.as_ref().expect("audio track missing");
+    let audio = metadata.audio_tracks.first().expect("audio track missing");
     assert_eq!(audio.sample_rate, 48000);
     assert!(audio.channels >= 2);
 
@@ -151,6 +151,120 @@ async fn hls_playlist_from_real_mp4() {
     assert!(playlist.contains("#EXT-X-ENDLIST"));
 }
 
+#[tokio::test]
+async fn hls_master_playlist_from_real_mp4() {
+    let (h, addr) = TestHarness::with_server().await;
+    let (lib_id, _) = h.create_library();
+    let path = fixture_path();
+
+    let (_, _, _, mf_id_str) = h.create_item_with_real_media(
+        lib_id,
+        "Big Buck Bunny",
+        &path,
+        "mp4",
+        "h264",
+        "aac",
+        640,
+        360,
+        "B",
+        24.0,
+    );
+
+    let resp = reqwest::get(format!("http://{addr}/api/stream/{mf_id_str}/master.m3u8"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+
+    // A single audio track stays muxed: one variant, no renditions.
+    let playlist = resp.text().await.unwrap();
+    assert!(playlist.starts_with("#EXTM3U"), "not a valid M3U8 playlist");
+    assert!(playlist.contains("#EXT-X-STREAM-INF:BANDWIDTH="));
+    assert!(playlist.contains("RESOLUTION=640x360"));
+    assert!(playlist.contains("\nindex.m3u8\n"));
+    assert!(!playlist.contains("#EXT-X-MEDIA"));
+
+    let resp = reqwest::get(format!("http://{addr}/api/stream/{mf_id_str}/audio_0.m3u8"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 404);
+}
+
+#[tokio::test]
+async fn hls_master_playlist_lists_text_subtitle_renditions() {
+    let (h, addr) = TestHarness::with_server().await;
+    let (lib_id, _) = h.create_library();
+    let path = fixture_path();
+
+    let (_, mf_id, _, mf_id_str) = h.create_item_with_real_media(
+        lib_id,
+        "Big Buck Bunny",
+        &path,
+        "mp4",
+        "h264",
+        "aac",
+        640,
+        360,
+        "B",
+        24.0,
+    );
+    let conn = h.conn();
+    sf_db::queries::subtitle_tracks::create_subtitle_track(
+        &conn, mf_id, 0, "subrip", Some("eng"), false, true,
+    )
+    .unwrap();
+    sf_db::queries::subtitle_tracks::create_subtitle_track(
+        &conn, mf_id, 1, "hdmv_pgs_subtitle", Some("eng"), false, false,
+    )
+    .unwrap();
+    sf_db::queries::subtitle_tracks::create_subtitle_track(
+        &conn, mf_id, 2, "subrip", Some("eng"), true, false,
+    )
+    .unwrap();
+    drop(conn);
+
+    let resp = reqwest::get(format!("http://{addr}/api/stream/{mf_id_str}/master.m3u8"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let playlist = resp.text().await.unwrap();
+
+    // Image-based PGS can't be served as WebVTT and is left out.
+    assert!(playlist.contains(
+        "#EXT-X-MEDIA:TYPE=SUBTITLES,GROUP-ID=\"subs\",LANGUAGE=\"eng\",NAME=\"eng\",DEFAULT=YES,AUTOSELECT=YES,FORCED=NO,URI=\"subtitles_0.m3u8\"\n"
+    ));
+    assert!(playlist.contains(
+        "NAME=\"eng (forced)\",DEFAULT=NO,AUTOSELECT=YES,FORCED=YES,URI=\"subtitles_2.m3u8\"\n"
+    ));
+    assert!(!playlist.contains("subtitles_1.m3u8"));
+    assert!(playlist.contains(",SUBTITLES=\"subs\"\nindex.m3u8\n"));
+
+    // The subtitle playlist follows the video segment timeline.
+    let video = reqwest::get(format!("http://{addr}/api/stream/{mf_id_str}/index.m3u8"))
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    let resp = reqwest::get(format!("http://{addr}/api/stream/{mf_id_str}/subtitles_2.m3u8"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let subtitles = resp.text().await.unwrap();
+    assert!(!subtitles.contains("#EXT-X-MAP"));
+    assert!(subtitles.contains("subtitles_2_segment_0.vtt"));
+    assert_eq!(
+        subtitles.matches("#EXTINF").count(),
+        video.matches("#EXTINF").count()
+    );
+
+    for missing in ["subtitles_1.m3u8", "subtitles_9.m3u8", "subtitles_0_segment_999.vtt"] {
+        let resp = reqwest::get(format!("http://{addr}/api/stream/{mf_id_str}/{missing}"))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 404, "{missing}");
+    }
+}
+
 #[tokio::test]
 async fn hls_init_segment_from_real_mp4() {
     let (h, addr) = TestHarness::with_server().await;
@@ -105,3 +105,39 @@ async fn get_subtitle_missing_media_file() {
     .unwrap();
     assert_eq!(resp.status(), 404);
 }
+
+#[tokio::test]
+async fn concurrent_cue_requests_extract_once() {
+    use std::os::unix::fs::PermissionsExt;
+
+    // A stand-in ffmpeg that counts its runs and emits a one-cue WebVTT.
+    let dir = tempfile::tempdir().unwrap();
+    let runs = dir.path().join("runs");
+    let ffmpeg = dir.path().join("ffmpeg");
+    std::fs::write(
+        &ffmpeg,
+        format!(
+            "#!/bin/sh\necho run >> '{}'\nsleep 0.3\nprintf 'WEBVTT\\n\\n00:00:01.000 --> 00:00:02.000\\nHello\\n'\n",
+            runs.display()
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&ffmpeg, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let mut config = sf_core::config::Config::default();
+    config.tools.ffmpeg_path = Some(ffmpeg);
+    let h = TestHarness::with_config(config);
+    let (lib_id, _) = h.create_library();
+    let (_, mf_id, _, _) = h.create_item_with_media(lib_id, "SubsMovie", "movie");
+    sf_db::queries::subtitle_tracks::create_subtitle_track(
+        &h.conn(), mf_id, 0, "subrip", Some("eng"), false, true,
+    )
+    .unwrap();
+
+    let cues = || sf_server::hls_prep::get_subtitle_cues(&h.ctx, mf_id, 0);
+    let (a, b, c) = tokio::join!(cues(), cues(), cues());
+    for cues in [a, b, c] {
+        assert_eq!(cues.unwrap().len(), 1);
+    }
+    assert_eq!(std::fs::read_to_string(&runs).unwrap().lines().count(), 1);
+}
@@ -62,7 +62,7 @@ test.describe('Edge Cases', () => {
 		const api = new MockApi(page);
 		const scenario = emptyState();
 		scenario.dashboard = createDashboardStats({
-			jobs: { total: 0, queued: 0, processing: 0 },
+			jobs: { total: 0, queued: 0, processing: 0, deferred: [] },
 			event_bus: { recent_events: 0 }
 		});
 		await api.setup(scenario);
@@ -150,7 +150,7 @@ export function createToolInfo(overrides: Partial<ToolInfo> = {}): ToolInfo {
 
 export function createDashboardStats(overrides: Partial<DashboardStats> = {}): DashboardStats {
 	return {
-		jobs: { total: 100, queued: 1, processing: 1 },
+		jobs: { total: 100, queued: 1, processing: 1, deferred: [] },
 		event_bus: { recent_events: 10 },
 		...overrides
 	};
@@ -26,7 +26,7 @@ export function emptyState(): Scenario {
 		items: [],
 		jobs: { jobs: [], total: 0 },
 		dashboard: createDashboardStats({
-			jobs: { total: 0, queued: 0, processing: 0 },
+			jobs: { total: 0, queued: 0, processing: 0, deferred: [] },
 			event_bus: { recent_events: 0 }
 		}),
 		rules: [],
@@ -98,7 +98,7 @@ export function populatedState(): Scenario {
 		items,
 		jobs: { jobs: allJobs, total: allJobs.length },
 		dashboard: createDashboardStats({
-			jobs: { total: allJobs.length, queued: 1, processing: 1 },
+			jobs: { total: allJobs.length, queued: 1, processing: 1, deferred: [] },
 			event_bus: { recent_events: 10 }
 		}),
 		rules,
@@ -134,7 +134,7 @@ export function paginatedState(): Scenario {
 		items,
 		jobs: { jobs: [], total: 0 },
 		dashboard: createDashboardStats({
-			jobs: { total: 0, queued: 0, processing: 0 },
+			jobs: { total: 0, queued: 0, processing: 0, deferred: [] },
 			event_bus: { recent_events: 0 }
 		}),
 		rules: [],
@@ -149,7 +149,7 @@ export function authDisabledState(): Scenario {
 		items: [],
 		jobs: { jobs: [], total: 0 },
 		dashboard: createDashboardStats({
-			jobs: { total: 0, queued: 0, processing: 0 },
+			jobs: { total: 0, queued: 0, processing: 0, deferred: [] },
 			event_bus: { recent_events: 0 }
 		}),
 		rules: [],
@@ -178,7 +178,7 @@ export function noWebCompatibleState(): Scenario {
 		items,
 		jobs: { jobs: [], total: 0 },
 		dashboard: createDashboardStats({
-			jobs: { total: 0, queued: 0, processing: 0 },
+			jobs: { total: 0, queued: 0, processing: 0, deferred: [] },
 			event_bus: { recent_events: 0 }
 		}),
 		rules: [],
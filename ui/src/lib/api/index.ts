@@ -140,6 +140,26 @@ export async function retryJob(id: string): Promise<Job> {
 	return result;
 }
 
+export async function runJobNow(id: string): Promise<Job> {
+	const result = await api.post<Job>(`/jobs/${id}/run-now`);
+	api.invalidate('/jobs');
+	return result;
+}
+
+export async function pauseJob(id: string): Promise<Job> {
+	return api.post<Job>(`/jobs/${id}/pause`);
+}
+
+export async function resumeJob(id: string): Promise<Job> {
+	return api.post<Job>(`/jobs/${id}/resume`);
+}
+
+export async function cancelJob(id: string): Promise<Job> {
+	const result = await api.post<Job>(`/jobs/${id}/cancel`);
+	api.invalidate('/jobs');
+	return result;
+}
+
 export async function deleteJob(id: string): Promise<void> {
 	await api.delete(`/jobs/${id}`);
 	api.invalidate('/jobs');
@@ -176,6 +196,19 @@ export async function submitConversion(data: {
 	return result;
 }
 
+export async function pauseConversion(id: string): Promise<void> {
+	await api.post(`/conversions/${id}/pause`);
+}
+
+export async function resumeConversion(id: string): Promise<void> {
+	await api.post(`/conversions/${id}/resume`);
+}
+
+export async function cancelConversion(id: string): Promise<void> {
+	await api.post(`/conversions/${id}/cancel`);
+	api.invalidate('/conversions');
+}
+
 export async function deleteConversion(id: string): Promise<void> {
 	await api.delete(`/conversions/${id}`);
 	api.invalidate('/conversions');
@@ -78,6 +78,27 @@ function createConversionsStore() {
 					);
 					break;
 
+				case 'conversion_paused':
+				case 'conversion_resumed':
+					activeConversions = activeConversions.map((j) =>
+						j.id === payload.job_id
+							? { ...j, paused: payload.type === 'conversion_paused' }
+							: j
+					);
+					break;
+
+				case 'conversion_cancelled': {
+					const cancelled = activeConversions.find((j) => j.id === payload.job_id);
+					activeConversions = activeConversions.filter((j) => j.id !== payload.job_id);
+					if (cancelled) {
+						conversionHistory = [
+							{ ...cancelled, status: 'failed', error: 'Cancelled by user', paused: false },
+							...conversionHistory
+						];
+					}
+					break;
+				}
+
 				case 'conversion_completed': {
 					const completed = activeConversions.find((j) => j.id === payload.job_id);
 					activeConversions = activeConversions.filter((j) => j.id !== payload.job_id);
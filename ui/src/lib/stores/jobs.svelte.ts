@@ -66,6 +66,25 @@ function createJobsStore() {
 					);
 					break;
 
+				case 'job_paused':
+				case 'job_resumed':
+					activeJobs = activeJobs.map((j) =>
+						j.id === payload.job_id ? { ...j, paused: payload.type === 'job_paused' } : j
+					);
+					break;
+
+				case 'job_cancelled': {
+					const cancelled = activeJobs.find((j) => j.id === payload.job_id);
+					activeJobs = activeJobs.filter((j) => j.id !== payload.job_id);
+					if (cancelled) {
+						jobHistory = [
+							{ ...cancelled, status: 'cancelled' as const, paused: false },
+							...jobHistory
+						];
+					}
+					break;
+				}
+
 				case 'job_completed': {
 					const completed = activeJobs.find((j) => j.id === payload.job_id);
 					activeJobs = activeJobs.filter((j) => j.id !== payload.job_id);
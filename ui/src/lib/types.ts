@@ -15,6 +15,9 @@ export interface Job {
 	created_at: string;
 	started_at?: string;
 	completed_at?: string;
+	scheduled_for?: string;
+	run_now?: boolean;
+	paused?: boolean;
 }
 
 // Conversion job types
@@ -42,6 +45,7 @@ export interface ConversionJob {
 	created_at: string;
 	started_at?: string;
 	completed_at?: string;
+	paused?: boolean;
 }
 
 // Playback types
@@ -175,8 +179,14 @@ export interface LibraryStats {
 }
 
 // Dashboard statistics
+export interface DeferredJob {
+	id: string;
+	file_name: string;
+	scheduled_for: string;
+}
+
 export interface DashboardStats {
-	jobs: { total: number; queued: number; processing: number };
+	jobs: { total: number; queued: number; processing: number; deferred: DeferredJob[] };
 	event_bus: { recent_events: number };
 }
 
@@ -203,6 +213,10 @@ export type EventPayload =
 	| { type: 'job_progress'; job_id: string; progress: number; step: string }
 	| { type: 'job_completed'; job_id: string }
 	| { type: 'job_failed'; job_id: string; error: string }
+	| { type: 'job_paused'; job_id: string }
+	| { type: 'job_resumed'; job_id: string }
+	| { type: 'job_cancelled'; job_id: string }
+	| { type: 'job_log'; job_id: string; kind: string; action?: string; command?: string; exit_code?: number; stderr?: string; duration_ms?: number; message?: string }
 	| { type: 'library_scan_started'; library_id: string }
 	| { type: 'library_scan_progress'; library_id: string; files_found: number; files_queued: number; phase: string; files_total: number; files_processed: number; items_to_enrich: number; items_enriched: number }
 	| { type: 'library_scan_complete'; library_id: string; files_found: number; files_queued: number; files_skipped: number; errors: number }
@@ -216,6 +230,9 @@ export type EventPayload =
 	| { type: 'conversion_progress'; job_id: string; progress: number; encode_fps?: number; eta_secs?: number; bitrate?: string; speed?: string; total_size?: number }
 	| { type: 'conversion_completed'; job_id: string }
 	| { type: 'conversion_failed'; job_id: string; error: string }
+	| { type: 'conversion_paused'; job_id: string }
+	| { type: 'conversion_resumed'; job_id: string }
+	| { type: 'conversion_cancelled'; job_id: string }
 	| { type: 'library_scan_error'; library_id: string; file_path: string; message: string }
 	| { type: 'item_enrichment_queued'; item_id: string; library_id: string }
 	| { type: 'item_enriched'; item_id: string; library_id: string }